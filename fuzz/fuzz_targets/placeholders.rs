@@ -0,0 +1,27 @@
+//! Feeds arbitrary templates to the placeholder parser, which must never panic.
+//!
+//! Run with `cargo +nightly fuzz run placeholders` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// The CLI is a binary crate, so the parser and the modules it uses are pulled in by path
+#[path = "../../src/lint.rs"]
+mod lint;
+#[cfg(not(feature = "regex"))]
+#[path = "../../src/scanner.rs"]
+mod scanner;
+#[path = "../../src/spice.rs"]
+mod spice;
+
+fuzz_target!(|data: &[u8]| {
+    let template = String::from_utf8_lossy(data);
+    for placeholder in lint::scan(&template) {
+        assert!(template.get(placeholder.range).is_some());
+    }
+    lint::problems(&template);
+    if let Some(spec) = template.strip_prefix("XNUM") {
+        let _ = lint::parse_number_range(spec);
+    }
+});
@@ -0,0 +1,178 @@
+use crate::picker::Pick;
+use crate::tui::Action;
+use crate::{squash, Generated};
+use anyhow::Result;
+use std::io::{BufRead, Write};
+
+/// Number of messages listed at a time when browsing.
+const BATCH_SIZE: usize = 10;
+
+/// Reads a trimmed line, or `None` at the end of input.
+fn read_answer<R: BufRead>(input: &mut R) -> Result<Option<String>> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_lowercase()))
+}
+
+/// Parses space or comma separated message numbers into zero-based indices, keeping the
+/// order they were given in and dropping repeats.
+fn parse_numbers(answer: &str, count: usize) -> Option<Vec<usize>> {
+    let mut indices = Vec::new();
+    for number in answer.split([' ', ',']).filter(|n| !n.is_empty()) {
+        let n: usize = number.parse().ok()?;
+        if !(1..=count).contains(&n) {
+            return None;
+        }
+        if !indices.contains(&(n - 1)) {
+            indices.push(n - 1);
+        }
+    }
+    (!indices.is_empty()).then_some(indices)
+}
+
+/// Lists the candidates with numbers and asks which to use and what to do with them.
+/// Returns `None` when the user asks for new candidates, which is only offered when
+/// `regenerate` is set.
+fn ask<R: BufRead, W: Write>(
+    candidates: &[String],
+    regenerate: bool,
+    input: &mut R,
+    output: &mut W,
+) -> Result<Option<Pick>> {
+    if candidates.is_empty() {
+        anyhow::bail!("No candidates to choose from");
+    }
+    for (i, candidate) in candidates.iter().enumerate() {
+        writeln!(output, "{}. {}", i + 1, candidate)?;
+    }
+    let others = if regenerate {
+        "r for new messages, or q to quit"
+    } else {
+        "or q to quit"
+    };
+    let indices = loop {
+        write!(
+            output,
+            "Choose messages by number, separated by spaces, {}: ",
+            others
+        )?;
+        output.flush()?;
+        let Some(answer) = read_answer(input)? else {
+            return Ok(Some(Pick::Cancel));
+        };
+        match answer.as_str() {
+            "q" => return Ok(Some(Pick::Cancel)),
+            "r" if regenerate => return Ok(None),
+            _ => match parse_numbers(&answer, candidates.len()) {
+                Some(indices) => break indices,
+                None => writeln!(
+                    output,
+                    "Please enter numbers from 1 to {}",
+                    candidates.len()
+                )?,
+            },
+        }
+    };
+    if indices.len() > 1 {
+        writeln!(output, "The {} messages will be combined", indices.len())?;
+    }
+    loop {
+        write!(output, "Print, edit or commit? Type p, e or c, default p: ")?;
+        output.flush()?;
+        let Some(answer) = read_answer(input)? else {
+            return Ok(Some(Pick::Cancel));
+        };
+        match answer.as_str() {
+            "" | "p" | "print" => return Ok(Some(Pick::Print(indices))),
+            "e" | "edit" => return Ok(Some(Pick::Edit(indices))),
+            "c" | "commit" => return Ok(Some(Pick::Commit(indices))),
+            _ => writeln!(output, "Please type p, e or c")?,
+        }
+    }
+}
+
+/// A line-oriented stand-in for the fuzzy finder, for screen readers.
+pub fn pick<R: BufRead, W: Write>(
+    candidates: &[String],
+    mut input: R,
+    mut output: W,
+) -> Result<Pick> {
+    Ok(ask(candidates, false, &mut input, &mut output)?.unwrap_or(Pick::Cancel))
+}
+
+/// A line-oriented stand-in for the browser, for screen readers: lists a batch of
+/// messages at a time until some are chosen.
+pub fn browse<F, R, W>(mut regenerate: F, mut input: R, mut output: W) -> Result<Action>
+where
+    F: FnMut(usize) -> Result<Vec<Generated>>,
+    R: BufRead,
+    W: Write,
+{
+    loop {
+        let candidates: Vec<String> = regenerate(BATCH_SIZE)?
+            .into_iter()
+            .map(|g| g.message)
+            .collect();
+        let Some(pick) = ask(&candidates, true, &mut input, &mut output)? else {
+            continue;
+        };
+        let picked = |indices: Vec<usize>| -> String {
+            let messages: Vec<&str> = indices.iter().map(|&i| candidates[i].as_str()).collect();
+            squash::message(&messages)
+        };
+        return Ok(match pick {
+            Pick::Print(indices) => Action::Print(picked(indices)),
+            Pick::Edit(indices) => Action::Edit(picked(indices)),
+            Pick::Commit(indices) => Action::Commit(picked(indices)),
+            Pick::Cancel => Action::Quit,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_pick_by_numbers() {
+        let candidates: Vec<String> = ["one", "two", "three"].map(String::from).to_vec();
+        let mut output = Vec::new();
+        let picked = pick(&candidates, "4\n3, 1 3\nx\nc\n".as_bytes(), &mut output).unwrap();
+        assert_eq!(picked, Pick::Commit(vec![2, 0]));
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("1. one\n2. two\n3. three\n"));
+        assert!(output.contains("Please enter numbers from 1 to 3"));
+        assert!(output.contains("Please type p, e or c"));
+        assert!(!output.contains('\x1b'));
+
+        assert_eq!(
+            pick(&candidates, "".as_bytes(), Vec::new()).unwrap(),
+            Pick::Cancel
+        );
+        assert_eq!(
+            pick(&candidates, "r\nq\n".as_bytes(), Vec::new()).unwrap(),
+            Pick::Cancel
+        );
+    }
+
+    #[test]
+    fn t_browse_regenerates() {
+        let mut batches = 0;
+        let regenerate = |count: usize| {
+            batches += 1;
+            Ok((0..count)
+                .map(|i| Generated {
+                    message: format!("batch {} message {}", batches, i + 1),
+                    name: "Ann".to_string(),
+                    template_index: i,
+                    drawn: Vec::new(),
+                    spliced: None,
+                })
+                .collect())
+        };
+        let action = browse(regenerate, "r\n2\n\n".as_bytes(), Vec::new()).unwrap();
+        assert_eq!(action, Action::Print("batch 2 message 2".to_string()));
+    }
+}
@@ -0,0 +1,42 @@
+use crate::template::{Node, Template};
+
+/// The letters and digits of `word`, lowercased, one for each message.
+pub fn letters(word: &str) -> Vec<char> {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The first letter or digit of `text`, lowercased.
+pub fn initial(text: &str) -> Option<char> {
+    text.chars()
+        .find(|c| c.is_alphanumeric())
+        .and_then(|c| c.to_lowercase().next())
+}
+
+/// Whether a message from `template` might start with `letter`: its text starts with that
+/// letter, or with a placeholder or directive that may turn into it.
+pub fn could_start_with(template: &str, letter: char) -> bool {
+    match Template::parse(template).nodes().first() {
+        Some(Node::Literal(text)) => initial(text).is_none_or(|initial| initial == letter),
+        Some(_) => true,
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_acrostic_letters() {
+        assert_eq!(letters("Ship It 2"), ['s', 'h', 'i', 'p', 'i', 't', '2']);
+        assert_eq!(initial("  [WIP] Fix"), Some('w'));
+        assert!(could_start_with("Fix the build", 'f'));
+        assert!(!could_start_with("fix the build", 'b'));
+        assert!(could_start_with("XNAMEX broke it", 'q'));
+        assert!(could_start_with("- XVERBX it", 'q'));
+        assert!(!could_start_with("", 'a'));
+    }
+}
@@ -0,0 +1,235 @@
+use crate::banner;
+use crate::text;
+use anyhow::Result;
+use std::io::{self, IsTerminal, Write};
+use std::time::Duration;
+
+/// Escape sequences for the little terminal control animations need.
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+const CLEAR_LINE: &str = "\x1b[2K";
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Raw keyboard input and the terminal size, read through crossterm.
+#[cfg(feature = "tui")]
+mod keyboard {
+    use anyhow::Result;
+    use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+    use ratatui::crossterm::terminal;
+    use std::io::{self, IsTerminal};
+    use std::time::{Duration, Instant};
+
+    /// Takes raw keyboard input if stdin is a terminal, returning whether it did.
+    pub fn enable() -> bool {
+        io::stdin().is_terminal() && terminal::enable_raw_mode().is_ok()
+    }
+
+    pub fn disable() {
+        let _ = terminal::disable_raw_mode();
+    }
+
+    /// Waits for `timeout`, returning early with `true` when a key is pressed.
+    pub fn pressed(timeout: Duration) -> Result<bool> {
+        let deadline = Instant::now() + timeout;
+        while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+            if !event::poll(left)? {
+                break;
+            }
+            if let Event::Key(key) = event::read()? {
+                let ctrl_c =
+                    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if key.kind == KeyEventKind::Press || ctrl_c {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    pub fn width() -> Option<usize> {
+        terminal::size().ok().map(|(w, _)| w as usize)
+    }
+}
+
+/// Without the `tui` feature keys can't be read, so animations always play to the end.
+#[cfg(not(feature = "tui"))]
+mod keyboard {
+    use anyhow::Result;
+    use std::time::Duration;
+
+    pub fn enable() -> bool {
+        false
+    }
+
+    pub fn disable() {}
+
+    pub fn pressed(timeout: Duration) -> Result<bool> {
+        std::thread::sleep(timeout);
+        Ok(false)
+    }
+
+    pub fn width() -> Option<usize> {
+        None
+    }
+}
+
+/// Hides the cursor and takes raw keyboard input for the length of an animation,
+/// restoring both when dropped, even if the animation fails part-way.
+struct TerminalGuard {
+    raw: bool,
+}
+
+impl TerminalGuard {
+    fn new() -> Result<Self> {
+        let raw = keyboard::enable();
+        let mut stdout = io::stdout();
+        write!(stdout, "{}", HIDE_CURSOR)?;
+        stdout.flush()?;
+        Ok(TerminalGuard { raw })
+    }
+
+    /// Waits for `timeout`, returning early with `true` when a key is pressed.
+    fn wait(&self, timeout: Duration) -> Result<bool> {
+        if !self.raw {
+            std::thread::sleep(timeout);
+            return Ok(false);
+        }
+        keyboard::pressed(timeout)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        if self.raw {
+            keyboard::disable();
+        }
+        let mut stdout = io::stdout();
+        let _ = write!(stdout, "{}", SHOW_CURSOR).and_then(|_| stdout.flush());
+    }
+}
+
+/// Clears the terminal and moves the cursor to its top left corner.
+pub fn clear_screen() -> io::Result<()> {
+    let mut stdout = io::stdout();
+    write!(stdout, "{}", CLEAR_SCREEN)?;
+    stdout.flush()
+}
+
+/// Whether animations can be shown; they are skipped when stdout is not a terminal.
+pub fn enabled() -> bool {
+    io::stdout().is_terminal()
+}
+
+/// Truncates text to fit the terminal width so a frame never wraps onto a second line.
+pub fn fit(text: &str, width: usize) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if text::width(line) <= width {
+        line.to_string()
+    } else {
+        format!("{}…", text::truncate(line, width.saturating_sub(1)))
+    }
+}
+
+/// Delays between slot-machine frames: evenly spread at first, slowing down towards the end
+/// like a reel coming to a stop.
+fn slot_delays(duration: Duration, frames: usize) -> Vec<Duration> {
+    let weights: Vec<f64> = (1..=frames).map(|i| (i * i) as f64).collect();
+    let total: f64 = weights.iter().sum();
+    weights
+        .iter()
+        .map(|w| duration.mul_f64(w / total))
+        .collect()
+}
+
+/// Cycles through the reel on a single line for about `duration`, then clears the line so
+/// the final message can be printed in its place. Any key press skips to the end.
+pub fn slots(reel: &[String], duration: Duration) -> Result<()> {
+    if reel.is_empty() {
+        return Ok(());
+    }
+    let width = keyboard::width()
+        .filter(|&w| w > 0)
+        .unwrap_or_else(banner::terminal_width);
+    let guard = TerminalGuard::new()?;
+    let mut stdout = io::stdout();
+    for (frame, delay) in slot_delays(duration, reel.len()).into_iter().enumerate() {
+        write!(stdout, "{}\r{}", CLEAR_LINE, fit(&reel[frame], width))?;
+        stdout.flush()?;
+        if guard.wait(delay)? {
+            break;
+        }
+    }
+    write!(stdout, "{}\r", CLEAR_LINE)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Splits text into the pieces a typewriter prints one at a time: single characters, with
+/// ANSI escape sequences kept whole so that styling never costs a delay.
+fn keystrokes(text: &str) -> Vec<&str> {
+    let mut keys = Vec::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let len = match rest.strip_prefix("\x1b[") {
+            Some(sequence) => sequence
+                .find(|c: char| ('@'..='~').contains(&c))
+                .map_or(rest.len(), |end| end + 3),
+            None => c.len_utf8(),
+        };
+        keys.push(&rest[..len]);
+        rest = &rest[len..];
+    }
+    keys
+}
+
+/// Prints text one character at a time with `delay` between characters, optionally ringing
+/// the terminal bell at the end. Any key press prints the rest at once.
+pub fn typewriter(text: &str, delay: Duration, bell: bool) -> Result<()> {
+    let guard = TerminalGuard::new()?;
+    let mut stdout = io::stdout();
+    let mut skipped = false;
+    for key in keystrokes(text) {
+        // Raw mode disables the newline translation the terminal normally does
+        let key = if key == "\n" { "\r\n" } else { key };
+        write!(stdout, "{}", key)?;
+        stdout.flush()?;
+        if !skipped && !key.starts_with('\x1b') && !key.trim().is_empty() {
+            skipped = guard.wait(delay)?;
+        }
+    }
+    if bell {
+        write!(stdout, "\x07")?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_slot_delays_slow_down() {
+        let delays = slot_delays(Duration::from_millis(1000), 10);
+        assert!(delays.windows(2).all(|d| d[0] <= d[1]));
+        let total: Duration = delays.iter().sum();
+        assert!(total.as_millis().abs_diff(1000) <= 1);
+    }
+
+    #[test]
+    fn t_keystrokes_keep_escapes_whole() {
+        assert_eq!(
+            keystrokes("\x1b[1mhé\x1b[0m"),
+            vec!["\x1b[1m", "h", "é", "\x1b[0m"]
+        );
+    }
+
+    #[test]
+    fn t_fit_truncates() {
+        assert_eq!(fit("short", 10), "short");
+        assert_eq!(fit("much too long", 5), "much…");
+        assert_eq!(fit("two\nlines", 10), "two");
+        assert_eq!(fit("修复了错误", 6), "修复…");
+        assert_eq!(fit("ship it 🚀🚀", 10), "ship it …");
+    }
+}
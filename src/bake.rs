@@ -0,0 +1,188 @@
+use crate::bundle::{self, Bundle};
+use crate::daily::fnv1a;
+use crate::error::WtcError;
+use crate::paths;
+use anyhow::{Context, Result};
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing::{debug, info, warn};
+
+/// Ends a baked binary, after the bundle and its length, so a binary can tell it was baked.
+const MAGIC: &[u8; 8] = b"WTCBAKE1";
+
+/// The bundle's length and `MAGIC`.
+const TRAILER_LEN: usize = 16;
+
+/// Splits a binary into the program and, if it was baked, the bundle appended to it.
+fn split(bytes: &[u8]) -> (&[u8], Option<&[u8]>) {
+    let Some(rest) = bytes.strip_suffix(MAGIC) else {
+        return (bytes, None);
+    };
+    let Some((rest, len)) = rest.split_last_chunk::<8>() else {
+        return (bytes, None);
+    };
+    match usize::try_from(u64::from_le_bytes(*len)) {
+        Ok(len) if len <= rest.len() => {
+            let (program, bundle) = rest.split_at(rest.len() - len);
+            (program, Some(bundle))
+        }
+        _ => (bytes, None),
+    }
+}
+
+/// `program` with `bundle` appended, as `split` takes it apart.
+fn join(program: &[u8], bundle: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(program.len() + bundle.len() + TRAILER_LEN);
+    bytes.extend_from_slice(program);
+    bytes.extend_from_slice(bundle);
+    bytes.extend_from_slice(&(bundle.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(MAGIC);
+    bytes
+}
+
+/// Writes a copy of this binary to `output` with the config file at `config`, or the
+/// default one, the files it names and the word lists baked in. Anything already baked
+/// into this binary is replaced, and `output` only if it exists with `force`.
+pub fn run(config: Option<&Path>, output: &Path, force: bool) -> Result<()> {
+    if output.exists() && !force {
+        anyhow::bail!("{} already exists; --force replaces it", output.display());
+    }
+    let config = config.map(Path::to_path_buf).or_else(paths::config_file);
+    let bundle = bundle::collect(config.as_deref(), paths::own_words_dir().as_deref())?;
+    if bundle.files.is_empty() {
+        anyhow::bail!("There is no config file or word list to bake in");
+    }
+    let exe = env::current_exe().context("Could not find this binary")?;
+    let bytes = fs::read(&exe).map_err(|source| WtcError::IoError {
+        context: format!("Failed to read this binary: {:?}", exe),
+        source,
+    })?;
+    let (program, _) = split(&bytes);
+    let json = serde_json::to_vec(&bundle)?;
+    let io_error = |source| WtcError::IoError {
+        context: format!("Failed to write baked binary: {:?}", output),
+        source,
+    };
+    fs::write(output, join(program, &json)).map_err(io_error)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(output, fs::Permissions::from_mode(0o755)).map_err(io_error)?;
+    }
+    eprintln!(
+        "Baked {} files into {}",
+        bundle.files.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+/// Reads the bundle baked into the binary at `path`, if there is one, without reading
+/// the rest of the binary.
+fn read(path: &Path) -> Result<Option<Vec<u8>>> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    if size < TRAILER_LEN as u64 {
+        return Ok(None);
+    }
+    let mut trailer = [0; TRAILER_LEN];
+    file.seek(SeekFrom::End(-(TRAILER_LEN as i64)))?;
+    file.read_exact(&mut trailer)?;
+    let (len, magic) = trailer.split_at(8);
+    if magic != MAGIC {
+        return Ok(None);
+    }
+    let len = u64::from_le_bytes(len.try_into()?);
+    if len > size - TRAILER_LEN as u64 {
+        anyhow::bail!("The bundle baked into {:?} is truncated", path);
+    }
+    let mut json = vec![0; len as usize];
+    file.seek(SeekFrom::End(-((len + TRAILER_LEN as u64) as i64)))?;
+    file.read_exact(&mut json)?;
+    Ok(Some(json))
+}
+
+/// Unpacks the bundle baked into this binary into the cache, once per bundle, and returns
+/// the directory it's in.
+fn unpack() -> Result<Option<PathBuf>> {
+    let exe = env::current_exe().context("Could not find this binary")?;
+    let Some(json) = read(&exe).with_context(|| format!("Failed to read {:?}", exe))? else {
+        return Ok(None);
+    };
+    let cache = paths::cache_dir().context("Could not determine the cache directory")?;
+    let dir = cache.join("baked").join(format!("{:016x}", fnv1a(&json)));
+    if dir.is_dir() {
+        return Ok(Some(dir));
+    }
+    let bundle: Bundle =
+        serde_json::from_slice(&json).context("The bundle baked into this binary is broken")?;
+    // Unpacked beside its final place and moved there, so a half-written bundle is never used
+    let partial = cache.join("baked").join(format!(
+        "{:016x}.{}.partial",
+        fnv1a(&json),
+        std::process::id()
+    ));
+    bundle::unpack(&bundle, &partial, true)?;
+    match fs::rename(&partial, &dir) {
+        Ok(()) => info!("Unpacked the baked-in bundle into {:?}", dir),
+        // Another run got there first
+        Err(_) if dir.is_dir() => {
+            let _ = fs::remove_dir_all(&partial);
+        }
+        Err(source) => {
+            return Err(WtcError::IoError {
+                context: format!("Failed to unpack the baked-in bundle into {:?}", dir),
+                source,
+            }
+            .into())
+        }
+    }
+    Ok(Some(dir))
+}
+
+/// The directory holding what's baked into this binary, or `None` if it wasn't baked.
+/// A bundle that can't be unpacked is warned about and left out.
+pub fn dir() -> Option<&'static Path> {
+    static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    DIR.get_or_init(|| match unpack() {
+        Ok(dir) => dir,
+        Err(e) => {
+            warn!("Ignoring what's baked into this binary: {:#}", e);
+            None
+        }
+    })
+    .as_deref()
+    .inspect(|dir| debug!("Using what's baked in at {:?}", dir))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_bake_trailer() {
+        let program = b"\x7fELF program".as_slice();
+        assert_eq!(split(program), (program, None));
+        let baked = join(program, b"{\"files\":{}}");
+        assert_eq!(split(&baked), (program, Some(b"{\"files\":{}}".as_slice())));
+        let rebaked = join(split(&baked).0, b"{}");
+        assert_eq!(split(&rebaked), (program, Some(b"{}".as_slice())));
+
+        let path = env::temp_dir().join(format!("wtc-bake-{}", std::process::id()));
+        fs::write(&path, &rebaked).unwrap();
+        assert_eq!(read(&path).unwrap(), Some(b"{}".to_vec()));
+        fs::write(&path, program).unwrap();
+        assert_eq!(read(&path).unwrap(), None);
+        // A length longer than the file itself
+        let mut broken = program.to_vec();
+        broken.extend_from_slice(&u64::MAX.to_le_bytes());
+        broken.extend_from_slice(MAGIC);
+        assert_eq!(split(&broken), (broken.as_slice(), None));
+        fs::write(&path, &broken).unwrap();
+        assert!(read(&path).is_err());
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,122 @@
+#[cfg(not(feature = "banner"))]
+use crate::error::WtcError;
+#[cfg(feature = "banner")]
+use crate::text;
+#[cfg(feature = "banner")]
+use anyhow::Context;
+use anyhow::Result;
+#[cfg(feature = "banner")]
+use figlet_rs::FIGfont;
+use std::env;
+#[cfg(feature = "banner")]
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Width used when the terminal width cannot be determined.
+const DEFAULT_WIDTH: usize = 80;
+
+/// Returns the width to wrap banners at, from `COLUMNS` or a sensible default.
+pub fn terminal_width() -> usize {
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse().ok())
+        .filter(|&c| c > 0)
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// Renders text with the embedded FIGlet font, returning its lines.
+///
+/// Characters the font does not support are skipped.
+#[cfg(feature = "banner")]
+fn render_line(font: &FIGfont, text: &str) -> Vec<String> {
+    match font.convert(text) {
+        Some(figure) => figure
+            .to_string()
+            .lines()
+            .map(|l| l.trim_end().to_string())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+#[cfg(feature = "banner")]
+fn width_of(lines: &[String]) -> usize {
+    lines.iter().map(|l| l.chars().count()).max().unwrap_or(0)
+}
+
+/// Renders a message as large ASCII-art text, wrapping words so that no
+/// rendered line is wider than `width` columns.
+#[cfg(feature = "banner")]
+pub fn render(message: &str, width: usize) -> Result<String> {
+    let font = FIGfont::standard()
+        .map_err(|e| anyhow::anyhow!(e))
+        .context("Failed to load the embedded banner font")?;
+
+    let mut rows: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for word in message.split_whitespace() {
+        let candidate = if current.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", current, word)
+        };
+        if current.is_empty() || width_of(&render_line(&font, &candidate)) <= width {
+            current = candidate;
+        } else {
+            rows.push(std::mem::replace(&mut current, word.to_string()));
+        }
+
+        // A single word that is too wide on its own gets broken up by grapheme clusters
+        while width_of(&render_line(&font, &current)) > width && text::length(&current) > 1 {
+            let mut head = "";
+            for (i, grapheme) in current.grapheme_indices(true) {
+                let longer = &current[..i + grapheme.len()];
+                if !head.is_empty() && width_of(&render_line(&font, longer)) > width {
+                    break;
+                }
+                head = longer;
+            }
+            let head = head.to_string();
+            current = current[head.len()..].to_string();
+            rows.push(head);
+        }
+    }
+    if !current.is_empty() {
+        rows.push(current);
+    }
+
+    let mut out = String::new();
+    for row in rows {
+        for line in render_line(&font, &row) {
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(not(feature = "banner"))]
+pub fn render(_message: &str, _width: usize) -> Result<String> {
+    Err(WtcError::Disabled {
+        what: "ASCII-art banners",
+        feature: "banner",
+    }
+    .into())
+}
+
+#[cfg(all(test, feature = "banner"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_banner_is_multiline() {
+        let banner = render("hi", 80).unwrap();
+        assert!(banner.lines().count() > 1);
+    }
+
+    #[test]
+    fn t_banner_respects_width() {
+        let banner = render("this message is definitely too long for one line", 40).unwrap();
+        assert!(banner.lines().all(|l| l.chars().count() <= 40));
+        assert!(banner.lines().count() > render("this", 40).unwrap().lines().count());
+    }
+}
@@ -0,0 +1,217 @@
+use crate::corpus::Corpus;
+use crate::error::{ErrorKind, WithKind};
+use crate::format::OutputFormat;
+use crate::output::Output;
+use crate::template::Template;
+use crate::{Constraints, Generated, MAX_ATTEMPTS};
+use anyhow::{Context, Result};
+use rand::prelude::IndexedRandom;
+use rand::Rng;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Stages of the generation pipeline, in the order they run.
+const STAGES: [&str; 6] = [
+    "pick",
+    "substitute",
+    "script",
+    "commitlint",
+    "rules",
+    "render",
+];
+
+/// Where the time went while generating a number of messages.
+#[derive(Debug)]
+pub struct Report {
+    templates: usize,
+    pool: usize,
+    names: usize,
+    load: Duration,
+    accepted: usize,
+    attempts: usize,
+    /// Time spent in each of `STAGES`, or `None` for stages that aren't configured.
+    stages: [Option<Duration>; STAGES.len()],
+}
+
+/// Runs `f`, adding the time it took to a stage.
+fn timed<T>(stage: &mut Option<Duration>, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    *stage.get_or_insert_default() += start.elapsed();
+    result
+}
+
+/// Generates `messages` accepted messages the way a batch would, timing each stage of the
+/// pipeline separately. `load` is how long loading the corpus took.
+#[allow(clippy::too_many_arguments)]
+pub fn measure<R>(
+    names: &[String],
+    commit_messages: &Corpus,
+    pool: &[usize],
+    constraints: &Constraints,
+    format: Option<&OutputFormat>,
+    output: &Output,
+    messages: usize,
+    load: Duration,
+    rng: &mut R,
+) -> Result<Report>
+where
+    R: Rng + ?Sized,
+{
+    let mut stages = [None; STAGES.len()];
+    let [pick, substitute, script, commitlint, rules, render] = &mut stages;
+    let mut accepted = 0;
+    let mut attempts = 0;
+    let mut rejected_in_a_row = 0;
+    // Messages are rendered into one buffer that is handed back after each one
+    let mut message = String::new();
+    while accepted < messages {
+        if rejected_in_a_row == MAX_ATTEMPTS {
+            return Err(anyhow::anyhow!(
+                "No acceptable message after {} attempts",
+                MAX_ATTEMPTS
+            ))
+            .kind(ErrorKind::EmptyPool);
+        }
+        attempts += 1;
+        rejected_in_a_row += 1;
+
+        let (name, template_index, template) = timed(pick, || -> Result<_> {
+            let name = names.choose(rng).context("Failed to select any names")?;
+            let index = *pool
+                .choose(rng)
+                .context("Failed to select any commit messages")?;
+            Ok((name, index, commit_messages.get(index)?))
+        })
+        .kind(ErrorKind::EmptyPool)?;
+        message.clear();
+        timed(substitute, || {
+            Template::parse(&template).render_into(name, rng, &mut message)
+        });
+        if let Some(s) = constraints.script {
+            match timed(script, || s.apply(&message, name, &template))? {
+                Some(transformed) => message = transformed,
+                None => continue,
+            }
+        }
+        let mut violations = Vec::new();
+        if let Some(c) = constraints.commitlint {
+            violations.extend(timed(commitlint, || c.violations(&message)));
+        }
+        if let Some(r) = constraints.rules {
+            violations.extend(timed(rules, || r.violations(&message)));
+        }
+        if !violations.is_empty() {
+            continue;
+        }
+
+        let generated = Generated {
+            message,
+            name: name.clone(),
+            template_index,
+            drawn: Vec::new(),
+            spliced: None,
+        };
+        timed(render, || match format {
+            Some(format) => black_box(format.render(&generated, &template)),
+            None => black_box(output.message(&generated.message, &generated.name)),
+        });
+        message = generated.message;
+        accepted += 1;
+        rejected_in_a_row = 0;
+    }
+
+    Ok(Report {
+        templates: commit_messages.len(),
+        pool: pool.len(),
+        names: names.len(),
+        load,
+        accepted,
+        attempts,
+        stages,
+    })
+}
+
+impl Report {
+    /// Renders the report as aligned lines for people.
+    pub fn render(&self) -> String {
+        let total: Duration = self.stages.iter().flatten().sum();
+        let per_second = self.accepted as f64 / total.as_secs_f64().max(f64::EPSILON);
+        let mut out = format!(
+            "corpus    {} templates ({} in the pool), {} names, loaded in {:.1?}\n\
+             messages  {} accepted of {} generated in {:.1?}, {:.0} per second\n\n\
+             {:<12}{:>10}{:>14}{:>8}\n",
+            self.templates,
+            self.pool,
+            self.names,
+            self.load,
+            self.accepted,
+            self.attempts,
+            total,
+            per_second,
+            "stage",
+            "total",
+            "per message",
+            "share"
+        );
+        for (name, time) in STAGES.iter().zip(&self.stages) {
+            let line = match time {
+                Some(time) => format!(
+                    "{:<12}{:>10}{:>14}{:>7.1}%",
+                    name,
+                    format!("{:.1?}", time),
+                    format!("{:.1?}", *time / self.attempts.max(1) as u32),
+                    100.0 * time.as_secs_f64() / total.as_secs_f64().max(f64::EPSILON)
+                ),
+                None => format!("{:<12}{:>10}", name, "-"),
+            };
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out.pop();
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::output::ColorChoice;
+    use crate::rules::Rules;
+    use crate::theme::Theme;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_bench_times_configured_stages() {
+        let names = vec!["Ann".to_string()];
+        let templates = Corpus::from(vec!["short".to_string(), "much too long".to_string()]);
+        let rules = Rules::parse("max-length = 5").unwrap();
+        let constraints = Constraints {
+            rules: Some(&rules),
+            ..Default::default()
+        };
+        let output = Output::new(ColorChoice::Never, true, Theme::default());
+        let mut rng = StdRng::seed_from_u64(1);
+        let report = measure(
+            &names,
+            &templates,
+            &[0, 1],
+            &constraints,
+            None,
+            &output,
+            50,
+            Duration::ZERO,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(report.accepted, 50);
+        assert!(report.attempts > 50);
+        assert!(report.stages[2].is_none() && report.stages[3].is_none());
+        assert!(report.stages[4].is_some() && report.stages[5].is_some());
+
+        let rendered = report.render();
+        assert!(rendered.contains("2 templates (2 in the pool), 1 names"));
+        assert!(rendered.lines().any(|l| l == "script               -"));
+    }
+}
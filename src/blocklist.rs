@@ -0,0 +1,72 @@
+use crate::error::{ErrorKind, WithKind};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+use tracing::info;
+
+/// Lowercases text and reduces it to its words, each surrounded by single spaces,
+/// so that entries only match whole words.
+pub fn normalize(text: &str) -> String {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    format!(" {} ", words.join(" "))
+}
+
+/// Words and phrases that remove any template containing them from the pool.
+#[derive(Debug, Default)]
+pub struct Blocklist {
+    entries: Vec<String>,
+}
+
+impl Blocklist {
+    /// Parses one word or phrase per line; empty lines and `#` comments are ignored.
+    pub fn parse(content: &str) -> Self {
+        let entries = content
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(normalize)
+            .filter(|e| e.trim() != "")
+            .collect();
+        Blocklist { entries }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read blocklist file: {:?}", path))
+            .kind(ErrorKind::Corpus)?;
+        let blocklist = Self::parse(&content);
+        info!(
+            "Loaded {} blocklist entries from {:?}",
+            blocklist.entries.len(),
+            path
+        );
+        Ok(blocklist)
+    }
+
+    /// Returns whether the text contains none of the blocked words or phrases.
+    pub fn allows(&self, text: &str) -> bool {
+        let text = normalize(text);
+        !self
+            .entries
+            .iter()
+            .any(|entry| text.contains(entry.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_blocklist_matches_whole_words_and_phrases() {
+        let blocklist = Blocklist::parse("# taboo\nProd\n\nlegacy billing\n");
+        assert!(!blocklist.allows("pushed to PROD, sorry"));
+        assert!(blocklist.allows("fixed the product page"));
+        assert!(!blocklist.allows("Rewrote legacy  billing again"));
+        assert!(blocklist.allows("billing is legacy"));
+    }
+}
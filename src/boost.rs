@@ -0,0 +1,184 @@
+use crate::blocklist;
+use crate::corpus::Corpus;
+use anyhow::Result;
+use rand::seq::IndexedRandom;
+use rand::Rng;
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::{debug, warn};
+
+/// Most times a boost may multiply a template's chances, so a typo can't make a pool of
+/// billions.
+pub const MAX_WEIGHT: usize = 1000;
+
+/// Templates to pick more often, or in every batch, from a `[[boost]]` table of the config
+/// file: the template with index `template`, or every template with the words `matching`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Boost {
+    pub template: Option<usize>,
+    /// A word or phrase, matched against whole words whatever their case, as a blocklist
+    /// entry is.
+    pub matching: Option<String>,
+    /// How many times as often the templates are picked; 1 leaves them be.
+    pub weight: Option<usize>,
+    /// Put a message from each of the templates in every batch.
+    pub pin: bool,
+}
+
+impl Boost {
+    fn check(&self) -> Result<()> {
+        match (self.template, &self.matching) {
+            (Some(_), Some(_)) => {
+                anyhow::bail!("A [[boost]] names a template and words to match; give one")
+            }
+            (None, None) => anyhow::bail!("A [[boost]] needs a `template` or `matching`"),
+            _ => {}
+        }
+        match self.weight {
+            Some(weight) if weight == 0 || weight > MAX_WEIGHT => anyhow::bail!(
+                "A [[boost]] weight must be from 1 to {}, not {}",
+                MAX_WEIGHT,
+                weight
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    fn applies(&self, index: usize, normalized: Option<&str>) -> bool {
+        match (self.template, &self.matching, normalized) {
+            (Some(template), ..) => template == index,
+            (None, Some(matching), Some(text)) => text.contains(&blocklist::normalize(matching)),
+            _ => false,
+        }
+    }
+}
+
+/// Weighs `pool` by `boosts`, repeating each template in it by the largest weight of the
+/// boosts for it, and returns the distinct templates pinned into every batch. Templates
+/// not in the pool, as when blocked, are neither boosted nor pinned.
+pub fn apply(
+    boosts: &[Boost],
+    commit_messages: &Corpus,
+    pool: &mut Vec<usize>,
+) -> Result<Vec<usize>> {
+    if boosts.is_empty() {
+        return Ok(Vec::new());
+    }
+    for boost in boosts {
+        boost.check()?;
+        if let Some(template) = boost.template.filter(|&t| t >= commit_messages.len()) {
+            warn!(
+                "Not boosting template {}: there are only {} templates",
+                template,
+                commit_messages.len()
+            );
+        }
+    }
+    let matching = boosts.iter().any(|boost| boost.matching.is_some());
+    // Each template's weight, worked out once however often it appears in the pool
+    let mut seen: HashMap<usize, usize> = HashMap::new();
+    let mut pinned = Vec::new();
+    let mut boosted = Vec::with_capacity(pool.len());
+    for &index in pool.iter() {
+        let weight = match seen.get(&index) {
+            Some(&weight) => weight,
+            None => {
+                let normalized = match matching {
+                    true => Some(blocklist::normalize(&commit_messages.get(index)?)),
+                    false => None,
+                };
+                let applying: Vec<&Boost> = (boosts.iter())
+                    .filter(|boost| boost.applies(index, normalized.as_deref()))
+                    .collect();
+                let weight = (applying.iter()).filter_map(|boost| boost.weight).max();
+                if applying.iter().any(|boost| boost.pin) {
+                    pinned.push(index);
+                }
+                *seen.entry(index).or_insert(weight.unwrap_or(1))
+            }
+        };
+        boosted.extend(std::iter::repeat_n(index, weight));
+    }
+    debug!(
+        "Boosts grew the pool from {} to {}, with {} pinned templates",
+        pool.len(),
+        boosted.len(),
+        pinned.len()
+    );
+    *pool = boosted;
+    Ok(pinned)
+}
+
+/// The pinned templates a batch of `count` messages starts with: all of them, or as many
+/// as fit, chosen at random.
+pub fn for_batch<R>(pinned: &[usize], count: usize, rng: &mut R) -> Vec<usize>
+where
+    R: Rng + ?Sized,
+{
+    match pinned.len() <= count {
+        true => pinned.to_vec(),
+        false => pinned.choose_multiple(rng, count).copied().collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_boost_pool() {
+        let templates = Corpus::from(vec![
+            "Fixed it".to_string(),
+            "XNAMEX is ON CALL again".to_string(),
+            "Blame the on-call rota".to_string(),
+        ]);
+        let boosts = [
+            Boost {
+                matching: Some("on call".to_string()),
+                weight: Some(3),
+                ..Default::default()
+            },
+            Boost {
+                template: Some(2),
+                pin: true,
+                ..Default::default()
+            },
+            Boost {
+                template: Some(1),
+                weight: Some(2),
+                ..Default::default()
+            },
+        ];
+        let mut pool = vec![0, 1, 2, 2];
+        let pinned = apply(&boosts, &templates, &mut pool).unwrap();
+        assert_eq!(pool, [0, 1, 1, 1, 2, 2, 2, 2, 2, 2]);
+        assert_eq!(pinned, [2]);
+
+        let mut pool = vec![0];
+        assert!(apply(&boosts, &templates, &mut pool).unwrap().is_empty());
+        assert_eq!(pool, [0]);
+
+        for boost in [
+            Boost::default(),
+            Boost {
+                template: Some(0),
+                matching: Some("fix".to_string()),
+                ..Default::default()
+            },
+            Boost {
+                template: Some(0),
+                weight: Some(0),
+                ..Default::default()
+            },
+        ] {
+            assert!(apply(&[boost], &templates, &mut vec![0]).is_err());
+        }
+
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(for_batch(&[4, 9], 5, &mut rng), [4, 9]);
+        assert_eq!(for_batch(&[4, 9, 7], 2, &mut rng).len(), 2);
+    }
+}
@@ -0,0 +1,149 @@
+/// The built-in templates for `branch-name`, one per line.
+pub const NAME_TEMPLATES: &str = include_str!("branch_names.txt");
+
+/// Branches `--commit` refuses to commit to unless the config file says otherwise.
+pub const DEFAULT_PROTECTED: [&str; 3] = ["main", "master", "release/*"];
+
+/// The first of `patterns` that `branch` matches, if any. A `*` in a pattern stands for
+/// any run of characters, `/` included, so `release/*` matches `release/1.2/hotfix` too.
+pub fn protected<'a>(branch: &str, patterns: &'a [String]) -> Option<&'a str> {
+    patterns
+        .iter()
+        .map(String::as_str)
+        .find(|pattern| matches(pattern, branch))
+}
+
+fn matches(pattern: &str, branch: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // Without a `*` there is a single part, which must be the whole name
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = branch.strip_prefix(first) else {
+        return false;
+    };
+    let middle: Vec<&str> = parts.collect();
+    let Some((last, middle)) = middle.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Turns generated text into a branch name git accepts: lowercase, with spaces and other
+/// characters git forbids made into single hyphens, and no empty, dot-led or `.lock`
+/// components. A `/` still separates components, as in `fix/cache`.
+pub fn slug(text: &str) -> String {
+    let text: String = text
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            c if c.is_alphanumeric() || matches!(c, '.' | '_' | '/') => c,
+            _ => '-',
+        })
+        .collect();
+    let components: Vec<String> = text
+        .split('/')
+        .map(|component| {
+            let mut out = String::with_capacity(component.len());
+            for c in component.chars() {
+                // Runs of hyphens read badly, and git forbids `..`
+                if matches!(c, '-' | '.') && out.ends_with(c) {
+                    continue;
+                }
+                out.push(c);
+            }
+            let mut out = out.trim_matches(['-', '.']);
+            while let Some(stripped) = out.strip_suffix(".lock") {
+                out = stripped.trim_end_matches(['-', '.']);
+            }
+            out.to_string()
+        })
+        .filter(|component| !component.is_empty())
+        .collect();
+    components.join("/")
+}
+
+/// Checks `name` against the rules git has for branch names (see `git help
+/// check-ref-format`), returning the first it breaks.
+pub fn check_name(name: &str) -> Result<(), &'static str> {
+    if name.is_empty() || name == "@" {
+        return Err("is empty or `@`");
+    }
+    if name.starts_with('-') {
+        return Err("starts with `-`");
+    }
+    if name.starts_with('/') || name.ends_with('/') || name.contains("//") {
+        return Err("has an empty component");
+    }
+    if name.ends_with('.') {
+        return Err("ends with `.`");
+    }
+    if name.contains("..") || name.contains("@{") {
+        return Err("contains `..` or `@{`");
+    }
+    if name.chars().any(|c| {
+        c.is_ascii_control() || matches!(c, ' ' | '~' | '^' | ':' | '?' | '*' | '[' | '\\')
+    }) {
+        return Err("contains a space, a control character or one of ~^:?*[\\");
+    }
+    if name
+        .split('/')
+        .any(|component| component.starts_with('.') || component.ends_with(".lock"))
+    {
+        return Err("has a component starting with `.` or ending with `.lock`");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::template::Template;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_protected_branches() {
+        let patterns: Vec<String> = DEFAULT_PROTECTED.map(String::from).to_vec();
+        assert_eq!(protected("main", &patterns), Some("main"));
+        assert_eq!(protected("release/2.0", &patterns), Some("release/*"));
+        assert_eq!(protected("release/2.0/fix", &patterns), Some("release/*"));
+        assert_eq!(protected("mainline", &patterns), None);
+        assert_eq!(protected("release", &patterns), None);
+        assert_eq!(protected("feature/main", &patterns), None);
+
+        let patterns = vec!["*-stable".to_string(), "hot*fix*".to_string()];
+        assert_eq!(protected("6.1-stable", &patterns), Some("*-stable"));
+        assert_eq!(protected("hotfix", &patterns), Some("hot*fix*"));
+        assert_eq!(protected("hot-new-fix-2", &patterns), Some("hot*fix*"));
+        assert_eq!(protected("stable", &patterns), None);
+    }
+
+    #[test]
+    fn t_branch_names() {
+        assert_eq!(
+            slug("fix/Race Condition in the  parser!"),
+            "fix/race-condition-in-the-parser"
+        );
+        assert_eq!(slug("feature/../.hidden/x.lock"), "feature/hidden/x");
+        assert_eq!(slug("wip//@{ME}~1^2: why?"), "wip/me-1-2-why");
+        assert_eq!(slug("//--"), "");
+        let mut rng = StdRng::seed_from_u64(7);
+        for template in NAME_TEMPLATES.lines() {
+            let name = slug(&Template::parse(template).render("Mary Jane", &mut rng));
+            assert_eq!(check_name(&name), Ok(()), "{}", name);
+            assert!(name.contains('/'), "{}", name);
+        }
+
+        assert!(check_name("fix/cache").is_ok());
+        for bad in [
+            "", "@", "-f", "a//b", "a/", "a.", "a..b", "a@{1}", "a b", "a:b", "x/.a", "a.lock",
+        ] {
+            assert!(check_name(bad).is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use crate::config::{self, PATH_KEYS};
+use crate::error::WtcError;
+use crate::paths;
+use crate::words::List;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use tracing::{debug, info};
+
+/// Identifies a bundle file, so importing something else fails clearly.
+const FORMAT: &str = "whatthecommit-bundle";
+const VERSION: u32 = 1;
+
+/// Name of the config file inside a bundle and the config directory.
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// Read from or write to stdin or stdout instead of a file.
+const STDIO: &str = "-";
+
+/// The config file and every file it needs, by `/`-separated path relative to the config
+/// directory, in one JSON document.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Bundle {
+    format: String,
+    version: u32,
+    pub files: BTreeMap<String, String>,
+}
+
+/// Writes the config file, the files it names and the word lists to `output`, or stdout
+/// for `-`, as one bundle.
+pub fn export(config: Option<&Path>, output: &Path) -> Result<()> {
+    let config = config.map(Path::to_path_buf).or_else(paths::config_file);
+    let bundle = collect(config.as_deref(), paths::own_words_dir().as_deref())?;
+    let json = serde_json::to_string_pretty(&bundle)? + "\n";
+    if output == Path::new(STDIO) {
+        io::stdout().lock().write_all(json.as_bytes())?;
+    } else {
+        fs::write(output, json).map_err(|source| WtcError::IoError {
+            context: format!("Failed to write bundle: {:?}", output),
+            source,
+        })?;
+        eprintln!(
+            "Bundled {} files into {}",
+            bundle.files.len(),
+            output.display()
+        );
+    }
+    Ok(())
+}
+
+/// Unpacks the bundle at `input`, or stdin for `-`, into the config directory. Files that
+/// already exist with other contents are only replaced with `force`.
+pub fn import(input: &Path, force: bool) -> Result<()> {
+    let mut json = String::new();
+    if input == Path::new(STDIO) {
+        io::stdin().lock().read_to_string(&mut json)?;
+    } else {
+        json = fs::read_to_string(input).map_err(|source| WtcError::IoError {
+            context: format!("Failed to read bundle: {:?}", input),
+            source,
+        })?;
+    }
+    let bundle: Bundle = serde_json::from_str(&json)
+        .with_context(|| format!("{} is not a whatthecommit bundle", input.display()))?;
+    let dir = paths::config_dir().context("Could not determine the config directory")?;
+    let written = unpack(&bundle, &dir, force)?;
+    eprintln!("Imported {} files into {}", written.len(), dir.display());
+    Ok(())
+}
+
+/// Gathers the config file at `config` with every file its path settings name, rewritten
+/// to point at their copies in the bundle, and the word lists in `words_dir`.
+pub fn collect(config: Option<&Path>, words_dir: Option<&Path>) -> Result<Bundle> {
+    let mut bundle = Bundle {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        files: BTreeMap::new(),
+    };
+    if let Some(config) = config.filter(|path| path.exists()) {
+        let content = fs::read_to_string(config)
+            .with_context(|| format!("Failed to read config file: {:?}", config))?;
+        let mut table: toml::Table = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse config file: {:?}", config))?;
+        let base = config.parent().unwrap_or(Path::new("."));
+        bundle_paths(&mut table, base, &mut bundle.files)?;
+        if let Some(toml::Value::Table(profiles)) = table.get_mut("profiles") {
+            for (_, profile) in profiles.iter_mut() {
+                if let toml::Value::Table(profile) = profile {
+                    bundle_paths(profile, base, &mut bundle.files)?;
+                }
+            }
+        }
+        bundle
+            .files
+            .insert(CONFIG_FILE.to_string(), toml::to_string(&table)?);
+    }
+    if let Some(words_dir) = words_dir {
+        for list in List::ALL {
+            let path = words_dir.join(list.file());
+            if path.exists() {
+                let words = read_text(&path)?;
+                bundle.files.insert(format!("words/{}", list.file()), words);
+            }
+        }
+    }
+    Ok(bundle)
+}
+
+/// Copies the file each path setting in `table` names into `files`, and points the setting
+/// at the copy. Copies are named after the original, numbered when two names clash.
+fn bundle_paths(
+    table: &mut toml::Table,
+    base: &Path,
+    files: &mut BTreeMap<String, String>,
+) -> Result<()> {
+    for key in PATH_KEYS {
+        let Some(toml::Value::String(value)) = table.get_mut(key) else {
+            continue;
+        };
+        if value == STDIO {
+            continue;
+        }
+        let path = config::resolve_path(Path::new(value.as_str()), base);
+        let content = read_text(&path)?;
+        let file_name = path
+            .file_name()
+            .map_or("file".into(), |name| name.to_string_lossy());
+        let name = (1..)
+            .map(|n| match n {
+                1 => format!("files/{}", file_name),
+                n => format!("files/{}-{}", n, file_name),
+            })
+            .find(|name| files.get(name).is_none_or(|existing| *existing == content))
+            .expect("some numbered name is free");
+        debug!("Bundling {:?} as {}", path, name);
+        files.insert(name.clone(), content);
+        *value = name;
+    }
+    Ok(())
+}
+
+fn read_text(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|source| {
+        WtcError::IoError {
+            context: format!("Failed to read {:?} for the bundle", path),
+            source,
+        }
+        .into()
+    })
+}
+
+/// Writes every file of `bundle` under `dir`, returning their paths. Nothing is written
+/// if the bundle is malformed or, without `force`, would replace a file that differs.
+pub fn unpack(bundle: &Bundle, dir: &Path, force: bool) -> Result<Vec<PathBuf>> {
+    if bundle.format != FORMAT {
+        anyhow::bail!("Not a whatthecommit bundle (format `{}`)", bundle.format);
+    }
+    if bundle.version > VERSION {
+        anyhow::bail!(
+            "Bundle version {} is newer than this version of whatthecommitcli understands ({})",
+            bundle.version,
+            VERSION
+        );
+    }
+    let mut targets = Vec::with_capacity(bundle.files.len());
+    let mut conflicts = Vec::new();
+    for (name, content) in &bundle.files {
+        // Only plain relative paths, so a bundle can't write outside the directory
+        let relative = Path::new(name);
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            anyhow::bail!("Bundle contains an unsafe path: {}", name);
+        }
+        let target = dir.join(relative);
+        if !force && fs::read_to_string(&target).is_ok_and(|existing| existing != *content) {
+            conflicts.push(target.display().to_string());
+        }
+        targets.push((target, content));
+    }
+    if !conflicts.is_empty() {
+        anyhow::bail!(
+            "Importing would replace files that differ (use --force to replace them):\n  {}",
+            conflicts.join("\n  ")
+        );
+    }
+    let mut written = Vec::with_capacity(targets.len());
+    for (target, content) in targets {
+        let io_error = |source| WtcError::IoError {
+            context: format!("Failed to write {:?} from the bundle", target),
+            source,
+        };
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        fs::write(&target, content).map_err(io_error)?;
+        info!("Imported {:?}", target);
+        written.push(target);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_bundle_round_trip() {
+        let root = std::env::temp_dir().join(format!("wtc-bundle-{}", std::process::id()));
+        let (from, to) = (root.join("from"), root.join("to"));
+        fs::create_dir_all(from.join("words")).unwrap();
+        fs::create_dir_all(from.join("team")).unwrap();
+        fs::write(from.join("team/names.txt"), "Ada\n").unwrap();
+        fs::write(from.join("names.txt"), "Grace\n").unwrap();
+        fs::write(from.join("words/verbs.txt"), "yeet\n").unwrap();
+        fs::write(
+            from.join("config.toml"),
+            "theme = \"ocean\"\nnames = \"team/names.txt\"\n\n\
+             [profiles.home]\nnames = \"names.txt\"\n",
+        )
+        .unwrap();
+
+        let bundle = collect(Some(&from.join("config.toml")), Some(&from.join("words"))).unwrap();
+        let names: Vec<&str> = bundle.files.keys().map(String::as_str).collect();
+        assert_eq!(
+            names,
+            [
+                "config.toml",
+                "files/2-names.txt",
+                "files/names.txt",
+                "words/verbs.txt"
+            ]
+        );
+
+        unpack(&bundle, &to, false).unwrap();
+        let config = config::Config::load(Some(&to.join("config.toml"))).unwrap();
+        let names = config.options.names.unwrap();
+        assert_eq!(fs::read_to_string(names).unwrap(), "Ada\n");
+        let home = config.profiles["home"].names.clone().unwrap();
+        assert_eq!(fs::read_to_string(home).unwrap(), "Grace\n");
+        assert_eq!(
+            fs::read_to_string(to.join("words/verbs.txt")).unwrap(),
+            "yeet\n"
+        );
+
+        // Importing again is fine, but not over changed files unless forced
+        unpack(&bundle, &to, false).unwrap();
+        fs::write(to.join("words/verbs.txt"), "vibe\n").unwrap();
+        assert!(unpack(&bundle, &to, false).is_err());
+        unpack(&bundle, &to, true).unwrap();
+
+        let mut unsafe_bundle = bundle;
+        unsafe_bundle
+            .files
+            .insert("../escape.txt".to_string(), String::new());
+        assert!(unpack(&unsafe_bundle, &to, true).is_err());
+        assert!(!root.join("escape.txt").exists());
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,74 @@
+use crate::paths;
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{debug, warn};
+
+/// Reads a cached value if the file is younger than `ttl` and still parses.
+fn read<T: DeserializeOwned>(path: &Path, ttl: Duration) -> Option<T> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    let age = SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_default();
+    if age >= ttl {
+        return None;
+    }
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+fn write<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    }
+    fs::write(path, serde_json::to_string(value)?)
+        .with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// Returns the value cached in `path` while it is younger than `ttl`, otherwise generates
+/// a new one and caches it. Failing to write the cache only warrants a warning.
+pub fn get_or_generate<T, F>(path: &Path, ttl: Duration, generate: F) -> Result<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Result<T>,
+{
+    if let Some(value) = read(path, ttl) {
+        debug!("Using cached value from {:?}", path);
+        return Ok(value);
+    }
+    let value = generate()?;
+    if let Err(e) = write(path, &value) {
+        warn!("Failed to cache: {:#}", e);
+    }
+    Ok(value)
+}
+
+/// Path of a named cache file in the cache directory.
+pub fn path(name: &str) -> Result<PathBuf> {
+    paths::cache_dir()
+        .map(|dir| dir.join(format!("{}.json", name)))
+        .context("Could not determine the cache directory")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_cached_until_stale() {
+        let dir = std::env::temp_dir().join(format!("wtc-cache-{}", std::process::id()));
+        let path = dir.join("value.json");
+        let ttl = Duration::from_secs(60);
+
+        let first: String = get_or_generate(&path, ttl, || Ok("first".to_string())).unwrap();
+        let second: String = get_or_generate(&path, ttl, || Ok("second".to_string())).unwrap();
+        assert_eq!((first.as_str(), second.as_str()), ("first", "first"));
+
+        let fresh: String =
+            get_or_generate(&path, Duration::ZERO, || Ok("fresh".to_string())).unwrap();
+        assert_eq!(fresh, "fresh");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,52 @@
+use anyhow::Result;
+use std::io::{BufRead, Write};
+
+/// Lists the candidates with numbers on `output` and reads the user's choice from `input`,
+/// asking again until a valid number is given. Returns the chosen index.
+pub fn run<R: BufRead, W: Write>(
+    candidates: &[String],
+    mut input: R,
+    mut output: W,
+) -> Result<usize> {
+    if candidates.is_empty() {
+        anyhow::bail!("No candidates to choose from");
+    }
+    let width = candidates.len().to_string().len();
+    for (i, candidate) in candidates.iter().enumerate() {
+        writeln!(output, "{:>width$}) {}", i + 1, candidate, width = width)?;
+    }
+    loop {
+        write!(output, "Choose a message [1-{}]: ", candidates.len())?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            anyhow::bail!("No message chosen");
+        }
+        match line.trim().parse::<usize>() {
+            Ok(n) if (1..=candidates.len()).contains(&n) => return Ok(n - 1),
+            _ => writeln!(
+                output,
+                "Please enter a number from 1 to {}",
+                candidates.len()
+            )?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_choose_reprompts_until_valid() {
+        let candidates = vec!["one".to_string(), "two".to_string()];
+        let mut output = Vec::new();
+        let chosen = run(&candidates, "x\n3\n2\n".as_bytes(), &mut output).unwrap();
+        assert_eq!(chosen, 1);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("1) one\n2) two\n"));
+        assert_eq!(output.matches("Please enter").count(), 2);
+
+        assert!(run(&candidates, "".as_bytes(), Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,71 @@
+use crate::daily;
+use std::borrow::Cow;
+use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Variables CI services set to identify a pipeline run, in the order they are tried.
+const SEED_VARS: [&str; 6] = [
+    "GITHUB_RUN_ID",
+    "CI_PIPELINE_ID",
+    "BUILDKITE_BUILD_ID",
+    "CIRCLE_WORKFLOW_ID",
+    "BUILD_ID",
+    "WTC_CI_SEED",
+];
+
+static STRICT: AtomicBool = AtomicBool::new(false);
+
+/// Turns problems that are otherwise only warned about, like malformed placeholders in
+/// templates, into errors for the rest of the run.
+pub fn set_strict() {
+    STRICT.store(true, Ordering::Relaxed);
+}
+
+/// Whether [`set_strict`] was called.
+pub fn strict() -> bool {
+    STRICT.load(Ordering::Relaxed)
+}
+
+/// A seed for the current pipeline run from the first of `SEED_VARS` that is set, so
+/// re-running a job gives the same message. Numeric ids are used as they are, and other
+/// ids are hashed.
+pub fn seed() -> Option<u64> {
+    SEED_VARS.iter().find_map(|var| {
+        let value = env::var(var).ok().filter(|v| !v.trim().is_empty())?;
+        Some(seed_from(value.trim()))
+    })
+}
+
+fn seed_from(id: &str) -> u64 {
+    id.parse().unwrap_or_else(|_| daily::fnv1a(id.as_bytes()))
+}
+
+/// Joins the lines of a message with spaces, so each message is exactly one line of output.
+pub fn single_line(message: &str) -> Cow<'_, str> {
+    if !message.contains(['\n', '\r']) {
+        return Cow::Borrowed(message);
+    }
+    let lines: Vec<&str> = message
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    Cow::Owned(lines.join(" "))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_ci_seed_and_single_line() {
+        assert_eq!(seed_from("8123456789"), 8123456789);
+        assert_eq!(seed_from("a1b2-c3"), seed_from("a1b2-c3"));
+        assert_ne!(seed_from("a1b2-c3"), seed_from("a1b2-c4"));
+        assert_eq!(single_line("fix: it"), "fix: it");
+        assert_eq!(
+            single_line("squash: two\r\n\n- one\n- two\n"),
+            "squash: two - one - two"
+        );
+    }
+}
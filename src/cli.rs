@@ -0,0 +1,2054 @@
+use crate::boost;
+use crate::branch;
+use crate::ci;
+use crate::config::{self, Config, Options};
+use crate::corpus::Mix;
+use crate::cowsay::Character;
+use crate::daily::DailyScope;
+use crate::error::ErrorFormat;
+use crate::escape::Escape;
+use crate::format::OutputFormat;
+use crate::history::{self, Retention};
+use crate::http::{self, NetworkPolicy};
+#[cfg(feature = "tui")]
+use crate::keys::KeyConfig;
+use crate::llm::{self, LlmMode};
+use crate::logging::LogFormat;
+use crate::name::NameCase;
+use crate::normalize::Normalizer;
+use crate::output::{ColorChoice, Output};
+use crate::prompt::PromptShell;
+use crate::rng::Backend;
+use crate::seasonal::Seasonal;
+use crate::signoff::Signoff;
+use crate::spice::Spice;
+use crate::standup::StandupFormat;
+use crate::stats::StatsFormat;
+use crate::theme::{ColorOverrides, Theme, ThemeName};
+use crate::transform::Transform;
+use crate::widget::{parse_key, Key, WidgetShell};
+use anyhow::{Context, Result};
+use clap::builder::BoolishValueParser;
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, CommandFactory, FromArgMatches, Parser, Subcommand};
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::ffi::OsString;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::info;
+use tracing::level_filters::LevelFilter;
+
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Show more log output (-v info, -vv debug, -vvv trace)
+    #[arg(short = 'v', long = "verbose", action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// Only report errors, with no log output
+    #[arg(short = 'q', long = "quiet", global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// How to write log output on stderr
+    #[arg(
+        long = "log-format",
+        env = "WTC_LOG_FORMAT",
+        value_name = "FORMAT",
+        value_enum,
+        default_value_t,
+        global = true
+    )]
+    pub log_format: LogFormat,
+
+    /// Path to the config file [default: config.toml in the config dir, see `paths`]
+    #[arg(long = "config", env = "WTC_CONFIG", value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Named profile from the config file to use
+    #[arg(
+        short = 'p',
+        long = "profile",
+        env = "WTC_PROFILE",
+        value_name = "NAME"
+    )]
+    pub profile: Option<String>,
+
+    /// Optional path to a custom names file, or - to read them from stdin
+    #[arg(short = 'n', long = "names", env = "WTC_NAMES", value_name = "FILE")]
+    pub names: Option<PathBuf>,
+
+    /// Blame the authors of the current git repository's commits instead of a names file
+    #[arg(
+        long = "names-from-git",
+        env = "WTC_NAMES_FROM_GIT",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true,
+        conflicts_with = "names"
+    )]
+    pub names_from_git: bool,
+
+    /// With --names-from-git, blame authors in proportion to how many commits they made
+    #[arg(
+        long = "weight-by-commits",
+        env = "WTC_WEIGHT_BY_COMMITS",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true,
+        requires = "names_from_git"
+    )]
+    pub weight_by_commits: bool,
+
+    /// Optional path to a custom commit messages template file, or - to read them from stdin
+    #[arg(
+        short = 'c',
+        long = "commit-messages-template",
+        env = "WTC_TEMPLATES",
+        value_name = "FILE"
+    )]
+    pub commit_messages_template: Option<PathBuf>,
+
+    /// Optional path to a grammar file of rules like `message ::= opener " " excuse | blame`
+    /// to make templates from, instead of a templates file
+    #[arg(
+        long = "grammar",
+        env = "WTC_GRAMMAR",
+        value_name = "FILE",
+        conflicts_with = "commit_messages_template"
+    )]
+    pub grammar: Option<PathBuf>,
+
+    /// Never use the embedded names and templates, only explicitly supplied files
+    #[arg(
+        long = "no-default-corpus",
+        env = "WTC_NO_DEFAULT_CORPUS",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true
+    )]
+    pub no_default_corpus: bool,
+
+    /// Draw from several template sources by share, e.g. "default=0.7,custom=0.3":
+    /// `default` for the built-in templates, `custom` for --commit-messages-template or the
+    /// team corpus, or the name of an installed pack
+    #[arg(
+        long = "mix",
+        env = "WTC_MIX",
+        value_name = "SHARES",
+        conflicts_with = "grammar"
+    )]
+    pub mix: Option<Mix>,
+
+    /// Always use the template with this index (see `list`), randomizing only its placeholders
+    #[arg(
+        short = 't',
+        long = "template-id",
+        env = "WTC_TEMPLATE_ID",
+        value_name = "ID"
+    )]
+    pub template_id: Option<usize>,
+
+    /// Optional path to a file of words or phrases (one per line) whose templates are never used
+    #[arg(long = "blocklist", env = "WTC_BLOCKLIST", value_name = "FILE")]
+    pub blocklist: Option<PathBuf>,
+
+    /// Only use templates starting with a command, like "Fix the build", going by a list of
+    /// verbs that words/imperatives.txt in the config directory adds to
+    #[arg(
+        long = "imperative",
+        env = "WTC_IMPERATIVE",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true
+    )]
+    pub imperative: bool,
+
+    /// Optional path to a fortune file of quotes separated by lines of `%`, for XQUOTEX
+    #[arg(long = "quotes", env = "WTC_QUOTES", value_name = "FILE")]
+    pub quotes: Option<PathBuf>,
+
+    /// Optional path to a commitlint config in JSON form; only messages passing its
+    /// header-max-length, type-enum and subject-case rules are printed
+    #[arg(long = "commitlint", env = "WTC_COMMITLINT", value_name = "FILE")]
+    pub commitlint: Option<PathBuf>,
+
+    /// Optional path to a TOML rules file (max-length, min-length, prefix, forbidden-words)
+    /// that every printed message must satisfy
+    #[arg(long = "rules", env = "WTC_RULES", value_name = "FILE")]
+    pub rules: Option<PathBuf>,
+
+    /// Number of messages to generate
+    #[arg(
+        long = "count",
+        env = "WTC_COUNT",
+        value_name = "N",
+        default_value_t = 1
+    )]
+    pub count: usize,
+
+    /// Generate N candidates and choose one with a built-in fuzzy finder
+    #[arg(
+        long = "pick",
+        env = "WTC_PICK",
+        value_name = "N",
+        conflicts_with = "count"
+    )]
+    pub pick: Option<usize>,
+
+    /// List N numbered candidates on stderr and print the one chosen on stdin
+    #[arg(
+        long = "choose",
+        env = "WTC_CHOOSE",
+        value_name = "N",
+        conflicts_with_all = ["count", "pick"]
+    )]
+    pub choose: Option<usize>,
+
+    /// Open the generated message in $VISUAL or $EDITOR before printing it
+    #[arg(
+        long = "edit",
+        env = "WTC_EDIT",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub edit: bool,
+
+    /// Run `git commit -m` with the generated message after printing it
+    #[arg(
+        long = "commit",
+        env = "WTC_COMMIT",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with = "count"
+    )]
+    pub commit: bool,
+
+    /// End committed messages with a `Generated-by` trailer giving the version, seed and
+    /// template, so generated commits can be found and reproduced
+    #[arg(
+        long = "provenance",
+        env = "WTC_PROVENANCE",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true
+    )]
+    pub provenance: bool,
+
+    /// Commit even on a protected branch
+    #[arg(
+        long = "force",
+        env = "WTC_FORCE",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub force: bool,
+
+    /// Words and phrases to swap in every message, from the config file's `[replace]` table
+    #[arg(skip)]
+    pub replace: BTreeMap<String, String>,
+
+    /// Branches committing to needs `--force`, from the config file
+    #[arg(skip)]
+    pub protected_branches: Vec<String>,
+
+    /// Minisign public keys `pack install` accepts signatures from, from the config file
+    #[arg(skip)]
+    pub trusted_keys: Vec<String>,
+
+    /// Show a desktop notification with the short hash after committing
+    #[arg(
+        long = "notify",
+        env = "WTC_NOTIFY",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true
+    )]
+    pub notify: bool,
+
+    /// Mix seasonal templates (Halloween, the year-end freeze, Friday deploys) into the
+    /// built-in corpus when in season
+    #[arg(long = "seasonal", env = "WTC_SEASONAL", value_enum)]
+    pub seasonal: Option<Seasonal>,
+
+    /// Leave out the easter eggs of special dates and times, such as April 1st
+    #[arg(
+        long = "no-easter-eggs",
+        env = "WTC_NO_EASTER_EGGS",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true
+    )]
+    pub no_easter_eggs: bool,
+
+    /// Print one message per letter of WORD, each starting with its letter, instead of
+    /// --count messages
+    #[arg(long = "acrostic", env = "WTC_ACROSTIC", value_name = "WORD")]
+    pub acrostic: Option<String>,
+
+    /// Never repeat a template or message within a --count batch
+    #[arg(
+        long = "unique",
+        env = "WTC_UNIQUE",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub unique: bool,
+
+    /// Keep printing a new message every --interval until interrupted
+    #[arg(
+        long = "stream",
+        env = "WTC_STREAM",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with_all = ["count", "pick", "choose", "edit", "commit", "stats"]
+    )]
+    pub stream: bool,
+
+    /// Time between messages with --stream, like 500ms, 5s or 1m
+    #[arg(
+        long = "interval",
+        env = "WTC_INTERVAL",
+        value_name = "DURATION",
+        default_value = "5s",
+        value_parser = parse_duration
+    )]
+    pub interval: Duration,
+
+    /// Clear the screen before each message with --stream (terminals only)
+    #[arg(
+        long = "clear",
+        env = "WTC_CLEAR",
+        value_parser = BoolishValueParser::new(),
+        requires = "stream"
+    )]
+    pub clear: bool,
+
+    /// Print length, placeholder and template coverage statistics for the batch to stderr
+    #[arg(
+        long = "stats",
+        env = "WTC_STATS",
+        value_name = "FORMAT",
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "text"
+    )]
+    pub stats: Option<StatsFormat>,
+
+    /// Print where each message came from to stderr: its template's file and line, the
+    /// name, the numbers drawn and the seed
+    #[arg(
+        long = "explain",
+        env = "WTC_EXPLAIN",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub explain: bool,
+
+    /// Optional path to a Lua script that can transform or veto generated messages
+    #[arg(short = 's', long = "script", env = "WTC_SCRIPT", value_name = "FILE")]
+    pub script: Option<PathBuf>,
+
+    /// Print each message as a line of JSON with the answers commitizen's conventional
+    /// commits questions collect, for a commitizen adapter to read
+    #[arg(long = "cz", env = "WTC_CZ", value_parser = BoolishValueParser::new(), conflicts_with = "format")]
+    pub cz: bool,
+
+    /// Output format, e.g. "{message}\t{name}\t{template_index}"
+    #[arg(
+        short = 'f',
+        long = "format",
+        env = "WTC_FORMAT",
+        value_name = "FORMAT"
+    )]
+    pub format: Option<OutputFormat>,
+
+    /// Also post the generated message to a Slack or Discord webhook URL (repeatable)
+    /// [env: WTC_POST=, URLs separated by spaces]
+    #[arg(long = "post", value_name = "URL")]
+    pub post: Vec<String>,
+
+    /// Longest a network request may take, like 500ms or 10s
+    #[arg(
+        long = "timeout",
+        env = "WTC_TIMEOUT",
+        value_name = "DURATION",
+        default_value = "10s",
+        value_parser = parse_duration
+    )]
+    pub timeout: Duration,
+
+    /// How many times to retry a network request that timed out or hit a server error
+    #[arg(
+        long = "retries",
+        env = "WTC_RETRIES",
+        value_name = "N",
+        default_value_t = 2
+    )]
+    pub retries: u32,
+
+    /// Wait before the first retry, doubling for each one after
+    #[arg(
+        long = "backoff",
+        env = "WTC_BACKOFF",
+        value_name = "DURATION",
+        default_value = "500ms",
+        value_parser = parse_duration
+    )]
+    pub backoff: Duration,
+
+    /// Have a local Ollama model rework each message, falling back on the message as it
+    /// is when the model can't be reached in time
+    #[arg(long = "llm", env = "WTC_LLM", value_name = "MODE", value_enum)]
+    pub llm: Option<LlmMode>,
+
+    /// Ollama model used by --llm
+    #[arg(long = "llm-model", env = "WTC_LLM_MODEL", value_name = "MODEL")]
+    pub llm_model: Option<String>,
+
+    /// Longest the model may take to answer before the message is used as it is
+    #[arg(
+        long = "llm-timeout",
+        env = "WTC_LLM_TIMEOUT",
+        value_name = "DURATION",
+        default_value = "5s",
+        value_parser = parse_duration
+    )]
+    pub llm_timeout: Duration,
+
+    /// Never touch the network; webhooks are skipped
+    #[arg(
+        long = "offline",
+        env = "WTC_OFFLINE",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true
+    )]
+    pub offline: bool,
+
+    /// Refuse remote corpora and packs whose content isn't what the lockfile records
+    #[arg(
+        long = "locked",
+        env = "WTC_LOCKED",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub locked: bool,
+
+    /// What to do when the network can't be reached; without it, shared files fall back
+    /// on the built-in ones with a warning and a webhook that can't be reached fails the
+    /// run
+    #[arg(
+        long = "network-policy",
+        env = "WTC_NETWORK_POLICY",
+        value_name = "POLICY",
+        value_enum
+    )]
+    pub network_policy: Option<NetworkPolicy>,
+
+    /// When to use colors and decorations
+    #[arg(long = "color", env = "WTC_COLOR", value_name = "WHEN", value_enum)]
+    pub color: Option<ColorChoice>,
+
+    /// Color theme used to highlight names, numbers and emoji
+    #[arg(long = "theme", env = "WTC_THEME", value_name = "THEME", value_enum)]
+    pub theme: Option<ThemeName>,
+
+    /// Style overrides from the config file's `[colors]` table
+    #[arg(skip)]
+    pub colors: Option<ColorOverrides>,
+
+    /// Print the bare message without colors or decorations
+    #[arg(
+        long = "plain",
+        env = "WTC_PLAIN",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true
+    )]
+    pub plain: bool,
+
+    /// Screen-reader-friendly mode: no colors or animations, and numbered line-by-line
+    /// prompts instead of the browser and fuzzy finder
+    #[arg(
+        long = "accessible",
+        env = "WTC_ACCESSIBLE",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true
+    )]
+    pub accessible: bool,
+
+    /// Spin through random messages like a slot machine for MS milliseconds before
+    /// landing on the result (terminals only)
+    #[arg(
+        long = "slots",
+        env = "WTC_SLOTS",
+        value_name = "MS",
+        num_args = 0..=1,
+        default_missing_value = "1500"
+    )]
+    pub slots: Option<u64>,
+
+    /// Type the output out one character at a time, MS milliseconds apart (terminals only)
+    #[arg(
+        long = "typewriter",
+        env = "WTC_TYPEWRITER",
+        value_name = "MS",
+        num_args = 0..=1,
+        default_missing_value = "40"
+    )]
+    pub typewriter: Option<u64>,
+
+    /// Ring the terminal bell when the typewriter finishes
+    #[arg(
+        long = "bell",
+        env = "WTC_BELL",
+        requires = "typewriter",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub bell: bool,
+
+    /// Render the message as large ASCII-art text
+    #[arg(
+        long = "banner",
+        env = "WTC_BANNER",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true,
+        conflicts_with = "format"
+    )]
+    pub banner: bool,
+
+    /// Wrap the message in a speech bubble delivered by an ASCII-art character
+    #[arg(
+        long = "cowsay",
+        env = "WTC_COWSAY",
+        value_name = "CHARACTER",
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "cow",
+        conflicts_with_all = ["format", "banner"]
+    )]
+    pub cowsay: Option<Character>,
+
+    /// Rewrite the whole message for laughs, in order: mock, leet, rot13 or piglatin
+    /// (repeatable)
+    #[arg(
+        long = "transform",
+        env = "WTC_TRANSFORM",
+        value_name = "TRANSFORM",
+        value_enum,
+        value_delimiter = ','
+    )]
+    pub transform: Vec<Transform>,
+
+    /// Fix the subject line for a style guide: whitespace, no-period, capitalize or all
+    /// (repeatable)
+    #[arg(
+        long = "normalize",
+        env = "WTC_NORMALIZE",
+        value_name = "NORMALIZER",
+        value_enum,
+        value_delimiter = ','
+    )]
+    pub normalize: Vec<Normalizer>,
+
+    /// Strip emoji and other symbols outside ASCII from the message, for terminals and
+    /// tools that choke on them
+    #[arg(
+        long = "no-emoji",
+        env = "WTC_NO_EMOJI",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true
+    )]
+    pub no_emoji: bool,
+
+    /// Lay the message out as a subject of at most 50 columns, a blank line, and a body
+    /// wrapped at WIDTH columns [default: 72]
+    #[arg(
+        long = "wrap",
+        env = "WTC_WRAP",
+        value_name = "WIDTH",
+        num_args = 0..=1,
+        default_missing_value = "72"
+    )]
+    pub wrap: Option<usize>,
+
+    /// End the message with N `Co-authored-by` trailers for names from the names pool, with
+    /// made-up addresses [default: 1]
+    #[arg(
+        long = "co-author",
+        env = "WTC_CO_AUTHOR",
+        value_name = "N",
+        num_args = 0..=1,
+        default_missing_value = "1"
+    )]
+    pub co_author: Option<usize>,
+
+    /// End the message with a `Signed-off-by` trailer for git's identity, or for a random
+    /// name from the names pool [default: git]
+    #[arg(
+        long = "signoff",
+        env = "WTC_SIGNOFF",
+        value_name = "WHO",
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "git"
+    )]
+    pub signoff: Option<Signoff>,
+
+    /// Escape the message for where it will be pasted: markdown, shell or json
+    #[arg(long = "escape", env = "WTC_ESCAPE", value_name = "TARGET", value_enum)]
+    pub escape: Option<Escape>,
+
+    /// Seed for the random number generator, for reproducible output
+    #[arg(long = "seed", env = "WTC_SEED", value_name = "N")]
+    pub seed: Option<u64>,
+
+    /// Show the same message all day (UTC), to everyone or per repository name
+    #[arg(
+        long = "daily",
+        env = "WTC_DAILY",
+        value_name = "SCOPE",
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "global",
+        conflicts_with = "seed"
+    )]
+    pub daily: Option<DailyScope>,
+
+    /// Random number generator: fast for huge batches, std (the default), or crypto for
+    /// unseedable randomness straight from the OS
+    #[arg(long = "rng", env = "WTC_RNG", value_name = "BACKEND", value_enum)]
+    pub rng: Option<Backend>,
+
+    /// Don't record emitted messages in the history file
+    #[arg(
+        long = "no-history",
+        env = "WTC_NO_HISTORY",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true
+    )]
+    pub no_history: bool,
+
+    /// Don't learn from batches skipped in the interactive modes, nor pick templates less
+    /// often for having been skipped
+    #[arg(
+        long = "no-learn",
+        env = "WTC_NO_LEARN",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true
+    )]
+    pub no_learn: bool,
+
+    /// Count generated messages, corpora, placeholders and templates for `usage`, locally
+    #[arg(
+        long = "usage-stats",
+        env = "WTC_USAGE_STATS",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true
+    )]
+    pub usage_stats: bool,
+
+    /// Key bindings from the config file's `[keys]` table
+    #[cfg(feature = "tui")]
+    #[arg(skip)]
+    pub keys: Option<KeyConfig>,
+
+    /// How much history to keep, from the config file
+    #[arg(skip)]
+    pub retention: Retention,
+
+    /// Proxy for every request instead of the one the environment names, from the config
+    /// file
+    #[arg(skip)]
+    pub proxy: Option<String>,
+
+    /// Credentials for private URLs, from the config file's `[[auth]]` tables
+    #[arg(skip)]
+    pub auth: Vec<http::Auth>,
+
+    /// Templates to pick more often or in every batch, from the config file's `[[boost]]`
+    /// tables
+    #[arg(skip)]
+    pub boost: Vec<boost::Boost>,
+
+    /// Base URL of the Ollama server --llm asks, from the config file
+    #[arg(skip)]
+    pub llm_url: Option<String>,
+
+    /// Shared templates file to fetch when no local one is given, from the config file
+    #[arg(skip)]
+    pub team_corpus_url: Option<String>,
+
+    /// Shared names file to fetch when no local one is given, from the config file
+    #[arg(skip)]
+    pub team_names_url: Option<String>,
+
+    /// Give placeholders repeated in a template the same value, so `XNUM10X ... XNUM10X` is
+    /// one number
+    #[arg(
+        long = "consistent",
+        env = "WTC_CONSISTENT",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true
+    )]
+    pub consistent: bool,
+
+    /// Only print messages that make a 5-7-5 haiku, from built-in haiku lines unless a
+    /// templates file is given
+    #[arg(
+        long = "haiku",
+        env = "WTC_HAIKU",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true
+    )]
+    pub haiku: bool,
+
+    /// Make up new templates by splicing the clauses of two random ones, keeping only
+    /// splices whose placeholders are still well-formed
+    #[arg(
+        long = "mutate",
+        env = "WTC_MUTATE",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true
+    )]
+    pub mutate: bool,
+
+    /// Fill XPATHX with files tracked in the current git repository instead of made-up paths
+    #[arg(
+        long = "real-paths",
+        env = "WTC_REAL_PATHS",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true
+    )]
+    pub real_paths: bool,
+
+    /// How rude messages may be: censored symbols for XSWEARX and only clean templates, mild
+    /// words and templates up to mild, mild words and templates up to medium (the default), or
+    /// anything
+    #[arg(
+        long = "spice",
+        env = "WTC_SPICE",
+        value_name = "LEVEL",
+        value_enum,
+        ignore_case = true
+    )]
+    pub spice: Option<Spice>,
+
+    /// Keep messages safe for work wherever templates come from, rating untagged ones by
+    /// their words; the same as `--spice censored`
+    #[arg(
+        long = "clean",
+        env = "WTC_CLEAN",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        default_value = "false",
+        hide_default_value = true,
+        conflicts_with = "spice"
+    )]
+    pub clean: bool,
+
+    /// How to report errors on stderr
+    #[arg(long = "errors", env = "WTC_ERRORS", value_name = "FORMAT", value_enum)]
+    pub errors: Option<ErrorFormat>,
+
+    /// Also write the message to the GitHub Actions step output named in $GITHUB_OUTPUT, as
+    /// `message`
+    #[arg(
+        long = "github-output",
+        env = "WTC_GITHUB_OUTPUT",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with = "stream"
+    )]
+    pub github_output: bool,
+
+    /// Act as a prepare-commit-msg hook, filling in the message file given after the other
+    /// flags when it has no message yet, as the pre-commit framework and git call it
+    #[arg(
+        long = "prepare-commit-msg",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with_all = ["count", "pick", "choose", "stream", "commit"]
+    )]
+    pub prepare_commit_msg: bool,
+
+    /// The message file, source and commit git passes to a prepare-commit-msg hook. Only
+    /// `--prepare-commit-msg` takes them, so otherwise a mistyped subcommand is still one.
+    #[arg(skip)]
+    pub hook_args: Vec<String>,
+
+    /// Strict mode for pipelines: no prompts, animations, color, network or history, a seed
+    /// from the CI run id, one line per message, and bad templates are errors
+    #[arg(
+        long = "ci",
+        env = "WTC_CI",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with_all = [
+            "pick", "choose", "edit", "stream", "slots", "typewriter", "banner", "cowsay",
+            "post", "notify"
+        ]
+    )]
+    pub ci: bool,
+
+    /// Settings given on the command line or in the environment, which the config files
+    /// don't override
+    #[arg(skip)]
+    given: BTreeSet<String>,
+}
+
+impl Args {
+    /// Parses the command line like `Parser::parse_from`, noting which settings it and the
+    /// environment gave.
+    pub fn parse_given(raw_args: Vec<OsString>) -> Self {
+        Self::try_parse_given(raw_args).unwrap_or_else(|e| e.exit())
+    }
+
+    fn try_parse_given(raw_args: Vec<OsString>) -> Result<Self, clap::Error> {
+        let mut command = Self::command();
+        let hook = raw_args.iter().any(|arg| arg == "--prepare-commit-msg");
+        if hook {
+            command = command.arg(
+                Arg::new(HOOK_ARGS)
+                    .value_name("HOOK_ARGS")
+                    .num_args(1..=3)
+                    .help("The message file, source and commit git passes to the hook"),
+            );
+        }
+        let mut matches = command.try_get_matches_from(raw_args)?;
+        let hook_args = match hook {
+            true => matches.remove_many::<String>(HOOK_ARGS),
+            false => None,
+        };
+        let given = matches
+            .ids()
+            .filter(|id| {
+                matches!(
+                    matches.value_source(id.as_str()),
+                    Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+                )
+            })
+            .map(|id| id.to_string())
+            .collect();
+        let mut args =
+            Self::from_arg_matches_mut(&mut matches).map_err(|e| e.format(&mut Self::command()))?;
+        args.given = given;
+        args.hook_args = hook_args.into_iter().flatten().collect();
+        Ok(args)
+    }
+
+    /// The value of the flag `id`: as given on the command line or in the environment, or
+    /// else as configured.
+    fn flag(&self, id: &str, value: bool, configured: Option<bool>) -> bool {
+        match self.given.contains(id) {
+            true => value,
+            false => configured.unwrap_or(value),
+        }
+    }
+
+    /// How network requests are made.
+    pub fn http(&self) -> http::Settings {
+        http::Settings {
+            timeout: self.timeout,
+            retries: self.retries,
+            backoff: self.backoff,
+            offline: self.offline || self.network_policy == Some(NetworkPolicy::Never),
+            proxy: self.proxy.clone(),
+            auth: self.auth.clone(),
+        }
+    }
+
+    /// How `--llm` reaches its model, which answers within `--llm-timeout`.
+    pub fn llm(&self) -> llm::Settings {
+        llm::Settings {
+            url: (self.llm_url.as_deref())
+                .unwrap_or(llm::DEFAULT_URL)
+                .to_string(),
+            model: (self.llm_model.as_deref())
+                .unwrap_or(llm::DEFAULT_MODEL)
+                .to_string(),
+            http: http::Settings {
+                timeout: self.llm_timeout,
+                ..self.http()
+            },
+        }
+    }
+
+    /// How rude messages may be, for both the templates used and `XSWEARX`.
+    pub fn spice(&self) -> Spice {
+        self.spice.unwrap_or_default()
+    }
+
+    /// Log level requested with `-v`/`-q`, or `None` to defer to `RUST_LOG`.
+    pub fn log_level(&self) -> Option<LevelFilter> {
+        match (self.quiet, self.verbose) {
+            (true, _) => Some(LevelFilter::OFF),
+            (false, 0) => None,
+            (false, 1) => Some(LevelFilter::INFO),
+            (false, 2) => Some(LevelFilter::DEBUG),
+            (false, _) => Some(LevelFilter::TRACE),
+        }
+    }
+
+    /// Fills in settings not given on the command line from the config files,
+    /// using the selected profile layered over the top-level settings.
+    pub fn apply_config(&mut self) -> Result<()> {
+        let config = Config::load_all(self.config.as_deref())?;
+        let options = config.options(self.profile.as_deref())?;
+        self.apply_options(options)?;
+        if self.ci {
+            self.apply_ci();
+        }
+        Ok(())
+    }
+
+    /// Overrides everything `--ci` rules out, including what the config file asked for,
+    /// and takes the seed from the CI run unless one was given.
+    fn apply_ci(&mut self) {
+        self.plain = true;
+        self.color = Some(ColorChoice::Never);
+        self.offline = true;
+        self.no_history = true;
+        self.no_learn = true;
+        self.usage_stats = false;
+        self.notify = false;
+        self.banner = false;
+        self.cowsay = None;
+        self.typewriter = None;
+        self.slots = None;
+        // The same CI run should give the same messages on any day
+        self.seasonal = Some(Seasonal::Off);
+        self.no_easter_eggs = true;
+        // A model's answers differ from run to run, seeded or not
+        self.llm = None;
+        if !self.post.is_empty() {
+            info!("Not posting to webhooks from the config file in CI mode");
+            self.post.clear();
+        }
+        if self.seed.is_none() && self.daily.is_none() {
+            self.seed = ci::seed();
+        }
+    }
+
+    fn apply_options(&mut self, options: Options) -> Result<()> {
+        // A names file on the command line wins over taking them from git in the config
+        let from_git = options.names_from_git.filter(|_| self.names.is_none());
+        self.names_from_git = self.flag("names_from_git", self.names_from_git, from_git);
+        self.weight_by_commits = self.flag(
+            "weight_by_commits",
+            self.weight_by_commits,
+            options.weight_by_commits,
+        );
+        self.names = self.names.take().or(options.names);
+        self.commit_messages_template = self
+            .commit_messages_template
+            .take()
+            .or(options.commit_messages_template);
+        self.team_corpus_url = options.team_corpus_url;
+        self.team_names_url = options.team_names_url;
+        self.proxy = options.proxy;
+        self.network_policy = self.network_policy.or(options.network_policy);
+        self.auth = options.auth.unwrap_or_default();
+        self.boost = options.boost.unwrap_or_default();
+        self.llm = self.llm.or(options.llm);
+        self.llm_model = self.llm_model.take().or(options.llm_model);
+        self.llm_url = options.llm_url;
+        self.grammar = self.grammar.take().or(options.grammar);
+        self.blocklist = self.blocklist.take().or(options.blocklist);
+        self.quotes = self.quotes.take().or(options.quotes);
+        self.imperative = self.flag("imperative", self.imperative, options.imperative);
+        self.script = self.script.take().or(options.script);
+        self.commitlint = self.commitlint.take().or(options.commitlint);
+        self.rules = self.rules.take().or(options.rules);
+        self.color = self.color.or(options.color);
+        self.theme = self.theme.or(options.theme);
+        self.colors = options.colors;
+        self.replace = options.replace.unwrap_or_default();
+        #[cfg(feature = "tui")]
+        {
+            self.keys = options.keys;
+        }
+        self.plain = self.flag("plain", self.plain, options.plain);
+        self.accessible = self.flag("accessible", self.accessible, options.accessible);
+        self.notify = self.flag("notify", self.notify, options.notify);
+        self.provenance = self.flag("provenance", self.provenance, options.provenance);
+        self.protected_branches = options
+            .protected_branches
+            .unwrap_or_else(|| branch::DEFAULT_PROTECTED.map(String::from).to_vec());
+        self.trusted_keys = options.trusted_keys.unwrap_or_default();
+        self.offline = self.flag("offline", self.offline, options.offline);
+        self.no_default_corpus = self.flag(
+            "no_default_corpus",
+            self.no_default_corpus,
+            options.no_default_corpus,
+        );
+        self.no_history = self.flag("no_history", self.no_history, options.no_history);
+        self.no_learn = self.flag("no_learn", self.no_learn, options.no_learn);
+        self.usage_stats = self.flag("usage_stats", self.usage_stats, options.usage_stats);
+        self.real_paths = self.flag("real_paths", self.real_paths, options.real_paths);
+        self.consistent = self.flag("consistent", self.consistent, options.consistent);
+        self.haiku = self.flag("haiku", self.haiku, options.haiku);
+        self.mutate = self.flag("mutate", self.mutate, options.mutate);
+        self.seasonal = self.seasonal.or(options.seasonal);
+        self.no_easter_eggs = self.flag(
+            "no_easter_eggs",
+            self.no_easter_eggs,
+            options.no_easter_eggs,
+        );
+        self.retention = Retention {
+            max_entries: options
+                .history_max_entries
+                .unwrap_or(history::DEFAULT_MAX_ENTRIES),
+            max_age_days: options.history_max_age_days,
+        };
+        self.errors = self.errors.or(options.errors);
+        self.clean = self.flag("clean", self.clean, options.clean);
+        self.spice = match self.clean {
+            true => Some(Spice::Censored),
+            false => self.spice.or(options.spice),
+        };
+        self.escape = self.escape.or(options.escape);
+        if self.transform.is_empty() {
+            self.transform = options.transform.unwrap_or_default();
+        }
+        if self.normalize.is_empty() {
+            self.normalize = options.normalize.unwrap_or_default();
+        }
+        self.no_emoji = self.flag("no_emoji", self.no_emoji, options.no_emoji);
+        self.wrap = self.wrap.or(options.wrap);
+        self.co_author = self.co_author.or(options.co_author);
+        self.signoff = self.signoff.or(options.signoff);
+        // A seed or daily mode from the command line replaces both configured ones
+        if self.seed.is_none() && self.daily.is_none() {
+            self.seed = options.seed;
+            self.daily = options.daily.filter(|_| options.seed.is_none());
+        }
+        self.rng = self.rng.or(options.rng);
+        if let (None, Some(mix)) = (&self.mix, options.mix) {
+            self.mix = Some(
+                mix.parse()
+                    .map_err(anyhow::Error::msg)
+                    .context("Invalid `mix` in config file")?,
+            );
+        }
+        if self.post.is_empty() {
+            self.post = post_urls(env::var("WTC_POST").ok(), options.post);
+        }
+
+        // An output mode chosen on the command line replaces the configured one entirely
+        if self.format.is_none() && !self.banner && self.cowsay.is_none() {
+            if let Some(format) = options.format {
+                self.format = Some(
+                    format
+                        .parse()
+                        .map_err(anyhow::Error::msg)
+                        .context("Invalid `format` in config file")?,
+                );
+            }
+            self.banner = self.flag("banner", self.banner, options.banner);
+            self.cowsay = options.cowsay;
+        }
+        Ok(())
+    }
+
+    /// Resolves the theme with the config file's color overrides.
+    pub fn theme(&self) -> Result<Theme> {
+        let theme = Theme::from(self.theme.unwrap_or_default());
+        match &self.colors {
+            Some(colors) => theme
+                .with_overrides(colors)
+                .map_err(anyhow::Error::msg)
+                .context("Invalid `[colors]` in config file"),
+            None => Ok(theme),
+        }
+    }
+
+    /// Builds the output settings once the config has been applied.
+    pub fn output(&self) -> Result<Output> {
+        Ok(Output::new(
+            self.color.unwrap_or_default(),
+            self.plain || self.accessible,
+            self.theme()?,
+        ))
+    }
+}
+
+/// Parses a duration like `90`, `30s`, `5m`, `1h` or `500ms`; bare numbers are seconds.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("`{}` is not a duration like 30s or 5m", value))?;
+    match unit {
+        "ms" => Ok(Duration::from_millis(number)),
+        "" | "s" => Ok(Duration::from_secs(number)),
+        "m" => Ok(Duration::from_secs(number * 60)),
+        "h" => Ok(Duration::from_secs(number * 60 * 60)),
+        _ => Err(format!("unknown unit `{}` (expected ms, s, m or h)", unit)),
+    }
+}
+
+/// Finds the config file requested on the raw command line or via `WTC_CONFIG`,
+/// before clap has parsed anything.
+fn raw_config_path(args: &[OsString]) -> Option<PathBuf> {
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        let arg = arg.to_string_lossy();
+        if arg == "--config" {
+            return iter.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    env::var_os("WTC_CONFIG").map(PathBuf::from)
+}
+
+/// Whether `arg` is an option that needs a value.
+fn takes_value(arg: Option<&clap::Arg>) -> bool {
+    arg.is_some_and(|arg| {
+        arg.get_action().takes_values() && arg.get_num_args().is_none_or(|n| n.min_values() > 0)
+    })
+}
+
+/// Finds the position of the first positional argument, skipping options and
+/// the values they take. This is where a subcommand or alias may appear.
+fn first_positional(args: &[OsString]) -> Option<usize> {
+    let command = Args::command();
+
+    let mut i = 1;
+    while i < args.len() {
+        let arg = args[i].to_str()?;
+        if arg == "--" {
+            return None;
+        } else if let Some(long) = arg.strip_prefix("--") {
+            if !long.contains('=')
+                && takes_value(command.get_arguments().find(|a| a.get_long() == Some(long)))
+            {
+                i += 1;
+            }
+        } else if let Some(short) = arg.strip_prefix('-').filter(|s| !s.is_empty()) {
+            let mut chars = short.chars();
+            if let (Some(c), None) = (chars.next(), chars.next()) {
+                if takes_value(command.get_arguments().find(|a| a.get_short() == Some(c))) {
+                    i += 1;
+                }
+            }
+        } else {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// The id of the positional arguments `--prepare-commit-msg` takes.
+const HOOK_ARGS: &str = "hook_args";
+
+/// Options an alias from a per-repository config file may use besides those for
+/// `config::REPO_KEYS`.
+const REPO_ALIAS_OPTIONS: [&str; 5] = ["profile", "verbose", "quiet", "count", "template-id"];
+
+/// Whether an alias from a per-repository config file may expand to `expansion`: only
+/// options for settings such a file may change, with no subcommand, and values such a file
+/// may give them, so files inside `root`, the canonical repository root, and packs for
+/// `--mix`.
+pub fn repo_alias_allowed(expansion: &str, root: &Path) -> bool {
+    let Ok(words) = shell_words::split(expansion) else {
+        return false;
+    };
+    let command = Args::command();
+    let allowed = |arg: Option<&clap::Arg>| {
+        arg.and_then(clap::Arg::get_long).is_some_and(|long| {
+            config::REPO_KEYS.contains(&long) || REPO_ALIAS_OPTIONS.contains(&long)
+        })
+    };
+    // An option taking a value takes the next word, and one whose value is optional takes
+    // it unless it is another option or the value must be attached, as clap reads them
+    let takes_next = |arg: Option<&clap::Arg>, next: Option<&&String>| {
+        takes_value(arg)
+            || arg
+                .is_some_and(|arg| arg.get_action().takes_values() && !arg.is_require_equals_set())
+                && next.is_some_and(|next| !next.starts_with('-'))
+    };
+    let value_allowed = |arg: Option<&clap::Arg>, value: Option<&str>| match (
+        arg.and_then(clap::Arg::get_long),
+        value,
+    ) {
+        (Some(long), Some(path)) if config::PATH_KEYS.contains(&long) => {
+            env::current_dir().is_ok_and(|cwd| config::contained(&cwd.join(path), root).is_some())
+        }
+        (Some("mix"), Some(mix)) => mix.parse::<Mix>().is_ok(),
+        _ => true,
+    };
+    let mut words = words.iter().peekable();
+    while let Some(word) = words.next() {
+        let (arg, inline) = if let Some(long) = word.strip_prefix("--").filter(|l| !l.is_empty()) {
+            let (long, inline) = match long.split_once('=') {
+                Some((long, value)) => (long, Some(value)),
+                None => (long, None),
+            };
+            (
+                command.get_arguments().find(|a| a.get_long() == Some(long)),
+                inline,
+            )
+        } else if let Some(short) = word.strip_prefix('-').filter(|s| !s.is_empty()) {
+            // Every option of a cluster must be allowed; the rest of the cluster after one
+            // taking a value is that value
+            let mut last = None;
+            for (i, c) in short.char_indices() {
+                let arg = command.get_arguments().find(|a| a.get_short() == Some(c));
+                if !allowed(arg) {
+                    return false;
+                }
+                let rest = &short[i + c.len_utf8()..];
+                last = Some((arg, Some(rest).filter(|rest| !rest.is_empty())));
+                if arg.is_some_and(|arg| arg.get_action().takes_values()) {
+                    break;
+                }
+            }
+            match last {
+                Some(last) => last,
+                None => return false,
+            }
+        } else {
+            return false;
+        };
+        if !allowed(arg) {
+            return false;
+        }
+        let value = match inline {
+            Some(value) => Some(value),
+            None if takes_next(arg, words.peek()) => words.next().map(String::as_str),
+            None => None,
+        };
+        if !value_allowed(arg, value) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Expands a config-defined alias given in place of a subcommand into its
+/// arguments, similar to git aliases. Built-in subcommands always take precedence.
+pub fn expand_aliases(args: Vec<OsString>) -> Result<Vec<OsString>> {
+    let Some(position) = first_positional(&args) else {
+        return Ok(args);
+    };
+    let name = args[position].to_string_lossy();
+    if Args::command().find_subcommand(name.as_ref()).is_some() {
+        return Ok(args);
+    }
+
+    // A broken config file is reported once the full config is loaded
+    let aliases = Config::load_all(raw_config_path(&args).as_deref())
+        .map(|config| config.alias)
+        .unwrap_or_default();
+    expand_alias(args, position, &aliases)
+}
+
+fn expand_alias(
+    args: Vec<OsString>,
+    position: usize,
+    aliases: &BTreeMap<String, String>,
+) -> Result<Vec<OsString>> {
+    let name = args[position].to_string_lossy().into_owned();
+    let Some(expansion) = aliases.get(&name) else {
+        return Ok(args);
+    };
+
+    let words =
+        shell_words::split(expansion).with_context(|| format!("Invalid alias `{}`", name))?;
+    let mut expanded = Vec::with_capacity(args.len() + words.len());
+    let mut args = args.into_iter();
+    expanded.extend(args.by_ref().take(position));
+    expanded.extend(words.into_iter().map(OsString::from));
+    expanded.extend(args.skip(1));
+    Ok(expanded)
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Interactively create a config file and optionally install the git hook
+    Init,
+    /// Print several expansions of a single template
+    Preview {
+        /// Template index in the loaded corpus, or an inline template string
+        template: String,
+        /// Number of expansions to print
+        #[arg(short = 'n', long = "count", default_value_t = 5)]
+        count: usize,
+    },
+    /// Re-render sample expansions of a template file whenever it is saved
+    Watch {
+        /// Template file to watch
+        path: PathBuf,
+        /// Number of expansions to print
+        #[arg(short = 'n', long = "count", default_value_t = 5)]
+        count: usize,
+    },
+    /// Find templates containing every query word
+    Search {
+        /// Words to look for, ignoring case
+        #[arg(required = true)]
+        query: Vec<String>,
+    },
+    /// List templates with their indices and the placeholders they use
+    List {
+        /// Only list templates containing every one of these words
+        filter: Vec<String>,
+        /// Number of matching templates to skip
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
+        /// Maximum number of templates to list
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Browse generated messages and templates in a full-screen interface
+    Tui,
+    /// Print a random name without a commit message
+    Name {
+        /// Case to print the name in
+        #[arg(long, value_enum, default_value_t)]
+        case: NameCase,
+        /// Number of names to print
+        #[arg(short = 'n', long = "count", default_value_t = 1)]
+        count: usize,
+    },
+    /// Print an alliterative release codename, such as Quarrelsome Quokka
+    Codename {
+        /// Number of codenames to print
+        #[arg(short = 'n', long = "count", default_value_t = 1)]
+        count: usize,
+        /// Letter the codename starts with
+        #[arg(long, value_name = "LETTER")]
+        starting_letter: Option<char>,
+        /// Print the codename this version always gets, the same on every machine
+        #[arg(long, value_name = "VERSION", conflicts_with = "count")]
+        version: Option<String>,
+    },
+    /// Print an excuse for when something breaks, from a corpus of its own
+    Excuse {
+        /// Number of excuses to print
+        #[arg(short = 'n', long = "count", default_value_t = 1)]
+        count: usize,
+        /// Optional path to a custom excuses template file
+        #[arg(short = 'f', long = "file", env = "WTC_EXCUSES", value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+    /// Print a code review comment, from a corpus of its own, for demo PRs and training
+    /// materials
+    Review {
+        /// Number of comments to print
+        #[arg(short = 'n', long = "count", default_value_t = 1)]
+        count: usize,
+        /// Optional path to a custom review comments template file
+        #[arg(short = 'f', long = "file", env = "WTC_REVIEWS", value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+    /// Print a series of commit messages telling a small story, from introducing a bug to
+    /// reverting it, sharing a name and values throughout
+    Series {
+        /// Number of messages in the series
+        #[arg(short = 'n', long = "count", default_value_t = 4)]
+        count: usize,
+        /// Optional path to a custom series template file with [bug], [panic], [blame]
+        /// and [revert] sections
+        #[arg(short = 'f', long = "file", env = "WTC_SERIES", value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+    /// Print a made-up standup update: what was done yesterday, what's next and blockers
+    Standup {
+        /// Number of items under yesterday and today; there is always one blocker
+        #[arg(long, value_name = "N", default_value_t = 2)]
+        items: usize,
+        /// How to lay out the update
+        #[arg(long, value_enum, default_value_t)]
+        output: StandupFormat,
+        /// Optional path to a custom standup template file with [yesterday], [today] and
+        /// [blockers] sections
+        #[arg(short = 'f', long = "file", env = "WTC_STANDUP", value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+    /// Print a made-up release notes section in Markdown, grouped under Added, Fixed and
+    /// Known Issues
+    ReleaseNotes {
+        /// Number of bullet points, split between the sections
+        #[arg(short = 'n', long = "count", default_value_t = 6)]
+        count: usize,
+        /// Version being released, used for the heading and every XVERSIONX; random if
+        /// not given
+        #[arg(long, value_name = "VERSION")]
+        version: Option<String>,
+        /// Optional path to a custom release notes template file with [added], [fixed]
+        /// and [known issues] sections
+        #[arg(
+            short = 'f',
+            long = "file",
+            env = "WTC_RELEASE_NOTES",
+            value_name = "FILE"
+        )]
+        file: Option<PathBuf>,
+    },
+    /// Print a random git-safe branch name, such as fix/urgent-404-do-not-ask
+    BranchName {
+        /// Number of branch names to print
+        #[arg(short = 'n', long = "count", default_value_t = 1)]
+        count: usize,
+        /// Create the branch and switch to it
+        #[arg(long, conflicts_with = "count")]
+        create: bool,
+        /// Optional path to a custom branch name template file
+        #[arg(
+            short = 'f',
+            long = "file",
+            env = "WTC_BRANCH_NAMES",
+            value_name = "FILE"
+        )]
+        file: Option<PathBuf>,
+    },
+    /// Answer generate, list-packs and set-config requests as JSON-RPC 2.0 over stdin and
+    /// stdout, one per line, so editor extensions can keep one process warm
+    Rpc,
+    /// Serve the corpora `rpc` answers from over the network, generating messages for
+    /// GenerateMessage calls of proto/whatthecommit.proto
+    Serve {
+        /// Speak gRPC, the only protocol served so far
+        #[arg(long, required = true)]
+        grpc: bool,
+        /// Address to listen on
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:50051")]
+        listen: SocketAddr,
+    },
+    /// Serve the Language Server Protocol on stdin and stdout, giving editors diagnostics,
+    /// placeholder completion and hover documentation for templates files
+    Lsp,
+    /// Lint every template of templates files and render each with a fixed seed, failing
+    /// if any has a problem, renders empty, breaks --rules or --commitlint, or panics
+    Verify {
+        /// Templates files to verify, the loaded templates if none are given
+        #[arg(value_name = "FILE")]
+        files: Vec<PathBuf>,
+        /// How many times to render each template, with the seed from --seed or else 0
+        #[arg(short = 'n', long = "renders", default_value_t = 20)]
+        renders: usize,
+    },
+    /// Report which placeholders each templates file uses, and what it never uses
+    Coverage {
+        /// Templates files to report on, the loaded templates if none are given
+        #[arg(value_name = "FILE")]
+        files: Vec<PathBuf>,
+        /// How to print the report
+        #[arg(long, value_enum, default_value_t)]
+        format: StatsFormat,
+    },
+    /// Rewrite names and templates files in their canonical form
+    Fmt {
+        /// Corpus files to format
+        #[arg(value_name = "FILE", required = true)]
+        files: Vec<PathBuf>,
+        /// Sort the templates of each section too
+        #[arg(long)]
+        sort: bool,
+        /// Only list files that aren't formatted, failing if there are any
+        #[arg(long)]
+        check: bool,
+    },
+    /// Report likely typos in the text of templates, with their lines
+    Spellcheck {
+        /// Templates files to check, the loaded templates if none are given
+        #[arg(value_name = "FILE")]
+        files: Vec<PathBuf>,
+        /// File of words to accept, one per line, such as names and jargon
+        #[arg(long, env = "WTC_ALLOWLIST", value_name = "FILE")]
+        allowlist: Option<PathBuf>,
+    },
+    /// Find templates repeated across templates files, exactly or nearly, and remove them
+    Dedupe {
+        /// Templates files to check, as one pool; the loaded templates file if none are given
+        #[arg(value_name = "FILE")]
+        files: Vec<PathBuf>,
+        /// Only report duplicates, failing if there are any (the default)
+        #[arg(long)]
+        check: bool,
+        /// Rewrite the files without their duplicates, keeping the first of each
+        #[arg(long, conflicts_with = "check")]
+        fix: bool,
+        /// Most edits apart templates can be to count as near-duplicates, 0 for exact ones only
+        #[arg(long, value_name = "EDITS", default_value_t = 2)]
+        distance: usize,
+    },
+    /// Print a short message for a shell prompt, cached between prompts
+    Prompt {
+        /// How long to keep showing the same message
+        #[arg(long, value_name = "DURATION", default_value = "5m", value_parser = parse_duration)]
+        ttl: Duration,
+        /// Shell whose prompt the escape codes are wrapped for
+        #[arg(long, value_enum, default_value_t)]
+        shell: PromptShell,
+        /// Shorten the message to this many characters
+        #[arg(long, value_name = "N", default_value_t = 50)]
+        max_width: usize,
+    },
+    /// Print a script that binds a key to insert a message into the shell's command line
+    Widget {
+        /// Shell to write the script for
+        #[arg(value_enum)]
+        shell: WidgetShell,
+        /// Key to bind, such as ctrl-g or alt-m
+        #[arg(long, value_name = "KEY", default_value = "ctrl-g", value_parser = parse_key)]
+        key: Key,
+    },
+    /// Print a message for the tmux status line, changing once per interval
+    Tmux {
+        /// How long to keep showing the same message
+        #[arg(long, value_name = "DURATION", default_value = "1m", value_parser = parse_duration)]
+        interval: Duration,
+        /// Shorten the message to this many characters
+        #[arg(long, value_name = "N", default_value_t = 60)]
+        max_width: usize,
+    },
+    /// Guess whether messages are real commits from this repo or generated
+    Quiz {
+        /// Number of rounds to play
+        #[arg(short = 'n', long = "rounds", default_value_t = 10)]
+        rounds: usize,
+    },
+    /// Measure how fast messages are generated with the current corpus and filters
+    Bench {
+        /// Number of messages to generate
+        #[arg(short = 'n', long = "messages", default_value_t = 10_000)]
+        messages: usize,
+    },
+    /// Print histograms of the templates picked and numbers drawn over many messages
+    Simulate {
+        /// Number of messages to generate, and numbers to draw from each range
+        #[arg(short = 'n', long = "messages", default_value_t = 10_000)]
+        messages: usize,
+    },
+    /// Show the key bindings of the interactive modes
+    Keys,
+    /// Show previously emitted messages
+    History {
+        /// Only show messages matching this regular expression (ignoring case)
+        #[arg(long, value_name = "REGEX")]
+        grep: Option<String>,
+        /// Only show the last N messages
+        #[arg(long, value_name = "N")]
+        limit: Option<usize>,
+        /// Delete the whole history
+        #[arg(long, conflicts_with_all = ["grep", "limit"])]
+        clear: bool,
+    },
+    /// Show the usage statistics recorded with `usage-stats` turned on
+    Usage {
+        /// Number of corpora, placeholders and templates to show in each list
+        #[arg(long, value_name = "N", default_value_t = 10)]
+        top: usize,
+        /// Delete the statistics
+        #[arg(long, conflicts_with = "top")]
+        clear: bool,
+    },
+    /// Show or reset how much skipping templates has made them rarer
+    Weights {
+        #[command(subcommand)]
+        action: WeightsAction,
+    },
+    /// Keep great messages in a favorites file
+    Fav {
+        #[command(subcommand)]
+        action: FavAction,
+    },
+    /// Print the resolved config, data and cache locations
+    Paths,
+    /// Carry the config file, the files it names and the word lists to another machine
+    Bundle {
+        #[command(subcommand)]
+        action: BundleAction,
+    },
+    /// Write a copy of this binary with everything `bundle` carries baked in
+    Bake {
+        /// File to write the baked binary to
+        #[arg(short = 'o', long = "output", value_name = "FILE")]
+        output: PathBuf,
+        /// Replace the output file if it exists
+        #[arg(long)]
+        force: bool,
+    },
+    /// Keep tokens for private URLs and webhooks in the OS keychain
+    Auth {
+        #[command(subcommand)]
+        action: AuthAction,
+    },
+    /// Work with templates files as packs
+    Pack {
+        #[command(subcommand)]
+        action: PackAction,
+    },
+    /// Translate templates with gettext PO files
+    I18n {
+        #[command(subcommand)]
+        action: I18nAction,
+    },
+    /// Print the man page, or install it into a directory
+    Man {
+        /// Directory to write the man page into instead of printing it
+        #[arg(short = 'o', long = "output", value_name = "DIR")]
+        output: Option<PathBuf>,
+    },
+    /// Replace this binary with the latest GitHub release, after checking its signed SHA-256
+    SelfUpdate {
+        /// Only say whether a newer release is available
+        #[arg(long)]
+        check: bool,
+        /// Reinstall the latest release if it is this version; an older one is never installed
+        #[arg(long, conflicts_with = "check")]
+        force: bool,
+    },
+    /// Check a shared team corpus for changes, run in the background to update the cache
+    #[command(hide = true)]
+    RefreshTeamCorpus { url: String },
+}
+
+#[derive(Subcommand)]
+pub enum FavAction {
+    /// Add a message to the favorites
+    Add {
+        /// The message
+        #[arg(required = true)]
+        message: Vec<String>,
+    },
+    /// List the favorites with their indices
+    List,
+    /// Print a random favorite
+    Random,
+    /// Remove the favorite with this index
+    Remove { index: usize },
+}
+
+#[derive(Subcommand)]
+pub enum WeightsAction {
+    /// List the skipped templates with the share of their chances they keep
+    Show,
+    /// Forget every skip, giving every template its full chances again
+    Reset,
+}
+
+#[derive(Subcommand)]
+pub enum AuthAction {
+    /// Store a token read from stdin, for the config file to use as ${keyring:NAME}
+    Login {
+        /// Name to store it under
+        name: String,
+    },
+    /// Remove a stored token
+    Logout {
+        /// Name it is stored under
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PackAction {
+    /// Combine templates files into one, section by section, dropping duplicates
+    Merge {
+        /// Templates files to merge, earlier ones winning conflicts
+        #[arg(value_name = "FILE", required = true)]
+        files: Vec<PathBuf>,
+        /// File to write the merged templates to, or - for stdout
+        #[arg(short = 'o', long = "output", value_name = "FILE", default_value = "-")]
+        output: PathBuf,
+        /// Most edits apart templates can be to be reported as near-duplicates
+        #[arg(long, value_name = "EDITS", default_value_t = 2)]
+        distance: usize,
+    },
+    /// Show the templates added, removed, reworded and moved between sections
+    Diff {
+        /// The earlier version of the templates file
+        old: PathBuf,
+        /// The later version of the templates file
+        new: PathBuf,
+        /// Most edits apart templates can be to count as reworded rather than replaced
+        #[arg(long, value_name = "EDITS", default_value_t = 2)]
+        distance: usize,
+    },
+    /// Install a templates file signed with minisign by one of the `trusted-keys`
+    Install {
+        /// URL or path of the templates file
+        source: String,
+        /// URL or path of its signature, the source with .minisig added if not given
+        #[arg(long, value_name = "SOURCE")]
+        signature: Option<String>,
+        /// File name to install it under, the source's if not given
+        #[arg(long)]
+        name: Option<String>,
+        /// Replace an installed pack with the same name
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum I18nAction {
+    /// Write a .pot file with every template to translate
+    Export {
+        /// Templates files to export, the loaded templates if none are given
+        #[arg(value_name = "FILE")]
+        files: Vec<PathBuf>,
+        /// File to write the PO template to, or - for stdout
+        #[arg(short = 'o', long = "output", value_name = "FILE", default_value = "-")]
+        output: PathBuf,
+    },
+    /// Build a localized templates file from a translated .po file
+    Import {
+        /// The translated PO file
+        po: PathBuf,
+        /// File to write the localized templates to, or - for stdout
+        #[arg(short = 'o', long = "output", value_name = "FILE", default_value = "-")]
+        output: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BundleAction {
+    /// Write everything into one bundle file
+    Export {
+        /// File to write the bundle to, or - for stdout
+        #[arg(short = 'o', long = "output", value_name = "FILE", default_value = "-")]
+        output: PathBuf,
+    },
+    /// Unpack a bundle into the config directory
+    Import {
+        /// Bundle file to read, or - for stdin
+        file: PathBuf,
+        /// Replace existing files that differ from the bundled ones
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// The webhook URLs to post to without `--post`: those of `WTC_POST`, separated by
+/// whitespace, which a URL can't hold as commas it may, or else the config file's.
+fn post_urls(env: Option<String>, config: Option<Vec<String>>) -> Vec<String> {
+    match env {
+        Some(urls) => urls.split_whitespace().map(String::from).collect(),
+        None => config.unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn os(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn t_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert!(parse_duration("5d").is_err());
+        assert!(parse_duration("m").is_err());
+    }
+
+    #[test]
+    fn t_expand_alias() {
+        let aliases = BTreeMap::from([(
+            "demo".to_string(),
+            "--cowsay tux --format '{name}: {message}'".to_string(),
+        )]);
+        assert_eq!(
+            expand_alias(os(&["wtc", "demo", "-v"]), 1, &aliases).unwrap(),
+            os(&[
+                "wtc",
+                "--cowsay",
+                "tux",
+                "--format",
+                "{name}: {message}",
+                "-v"
+            ])
+        );
+        assert_eq!(
+            expand_alias(os(&["wtc", "other"]), 1, &aliases).unwrap(),
+            os(&["wtc", "other"])
+        );
+    }
+
+    #[test]
+    fn t_first_positional_skips_option_values() {
+        assert_eq!(
+            first_positional(&os(&["wtc", "--config", "a.toml", "-n", "x", "demo"])),
+            Some(5)
+        );
+        assert_eq!(first_positional(&os(&["wtc", "--cowsay", "demo"])), Some(2));
+        assert_eq!(first_positional(&os(&["wtc", "-v", "--plain"])), None);
+    }
+
+    #[test]
+    fn t_raw_config_path() {
+        assert_eq!(
+            raw_config_path(&os(&["wtc", "--config", "a.toml", "demo"])),
+            Some(PathBuf::from("a.toml"))
+        );
+        assert_eq!(
+            raw_config_path(&os(&["wtc", "--config=b.toml"])),
+            Some(PathBuf::from("b.toml"))
+        );
+    }
+
+    #[test]
+    fn t_ci_overrides_config() {
+        assert!(Args::try_parse_from(["wtc", "--ci", "--pick", "5"]).is_err());
+        let mut args = Args::try_parse_from(["wtc", "--ci", "--seed", "7"]).unwrap();
+        let options = Options {
+            banner: Some(true),
+            post: Some(vec!["https://hooks.example/x".to_string()]),
+            ..Default::default()
+        };
+        args.apply_options(options).unwrap();
+        args.apply_ci();
+        assert!(args.plain && args.offline && args.no_history);
+        assert!(!args.banner && args.post.is_empty());
+        assert_eq!(args.seed, Some(7));
+    }
+
+    #[test]
+    fn t_post_urls() {
+        let commas = "https://hooks.example/x?tags=a,b";
+        let args = Args::try_parse_from(["wtc", "--post", commas, "--post", "https://b"]).unwrap();
+        assert_eq!(args.post, [commas, "https://b"]);
+
+        let config = Some(vec!["https://config".to_string()]);
+        assert_eq!(
+            post_urls(Some(format!(" {}\nhttps://b ", commas)), config.clone()),
+            [commas, "https://b"]
+        );
+        assert_eq!(post_urls(None, config.clone()), ["https://config"]);
+        let mut args = Args::try_parse_from(["wtc", "--post", commas]).unwrap();
+        args.apply_options(Options {
+            post: config,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(args.post, [commas]);
+    }
+
+    #[test]
+    fn t_network_policy() {
+        let mut args = Args::try_parse_from(["wtc", "--network-policy", "prefer"]).unwrap();
+        let options = Options {
+            network_policy: Some(NetworkPolicy::Never),
+            ..Default::default()
+        };
+        args.apply_options(options.clone()).unwrap();
+        assert_eq!(args.network_policy, Some(NetworkPolicy::Prefer));
+        assert!(!args.http().offline);
+
+        let mut args = Args::try_parse_from(["wtc"]).unwrap();
+        args.apply_options(options).unwrap();
+        assert!(args.http().offline);
+    }
+
+    #[test]
+    fn t_flags_beat_config() {
+        let options = Options {
+            plain: Some(true),
+            offline: Some(true),
+            names_from_git: Some(true),
+            ..Default::default()
+        };
+        let applied = |argv: &[&str]| {
+            let mut args = Args::try_parse_given(os(argv)).unwrap();
+            args.apply_options(options.clone()).unwrap();
+            args
+        };
+        let args = applied(&["wtc"]);
+        assert!(args.plain && args.offline && args.names_from_git);
+        let args = applied(&["wtc", "--plain=false", "--offline=no"]);
+        assert!(!args.plain && !args.offline && args.names_from_git);
+        let args = applied(&["wtc", "--plain", "--names", "team.txt"]);
+        assert!(args.plain && !args.names_from_git);
+
+        // A flag's value must be attached, so a following word is never taken for it
+        let args = Args::try_parse_given(os(&["wtc", "--plain", "paths"])).unwrap();
+        assert!(args.plain && matches!(args.command, Some(Command::Paths)));
+        assert!(Args::try_parse_given(os(&["wtc", "--plain=maybe"])).is_err());
+    }
+
+    #[test]
+    fn t_log_level() {
+        let level = |argv: &[&str]| Args::try_parse_from(argv).unwrap().log_level();
+        assert_eq!(level(&["wtc"]), None, "RUST_LOG decides");
+        assert_eq!(level(&["wtc", "-v"]), Some(LevelFilter::INFO));
+        assert_eq!(level(&["wtc", "-vv"]), Some(LevelFilter::DEBUG));
+        assert_eq!(
+            level(&["wtc", "-v", "--verbose", "-v", "-v"]),
+            Some(LevelFilter::TRACE)
+        );
+        assert_eq!(level(&["wtc", "paths", "-vv"]), Some(LevelFilter::DEBUG));
+        assert_eq!(level(&["wtc", "paths", "--quiet"]), Some(LevelFilter::OFF));
+        assert!(Args::try_parse_from(["wtc", "-q", "-v"]).is_err());
+    }
+
+    #[test]
+    fn t_stream_flags() {
+        let args = Args::try_parse_from(["wtc", "--stream"]).unwrap();
+        assert!(args.stream);
+        assert_eq!(args.interval, Duration::from_secs(5));
+        let args =
+            Args::try_parse_from(["wtc", "--stream", "--interval", "250ms", "--clear"]).unwrap();
+        assert_eq!(args.interval, Duration::from_millis(250));
+        assert!(args.clear);
+        assert!(Args::try_parse_from(["wtc", "--clear"]).is_err());
+        assert!(Args::try_parse_from(["wtc", "--stream", "--count", "3"]).is_err());
+        assert!(Args::try_parse_from(["wtc", "--stream", "--interval", "soon"]).is_err());
+    }
+
+    #[test]
+    fn t_hook_args() {
+        let argv = [
+            "wtc",
+            "--prepare-commit-msg",
+            "--spice",
+            "mild",
+            "COMMIT_EDITMSG",
+            "message",
+        ];
+        let args = Args::try_parse_given(os(&argv)).unwrap();
+        assert_eq!(args.hook_args, ["COMMIT_EDITMSG", "message"]);
+        assert_eq!(args.spice, Some(Spice::Mild));
+        let argv = [
+            "wtc",
+            "--prepare-commit-msg",
+            "COMMIT_EDITMSG",
+            "message",
+            "HEAD",
+            "more",
+        ];
+        assert!(Args::try_parse_given(os(&argv)).is_err());
+
+        // Without the hook, a mistyped subcommand isn't taken for the message file
+        let Err(err) = Args::try_parse_given(os(&["wtc", "revew"])) else {
+            panic!("`revew` isn't a subcommand");
+        };
+        assert_eq!(err.kind(), clap::error::ErrorKind::InvalidSubcommand);
+        assert!(err.to_string().contains("'review'"), "{}", err);
+    }
+
+    #[test]
+    fn t_builtin_subcommands_are_not_aliased() {
+        assert_eq!(
+            expand_aliases(os(&["wtc", "paths"])).unwrap(),
+            os(&["wtc", "paths"])
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use rand::seq::IndexedRandom;
+use rand::Rng;
+
+/// Domains reserved for examples, so a made-up address never reaches anyone.
+const DOMAINS: [&str; 3] = ["example.com", "example.org", "example.net"];
+
+/// A plausible address for `name`, like "mary.obrien@example.org": the ASCII letters and
+/// digits of each word, lowercased and joined by dots. A name with none gets a numbered
+/// address instead.
+pub fn email<R>(name: &str, rng: &mut R) -> String
+where
+    R: Rng + ?Sized,
+{
+    let words: Vec<String> = (name.split_whitespace())
+        .map(|word| {
+            (word.chars())
+                .filter(char::is_ascii_alphanumeric)
+                .collect::<String>()
+                .to_ascii_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect();
+    let local = match words.is_empty() {
+        true => format!("dev{}", rng.random_range(100..1000)),
+        false => words.join("."),
+    };
+    let domain = DOMAINS.choose(rng).copied().unwrap_or(DOMAINS[0]);
+    format!("{}@{}", local, domain)
+}
+
+/// `Co-authored-by` trailers for `count` different names from `names`, or all of them if
+/// there aren't that many.
+pub fn trailers<R>(names: &[String], count: usize, rng: &mut R) -> Vec<String>
+where
+    R: Rng + ?Sized,
+{
+    // Names weighted by repeating them, as from git history, are only counted once
+    let mut names: Vec<&str> = (names.iter())
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    let chosen: Vec<&str> = names.choose_multiple(rng, count).copied().collect();
+    (chosen.into_iter())
+        .map(|name| format!("Co-authored-by: {} <{}>", name, email(name, rng)))
+        .collect()
+}
+
+/// Whether `line` is a trailer, a `Key: value` line like git writes at the end of a
+/// message.
+fn is_trailer(line: &str) -> bool {
+    line.split_once(": ").is_some_and(|(key, _)| {
+        !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+/// `message` ending with `trailers`, added to the trailers it already ends with or after
+/// a blank line, as git expects them.
+pub fn append(message: &str, trailers: &[String]) -> String {
+    let message = message.trim_end();
+    if trailers.is_empty() {
+        return message.to_string();
+    }
+    let in_block = message
+        .rsplit_once("\n\n")
+        .is_some_and(|(_, last)| last.lines().all(is_trailer));
+    let separator = if in_block { "\n" } else { "\n\n" };
+    format!("{}{}{}", message, separator, trailers.join("\n"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_co_author_trailers() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let address = email("Mary O'Brien", &mut rng);
+        assert!(address.starts_with("mary.obrien@example."));
+        assert!(email("修复", &mut rng).starts_with("dev"));
+
+        let names = ["Ann", "Bob", " ", "Ann"].map(String::from);
+        let trailers = trailers(&names, 5, &mut rng);
+        assert_eq!(trailers.len(), 2);
+        assert!(trailers
+            .iter()
+            .any(|t| t.starts_with("Co-authored-by: Ann <ann@")));
+
+        let ann = ["Co-authored-by: Ann <ann@example.com>".to_string()];
+        assert_eq!(append("Fix: it\n", &ann), format!("Fix: it\n\n{}", ann[0]));
+        assert_eq!(
+            append("Fixed it\n\nCo-authored-by: Bob <bob@example.org>", &ann),
+            format!(
+                "Fixed it\n\nCo-authored-by: Bob <bob@example.org>\n{}",
+                ann[0]
+            )
+        );
+        assert_eq!(append("Fixed it\n\n", &[]), "Fixed it");
+    }
+}
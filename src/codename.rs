@@ -0,0 +1,189 @@
+use crate::daily;
+use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+
+/// Adjectives a codename starts with, at least one for each letter of [`ANIMALS`].
+const ADJECTIVES: [&str; 52] = [
+    "Anxious",
+    "Awkward",
+    "Belligerent",
+    "Bewildered",
+    "Caffeinated",
+    "Cranky",
+    "Dubious",
+    "Disgruntled",
+    "Erratic",
+    "Exhausted",
+    "Flaky",
+    "Frantic",
+    "Grumpy",
+    "Gullible",
+    "Hasty",
+    "Haunted",
+    "Impatient",
+    "Indecisive",
+    "Jittery",
+    "Jaded",
+    "Kludgy",
+    "Kooky",
+    "Lethargic",
+    "Legacy",
+    "Meddlesome",
+    "Melodramatic",
+    "Neurotic",
+    "Nervous",
+    "Obstinate",
+    "Overengineered",
+    "Petulant",
+    "Panicked",
+    "Quarrelsome",
+    "Quirky",
+    "Reckless",
+    "Rambunctious",
+    "Sleepy",
+    "Suspicious",
+    "Truculent",
+    "Tangled",
+    "Unhinged",
+    "Untested",
+    "Volatile",
+    "Vexed",
+    "Wobbly",
+    "Wistful",
+    "Xeroxed",
+    "Xeric",
+    "Yawning",
+    "Yodeling",
+    "Zealous",
+    "Zany",
+];
+
+/// Animals a codename ends with.
+const ANIMALS: [&str; 52] = [
+    "Aardvark",
+    "Axolotl",
+    "Badger",
+    "Baboon",
+    "Capybara",
+    "Cassowary",
+    "Dingo",
+    "Dodo",
+    "Echidna",
+    "Emu",
+    "Ferret",
+    "Flamingo",
+    "Gecko",
+    "Gnu",
+    "Hedgehog",
+    "Hyena",
+    "Ibex",
+    "Iguana",
+    "Jackal",
+    "Jellyfish",
+    "Kakapo",
+    "Koala",
+    "Lemur",
+    "Llama",
+    "Marmot",
+    "Mongoose",
+    "Narwhal",
+    "Newt",
+    "Ocelot",
+    "Okapi",
+    "Pangolin",
+    "Platypus",
+    "Quokka",
+    "Quail",
+    "Raccoon",
+    "Raven",
+    "Sloth",
+    "Stoat",
+    "Tapir",
+    "Tortoise",
+    "Urchin",
+    "Uakari",
+    "Vulture",
+    "Vole",
+    "Wombat",
+    "Walrus",
+    "Xerus",
+    "Xenops",
+    "Yak",
+    "Yeti",
+    "Zebra",
+    "Zorilla",
+];
+
+/// Words of `words` starting with `letter`, ignoring case.
+fn starting_with(words: &'static [&'static str], letter: char) -> Vec<&'static str> {
+    words
+        .iter()
+        .copied()
+        .filter(|word| {
+            word.chars()
+                .next()
+                .is_some_and(|c| c.eq_ignore_ascii_case(&letter))
+        })
+        .collect()
+}
+
+/// An alliterative codename such as "Quarrelsome Quokka", starting with `letter` if given.
+pub fn generate<R>(letter: Option<char>, rng: &mut R) -> Result<String>
+where
+    R: Rng + ?Sized,
+{
+    let letter = match letter {
+        Some(letter) => letter,
+        None => ANIMALS.choose(rng).unwrap().chars().next().unwrap(),
+    };
+    let (adjectives, animals) = (
+        starting_with(&ADJECTIVES, letter),
+        starting_with(&ANIMALS, letter),
+    );
+    match (adjectives.choose(rng), animals.choose(rng)) {
+        (Some(adjective), Some(animal)) => Ok(format!("{} {}", adjective, animal)),
+        _ => anyhow::bail!("No codename starts with {:?}", letter),
+    }
+}
+
+/// Prints `count` codenames, or with `version` the one codename that version always gets.
+pub fn run<R>(count: usize, letter: Option<char>, version: Option<&str>, rng: &mut R) -> Result<()>
+where
+    R: Rng + ?Sized,
+{
+    if let Some(version) = version {
+        let mut rng = StdRng::seed_from_u64(daily::fnv1a(version.as_bytes()));
+        println!("{}", generate(letter, &mut rng)?);
+        return Ok(());
+    }
+    for _ in 0..count {
+        println!("{}", generate(letter, rng)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_codenames() {
+        for animal in ANIMALS {
+            let letter = animal.chars().next().unwrap();
+            assert!(!starting_with(&ADJECTIVES, letter).is_empty(), "{}", animal);
+        }
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..20 {
+            let codename = generate(None, &mut rng).unwrap();
+            let initials: Vec<char> = codename
+                .split(' ')
+                .map(|w| w.chars().next().unwrap())
+                .collect();
+            assert_eq!(initials[0], initials[1], "{}", codename);
+        }
+        assert!(generate(Some('q'), &mut rng).unwrap().starts_with('Q'));
+        assert!(generate(Some('7'), &mut rng).is_err());
+    }
+}
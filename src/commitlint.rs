@@ -0,0 +1,223 @@
+use crate::conventional;
+use crate::text;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// Rules the commitlint check understands; any others in the config are ignored.
+const SUPPORTED_RULES: [&str; 3] = ["header-max-length", "type-enum", "subject-case"];
+
+/// A commitlint config file, of which only `rules` is read.
+#[derive(Debug, Default, Deserialize)]
+struct RawConfig {
+    #[serde(default)]
+    rules: BTreeMap<String, Value>,
+}
+
+/// Whether a rule requires or forbids its condition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Applicable {
+    Always,
+    Never,
+}
+
+/// Checks messages against the supported subset of a commitlint config.
+///
+/// Rules use commitlint's `[level, "always" | "never", value]` form. Only
+/// error-level (2) rules are enforced.
+#[derive(Debug, Default, PartialEq)]
+pub struct Commitlint {
+    header_max_length: Option<usize>,
+    type_enum: Option<(Applicable, Vec<String>)>,
+    subject_case: Option<(Applicable, Vec<String>)>,
+}
+
+/// Splits an enforced rule into its applicability and value, or `None` if it is disabled
+/// or only a warning.
+fn enforced<'a>(name: &str, rule: &'a Value) -> Result<Option<(Applicable, &'a Value)>> {
+    let parts = rule
+        .as_array()
+        .with_context(|| format!("Rule `{}` must be an array", name))?;
+    let level = parts.first().and_then(Value::as_u64).unwrap_or(0);
+    if level < 2 {
+        return Ok(None);
+    }
+    let applicable = match parts.get(1).and_then(Value::as_str) {
+        None | Some("always") => Applicable::Always,
+        Some("never") => Applicable::Never,
+        Some(other) => anyhow::bail!("Rule `{}` has unknown applicability `{}`", name, other),
+    };
+    let value = parts
+        .get(2)
+        .with_context(|| format!("Rule `{}` is missing its value", name))?;
+    Ok(Some((applicable, value)))
+}
+
+fn strings(name: &str, value: &Value) -> Result<Vec<String>> {
+    let list = match value {
+        Value::String(s) => vec![s.clone()],
+        Value::Array(items) => items
+            .iter()
+            .map(|v| v.as_str().map(str::to_string))
+            .collect::<Option<_>>()
+            .with_context(|| format!("Rule `{}` must list strings", name))?,
+        _ => anyhow::bail!("Rule `{}` must list strings", name),
+    };
+    Ok(list)
+}
+
+/// Returns whether `text` is written in the named commitlint case.
+fn is_case(text: &str, case: &str) -> bool {
+    let words = || text.split_whitespace();
+    let capitalized = |w: &str| w.chars().next().is_some_and(|c| !c.is_lowercase());
+    match case {
+        "lower-case" | "lowercase" => text == text.to_lowercase(),
+        "upper-case" | "uppercase" => text == text.to_uppercase(),
+        "sentence-case" | "sentencecase" => capitalized(text),
+        "start-case" => words().all(capitalized),
+        "pascal-case" => !text.contains([' ', '-', '_']) && capitalized(text),
+        "camel-case" => {
+            !text.contains([' ', '-', '_']) && text.chars().next().is_some_and(char::is_lowercase)
+        }
+        "kebab-case" => text == text.to_lowercase() && !text.contains([' ', '_']),
+        "snake-case" => text == text.to_lowercase() && !text.contains([' ', '-']),
+        _ => false,
+    }
+}
+
+impl Commitlint {
+    /// Parses a commitlint config in JSON form, such as `.commitlintrc.json`.
+    pub fn parse(content: &str) -> Result<Self> {
+        let raw: RawConfig = serde_json::from_str(content)?;
+        let mut lint = Commitlint::default();
+        for (name, rule) in &raw.rules {
+            if !SUPPORTED_RULES.contains(&name.as_str()) {
+                warn!("Ignoring unsupported commitlint rule `{}`", name);
+                continue;
+            }
+            let Some((applicable, value)) = enforced(name, rule)? else {
+                continue;
+            };
+            match name.as_str() {
+                "header-max-length" => {
+                    let max = value
+                        .as_u64()
+                        .context("Rule `header-max-length` must be a number")?;
+                    lint.header_max_length = Some(max as usize);
+                }
+                "type-enum" => lint.type_enum = Some((applicable, strings(name, value)?)),
+                _ => lint.subject_case = Some((applicable, strings(name, value)?)),
+            }
+        }
+        Ok(lint)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read commitlint config: {:?}", path))?;
+        let lint = Self::parse(&content)
+            .with_context(|| format!("Failed to parse commitlint config: {:?}", path))?;
+        info!("Loaded commitlint rules from {:?}", path);
+        Ok(lint)
+    }
+
+    /// Returns a description of every rule the message breaks.
+    ///
+    /// Like commitlint, the type and subject rules only apply to headers in the
+    /// `type(scope): subject` form.
+    pub fn violations(&self, message: &str) -> Vec<String> {
+        let mut violations = Vec::new();
+        let header = message.lines().next().unwrap_or_default();
+
+        if let Some(max) = self.header_max_length {
+            let len = text::length(header);
+            if len > max {
+                violations.push(format!("header-max-length: {} > {}", len, max));
+            }
+        }
+
+        let word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        let Some(conventional::Header { kind, subject, .. }) = conventional::parse(header, word)
+        else {
+            return violations;
+        };
+
+        if let Some((applicable, types)) = &self.type_enum {
+            if types.iter().any(|t| t == kind) != (*applicable == Applicable::Always) {
+                violations.push(format!("type-enum: `{}`", kind));
+            }
+        }
+        if let Some((applicable, cases)) = &self.subject_case {
+            if cases.iter().any(|c| is_case(subject, c)) != (*applicable == Applicable::Always) {
+                violations.push(format!("subject-case: `{}`", subject));
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CONFIG: &str = r#"{
+        "extends": ["@commitlint/config-conventional"],
+        "rules": {
+            "header-max-length": [2, "always", 20],
+            "type-enum": [2, "always", ["feat", "fix"]],
+            "subject-case": [2, "never", ["upper-case", "start-case"]],
+            "body-max-line-length": [2, "always", 100],
+            "scope-case": [1, "always", "lower-case"]
+        }
+    }"#;
+
+    #[test]
+    fn t_commitlint_rules() {
+        let lint = Commitlint::parse(CONFIG).unwrap();
+        assert!(lint.violations("fix(ui): oops").is_empty());
+        assert!(lint.violations("no type at all").is_empty());
+        assert_eq!(lint.violations("chore: oops"), vec!["type-enum: `chore`"]);
+        assert_eq!(
+            lint.violations("fix: SHOUTING"),
+            vec!["subject-case: `SHOUTING`"]
+        );
+        assert_eq!(
+            lint.violations("this header is far too long"),
+            vec!["header-max-length: 27 > 20"]
+        );
+    }
+
+    #[test]
+    fn t_header_forms() {
+        let lint = Commitlint::parse(CONFIG).unwrap();
+        assert_eq!(
+            lint.violations("fix(ui)!: SHOUTING"),
+            vec!["subject-case: `SHOUTING`"]
+        );
+        assert_eq!(
+            lint.violations("chore()!: oops"),
+            vec!["type-enum: `chore`"]
+        );
+        assert_eq!(
+            lint.violations("snake_case: oops"),
+            vec!["type-enum: `snake_case`"]
+        );
+        // Only the header counts, and only in the `type(scope): subject` form
+        assert!(lint
+            .violations("fix: oops\n\nSHOUTING in a very long body")
+            .is_empty());
+        assert!(lint.violations("chore (deps): oops").is_empty());
+        assert!(lint.violations("chore(deps:oops").is_empty());
+    }
+
+    #[test]
+    fn t_warnings_are_not_enforced() {
+        let lint =
+            Commitlint::parse(r#"{"rules": {"header-max-length": [1, "always", 5]}}"#).unwrap();
+        assert_eq!(lint, Commitlint::default());
+    }
+}
@@ -0,0 +1,78 @@
+use rand::seq::IndexedRandom;
+use rand::Rng;
+
+/// Hand-picked names, drawn about half the time.
+const CURATED: [&str; 20] = [
+    "Synergex",
+    "Cloudnado",
+    "Blockchainly",
+    "Kubernautics",
+    "DataDingo",
+    "Quantumfy",
+    "Stackwise",
+    "Monolithix",
+    "Serverlessly",
+    "Scrumtastic",
+    "PivotPoint",
+    "Bytebarn",
+    "Legacy Systems Inc.",
+    "Hyperscale Labs",
+    "Agilitron Dynamics",
+    "Disruptr",
+    "Paradigm Shift Co.",
+    "Webscale Ventures",
+    "NullPointer Partners",
+    "Yakshave.io",
+];
+
+/// Syllables a generated name starts with.
+const PREFIXES: [&str; 16] = [
+    "Cloud", "Data", "Byte", "Hyper", "Quant", "Sync", "Stack", "Flux", "Nimbus", "Vertex",
+    "Pixel", "Logi", "Omni", "Cyber", "Meta", "Neo",
+];
+
+/// Syllables a generated name ends with.
+const SUFFIXES: [&str; 14] = [
+    "ly", "ify", "ora", "io", "nix", "hub", "forge", "ware", "base", "scale", "soft", "tron",
+    "verse", "matic",
+];
+
+/// Endings sometimes added to a generated name to make it sound more corporate.
+const ENDINGS: [&str; 5] = [" Inc.", " Labs", " Systems", " AI", " Cloud"];
+
+/// Generates a plausible fake company or product name, either from a curated list or
+/// stitched together from syllables.
+pub fn generate<R>(rng: &mut R) -> String
+where
+    R: Rng + ?Sized,
+{
+    if rng.random_bool(0.5) {
+        return CURATED.choose(rng).unwrap().to_string();
+    }
+    let mut name = String::new();
+    name.push_str(PREFIXES.choose(rng).unwrap());
+    name.push_str(SUFFIXES.choose(rng).unwrap());
+    if rng.random_ratio(1, 4) {
+        name.push_str(ENDINGS.choose(rng).unwrap());
+    }
+    name
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_generate_company() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let names: Vec<String> = (0..200).map(|_| generate(&mut rng)).collect();
+        assert!(names.iter().all(|n| !n.is_empty() && !n.contains('X')));
+        assert!(names.iter().any(|n| CURATED.contains(&n.as_str())));
+        assert!(names.iter().any(|n| !CURATED.contains(&n.as_str())));
+
+        let mut again = StdRng::seed_from_u64(7);
+        assert_eq!(generate(&mut again), names[0]);
+    }
+}
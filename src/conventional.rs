@@ -0,0 +1,58 @@
+/// The parts of a Conventional Commits header, `type(scope)!: subject`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Header<'a> {
+    pub kind: &'a str,
+    pub scope: Option<&'a str>,
+    pub breaking: bool,
+    pub subject: &'a str,
+}
+
+/// Parses a one-line header whose type is made of characters `in_type` accepts. The scope
+/// may be empty but can't hold a `)`; the subject is everything after the `: `.
+pub fn parse(header: &str, in_type: fn(char) -> bool) -> Option<Header<'_>> {
+    let type_len = header.find(|c| !in_type(c)).unwrap_or(header.len());
+    if type_len == 0 {
+        return None;
+    }
+    let (kind, mut rest) = header.split_at(type_len);
+    let mut scope = None;
+    if let Some(after) = rest.strip_prefix('(') {
+        let (inside, after) = after.split_once(')')?;
+        scope = Some(inside);
+        rest = after;
+    }
+    let breaking = rest.starts_with('!');
+    let subject = rest[breaking as usize..].strip_prefix(": ")?;
+    Some(Header {
+        kind,
+        scope,
+        breaking,
+        subject,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_conventional_headers() {
+        let letters = |c: char| c.is_ascii_alphabetic();
+        assert_eq!(
+            parse("feat(api)!: drop v1", letters),
+            Some(Header {
+                kind: "feat",
+                scope: Some("api"),
+                breaking: true,
+                subject: "drop v1",
+            })
+        );
+        let plain = parse("fix: it", letters).unwrap();
+        assert_eq!((plain.scope, plain.breaking), (None, false));
+        assert_eq!(parse("fix(): ", letters).unwrap().scope, Some(""));
+        assert_eq!(parse("fix(api: it", letters), None);
+        assert_eq!(parse("fix:it", letters), None);
+        assert_eq!(parse(": it", letters), None);
+        assert_eq!(parse("fix2: it", letters), None);
+    }
+}
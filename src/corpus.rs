@@ -0,0 +1,680 @@
+use crate::ci;
+use crate::diagnostic::{self, Diagnostic};
+use crate::error::WtcError;
+use crate::lint;
+use crate::paths;
+use anyhow::{Context, Result};
+use std::borrow::Cow;
+use std::fs::{self, File};
+use std::io::{self, BufRead, Read};
+#[cfg(not(feature = "mmap"))]
+use std::io::{BufReader, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+#[cfg(not(feature = "mmap"))]
+use std::sync::Mutex;
+use tracing::{debug, info, instrument, warn};
+
+/// Template files at least this big are indexed rather than loaded whole.
+pub const STREAM_THRESHOLD: u64 = 64 * 1024 * 1024;
+
+/// Byte order marks that corpus files, often exported from Windows tools, may start with.
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+const UTF16_LE_BOM: &[u8] = b"\xff\xfe";
+const UTF16_BE_BOM: &[u8] = b"\xfe\xff";
+
+const NOT_UTF8: &str = "is not valid UTF-8; the file may be in a legacy encoding such as \
+    Windows-1252, so save it as UTF-8";
+const HAS_NUL: &str = "contains NUL bytes; the file is probably UTF-16 without a byte \
+    order mark, so save it as UTF-8";
+const INDEXED_UTF16: &str = "is UTF-16, but files this big are read a line at a time and \
+    must be UTF-8, so save it as UTF-8";
+const INDEXED_CR: &str = "ends lines with a lone carriage return, but files this big are \
+    read a line at a time and must end them with LF or CRLF";
+
+/// Decodes a names or templates file: UTF-8, with or without a byte order mark, or UTF-16
+/// with one. Files with old Mac line endings, a lone `\r`, get `\n` instead; CRLF is left
+/// for `str::lines` to handle. Anything else fails with the line it fails on.
+pub fn decode(bytes: Vec<u8>) -> Result<String, WtcError> {
+    let text = if let Some(units) = bytes.strip_prefix(UTF16_LE_BOM) {
+        decode_utf16(units, u16::from_le_bytes)?
+    } else if let Some(units) = bytes.strip_prefix(UTF16_BE_BOM) {
+        decode_utf16(units, u16::from_be_bytes)?
+    } else {
+        let mut bytes = bytes;
+        if bytes.starts_with(UTF8_BOM) {
+            bytes.drain(..UTF8_BOM.len());
+        }
+        String::from_utf8(bytes).map_err(|e| {
+            let valid = &e.as_bytes()[..e.utf8_error().valid_up_to()];
+            WtcError::InvalidTemplate {
+                line: valid.iter().filter(|&&b| b == b'\n').count() + 1,
+                problem: NOT_UTF8,
+            }
+        })?
+    };
+    if let Some(at) = text.find('\0') {
+        return Err(WtcError::InvalidTemplate {
+            line: text[..at].matches('\n').count() + 1,
+            problem: HAS_NUL,
+        });
+    }
+    if !text.contains('\n') && text.contains('\r') {
+        return Ok(text.replace('\r', "\n"));
+    }
+    Ok(text)
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> Result<String, WtcError> {
+    let units = bytes.chunks(2).map(|pair| match pair {
+        [a, b] => unit([*a, *b]),
+        // An odd trailing byte can't be a whole character
+        _ => 0xdc00,
+    });
+    let mut text = String::with_capacity(bytes.len() / 2);
+    for c in char::decode_utf16(units) {
+        match c {
+            Ok(c) => text.push(c),
+            Err(_) => {
+                return Err(WtcError::InvalidTemplate {
+                    line: text.matches('\n').count() + 1,
+                    problem: "is not valid UTF-16",
+                })
+            }
+        }
+    }
+    Ok(text)
+}
+
+/// The templates files at `paths`, read whole, or else the `loaded` templates under the
+/// name given with them, for the subcommands that check or rewrite template packs.
+pub fn packs(paths: &[PathBuf], loaded: (&Path, &[String])) -> Result<Vec<(PathBuf, String)>> {
+    if paths.is_empty() {
+        return Ok(vec![(loaded.0.to_path_buf(), loaded.1.join("\n"))]);
+    }
+    paths
+        .iter()
+        .map(|path| {
+            let content = fs::read(path).map_err(|source| WtcError::IoError {
+                context: format!("Failed to read templates file: {:?}", path),
+                source,
+            })?;
+            Ok((path.clone(), decode(content)?))
+        })
+        .collect()
+}
+
+/// A template corpus, either held in memory or read from its file on demand.
+pub enum Corpus {
+    Lines(Vec<String>),
+    Indexed(LineIndex),
+}
+
+impl From<Vec<String>> for Corpus {
+    fn from(lines: Vec<String>) -> Self {
+        Corpus::Lines(lines)
+    }
+}
+
+impl Corpus {
+    pub fn len(&self) -> usize {
+        match self {
+            Corpus::Lines(lines) => lines.len(),
+            Corpus::Indexed(index) => index.lines.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The template at `index`, which must be below `len()`.
+    pub fn get(&self, index: usize) -> Result<Cow<'_, str>> {
+        match self {
+            Corpus::Lines(lines) => Ok(Cow::Borrowed(&lines[index])),
+            Corpus::Indexed(index_file) => index_file.get(index),
+        }
+    }
+
+    /// Every template, reading the whole file for an indexed corpus. Only for the
+    /// subcommands that show the corpus rather than generate from it.
+    pub fn to_lines(&self) -> Result<Vec<String>> {
+        match self {
+            Corpus::Lines(lines) => Ok(lines.clone()),
+            Corpus::Indexed(index) => (0..index.lines.len())
+                .map(|i| index.get(i).map(Cow::into_owned))
+                .collect(),
+        }
+    }
+}
+
+/// Byte ranges of the non-empty lines of a file, so that any line can be read without
+/// keeping the others in memory.
+///
+/// With the `mmap` feature the file is memory-mapped, and lines are borrowed straight from
+/// the map; otherwise each line is read from the file when it is needed.
+pub struct LineIndex {
+    path: PathBuf,
+    #[cfg(feature = "mmap")]
+    map: memmap2::Mmap,
+    #[cfg(not(feature = "mmap"))]
+    file: Mutex<File>,
+    lines: Vec<Range<u64>>,
+}
+
+/// Checks one line of a names or templates file, `number` counting from one, returning
+/// its text without the line ending, or `None` when it is blank. A byte order mark on the
+/// first line must already be stripped.
+fn line_text<'a>(
+    path: &Path,
+    what: &str,
+    line: &'a [u8],
+    number: usize,
+) -> Result<Option<&'a str>> {
+    let invalid = |problem| {
+        Err(WtcError::InvalidTemplate {
+            line: number,
+            problem,
+        })
+        .with_context(|| format!("Invalid {} file: {:?}", what, path))
+    };
+    let Ok(text) = std::str::from_utf8(line) else {
+        return invalid(NOT_UTF8);
+    };
+    if text.contains('\0') {
+        return invalid(HAS_NUL);
+    }
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    let content = text.strip_suffix('\n').unwrap_or(text);
+    let content = content.strip_suffix('\r').unwrap_or(content);
+    if let Some((at, problem)) = diagnostic::check_line(content) {
+        let diagnostic = Diagnostic::new(path, number, content, at, problem);
+        return Err(WtcError::Diagnostic(Box::new(diagnostic)).into());
+    }
+    // Malformed placeholders only matter in templates, where they are left as written
+    if what == "commit messages" {
+        check_placeholders(path, number, content)?;
+    }
+    Ok(Some(content))
+}
+
+/// Warns about each malformed placeholder of `line`, line `number` of the templates at
+/// `path`, once as it is loaded rather than every time it is rendered. In strict CI mode
+/// the first one is an error. Problems only worth a lint are left to `verify`.
+pub fn check_placeholders(path: &Path, number: usize, line: &str) -> Result<()> {
+    for placeholder in lint::scan(line) {
+        if placeholder.lint_only {
+            continue;
+        }
+        if let Some(problem) = placeholder.problem {
+            let problem = format!("`{}`: {}", &line[placeholder.range.clone()], problem);
+            let diagnostic = Box::new(Diagnostic::new(
+                path,
+                number,
+                line,
+                placeholder.range.start,
+                problem,
+            ));
+            if ci::strict() {
+                return Err(WtcError::Diagnostic(diagnostic).into());
+            }
+            warn!("{}", diagnostic);
+        }
+    }
+    Ok(())
+}
+
+/// Records the range of `line`, found at `offset`, unless it is blank. `number` is the
+/// one-based line number, for errors. A first line starting with a UTF-16 byte order mark,
+/// or a whole file of lines ending in a lone `\r`, can't be indexed and is an error.
+fn push_line(
+    lines: &mut Vec<Range<u64>>,
+    path: &Path,
+    line: &[u8],
+    offset: u64,
+    number: usize,
+) -> Result<()> {
+    let unindexable = if number > 1 {
+        None
+    } else if line.starts_with(UTF16_LE_BOM) || line.starts_with(UTF16_BE_BOM) {
+        Some(INDEXED_UTF16)
+    } else {
+        // Only a file without a single LF is read in one line
+        (!line.ends_with(b"\n") && line.contains(&b'\r')).then_some(INDEXED_CR)
+    };
+    if let Some(problem) = unindexable {
+        return Err(WtcError::InvalidTemplate { line: 1, problem })
+            .with_context(|| format!("Invalid commit messages file: {:?}", path));
+    }
+    let (line, offset) = match line.strip_prefix(UTF8_BOM) {
+        Some(rest) if number == 1 => (rest, offset + UTF8_BOM.len() as u64),
+        _ => (line, offset),
+    };
+    if let Some(content) = line_text(path, "commit messages", line, number)? {
+        lines.push(offset..offset + content.len() as u64);
+    }
+    Ok(())
+}
+
+/// Reads the non-blank lines of a names or templates stream, such as stdin, one at a time,
+/// checking each like a line of a file. `path` names the stream in errors.
+pub fn stream_lines<R: BufRead>(
+    mut reader: R,
+    path: &Path,
+    what: &str,
+    mut each: impl FnMut(&str),
+) -> Result<()> {
+    let mut line = Vec::new();
+    for number in 1.. {
+        line.clear();
+        let read = reader
+            .read_until(b'\n', &mut line)
+            .map_err(|source| WtcError::IoError {
+                context: format!("Failed to read {} from {:?}", what, path),
+                source,
+            })?;
+        if read == 0 {
+            break;
+        }
+        let bytes = match line.strip_prefix(UTF8_BOM) {
+            Some(rest) if number == 1 => rest,
+            _ => &line[..],
+        };
+        if let Some(text) = line_text(path, what, bytes, number)? {
+            each(text);
+        }
+    }
+    Ok(())
+}
+
+/// The error for a template file that can't be read.
+fn read_error(path: &Path, source: io::Error) -> WtcError {
+    WtcError::IoError {
+        context: format!("Failed to read commit messages file: {:?}", path),
+        source,
+    }
+}
+
+/// The error for a template file that no longer matches its index.
+fn changed_error(path: &Path, source: std::str::Utf8Error) -> WtcError {
+    WtcError::IoError {
+        context: format!("{:?} changed while it was being read", path),
+        source: io::Error::new(io::ErrorKind::InvalidData, source),
+    }
+}
+
+impl LineIndex {
+    /// Indexes a template file in one pass, skipping blank lines like a whole-file load.
+    #[instrument(level = "debug", skip_all, fields(path = ?path))]
+    pub fn build(path: &Path) -> Result<Self> {
+        let file = File::open(path).map_err(|e| read_error(path, e))?;
+        let mut lines = Vec::new();
+
+        #[cfg(feature = "mmap")]
+        let index = {
+            // SAFETY: the map is only ever read. Like any mapping, it assumes the file isn't
+            // truncated while in use.
+            let map = unsafe { memmap2::Mmap::map(&file) }.map_err(|e| read_error(path, e))?;
+            let mut offset = 0;
+            for (i, line) in map.split_inclusive(|&b| b == b'\n').enumerate() {
+                push_line(&mut lines, path, line, offset, i + 1)?;
+                offset += line.len() as u64;
+            }
+            LineIndex {
+                path: path.to_path_buf(),
+                map,
+                lines,
+            }
+        };
+
+        #[cfg(not(feature = "mmap"))]
+        let index = {
+            let mut reader = BufReader::with_capacity(1 << 20, &file);
+            let mut line = Vec::new();
+            let mut offset = 0;
+            for number in 1.. {
+                line.clear();
+                let read = reader
+                    .read_until(b'\n', &mut line)
+                    .map_err(|e| read_error(path, e))?;
+                if read == 0 {
+                    break;
+                }
+                push_line(&mut lines, path, &line, offset, number)?;
+                offset += read as u64;
+            }
+            LineIndex {
+                path: path.to_path_buf(),
+                file: Mutex::new(file),
+                lines,
+            }
+        };
+
+        if index.lines.is_empty() {
+            return Err(WtcError::EmptyCorpus {
+                what: "commit messages",
+            }
+            .into());
+        }
+        info!(
+            "Indexed {} commit messages in {:?}",
+            index.lines.len(),
+            path
+        );
+        Ok(index)
+    }
+
+    #[cfg(feature = "mmap")]
+    fn get(&self, index: usize) -> Result<Cow<'_, str>> {
+        let range = &self.lines[index];
+        let text = std::str::from_utf8(&self.map[range.start as usize..range.end as usize])
+            .map_err(|e| changed_error(&self.path, e))?;
+        Ok(Cow::Borrowed(text))
+    }
+
+    #[cfg(not(feature = "mmap"))]
+    fn get(&self, index: usize) -> Result<Cow<'_, str>> {
+        let range = self.lines[index].clone();
+        let mut buf = vec![0; (range.end - range.start) as usize];
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        file.seek(SeekFrom::Start(range.start))
+            .and_then(|_| file.read_exact(&mut buf))
+            .map_err(|e| read_error(&self.path, e))?;
+        let text = String::from_utf8(buf).map_err(|e| changed_error(&self.path, e.utf8_error()))?;
+        Ok(Cow::Owned(text))
+    }
+}
+
+/// Whether a template file is big enough to be indexed rather than loaded whole. UTF-16
+/// files are always loaded whole, to be converted.
+pub fn should_index(path: &Path) -> bool {
+    let mut start = [0; 2];
+    fs::metadata(path).is_ok_and(|m| m.len() >= STREAM_THRESHOLD)
+        && File::open(path)
+            .and_then(|mut f| f.read_exact(&mut start))
+            .is_ok()
+        && ![UTF16_LE_BOM, UTF16_BE_BOM].contains(&&start[..])
+}
+
+/// Most entries a pool weighed by `--mix` is given, so a tiny share of a huge corpus can't
+/// blow it up.
+const MAX_MIXED_POOL: f64 = 1_000_000.0;
+
+/// Copies of each template of the source with the smallest share per template, so the
+/// others round to their shares closely.
+const MIX_PRECISION: f64 = 10.0;
+
+/// How often each source of templates is drawn from, as `--mix` gives it, e.g.
+/// `default=0.7,custom=0.3`. Shares need not add up to 1; each source gets its share of
+/// their total.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mix {
+    pub shares: Vec<(String, f64)>,
+}
+
+impl FromStr for Mix {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut shares: Vec<(String, f64)> = Vec::new();
+        for part in s.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            let (source, share) = part
+                .split_once('=')
+                .ok_or_else(|| format!("expected SOURCE=SHARE, got `{}`", part))?;
+            let source = source.trim();
+            let share: f64 = share
+                .trim()
+                .parse()
+                .ok()
+                .filter(|share: &f64| share.is_finite() && *share > 0.0)
+                .ok_or_else(|| format!("the share of `{}` must be a positive number", source))?;
+            if source.is_empty() {
+                return Err(format!("`{}` names no source", part));
+            }
+            if !matches!(source, "default" | "custom") && !paths::is_pack_name(source) {
+                return Err(format!("`{}` can't be the name of a pack", source));
+            }
+            if shares.iter().any(|(seen, _)| seen == source) {
+                return Err(format!("`{}` is given twice", source));
+            }
+            shares.push((source.to_string(), share));
+        }
+        if shares.is_empty() {
+            return Err("give at least one SOURCE=SHARE".to_string());
+        }
+        Ok(Mix { shares })
+    }
+}
+
+/// Where each source of a corpus composed for `--mix` is in it, and its share.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mixed {
+    spans: Vec<(String, Range<usize>, f64)>,
+}
+
+impl Mixed {
+    /// Puts the templates of each of `sources`, a name, its templates and its share, one
+    /// after the other in a corpus.
+    pub fn compose(sources: Vec<(String, Vec<String>, f64)>) -> (Corpus, Mixed) {
+        let mut lines = Vec::new();
+        let mut spans = Vec::with_capacity(sources.len());
+        for (source, templates, share) in sources {
+            let start = lines.len();
+            lines.extend(templates);
+            spans.push((source, start..lines.len(), share));
+        }
+        (Corpus::from(lines), Mixed { spans })
+    }
+
+    /// Repeats each template of `pool` so each source is drawn from its share of the time.
+    /// Templates from no source are left as they are, and a source with none left in the
+    /// pool, as when all are blocked, gives its share to the others.
+    pub fn weigh(&self, pool: &mut Vec<usize>) {
+        let source_of =
+            |index: usize| (self.spans.iter()).position(|(_, span, _)| span.contains(&index));
+        let mut counts = vec![0usize; self.spans.len()];
+        for &index in pool.iter() {
+            if let Some(source) = source_of(index) {
+                counts[source] += 1;
+            }
+        }
+        for ((source, _, _), &count) in self.spans.iter().zip(&counts) {
+            if count == 0 {
+                warn!("No templates of `{}` are left to mix in", source);
+            }
+        }
+        let total: f64 = (self.spans.iter().zip(&counts))
+            .filter(|(_, &count)| count > 0)
+            .map(|((_, _, share), _)| share)
+            .sum();
+        // The share of the pool each template of a source gets
+        let per_template: Vec<f64> = (self.spans.iter().zip(&counts))
+            .map(|((_, _, share), &count)| share / total / count.max(1) as f64)
+            .collect();
+        let Some(smallest) = (per_template.iter().zip(&counts))
+            .filter(|(_, &count)| count > 0)
+            .map(|(&share, _)| share)
+            .min_by(f64::total_cmp)
+        else {
+            return;
+        };
+        let size = (MIX_PRECISION / smallest).min(MAX_MIXED_POOL);
+        let copies: Vec<usize> = (per_template.iter())
+            .map(|share| (share * size).round().max(1.0) as usize)
+            .collect();
+        let mixed: Vec<usize> = (pool.iter())
+            .flat_map(|&index| {
+                let copies = source_of(index).map_or(1, |source| copies[source]);
+                std::iter::repeat_n(index, copies)
+            })
+            .collect();
+        debug!(
+            "Mixing {} sources grew the pool from {} to {}",
+            self.spans.len(),
+            pool.len(),
+            mixed.len()
+        );
+        *pool = mixed;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_indexed_matches_loaded() {
+        let path = std::env::temp_dir().join(format!("wtc-corpus-{}.txt", std::process::id()));
+        fs::write(&path, "first XNAMEX\n\n  \nsecond\r\nthird, no newline").unwrap();
+        let corpus = Corpus::Indexed(LineIndex::build(&path).unwrap());
+        assert_eq!(corpus.len(), 3);
+        assert_eq!(corpus.get(1).unwrap(), "second");
+        assert_eq!(corpus.get(0).unwrap(), "first XNAMEX");
+        assert_eq!(
+            corpus.to_lines().unwrap(),
+            crate::load_lines_or_default(&Some(path.clone()), None, "commit messages").unwrap()
+        );
+
+        fs::write(&path, "\n \n").unwrap();
+        let err = LineIndex::build(&path).err().unwrap();
+        assert!(matches!(
+            err.downcast_ref::<WtcError>(),
+            Some(WtcError::EmptyCorpus { .. })
+        ));
+
+        // A byte order mark isn't part of the first template
+        fs::write(&path, b"\xef\xbb\xbfbom\nsecond\n").unwrap();
+        assert_eq!(LineIndex::build(&path).unwrap().get(0).unwrap(), "bom");
+        fs::write(&path, b"fine\nnul\0here\n").unwrap();
+        let err = LineIndex::build(&path).err().unwrap();
+        assert!(format!("{:#}", err).contains("Line 2 contains NUL bytes"));
+
+        fs::write(&path, b"fine\nnot \xff utf-8\n").unwrap();
+        let err = LineIndex::build(&path).err().unwrap();
+        assert!(matches!(
+            err.downcast_ref::<WtcError>(),
+            Some(WtcError::InvalidTemplate { line: 2, .. })
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn t_indexed_utf16_is_an_error() {
+        let path = std::env::temp_dir().join(format!("wtc-utf16-{}.txt", std::process::id()));
+        for (bom, unit) in [
+            (UTF16_LE_BOM, u16::to_le_bytes as fn(u16) -> [u8; 2]),
+            (UTF16_BE_BOM, u16::to_be_bytes),
+        ] {
+            let mut bytes = bom.to_vec();
+            bytes.extend("first\nsecond\n".encode_utf16().flat_map(unit));
+            fs::write(&path, bytes).unwrap();
+            let err = format!("{:#}", LineIndex::build(&path).err().unwrap());
+            assert!(err.contains("Line 1 is UTF-16"), "{}", err);
+            assert!(!err.contains("NUL"), "{}", err);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn t_indexed_cr_line_endings_are_an_error() {
+        let path = std::env::temp_dir().join(format!("wtc-cr-{}.txt", std::process::id()));
+        fs::write(&path, "first\rsecond\rthird\r").unwrap();
+        let err = format!("{:#}", LineIndex::build(&path).err().unwrap());
+        assert!(
+            err.contains("Line 1 ends lines with a lone carriage return"),
+            "{}",
+            err
+        );
+        // CRLF is fine
+        fs::write(&path, "first\r\nsecond\r\n").unwrap();
+        assert_eq!(LineIndex::build(&path).unwrap().lines.len(), 2);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn t_indexed_lines_borrow_from_the_map() {
+        let path = std::env::temp_dir().join(format!("wtc-mmap-{}.txt", std::process::id()));
+        fs::write(&path, "Çağla first\n\nβ second 🚀\r\nthird").unwrap();
+        let index = LineIndex::build(&path).unwrap();
+        // Lines can be read in any order, and more than once
+        for i in [2, 0, 1, 1] {
+            let line = index.get(i).unwrap();
+            assert_eq!(line, ["Çağla first", "β second 🚀", "third"][i]);
+            assert_eq!(matches!(line, Cow::Borrowed(_)), cfg!(feature = "mmap"));
+        }
+        drop(index);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn t_decode_encodings() {
+        let utf16 = |bom: &[u8], text: &str, unit: fn(u16) -> [u8; 2]| -> Vec<u8> {
+            let mut bytes = bom.to_vec();
+            bytes.extend(text.encode_utf16().flat_map(unit));
+            bytes
+        };
+        let text = "Çağla fixed it\r\nXNAMEX 🚀\r\n";
+        assert_eq!(decode(text.into()).unwrap(), text);
+        assert_eq!(decode([UTF8_BOM, text.as_bytes()].concat()).unwrap(), text);
+        assert_eq!(
+            decode(utf16(UTF16_LE_BOM, text, u16::to_le_bytes)).unwrap(),
+            text
+        );
+        assert_eq!(
+            decode(utf16(UTF16_BE_BOM, text, u16::to_be_bytes)).unwrap(),
+            text
+        );
+        assert_eq!(decode(b"one\rtwo\r".to_vec()).unwrap(), "one\ntwo\n");
+
+        let problem = |bytes: Vec<u8>| match decode(bytes) {
+            Err(WtcError::InvalidTemplate { line, problem }) => (line, problem),
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(
+            problem(utf16(b"", "no\nbom", u16::to_le_bytes)),
+            (1, HAS_NUL)
+        );
+        assert_eq!(problem(b"caf\xe9\nok".to_vec()), (1, NOT_UTF8));
+        let mut broken = utf16(UTF16_LE_BOM, "ok\n", u16::to_le_bytes);
+        broken.extend([0x00, 0xd8]);
+        assert_eq!(problem(broken), (2, "is not valid UTF-16"));
+    }
+
+    #[test]
+    fn t_mix_shares() {
+        let mix: Mix = "default=0.7, custom=0.3".parse().unwrap();
+        assert_eq!(mix.shares[1], ("custom".to_string(), 0.3));
+        for bad in [
+            "",
+            "default",
+            "default=0",
+            "default=x",
+            "=1",
+            "a=1,a=2",
+            "/tmp/wtc_secret.txt=1",
+            "../secret=1",
+            ".hidden=1",
+            "a\\b=1",
+        ] {
+            assert!(bad.parse::<Mix>().is_err(), "{}", bad);
+        }
+
+        let lines = |n: usize| (0..n).map(|i| format!("template {}", i)).collect();
+        let (corpus, mixed) = Mixed::compose(vec![
+            ("default".to_string(), lines(4), 0.7),
+            ("custom".to_string(), lines(1), 0.3),
+            ("blocked".to_string(), lines(2), 5.0),
+        ]);
+        assert_eq!(corpus.len(), 7);
+        // The last template came from none of the sources, like a seasonal one
+        let mut pool = vec![0, 1, 2, 3, 4, 7];
+        mixed.weigh(&mut pool);
+        let count = |index| pool.iter().filter(|&&i| i == index).count();
+        assert_eq!((count(0), count(4), count(7)), (10, 17, 1));
+        let custom = count(4) as f64 / (pool.len() - 1) as f64;
+        assert!((custom - 0.3).abs() < 0.01, "{}", custom);
+    }
+}
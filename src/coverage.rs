@@ -0,0 +1,196 @@
+use crate::corpus;
+use crate::lint::{self, Directive};
+use crate::sections;
+use crate::stats::{StatsFormat, HEATMAP_PLACEHOLDERS};
+use crate::words::List;
+use anyhow::Result;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+/// Placeholder use across the templates of one file.
+#[derive(Debug, PartialEq)]
+struct Coverage {
+    templates: usize,
+    /// How many templates use each placeholder, in the order of `HEATMAP_PLACEHOLDERS`,
+    /// with `XNUM` standing for every number range.
+    used: Vec<(&'static str, usize)>,
+    /// Templates without a single placeholder.
+    none: usize,
+    /// Variables set but never written, with the one-based line setting them.
+    unused_variables: Vec<(usize, String)>,
+}
+
+/// The placeholders of a template, counted once each, and its variables set but never
+/// written.
+fn placeholders(template: &str) -> (Vec<&'static str>, Vec<String>) {
+    let mut found = Vec::new();
+    let (mut set, mut written) = (Vec::new(), Vec::new());
+    for placeholder in lint::scan(template) {
+        if placeholder.problem.is_some() {
+            continue;
+        }
+        let text = &template[placeholder.range];
+        if let Some(inner) = text.strip_prefix("{{").and_then(|t| t.strip_suffix("}}")) {
+            match lint::parse_directive(inner) {
+                Ok(Directive::Set { name, .. }) => set.push(name),
+                Ok(Directive::Get { name, .. }) => written.push(name),
+                _ => {}
+            }
+            continue;
+        }
+        let text = if text.starts_with("XNUM") {
+            "XNUM"
+        } else {
+            text
+        };
+        if let Some(known) = HEATMAP_PLACEHOLDERS.iter().find(|&&p| p == text) {
+            if !found.contains(known) {
+                found.push(*known);
+            }
+        }
+    }
+    let unused = set
+        .into_iter()
+        .filter(|name| !written.contains(name))
+        .map(String::from)
+        .collect();
+    (found, unused)
+}
+
+fn analyze(text: &str) -> Coverage {
+    let kinds = &HEATMAP_PLACEHOLDERS[..HEATMAP_PLACEHOLDERS.len() - 1];
+    let mut used: Vec<(&'static str, usize)> = kinds.iter().map(|&p| (p, 0)).collect();
+    let mut coverage = Coverage {
+        templates: 0,
+        used: Vec::new(),
+        none: 0,
+        unused_variables: Vec::new(),
+    };
+    for (number, template) in sections::templates(text) {
+        coverage.templates += 1;
+        let (found, unused) = placeholders(template);
+        if found.is_empty() {
+            coverage.none += 1;
+        }
+        for (placeholder, count) in &mut used {
+            *count += found.contains(placeholder) as usize;
+        }
+        let unused = unused.into_iter().map(|name| (number + 1, name));
+        coverage.unused_variables.extend(unused);
+    }
+    coverage.used = used;
+    coverage
+}
+
+/// Describes the coverage of the templates file at `path`. `custom` are the word lists
+/// the user has their own file for, reported when no template draws from them.
+fn render(path: &Path, coverage: &Coverage, custom: &[List], format: StatsFormat) -> String {
+    let unused: Vec<&str> = coverage
+        .used
+        .iter()
+        .filter(|(_, count)| *count == 0)
+        .map(|(placeholder, _)| *placeholder)
+        .collect();
+    let unused_lists: Vec<&str> = custom
+        .iter()
+        .filter(|list| unused.contains(&list.placeholder()))
+        .map(|list| list.file())
+        .collect();
+    match format {
+        StatsFormat::Text => {
+            let mut lines = vec![format!(
+                "{}: {} templates, {} without placeholders",
+                path.display(),
+                coverage.templates,
+                coverage.none
+            )];
+            for (placeholder, count) in coverage.used.iter().filter(|(_, count)| *count > 0) {
+                lines.push(format!("  {:<14}{}", placeholder, count));
+            }
+            if !unused.is_empty() {
+                lines.push(format!("  unused        {}", unused.join(", ")));
+            }
+            for (line, name) in &coverage.unused_variables {
+                lines.push(format!(
+                    "  line {}: `{}` is set but never written",
+                    line, name
+                ));
+            }
+            for file in unused_lists {
+                lines.push(format!("  words/{} is never drawn from", file));
+            }
+            lines.join("\n")
+        }
+        StatsFormat::Json => json!({
+            "path": path,
+            "templates": coverage.templates,
+            "without-placeholders": coverage.none,
+            "placeholders": coverage
+                .used
+                .iter()
+                .map(|(placeholder, count)| (placeholder.to_string(), json!(count)))
+                .collect::<serde_json::Map<_, _>>(),
+            "unused": unused,
+            "unused-variables": coverage
+                .unused_variables
+                .iter()
+                .map(|(line, name)| json!({ "line": line, "name": name }))
+                .collect::<Vec<_>>(),
+            "unused-word-lists": unused_lists,
+        })
+        .to_string(),
+    }
+}
+
+/// Prints the placeholder coverage of the templates files at `paths`, or of the loaded
+/// templates if none are given.
+pub fn run(
+    paths: &[PathBuf],
+    loaded: (&Path, &[String]),
+    words_dir: Option<&Path>,
+    format: StatsFormat,
+) -> Result<()> {
+    let custom: Vec<List> = List::ALL
+        .into_iter()
+        .filter(|list| words_dir.is_some_and(|dir| dir.join(list.file()).is_file()))
+        .collect();
+    for (path, text) in corpus::packs(paths, loaded)? {
+        println!("{}", render(&path, &analyze(&text), &custom, format));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_placeholder_coverage() {
+        let text = "[today]\nXNAMEX broke XNUM1,5X tests\nfixed it\n{{set n = num:1..3}}{{set m = num:1..3}}{{n}} XVERBX\n{{50%: XNAMEX }}again";
+        let coverage = analyze(text);
+        assert_eq!(coverage.templates, 4);
+        assert_eq!(coverage.none, 1);
+        let count = |p: &str| coverage.used.iter().find(|(q, _)| *q == p).unwrap().1;
+        assert_eq!((count("XNAMEX"), count("XNUM"), count("XVERBX")), (2, 1, 1));
+        assert_eq!(coverage.unused_variables, vec![(4, "m".to_string())]);
+
+        let report = render(
+            Path::new("t.txt"),
+            &coverage,
+            &[List::Verb, List::Noun],
+            StatsFormat::Text,
+        );
+        assert!(report.starts_with("t.txt: 4 templates, 1 without placeholders"));
+        assert!(report.contains("line 4: `m` is set but never written"));
+        assert!(report.contains("words/nouns.txt is never drawn from"));
+        assert!(!report.contains("verbs.txt"));
+        let json: serde_json::Value = serde_json::from_str(&render(
+            Path::new("t.txt"),
+            &coverage,
+            &[],
+            StatsFormat::Json,
+        ))
+        .unwrap();
+        assert_eq!(json["placeholders"]["XNAMEX"], 2);
+    }
+}
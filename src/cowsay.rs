@@ -0,0 +1,108 @@
+use crate::text;
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// Maximum width of the text inside the speech bubble.
+const BUBBLE_WIDTH: usize = 40;
+
+/// Characters that can deliver the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Character {
+    /// The classic cow
+    #[default]
+    Cow,
+    /// The Linux penguin
+    Tux,
+    /// The Rust crab
+    Ferris,
+}
+
+impl Character {
+    fn figure(self) -> &'static str {
+        match self {
+            Character::Cow => {
+                r"        \   ^__^
+         \  (oo)\_______
+            (__)\       )\/\
+                ||----w |
+                ||     ||"
+            }
+            Character::Tux => {
+                r"   \
+    \
+        .--.
+       |o_o |
+       |:_/ |
+      //   \ \
+     (|     | )
+    /'\_   _/`\
+    \___)=(___/"
+            }
+            Character::Ferris => {
+                r"        \
+         \
+            _~^~^~_
+        \) /  o o  \ (/
+          '_   -   _'
+          / '-----' \"
+            }
+        }
+    }
+}
+
+/// Wraps a message in a speech bubble delivered by an ASCII-art character.
+pub fn render(message: &str, character: Character) -> String {
+    let lines = text::wrap(message, BUBBLE_WIDTH);
+    let width = lines.iter().map(|l| text::width(l)).max().unwrap_or(0);
+
+    let mut out = format!(" {}\n", "_".repeat(width + 2));
+    for (i, line) in lines.iter().enumerate() {
+        let (open, close) = match (lines.len(), i) {
+            (1, _) => ('<', '>'),
+            (_, 0) => ('/', '\\'),
+            (n, i) if i == n - 1 => ('\\', '/'),
+            _ => ('|', '|'),
+        };
+        let padding = width - text::width(line);
+        out.push_str(&format!(
+            "{} {}{} {}\n",
+            open,
+            line,
+            " ".repeat(padding),
+            close
+        ));
+    }
+    out.push_str(&format!(" {}\n", "-".repeat(width + 2)));
+    out.push_str(character.figure());
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_single_line_bubble() {
+        let out = render("moo", Character::Cow);
+        assert!(out.starts_with(" _____\n< moo >\n -----\n"));
+        assert!(out.contains("(oo)"));
+    }
+
+    #[test]
+    fn t_multi_line_bubble() {
+        let message = "this message is long enough that it needs to be wrapped across lines";
+        let out = render(message, Character::Ferris);
+        let bubble: Vec<&str> = out.lines().skip(1).take(2).collect();
+        assert!(bubble[0].starts_with("/ ") && bubble[0].ends_with(" \\"));
+        assert!(bubble[1].starts_with("\\ ") && bubble[1].ends_with(" /"));
+    }
+
+    #[test]
+    fn t_wrap_breaks_long_words() {
+        let lines = text::wrap(&"x".repeat(45), 40);
+        assert_eq!(lines, vec!["x".repeat(40), "x".repeat(5)]);
+        assert_eq!(text::wrap("修复了 错误", 4), vec!["修复", "了", "错误"]);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::conventional;
+use serde::Serialize;
+
+/// Type given to messages that don't name one in a Conventional Commits header.
+const DEFAULT_TYPE: &str = "chore";
+
+/// The answers commitizen's conventional commits questions collect, with the whole message
+/// too for adapters that only commit it.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Answers<'a> {
+    pub prefix: &'a str,
+    pub scope: &'a str,
+    pub subject: &'a str,
+    pub body: &'a str,
+    pub is_breaking_change: bool,
+    pub footer: &'a str,
+    pub message: &'a str,
+}
+
+/// Splits a message into commitizen's answers, reading a Conventional Commits header if it
+/// has one.
+pub fn answers(message: &str) -> Answers<'_> {
+    let (first, body) = message.split_once('\n').unwrap_or((message, ""));
+    let body = body.trim();
+    let answers = Answers {
+        prefix: DEFAULT_TYPE,
+        scope: "",
+        subject: first.trim(),
+        body,
+        is_breaking_change: false,
+        footer: "",
+        message,
+    };
+    let header = conventional::parse(first.trim(), |c| c.is_ascii_alphabetic())
+        .filter(|header| !header.subject.is_empty());
+    match header {
+        Some(header) => Answers {
+            prefix: header.kind,
+            scope: header.scope.unwrap_or_default(),
+            subject: header.subject,
+            is_breaking_change: header.breaking,
+            ..answers
+        },
+        None => answers,
+    }
+}
+
+/// The answers for `message` as one line of JSON.
+pub fn render(message: &str) -> String {
+    // Serializing plain strings can't fail
+    serde_json::to_string(&answers(message)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_commitizen_answers() {
+        let plain = answers("fixed the thing\n\nit was DNS");
+        assert_eq!(
+            (plain.prefix, plain.subject, plain.body),
+            ("chore", "fixed the thing", "it was DNS")
+        );
+        let conventional = answers("feat(parser)!: drop XML");
+        assert_eq!(
+            (
+                conventional.prefix,
+                conventional.scope,
+                conventional.subject,
+                conventional.is_breaking_change
+            ),
+            ("feat", "parser", "drop XML", true)
+        );
+        assert_eq!(
+            render("fix: it"),
+            r#"{"prefix":"fix","scope":"","subject":"it","body":"","is_breaking_change":false,"footer":"","message":"fix: it"}"#
+        );
+    }
+}
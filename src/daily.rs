@@ -0,0 +1,75 @@
+use crate::error::{ErrorKind, WithKind};
+use crate::paths;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::debug;
+
+/// Who shares the message of the day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DailyScope {
+    /// Everyone sees the same message
+    #[default]
+    Global,
+    /// Everyone working in a repository with the same directory name sees the same message
+    Repo,
+}
+
+/// 64-bit FNV-1a, used instead of `DefaultHasher` so that seeds stay the same
+/// across Rust releases and platforms.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Derives the seed for a UTC day number, optionally mixed with a repository name.
+fn seed_for(day: u64, repo: Option<&str>) -> u64 {
+    let key = match repo {
+        Some(repo) => format!("{}:{}", day, repo),
+        None => day.to_string(),
+    };
+    fnv1a(key.as_bytes())
+}
+
+/// Returns a seed that stays the same for the whole (UTC) day.
+pub fn seed(scope: DailyScope) -> Result<u64> {
+    let day = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .context("The system clock is set before 1970")?
+        .as_secs()
+        / 86_400;
+    let repo = match scope {
+        DailyScope::Global => None,
+        DailyScope::Repo => {
+            let root = paths::repo_root_of_cwd()
+                .context("--daily=repo needs to run inside a git repository")
+                .kind(ErrorKind::Git)?;
+            Some(
+                root.file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+            )
+        }
+    };
+    debug!("Daily seed for day {} and repo {:?}", day, repo);
+    Ok(seed_for(day, repo.as_deref()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_daily_seed_is_stable() {
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(seed_for(20000, None), seed_for(20000, None));
+        assert_ne!(seed_for(20000, None), seed_for(20001, None));
+        assert_ne!(
+            seed_for(20000, None),
+            seed_for(20000, Some("whatthecommit"))
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use jiff::tz::TimeZone;
+use jiff::{civil, Timestamp};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A calendar day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    /// The day of the week, counting from 0 for Monday.
+    weekday: u32,
+}
+
+impl From<civil::Date> for Date {
+    fn from(date: civil::Date) -> Self {
+        Date {
+            year: i64::from(date.year()),
+            month: date.month() as u32,
+            day: date.day() as u32,
+            weekday: date.weekday().to_monday_zero_offset() as u32,
+        }
+    }
+}
+
+impl Date {
+    /// The day a Unix timestamp falls on in UTC.
+    pub fn from_unix(secs: u64) -> Self {
+        in_zone(secs, &TimeZone::UTC).0
+    }
+
+    /// Today in local time, by the system clock.
+    pub fn today() -> Self {
+        local(now()).0
+    }
+
+    /// The day of the week, counting from 0 for Monday.
+    pub fn weekday(self) -> u32 {
+        self.weekday
+    }
+
+    /// Whether the day is on or between `from` and `to`, both `(month, day)`. A range
+    /// whose end comes before its start wraps around the new year.
+    pub fn between(self, from: (u32, u32), to: (u32, u32)) -> bool {
+        let at = (self.month, self.day);
+        match from <= to {
+            true => from <= at && at <= to,
+            false => from <= at || at <= to,
+        }
+    }
+}
+
+/// Seconds since the Unix epoch, by the system clock.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// The local day and hour at the Unix timestamp `secs`, in the time zone `TZ` names or
+/// else the system's.
+pub fn local(secs: u64) -> (Date, u32) {
+    in_zone(secs, &TimeZone::system())
+}
+
+/// The day and hour at the Unix timestamp `secs` in `zone`.
+pub fn in_zone(secs: u64, zone: &TimeZone) -> (Date, u32) {
+    let at = i64::try_from(secs)
+        .ok()
+        .and_then(|secs| Timestamp::from_second(secs).ok())
+        .unwrap_or(Timestamp::MAX)
+        .to_zoned(zone.clone());
+    (Date::from(at.date()), at.hour() as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_local_time_zone() {
+        let date = Date::from_unix(1_709_210_096);
+        assert_eq!((date.year, date.month, date.day), (2024, 2, 29));
+        assert_eq!(date.weekday(), 3);
+        assert!(date.between((2, 1), (3, 1)));
+        assert!(date.between((12, 1), (3, 1)));
+        assert!(!date.between((3, 1), (12, 1)));
+
+        // Daylight saving time follows the zone's rule, even years past any listed change
+        let new_york = TimeZone::posix("EST5EDT,M3.2.0,M11.1.0").unwrap();
+        let summer = |year: i16| {
+            let at = civil::date(year, 7, 1).at(12, 0, 0, 0);
+            at.to_zoned(TimeZone::UTC).unwrap().timestamp().as_second() as u64
+        };
+        for year in [2024, 2061] {
+            let (date, hour) = in_zone(summer(year), &new_york);
+            assert_eq!((date.month, date.day, hour), (7, 1, 8), "{}", year);
+        }
+        let winter = summer(2061) - 200 * 86_400;
+        assert_eq!(in_zone(winter, &new_york).1, 7);
+    }
+}
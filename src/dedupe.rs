@@ -0,0 +1,228 @@
+use crate::corpus;
+use crate::diagnostic::Diagnostic;
+use crate::sections;
+use anyhow::{Context, Result};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Lowercases a template and collapses its whitespace, so templates differing only in
+/// those count as the same.
+pub fn normalize(template: &str) -> Vec<char> {
+    let words: Vec<&str> = template.split_whitespace().collect();
+    words.join(" ").to_lowercase().chars().collect()
+}
+
+/// The edit distance between `a` and `b`, if it is at most `max`. Only the band of cells
+/// within `max` of the diagonal can be that close, so only those are computed.
+fn distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    let far = max + 1;
+    let mut previous: Vec<usize> = (0..=b.len()).map(|j| j.min(far)).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![far; b.len() + 1];
+        row[0] = i.min(far);
+        for j in i.saturating_sub(max).max(1)..=(i + max).min(b.len()) {
+            let substitution = previous[j - 1] + (a[i - 1] != b[j - 1]) as usize;
+            row[j] = substitution
+                .min(previous[j] + 1)
+                .min(row[j - 1] + 1)
+                .min(far);
+        }
+        if row.iter().all(|&d| d > max) {
+            return None;
+        }
+        previous = row;
+    }
+    Some(previous[b.len()]).filter(|&d| d <= max)
+}
+
+/// Whether normalized templates `a` and `b` are near-duplicates: at most `max_distance`
+/// edits apart, and at most one edit per ten characters, so short templates aren't mistaken
+/// for each other.
+pub fn near(a: &[char], b: &[char], max_distance: usize) -> bool {
+    let max = max_distance.min(a.len().min(b.len()) / 10);
+    max > 0 && distance(a, b, max).is_some()
+}
+
+/// A template found again earlier in the pool.
+#[derive(Debug, PartialEq)]
+struct Duplicate {
+    /// The pack and zero-based line of the duplicate.
+    pack: usize,
+    line: usize,
+    /// The pack and zero-based line of the template it repeats.
+    of: (usize, usize),
+    /// Whether it only nearly repeats it.
+    near: bool,
+}
+
+/// Finds the templates of `packs`, taken in order as one pool, that repeat an earlier
+/// one. Templates repeat exactly when they match once whitespace and case are set aside,
+/// and nearly when they are `near` each other.
+fn find(packs: &[(PathBuf, String)], max_distance: usize) -> Vec<Duplicate> {
+    let mut kept: Vec<(usize, usize, Vec<char>)> = Vec::new();
+    let mut exact: HashMap<Vec<char>, (usize, usize)> = HashMap::new();
+    let mut found = Vec::new();
+    for (pack, (_, text)) in packs.iter().enumerate() {
+        for (line, template) in sections::templates(text) {
+            let normalized = normalize(template);
+            if let Some(&of) = exact.get(&normalized) {
+                found.push(Duplicate {
+                    pack,
+                    line,
+                    of,
+                    near: false,
+                });
+                continue;
+            }
+            let similar = kept
+                .iter()
+                .find(|(_, _, other)| near(&normalized, other, max_distance));
+            if let Some(&(of_pack, of_line, _)) = similar {
+                found.push(Duplicate {
+                    pack,
+                    line,
+                    of: (of_pack, of_line),
+                    near: true,
+                });
+                continue;
+            }
+            exact.insert(normalized.clone(), (pack, line));
+            kept.push((pack, line, normalized));
+        }
+    }
+    found
+}
+
+/// `text` without its zero-based lines in `lines`, keeping every other line exactly as it
+/// was, line endings included.
+fn without_lines(text: &str, lines: &BTreeSet<usize>) -> String {
+    text.split_inclusive('\n')
+        .enumerate()
+        .filter(|(number, _)| !lines.contains(number))
+        .map(|(_, line)| line)
+        .collect()
+}
+
+/// Looks for duplicate templates across the templates files at `paths`, or in the loaded
+/// templates if none are given. Without `fix`, prints every duplicate and fails if there
+/// are any; with it, rewrites the files without them, keeping the first of each.
+pub fn run(
+    paths: &[PathBuf],
+    loaded: (&Path, &[String]),
+    max_distance: usize,
+    fix: bool,
+) -> Result<()> {
+    if fix && paths.is_empty() {
+        anyhow::bail!("The built-in templates can't be rewritten; give the files to fix");
+    }
+    let packs = corpus::packs(paths, loaded)?;
+    let duplicates = find(&packs, max_distance);
+    if fix {
+        for (pack, (path, text)) in packs.iter().enumerate() {
+            let lines: BTreeSet<usize> = duplicates
+                .iter()
+                .filter(|d| d.pack == pack)
+                .map(|d| d.line)
+                .collect();
+            if lines.is_empty() {
+                continue;
+            }
+            fs::write(path, without_lines(text, &lines))
+                .with_context(|| format!("Failed to write templates file: {:?}", path))?;
+            let noun = if lines.len() == 1 {
+                "duplicate"
+            } else {
+                "duplicates"
+            };
+            println!("{}: removed {} {}", path.display(), lines.len(), noun);
+        }
+        return Ok(());
+    }
+
+    let lines: Vec<Vec<&str>> = packs
+        .iter()
+        .map(|(_, text)| text.lines().collect())
+        .collect();
+    for duplicate in &duplicates {
+        let (of_pack, of_line) = duplicate.of;
+        let original = format!("{}:{}", packs[of_pack].0.display(), of_line + 1);
+        let problem = match duplicate.near {
+            false => format!("duplicates {}", original),
+            true => format!(
+                "nearly duplicates {}, {:?}",
+                original, lines[of_pack][of_line]
+            ),
+        };
+        let text = lines[duplicate.pack][duplicate.line];
+        let path = &packs[duplicate.pack].0;
+        eprintln!(
+            "{}",
+            Diagnostic::new(path, duplicate.line + 1, text, 0, problem)
+        );
+    }
+    if !duplicates.is_empty() {
+        let noun = if duplicates.len() == 1 {
+            "duplicate template"
+        } else {
+            "duplicate templates"
+        };
+        anyhow::bail!("{} {}; --fix removes them", duplicates.len(), noun);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_find_duplicates() {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert_eq!(distance(&chars("kitten"), &chars("sitting"), 3), Some(3));
+        assert_eq!(distance(&chars("kitten"), &chars("sitting"), 2), None);
+        assert_eq!(distance(&chars("abc"), &chars("abc"), 0), Some(0));
+
+        let packs = vec![
+            (
+                PathBuf::from("a.txt"),
+                "Fixed the build again\nfixed the build AGAIN \nfix\n".to_string(),
+            ),
+            (
+                PathBuf::from("b.txt"),
+                "[friday]\nfixed  THE build again\r\nfox\nFixed the builds again\nXNAMEX broke it"
+                    .to_string(),
+            ),
+        ];
+        let found = find(&packs, 2);
+        assert_eq!(
+            found,
+            [
+                Duplicate {
+                    pack: 0,
+                    line: 1,
+                    of: (0, 0),
+                    near: false,
+                },
+                Duplicate {
+                    pack: 1,
+                    line: 1,
+                    of: (0, 0),
+                    near: false,
+                },
+                Duplicate {
+                    pack: 1,
+                    line: 3,
+                    of: (0, 0),
+                    near: true,
+                },
+            ]
+        );
+
+        let fixed = without_lines(&packs[1].1, &BTreeSet::from([1, 3]));
+        assert_eq!(fixed, "[friday]\nfox\nXNAMEX broke it");
+    }
+}
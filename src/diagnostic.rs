@@ -0,0 +1,141 @@
+use crate::text;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Longest line, in characters, a names or templates file may have.
+pub const MAX_LINE_LENGTH: usize = 1000;
+
+/// Columns of a line shown on either side of the problem, so that long lines stay readable.
+const CONTEXT: usize = 40;
+
+/// A problem at a line and column of a corpus file, shown with the line and a caret under
+/// the column, like a compiler error:
+///
+/// ```text
+/// my-commits.txt:3:16: contains a control character (U+0007)
+///   |
+/// 3 | Fixed the bell � again
+///   |                ^
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    /// One-based line number.
+    pub line: usize,
+    /// One-based column, in characters.
+    pub column: usize,
+    /// The whole line, for the snippet.
+    pub text: String,
+    pub problem: String,
+}
+
+impl Diagnostic {
+    /// A problem at byte `offset` of `text`, the line numbered `line`.
+    pub fn new(path: &Path, line: usize, text: &str, offset: usize, problem: String) -> Self {
+        Diagnostic {
+            path: path.to_path_buf(),
+            line,
+            column: text[..offset].chars().count() + 1,
+            text: text.to_string(),
+            problem,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chars: Vec<char> = self
+            .text
+            .chars()
+            .map(|c| if c.is_control() { '�' } else { c })
+            .collect();
+        let start = self.column.saturating_sub(CONTEXT + 1);
+        let end = chars.len().min(self.column + CONTEXT);
+        let before: String = chars[start..(self.column - 1).min(chars.len())]
+            .iter()
+            .collect();
+        let shown: String = chars[start..end].iter().collect();
+        let (lead, trail) = (
+            if start > 0 { "…" } else { "" },
+            if end < chars.len() { "…" } else { "" },
+        );
+        let gutter = " ".repeat(self.line.to_string().len());
+        write!(
+            f,
+            "{}:{}:{}: {}\n{} |\n{} | {}{}{}\n{} | {}^",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.problem,
+            gutter,
+            self.line,
+            lead,
+            shown,
+            trail,
+            gutter,
+            " ".repeat(text::width(lead) + text::width(&before)),
+        )
+    }
+}
+
+/// Finds the first problem on a line of a names or templates file: a control character
+/// other than tab, or more than `MAX_LINE_LENGTH` characters.
+///
+/// # Returns
+/// The byte offset of the problem and what it is.
+pub fn check_line(line: &str) -> Option<(usize, String)> {
+    if let Some((at, c)) = line
+        .char_indices()
+        .find(|&(_, c)| c.is_control() && c != '\t')
+    {
+        return Some((
+            at,
+            format!("contains a control character (U+{:04X})", c as u32),
+        ));
+    }
+    let (at, _) = line.char_indices().nth(MAX_LINE_LENGTH)?;
+    Some((at, format!("is longer than {} characters", MAX_LINE_LENGTH)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_check_line() {
+        assert_eq!(check_line("fine\twith a tab"), None);
+        assert_eq!(
+            check_line("héllo\x07"),
+            Some((6, "contains a control character (U+0007)".to_string()))
+        );
+        let long = "é".repeat(MAX_LINE_LENGTH + 5);
+        assert_eq!(
+            check_line(&long).map(|(at, _)| at),
+            Some(2 * MAX_LINE_LENGTH)
+        );
+        assert_eq!(check_line(&long[..2 * MAX_LINE_LENGTH]), None);
+    }
+
+    #[test]
+    fn t_render_snippet() {
+        let text = "Fixed the bell \x07 again";
+        let (at, problem) = check_line(text).unwrap();
+        let diagnostic = Diagnostic::new(Path::new("my-commits.txt"), 3, text, at, problem);
+        assert_eq!(
+            diagnostic.to_string(),
+            "my-commits.txt:3:16: contains a control character (U+0007)\n  |\n3 | Fixed the bell � again\n  |                ^"
+        );
+
+        // Long lines are cut down to the part around the problem
+        let text = format!("{}修{}", "a".repeat(100), "b".repeat(100));
+        let diagnostic = Diagnostic::new(Path::new("x"), 12, &text, 100, "bad".to_string());
+        let snippet = diagnostic.to_string();
+        let lines: Vec<&str> = snippet.lines().collect();
+        assert_eq!(lines[0], "x:12:101: bad");
+        assert_eq!(
+            lines[2],
+            format!("12 | …{}修{}…", "a".repeat(40), "b".repeat(40))
+        );
+        assert_eq!(lines[3], format!("   | {}^", " ".repeat(41)));
+    }
+}
@@ -0,0 +1,245 @@
+//! Stand-ins for the modules left out of builds without their cargo feature. Each keeps
+//! the interface of the module it replaces and fails with [`WtcError::Disabled`].
+
+#[cfg(not(feature = "git"))]
+pub mod git {
+    use crate::error::WtcError;
+    use anyhow::Result;
+
+    fn disabled<T>() -> Result<T> {
+        Err(WtcError::Disabled {
+            what: "git",
+            feature: "git",
+        }
+        .into())
+    }
+
+    pub fn commit(_message: &str) -> Result<()> {
+        disabled()
+    }
+
+    pub fn current_branch() -> Result<Option<String>> {
+        disabled()
+    }
+
+    pub fn create_branch(_name: &str) -> Result<()> {
+        disabled()
+    }
+
+    pub fn short_head() -> Result<String> {
+        disabled()
+    }
+
+    pub fn tracked_files() -> Result<Vec<String>> {
+        disabled()
+    }
+
+    pub fn log_subjects(_limit: usize) -> Result<Vec<String>> {
+        disabled()
+    }
+
+    pub fn staged_stat() -> Result<String> {
+        disabled()
+    }
+
+    pub fn authors() -> Result<Vec<(String, usize)>> {
+        disabled()
+    }
+
+    pub fn identity() -> Result<String> {
+        disabled()
+    }
+}
+
+#[cfg(not(feature = "notify"))]
+pub mod notify {
+    use crate::error::WtcError;
+    use anyhow::Result;
+
+    pub fn committed(_hash: &str, _message: &str) -> Result<()> {
+        Err(WtcError::Disabled {
+            what: "desktop notifications",
+            feature: "notify",
+        }
+        .into())
+    }
+}
+
+#[cfg(not(feature = "network"))]
+pub mod team {
+    use crate::error::WtcError;
+    use anyhow::Result;
+    use std::path::PathBuf;
+
+    fn disabled() -> anyhow::Error {
+        WtcError::Disabled {
+            what: "shared team corpora",
+            feature: "network",
+        }
+        .into()
+    }
+
+    pub fn corpus(_url: &str, _settings: &crate::http::Settings, _locked: bool) -> Result<PathBuf> {
+        Err(disabled())
+    }
+
+    pub fn refresh(_url: &str, _settings: &crate::http::Settings) -> Result<()> {
+        Err(disabled())
+    }
+}
+
+#[cfg(not(feature = "network"))]
+pub mod install {
+    use crate::error::WtcError;
+    use anyhow::Result;
+
+    pub fn run(
+        _source: &str,
+        _signature: Option<&str>,
+        _name: Option<&str>,
+        _force: bool,
+        _trusted: &[String],
+        _settings: &crate::http::Settings,
+        _locked: bool,
+    ) -> Result<()> {
+        Err(WtcError::Disabled {
+            what: "installing signed packs",
+            feature: "network",
+        }
+        .into())
+    }
+}
+
+#[cfg(not(feature = "grpc"))]
+pub mod grpc {
+    use crate::error::WtcError;
+    use crate::rpc::Methods;
+    use anyhow::Result;
+    use std::net::SocketAddr;
+
+    pub fn serve<M: Methods>(_methods: &mut M, _addr: SocketAddr) -> Result<()> {
+        Err(WtcError::Disabled {
+            what: "serving gRPC",
+            feature: "grpc",
+        }
+        .into())
+    }
+}
+
+#[cfg(not(feature = "network"))]
+pub mod keychain {
+    use crate::error::WtcError;
+    use anyhow::Result;
+
+    fn disabled<T>() -> Result<T> {
+        Err(WtcError::Disabled {
+            what: "keeping tokens in the keychain",
+            feature: "network",
+        }
+        .into())
+    }
+
+    pub fn login(_name: &str) -> Result<()> {
+        disabled()
+    }
+
+    pub fn logout(_name: &str) -> Result<()> {
+        disabled()
+    }
+}
+
+#[cfg(not(feature = "regex"))]
+pub mod regex {
+    use crate::error::WtcError;
+
+    /// A regular expression, which can't be compiled without the `regex` feature.
+    #[derive(Debug)]
+    pub enum Regex {}
+
+    impl Regex {
+        pub fn new(_pattern: &str) -> Result<Self, WtcError> {
+            Err(WtcError::Disabled {
+                what: "regular expressions",
+                feature: "regex",
+            })
+        }
+
+        pub fn is_match(&self, _text: &str) -> bool {
+            match *self {}
+        }
+
+        pub fn as_str(&self) -> &str {
+            match *self {}
+        }
+    }
+}
+
+#[cfg(not(feature = "self-update"))]
+pub mod self_update {
+    use crate::error::WtcError;
+    use anyhow::Result;
+
+    pub fn run(_check: bool, _force: bool, _settings: &crate::http::Settings) -> Result<()> {
+        Err(WtcError::Disabled {
+            what: "self-update",
+            feature: "self-update",
+        }
+        .into())
+    }
+}
+
+#[cfg(not(feature = "network"))]
+pub mod webhook {
+    use crate::error::WtcError;
+    use anyhow::Result;
+
+    pub fn post(_url: &str, _message: &str, _settings: &crate::http::Settings) -> Result<()> {
+        Err(WtcError::Disabled {
+            what: "webhooks",
+            feature: "network",
+        }
+        .into())
+    }
+}
+
+#[cfg(not(feature = "lua"))]
+pub mod script {
+    use crate::error::WtcError;
+    use anyhow::Result;
+    use std::path::Path;
+
+    /// A Lua script, which can't exist without Lua.
+    pub enum Script {}
+
+    impl Script {
+        pub fn load(_path: &Path) -> Result<Self> {
+            Err(WtcError::Disabled {
+                what: "Lua scripts",
+                feature: "lua",
+            }
+            .into())
+        }
+
+        pub fn apply(
+            &self,
+            _message: &str,
+            _name: &str,
+            _template: &str,
+        ) -> Result<Option<String>> {
+            match *self {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(not(feature = "lua"))]
+    #[test]
+    fn t_disabled_script() {
+        let err = super::script::Script::load("x.lua".as_ref()).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Can't use Lua scripts: this build was compiled without the `lua` feature"
+        );
+    }
+}
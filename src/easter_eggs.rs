@@ -0,0 +1,91 @@
+use crate::date::Date;
+use serde::Deserialize;
+use tracing::debug;
+
+/// The built-in table of easter eggs.
+const EGGS: &str = include_str!("easter_eggs.toml");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+/// Themed templates and a banner for a special date or time.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Egg {
+    pub name: String,
+    month: Option<u32>,
+    day: Option<u32>,
+    weekday: Option<Weekday>,
+    hour: Option<u32>,
+    /// Shown above the messages in a terminal
+    pub banner: Option<String>,
+    /// Mixed into the built-in corpus
+    #[serde(default)]
+    pub templates: Vec<String>,
+}
+
+impl Egg {
+    /// Whether every part of the date and time the egg gives matches `date` and `hour`.
+    fn hatches(&self, date: Date, hour: u32) -> bool {
+        self.month.is_none_or(|month| month == date.month)
+            && self.day.is_none_or(|day| day == date.day)
+            && self
+                .weekday
+                .is_none_or(|weekday| weekday as u32 == date.weekday())
+            && self.hour.is_none_or(|h| h == hour)
+    }
+}
+
+#[derive(Deserialize)]
+struct Table {
+    egg: Vec<Egg>,
+}
+
+/// Every egg in the table.
+fn all() -> Vec<Egg> {
+    toml::from_str::<Table>(EGGS)
+        .expect("the built-in easter eggs are valid")
+        .egg
+}
+
+/// The eggs that hatch on `date` at `hour`, local time.
+pub fn hatched(date: Date, hour: u32) -> Vec<Egg> {
+    all()
+        .into_iter()
+        .filter(|egg| egg.hatches(date, hour))
+        .inspect(|egg| debug!("Easter egg: {}", egg.name))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lint::assert_lints_clean;
+
+    #[test]
+    fn t_easter_eggs() {
+        assert_lints_clean("easter eggs", all().iter().flat_map(|egg| &egg.templates));
+        let names = |date, hour| -> Vec<String> {
+            hatched(date, hour)
+                .into_iter()
+                .map(|egg| egg.name)
+                .collect()
+        };
+        // 2023-04-01 was a Saturday, and 2024-09-13 a Friday
+        let april_first = Date::from_unix(1_680_307_200);
+        assert_eq!(names(april_first, 12), ["april-fools"]);
+        assert_eq!(names(april_first, 3), ["april-fools", "3am"]);
+        let friday = Date::from_unix(1_726_185_600);
+        assert_eq!(names(friday, 15), ["friday-the-13th"]);
+        assert!(names(Date::from_unix(1_726_272_000), 15).is_empty());
+    }
+}
@@ -0,0 +1,99 @@
+use anyhow::{Context, Result};
+use std::env;
+use std::fs;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const INSTRUCTIONS: &str = "
+# Edit the commit message above. Lines starting with '#' are ignored,
+# and an empty message aborts.
+";
+
+/// The editor to use, following the same variables as git: `VISUAL`, then `EDITOR`, then `vi`.
+fn editor() -> String {
+    env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// Drops comment lines and surrounding blank lines from the edited file.
+fn strip_comments(content: &str) -> String {
+    content
+        .lines()
+        .filter(|l| !l.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Opens `message` in `editor` (a command line, possibly with arguments) and returns the
+/// edited text. The temporary file is removed afterwards.
+fn edit_with(editor: &str, message: &str) -> Result<String> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    let path = env::temp_dir().join(format!(
+        "whatthecommit-{}-{}.COMMIT_EDITMSG",
+        std::process::id(),
+        nanos
+    ));
+    fs::write(&path, format!("{}\n{}", message, INSTRUCTIONS))
+        .with_context(|| format!("Failed to write temporary file: {:?}", path))?;
+
+    let result = (|| {
+        let mut words = shell_words::split(editor)
+            .with_context(|| format!("Invalid editor command `{}`", editor))?;
+        if words.is_empty() {
+            anyhow::bail!("Empty editor command");
+        }
+        let program = words.remove(0);
+        let status = Command::new(&program)
+            .args(words)
+            .arg(&path)
+            .status()
+            .with_context(|| format!("Failed to run editor `{}`", program))?;
+        if !status.success() {
+            anyhow::bail!("Editor `{}` exited with {}", program, status);
+        }
+        fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read temporary file: {:?}", path))
+    })();
+    let _ = fs::remove_file(&path);
+
+    let edited = strip_comments(&result?);
+    if edited.is_empty() {
+        anyhow::bail!("Aborting because the edited message is empty");
+    }
+    Ok(edited)
+}
+
+/// Lets the user edit a message in their editor, like `git commit` does.
+pub fn edit(message: &str) -> Result<String> {
+    edit_with(&editor(), message)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_strip_comments() {
+        assert_eq!(
+            strip_comments("Fix it\n\nfor real\n# note\n#another\n\n"),
+            "Fix it\n\nfor real"
+        );
+        assert_eq!(strip_comments(INSTRUCTIONS), "");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn t_edit_with_editor_command() {
+        assert_eq!(edit_with("true", "keep me").unwrap(), "keep me");
+        let err = edit_with("sh -c ': > \"$0\"'", "gone").unwrap_err();
+        assert!(err.to_string().contains("empty"));
+    }
+}
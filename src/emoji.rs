@@ -0,0 +1,126 @@
+/// Shortcodes for the emoji commit messages use most, standing in for a line that was
+/// nothing but emoji, as GitHub and gitmoji write them.
+const SHORTCODES: [(char, &str); 24] = [
+    ('🔥', "fire"),
+    ('🐛', "bug"),
+    ('✨', "sparkles"),
+    ('🚀', "rocket"),
+    ('💩', "poop"),
+    ('🎉', "tada"),
+    ('👍', "+1"),
+    ('😂', "joy"),
+    ('🙈', "see_no_evil"),
+    ('🤷', "shrug"),
+    ('💥', "boom"),
+    ('✅', "white_check_mark"),
+    ('❌', "x"),
+    ('⚡', "zap"),
+    ('🔧', "wrench"),
+    ('📝', "memo"),
+    ('🚧', "construction"),
+    ('💀', "skull"),
+    ('🤦', "facepalm"),
+    ('❤', "heart"),
+    ('😭', "sob"),
+    ('🙏', "pray"),
+    ('👀', "eyes"),
+    ('🍺', "beer"),
+];
+
+/// What a line of nothing but emoji none of `SHORTCODES` knows becomes.
+const UNKNOWN: &str = "(emoji)";
+
+/// Whether a character is (part of) an emoji.
+pub fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF // Mahjong through Symbols and Pictographs Extended-A
+        | 0x2600..=0x27BF // Miscellaneous Symbols and Dingbats
+        | 0x2B00..=0x2BFF // Arrows, stars and other symbols
+        | 0xFE0F          // Variation selector for emoji presentation
+        | 0x200D          // Zero-width joiner
+    )
+}
+
+/// The ASCII a piece of typography stands for, where it has an obvious one.
+fn ascii(c: char) -> Option<&'static str> {
+    Some(match c {
+        '‘' | '’' | '‚' | '′' => "'",
+        '“' | '”' | '„' | '″' => "\"",
+        '–' | '—' | '‐' | '‑' | '−' => "-",
+        '…' => "...",
+        '•' | '·' => "*",
+        '→' => "->",
+        '←' => "<-",
+        '×' => "x",
+        _ => return None,
+    })
+}
+
+/// Removes the emoji and other symbols outside ASCII from a line, keeping letters and
+/// digits of any script. Typography with an ASCII look-alike is swapped for it.
+fn strip_line(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    for c in line.chars() {
+        match ascii(c) {
+            Some(replacement) => stripped.push_str(replacement),
+            None if c.is_ascii() || c.is_alphanumeric() => stripped.push(c),
+            None if c.is_whitespace() => stripped.push(' '),
+            None => {}
+        }
+    }
+    stripped
+}
+
+/// The shortcodes of the emoji on `line`, or `UNKNOWN` if it has none of them.
+fn shortcodes(line: &str) -> String {
+    let codes: Vec<String> = (line.chars())
+        .filter_map(|c| SHORTCODES.iter().find(|(emoji, _)| *emoji == c))
+        .map(|(_, code)| format!(":{}:", code))
+        .collect();
+    match codes.is_empty() {
+        true => UNKNOWN.to_string(),
+        false => codes.join(" "),
+    }
+}
+
+/// Strips emoji and other symbols outside ASCII from `message`, for `--no-emoji`. A line
+/// left with nothing but spaces and punctuation, having had emoji, gets their shortcodes
+/// instead, so "🔥🔥🔥" becomes ":fire: :fire: :fire:" rather than nothing. Lines that
+/// change keep their indentation and have the gaps left behind closed up.
+pub fn strip(message: &str) -> String {
+    let lines: Vec<String> = (message.split('\n'))
+        .map(|line| {
+            let stripped = strip_line(line);
+            if stripped == line {
+                return stripped;
+            }
+            if line.chars().any(is_emoji) && !stripped.chars().any(char::is_alphanumeric) {
+                return shortcodes(line);
+            }
+            let indent = &line[..line.len() - line.trim_start().len()];
+            let words: Vec<&str> = stripped.split_whitespace().collect();
+            format!("{}{}", indent, words.join(" "))
+        })
+        .collect();
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_strip_emoji() {
+        assert_eq!(strip("Fix the build 🔥 again"), "Fix the build again");
+        assert_eq!(strip("🚀 Ship it!"), "Ship it!");
+        assert_eq!(strip("🔥🔥🔥"), ":fire: :fire: :fire:");
+        assert_eq!(strip("🦀 !"), "(emoji)");
+        assert_eq!(
+            strip("Don’t touch… it — really"),
+            "Don't touch... it - really"
+        );
+        assert_eq!(strip("Café ☕ for 修复\n\n  👍🏽"), "Café for 修复\n\n:+1:");
+        assert_eq!(strip("as  is\n\n  indented"), "as  is\n\n  indented");
+        assert_eq!(strip("\u{feff} - Temporary commit."), "- Temporary commit.");
+    }
+}
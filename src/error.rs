@@ -0,0 +1,263 @@
+use crate::diagnostic::Diagnostic;
+use crate::output::Output;
+use clap::ValueEnum;
+use serde::Deserialize;
+use serde_json::json;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Broad categories of failure, each mapped to a documented exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Anything not covered by a more specific kind
+    Other,
+    /// A corpus file (names or templates) could not be read or parsed
+    Corpus,
+    /// No candidates were left to pick from, e.g. after filtering
+    EmptyPool,
+    /// A git command failed
+    Git,
+    /// A network request failed
+    Network,
+}
+
+impl ErrorKind {
+    /// Process exit code for this kind of failure.
+    ///
+    /// Exit code 2 is reserved for command-line usage errors reported by clap.
+    pub fn exit_code(self) -> u8 {
+        match self {
+            ErrorKind::Other => 1,
+            ErrorKind::Corpus => 3,
+            ErrorKind::EmptyPool => 4,
+            ErrorKind::Git => 5,
+            ErrorKind::Network => 6,
+        }
+    }
+
+    /// Stable identifier used in machine-readable error output.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorKind::Other => "other",
+            ErrorKind::Corpus => "corpus",
+            ErrorKind::EmptyPool => "empty_pool",
+            ErrorKind::Git => "git",
+            ErrorKind::Network => "network",
+        }
+    }
+}
+
+/// Failures of the generation engine and of the corpus, git and webhook layers it uses.
+///
+/// Each variant maps to an [`ErrorKind`], so callers can match on the failure while the
+/// CLI still exits with the documented code.
+#[derive(Debug, thiserror::Error)]
+pub enum WtcError {
+    /// No corpus file was given and the default corpus is disabled
+    #[error("No {what} file given and the default corpus is disabled")]
+    NoCorpus { what: &'static str },
+    /// A corpus file has no non-blank lines
+    #[error("{what} file is empty or contains only empty lines")]
+    EmptyCorpus { what: &'static str },
+    /// A line of a corpus file can't be used
+    #[error("Line {line} {problem}")]
+    InvalidTemplate { line: usize, problem: &'static str },
+    /// A line of a corpus file has a problem at a known column
+    #[error("{0}")]
+    Diagnostic(Box<Diagnostic>),
+    /// `--template-id` is past the end of the corpus
+    #[error("Template id {id} is out of range (the corpus has {templates} templates)")]
+    TemplateOutOfRange { id: usize, templates: usize },
+    /// Nothing was left to pick from, or nothing picked got past the filters
+    #[error("{0}")]
+    EmptyPool(String),
+    /// A corpus file could not be read
+    #[error("{context}")]
+    IoError {
+        context: String,
+        #[source]
+        source: io::Error,
+    },
+    /// A git command could not be run or failed
+    #[cfg_attr(not(feature = "git"), allow(dead_code))]
+    #[error("{message}")]
+    GitError {
+        message: String,
+        #[source]
+        source: Option<io::Error>,
+    },
+    /// A request to a webhook or shared corpus failed
+    #[cfg_attr(not(feature = "network"), allow(dead_code))]
+    #[error("Request to {url} failed")]
+    NetworkError {
+        url: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// A request was refused because `--offline` is on
+    #[cfg_attr(not(feature = "network"), allow(dead_code))]
+    #[error("Not contacting {url} while offline")]
+    Offline { url: String },
+    /// Content fetched with `--locked` isn't what the lockfile records
+    #[cfg_attr(not(feature = "network"), allow(dead_code))]
+    #[error("{url} {problem} in {lockfile:?}; run without --locked to update the lockfile")]
+    Unlocked {
+        url: String,
+        problem: &'static str,
+        lockfile: PathBuf,
+    },
+    /// Something was asked of a cargo feature this build was compiled without
+    #[error("Can't use {what}: this build was compiled without the `{feature}` feature")]
+    Disabled {
+        what: &'static str,
+        feature: &'static str,
+    },
+}
+
+impl WtcError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            WtcError::NoCorpus { .. }
+            | WtcError::EmptyCorpus { .. }
+            | WtcError::InvalidTemplate { .. }
+            | WtcError::Diagnostic(_)
+            | WtcError::IoError { .. }
+            | WtcError::Unlocked { .. } => ErrorKind::Corpus,
+            WtcError::TemplateOutOfRange { .. } | WtcError::Disabled { .. } => ErrorKind::Other,
+            WtcError::EmptyPool(_) => ErrorKind::EmptyPool,
+            WtcError::GitError { .. } => ErrorKind::Git,
+            WtcError::NetworkError { .. } | WtcError::Offline { .. } => ErrorKind::Network,
+        }
+    }
+}
+
+/// An error tagged with the kind of failure it represents.
+///
+/// Displays as the wrapped error so tagging never changes user-facing messages.
+#[derive(Debug)]
+struct KindError {
+    kind: ErrorKind,
+    error: anyhow::Error,
+}
+
+impl fmt::Display for KindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl std::error::Error for KindError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// Tags errors with an [`ErrorKind`].
+pub trait WithKind<T> {
+    fn kind(self, kind: ErrorKind) -> anyhow::Result<T>;
+}
+
+impl<T, E> WithKind<T> for Result<T, E>
+where
+    E: Into<anyhow::Error>,
+{
+    fn kind(self, kind: ErrorKind) -> anyhow::Result<T> {
+        self.map_err(|e| {
+            KindError {
+                kind,
+                error: e.into(),
+            }
+            .into()
+        })
+    }
+}
+
+/// Returns the kind an error was tagged with or, failing that, the kind of the first
+/// [`WtcError`] behind it, or else [`ErrorKind::Other`].
+pub fn kind_of(error: &anyhow::Error) -> ErrorKind {
+    let chain = || error.chain();
+    chain()
+        .find_map(|e| e.downcast_ref::<KindError>())
+        .map(|e| e.kind)
+        .or_else(|| {
+            chain()
+                .find_map(|e| e.downcast_ref::<WtcError>())
+                .map(WtcError::kind)
+        })
+        .unwrap_or(ErrorKind::Other)
+}
+
+/// How errors are reported on stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorFormat {
+    /// Human-readable message
+    #[default]
+    Text,
+    /// A single-line JSON object
+    Json,
+}
+
+/// Prints an error to stderr in the requested format and returns the matching exit code.
+pub fn report(error: &anyhow::Error, format: ErrorFormat, output: &Output) -> ExitCode {
+    let kind = kind_of(error);
+    match format {
+        ErrorFormat::Text => eprintln!("{} {:#}", output.error_label(), error),
+        ErrorFormat::Json => {
+            let causes: Vec<String> = error.chain().skip(1).map(|e| e.to_string()).collect();
+            let object = json!({
+                "error": {
+                    "kind": kind.as_str(),
+                    "code": kind.exit_code(),
+                    "message": error.to_string(),
+                    "causes": causes,
+                }
+            });
+            eprintln!("{}", object);
+        }
+    }
+    ExitCode::from(kind.exit_code())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn t_kind_survives_added_context() {
+        let result: anyhow::Result<()> = Err(anyhow::anyhow!("file is empty"))
+            .kind(ErrorKind::EmptyPool)
+            .context("Failed to load names");
+        let error = result.unwrap_err();
+        assert_eq!(kind_of(&error), ErrorKind::EmptyPool);
+        assert_eq!(
+            format!("{:#}", error),
+            "Failed to load names: file is empty"
+        );
+    }
+
+    #[test]
+    fn t_typed_errors_keep_their_kind() {
+        let result: anyhow::Result<()> = Err(WtcError::EmptyCorpus { what: "names" }.into());
+        let error = result.context("Failed to load names").unwrap_err();
+        assert_eq!(kind_of(&error), ErrorKind::Corpus);
+        assert!(matches!(
+            error.downcast_ref::<WtcError>(),
+            Some(WtcError::EmptyCorpus { what: "names" })
+        ));
+        assert_eq!(
+            format!("{:#}", error),
+            "Failed to load names: names file is empty or contains only empty lines"
+        );
+    }
+
+    #[test]
+    fn t_untagged_errors_are_other() {
+        let error = anyhow::anyhow!("boom");
+        assert_eq!(kind_of(&error), ErrorKind::Other);
+        assert_eq!(ErrorKind::Other.exit_code(), 1);
+    }
+}
@@ -0,0 +1,74 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::borrow::Cow;
+
+/// Characters with a meaning anywhere in a line of Markdown.
+const MARKDOWN_SPECIAL: &[char] = &['\\', '`', '*', '_', '[', ']', '<', '>', '|', '~'];
+
+/// Where the message will be pasted, so characters that mean something there can be
+/// escaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Escape {
+    /// Leave the message as it is
+    #[default]
+    None,
+    /// Backslashes before backticks, asterisks and the like, and a heading `#`
+    Markdown,
+    /// A single-quoted shell word
+    Shell,
+    /// A quoted JSON string
+    Json,
+}
+
+impl Escape {
+    /// Escapes a finished message for where it will be pasted.
+    pub fn apply(self, message: &str) -> Cow<'_, str> {
+        match self {
+            Escape::None => Cow::Borrowed(message),
+            Escape::Markdown => Cow::Owned(markdown(message)),
+            Escape::Shell => Cow::Owned(format!("'{}'", message.replace('\'', r"'\''"))),
+            // Serializing a string can't fail
+            Escape::Json => Cow::Owned(serde_json::to_string(message).unwrap_or_default()),
+        }
+    }
+}
+
+/// Puts a backslash before every character Markdown would read as formatting, and
+/// before a `#` starting a line, which would make it a heading.
+fn markdown(message: &str) -> String {
+    let mut out = String::with_capacity(message.len() + 8);
+    let mut line_start = true;
+    for c in message.chars() {
+        if MARKDOWN_SPECIAL.contains(&c) || (line_start && c == '#') {
+            out.push('\\');
+        }
+        out.push(c);
+        line_start = c == '\n';
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_escape_for_targets() {
+        let message = "#42: fix *all* the `bugs` in Bob's_code";
+        assert_eq!(Escape::None.apply(message), message);
+        assert_eq!(
+            Escape::Markdown.apply(message),
+            r"\#42: fix \*all\* the \`bugs\` in Bob's\_code"
+        );
+        assert_eq!(
+            Escape::Shell.apply(message),
+            r"'#42: fix *all* the `bugs` in Bob'\''s_code'"
+        );
+        assert_eq!(
+            Escape::Json.apply("say \"hi\"\n\tbye"),
+            r#""say \"hi\"\n\tbye""#
+        );
+        assert_eq!(Escape::Markdown.apply("a # b\n# c"), "a # b\n\\# c");
+    }
+}
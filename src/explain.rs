@@ -0,0 +1,128 @@
+use crate::Generated;
+use std::fmt::Write as _;
+
+/// Where the templates a message can come from were loaded, for saying where one came
+/// from.
+#[derive(Debug)]
+pub struct Explainer {
+    /// The file or stream the templates came from, as shown.
+    source: String,
+    /// The text of that file, when its non-blank lines are the templates, for line numbers.
+    text: Option<String>,
+    /// How many templates came from `source`; any after them are seasonal or easter egg
+    /// templates.
+    loaded: usize,
+    seed: Option<u64>,
+}
+
+/// The one-based number of the line of `text` holding template `index`, blank lines not
+/// counting as templates.
+fn line_number(text: &str, index: usize) -> Option<usize> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .nth(index)
+        .map(|(number, _)| number + 1)
+}
+
+impl Explainer {
+    pub fn new(source: String, text: Option<String>, loaded: usize, seed: Option<u64>) -> Self {
+        Explainer {
+            source,
+            text,
+            loaded,
+            seed,
+        }
+    }
+
+    /// Template `index` and where it came from, like "#3, line 5 of templates.txt".
+    fn locate(&self, index: usize) -> String {
+        if index >= self.loaded {
+            return format!("#{}, a seasonal or easter egg template", index);
+        }
+        match (self.text.as_deref()).and_then(|text| line_number(text, index)) {
+            Some(line) => format!("#{}, line {} of {}", index, line, self.source),
+            None => format!("#{} of {}", index, self.source),
+        }
+    }
+
+    /// Describes where `generated` came from, `template` being the template it was made
+    /// from, as aligned lines for stderr.
+    pub fn render(&self, generated: &Generated, template: &str) -> String {
+        let mut out = format!("template  {}", self.locate(generated.template_index));
+        if let Some((second, _)) = &generated.spliced {
+            let _ = write!(out, ", spliced with {} by --mutate", self.locate(*second));
+        }
+        let _ = write!(
+            out,
+            "\n          {}\nname      {}\n",
+            template, generated.name
+        );
+        let numbers: Vec<String> = (generated.drawn.iter())
+            .map(|(range, number)| format!("{} from {}", number, range))
+            .collect();
+        let _ = match numbers.is_empty() {
+            true => writeln!(out, "numbers   none"),
+            false => writeln!(out, "numbers   {}", numbers.join("; ")),
+        };
+        let _ = match self.seed {
+            Some(seed) => write!(out, "seed      {}", seed),
+            None => write!(out, "seed      none; --rng crypto can't be seeded"),
+        };
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lint::parse_number_range;
+
+    #[test]
+    fn t_explain_provenance() {
+        let text = "Fixed it\n\n  \nFixed XNUM1,5X bugs\n";
+        assert_eq!(line_number(text, 1), Some(4));
+        assert_eq!(line_number(text, 2), None);
+
+        let explainer = Explainer::new(
+            "templates.txt".to_string(),
+            Some(text.to_string()),
+            2,
+            Some(7),
+        );
+        let generated = Generated {
+            message: "Fixed 3 bugs".to_string(),
+            name: "Ann".to_string(),
+            template_index: 1,
+            drawn: vec![(parse_number_range("1,5").unwrap(), 3)],
+            spliced: None,
+        };
+        assert_eq!(
+            explainer.render(&generated, "Fixed XNUM1,5X bugs"),
+            "template  #1, line 4 of templates.txt\n          Fixed XNUM1,5X bugs\n\
+             name      Ann\nnumbers   3 from 1..5\nseed      7"
+        );
+
+        let spliced = Generated {
+            drawn: Vec::new(),
+            spliced: Some((0, "Fixed it, then XNUM1,5X bugs".to_string())),
+            ..generated.clone()
+        };
+        assert!(explainer
+            .render(&spliced, "Fixed it, then XNUM1,5X bugs")
+            .starts_with(
+                "template  #1, line 4 of templates.txt, spliced with #0, line 1 of \
+                 templates.txt by --mutate\n          Fixed it, then XNUM1,5X bugs\n"
+            ));
+
+        let seasonal = Generated {
+            template_index: 2,
+            drawn: Vec::new(),
+            ..generated
+        };
+        let explainer = Explainer::new("stdin".to_string(), None, 2, None);
+        let rendered = explainer.render(&seasonal, "Boo");
+        assert!(rendered.starts_with("template  #2, a seasonal or easter egg template\n"));
+        assert!(rendered.contains("numbers   none\nseed      none"));
+    }
+}
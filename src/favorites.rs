@@ -0,0 +1,117 @@
+use crate::paths;
+use anyhow::{Context, Result};
+use rand::prelude::IndexedRandom;
+use rand::Rng;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Location of the favorites file, one message per line.
+///
+/// The file doubles as a template file, e.g. `-c ~/.local/share/whatthecommit/favorites.txt`.
+pub fn path() -> Result<PathBuf> {
+    paths::data_dir()
+        .map(|d| d.join("favorites.txt"))
+        .context("Could not determine the data directory")
+}
+
+/// Reads the favorites; a missing file means there are none yet.
+pub fn load(path: &Path) -> Result<Vec<String>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(String::from)
+            .collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read favorites file: {:?}", path)),
+    }
+}
+
+fn save(path: &Path, favorites: &[String]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory: {:?}", dir))?;
+    }
+    let mut content = favorites.join("\n");
+    content.push('\n');
+    fs::write(path, content).with_context(|| format!("Failed to write favorites file: {:?}", path))
+}
+
+/// Adds a message to the favorites, returning `false` if it was already there.
+///
+/// Line breaks are folded into spaces so that each favorite stays on one line.
+pub fn add(path: &Path, message: &str) -> Result<bool> {
+    let message = message.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut favorites = load(path)?;
+    if message.is_empty() || favorites.contains(&message) {
+        return Ok(false);
+    }
+    favorites.push(message);
+    save(path, &favorites)?;
+    Ok(true)
+}
+
+/// Stars a message from an interactive mode, describing the outcome for a status line.
+#[cfg(feature = "tui")]
+pub fn star(message: &str) -> String {
+    match path().and_then(|path| add(&path, message)) {
+        Ok(true) => "Added to favorites".to_string(),
+        Ok(false) => "Already a favorite".to_string(),
+        Err(e) => format!("Error: {:#}", e),
+    }
+}
+
+/// Removes the favorite at `index`, returning it.
+pub fn remove(path: &Path, index: usize) -> Result<String> {
+    let mut favorites = load(path)?;
+    if index >= favorites.len() {
+        anyhow::bail!(
+            "Favorite {} does not exist (there are {} favorites)",
+            index,
+            favorites.len()
+        );
+    }
+    let removed = favorites.remove(index);
+    save(path, &favorites)?;
+    Ok(removed)
+}
+
+/// Prints the favorites with their indices.
+pub fn list(path: &Path) -> Result<()> {
+    let favorites = load(path)?;
+    let width = favorites.len().saturating_sub(1).to_string().len();
+    for (i, favorite) in favorites.iter().enumerate() {
+        println!("{:>width$}  {}", i, favorite, width = width);
+    }
+    Ok(())
+}
+
+/// Prints a random favorite.
+pub fn random<R: Rng + ?Sized>(path: &Path, rng: &mut R) -> Result<()> {
+    let favorites = load(path)?;
+    let favorite = favorites
+        .choose(rng)
+        .context("No favorites yet; add one with `fav add`")?;
+    println!("{}", favorite);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_add_and_remove_favorites() {
+        let path = std::env::temp_dir()
+            .join(format!("wtc-favorites-{}", std::process::id()))
+            .join("favorites.txt");
+        assert!(load(&path).unwrap().is_empty());
+        assert!(add(&path, "fix\nit").unwrap());
+        assert!(!add(&path, "fix it").unwrap());
+        assert!(add(&path, "ship it").unwrap());
+        assert_eq!(remove(&path, 0).unwrap(), "fix it");
+        assert_eq!(load(&path).unwrap(), vec!["ship it"]);
+        assert!(remove(&path, 1).is_err());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
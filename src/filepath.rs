@@ -0,0 +1,84 @@
+use rand::seq::IndexedRandom;
+use rand::Rng;
+
+/// Top-level directories a made-up path starts in.
+const ROOTS: [&str; 8] = [
+    "src", "lib", "app", "pkg", "internal", "scripts", "config", "test",
+];
+
+/// Directories nested under the root.
+const DIRECTORIES: [&str; 14] = [
+    "utils",
+    "legacy",
+    "core",
+    "helpers",
+    "old",
+    "tmp",
+    "vendor",
+    "misc",
+    "experimental",
+    "hacks",
+    "common",
+    "v2",
+    "deprecated",
+    "shared",
+];
+
+/// File names, without their extension.
+const STEMS: [&str; 14] = [
+    "do_not_touch",
+    "final_v2",
+    "temp",
+    "index",
+    "helpers",
+    "new_new",
+    "fix",
+    "backup",
+    "main",
+    "copy_of_main",
+    "utils_old",
+    "workaround",
+    "magic",
+    "mod",
+];
+
+const EXTENSIONS: [&str; 9] = ["rs", "py", "js", "ts", "go", "sh", "yaml", "c", "java"];
+
+/// A plausible-looking source path such as `src/utils/legacy/do_not_touch.rs`, one to
+/// three directories deep.
+pub fn generate<R>(rng: &mut R) -> String
+where
+    R: Rng + ?Sized,
+{
+    let mut path = ROOTS.choose(rng).unwrap().to_string();
+    let depth = rng.random_range(0..=2);
+    for directory in DIRECTORIES.choose_multiple(rng, depth) {
+        path.push('/');
+        path.push_str(directory);
+    }
+    path.push('/');
+    path.push_str(STEMS.choose(rng).unwrap());
+    path.push('.');
+    path.push_str(EXTENSIONS.choose(rng).unwrap());
+    path
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_generate_path() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..50 {
+            let path = generate(&mut rng);
+            let parts: Vec<&str> = path.split('/').collect();
+            assert!((2..=4).contains(&parts.len()), "{}", path);
+            assert!(ROOTS.contains(&parts[0]));
+            let (stem, extension) = parts[parts.len() - 1].split_once('.').unwrap();
+            assert!(STEMS.contains(&stem) && EXTENSIONS.contains(&extension));
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use crate::Generated;
+use std::str::FromStr;
+
+/// A field that can be interpolated into an output format string.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Message,
+    Name,
+    Template,
+    TemplateIndex,
+}
+
+impl FromStr for Field {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "message" => Ok(Field::Message),
+            "name" => Ok(Field::Name),
+            "template" => Ok(Field::Template),
+            "template_index" => Ok(Field::TemplateIndex),
+            other => Err(format!(
+                "unknown field `{{{}}}` (expected one of: message, name, template, template_index)",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Field(Field),
+}
+
+/// A parsed `--format` string such as `"{message}\t{name}\t{template_index}"`.
+///
+/// # Syntax
+/// - `{field}` - Replaced with the value of `field`
+/// - `{{` and `}}` - Literal braces
+/// - `\t`, `\n`, `\\` - Tab, newline and backslash escapes
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputFormat {
+    segments: Vec<Segment>,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('t') => literal.push('\t'),
+                    Some('n') => literal.push('\n'),
+                    Some('\\') => literal.push('\\'),
+                    Some(other) => return Err(format!("unknown escape sequence `\\{}`", other)),
+                    None => return Err("format ends with a lone `\\`".to_string()),
+                },
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut field = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => field.push(c),
+                            None => return Err(format!("unclosed field `{{{}`", field)),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Field(field.trim().parse()?));
+                }
+                '}' => return Err("unmatched `}` (use `}}` for a literal brace)".to_string()),
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(OutputFormat { segments })
+    }
+}
+
+impl OutputFormat {
+    /// Renders a generated message, given the template it came from.
+    pub fn render(&self, generated: &Generated, template: &str) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(s) => out.push_str(s),
+                Segment::Field(Field::Message) => out.push_str(&generated.message),
+                Segment::Field(Field::Name) => out.push_str(&generated.name),
+                Segment::Field(Field::Template) => out.push_str(template),
+                // A message spliced by `--mutate` came from two, shown as `3+7`
+                Segment::Field(Field::TemplateIndex) => {
+                    let indices: Vec<String> =
+                        generated.templates().iter().map(usize::to_string).collect();
+                    out.push_str(&indices.join("+"))
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn generated() -> Generated {
+        Generated {
+            message: "Alice broke it".to_string(),
+            name: "Alice".to_string(),
+            template_index: 7,
+            drawn: Vec::new(),
+            spliced: None,
+        }
+    }
+
+    #[test]
+    fn t_format_fields_and_escapes() {
+        let format: OutputFormat = r"{message}\t{name}\t{template_index}".parse().unwrap();
+        assert_eq!(
+            format.render(&generated(), "XNAMEX broke it"),
+            "Alice broke it\tAlice\t7"
+        );
+        let spliced = Generated {
+            spliced: Some((3, "XNAMEX broke it, then fixed it".to_string())),
+            ..generated()
+        };
+        assert!(format.render(&spliced, "").ends_with("\t7+3"));
+    }
+
+    #[test]
+    fn t_format_template_and_literal_braces() {
+        let format: OutputFormat = "{{{template}}} -> {message}".parse().unwrap();
+        assert_eq!(
+            format.render(&generated(), "XNAMEX broke it"),
+            "{XNAMEX broke it} -> Alice broke it"
+        );
+    }
+
+    #[test]
+    fn t_format_rejects_malformed() {
+        assert!("{nope}".parse::<OutputFormat>().is_err());
+        assert!("{message".parse::<OutputFormat>().is_err());
+        assert!("message}".parse::<OutputFormat>().is_err());
+        assert!(r"\q".parse::<OutputFormat>().is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use crate::corpus;
+use crate::error::WtcError;
+use crate::lint::PLACEHOLDERS;
+use crate::sections;
+use anyhow::Result;
+use std::fs;
+use std::path::PathBuf;
+
+/// Whether the byte at `at` of `text` is alphanumeric, so a placeholder can't start right
+/// after it or end right before it.
+fn is_word_byte(text: &str, at: usize) -> bool {
+    text.as_bytes()
+        .get(at)
+        .is_some_and(u8::is_ascii_alphanumeric)
+}
+
+/// `template` with placeholders written in the wrong case, such as `xnamex` or
+/// `XNum1,5:HexX`, written the one way they're recognized.
+fn placeholder_case(template: &str) -> String {
+    let mut fixed = String::with_capacity(template.len());
+    let mut at = 0;
+    while at < template.len() {
+        let rest = &template[at..];
+        let starts_word = !(at > 0 && is_word_byte(template, at - 1));
+        if starts_word && rest.starts_with(['x', 'X']) {
+            let word = PLACEHOLDERS.iter().map(|(p, ..)| *p).find(|p| {
+                rest.get(..p.len())
+                    .is_some_and(|r| r.eq_ignore_ascii_case(p))
+                    && !is_word_byte(template, at + p.len())
+            });
+            if let Some(word) = word {
+                fixed.push_str(word);
+                at += word.len();
+                continue;
+            }
+            let len = rest
+                .bytes()
+                .take_while(|&b| b.is_ascii_alphanumeric() || b",:-".contains(&b))
+                .count();
+            let token = &rest[..len];
+            let is_number = len >= "XNUMX".len()
+                && token[..4].eq_ignore_ascii_case("XNUM")
+                && token.ends_with(['x', 'X']);
+            if is_number {
+                fixed.push_str("XNUM");
+                fixed.push_str(&token[4..len - 1].to_ascii_lowercase());
+                fixed.push('X');
+                at += len;
+                continue;
+            }
+        }
+        let c = rest.chars().next().unwrap_or_default();
+        fixed.push(c);
+        at += c.len_utf8();
+    }
+    fixed
+}
+
+/// The canonical form of the corpus file `text`: every line trimmed, empty lines gone but
+/// for one before each section header, placeholders in the right case, and with `sort`,
+/// the templates of each section sorted, after its comments.
+pub fn format(text: &str, sort: bool) -> String {
+    let sectioned = sections::is_sectioned(text);
+    // Each section's header, if any, its comments and its templates
+    let mut groups: Vec<(Option<&str>, Vec<&str>, Vec<String>)> = vec![(None, vec![], vec![])];
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let group = groups.last_mut().expect("there is always a group");
+        if sectioned && line.starts_with('[') {
+            groups.push((Some(line), vec![], vec![]));
+        } else if sectioned && line.starts_with('#') {
+            group.1.push(line);
+        } else {
+            group.2.push(placeholder_case(line));
+        }
+    }
+
+    let mut formatted = String::new();
+    for (header, comments, mut templates) in groups {
+        if sort {
+            templates.sort_by_cached_key(|t| t.to_lowercase());
+        }
+        if let Some(header) = header {
+            if !formatted.is_empty() {
+                formatted.push('\n');
+            }
+            formatted.push_str(header);
+            formatted.push('\n');
+        }
+        for line in comments.into_iter().map(String::from).chain(templates) {
+            formatted.push_str(&line);
+            formatted.push('\n');
+        }
+    }
+    formatted
+}
+
+/// Formats the corpus files at `paths` in place. With `check`, only lists the ones that
+/// aren't formatted, and fails if there are any.
+pub fn run(paths: &[PathBuf], sort: bool, check: bool) -> Result<()> {
+    let mut unformatted = 0;
+    for path in paths {
+        let bytes = fs::read(path).map_err(|source| WtcError::IoError {
+            context: format!("Failed to read corpus file: {:?}", path),
+            source,
+        })?;
+        let text = corpus::decode(bytes.clone())?;
+        let formatted = format(&text, sort);
+        if formatted.as_bytes() == bytes {
+            continue;
+        }
+        if check {
+            println!("{} is not formatted", path.display());
+            unformatted += 1;
+            continue;
+        }
+        fs::write(path, formatted).map_err(|source| WtcError::IoError {
+            context: format!("Failed to write corpus file: {:?}", path),
+            source,
+        })?;
+        println!("Formatted {}", path.display());
+    }
+    if unformatted > 0 {
+        let noun = if unformatted == 1 { "file" } else { "files" };
+        anyhow::bail!("{} {} not formatted; fmt rewrites them", unformatted, noun);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_format_corpus() {
+        assert_eq!(
+            placeholder_case("xNameX broke xnum1,5:HEXx tests, not xnamexes or XNAMEX"),
+            "XNAMEX broke XNUM1,5:hexX tests, not xnamexes or XNAMEX"
+        );
+        assert_eq!(placeholder_case("Max x relax xnumx"), "Max x relax XNUMX");
+
+        let text = "  zeta xverbx\r\n\n\nalpha  \n";
+        assert_eq!(format(text, false), "zeta XVERBX\nalpha\n");
+        assert_eq!(format(text, true), "alpha\nzeta XVERBX\n");
+
+        let sectioned = "[today]\n b\n# note\na\n\n\n[blockers]\nnone\n";
+        let formatted = format(sectioned, true);
+        assert_eq!(formatted, "[today]\n# note\na\nb\n\n[blockers]\nnone\n");
+        assert_eq!(format(&formatted, true), formatted);
+    }
+}
@@ -0,0 +1,188 @@
+use crate::error::WtcError;
+use anyhow::Result;
+use std::io;
+use std::process::Command;
+
+/// The error for git not starting at all.
+fn not_run(source: io::Error) -> WtcError {
+    WtcError::GitError {
+        message: "Failed to run git".to_string(),
+        source: Some(source),
+    }
+}
+
+/// The error for a git command that ran but failed.
+fn failed(message: String) -> WtcError {
+    WtcError::GitError {
+        message,
+        source: None,
+    }
+}
+
+/// Runs `git commit -m <message>` in the current directory, letting git print its own output.
+pub fn commit(message: &str) -> Result<()> {
+    let status = Command::new("git")
+        .args(["commit", "-m", message])
+        .status()
+        .map_err(not_run)?;
+    if !status.success() {
+        return Err(failed(format!("git commit failed ({})", status)).into());
+    }
+    Ok(())
+}
+
+/// Returns the name of the checked-out branch, or `None` with a detached `HEAD`.
+pub fn current_branch() -> Result<Option<String>> {
+    let output = Command::new("git")
+        .args(["symbolic-ref", "--quiet", "--short", "HEAD"])
+        .output()
+        .map_err(not_run)?;
+    if !output.status.success() {
+        // `--quiet` fails silently only for a detached `HEAD`
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.trim().is_empty() {
+            return Ok(None);
+        }
+        return Err(failed(format!("git symbolic-ref failed: {}", stderr.trim())).into());
+    }
+    Ok(Some(
+        String::from_utf8_lossy(&output.stdout).trim().to_string(),
+    ))
+}
+
+/// Runs `git checkout -b <name>`, creating the branch at `HEAD` and switching to it.
+pub fn create_branch(name: &str) -> Result<()> {
+    let status = Command::new("git")
+        .args(["checkout", "-b", name])
+        .status()
+        .map_err(not_run)?;
+    if !status.success() {
+        return Err(failed(format!("git checkout -b failed ({})", status)).into());
+    }
+    Ok(())
+}
+
+/// Returns the abbreviated hash of `HEAD`.
+pub fn short_head() -> Result<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .map_err(not_run)?;
+    if !output.status.success() {
+        return Err(failed(format!("git rev-parse failed ({})", output.status)).into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Returns the paths of the files tracked in the repository around the current directory,
+/// relative to it.
+pub fn tracked_files() -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .args(["ls-files"])
+        .output()
+        .map_err(not_run)?;
+    if !output.status.success() {
+        return Err(failed(format!(
+            "git ls-files failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Returns the subjects of up to `limit` of the most recent commits, skipping merges.
+pub fn log_subjects(limit: usize) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .args([
+            "log",
+            "--no-merges",
+            "--format=%s",
+            "-n",
+            &limit.to_string(),
+        ])
+        .output()
+        .map_err(not_run)?;
+    if !output.status.success() {
+        return Err(failed(format!(
+            "git log failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Returns `git diff --cached --stat`, a summary of what is staged: the files changed and
+/// how many lines each gained and lost. It is empty with nothing staged.
+pub fn staged_stat() -> Result<String> {
+    let output = Command::new("git")
+        .args(["diff", "--cached", "--stat", "--no-color"])
+        .output()
+        .map_err(not_run)?;
+    if !output.status.success() {
+        return Err(failed(format!(
+            "git diff failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string())
+}
+
+/// Returns the authors of the commits reachable from `HEAD`, skipping merges, with how many
+/// commits each made, most prolific first, as `git shortlog` counts them, so a `.mailmap`
+/// merges the names one person committed under.
+pub fn authors() -> Result<Vec<(String, usize)>> {
+    let output = Command::new("git")
+        .args(["shortlog", "--summary", "--numbered", "--no-merges", "HEAD"])
+        .output()
+        .map_err(not_run)?;
+    if !output.status.success() {
+        return Err(failed(format!(
+            "git shortlog failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (count, name) = line.trim().split_once('\t')?;
+            Some((name.trim().to_string(), count.trim().parse().ok()?))
+        })
+        .collect())
+}
+
+/// Returns the committer identity git would sign off with, as "Name <email>", from the
+/// config or the `GIT_COMMITTER_*` variables.
+pub fn identity() -> Result<String> {
+    let output = Command::new("git")
+        .args(["var", "GIT_COMMITTER_IDENT"])
+        .output()
+        .map_err(not_run)?;
+    if !output.status.success() {
+        return Err(failed(format!(
+            "git has no identity to sign off with; set user.name and user.email: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+        .into());
+    }
+    // The identity is followed by a timestamp and time zone
+    let ident = String::from_utf8_lossy(&output.stdout);
+    match ident.find('>') {
+        Some(end) => Ok(ident[..=end].trim().to_string()),
+        None => Err(failed(format!("Unexpected identity from git: {}", ident.trim())).into()),
+    }
+}
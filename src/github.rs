@@ -0,0 +1,61 @@
+use crate::error::WtcError;
+use crate::uuid;
+use anyhow::Result;
+use std::env;
+use std::fs::OpenOptions;
+use std::io::Write;
+use tracing::{info, warn};
+
+/// Appends `message=<messages>` to the file GitHub Actions names in `GITHUB_OUTPUT`, so a
+/// later step can read it as `steps.<id>.outputs.message`. Several messages go on separate
+/// lines. Outside GitHub Actions this only warns.
+pub fn write_output(messages: &[&str]) -> Result<()> {
+    let Some(path) = env::var_os("GITHUB_OUTPUT").filter(|p| !p.is_empty()) else {
+        warn!("GITHUB_OUTPUT is not set, so there is no step output to write");
+        return Ok(());
+    };
+    let entry = output_entry("message", &messages.join("\n"), &delimiter());
+    let context = || format!("Failed to write GitHub step output: {:?}", path);
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(entry.as_bytes()))
+        .map_err(|source| WtcError::IoError {
+            context: context(),
+            source,
+        })?;
+    info!("Wrote the message to GitHub step output {:?}", path);
+    Ok(())
+}
+
+/// A heredoc delimiter no message will contain by chance.
+fn delimiter() -> String {
+    format!("ghadelimiter_{}", uuid::v4(&mut rand::rng()))
+}
+
+/// Formats one output as `key=value`, or in the heredoc form GitHub documents for values
+/// with line breaks, using `delimiter` to end it.
+fn output_entry(key: &str, value: &str, delimiter: &str) -> String {
+    if value.contains(['\n', '\r']) {
+        format!("{key}<<{delimiter}\n{value}\n{delimiter}\n")
+    } else {
+        format!("{key}={value}\n")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_output_entry() {
+        assert_eq!(output_entry("message", "fix it", "EOF"), "message=fix it\n");
+        assert_eq!(
+            output_entry("message", "one\ntwo", "EOF"),
+            "message<<EOF\none\ntwo\nEOF\n"
+        );
+        assert!(delimiter().starts_with("ghadelimiter_"));
+        assert_ne!(delimiter(), delimiter());
+    }
+}
@@ -0,0 +1,410 @@
+use anyhow::{Context, Result};
+use rand::seq::IndexedRandom;
+use rand::Rng;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use tracing::info;
+
+/// The rule messages are made from, when the grammar has one by this name; otherwise the
+/// first rule is.
+const START: &str = "message";
+
+/// How deep expansion goes picking alternatives at random; below this, every rule takes the
+/// alternative that finishes soonest, so recursive rules always end.
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Symbol {
+    Text(String),
+    /// A rule, by its index.
+    Rule(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    name: String,
+    alternatives: Vec<Vec<Symbol>>,
+    /// How many levels of rules the shortest expansion of each alternative goes through.
+    heights: Vec<usize>,
+}
+
+/// A grammar of rules like `message ::= opener " " excuse | blame`, each rule a choice
+/// between alternatives, each alternative a sequence of quoted text and other rules.
+/// What it expands to is a template, so the text may hold placeholders.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grammar {
+    rules: Vec<Rule>,
+    start: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Text(String),
+    Name(String),
+    Define,
+    Or,
+}
+
+/// Splits a line into tokens, up to a `#` that isn't quoted.
+fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    while let Some(&(at, c)) = chars.peek() {
+        match c {
+            '#' => break,
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '|' => {
+                chars.next();
+                tokens.push(Token::Or);
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, 'n')) => text.push('\n'),
+                            Some((_, escaped)) => text.push(escaped),
+                            None => return Err("a `\\` ends the line".to_string()),
+                        },
+                        Some((_, end)) if end == c => break,
+                        Some((_, other)) => text.push(other),
+                        None => return Err(format!("the text at column {} isn't closed", at + 1)),
+                    }
+                }
+                tokens.push(Token::Text(text));
+            }
+            _ if line[at..].starts_with("::=") => {
+                chars.nth(2);
+                tokens.push(Token::Define);
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut name = String::new();
+                while let Some(&(_, c)) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_' || c == '-') {
+                        break;
+                    }
+                    name.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Name(name));
+            }
+            other => {
+                return Err(format!(
+                    "unexpected `{}` at column {}; text goes in quotes",
+                    other,
+                    at + 1
+                ))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+impl std::str::FromStr for Grammar {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        // Each rule's name, the line it is defined on and its alternatives, as tokens
+        let mut rules: Vec<(String, usize, Vec<Vec<Token>>)> = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let number = number + 1;
+            let at_line = |problem: String| anyhow::anyhow!("line {}: {}", number, problem);
+            let tokens = tokenize(line).map_err(at_line)?;
+            let body = match tokens.as_slice() {
+                [] => continue,
+                [Token::Name(name), Token::Define, ..] => {
+                    if let Some((_, line, _)) = rules.iter().find(|(n, ..)| n == name) {
+                        return Err(at_line(format!(
+                            "`{}` is already defined on line {}",
+                            name, line
+                        )));
+                    }
+                    rules.push((name.clone(), number, vec![Vec::new()]));
+                    &tokens[2..]
+                }
+                [Token::Or, ..] => match rules.last_mut() {
+                    Some((_, _, alternatives)) => {
+                        alternatives.push(Vec::new());
+                        &tokens[1..]
+                    }
+                    None => return Err(at_line("`|` before any rule".to_string())),
+                },
+                _ => {
+                    return Err(at_line(
+                        "rules look like `name ::= \"text\" other | \"more text\"`".to_string(),
+                    ))
+                }
+            };
+            let (_, _, alternatives) = rules.last_mut().expect("a rule was just found");
+            for token in body {
+                match token {
+                    Token::Or => alternatives.push(Vec::new()),
+                    Token::Define => return Err(at_line("one `::=` per rule".to_string())),
+                    token => alternatives
+                        .last_mut()
+                        .expect("every rule has an alternative")
+                        .push(token.clone()),
+                }
+            }
+        }
+        if rules.is_empty() {
+            anyhow::bail!("There are no rules");
+        }
+
+        let index = |name: &str| rules.iter().position(|(n, ..)| n == name);
+        let mut resolved = Vec::with_capacity(rules.len());
+        for (name, line, alternatives) in &rules {
+            let mut symbols = Vec::with_capacity(alternatives.len());
+            for alternative in alternatives {
+                if alternative.is_empty() {
+                    anyhow::bail!(
+                        "line {}: `{}` has an empty alternative; write \"\" for nothing",
+                        line,
+                        name
+                    );
+                }
+                let alternative = alternative
+                    .iter()
+                    .map(|token| match token {
+                        Token::Text(text) => Ok(Symbol::Text(text.clone())),
+                        Token::Name(used) => index(used).map(Symbol::Rule).with_context(|| {
+                            format!(
+                                "line {}: `{}` uses `{}`, which isn't defined",
+                                line, name, used
+                            )
+                        }),
+                        _ => unreachable!("only text and names are kept"),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                symbols.push(alternative);
+            }
+            resolved.push(Rule {
+                name: name.clone(),
+                alternatives: symbols,
+                heights: Vec::new(),
+            });
+        }
+        measure(&mut resolved)?;
+        let start = index(START).unwrap_or(0);
+        Ok(Grammar {
+            rules: resolved,
+            start,
+        })
+    }
+}
+
+/// Works out the heights of every alternative, failing for rules that can never finish
+/// expanding, such as `a ::= "x" a`.
+fn measure(rules: &mut [Rule]) -> Result<()> {
+    let mut heights: Vec<Option<usize>> = vec![None; rules.len()];
+    let height_of = |alternative: &[Symbol], heights: &[Option<usize>]| {
+        alternative
+            .iter()
+            .try_fold(1, |height, symbol| match symbol {
+                Symbol::Text(_) => Some(height),
+                Symbol::Rule(rule) => heights[*rule].map(|h| height.max(h + 1)),
+            })
+    };
+    loop {
+        let mut changed = false;
+        for (i, rule) in rules.iter().enumerate() {
+            let lowest = rule
+                .alternatives
+                .iter()
+                .filter_map(|alternative| height_of(alternative, &heights))
+                .min();
+            if lowest.is_some() && lowest != heights[i] {
+                heights[i] = lowest;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    let endless: Vec<&str> = rules
+        .iter()
+        .zip(&heights)
+        .filter(|(_, height)| height.is_none())
+        .map(|(rule, _)| rule.name.as_str())
+        .collect();
+    if !endless.is_empty() {
+        anyhow::bail!(
+            "These rules never stop expanding; give each an alternative without itself: {}",
+            endless.join(", ")
+        );
+    }
+    for rule in rules.iter_mut() {
+        rule.heights = (rule.alternatives.iter())
+            .map(|alternative| height_of(alternative, &heights).unwrap_or(usize::MAX))
+            .collect();
+    }
+    Ok(())
+}
+
+impl Grammar {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read grammar file: {:?}", path))?;
+        let grammar: Grammar = content
+            .parse()
+            .with_context(|| format!("Failed to parse grammar file: {:?}", path))?;
+        info!(
+            "Loaded {} grammar rules from {:?}",
+            grammar.rules.len(),
+            path
+        );
+        Ok(grammar)
+    }
+
+    fn expand_into<R>(&self, rule: usize, depth: usize, out: &mut String, rng: &mut R)
+    where
+        R: Rng + ?Sized,
+    {
+        let rule = &self.rules[rule];
+        let alternative = match depth < MAX_DEPTH {
+            true => rule.alternatives.choose(rng),
+            false => (rule.heights.iter().enumerate())
+                .min_by_key(|(_, height)| **height)
+                .map(|(i, _)| &rule.alternatives[i]),
+        };
+        for symbol in alternative.expect("every rule has an alternative") {
+            match symbol {
+                Symbol::Text(text) => out.push_str(text),
+                Symbol::Rule(next) => self.expand_into(*next, depth + 1, out, rng),
+            }
+        }
+    }
+
+    /// Expands the start rule into a template.
+    pub fn expand<R>(&self, rng: &mut R) -> String
+    where
+        R: Rng + ?Sized,
+    {
+        let mut template = String::new();
+        self.expand_into(self.start, 0, &mut template, rng);
+        template
+    }
+
+    /// Expands up to `count` distinct templates, fewer if the grammar can't make that
+    /// many, leaving out empty ones.
+    pub fn templates<R>(&self, count: usize, rng: &mut R) -> Vec<String>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut seen = HashSet::new();
+        (0..count)
+            .map(|_| self.expand(rng))
+            .filter(|template| !template.trim().is_empty() && seen.insert(template.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_grammar_expand() {
+        let grammar: Grammar = r#"
+            # Openers come first
+            opener ::= "Fixed" | "Broke"
+            message ::= opener " it " excuse   # the start rule
+                      | "Blame XNAMEX"
+            excuse ::= "because " reason | "again"
+            reason ::= "of 'reasons'" | reason " and " reason
+        "#
+        .parse()
+        .unwrap();
+        assert_eq!(grammar.rules[grammar.start].name, "message");
+        let mut rng = StdRng::seed_from_u64(3);
+        let templates = grammar.templates(200, &mut rng);
+        assert!(templates.contains(&"Blame XNAMEX".to_string()));
+        assert!(templates.contains(&"Broke it again".to_string()));
+        assert!(templates.iter().any(|t| t.contains("of 'reasons' and of")));
+        assert!(templates.iter().all(|t| t.starts_with(['F', 'B'])));
+
+        for (text, problem) in [
+            ("a ::= \"x\" b", "`b`, which isn't defined"),
+            ("a ::= \"x\" a", "never stop expanding"),
+            ("a ::= \"x\" |", "empty alternative"),
+            ("a ::= \"x\nb ::= \"y\"", "line 1: the text"),
+            ("a ::= \"x\"\na ::= \"y\"", "already defined on line 1"),
+            ("| \"x\"", "before any rule"),
+            ("", "no rules"),
+        ] {
+            let error = format!("{:#}", text.parse::<Grammar>().unwrap_err());
+            assert!(error.contains(problem), "{}: {}", text, error);
+        }
+    }
+
+    #[test]
+    fn t_recursion_limits() {
+        let mut rng = StdRng::seed_from_u64(5);
+        // Recursing nine times in ten, expansion would run on; past the depth limit every
+        // rule takes its shortest way out
+        let grammar: Grammar = r#"
+            list ::= item ", " list | item ", " list | item ", " list | item ", " list
+                   | item ", " list | item ", " list | item ", " list | item ", " list
+                   | item ", " list | item
+            item ::= "x" | "(" list ")"
+        "#
+        .parse()
+        .unwrap();
+        assert_eq!(grammar.rules[grammar.start].name, "list");
+        assert_eq!(grammar.rules[0].heights[0], 3);
+        assert_eq!(grammar.rules[0].heights[9], 2);
+        assert_eq!(grammar.rules[1].heights, [1, 3]);
+        for _ in 0..20 {
+            let template = grammar.expand(&mut rng);
+            let nesting = template.chars().scan(0, |depth, c| {
+                *depth += match c {
+                    '(' => 1,
+                    ')' => -1,
+                    _ => 0,
+                };
+                Some(*depth)
+            });
+            // Each pair of parentheses takes two levels, an item and a list
+            assert!(nesting.max().unwrap_or(0) <= MAX_DEPTH as i32 / 2);
+        }
+        let mut out = String::new();
+        grammar.expand_into(0, MAX_DEPTH, &mut out, &mut rng);
+        assert_eq!(out, "x");
+
+        // Rules that only ever lead to each other are found, however far apart
+        let error = "a ::= \"x\" | b\nb ::= c \"y\"\nc ::= b\nd ::= c | a"
+            .parse::<Grammar>()
+            .unwrap_err();
+        assert!(error.to_string().ends_with("b, c"), "{}", error);
+    }
+
+    #[test]
+    fn t_undefined_and_malformed_rules() {
+        for (text, problem) in [
+            // A rule used on a continuation line is reported on the line its rule starts
+            (
+                "a ::= \"x\"\n  | \"y\" missing-rule",
+                "line 1: `a` uses `missing-rule`",
+            ),
+            ("a ::= \"x\" B", "uses `B`, which isn't defined"),
+            ("a ::= \"x\" ::= \"y\"", "line 1: one `::=` per rule"),
+            ("a \"x\"", "rules look like"),
+            ("a ::= x + \"y\"", "unexpected `+` at column 9"),
+            ("a ::= \"x\\", "a `\\` ends the line"),
+        ] {
+            let error = format!("{:#}", text.parse::<Grammar>().unwrap_err());
+            assert!(error.contains(problem), "{}: {}", text, error);
+        }
+        // Escapes and a `#` inside quotes are text
+        let grammar: Grammar = r##"a ::= "#1 \"fix\"\n" 'it\'s'"##.parse().unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(grammar.expand(&mut rng), "#1 \"fix\"\nit's");
+    }
+}
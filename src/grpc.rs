@@ -0,0 +1,278 @@
+use crate::rpc::{self, Methods};
+use anyhow::{Context, Result};
+use std::net::SocketAddr;
+use tokio::sync::{mpsc, oneshot};
+use tonic::codegen::{http, Body, BoxFuture, Poll, StdError};
+use tonic::server::{Grpc, NamedService, UnaryService};
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+use tonic_prost::ProstCodec;
+use tracing::{debug, info};
+
+/// The service's full name, from `package` and `service` in `proto/whatthecommit.proto`.
+const SERVICE: &str = "whatthecommit.WhatTheCommit";
+
+/// The path of `GenerateMessage` calls.
+const GENERATE_MESSAGE: &str = "/whatthecommit.WhatTheCommit/GenerateMessage";
+
+/// `GenerateRequest` of `proto/whatthecommit.proto`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GenerateRequest {
+    #[prost(uint32, tag = "1")]
+    pub count: u32,
+    #[prost(string, tag = "2")]
+    pub pack: String,
+    #[prost(uint64, optional, tag = "3")]
+    pub seed: Option<u64>,
+}
+
+/// `Message` of `proto/whatthecommit.proto`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Message {
+    #[prost(string, tag = "1")]
+    pub message: String,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(string, tag = "3")]
+    pub template: String,
+}
+
+/// `GenerateReply` of `proto/whatthecommit.proto`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GenerateReply {
+    #[prost(message, repeated, tag = "1")]
+    pub messages: Vec<Message>,
+}
+
+/// A `GenerateMessage` call, waiting for the thread that holds the corpora to answer it.
+struct Job {
+    request: GenerateRequest,
+    reply: oneshot::Sender<Result<GenerateReply, Status>>,
+}
+
+/// Answers `request` from `methods`, the same corpora and settings `rpc` serves.
+fn generate<M: Methods>(
+    methods: &mut M,
+    request: GenerateRequest,
+) -> Result<GenerateReply, Status> {
+    let pack = match request.pack.as_str() {
+        "" => rpc::DEFAULT_CORPUS,
+        pack => pack,
+    };
+    if !methods.packs().iter().any(|p| p.name == pack) {
+        return Err(Status::not_found(format!("No pack named `{}`", pack)));
+    }
+    let seed = |methods: &mut M, seed| {
+        let settings = rpc::Settings {
+            seed: Some(seed),
+            ..Default::default()
+        };
+        (methods.set_config(settings)).map_err(|e| Status::failed_precondition(format!("{:#}", e)))
+    };
+    if let Some(s) = request.seed {
+        seed(methods, s)?;
+    }
+    let messages = methods.generate(pack, request.count.max(1) as usize);
+    // A request's seed is its own, so the requests after it aren't predictable
+    if request.seed.is_some() {
+        seed(methods, rand::random())?;
+    }
+    let messages = messages.map_err(|e| Status::internal(format!("{:#}", e)))?;
+    Ok(GenerateReply {
+        messages: (messages.into_iter())
+            .map(|m| Message {
+                message: m.message,
+                name: m.name,
+                template: m.template,
+            })
+            .collect(),
+    })
+}
+
+/// Hands `GenerateMessage` calls over to the thread that answers them.
+#[derive(Clone)]
+struct Generate(mpsc::UnboundedSender<Job>);
+
+impl UnaryService<GenerateRequest> for Generate {
+    type Response = GenerateReply;
+    type Future = BoxFuture<Response<GenerateReply>, Status>;
+
+    fn call(&mut self, request: Request<GenerateRequest>) -> Self::Future {
+        let jobs = self.0.clone();
+        Box::pin(async move {
+            let (reply, answer) = oneshot::channel();
+            let request = request.into_inner();
+            let gone = || Status::unavailable("The server is shutting down");
+            jobs.send(Job { request, reply }).map_err(|_| gone())?;
+            answer.await.map_err(|_| gone())?.map(Response::new)
+        })
+    }
+}
+
+/// The `WhatTheCommit` service, routing calls by path as tonic's generated servers do.
+#[derive(Clone)]
+struct WhatTheCommit(Generate);
+
+impl NamedService for WhatTheCommit {
+    const NAME: &'static str = SERVICE;
+}
+
+impl<B> tonic::codegen::Service<http::Request<B>> for WhatTheCommit
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<tonic::body::Body>;
+    type Error = std::convert::Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        debug!("gRPC request: {}", request.uri().path());
+        if request.uri().path() != GENERATE_MESSAGE {
+            let path = request.uri().path().to_string();
+            return Box::pin(async move {
+                Ok(Status::unimplemented(format!("No method at {}", path)).into_http())
+            });
+        }
+        let generate = self.0.clone();
+        Box::pin(async move {
+            let mut grpc = Grpc::new(ProstCodec::<GenerateReply, GenerateRequest>::default());
+            Ok(grpc.unary(generate, request).await)
+        })
+    }
+}
+
+/// Listens for gRPC calls on `listener`, answering each from `methods` in turn, until the
+/// server fails. The server runs on other threads, since `methods` needn't be `Send`.
+fn serve_on<M: Methods>(methods: &mut M, listener: std::net::TcpListener) -> Result<()> {
+    let runtime = tokio::runtime::Runtime::new().context("Failed to start the gRPC server")?;
+    let (jobs, mut queue) = mpsc::unbounded_channel();
+    let server = runtime.spawn(async move {
+        listener.set_nonblocking(true)?;
+        let incoming = TcpIncoming::from(tokio::net::TcpListener::from_std(listener)?);
+        Server::builder()
+            .add_service(WhatTheCommit(Generate(jobs)))
+            .serve_with_incoming(incoming)
+            .await
+            .context("The gRPC server failed")
+    });
+    // The queue ends once the server stops and drops the service's end of it
+    while let Some(Job { request, reply }) = queue.blocking_recv() {
+        let _ = reply.send(generate(methods, request));
+    }
+    runtime.block_on(server)?
+}
+
+/// Serves `GenerateMessage` of `proto/whatthecommit.proto` on `addr`, drawing from the
+/// corpora of `methods`.
+pub fn serve<M: Methods>(methods: &mut M, addr: SocketAddr) -> Result<()> {
+    let listener = std::net::TcpListener::bind(addr)
+        .with_context(|| format!("Failed to listen for gRPC on {}", addr))?;
+    info!("Serving gRPC on {}", listener.local_addr()?);
+    serve_on(methods, listener)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tonic::transport::Channel;
+
+    /// Hands out numbered messages, restarting the numbers when seeded.
+    #[derive(Default)]
+    struct Fake {
+        drawn: usize,
+    }
+
+    impl Methods for Fake {
+        fn generate(&mut self, corpus: &str, count: usize) -> Result<Vec<rpc::Message>> {
+            Ok((0..count)
+                .map(|_| {
+                    self.drawn += 1;
+                    rpc::Message {
+                        message: format!("{} {}", corpus, self.drawn),
+                        name: "Ann".to_string(),
+                        template: "XNAMEX fixed it".to_string(),
+                    }
+                })
+                .collect())
+        }
+
+        fn packs(&self) -> Vec<rpc::Pack> {
+            vec![rpc::Pack {
+                name: rpc::DEFAULT_CORPUS,
+                templates: 1,
+            }]
+        }
+
+        fn set_config(&mut self, settings: rpc::Settings) -> Result<()> {
+            self.drawn = settings.seed.unwrap_or_default() as usize;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn t_generate_message() {
+        let mut fake = Fake::default();
+        let request = |count, pack: &str, seed| GenerateRequest {
+            count,
+            pack: pack.to_string(),
+            seed,
+        };
+        let messages = |reply: GenerateReply| -> Vec<String> {
+            reply.messages.into_iter().map(|m| m.message).collect()
+        };
+        let reply = generate(&mut fake, request(2, "", None)).unwrap();
+        assert_eq!(messages(reply), ["commit-messages 1", "commit-messages 2"]);
+        let reply = generate(&mut fake, request(0, "commit-messages", Some(7))).unwrap();
+        assert_eq!(messages(reply), ["commit-messages 8"]);
+        assert_ne!(fake.drawn, 8, "the seed only holds for its request");
+        let err = generate(&mut fake, request(1, "haiku", None)).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+    }
+
+    #[test]
+    fn t_grpc_round_trip() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || serve_on(&mut Fake::default(), listener));
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let reply = runtime.block_on(async {
+            let channel = Channel::from_shared(format!("http://{}", addr))
+                .unwrap()
+                .connect()
+                .await
+                .unwrap();
+            let mut client = tonic::client::Grpc::new(channel);
+            client.ready().await.unwrap();
+            let codec = ProstCodec::<GenerateRequest, GenerateReply>::default();
+            let request = GenerateRequest {
+                count: 2,
+                pack: String::new(),
+                seed: None,
+            };
+            let path = http::uri::PathAndQuery::from_static(GENERATE_MESSAGE);
+            let reply = client.unary(Request::new(request), path.clone(), codec);
+            let reply = reply.await.unwrap().into_inner();
+
+            // Anything but GenerateMessage isn't there
+            client.ready().await.unwrap();
+            let codec = ProstCodec::<GenerateRequest, GenerateReply>::default();
+            let other = http::uri::PathAndQuery::from_static("/whatthecommit.WhatTheCommit/Other");
+            let err = client
+                .unary(Request::new(GenerateRequest::default()), other, codec)
+                .await
+                .unwrap_err();
+            assert_eq!(err.code(), tonic::Code::Unimplemented);
+            reply
+        });
+        assert_eq!(reply.messages.len(), 2);
+        assert_eq!(reply.messages[1].message, "commit-messages 2");
+        assert_eq!(reply.messages[0].template, "XNAMEX fixed it");
+    }
+}
@@ -0,0 +1,126 @@
+use crate::sections;
+
+/// The built-in lines, under `[five]` and `[seven]` headers for their syllables.
+const LINES: &str = include_str!("haiku.txt");
+
+/// Syllables in each line of a haiku.
+const PATTERN: [usize; 3] = [5, 7, 5];
+
+/// Separates the lines of a haiku, so it still fits on one line as a commit subject.
+pub const SEPARATOR: &str = " / ";
+
+/// Estimates the syllables in a word by counting its groups of vowels, less a silent `e`
+/// or `-ed` at the end. Digits count one each, and anything else nothing.
+pub fn syllables(word: &str) -> usize {
+    let word: String = word
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if !word.chars().any(char::is_alphabetic) {
+        return word.len();
+    }
+    let vowel = |c: char| "aeiouy".contains(c);
+    let mut count = 0;
+    let mut previous = false;
+    for c in word.chars() {
+        let is_vowel = vowel(c);
+        if is_vowel && !previous {
+            count += 1;
+        }
+        previous = is_vowel;
+    }
+    // "code" and "fixed" but not "table" or "tested"
+    let silent = (word.ends_with('e') && !word.ends_with("le"))
+        || (word.ends_with("ed") && !word.ends_with("ted") && !word.ends_with("ded"));
+    if silent && count > 1 {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+/// The syllables in a line of words, with hyphenated words counted by their parts.
+fn line_syllables(line: &str) -> usize {
+    line.split(|c: char| c.is_whitespace() || c == '-' || c == '/')
+        .map(syllables)
+        .sum()
+}
+
+/// Lays out `message` as a 5-7-5 haiku joined by [`SEPARATOR`], if its words split that
+/// way without breaking one. Lines already separated are joined up first.
+pub fn arrange(message: &str) -> Option<String> {
+    let words: Vec<&str> = message
+        .split_whitespace()
+        .filter(|word| *word != SEPARATOR.trim())
+        .collect();
+    let mut lines = Vec::with_capacity(PATTERN.len());
+    let mut words = words.into_iter().peekable();
+    for wanted in PATTERN {
+        let mut line = Vec::new();
+        let mut count = 0;
+        while count < wanted {
+            let word = words.next()?;
+            count += line_syllables(word);
+            line.push(word);
+        }
+        // Punctuation alone adds no syllables, so it stays on the line it follows
+        while let Some(word) = words.next_if(|word| line_syllables(word) == 0) {
+            line.push(word);
+        }
+        if count != wanted {
+            return None;
+        }
+        lines.push(line.join(" "));
+    }
+    words.next().is_none().then(|| lines.join(SEPARATOR))
+}
+
+/// Every haiku the built-in lines make, as templates: each five-syllable line with each
+/// seven and each other five.
+pub fn templates() -> Vec<String> {
+    let [fives, sevens] = sections::parse(LINES, ["five", "seven"], "haiku")
+        .expect("the built-in haiku lines are valid");
+    let mut templates = Vec::with_capacity(fives.len() * sevens.len() * fives.len());
+    for first in &fives {
+        for second in &sevens {
+            for third in fives.iter().filter(|third| *third != first) {
+                templates.push([first.as_str(), second, third].join(SEPARATOR));
+            }
+        }
+    }
+    templates
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_haiku() {
+        assert_eq!(syllables("code"), 1);
+        assert_eq!(syllables("yesterday"), 3);
+        assert_eq!(syllables("fixed"), 1);
+        assert_eq!(syllables("tested"), 2);
+        assert_eq!(syllables("table"), 2);
+        assert_eq!(syllables("404"), 3);
+        assert_eq!(syllables("..."), 0);
+
+        let [fives, sevens] = sections::parse(LINES, ["five", "seven"], "haiku").unwrap();
+        for (lines, wanted) in [(fives, 5), (sevens, 7)] {
+            for line in lines {
+                assert_eq!(line_syllables(&line), wanted, "{}", line);
+            }
+        }
+        let templates = templates();
+        assert!(templates.iter().all(|t| arrange(t).as_ref() == Some(t)));
+
+        assert_eq!(
+            arrange("the build is broken again, nobody knows why the cache is frozen ."),
+            Some(
+                "the build is broken / again, nobody knows why / the cache is frozen .".to_string()
+            )
+        );
+        assert_eq!(arrange("fix the build"), None);
+        assert_eq!(arrange("the build is not broken again"), None);
+    }
+}
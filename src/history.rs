@@ -0,0 +1,244 @@
+use crate::date::Date;
+#[cfg(not(feature = "regex"))]
+use crate::disabled::regex::Regex;
+use crate::paths;
+use anyhow::{Context, Result};
+#[cfg(feature = "regex")]
+use regex_lite::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Entries kept when the config does not say otherwise.
+pub const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+/// One emitted message, stored as a line of JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Seconds since the Unix epoch
+    pub time: u64,
+    pub message: String,
+    /// Seed that reproduces the batch the message came from, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Repository the message was generated in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<PathBuf>,
+}
+
+impl Entry {
+    /// Creates an entry for a message emitted now in the current directory.
+    pub fn now(message: &str, seed: Option<u64>) -> Self {
+        Entry {
+            time: now(),
+            message: message.to_string(),
+            seed,
+            repo: paths::repo_root_of_cwd(),
+        }
+    }
+}
+
+/// How much history to keep.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Retention {
+    pub max_entries: usize,
+    pub max_age_days: Option<u64>,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Retention {
+            max_entries: DEFAULT_MAX_ENTRIES,
+            max_age_days: None,
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Location of the history file.
+pub fn path() -> Result<PathBuf> {
+    paths::data_dir()
+        .map(|d| d.join("history.jsonl"))
+        .context("Could not determine the data directory")
+}
+
+/// Reads the history, oldest first, skipping lines that cannot be parsed.
+pub fn load(path: &Path) -> Result<Vec<Entry>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content
+            .lines()
+            .filter_map(|l| serde_json::from_str(l).ok())
+            .collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read history file: {:?}", path)),
+    }
+}
+
+/// Drops entries beyond the retention limits, keeping the newest.
+fn prune(entries: Vec<Entry>, retention: Retention, now: u64) -> Vec<Entry> {
+    let oldest = retention
+        .max_age_days
+        .map_or(0, |days| now.saturating_sub(days * 86_400));
+    let mut kept: Vec<Entry> = entries.into_iter().filter(|e| e.time >= oldest).collect();
+    let excess = kept.len().saturating_sub(retention.max_entries);
+    kept.drain(..excess);
+    kept
+}
+
+/// Appends entries to the history, rewriting the file only when it outgrows the retention
+/// limits.
+pub fn record(path: &Path, entries: &[Entry], retention: Retention) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory: {:?}", dir))?;
+    }
+    let mut lines = String::new();
+    for entry in entries {
+        lines.push_str(&serde_json::to_string(entry)?);
+        lines.push('\n');
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(lines.as_bytes()))
+        .with_context(|| format!("Failed to write history file: {:?}", path))?;
+
+    let all = load(path)?;
+    let count = all.len();
+    let kept = prune(all, retention, now());
+    if kept.len() < count {
+        let mut content = String::new();
+        for entry in &kept {
+            content.push_str(&serde_json::to_string(entry)?);
+            content.push('\n');
+        }
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write history file: {:?}", path))?;
+    }
+    Ok(())
+}
+
+/// Formats a Unix timestamp as a UTC `YYYY-MM-DD HH:MM` string.
+pub fn format_time(secs: u64) -> String {
+    let date = Date::from_unix(secs);
+    let minutes = secs % 86_400 / 60;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        date.year,
+        date.month,
+        date.day,
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+/// Prints the history, newest last, optionally only entries whose message matches `grep`.
+pub fn print(path: &Path, grep: Option<&str>, limit: Option<usize>) -> Result<()> {
+    let grep = grep
+        .map(|g| Regex::new(&format!("(?i){}", g)))
+        .transpose()
+        .context("Invalid --grep pattern")?;
+    let entries: Vec<Entry> = load(path)?
+        .into_iter()
+        .filter(|e| grep.as_ref().is_none_or(|re| re.is_match(&e.message)))
+        .collect();
+    let skip = limit.map_or(0, |limit| entries.len().saturating_sub(limit));
+    for entry in &entries[skip..] {
+        let seed = entry.seed.map(|s| s.to_string()).unwrap_or_default();
+        let repo = entry
+            .repo
+            .as_deref()
+            .and_then(Path::file_name)
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        println!(
+            "{}  {:<20}  {:<16}  {}",
+            format_time(entry.time),
+            seed,
+            repo,
+            entry.message
+        );
+    }
+    Ok(())
+}
+
+/// Deletes the history file.
+pub fn clear(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(e).with_context(|| format!("Failed to remove history file: {:?}", path))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(time: u64, message: &str) -> Entry {
+        Entry {
+            time,
+            message: message.to_string(),
+            seed: None,
+            repo: None,
+        }
+    }
+
+    #[test]
+    fn t_format_time() {
+        assert_eq!(format_time(0), "1970-01-01 00:00");
+        assert_eq!(format_time(1_709_210_096), "2024-02-29 12:34");
+    }
+
+    #[test]
+    fn t_prune_by_count_and_age() {
+        let entries = vec![
+            entry(0, "ancient"),
+            entry(90_000, "old"),
+            entry(100_000, "new"),
+        ];
+        let retention = Retention {
+            max_entries: 2,
+            max_age_days: None,
+        };
+        let kept = prune(entries.clone(), retention, 100_000);
+        assert_eq!(kept, vec![entry(90_000, "old"), entry(100_000, "new")]);
+
+        let retention = Retention {
+            max_entries: 10,
+            max_age_days: Some(1),
+        };
+        assert_eq!(prune(entries, retention, 100_000).len(), 2);
+    }
+
+    #[test]
+    fn t_record_appends_and_prunes() {
+        let path = std::env::temp_dir()
+            .join(format!("wtc-history-{}", std::process::id()))
+            .join("history.jsonl");
+        let retention = Retention {
+            max_entries: 2,
+            max_age_days: None,
+        };
+        for message in ["one", "two", "three"] {
+            record(&path, &[Entry::now(message, Some(7))], retention).unwrap();
+        }
+        let messages: Vec<String> = load(&path)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.message)
+            .collect();
+        assert_eq!(messages, vec!["two", "three"]);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,71 @@
+use crate::error::WtcError;
+use anyhow::Result;
+use std::env;
+use std::fs;
+use std::path::Path;
+use tracing::debug;
+
+/// Where the pre-commit framework says the message came from, since it passes only the
+/// message file to hooks.
+const SOURCE_VAR: &str = "PRE_COMMIT_COMMIT_MSG_SOURCE";
+
+/// Fills in the commit message file git passes to a prepare-commit-msg hook with the
+/// message from `generate`, above git's comments, when it holds no message yet.
+///
+/// `source` is the hook's second argument, or else the pre-commit framework's variable
+/// for it. A message given with `-m` or `-F`, a merge, a squash or an amend already has a
+/// message, so the file is left alone.
+pub fn run<F>(path: &Path, source: Option<&str>, generate: F) -> Result<()>
+where
+    F: FnOnce() -> Result<String>,
+{
+    let source = source
+        .map(String::from)
+        .or_else(|| env::var(SOURCE_VAR).ok())
+        .filter(|s| !s.is_empty());
+    if let Some(source) = source {
+        debug!("Leaving the {} commit message as it is", source);
+        return Ok(());
+    }
+    let existing = fs::read_to_string(path).map_err(|source| WtcError::IoError {
+        context: format!("Failed to read commit message file: {:?}", path),
+        source,
+    })?;
+    if has_message(&existing) {
+        debug!("The commit message file already has a message");
+        return Ok(());
+    }
+    let filled = format!("{}\n{}", generate()?, existing);
+    fs::write(path, filled).map_err(|source| WtcError::IoError {
+        context: format!("Failed to write commit message file: {:?}", path),
+        source,
+    })?;
+    Ok(())
+}
+
+/// Whether a commit message file has a line that is neither blank nor a git comment.
+fn has_message(text: &str) -> bool {
+    text.lines()
+        .any(|line| !line.starts_with('#') && !line.trim().is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_fills_only_empty_messages() {
+        let path = std::env::temp_dir().join(format!("wtc-hook-{}.txt", std::process::id()));
+        fs::write(&path, "\n# Please enter the commit message\n").unwrap();
+        run(&path, None, || Ok("fixed it".to_string())).unwrap();
+        let filled = fs::read_to_string(&path).unwrap();
+        assert_eq!(filled, "fixed it\n\n# Please enter the commit message\n");
+
+        // Once there is a message, or git says where one came from, nothing is generated
+        run(&path, None, || panic!("generated again")).unwrap();
+        fs::write(&path, "\n").unwrap();
+        run(&path, Some("message"), || panic!("generated for -m")).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "\n");
+        fs::remove_file(path).unwrap();
+    }
+}
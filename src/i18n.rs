@@ -0,0 +1,366 @@
+use crate::corpus;
+use crate::error::WtcError;
+use crate::lint;
+use crate::pack;
+use anyhow::Result;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The placeholders and directive pieces of a template, which a translation has to keep,
+/// sorted so that translations may reorder them.
+fn protected(template: &str) -> Vec<&str> {
+    let mut pieces: Vec<&str> = lint::scan(template)
+        .into_iter()
+        .map(|p| template[p.range].trim())
+        .collect();
+    pieces.sort_unstable();
+    pieces
+}
+
+/// Quotes `text` as a PO string.
+fn quote(text: &str) -> String {
+    let mut quoted = String::from('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\t' => quoted.push_str("\\t"),
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Reads a quoted PO string, or `None` if `text` isn't one.
+fn unquote(text: &str) -> Option<String> {
+    let inner = text.trim().strip_prefix('"')?.strip_suffix('"')?;
+    let mut text = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        text.push(match chars.next()? {
+            'n' => '\n',
+            't' => '\t',
+            other => other,
+        });
+    }
+    Some(text)
+}
+
+/// The PO template of the templates files `packs`: a message per template, with its
+/// section as the context and the placeholders to keep in a comment for translators.
+/// Templates repeated under the same section become one message with every reference.
+fn export(packs: &[(PathBuf, String)]) -> String {
+    let mut messages: Vec<(Option<String>, &str, Vec<String>)> = Vec::new();
+    for (path, text) in packs {
+        for entry in pack::entries(text) {
+            let reference = format!("{}:{}", path.display(), entry.line + 1);
+            let same = messages.iter_mut().find(|(section, template, _)| {
+                *section == entry.section && *template == entry.template
+            });
+            match same {
+                Some((_, _, references)) => references.push(reference),
+                None => messages.push((entry.section, entry.template, vec![reference])),
+            }
+        }
+    }
+
+    let mut pot = format!(
+        "msgid \"\"\nmsgstr \"\"\n\"Project-Id-Version: {} {}\\n\"\n\"MIME-Version: 1.0\\n\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n\"Content-Transfer-Encoding: 8bit\\n\"\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    );
+    for (section, template, references) in messages {
+        pot.push('\n');
+        let mut keep = protected(template);
+        keep.dedup();
+        if !keep.is_empty() {
+            let _ = writeln!(pot, "#. Keep as they are: {}", keep.join(" "));
+        }
+        let _ = writeln!(pot, "#: {}", references.join(" "));
+        if let Some(section) = section {
+            let _ = writeln!(pot, "msgctxt {}", quote(&section));
+        }
+        let _ = writeln!(pot, "msgid {}\nmsgstr \"\"", quote(template));
+    }
+    pot
+}
+
+/// A message of a PO file.
+#[derive(Debug, Default, PartialEq)]
+struct Message {
+    context: Option<String>,
+    id: String,
+    text: String,
+    fuzzy: bool,
+    /// One-based line its `msgid` is on.
+    line: usize,
+}
+
+/// The field of a message continuation lines add to.
+#[derive(Clone, Copy)]
+enum Field {
+    Context,
+    Id,
+    Text,
+}
+
+/// Parses the messages of a PO file, leaving out the header and obsolete messages.
+fn parse(po: &str) -> Result<Vec<Message>> {
+    let mut messages = Vec::new();
+    let mut message = Message::default();
+    let mut field = None;
+    for (number, line) in po.lines().enumerate() {
+        let line = line.trim();
+        let invalid =
+            || anyhow::anyhow!("Line {} of the PO file isn't valid: {}", number + 1, line);
+        // A message ends at the first comment or keyword after its msgstr
+        let starts_message = line.is_empty()
+            || line.starts_with('#')
+            || line.starts_with("msgctxt ")
+            || line.starts_with("msgid ");
+        if starts_message && matches!(field, Some(Field::Text)) {
+            messages.push(std::mem::take(&mut message));
+            field = None;
+        }
+        if line.is_empty() || line.starts_with("#~") {
+            continue;
+        }
+        if let Some(flags) = line.strip_prefix("#,") {
+            message.fuzzy |= flags.split(',').any(|flag| flag.trim() == "fuzzy");
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        let (next, rest) = if let Some(rest) = line.strip_prefix("msgctxt ") {
+            (Field::Context, rest)
+        } else if let Some(rest) = line.strip_prefix("msgid ") {
+            message.line = number + 1;
+            (Field::Id, rest)
+        } else if let Some(rest) = line.strip_prefix("msgstr ") {
+            (Field::Text, rest)
+        } else if line.starts_with('"') {
+            (field.ok_or_else(invalid)?, line)
+        } else {
+            anyhow::bail!(
+                "Line {} of the PO file isn't valid, maybe a plural form, which templates don't have: {}",
+                number + 1,
+                line
+            );
+        };
+        let text = unquote(rest).ok_or_else(invalid)?;
+        match next {
+            Field::Context => message
+                .context
+                .get_or_insert_with(String::new)
+                .push_str(&text),
+            Field::Id => message.id.push_str(&text),
+            Field::Text => message.text.push_str(&text),
+        }
+        field = Some(next);
+    }
+    if matches!(field, Some(Field::Text)) {
+        messages.push(message);
+    }
+    messages.retain(|m| !m.id.is_empty());
+    Ok(messages)
+}
+
+/// What importing a PO file made: the localized templates file, by section, and why
+/// messages were left out.
+#[derive(Debug, PartialEq)]
+struct Import {
+    sections: Vec<(Option<String>, Vec<String>)>,
+    untranslated: usize,
+    problems: Vec<String>,
+}
+
+/// Builds a localized templates file from translated `messages`. Messages that are
+/// untranslated, fuzzy, span lines or lose or change a placeholder are left out.
+fn import(messages: &[Message]) -> Import {
+    let mut import = Import {
+        sections: Vec::new(),
+        untranslated: 0,
+        problems: Vec::new(),
+    };
+    for message in messages {
+        let translation = message.text.trim();
+        if translation.is_empty() || message.fuzzy {
+            import.untranslated += 1;
+            continue;
+        }
+        if translation.contains('\n') {
+            import.problems.push(format!(
+                "line {}: the translation spans lines, but templates are one line each",
+                message.line
+            ));
+            continue;
+        }
+        if protected(translation) != protected(&message.id) {
+            import.problems.push(format!(
+                "line {}: the translation has to keep the placeholders {:?}",
+                message.line,
+                protected(&message.id).join(" ")
+            ));
+            continue;
+        }
+        let section = import
+            .sections
+            .iter()
+            .position(|(section, _)| *section == message.context)
+            .unwrap_or_else(|| {
+                import.sections.push((message.context.clone(), Vec::new()));
+                import.sections.len() - 1
+            });
+        import.sections[section].1.push(translation.to_string());
+    }
+    import
+}
+
+/// Writes the PO template of the templates files at `paths`, or of the loaded templates if
+/// none are given, to `output`, or stdout for `-`.
+pub fn export_files(paths: &[PathBuf], loaded: (&Path, &[String]), output: &Path) -> Result<()> {
+    let packs = corpus::packs(paths, loaded)?;
+    pack::write(output, &export(&packs))
+}
+
+/// Builds a localized templates file from the translated PO file at `po` and writes it to
+/// `output`, or stdout for `-`, reporting the messages left out on stderr.
+pub fn import_file(po: &Path, output: &Path) -> Result<()> {
+    let text = fs::read(po).map_err(|source| WtcError::IoError {
+        context: format!("Failed to read PO file: {:?}", po),
+        source,
+    })?;
+    let import = import(&parse(&corpus::decode(text)?)?);
+    for problem in &import.problems {
+        eprintln!("{}: {}", po.display(), problem);
+    }
+    let translated: usize = import.sections.iter().map(|(_, t)| t.len()).sum();
+    if translated == 0 {
+        anyhow::bail!("{:?} has no translated templates to build a pack from", po);
+    }
+    pack::write(output, &pack::render(&import.sections))?;
+    eprintln!(
+        "Built {} templates, leaving out {} untranslated and {} with problems",
+        translated,
+        import.untranslated,
+        import.problems.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_po_round_trip() {
+        let packs = vec![(
+            PathBuf::from("standup.txt"),
+            "[today]\nXNAMEX fixed \"XNUM1,5X\" bugs\nshipping it\nshipping it\n[blockers]\nnone\n"
+                .to_string(),
+        )];
+        let pot = export(&packs);
+        assert!(pot.starts_with("msgid \"\"\nmsgstr \"\"\n\"Project-Id-Version: whatthecommitcli"));
+        assert!(pot.contains(
+            "\n#. Keep as they are: XNAMEX XNUM1,5X\n#: standup.txt:2\nmsgctxt \"today\"\nmsgid \"XNAMEX fixed \\\"XNUM1,5X\\\" bugs\"\nmsgstr \"\"\n"
+        ));
+        assert!(pot.contains("#: standup.txt:3 standup.txt:4\n"));
+
+        let messages = parse(&pot).unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].id, "XNAMEX fixed \"XNUM1,5X\" bugs");
+        assert_eq!(messages[2].context.as_deref(), Some("blockers"));
+
+        let po = pot
+            .replace(
+                "bugs\"\nmsgstr \"\"",
+                "bugs\"\nmsgstr \"\"\n\"XNAMEX hat \\\"XNUM1,5X\\\" \"\n\"Fehler behoben\"",
+            )
+            .replace(
+                "\"shipping it\"\nmsgstr \"\"",
+                "\"shipping it\"\nmsgstr \"wird ausgeliefert XNAMEX\"",
+            )
+            .replace("#: standup.txt:6", "#, fuzzy\n#: standup.txt:6")
+            .replace("\"none\"\nmsgstr \"\"", "\"none\"\nmsgstr \"keine\"");
+        let import = import(&parse(&po).unwrap());
+        assert_eq!(
+            import.sections,
+            [(
+                Some("today".to_string()),
+                vec!["XNAMEX hat \"XNUM1,5X\" Fehler behoben".to_string()]
+            )]
+        );
+        assert_eq!(import.untranslated, 1);
+        assert_eq!(import.problems.len(), 1);
+        assert!(import.problems[0].contains("keep the placeholders"));
+        assert!(parse("msgid \"a\"\nmsgid_plural \"b\"\n").is_err());
+    }
+
+    #[test]
+    fn t_po_escapes() {
+        let text = "say \"hi\"\tto C:\\path\nthen é";
+        let quoted = quote(text);
+        assert_eq!(quoted, r#""say \"hi\"\tto C:\\path\nthen é""#);
+        assert_eq!(unquote(&quoted).as_deref(), Some(text));
+        // Escapes PO has that templates don't are read as the character escaped
+        assert_eq!(unquote(r#"  "a\'b\?"  "#).as_deref(), Some("a'b?"));
+        assert_eq!(unquote(r#""ends in \""#), None);
+        assert_eq!(unquote(r#""unterminated"#), None);
+        assert_eq!(unquote("bare"), None);
+    }
+
+    #[test]
+    fn t_po_multiline_messages() {
+        let po = r#"msgid ""
+msgstr ""
+"Language: de\n"
+
+#~ msgid "retired"
+#~ msgstr "ausgemustert"
+
+msgctxt ""
+"to"
+"day"
+msgid ""
+"XNAMEX fixed "
+"it"
+msgstr ""
+"XNAMEX hat es "
+"repariert"
+
+msgid "two\nlines"
+msgstr "zwei\nZeilen"
+"#;
+        let messages = parse(po).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].context.as_deref(), Some("today"));
+        assert_eq!(messages[0].id, "XNAMEX fixed it");
+        assert_eq!(messages[0].text, "XNAMEX hat es repariert");
+        assert_eq!(messages[0].line, 11);
+        assert_eq!(messages[1].id, "two\nlines");
+
+        let import = import(&messages);
+        assert_eq!(
+            import.sections,
+            [(
+                Some("today".to_string()),
+                vec!["XNAMEX hat es repariert".to_string()]
+            )]
+        );
+        assert!(import.problems[0].starts_with("line 18: the translation spans lines"));
+
+        let err = parse("\"orphan\"\n").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Line 1 of the PO file isn't valid"));
+        assert!(parse("msgid \"a\nmsgstr \"\"\n").is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use crate::lint::Directive;
+use crate::template::{Node, Template};
+use crate::words::{self, List};
+use anyhow::Result;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Verbs a commit subject in the imperative mood starts with, as in "Fix the build".
+const VERBS: [&str; 64] = [
+    "add",
+    "adjust",
+    "allow",
+    "apply",
+    "avoid",
+    "break",
+    "bump",
+    "change",
+    "check",
+    "clean",
+    "configure",
+    "convert",
+    "create",
+    "delete",
+    "deploy",
+    "deprecate",
+    "disable",
+    "do",
+    "document",
+    "don't",
+    "drop",
+    "enable",
+    "ensure",
+    "extract",
+    "fix",
+    "format",
+    "handle",
+    "hardcode",
+    "ignore",
+    "implement",
+    "improve",
+    "inline",
+    "introduce",
+    "make",
+    "merge",
+    "move",
+    "optimize",
+    "patch",
+    "prepare",
+    "prevent",
+    "push",
+    "put",
+    "refactor",
+    "reformat",
+    "release",
+    "remove",
+    "rename",
+    "replace",
+    "restore",
+    "revert",
+    "rewrite",
+    "set",
+    "ship",
+    "simplify",
+    "split",
+    "support",
+    "test",
+    "trust",
+    "try",
+    "tweak",
+    "undo",
+    "update",
+    "upgrade",
+    "use",
+];
+
+/// The file in the words directory with verbs to add to the built-in ones.
+pub const FILE: &str = "imperatives.txt";
+
+/// The verbs `--imperative` lets templates start with.
+#[derive(Debug)]
+pub struct Imperatives {
+    verbs: HashSet<String>,
+}
+
+impl Imperatives {
+    /// The built-in verbs and those of `XVERBX`, with the lines of any `imperatives.txt`
+    /// in `dir` added to them.
+    pub fn load(dir: Option<&Path>) -> Result<Self> {
+        let mut verbs: HashSet<String> = VERBS.iter().map(|verb| verb.to_string()).collect();
+        let list = words::get().list(List::Verb);
+        verbs.extend(list.iter().map(|verb| verb.to_lowercase()));
+        if let Some(dir) = dir {
+            if let Some(own) = words::read(&dir.join(FILE))? {
+                verbs.extend(own.iter().map(|verb| verb.to_lowercase()));
+            }
+        }
+        Ok(Imperatives { verbs })
+    }
+
+    /// Whether `template` starts with a command: one of the verbs, or `XVERBX`, after any
+    /// directives that write nothing.
+    pub fn allows(&self, template: &str) -> bool {
+        let parsed = Template::parse(template);
+        let first = parsed.nodes().iter().find(|node| match node {
+            Node::Directive(
+                Directive::Set { .. }
+                | Directive::Spice(_)
+                | Directive::MaxLen(_)
+                | Directive::Requires(_)
+                | Directive::NoConventional,
+            ) => false,
+            Node::Literal(text) => !text.trim().is_empty(),
+            _ => true,
+        });
+        match first {
+            Some(Node::Word(List::Verb)) => true,
+            Some(Node::Literal(text)) => {
+                let word = text.split_whitespace().next().unwrap_or_default();
+                let word = word.trim_end_matches(|c: char| !c.is_alphanumeric());
+                self.verbs.contains(&word.to_lowercase())
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn t_imperative_first_words() {
+        let imperatives = Imperatives::load(None).unwrap();
+        assert!(imperatives.allows("Fix the build"));
+        assert!(imperatives.allows("{{spice: medium}}  Revert, again"));
+        assert!(imperatives.allows("XVERBX the XNOUNX"));
+        assert!(imperatives.allows("Don't touch this"));
+        assert!(!imperatives.allows("Fixed the build"));
+        assert!(!imperatives.allows("XNAMEX broke it"));
+        assert!(!imperatives.allows(""));
+
+        let dir = std::env::temp_dir().join(format!("wtc-imperatives-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        assert!(!imperatives.allows("Frobnicate it"));
+        fs::write(dir.join(FILE), "Frobnicate\n").unwrap();
+        assert!(Imperatives::load(Some(&dir))
+            .unwrap()
+            .allows("frobnicate it"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,299 @@
+use crate::error::{ErrorKind, WithKind};
+use crate::paths;
+use crate::theme::ThemeName;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Hook script filling in commit messages that are still empty.
+///
+/// Git passes the message source as `$2`; it is empty only when no message was given
+/// with `-m`, `-F`, a template, a merge or an amended commit.
+const HOOK_SCRIPT: &str = r#"#!/bin/sh
+# Installed by `whatthecommitcli init`: fills in empty commit messages.
+[ -n "$2" ] && exit 0
+if ! grep -q -v -e '^#' -e '^[[:space:]]*$' "$1"; then
+    msg=$(whatthecommitcli --plain) || exit 0
+    { echo "$msg"; cat "$1"; } > "$1.wtc" && mv "$1.wtc" "$1"
+fi
+"#;
+
+/// Answers collected by the wizard.
+#[derive(Debug, Default, PartialEq)]
+struct Answers {
+    names: Vec<String>,
+    theme: Option<ThemeName>,
+    templates: Option<PathBuf>,
+    install_hook: bool,
+}
+
+/// Asks questions on a reader/writer pair so the wizard can be driven by tests.
+struct Prompter<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    fn ask(&mut self, question: &str) -> Result<String> {
+        write!(self.output, "{} ", question)?;
+        self.output.flush()?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            writeln!(self.output)?;
+            anyhow::bail!("Input ended before the setup was finished; nothing was written");
+        }
+        Ok(line.trim().to_string())
+    }
+
+    fn confirm(&mut self, question: &str) -> Result<bool> {
+        let answer = self.ask(&format!("{} [y/N]", question))?;
+        Ok(matches!(answer.to_ascii_lowercase().as_str(), "y" | "yes"))
+    }
+
+    /// Asks every question, resolving a relative template path against `cwd`, where it
+    /// was typed, since the config file's own paths are relative to its directory.
+    fn answers(&mut self, in_repo: bool, cwd: &Path) -> Result<Answers> {
+        let mut answers = Answers::default();
+
+        let names =
+            self.ask("Team member names, comma-separated (empty keeps the built-in names):")?;
+        answers.names = names
+            .split(',')
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .collect();
+
+        loop {
+            let theme = self.ask("Color theme [default/ocean/mono]:")?;
+            if theme.is_empty() {
+                break;
+            }
+            match ThemeName::from_str(&theme, true) {
+                Ok(theme) => {
+                    answers.theme = Some(theme);
+                    break;
+                }
+                Err(_) => writeln!(self.output, "Unknown theme `{}`", theme)?,
+            }
+        }
+
+        if self
+            .confirm("Only use your own templates (never the built-in, occasionally spicy ones)?")?
+        {
+            loop {
+                let path = self.ask("Path to your commit messages template file:")?;
+                if !path.is_empty() {
+                    let path = PathBuf::from(path);
+                    answers.templates = Some(match path.is_absolute() || path.starts_with("~") {
+                        true => path,
+                        false => cwd.join(path),
+                    });
+                    break;
+                }
+            }
+        }
+
+        if in_repo {
+            answers.install_hook = self.confirm(
+                "Install a git hook in this repository that fills in empty commit messages?",
+            )?;
+        }
+
+        Ok(answers)
+    }
+}
+
+/// Quotes a string as a TOML value.
+fn toml_string(s: &str) -> String {
+    toml::Value::String(s.to_string()).to_string()
+}
+
+/// Renders the config file for the collected answers.
+fn config_content(answers: &Answers) -> String {
+    let mut content = String::from("# Generated by `whatthecommitcli init`\n");
+    if !answers.names.is_empty() {
+        content.push_str("names = \"names.txt\"\n");
+    }
+    if let Some(theme) = answers.theme {
+        let theme = theme.to_possible_value().expect("themes are never skipped");
+        content.push_str(&format!("theme = {}\n", toml_string(theme.get_name())));
+    }
+    if let Some(templates) = &answers.templates {
+        content.push_str(&format!(
+            "commit-messages-template = {}\n",
+            toml_string(&templates.to_string_lossy())
+        ));
+        // The built-in names are still wanted when none were given
+        if !answers.names.is_empty() {
+            content.push_str("no-default-corpus = true\n");
+        }
+    }
+    content
+}
+
+/// Returns the hooks directory of the git repository in the current directory, if any.
+fn git_hooks_dir() -> Option<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path", "hooks"])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+}
+
+/// Installs the prepare-commit-msg hook, refusing to overwrite a foreign hook.
+fn install_hook(hooks_dir: &Path) -> Result<PathBuf> {
+    let path = hooks_dir.join("prepare-commit-msg");
+    if let Ok(existing) = fs::read_to_string(&path) {
+        if existing != HOOK_SCRIPT {
+            return Err(anyhow::anyhow!(
+                "A prepare-commit-msg hook already exists at {:?}; not overwriting it",
+                path
+            ))
+            .kind(ErrorKind::Git);
+        }
+    }
+
+    fs::create_dir_all(hooks_dir)
+        .with_context(|| format!("Failed to create hooks directory: {:?}", hooks_dir))
+        .kind(ErrorKind::Git)?;
+    fs::write(&path, HOOK_SCRIPT)
+        .with_context(|| format!("Failed to write hook: {:?}", path))
+        .kind(ErrorKind::Git)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("Failed to make hook executable: {:?}", path))
+            .kind(ErrorKind::Git)?;
+    }
+    Ok(path)
+}
+
+/// Runs the interactive setup wizard, writing the config file (and names file)
+/// and optionally installing the git hook.
+pub fn run(config: Option<&Path>) -> Result<()> {
+    let config_path = config
+        .map(Path::to_path_buf)
+        .or_else(paths::config_file)
+        .context("Could not determine the config directory")?;
+    let config_dir = config_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let hooks_dir = git_hooks_dir();
+
+    let stdin = io::stdin();
+    let mut prompter = Prompter {
+        input: stdin.lock(),
+        output: io::stdout(),
+    };
+
+    if config_path.exists()
+        && !prompter.confirm(&format!(
+            "{} already exists. Overwrite it?",
+            config_path.display()
+        ))?
+    {
+        println!("Nothing changed.");
+        return Ok(());
+    }
+
+    let cwd = env::current_dir().context("Could not determine the current directory")?;
+    let answers = prompter.answers(hooks_dir.is_some(), &cwd)?;
+
+    fs::create_dir_all(&config_dir)
+        .with_context(|| format!("Failed to create config directory: {:?}", config_dir))?;
+    if !answers.names.is_empty() {
+        let names_path = config_dir.join("names.txt");
+        fs::write(&names_path, answers.names.join("\n") + "\n")
+            .with_context(|| format!("Failed to write names file: {:?}", names_path))?;
+        println!("Wrote {}", names_path.display());
+    }
+    fs::write(&config_path, config_content(&answers))
+        .with_context(|| format!("Failed to write config file: {:?}", config_path))?;
+    println!("Wrote {}", config_path.display());
+
+    if let (true, Some(hooks_dir)) = (answers.install_hook, hooks_dir) {
+        let hook = install_hook(&hooks_dir)?;
+        println!("Installed {}", hook.display());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn answers(input: &str, in_repo: bool) -> Answers {
+        let mut prompter = Prompter {
+            input: input.as_bytes(),
+            output: Vec::new(),
+        };
+        prompter.answers(in_repo, Path::new("/work")).unwrap()
+    }
+
+    #[test]
+    fn t_wizard_collects_answers() {
+        let answers = answers("Alice, Bob ,\nsparkly\nocean\ny\n\nsfw.txt\ny\n", true);
+        assert_eq!(
+            answers,
+            Answers {
+                names: vec!["Alice".to_string(), "Bob".to_string()],
+                theme: Some(ThemeName::Ocean),
+                templates: Some(PathBuf::from("/work/sfw.txt")),
+                install_hook: true,
+            }
+        );
+    }
+
+    #[test]
+    fn t_wizard_defaults_outside_repo() {
+        assert_eq!(answers("\n\n\n", false), Answers::default());
+    }
+
+    #[test]
+    fn t_wizard_stops_at_end_of_input() {
+        let mut prompter = Prompter {
+            input: "\n\ny\n".as_bytes(),
+            output: Vec::new(),
+        };
+        let err = prompter.answers(false, Path::new("/work")).unwrap_err();
+        assert!(err.to_string().contains("Input ended"));
+        // Absolute and home paths are kept as typed
+        let answers = answers("\n\ny\n~/sfw.txt\n", false);
+        assert_eq!(answers.templates, Some(PathBuf::from("~/sfw.txt")));
+    }
+
+    #[test]
+    fn t_config_content_parses() {
+        let mut answers = Answers {
+            names: vec!["Alice".to_string()],
+            theme: Some(ThemeName::Mono),
+            templates: Some(PathBuf::from("/work/my \"quoted\" file.txt")),
+            install_hook: false,
+        };
+        let config =
+            crate::config::Config::parse(&config_content(&answers), Path::new("/c")).unwrap();
+        assert_eq!(config.options.names, Some(PathBuf::from("/c/names.txt")));
+        assert_eq!(config.options.theme, Some(ThemeName::Mono));
+        assert_eq!(config.options.no_default_corpus, Some(true));
+        assert_eq!(
+            config.options.commit_messages_template,
+            Some(PathBuf::from("/work/my \"quoted\" file.txt"))
+        );
+
+        // Without names of their own, the built-in names stay available
+        answers.names.clear();
+        let config =
+            crate::config::Config::parse(&config_content(&answers), Path::new("/c")).unwrap();
+        assert_eq!(config.options.no_default_corpus, None);
+    }
+}
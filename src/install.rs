@@ -0,0 +1,171 @@
+use crate::corpus;
+use crate::error::WtcError;
+use crate::http;
+use crate::lockfile;
+use crate::minisign::{self, PublicKey};
+use crate::paths;
+use crate::sections;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+
+fn is_url(source: &str) -> bool {
+    source.starts_with("https://") || source.starts_with("http://")
+}
+
+/// Reads `source`, a URL or a path.
+fn fetch(source: &str, settings: &http::Settings) -> Result<Vec<u8>> {
+    if is_url(source) {
+        return http::get(source, settings);
+    }
+    fs::read(source).map_err(|source_error| {
+        WtcError::IoError {
+            context: format!("Failed to read {:?}", source),
+            source: source_error,
+        }
+        .into()
+    })
+}
+
+/// The file name a pack from `source` is installed under: the last part of its path,
+/// without any query.
+fn default_name(source: &str) -> Option<&str> {
+    let path = if is_url(source) {
+        source.split(['?', '#']).next()?
+    } else {
+        source
+    };
+    path.rsplit(['/', '\\'])
+        .next()
+        .filter(|name| !name.is_empty())
+}
+
+/// Where the signature of `source` is, when it isn't given: `source` with `.minisig`
+/// added to its path, ahead of any query or fragment of a URL.
+fn signature_source(source: &str) -> String {
+    let end = if is_url(source) {
+        source.find(['?', '#']).unwrap_or(source.len())
+    } else {
+        source.len()
+    };
+    format!("{}.minisig{}", &source[..end], &source[end..])
+}
+
+/// Fails unless `name` is a plain file name, so a pack can't be written outside the packs
+/// directory.
+fn check_name(name: &str) -> Result<()> {
+    if !paths::is_pack_name(name) {
+        anyhow::bail!("{:?} can't be the name of a pack", name);
+    }
+    Ok(())
+}
+
+/// Downloads the templates file at `source`, a URL or a path, and its minisign signature
+/// from `signature`, or `source` with `.minisig` added, and installs it into the packs
+/// directory as `name`, or the file name of `source`, once the signature is verified
+/// against the `trusted` public keys, and one from a URL against the lockfile, which
+/// `locked` insists on. An installed pack is only replaced with `force`.
+pub fn run(
+    source: &str,
+    signature: Option<&str>,
+    name: Option<&str>,
+    force: bool,
+    trusted: &[String],
+    settings: &http::Settings,
+    locked: bool,
+) -> Result<()> {
+    if trusted.is_empty() {
+        anyhow::bail!(
+            "No keys are trusted to sign packs; add the publisher's minisign public key to `trusted-keys` in the config file"
+        );
+    }
+    let trusted = trusted
+        .iter()
+        .map(|key| key.parse())
+        .collect::<Result<Vec<PublicKey>>>()?;
+    let name = name.or_else(|| default_name(source)).with_context(|| {
+        format!(
+            "Can't name a pack after {:?}; give it one with --name",
+            source
+        )
+    })?;
+    check_name(name)?;
+    let dest: PathBuf = paths::packs_dir()
+        .context("Could not determine the data directory")?
+        .join(name);
+    if dest.exists() && !force {
+        anyhow::bail!(
+            "A pack named {:?} is already installed; --force replaces it",
+            name
+        );
+    }
+
+    let bytes = fetch(source, settings)?;
+    let signature_source = match signature {
+        Some(signature) => signature.to_string(),
+        None => signature_source(source),
+    };
+    let signature = fetch(&signature_source, settings)?;
+    let comment = minisign::verify(&bytes, &String::from_utf8_lossy(&signature), &trusted)
+        .with_context(|| format!("Not installing {}", source))?;
+    if is_url(source) {
+        lockfile::check_source(source, &bytes, locked)?;
+    }
+    let text = corpus::decode(bytes.clone())?;
+    let templates = sections::templates(&text).count();
+    if templates == 0 {
+        anyhow::bail!("{} has no templates; not installing it", source);
+    }
+
+    let dir = dest.parent().expect("the pack is in the packs directory");
+    fs::create_dir_all(dir).map_err(|source| WtcError::IoError {
+        context: format!("Failed to create the packs directory: {:?}", dir),
+        source,
+    })?;
+    fs::write(&dest, &bytes).map_err(|source| WtcError::IoError {
+        context: format!("Failed to write pack: {:?}", dest),
+        source,
+    })?;
+    println!(
+        "Installed {} templates to {} (signed: {})",
+        templates,
+        dest.display(),
+        comment
+    );
+    println!("Use it with -c {}", dest.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_pack_names() {
+        assert_eq!(
+            default_name("https://example.com/packs/standup.txt?raw=1"),
+            Some("standup.txt")
+        );
+        assert_eq!(default_name("packs/ops.txt"), Some("ops.txt"));
+        assert_eq!(default_name("https://example.com/"), None);
+        assert_eq!(
+            signature_source("https://example.com/packs/standup.txt?raw=1#top"),
+            "https://example.com/packs/standup.txt.minisig?raw=1#top"
+        );
+        assert_eq!(signature_source("packs/ops.txt"), "packs/ops.txt.minisig");
+        assert!(check_name("ops.txt").is_ok());
+        for name in ["../config.toml", ".hidden", "a/b"] {
+            assert!(check_name(name).is_err(), "{}", name);
+        }
+        let settings = http::Settings {
+            timeout: std::time::Duration::from_secs(1),
+            retries: 0,
+            backoff: std::time::Duration::ZERO,
+            offline: true,
+            proxy: None,
+            auth: Vec::new(),
+        };
+        let untrusted = run("ops.txt", None, None, false, &[], &settings, false).unwrap_err();
+        assert!(untrusted.to_string().contains("trusted-keys"));
+    }
+}
@@ -0,0 +1,50 @@
+use rand::seq::IndexedRandom;
+use rand::Rng;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// The IPv4 blocks reserved for documentation (RFC 5737): TEST-NET-1, -2 and -3.
+const V4_NETWORKS: [[u8; 3]; 3] = [[192, 0, 2], [198, 51, 100], [203, 0, 113]];
+
+/// The first two groups of the IPv6 block reserved for documentation, `2001:db8::/32`
+/// (RFC 3849).
+const V6_PREFIX: [u16; 2] = [0x2001, 0x0db8];
+
+/// A random host address in one of the IPv4 documentation blocks, so a generated message
+/// never names a real machine.
+pub fn v4<R>(rng: &mut R) -> Ipv4Addr
+where
+    R: Rng + ?Sized,
+{
+    let [a, b, c] = *V4_NETWORKS.choose(rng).unwrap();
+    Ipv4Addr::new(a, b, c, rng.random_range(1..=254))
+}
+
+/// A random address in the IPv6 documentation block.
+pub fn v6<R>(rng: &mut R) -> Ipv6Addr
+where
+    R: Rng + ?Sized,
+{
+    let [a, b] = V6_PREFIX;
+    // Mostly zeros, like the addresses people actually type
+    Ipv6Addr::new(a, b, 0, 0, 0, 0, 0, rng.random_range(1..=0xffff))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_addresses_are_for_documentation() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let ip = v4(&mut rng);
+            assert!(ip.is_documentation(), "{}", ip);
+            assert!(!matches!(ip.octets()[3], 0 | 255));
+            let ip = v6(&mut rng);
+            assert_eq!(ip.segments()[..2], V6_PREFIX);
+            assert!(ip.to_string().starts_with("2001:db8::"));
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use anyhow::{Context, Result};
+use std::env;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// The service every token is stored under, with its name as the account.
+const SERVICE: &str = "whatthecommit";
+
+/// Marks a `${...}` name as a token in the keychain rather than an environment variable.
+const KEYRING_PREFIX: &str = "keyring:";
+
+/// The keychain entry of the token `name`: the login keychain on macOS, the Credential
+/// Manager on Windows and the Secret Service (GNOME Keyring, KWallet) elsewhere.
+fn entry(name: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(SERVICE, name)
+        .with_context(|| format!("The keychain can't hold a token named {}", name))
+}
+
+fn store(name: &str, token: &str) -> Result<()> {
+    entry(name)?
+        .set_password(token)
+        .context("Failed to store the token in the keychain")
+}
+
+fn lookup(name: &str) -> Result<Option<String>> {
+    match entry(name)?.get_password() {
+        Ok(token) => Ok(Some(token)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => {
+            Err(e).with_context(|| format!("Failed to read the token {} from the keychain", name))
+        }
+    }
+}
+
+fn delete(name: &str) -> Result<bool> {
+    match entry(name)?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(e).context("Failed to remove the token from the keychain"),
+    }
+}
+
+/// The value of what `${name}` stands for: the token stored as `NAME` for
+/// `keyring:NAME`, or else the environment variable `name`.
+pub fn resolve(name: &str) -> Result<Option<String>> {
+    match name.strip_prefix(KEYRING_PREFIX) {
+        Some(name) => lookup(name),
+        None => Ok(env::var(name).ok()),
+    }
+}
+
+/// `value` with every `${NAME}` in it replaced by what `resolve` gives for `NAME`.
+pub fn expand(value: &str, resolve: &impl Fn(&str) -> Result<Option<String>>) -> Result<String> {
+    let mut expanded = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .with_context(|| format!("{:?} has a `${{` without its `}}`", value))?;
+        let name = &after[..end];
+        let resolved = resolve(name)?.with_context(|| match name.strip_prefix(KEYRING_PREFIX) {
+            Some(token) => format!(
+                "No token named {} is in the keychain; store it with `auth login {}`",
+                token, token
+            ),
+            None => format!("The environment variable {} isn't set", name),
+        })?;
+        expanded.push_str(&resolved);
+        rest = &after[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Fails unless `name` can name a token: `${keyring:NAME}` ends at the first `}`.
+fn check_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.contains(|c: char| c == '}' || c.is_whitespace()) {
+        anyhow::bail!("{:?} can't be the name of a token", name);
+    }
+    Ok(())
+}
+
+/// Reads a token from stdin, without echoing it when typed at a terminal.
+fn read_token(name: &str) -> Result<String> {
+    let stdin = io::stdin();
+    let typed = stdin.is_terminal();
+    // Where echo can't be turned off, the token is still read, just shown as it is typed
+    let stty = |arg| {
+        typed
+            && cfg!(unix)
+            && Command::new("stty")
+                .arg(arg)
+                .stdin(Stdio::inherit())
+                .status()
+                .is_ok_and(|s| s.success())
+    };
+    if typed {
+        eprint!("Token for {}: ", name);
+        io::stderr().flush()?;
+    }
+    let hidden = stty("-echo");
+    let mut token = String::new();
+    let read = stdin.lock().read_line(&mut token);
+    if hidden {
+        stty("echo");
+        eprintln!();
+    }
+    read.context("Failed to read the token")?;
+    let token = token.trim_end_matches(['\r', '\n']).to_string();
+    if token.is_empty() {
+        anyhow::bail!("No token was given");
+    }
+    Ok(token)
+}
+
+/// Reads a token from stdin and stores it in the keychain as `name`.
+pub fn login(name: &str) -> Result<()> {
+    check_name(name)?;
+    let token = read_token(name)?;
+    store(name, &token)?;
+    println!(
+        "Stored the token {} in the keychain; use it as ${{{}{}}}",
+        name, KEYRING_PREFIX, name
+    );
+    Ok(())
+}
+
+/// Removes the token `name` from the keychain.
+pub fn logout(name: &str) -> Result<()> {
+    check_name(name)?;
+    if !delete(name)? {
+        anyhow::bail!("No token named {} is in the keychain", name);
+    }
+    println!("Removed the token {} from the keychain", name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_expand_tokens() {
+        let resolve = |name: &str| {
+            Ok(match name {
+                "GITHUB_TOKEN" => Some("ghp_1".to_string()),
+                "keyring:slack" => Some("T0/B0/x".to_string()),
+                _ => None,
+            })
+        };
+        assert_eq!(
+            expand("Bearer ${GITHUB_TOKEN}", &resolve).unwrap(),
+            "Bearer ghp_1"
+        );
+        assert_eq!(
+            expand(
+                "https://hooks.slack.com/services/${keyring:slack}",
+                &resolve
+            )
+            .unwrap(),
+            "https://hooks.slack.com/services/T0/B0/x"
+        );
+        assert_eq!(expand("no tokens", &resolve).unwrap(), "no tokens");
+        let missing = expand("${keyring:github}", &resolve).unwrap_err();
+        assert!(missing.to_string().contains("auth login github"));
+        assert!(expand("${UNSET}", &resolve)
+            .unwrap_err()
+            .to_string()
+            .contains("UNSET isn't set"));
+        assert!(expand("${OPEN", &resolve).is_err());
+        assert!(check_name("github").is_ok());
+        assert!(check_name("a}b").is_err() && check_name("").is_err());
+    }
+}
@@ -0,0 +1,354 @@
+use anyhow::{Context, Result};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Something a key can be bound to in the interactive modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyAction {
+    Accept,
+    Commit,
+    Regenerate,
+    Edit,
+    Star,
+    Copy,
+    Mark,
+    Quit,
+}
+
+impl KeyAction {
+    fn describe(self) -> &'static str {
+        match self {
+            KeyAction::Accept => "print",
+            KeyAction::Commit => "commit",
+            KeyAction::Regenerate => "regenerate",
+            KeyAction::Edit => "edit",
+            KeyAction::Star => "star",
+            KeyAction::Copy => "copy",
+            KeyAction::Mark => "mark",
+            KeyAction::Quit => "quit",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            KeyAction::Accept => "accept",
+            _ => self.describe(),
+        }
+    }
+}
+
+/// A key press with its modifiers, written like `q`, `enter` or `ctrl-g`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+/// Modifiers that distinguish bindings; shift is implied by the character itself.
+const MODIFIERS: KeyModifiers = KeyModifiers::CONTROL.union(KeyModifiers::ALT);
+
+impl Key {
+    const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Key { code, modifiers }
+    }
+
+    const fn plain(c: char) -> Self {
+        Key::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    const fn ctrl(c: char) -> Self {
+        Key::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = spec;
+        loop {
+            if let Some(r) = rest.strip_prefix("ctrl-").filter(|r| !r.is_empty()) {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("alt-").filter(|r| !r.is_empty()) {
+                modifiers |= KeyModifiers::ALT;
+                rest = r;
+            } else {
+                break;
+            }
+        }
+        let code = match rest {
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            _ => match rest.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                Some(n @ 1..=12) => KeyCode::F(n),
+                _ => {
+                    let mut chars = rest.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => KeyCode::Char(c),
+                        _ => anyhow::bail!("Unknown key `{}`", spec),
+                    }
+                }
+            },
+        };
+        Ok(Key::new(code, modifiers))
+    }
+
+    fn matches(&self, event: &KeyEvent) -> bool {
+        self.code == event.code && self.modifiers == event.modifiers & MODIFIERS
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt-")?;
+        }
+        let name = match self.code {
+            KeyCode::Char(' ') => "space",
+            KeyCode::Char(c) => return write!(f, "{}", c),
+            KeyCode::F(n) => return write!(f, "f{}", n),
+            KeyCode::Enter => "enter",
+            KeyCode::Esc => "esc",
+            KeyCode::Tab => "tab",
+            KeyCode::Backspace => "backspace",
+            KeyCode::Delete => "delete",
+            KeyCode::Up => "up",
+            KeyCode::Down => "down",
+            KeyCode::Left => "left",
+            KeyCode::Right => "right",
+            KeyCode::Home => "home",
+            KeyCode::End => "end",
+            KeyCode::PageUp => "pageup",
+            KeyCode::PageDown => "pagedown",
+            _ => "?",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// One key or a list of keys, as written in the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum KeySpecs {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeySpecs {
+    fn parse(&self) -> Result<Vec<Key>> {
+        match self {
+            KeySpecs::One(spec) => Ok(vec![Key::parse(spec)?]),
+            KeySpecs::Many(specs) => specs.iter().map(|spec| Key::parse(spec)).collect(),
+        }
+    }
+}
+
+/// The config file's `[keys]` table, with a sub-table per interactive mode mapping
+/// actions to the keys that trigger them.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyConfig {
+    pub tui: BTreeMap<KeyAction, KeySpecs>,
+    pub picker: BTreeMap<KeyAction, KeySpecs>,
+}
+
+/// The keys bound to each action in one interactive mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(KeyAction, Vec<Key>)>,
+}
+
+impl Keymap {
+    /// Bindings of the full-screen browser, vim-flavoured single keys.
+    pub fn tui_defaults() -> Self {
+        Keymap {
+            bindings: vec![
+                (
+                    KeyAction::Accept,
+                    vec![Key::new(KeyCode::Enter, KeyModifiers::NONE)],
+                ),
+                (KeyAction::Commit, vec![Key::plain('c')]),
+                (KeyAction::Regenerate, vec![Key::plain('r')]),
+                (KeyAction::Edit, vec![Key::plain('e')]),
+                (KeyAction::Star, vec![Key::plain('s')]),
+                (KeyAction::Copy, vec![Key::plain('y')]),
+                (KeyAction::Mark, vec![Key::plain(' ')]),
+                (
+                    KeyAction::Quit,
+                    vec![
+                        Key::plain('q'),
+                        Key::new(KeyCode::Esc, KeyModifiers::NONE),
+                        Key::ctrl('c'),
+                    ],
+                ),
+            ],
+        }
+    }
+
+    /// Bindings of the fuzzy finder, where plain characters are typed into the query.
+    pub fn picker_defaults() -> Self {
+        Keymap {
+            bindings: vec![
+                (
+                    KeyAction::Accept,
+                    vec![Key::new(KeyCode::Enter, KeyModifiers::NONE)],
+                ),
+                (KeyAction::Commit, vec![Key::ctrl('g')]),
+                (KeyAction::Edit, vec![Key::ctrl('e')]),
+                (KeyAction::Star, vec![Key::ctrl('s')]),
+                (KeyAction::Copy, vec![Key::ctrl('y')]),
+                (
+                    KeyAction::Mark,
+                    vec![Key::new(KeyCode::Tab, KeyModifiers::NONE)],
+                ),
+                (
+                    KeyAction::Quit,
+                    vec![Key::new(KeyCode::Esc, KeyModifiers::NONE), Key::ctrl('c')],
+                ),
+            ],
+        }
+    }
+
+    /// Replaces the keys of every action set in `overrides`, rejecting actions the mode
+    /// doesn't support and keys bound to two actions.
+    fn with_overrides(mut self, overrides: &BTreeMap<KeyAction, KeySpecs>) -> Result<Self> {
+        for (action, specs) in overrides {
+            let keys = specs.parse()?;
+            let (_, bound) = self
+                .bindings
+                .iter_mut()
+                .find(|(a, _)| a == action)
+                .with_context(|| format!("`{}` is not available here", action.name()))?;
+            *bound = keys;
+        }
+        for (i, (action, keys)) in self.bindings.iter().enumerate() {
+            for (other, other_keys) in &self.bindings[i + 1..] {
+                if let Some(key) = keys.iter().find(|k| other_keys.contains(k)) {
+                    anyhow::bail!(
+                        "`{}` is bound to both `{}` and `{}`",
+                        key,
+                        action.name(),
+                        other.name()
+                    );
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Builds the browser and finder keymaps from the config file's `[keys]` table.
+    pub fn load(config: Option<&KeyConfig>) -> Result<(Keymap, Keymap)> {
+        let default = KeyConfig::default();
+        let config = config.unwrap_or(&default);
+        let tui = Keymap::tui_defaults()
+            .with_overrides(&config.tui)
+            .context("Invalid `[keys.tui]` in config file")?;
+        let picker = Keymap::picker_defaults()
+            .with_overrides(&config.picker)
+            .context("Invalid `[keys.picker]` in config file")?;
+        Ok((tui, picker))
+    }
+
+    /// The action bound to a key press, if any.
+    pub fn action(&self, event: &KeyEvent) -> Option<KeyAction> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|k| k.matches(event)))
+            .map(|(action, _)| *action)
+    }
+
+    /// A one-line summary like `enter print  c commit`, using each action's first key.
+    pub fn help(&self) -> String {
+        self.bindings
+            .iter()
+            .filter_map(|(action, keys)| {
+                let key = keys.first()?;
+                Some(format!("{} {}", key, action.describe()))
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+
+    /// Lists every action with all of its keys.
+    pub fn describe(&self) -> String {
+        self.bindings
+            .iter()
+            .map(|(action, keys)| {
+                let keys: Vec<String> = keys.iter().map(Key::to_string).collect();
+                format!("  {:<12}{}\n", action.name(), keys.join(", "))
+            })
+            .collect()
+    }
+}
+
+/// Prints the current bindings of both interactive modes.
+pub fn print(config: Option<&KeyConfig>) -> Result<()> {
+    let (tui, picker) = Keymap::load(config)?;
+    print!("tui\n{}\npicker\n{}", tui.describe(), picker.describe());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_parse_keys() {
+        assert_eq!(Key::parse("q").unwrap(), Key::plain('q'));
+        assert_eq!(Key::parse("ctrl-g").unwrap(), Key::ctrl('g'));
+        assert_eq!(Key::parse("-").unwrap(), Key::plain('-'));
+        assert_eq!(
+            Key::parse("alt-enter").unwrap(),
+            Key::new(KeyCode::Enter, KeyModifiers::ALT)
+        );
+        assert_eq!(Key::parse("f5").unwrap().to_string(), "f5");
+        assert_eq!(Key::parse("ctrl-space").unwrap().to_string(), "ctrl-space");
+        assert!(Key::parse("hyper-x").is_err());
+    }
+
+    #[test]
+    fn t_overrides_replace_defaults() {
+        let config: KeyConfig = toml::from_str(
+            r#"
+            [tui]
+            accept = ["l", "enter"]
+            quit = "ctrl-q"
+            "#,
+        )
+        .unwrap();
+        let (tui, _) = Keymap::load(Some(&config)).unwrap();
+        let press = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        assert_eq!(tui.action(&press('l')), Some(KeyAction::Accept));
+        assert_eq!(tui.action(&press('q')), None);
+        assert_eq!(
+            tui.action(&KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL)),
+            Some(KeyAction::Quit)
+        );
+    }
+
+    #[test]
+    fn t_conflicts_and_unsupported_actions() {
+        let conflict: KeyConfig = toml::from_str("[tui]\nstar = \"c\"").unwrap();
+        let err = Keymap::load(Some(&conflict)).unwrap_err();
+        assert!(format!("{:#}", err).contains("`c` is bound to both `commit` and `star`"));
+
+        let unsupported: KeyConfig = toml::from_str("[picker]\nregenerate = \"ctrl-r\"").unwrap();
+        assert!(Keymap::load(Some(&unsupported)).is_err());
+    }
+}
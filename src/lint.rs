@@ -0,0 +1,1129 @@
+#[cfg(not(feature = "regex"))]
+use crate::scanner as matchers;
+use crate::spice::Spice;
+use clap::ValueEnum;
+use std::fmt;
+use std::num::IntErrorKind;
+use std::ops::Range;
+
+/// What a placeholder other than a number one is replaced with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Name,
+    UpperName,
+    LowerName,
+    TitleName,
+    MockName,
+    Pronoun,
+    Possessive,
+    Company,
+    Language,
+    Verb,
+    Noun,
+    Adjective,
+    Ipv4,
+    Ipv6,
+    Uuid,
+    ShortUuid,
+    Path,
+    Url,
+    Version,
+    Swear,
+    Quote,
+}
+
+/// Every placeholder but the number ones, with what it is replaced with. Scanning and
+/// rendering templates, the corpus heatmap and the man page all go by this table.
+pub const PLACEHOLDERS: [(&str, Kind, &str); 21] = [
+    (
+        "XNAMEX",
+        Kind::Name,
+        "A random name from the names file, as-is.",
+    ),
+    (
+        "XUPPERNAMEX",
+        Kind::UpperName,
+        "The name in UPPERCASE.",
+    ),
+    (
+        "XLOWERNAMEX",
+        Kind::LowerName,
+        "The name in lowercase.",
+    ),
+    (
+        "XTITLENAMEX",
+        Kind::TitleName,
+        "The name in Title Case, with each word capitalized.",
+    ),
+    (
+        "XMOCKNAMEX",
+        Kind::MockName,
+        "The name in mocking alternating case, such as jOhN.",
+    ),
+    (
+        "XPRONOUNX",
+        Kind::Pronoun,
+        "The name's subject pronoun, such as she, or they when the names file gives none.",
+    ),
+    (
+        "XPOSSESSIVEX",
+        Kind::Possessive,
+        "The name's possessive pronoun, such as her, or their when the names file gives none.",
+    ),
+    (
+        "XCOMPANYX",
+        Kind::Company,
+        "A made-up company or product name, such as Cloudnado or Vertexforge Labs.",
+    ),
+    (
+        "XLANGX",
+        Kind::Language,
+        "A programming language or framework, such as Rust or Django. Lines of words/languages.txt in the config directory are added to the built-in list.",
+    ),
+    (
+        "XVERBX",
+        Kind::Verb,
+        "A verb in the imperative, such as refactor. A words/verbs.txt in the config directory replaces the built-in list.",
+    ),
+    (
+        "XNOUNX",
+        Kind::Noun,
+        "A noun, such as parser. A words/nouns.txt replaces the built-in list.",
+    ),
+    (
+        "XADJX",
+        Kind::Adjective,
+        "An adjective, such as flaky. A words/adjectives.txt replaces the built-in list.",
+    ),
+    (
+        "XIPX",
+        Kind::Ipv4,
+        "An IPv4 address from a block reserved for documentation, such as 192.0.2.17.",
+    ),
+    (
+        "XIP6X",
+        Kind::Ipv6,
+        "An IPv6 address from the documentation block 2001:db8::/32.",
+    ),
+    (
+        "XUUIDX",
+        Kind::Uuid,
+        "A random version 4 UUID, such as 3f2b8c1e-9d4a-4e7b-a1c2-5d6e7f809a1b.",
+    ),
+    (
+        "XSHORTUUIDX",
+        Kind::ShortUuid,
+        "The first 8 hex digits of a random UUID, such as 3f2b8c1e.",
+    ),
+    (
+        "XPATHX",
+        Kind::Path,
+        "A made-up source path such as src/utils/legacy/do_not_touch.rs, or with --real-paths a file tracked in the current git repository.",
+    ),
+    (
+        "XURLX",
+        Kind::Url,
+        "A believable link that doesn't resolve, such as a Q&A question or an internal wiki page under the reserved .example and .invalid domains.",
+    ),
+    (
+        "XVERSIONX",
+        Kind::Version,
+        "A version number such as 2.13.4, or the one given to release-notes --version.",
+    ),
+    (
+        "XSWEARX",
+        Kind::Swear,
+        "A mild swear word, a censored one such as %$#@! with --spice censored, or a strong one with --spice strong.",
+    ),
+    (
+        "XQUOTEX",
+        Kind::Quote,
+        "A short quote from a built-in list, or from the fortune file given with --quotes.",
+    ),
+];
+
+/// The placeholder of `PLACEHOLDERS` at the start of `rest`, if any.
+pub fn known(rest: &str) -> Option<(&'static str, Kind)> {
+    PLACEHOLDERS
+        .iter()
+        .find(|(placeholder, ..)| rest.starts_with(placeholder))
+        .map(|&(placeholder, kind, _)| (placeholder, kind))
+}
+
+/// What `scan` looks for: `XNUM`, `{{`, and enough of each placeholder to catch it
+/// misspelled, which is all of it but its last letter and closing `X`, keeping at least
+/// two letters. A start another one begins with is left out.
+pub fn starts() -> Vec<&'static str> {
+    let mut starts = vec!["XNUM"];
+    for (placeholder, ..) in PLACEHOLDERS {
+        let start = &placeholder[..(placeholder.len() - 2).max("XAB".len())];
+        if !starts.iter().any(|s| start.starts_with(s)) {
+            starts.retain(|s| !s.starts_with(start));
+            starts.push(start);
+        }
+    }
+    starts.push("{{");
+    starts
+}
+
+/// A placeholder found in a template, or something that looks like one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placeholder {
+    /// Byte range of the placeholder in the template.
+    pub range: Range<usize>,
+    /// What is wrong with it, if anything.
+    pub problem: Option<String>,
+    /// Whether the placeholder is used anyway, as documented, so its problem is only worth
+    /// reporting when linting, like a range whose end is below its start.
+    pub lint_only: bool,
+}
+
+/// Largest distance from zero a bound of a number placeholder can have.
+const LIMIT: i128 = u64::MAX as i128;
+
+/// The numbers a number placeholder can produce: the multiples of `step` from `first` to
+/// `last`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumberRange {
+    pub first: i128,
+    pub last: i128,
+    pub step: u64,
+    /// How the numbers are spread over the range.
+    pub distribution: Distribution,
+    /// How the number is written in the message.
+    pub format: NumberFormat,
+    /// Whether both bounds were written with the start above the end, so the range was
+    /// adjusted.
+    pub reversed: bool,
+}
+
+/// How the numbers of a number placeholder are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Distribution {
+    /// Every number equally likely
+    #[default]
+    Uniform,
+    /// Every order of magnitude equally likely, `:log`, so `XNUM1,100000:logX` gives as many
+    /// two-digit numbers as five-digit ones
+    Log,
+    /// A bell curve around the middle of the range, `:normal`
+    Normal,
+}
+
+/// How a number placeholder writes its number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberFormat {
+    /// Decimal digits
+    #[default]
+    Plain,
+    /// Roman numerals, `:roman`, for numbers from 1 to 3999
+    Roman,
+    /// English ordinals, `:ordinal`, such as 3rd or 21st
+    Ordinal,
+    /// A percentage, `:percent`, such as 42%
+    Percent,
+    /// Dollars and cents with thousands separators, `:currency`, such as $1,234.56
+    Currency,
+}
+
+/// Shows the numbers a range draws, such as `1..100` or `0..100 step 10, log`, leaving out
+/// how they're written.
+impl fmt::Display for NumberRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.first, self.last)?;
+        if self.step != 1 {
+            write!(f, " step {}", self.step)?;
+        }
+        match self.distribution {
+            Distribution::Uniform => Ok(()),
+            Distribution::Log => f.write_str(", log"),
+            Distribution::Normal => f.write_str(", normal"),
+        }
+    }
+}
+
+/// Largest number Roman numerals can write without overlines.
+pub const ROMAN_LIMIT: i128 = 3999;
+
+/// Parses a number placeholder, the part between `XNUM` and the closing `X`: a range with
+/// optional bounds, then optional modifiers, each after a colon: a `:step`, a
+/// distribution, `:log` or `:normal`, and a format, `:roman`, `:ordinal`, `:percent` or
+/// `:currency`.
+///
+/// If start > end, end is set to start * 2, or for a start of zero or below the bounds are
+/// swapped.
+///
+/// # Returns
+/// The range, or what is wrong with it. Bounds that don't fit, extra commas and steps with
+/// no multiple in the range are reported rather than replaced with a guess.
+///
+/// # Examples
+/// - "" -> 1 to 999 - default range
+/// - "10" -> 1 to 10 - simple upper limit
+/// - "1,5" -> 1 to 5 - explicit range
+/// - ",5" -> 1 to 5 - range with default start
+/// - "5," -> 5 to 999 - range with default end
+/// - "-10" -> -10 to -1 - negative limit
+/// - "-50,50" -> -50 to 50 - negative bounds
+/// - "0,100:10" -> 0 to 100 in multiples of 10
+/// - "1,100000:log" -> 1 to 100000, log-uniform
+/// - "3,20:roman" -> 3 to 20, as Roman numerals
+pub fn parse_number_range(spec: &str) -> Result<NumberRange, String> {
+    let bound = |text: &str, default: i128| -> Result<i128, String> {
+        if text.is_empty() {
+            return Ok(default);
+        }
+        let too_large = || format!("{} is too large; the limit is {}", text, LIMIT);
+        let too_small = || format!("{} is too small; the limit is -{}", text, LIMIT);
+        match text.parse::<i128>() {
+            Ok(n) if n > LIMIT => Err(too_large()),
+            Ok(n) if n < -LIMIT => Err(too_small()),
+            Ok(n) => Ok(n),
+            Err(e) => Err(match e.kind() {
+                IntErrorKind::PosOverflow => too_large(),
+                IntErrorKind::NegOverflow => too_small(),
+                _ => format!("`{}` is not a number", text),
+            }),
+        }
+    };
+    let mut modifiers = spec.split(':');
+    let bounds = modifiers.next().unwrap_or_default();
+    let (mut step, mut distribution, mut format) = (None, None, None);
+    for modifier in modifiers {
+        let repeated = match modifier {
+            "log" => distribution.replace(Distribution::Log).is_some(),
+            "normal" => distribution.replace(Distribution::Normal).is_some(),
+            "roman" => format.replace(NumberFormat::Roman).is_some(),
+            "ordinal" => format.replace(NumberFormat::Ordinal).is_some(),
+            "percent" => format.replace(NumberFormat::Percent).is_some(),
+            "currency" => format.replace(NumberFormat::Currency).is_some(),
+            _ if modifier.starts_with(|c: char| c.is_ascii_lowercase()) => {
+                return Err(format!(
+                    "unknown modifier `{}`; use a step, log, normal, roman, ordinal, percent or currency",
+                    modifier
+                ))
+            }
+            _ => match modifier.parse::<u64>() {
+                Ok(0) => return Err("the step after the colon must be at least 1".to_string()),
+                Ok(n) => step.replace(n).is_some(),
+                Err(_) => return Err(format!("the step `{}` is not a whole number", modifier)),
+            },
+        };
+        if repeated {
+            return Err(format!("`{}` repeats a modifier given already", modifier));
+        }
+    }
+    let step = step.unwrap_or(1);
+    let distribution = distribution.unwrap_or_default();
+    let format = format.unwrap_or_default();
+    let (start, end, explicit) = match bounds.split_once(',') {
+        // A single negative bound mirrors a positive one, so -10 means -10 to -1
+        None => match bound(bounds, 999)? {
+            end if end < 0 => (end, -1, false),
+            end => (1, end, false),
+        },
+        Some((_, end)) if end.contains(',') => {
+            return Err("a range has a single comma between its bounds".to_string())
+        }
+        Some((start, end)) => (
+            bound(start, 1)?,
+            bound(end, 999)?,
+            !start.is_empty() && !end.is_empty(),
+        ),
+    };
+    let reversed = start > end;
+    let (start, end) = match (reversed, start > 0) {
+        (false, _) => (start, end),
+        (true, true) => (start, (start * 2).min(LIMIT)),
+        (true, false) => (end, start),
+    };
+    let step_i = i128::from(step);
+    // Round the start up and the end down to multiples of the step
+    let first = -(-start).div_euclid(step_i) * step_i;
+    let last = end.div_euclid(step_i) * step_i;
+    if first > last {
+        return Err(format!(
+            "there is no multiple of {} from {} to {}",
+            step, start, end
+        ));
+    }
+    if distribution == Distribution::Log && first < 1 {
+        return Err("a log distribution needs a range above zero".to_string());
+    }
+    if format == NumberFormat::Roman && (first < 1 || last > ROMAN_LIMIT) {
+        return Err(format!(
+            "Roman numerals need a range from 1 to {}",
+            ROMAN_LIMIT
+        ));
+    }
+    Ok(NumberRange {
+        first,
+        last,
+        step,
+        distribution,
+        format,
+        reversed: explicit && reversed,
+    })
+}
+
+/// A directive, the part of a template between `{{` and `}}`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Directive<'a> {
+    /// `set NAME = num:RANGE`: draws a number once, writing nothing, so it can be used
+    /// later. The range is written like that of a number placeholder, with `..` allowed
+    /// for the comma, as in `num:1..10` or `num:1..1000:log`.
+    Set { name: &'a str, range: NumberRange },
+    /// `NAME`, or `NAME*K`, `NAME+K` or `NAME-K`: a number set earlier, maybe worked on.
+    Get {
+        name: &'a str,
+        op: Option<(char, i128)>,
+    },
+    /// `N%: TEXT`: writes `TEXT`, which may hold placeholders, `N` times in a hundred.
+    /// The one space after the colon is left out.
+    Maybe { chance: u8, body: &'a str },
+    /// `repeat N: TEXT`: writes `TEXT` `N` times, drawing its placeholders anew each time.
+    /// Text after the last placeholder separates the items and is left off the last one.
+    Repeat { count: u8, body: &'a str },
+    /// `spice: LEVEL` or `rating: LEVEL`: writes nothing, but keeps the template out unless
+    /// `--spice` allows `LEVEL`, such as `mild`, `medium`, `savage` or the ratings `G`, `PG` and `R`.
+    Spice(Spice),
+    /// `max-len: N`: writes nothing, but a message from the template longer than `N`
+    /// characters is drawn again.
+    MaxLen(usize),
+    /// `requires: PLACEHOLDER`: writes nothing, but optional text holding `PLACEHOLDER` is
+    /// always written, so every message from the template has it.
+    Requires(&'a str),
+    /// `no-conventional`: writes nothing, but keeps the template out when messages have to
+    /// be Conventional Commits, for `--cz` and `--commitlint`.
+    NoConventional,
+}
+
+/// The most times a `repeat` directive may write its text.
+const REPEAT_LIMIT: u8 = 100;
+
+/// Whether `name` can name a template variable: lowercase letters, digits and
+/// underscores, not starting with a digit.
+fn is_variable(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Parses the inside of a directive, without its braces.
+pub fn parse_directive(text: &str) -> Result<Directive<'_>, String> {
+    if let Some((head, body)) = text.split_once(':') {
+        if let Some(count) = head.trim().strip_prefix("repeat ") {
+            let count = count
+                .trim()
+                .parse::<u8>()
+                .ok()
+                .filter(|&c| c <= REPEAT_LIMIT)
+                .ok_or_else(|| {
+                    format!(
+                        "`{}` is not a count from 0 to {}",
+                        count.trim(),
+                        REPEAT_LIMIT
+                    )
+                })?;
+            return Ok(Directive::Repeat {
+                count,
+                body: body_of(body)?,
+            });
+        }
+        if matches!(head.trim(), "spice" | "rating") {
+            let level = body.trim();
+            return Spice::from_str(level, true)
+                .map(Directive::Spice)
+                .map_err(|_| {
+                    format!(
+                        "`{}` is not a spice level; use censored, mild, medium, savage, G, PG or R",
+                        level
+                    )
+                });
+        }
+        if head.trim() == "max-len" {
+            let len = body.trim();
+            return (len.parse::<usize>().ok())
+                .filter(|&len| len > 0)
+                .map(Directive::MaxLen)
+                .ok_or_else(|| format!("`{}` is not a length of at least 1", len));
+        }
+        if head.trim() == "requires" {
+            let placeholder = body.trim();
+            let known = PLACEHOLDERS.iter().any(|(p, ..)| *p == placeholder)
+                || matchers::number_spec(placeholder)
+                    .is_some_and(|spec| spec.len() + "XNUMX".len() == placeholder.len());
+            if !known {
+                return Err(format!("`{}` is not a placeholder", placeholder));
+            }
+            return Ok(Directive::Requires(placeholder));
+        }
+        if let Some(chance) = head.trim().strip_suffix('%') {
+            let chance = chance
+                .parse::<u8>()
+                .ok()
+                .filter(|&c| c <= 100)
+                .ok_or_else(|| format!("the chance `{}%` must be from 0% to 100%", chance))?;
+            return Ok(Directive::Maybe {
+                chance,
+                body: body_of(body)?,
+            });
+        }
+    }
+    let text = text.trim();
+    if text == "no-conventional" {
+        return Ok(Directive::NoConventional);
+    }
+    if let Some(rest) = text.strip_prefix("set ") {
+        let (name, value) = rest
+            .split_once('=')
+            .ok_or("a set directive looks like {{set n = num:1..10}}")?;
+        let name = name.trim();
+        if !is_variable(name) {
+            return Err(format!("`{}` is not a variable name", name));
+        }
+        let spec = value
+            .trim()
+            .strip_prefix("num:")
+            .ok_or("variables hold numbers, set with num:START..END")?;
+        let range = parse_number_range(&spec.replacen("..", ",", 1))?;
+        return Ok(Directive::Set { name, range });
+    }
+    let (name, op) = match text.find(['*', '+', '-']) {
+        None => (text, None),
+        Some(at) => {
+            let operand = text[at + 1..].trim();
+            let operand = operand
+                .parse::<i128>()
+                .ok()
+                .filter(|n| n.abs() <= LIMIT)
+                .ok_or_else(|| format!("`{}` is not a number to work with", operand))?;
+            let op = text[at..].chars().next().unwrap_or('+');
+            (text[..at].trim(), Some((op, operand)))
+        }
+    };
+    if !is_variable(name) {
+        return Err(format!("unknown directive `{}`", text));
+    }
+    Ok(Directive::Get { name, op })
+}
+
+/// The text a directive writes, after the colon and the space following it.
+fn body_of(text: &str) -> Result<&str, String> {
+    if text.contains("{{") {
+        return Err("directives can't be nested".to_string());
+    }
+    Ok(text.strip_prefix(' ').unwrap_or(text))
+}
+
+/// Reads the directive at the start of `rest`, which begins with `{{`, checking that any
+/// variable it uses is among those `defined` so far and adding any it sets.
+///
+/// # Returns
+/// The length of the directive with its braces and the directive, or what is wrong with it.
+pub fn directive_at<'a>(
+    rest: &'a str,
+    defined: &mut Vec<&'a str>,
+) -> (usize, Result<Directive<'a>, String>) {
+    let inner = &rest[2..];
+    let Some(end) = inner.find("}}") else {
+        return (
+            rest.len(),
+            Err("this `{{` is never closed with `}}`".to_string()),
+        );
+    };
+    let directive = parse_directive(&inner[..end]).and_then(|directive| {
+        match directive {
+            Directive::Set { name, .. } => defined.push(name),
+            Directive::Get { name, .. } if !defined.contains(&name) => {
+                return Err(format!("`{}` is used before it is set", name))
+            }
+            Directive::Get { .. }
+            | Directive::Maybe { .. }
+            | Directive::Repeat { .. }
+            | Directive::Spice(_)
+            | Directive::MaxLen(_)
+            | Directive::Requires(_)
+            | Directive::NoConventional => {}
+        }
+        Ok(directive)
+    });
+    (end + 4, directive)
+}
+
+/// Checks the range of a well-formed `XNUM...X` placeholder, and whether it is used
+/// despite the problem.
+fn number_problem(spec: &str) -> (Option<String>, bool) {
+    match parse_number_range(spec) {
+        Err(problem) => (Some(problem), false),
+        Ok(range) if range.reversed => (
+            Some(format!(
+                "range {} ends below its start; commas always separate the bounds",
+                spec
+            )),
+            true,
+        ),
+        Ok(_) => (None, false),
+    }
+}
+
+/// The patterns of the placeholder grammar. Builds without the `regex` feature use the
+/// hand-written `scanner` instead, which must find the same things.
+#[cfg(feature = "regex")]
+pub(crate) mod matchers {
+    use super::starts;
+    use regex_lite::Regex;
+    use std::ops::Range;
+    use std::sync::OnceLock;
+
+    /// The first start of a placeholder or directive at or after byte `at`.
+    pub fn next_start(text: &str, at: usize) -> Option<Range<usize>> {
+        static START: OnceLock<Regex> = OnceLock::new();
+        let start_re = START.get_or_init(|| {
+            let starts: Vec<String> = starts().into_iter().map(regex_lite::escape).collect();
+            Regex::new(&starts.join("|")).unwrap()
+        });
+        start_re.find_at(text, at).map(|m| m.range())
+    }
+
+    /// The spec of a well-formed number placeholder at the start of `rest`.
+    pub fn number_spec(rest: &str) -> Option<&str> {
+        static NUMBER: OnceLock<Regex> = OnceLock::new();
+        let number_re = NUMBER.get_or_init(|| Regex::new(r"^XNUM([0-9a-z,:-]*)X").unwrap());
+        Some(number_re.captures(rest)?.get(1)?.as_str())
+    }
+
+    /// The length of the `X` and capital letters at the start of `rest`.
+    pub fn junk_len(rest: &str) -> Option<usize> {
+        static JUNK: OnceLock<Regex> = OnceLock::new();
+        let junk_re = JUNK.get_or_init(|| Regex::new(r"^X[A-Z]*").unwrap());
+        junk_re.find(rest).map(|m| m.len())
+    }
+}
+
+/// Finds every placeholder and directive in a template, flagging malformed ones such as
+/// `XNUM1-5X`, `XNAME` without its closing `X`, a misspelled `XNAMX` or `{{n}}` before
+/// `n` is set.
+pub fn scan(template: &str) -> Vec<Placeholder> {
+    let mut placeholders = Vec::new();
+    let mut defined = Vec::new();
+    // The `requires` directives, by where they are in `placeholders`
+    let mut required = Vec::new();
+    let mut at = 0;
+    while let Some(found) = matchers::next_start(template, at) {
+        let rest = &template[found.start..];
+        let mut lint_only = false;
+        let (len, problem) = if rest.starts_with("{{") {
+            let (len, directive) = directive_at(rest, &mut defined);
+            if let Ok(Directive::Maybe { body, .. } | Directive::Repeat { body, .. }) = directive {
+                // The body is a slice of the template, so its placeholders are reported in
+                // place, between the directive's opening and closing
+                let start = body.as_ptr() as usize - template.as_ptr() as usize;
+                let end = start + body.len();
+                placeholders.push(Placeholder {
+                    range: found.start..start,
+                    problem: None,
+                    lint_only: false,
+                });
+                placeholders.extend(scan(body).into_iter().map(|p| Placeholder {
+                    range: p.range.start + start..p.range.end + start,
+                    ..p
+                }));
+                placeholders.push(Placeholder {
+                    range: end..found.start + len,
+                    problem: None,
+                    lint_only: false,
+                });
+                at = found.start + len;
+                continue;
+            }
+            if let Ok(Directive::Requires(placeholder)) = directive {
+                required.push((placeholders.len(), placeholder));
+            }
+            (len, directive.err())
+        } else if let Some(spec) = matchers::number_spec(rest) {
+            let problem;
+            (problem, lint_only) = number_problem(spec);
+            (spec.len() + "XNUMX".len(), problem)
+        } else if rest.starts_with("XNUM") {
+            let len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let problem =
+                "number placeholders look like XNUMX, XNUM10X, XNUM-5,5X or XNUM0,100:10X";
+            (len, Some(problem.to_string()))
+        } else if let Some((name, _)) = known(rest) {
+            (name.len(), None)
+        } else {
+            let len = matchers::junk_len(rest).unwrap_or(found.len());
+            let names: Vec<&str> = PLACEHOLDERS.iter().map(|(p, ..)| *p).collect();
+            let (last, others) = names.split_last().unwrap();
+            let problem = format!(
+                "unknown placeholder; the others are {} and {}",
+                others.join(", "),
+                last
+            );
+            (len, Some(problem))
+        };
+        let range = found.start..found.start + len;
+        at = range.end;
+        placeholders.push(Placeholder {
+            range,
+            problem,
+            lint_only,
+        });
+    }
+    for (i, placeholder) in required {
+        if !placeholders
+            .iter()
+            .any(|p| &template[p.range.clone()] == placeholder)
+        {
+            placeholders[i].problem = Some(format!("the template never writes {}", placeholder));
+        }
+    }
+    placeholders
+}
+
+/// What a template's directives that write nothing say about where it may be used.
+#[derive(Debug, Default, PartialEq)]
+pub struct Tags<'a> {
+    /// The highest level its `{{spice: LEVEL}}` directives give.
+    pub spice: Option<Spice>,
+    /// The lowest length its `{{max-len: N}}` directives give.
+    pub max_len: Option<usize>,
+    /// The placeholders its `{{requires: PLACEHOLDER}}` directives name.
+    pub requires: Vec<&'a str>,
+    /// Whether it has a `{{no-conventional}}` directive.
+    pub no_conventional: bool,
+}
+
+/// Reads the tags of a template, skipping any directive that can't be read.
+pub fn tags(template: &str) -> Tags<'_> {
+    let mut tags = Tags::default();
+    if !template.contains("{{") {
+        return tags;
+    }
+    let mut defined = Vec::new();
+    let mut at = 0;
+    while let Some(start) = template[at..].find("{{").map(|i| at + i) {
+        let (len, directive) = directive_at(&template[start..], &mut defined);
+        match directive {
+            Ok(Directive::Spice(spice)) => tags.spice = tags.spice.max(Some(spice)),
+            Ok(Directive::MaxLen(len)) => {
+                tags.max_len = Some(tags.max_len.map_or(len, |max| max.min(len)))
+            }
+            Ok(Directive::Requires(placeholder)) => tags.requires.push(placeholder),
+            Ok(Directive::NoConventional) => tags.no_conventional = true,
+            _ => {}
+        }
+        at = start + len.max(2);
+    }
+    tags
+}
+
+/// How spicy a template is tagged as: the highest level its `{{spice: LEVEL}}` directives
+/// give, or `None` without one.
+pub fn spice(template: &str) -> Option<Spice> {
+    tags(template).spice
+}
+
+/// Fails the test unless every one of `templates`, from the embedded corpus `name`, lints
+/// clean.
+#[cfg(test)]
+pub(crate) fn assert_lints_clean<T: AsRef<str>>(
+    name: &str,
+    templates: impl IntoIterator<Item = T>,
+) {
+    for template in templates {
+        let template = template.as_ref();
+        assert_eq!(
+            problems(template),
+            Vec::<String>::new(),
+            "{}: {}",
+            name,
+            template
+        );
+    }
+}
+
+/// Describes every problem in a template, with its one-based column.
+pub fn problems(template: &str) -> Vec<String> {
+    scan(template)
+        .into_iter()
+        .filter_map(|p| {
+            let column = template[..p.range.start].chars().count() + 1;
+            let problem = p.problem?;
+            Some(format!(
+                "col {}: `{}`: {}",
+                column, &template[p.range], problem
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_every_placeholder_is_scanned() {
+        for (placeholder, ..) in PLACEHOLDERS {
+            let template = format!("fixed {} twice", placeholder);
+            let found = scan(&template);
+            assert_eq!(found.len(), 1, "{}", placeholder);
+            assert_eq!(&template[found[0].range.clone()], placeholder);
+            assert_eq!(found[0].problem, None, "{}", placeholder);
+            // Misspelled with a letter too many, it is still caught
+            let misspelled = format!("{}QX", &placeholder[..placeholder.len() - 1]);
+            let found = scan(&misspelled);
+            assert!(
+                found.len() == 1 && found[0].problem.is_some(),
+                "{} isn't flagged",
+                misspelled
+            );
+        }
+    }
+
+    #[test]
+    fn t_scan_valid_placeholders() {
+        let template = "XNAMEX fixed XNUM1,5X bugs for XUPPERNAMEX";
+        let found = scan(template);
+        let texts: Vec<&str> = found.iter().map(|p| &template[p.range.clone()]).collect();
+        assert_eq!(texts, vec!["XNAMEX", "XNUM1,5X", "XUPPERNAMEX"]);
+        assert!(found.iter().all(|p| p.problem.is_none()));
+        assert!(scan("XNAMEX's fault").iter().all(|p| p.problem.is_none()));
+    }
+
+    #[test]
+    fn t_scan_flags_malformed() {
+        assert_eq!(
+            problems("fixed XNUM1.5X bugs"),
+            vec![
+                "col 7: `XNUM1.5X`: number placeholders look like XNUMX, XNUM10X, XNUM-5,5X or XNUM0,100:10X"
+            ]
+        );
+        let flagged = |template: &str| -> Vec<String> {
+            scan(template)
+                .into_iter()
+                .filter(|p| p.problem.is_some())
+                .map(|p| template[p.range].to_string())
+                .collect()
+        };
+        assert_eq!(flagged("blame XNAMX today"), vec!["XNAMX"]);
+        assert_eq!(flagged("blame XNAME today"), vec!["XNAME"]);
+        assert_eq!(flagged("moved to XCOMPANY today"), vec!["XCOMPANY"]);
+        assert!(flagged("moved to XCOMPANYX today").is_empty());
+        assert_eq!(flagged("in XLANG again"), vec!["XLANG"]);
+        assert_eq!(flagged("XVERBX the XADJ XNOUNX"), vec!["XADJ"]);
+        assert!(flagged("hardcoded XIPX and XIP6X").is_empty());
+        assert_eq!(flagged("hardcoded XIP4X"), vec!["XIP"]);
+        assert!(flagged("flag XUUIDX and XSHORTUUIDX").is_empty());
+        assert_eq!(flagged("flag XUUID"), vec!["XUUID"]);
+        assert_eq!(flagged("XNUM1,000X lines"), vec!["XNUM1,000X"]);
+        assert!(flagged("XNUM1000X lines").is_empty());
+        assert!(flagged("XNUM99999999999X lines").is_empty());
+        assert_eq!(
+            flagged("XNUM99999999999999999999X lines"),
+            vec!["XNUM99999999999999999999X"]
+        );
+        assert_eq!(flagged("XNUM1,2,3X lines"), vec!["XNUM1,2,3X"]);
+        assert_eq!(flagged("XNUM1-5X lines"), vec!["XNUM1-5X"]);
+        assert!(flagged("XNUM-50,50X and XNUM0,100:10X lines").is_empty());
+        assert_eq!(flagged("XNUM1,5:10X lines"), vec!["XNUM1,5:10X"]);
+        assert_eq!(flagged("XNUM0,100:0X lines"), vec!["XNUM0,100:0X"]);
+    }
+
+    #[test]
+    fn t_directives() {
+        assert_eq!(
+            parse_directive("set n = num:1..10:log").map(|d| match d {
+                Directive::Set { name, range } => (name, range.first, range.last),
+                _ => unreachable!(),
+            }),
+            Ok(("n", 1, 10))
+        );
+        assert_eq!(
+            parse_directive(" hours_2 * 10 "),
+            Ok(Directive::Get {
+                name: "hours_2",
+                op: Some(('*', 10))
+            })
+        );
+        assert_eq!(
+            parse_directive("n-1"),
+            Ok(Directive::Get {
+                name: "n",
+                op: Some(('-', 1))
+            })
+        );
+        assert!(parse_directive("set N = num:1..10").is_err());
+        assert!(parse_directive("set n = word:verb").is_err());
+        assert!(parse_directive("n*many").is_err());
+        assert!(parse_directive("hello world").is_err());
+        assert_eq!(
+            parse_directive("50%: , I think"),
+            Ok(Directive::Maybe {
+                chance: 50,
+                body: ", I think"
+            })
+        );
+        assert!(parse_directive("150%: always")
+            .unwrap_err()
+            .contains("from 0% to 100%"));
+        assert_eq!(
+            parse_directive("repeat 3: XPATHX, "),
+            Ok(Directive::Repeat {
+                count: 3,
+                body: "XPATHX, "
+            })
+        );
+        assert!(parse_directive("repeat 500: x").is_err());
+        assert!(parse_directive("repeat many: x").is_err());
+        assert_eq!(
+            parse_directive("spice: savage"),
+            Ok(Directive::Spice(Spice::Strong))
+        );
+        assert!(parse_directive("spice: hot")
+            .unwrap_err()
+            .contains("spice level"));
+        assert_eq!(
+            spice("{{spice: medium}}Damn {{rating: g}}it"),
+            Some(Spice::Medium)
+        );
+        assert_eq!(spice("Fixed it"), None);
+        let flagged: Vec<String> = scan("fixed{{30%: XNAM's}} it")
+            .into_iter()
+            .filter(|p| p.problem.is_some())
+            .map(|p| "fixed{{30%: XNAM's}} it"[p.range].to_string())
+            .collect();
+        assert_eq!(flagged, ["XNAM"]);
+    }
+
+    #[test]
+    fn t_constraint_tags() {
+        assert_eq!(parse_directive("max-len: 40"), Ok(Directive::MaxLen(40)));
+        assert!(parse_directive("max-len: 0").is_err());
+        assert_eq!(
+            parse_directive("requires: XNUM1,5X"),
+            Ok(Directive::Requires("XNUM1,5X"))
+        );
+        assert!(parse_directive("requires: XNAM")
+            .unwrap_err()
+            .contains("not a placeholder"));
+        assert_eq!(
+            parse_directive(" no-conventional "),
+            Ok(Directive::NoConventional)
+        );
+
+        let template = "{{max-len: 50}}{{no-conventional}}{{max-len: 30}}fix{{50%: XNAMEX}}{{requires: XNAMEX}}";
+        assert_eq!(
+            tags(template),
+            Tags {
+                spice: None,
+                max_len: Some(30),
+                requires: vec!["XNAMEX"],
+                no_conventional: true,
+            }
+        );
+        assert_eq!(tags("Fixed it"), Tags::default());
+        assert!(problems(template).is_empty());
+        assert!(problems("{{requires: XQUOTEX}}{{50%: as they say, XQUOTEX}}").is_empty());
+        assert_eq!(
+            problems("{{requires: XQUOTEX}}XUPPERNAMEX said so"),
+            vec!["col 1: `{{requires: XQUOTEX}}`: the template never writes XQUOTEX"]
+        );
+        assert_eq!(
+            problems("XQUOTX")
+                .iter()
+                .map(|p| p.split(';').next().unwrap())
+                .collect::<Vec<_>>(),
+            ["col 1: `XQUOTX`: unknown placeholder"]
+        );
+    }
+
+    #[test]
+    fn t_directive_problems() {
+        assert_eq!(
+            problems("{{n}} {{set n = num:1..5}}{{n+1}} {{set m = num:1..5:10}} {{m"),
+            vec![
+                "col 1: `{{n}}`: `n` is used before it is set",
+                "col 35: `{{set m = num:1..5:10}}`: there is no multiple of 10 from 1 to 5",
+                "col 59: `{{m`: this `{{` is never closed with `}}`",
+            ]
+        );
+    }
+
+    #[test]
+    fn t_parse_number_range() {
+        let range = |first, last, step| NumberRange {
+            first,
+            last,
+            step,
+            distribution: Distribution::Uniform,
+            format: NumberFormat::Plain,
+            reversed: false,
+        };
+        assert_eq!(parse_number_range(""), Ok(range(1, 999, 1)));
+        assert_eq!(parse_number_range("10"), Ok(range(1, 10, 1)));
+        assert_eq!(parse_number_range(",5"), Ok(range(1, 5, 1)));
+        assert_eq!(parse_number_range("5,"), Ok(range(5, 999, 1)));
+        assert_eq!(parse_number_range(","), Ok(range(1, 999, 1)));
+        assert_eq!(
+            parse_number_range("1,1000000000000"),
+            Ok(range(1, 1_000_000_000_000, 1))
+        );
+        assert_eq!(
+            parse_number_range("18446744073709551615"),
+            Ok(range(1, u64::MAX.into(), 1))
+        );
+        assert!(parse_number_range("18446744073709551616")
+            .unwrap_err()
+            .contains("too large"));
+        assert!(parse_number_range("1,x")
+            .unwrap_err()
+            .contains("not a number"));
+
+        assert_eq!(parse_number_range("-50,50"), Ok(range(-50, 50, 1)));
+        assert_eq!(parse_number_range("-5"), Ok(range(-5, -1, 1)));
+        assert_eq!(parse_number_range("0,100:10"), Ok(range(0, 100, 10)));
+        assert_eq!(parse_number_range("-15,15:10"), Ok(range(-10, 10, 10)));
+        assert_eq!(parse_number_range(":5"), Ok(range(5, 995, 5)));
+        assert_eq!(
+            parse_number_range("10,5"),
+            Ok(NumberRange {
+                reversed: true,
+                ..range(10, 20, 1)
+            })
+        );
+        let reversed = scan("Fixed XNUM10,5X bugs");
+        assert!(reversed[0].problem.is_some() && reversed[0].lint_only);
+        assert!(!scan("Fixed XNUM1,2,3X bugs")[0].lint_only);
+        assert_eq!(
+            parse_number_range("-5,-10"),
+            Ok(NumberRange {
+                reversed: true,
+                ..range(-10, -5, 1)
+            })
+        );
+        assert!(parse_number_range("-18446744073709551616")
+            .unwrap_err()
+            .contains("too small"));
+        assert!(parse_number_range("1,5:10")
+            .unwrap_err()
+            .contains("no multiple of 10 from 1 to 5"));
+        assert!(parse_number_range("1,5:")
+            .unwrap_err()
+            .contains("not a whole number"));
+
+        let log = parse_number_range("1,100000:log").unwrap();
+        assert_eq!((log.last, log.distribution), (100_000, Distribution::Log));
+        let normal = parse_number_range("0,100:10:normal").unwrap();
+        assert_eq!(
+            (normal.step, normal.distribution),
+            (10, Distribution::Normal)
+        );
+        assert!(parse_number_range("-5,5:log")
+            .unwrap_err()
+            .contains("above zero"));
+        assert!(parse_number_range("1,5:bell")
+            .unwrap_err()
+            .contains("unknown modifier `bell`"));
+        assert_eq!(
+            parse_number_range("3,20:roman").map(|r| r.format),
+            Ok(NumberFormat::Roman)
+        );
+        assert!(parse_number_range("1,9:roman:ordinal")
+            .unwrap_err()
+            .contains("repeats"));
+        assert!(parse_number_range("0,20:roman")
+            .unwrap_err()
+            .contains("from 1 to 3999"));
+        assert!(parse_number_range("1,50:log:normal")
+            .unwrap_err()
+            .contains("repeats"));
+    }
+
+    #[test]
+    fn t_scan_never_panics() {
+        // A cheap stand-in for the fuzz target: glue placeholder fragments together at
+        // random and check that every reported range can be sliced.
+        const PIECES: [&str; 12] = [
+            "X",
+            "NUM",
+            "NAME",
+            "UPPER",
+            "LOWER",
+            "9",
+            "99999999999",
+            ",",
+            "-",
+            " ",
+            "é",
+            "🦀",
+        ];
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        for _ in 0..2_000 {
+            let mut template = String::new();
+            for _ in 0..(state % 12) {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                template.push_str(PIECES[(state % PIECES.len() as u64) as usize]);
+            }
+            for placeholder in scan(&template) {
+                assert!(template.get(placeholder.range).is_some(), "{:?}", template);
+            }
+            problems(&template);
+            state = state.wrapping_add(1);
+        }
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn t_scanner_matches_patterns() {
+        use crate::scanner;
+        // Random glue as above, with the pieces that make the hand-written scanner branch
+        const PIECES: [&str; 16] = [
+            "X", "NUM", "NAM", "E", "UPPER", "SHORTUU", "IP", "6", "{{", "}}", ",", ":log", "-",
+            " ", "é", "xX",
+        ];
+        let mut state = 0x9e37_79b9_7f4a_7c15_u64;
+        for _ in 0..5_000 {
+            let mut template = String::new();
+            for _ in 0..(state % 10) {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                template.push_str(PIECES[(state % PIECES.len() as u64) as usize]);
+            }
+            for at in (0..=template.len()).filter(|&at| template.is_char_boundary(at)) {
+                let rest = &template[at..];
+                assert_eq!(
+                    scanner::next_start(&template, at),
+                    matchers::next_start(&template, at),
+                    "{:?} at {}",
+                    template,
+                    at
+                );
+                assert_eq!(scanner::number_spec(rest), matchers::number_spec(rest));
+                assert_eq!(scanner::junk_len(rest), matchers::junk_len(rest));
+            }
+            state = state.wrapping_add(1);
+        }
+    }
+
+    #[test]
+    fn t_scan_shared_across_threads() {
+        // The patterns are compiled once and shared, so scanning from many threads at
+        // once must find the same placeholders as scanning from one
+        let template = "XNAMEX bumped XNUM1,9X deps{{50%: for XCOMPANYX}} XNAM";
+        let expected = scan(template);
+        let threads: Vec<_> = (0..8)
+            .map(|_| std::thread::spawn(move || scan(template)))
+            .collect();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), expected);
+        }
+        assert_eq!(expected.len(), 6);
+        assert!(expected[5].problem.is_some());
+    }
+}
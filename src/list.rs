@@ -0,0 +1,138 @@
+use crate::lint::Directive;
+use crate::search;
+use crate::template::{Node, Template};
+use crate::words::List;
+use anyhow::Result;
+
+/// Returns the kinds of placeholders a template uses: `name`, `pronoun`, `company`, the word lists', `ip`,
+/// `uuid`, `path`, `url`, `version`,
+/// `variable`, `optional`, `repeat`, `swear`
+/// and `number`. Placeholders in optional and repeated text count too.
+pub fn placeholder_kinds(template: &str) -> Vec<&'static str> {
+    let template = Template::parse(template);
+    let mut nodes = template.nodes().to_vec();
+    for node in template.nodes() {
+        if let Node::Maybe { body, .. } | Node::Repeat { body, .. } = node {
+            nodes.extend_from_slice(body.nodes());
+        }
+    }
+    let mut kinds = Vec::new();
+    if nodes.iter().any(|n| matches!(n, Node::Name(_))) {
+        kinds.push("name");
+    }
+    if nodes
+        .iter()
+        .any(|n| matches!(n, Node::Pronoun | Node::Possessive))
+    {
+        kinds.push("pronoun");
+    }
+    if nodes.iter().any(|n| matches!(n, Node::Company)) {
+        kinds.push("company");
+    }
+    for list in List::ALL {
+        if nodes.contains(&Node::Word(list)) {
+            kinds.push(list.kind());
+        }
+    }
+    if nodes.iter().any(|n| matches!(n, Node::Ipv4 | Node::Ipv6)) {
+        kinds.push("ip");
+    }
+    if nodes
+        .iter()
+        .any(|n| matches!(n, Node::Uuid | Node::ShortUuid))
+    {
+        kinds.push("uuid");
+    }
+    if nodes.contains(&Node::Path) {
+        kinds.push("path");
+    }
+    if nodes.contains(&Node::Url) {
+        kinds.push("url");
+    }
+    if nodes.contains(&Node::Version) {
+        kinds.push("version");
+    }
+    if nodes
+        .iter()
+        .any(|n| matches!(n, Node::Directive(Directive::Set { .. })))
+    {
+        kinds.push("variable");
+    }
+    if nodes.iter().any(|n| matches!(n, Node::Maybe { .. })) {
+        kinds.push("optional");
+    }
+    if nodes.iter().any(|n| matches!(n, Node::Repeat { .. })) {
+        kinds.push("repeat");
+    }
+    if nodes.iter().any(|n| matches!(n, Node::Swear)) {
+        kinds.push("swear");
+    }
+    if nodes.contains(&Node::Quote) {
+        kinds.push("quote");
+    }
+    if nodes
+        .iter()
+        .any(|n| matches!(n, Node::Number(_) | Node::Malformed { .. }))
+    {
+        kinds.push("number");
+    }
+    kinds
+}
+
+/// Summarizes the kinds of placeholders a template uses.
+fn placeholders(template: &str) -> String {
+    let kinds = placeholder_kinds(template);
+    if kinds.is_empty() {
+        "-".to_string()
+    } else {
+        kinds.join(",")
+    }
+}
+
+/// Prints the templates with their indices and placeholder summaries.
+///
+/// Only templates containing every `filter` word are listed; `offset` and `limit`
+/// page through the result.
+pub fn run(
+    commit_messages: &[String],
+    filter: &[String],
+    offset: usize,
+    limit: Option<usize>,
+) -> Result<()> {
+    let width = commit_messages.len().saturating_sub(1).to_string().len();
+    commit_messages
+        .iter()
+        .enumerate()
+        .filter(|(_, template)| search::matches(template, filter))
+        .skip(offset)
+        .take(limit.unwrap_or(usize::MAX))
+        .for_each(|(index, template)| {
+            println!(
+                "{:>width$}  {:<11}  {}",
+                index,
+                placeholders(template),
+                template,
+                width = width
+            );
+        });
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_placeholder_summary() {
+        assert_eq!(
+            placeholders("XUPPERNAMEX did XNUM1,5X things"),
+            "name,number"
+        );
+        assert_eq!(placeholders("XNUMX% done"), "number");
+        assert_eq!(placeholders("XNAMEX left XCOMPANYX"), "name,company");
+        assert_eq!(placeholders("XVERBX the XNOUNX"), "verb,noun");
+        assert_eq!(placeholders("fixed{{50%: for XNAMEX}}"), "name,optional");
+        assert_eq!(placeholders("{{repeat 2: XPATHX }}"), "path,repeat");
+        assert_eq!(placeholders("it works"), "-");
+    }
+}
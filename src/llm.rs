@@ -0,0 +1,157 @@
+use crate::git;
+use crate::http;
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Deserialize;
+use tracing::{debug, info};
+
+/// Where Ollama listens unless `llm-url` says otherwise.
+pub const DEFAULT_URL: &str = "http://localhost:11434";
+
+/// The model asked unless `llm-model` or `--llm-model` names another.
+pub const DEFAULT_MODEL: &str = "llama3.2";
+
+/// Most of the staged diff summary sent along, so a huge change doesn't make a huge prompt.
+const MAX_STAT: usize = 2000;
+
+/// Longest reply taken as a commit message; anything longer is the model rambling.
+const MAX_REPLY: usize = 200;
+
+/// What a local language model does to the generated message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LlmMode {
+    /// Keep the joke, but make it vaguely about the staged changes
+    Rewrite,
+}
+
+/// How the model is reached.
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "network"), allow(dead_code))]
+pub struct Settings {
+    /// Base URL of the Ollama server.
+    pub url: String,
+    pub model: String,
+    /// Used as is, except that a slow model is never retried.
+    pub http: http::Settings,
+}
+
+/// The prompt asking for `message` to be made about the change `stat` summarizes.
+fn prompt(message: &str, stat: &str) -> String {
+    let stat = match stat.char_indices().nth(MAX_STAT) {
+        Some((end, _)) => &stat[..end],
+        None => stat,
+    };
+    format!(
+        "Here is a joke commit message:\n\n{}\n\n\
+         Here is a summary of the staged changes it will be committed with:\n\n{}\n\n\
+         Rewrite the commit message so it stays just as funny but is vaguely about these \
+         changes. Keep it to one line of similar length. Reply with the commit message \
+         only, without quotes or explanation.",
+        message, stat
+    )
+}
+
+/// The commit message in a model's `reply`: its first line, without any reasoning the
+/// model showed first or quotes it put around the message, or `None` if there isn't one
+/// that looks like a commit message.
+fn clean(reply: &str) -> Option<String> {
+    let reply = match reply.split_once("</think>") {
+        Some((_, answer)) => answer,
+        None => reply,
+    };
+    let line = reply.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let line = line.trim_matches(|c| matches!(c, '"' | '\'' | '`' | '“' | '”'));
+    let line = line.trim();
+    (!line.is_empty() && line.chars().count() <= MAX_REPLY).then(|| line.to_string())
+}
+
+/// What `/api/generate` answers with when not streaming.
+#[cfg(feature = "network")]
+#[derive(Deserialize)]
+struct Reply {
+    response: String,
+}
+
+#[cfg(feature = "network")]
+fn ask(prompt: &str, settings: &Settings) -> Result<String> {
+    let url = format!("{}/api/generate", settings.url.trim_end_matches('/'));
+    let body = serde_json::json!({
+        "model": settings.model,
+        "prompt": prompt,
+        "stream": false,
+    });
+    let http = http::Settings {
+        retries: 0,
+        ..settings.http.clone()
+    };
+    let reply = http::post_json_reply(&url, &body.to_string(), &http)?;
+    let reply: Reply = serde_json::from_slice(&reply)
+        .map_err(|e| anyhow::anyhow!("{} didn't answer like Ollama: {}", url, e))?;
+    Ok(reply.response)
+}
+
+#[cfg(not(feature = "network"))]
+fn ask(_prompt: &str, _settings: &Settings) -> Result<String> {
+    Err(crate::error::WtcError::Disabled {
+        what: "--llm",
+        feature: "network",
+    }
+    .into())
+}
+
+/// `message` as the model rewrites it for `mode`, or `message` itself when there's nothing
+/// staged, the model can't be reached in time or its answer isn't a commit message.
+pub fn apply(mode: LlmMode, message: &str, settings: &Settings) -> String {
+    let LlmMode::Rewrite = mode;
+    let stat = match git::staged_stat() {
+        Ok(stat) if !stat.is_empty() => stat,
+        Ok(_) => {
+            debug!("Nothing is staged, so not rewriting the message");
+            return message.to_string();
+        }
+        Err(e) => {
+            info!("Not rewriting the message: {:#}", e);
+            return message.to_string();
+        }
+    };
+    match ask(&prompt(message, &stat), settings).map(|reply| clean(&reply)) {
+        Ok(Some(rewritten)) => {
+            debug!(
+                "{} rewrote {:?} as {:?}",
+                settings.model, message, rewritten
+            );
+            rewritten
+        }
+        Ok(None) => {
+            info!("{} didn't answer with a commit message", settings.model);
+            message.to_string()
+        }
+        Err(e) => {
+            info!("Not rewriting the message: {:#}", e);
+            message.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_llm_replies() {
+        let prompt = prompt("Fixed it, Ada", " src/http.rs | 4 ++--");
+        assert!(prompt.contains("Fixed it, Ada") && prompt.contains("src/http.rs"));
+        assert!(super::prompt("m", &"x".repeat(5000)).len() < 2500);
+        assert_eq!(
+            clean("\n\"Taught http.rs to fix itself, Ada\"\n\nThis keeps the joke.").as_deref(),
+            Some("Taught http.rs to fix itself, Ada")
+        );
+        assert_eq!(
+            clean("<think>\nThey want a joke.\n</think>\n`retry harder`").as_deref(),
+            Some("retry harder")
+        );
+        assert_eq!(clean("  \n\"\"\n"), None);
+        assert_eq!(clean(&"a".repeat(MAX_REPLY + 1)), None);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::error::WtcError;
+use crate::paths;
+use anyhow::Result;
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// Put at the top of the lockfile, which is meant to be committed but not edited.
+const HEADER: &str = "# Written by whatthecommitcli; commit it, and check it with --locked\n";
+
+/// The content hash of every remote corpus source, by URL.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Lock {
+    #[serde(default)]
+    sources: BTreeMap<String, String>,
+}
+
+fn read(path: &Path) -> Result<Lock> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Lock::default()),
+        Err(source) => {
+            return Err(WtcError::IoError {
+                context: format!("Failed to read lockfile: {:?}", path),
+                source,
+            }
+            .into())
+        }
+    };
+    toml::from_str(&text).map_err(|e| anyhow::anyhow!("Lockfile {:?} isn't valid: {}", path, e))
+}
+
+fn write(path: &Path, lock: &Lock) -> Result<()> {
+    let text = format!("{}{}", HEADER, toml::to_string(lock)?);
+    let io_error = |source| WtcError::IoError {
+        context: format!("Failed to write lockfile: {:?}", path),
+        source,
+    };
+    // Outside a repository it goes into the config directory, which may not exist yet
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(io_error)?;
+    }
+    fs::write(path, text).map_err(io_error)?;
+    Ok(())
+}
+
+/// The SHA-256 of `bytes` in lowercase hex, as `sha256sum` writes it.
+pub fn sha256(bytes: &[u8]) -> String {
+    let sum = digest(&SHA256, bytes);
+    sum.as_ref().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// How `bytes` are recorded in the lockfile.
+fn hash(bytes: &[u8]) -> String {
+    format!("sha256:{}", sha256(bytes))
+}
+
+/// Checks the content `bytes` fetched from `url` against the lockfile at `path`. With
+/// `locked`, content the lockfile doesn't record, or records differently, is refused;
+/// without it, the lockfile is brought up to date, and only warned about if it can't be.
+pub fn check(path: &Path, url: &str, bytes: &[u8], locked: bool) -> Result<()> {
+    let mut lock = read(path)?;
+    let actual = hash(bytes);
+    let problem = match lock.sources.get(url) {
+        Some(expected) if *expected == actual => return Ok(()),
+        Some(_) => "has changed since it was locked",
+        None => "isn't locked",
+    };
+    if locked {
+        return Err(WtcError::Unlocked {
+            url: url.to_string(),
+            problem,
+            lockfile: path.to_path_buf(),
+        }
+        .into());
+    }
+    lock.sources.insert(url.to_string(), actual);
+    match write(path, &lock) {
+        Ok(()) => info!("Locked {} in {:?}", url, path),
+        Err(e) => warn!("{:#}", e),
+    }
+    Ok(())
+}
+
+/// Checks the content `bytes` fetched from `url` against the lockfile where
+/// `paths::lock_file` puts it, as [`check`] does.
+pub fn check_source(url: &str, bytes: &[u8], locked: bool) -> Result<()> {
+    match paths::lock_file() {
+        Some(path) => check(&path, url, bytes, locked),
+        None if locked => anyhow::bail!("Could not determine where the lockfile is"),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::paths::LOCK_FILE;
+    use std::env;
+
+    #[test]
+    fn t_lock_sources() {
+        let dir = env::temp_dir().join(format!("wtc-lock-{}", std::process::id()));
+        let path = dir.join(LOCK_FILE);
+        let url = "https://example.com/templates.txt";
+        assert!(check(&path, url, b"a\n", true).is_err());
+        assert!(!path.exists());
+
+        check(&path, url, b"a\n", false).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with(HEADER));
+        assert!(text.contains(&hash(b"a\n")));
+        check(&path, url, b"a\n", true).unwrap();
+
+        let changed = check(&path, url, b"b\n", true).unwrap_err();
+        assert!(matches!(
+            changed.downcast_ref::<WtcError>(),
+            Some(WtcError::Unlocked { problem, .. }) if problem.contains("changed")
+        ));
+        check(&path, url, b"b\n", false).unwrap();
+        check(&path, url, b"b\n", true).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
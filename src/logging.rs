@@ -0,0 +1,166 @@
+#[cfg(not(feature = "structured-logs"))]
+use crate::error::WtcError;
+use anyhow::Result;
+use clap::ValueEnum;
+#[cfg(not(feature = "structured-logs"))]
+use std::env;
+use std::io::{self, IsTerminal};
+use tracing::level_filters::LevelFilter;
+use tracing::Subscriber;
+#[cfg(not(feature = "structured-logs"))]
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+#[cfg(feature = "structured-logs")]
+use tracing_subscriber::EnvFilter;
+
+/// Which events are logged, by level and target.
+#[cfg(feature = "structured-logs")]
+type Filter = EnvFilter;
+#[cfg(not(feature = "structured-logs"))]
+type Filter = Targets;
+
+/// How log lines are written to stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines, with the spans they happened in
+    #[default]
+    Text,
+    /// One JSON object per line, for log collectors
+    Json,
+}
+
+/// Sends log output to stderr. `level` comes from `-v`/`-q` and overrides `RUST_LOG`,
+/// which otherwise defaults to warnings only.
+pub fn init(level: Option<LevelFilter>, format: LogFormat) -> Result<()> {
+    subscriber(
+        filter(level),
+        format,
+        io::stderr,
+        io::stderr().is_terminal(),
+    )?
+    .init();
+    Ok(())
+}
+
+/// Which events are logged: those at `level` and above, or else what `RUST_LOG` asks for.
+#[cfg(feature = "structured-logs")]
+fn filter(level: Option<LevelFilter>) -> EnvFilter {
+    match level {
+        Some(level) => EnvFilter::default().add_directive(level.into()),
+        None => EnvFilter::builder()
+            .with_default_directive(LevelFilter::WARN.into())
+            .from_env_lossy(),
+    }
+}
+
+/// Which events are logged: those at `level` and above, or else the levels `RUST_LOG`
+/// sets per target.
+#[cfg(not(feature = "structured-logs"))]
+fn filter(level: Option<LevelFilter>) -> Targets {
+    match level {
+        Some(level) => Targets::new().with_default(level),
+        None => env::var("RUST_LOG")
+            .ok()
+            .filter(|directives| !directives.trim().is_empty())
+            .and_then(|directives| directives.parse().ok())
+            .unwrap_or_else(|| Targets::new().with_default(LevelFilter::WARN)),
+    }
+}
+
+/// Formats the events `filter` lets through as `format`, writing them to `writer`.
+fn subscriber<W>(
+    filter: Filter,
+    format: LogFormat,
+    writer: W,
+    ansi: bool,
+) -> Result<Box<dyn Subscriber + Send + Sync>>
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(LevelFilter::TRACE)
+        .with_writer(writer)
+        .with_ansi(ansi);
+    match format {
+        LogFormat::Text => Ok(Box::new(builder.finish().with(filter))),
+        #[cfg(feature = "structured-logs")]
+        LogFormat::Json => Ok(Box::new(
+            builder.json().with_current_span(true).finish().with(filter),
+        )),
+        #[cfg(not(feature = "structured-logs"))]
+        LogFormat::Json => Err(WtcError::Disabled {
+            what: "JSON logs",
+            feature: "structured-logs",
+        }
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use tracing::{debug, debug_span, warn};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(bytes)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// What logging a warning and a debug message inside a span writes.
+    fn logged(level: LevelFilter, format: LogFormat) -> String {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber =
+            subscriber(filter(Some(level)), format, move || writer.clone(), false).unwrap();
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = debug_span!("load", what = "names").entered();
+            warn!("Skipping line {}", 3);
+            debug!("Loaded {} names", 2);
+        });
+        let bytes = buffer.0.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn t_text_logs() {
+        let text = logged(LevelFilter::WARN, LogFormat::Text);
+        assert_eq!(text.lines().count(), 1, "{}", text);
+        assert!(text.contains(" WARN "), "{}", text);
+        assert!(text.ends_with("Skipping line 3\n"), "{}", text);
+        assert!(!text.contains("load"), "debug spans are hidden: {}", text);
+        let text = logged(LevelFilter::DEBUG, LogFormat::Text);
+        assert_eq!(text.lines().count(), 2, "{}", text);
+        assert!(text
+            .lines()
+            .all(|line| line.contains("load{what=\"names\"}")));
+        assert_eq!(logged(LevelFilter::OFF, LogFormat::Text), "");
+    }
+
+    #[cfg(feature = "structured-logs")]
+    #[test]
+    fn t_json_logs() {
+        let json = logged(LevelFilter::DEBUG, LogFormat::Json);
+        let lines: Vec<serde_json::Value> = json
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["level"], "WARN");
+        assert_eq!(lines[0]["fields"]["message"], "Skipping line 3");
+        assert_eq!(lines[1]["fields"]["message"], "Loaded 2 names");
+        assert_eq!(lines[1]["span"]["name"], "load");
+        assert_eq!(lines[1]["span"]["what"], "names");
+    }
+}
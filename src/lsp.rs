@@ -0,0 +1,514 @@
+use crate::diagnostic;
+use crate::lint::{self, Directive, Distribution, NumberFormat, NumberRange};
+use crate::man::PLACEHOLDERS;
+use crate::rpc::{self, Error, INVALID_PARAMS, METHOD_NOT_FOUND, PARSE_ERROR};
+use crate::sections;
+use crate::spice::Spice;
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use tracing::debug;
+
+/// LSP diagnostic severities.
+const ERROR: u8 = 1;
+const WARNING: u8 = 2;
+
+/// Directives offered as completions, with what each does.
+const DIRECTIVES: [(&str, &str); 3] = [
+    (
+        "{{set n = num:1..10}}",
+        "Draws a number into `n`, writing nothing, so `{{n}}` can write it later.",
+    ),
+    (
+        "{{50%: }}",
+        "Writes its text, which may hold placeholders, half the time.",
+    ),
+    (
+        "{{repeat 3: }}",
+        "Writes its text 3 times, drawing its placeholders anew each time.",
+    ),
+];
+
+/// Reads one message, framed by a `Content-Length` header, or `None` at the end of the
+/// input. A body that isn't JSON is read as `null`.
+fn read_message<R: BufRead>(input: &mut R) -> Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = Some(value.trim().parse::<usize>()?);
+            }
+        }
+    }
+    let length = length.context("A message has no Content-Length header")?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body).unwrap_or(Value::Null)))
+}
+
+fn write_message<W: Write>(output: &mut W, message: &Value) -> Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()?;
+    Ok(())
+}
+
+/// The UTF-16 offset LSP positions count in, of byte `at` of `line`.
+fn character(line: &str, at: usize) -> usize {
+    line[..at].encode_utf16().count()
+}
+
+/// The byte offset of UTF-16 offset `character` of `line`.
+fn byte_offset(line: &str, character: usize) -> usize {
+    let mut units = 0;
+    for (at, c) in line.char_indices() {
+        if units >= character {
+            return at;
+        }
+        units += c.len_utf16();
+    }
+    line.len()
+}
+
+fn range(number: usize, line: &str, bytes: std::ops::Range<usize>) -> Value {
+    json!({
+        "start": { "line": number, "character": character(line, bytes.start) },
+        "end": { "line": number, "character": character(line, bytes.end) },
+    })
+}
+
+/// The problems the linter finds in a templates file, as LSP diagnostics. What would stop
+/// the file from loading is an error; malformed placeholders, which are left as written,
+/// are warnings.
+fn diagnostics(text: &str) -> Vec<Value> {
+    let mut found = Vec::new();
+    let mut push = |number, line: &str, bytes, severity, problem: String| {
+        found.push(json!({
+            "range": range(number, line, bytes),
+            "severity": severity,
+            "source": "whatthecommitcli",
+            "message": problem,
+        }));
+    };
+    for (number, line) in sections::templates(text) {
+        if let Some((at, problem)) = diagnostic::check_line(line) {
+            let end = line[at..].chars().next().map_or(at, |c| at + c.len_utf8());
+            push(number, line, at..end, ERROR, problem);
+            continue;
+        }
+        for placeholder in lint::scan(line) {
+            if let Some(problem) = placeholder.problem {
+                push(number, line, placeholder.range, WARNING, problem);
+            }
+        }
+    }
+    found
+}
+
+/// Describes the numbers a number placeholder or `set` directive draws.
+fn describe_range(range: &NumberRange) -> String {
+    let mut text = format!("A random number from {} to {}", range.first, range.last);
+    if range.step > 1 {
+        text += &format!(", in multiples of {}", range.step);
+    }
+    text += match range.distribution {
+        Distribution::Uniform => "",
+        Distribution::Log => ", log-uniform",
+        Distribution::Normal => ", on a bell curve around the middle",
+    };
+    text += match range.format {
+        NumberFormat::Plain => "",
+        NumberFormat::Roman => ", in Roman numerals",
+        NumberFormat::Ordinal => ", as an ordinal",
+        NumberFormat::Percent => ", as a percentage",
+        NumberFormat::Currency => ", in dollars and cents",
+    };
+    text + "."
+}
+
+fn describe_directive(directive: &Directive) -> String {
+    match directive {
+        Directive::Set { name, range } => format!(
+            "{} Kept in `{}`, writing nothing.",
+            describe_range(range),
+            name
+        ),
+        Directive::Get { name, op: None } => format!("The number set in `{}`.", name),
+        Directive::Get {
+            name,
+            op: Some((op, operand)),
+        } => {
+            let op = match op {
+                '*' => "times",
+                '+' => "plus",
+                _ => "minus",
+            };
+            format!("The number set in `{}`, {} {}.", name, op, operand)
+        }
+        Directive::Maybe { chance, .. } => {
+            format!("Writes its text {}% of the time.", chance)
+        }
+        Directive::Repeat { count, .. } => format!(
+            "Writes its text {} times, drawing its placeholders anew each time.",
+            count
+        ),
+        Directive::Spice(level) => format!(
+            "Writes nothing; the template is only used with `--spice {}` or spicier.",
+            match level {
+                Spice::Censored => "censored",
+                Spice::Mild => "mild",
+                Spice::Medium => "medium",
+                Spice::Strong => "savage",
+            }
+        ),
+        Directive::MaxLen(len) => format!(
+            "Writes nothing; messages from the template longer than {} characters are drawn again.",
+            len
+        ),
+        Directive::Requires(placeholder) => format!(
+            "Writes nothing; optional text holding `{}` is always written.",
+            placeholder
+        ),
+        Directive::NoConventional => {
+            "Writes nothing; the template isn't used with `--cz` or `--commitlint`.".to_string()
+        }
+    }
+}
+
+/// Documentation for the placeholder or directive at byte `at` of `line`, with its range.
+fn hover(number: usize, line: &str, at: usize) -> Option<Value> {
+    let placeholder = lint::scan(line)
+        .into_iter()
+        .find(|p| p.range.contains(&at))?;
+    let text = &line[placeholder.range.clone()];
+    let docs = if let Some(problem) = placeholder.problem {
+        format!("`{}`: {}", text, problem)
+    } else if let Some((_, docs)) = PLACEHOLDERS.iter().find(|(p, _)| *p == text) {
+        format!("**{}**\n\n{}", text, docs)
+    } else if let Some(spec) = text.strip_prefix("XNUM").and_then(|t| t.strip_suffix('X')) {
+        let range = lint::parse_number_range(spec).ok()?;
+        format!("**{}**\n\n{}", text, describe_range(&range))
+    } else {
+        // The opening of a directive; the variables it uses were checked by the scan
+        let inner = line[placeholder.range.start..].strip_prefix("{{")?;
+        let directive = lint::parse_directive(&inner[..inner.find("}}")?]).ok()?;
+        describe_directive(&directive)
+    };
+    Some(json!({
+        "contents": { "kind": "markdown", "value": docs },
+        "range": range(number, line, placeholder.range),
+    }))
+}
+
+/// Placeholders and directives to complete at byte `at` of `line`, replacing a
+/// placeholder being typed there.
+fn completions(number: usize, line: &str, at: usize) -> Value {
+    let typed = line[..at]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_ascii_uppercase() || c.is_ascii_digit() || ",:-".contains(*c))
+        .last()
+        .map_or(at, |(start, _)| start);
+    let start = match line[typed..at].starts_with('X') {
+        true => typed,
+        false => at,
+    };
+    let items = PLACEHOLDERS
+        .iter()
+        .chain(&DIRECTIVES)
+        .map(|(label, docs)| {
+            json!({
+                "label": label,
+                "kind": 15,
+                "documentation": { "kind": "markdown", "value": docs },
+                "textEdit": { "range": range(number, line, start..at), "newText": label },
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({ "isIncomplete": false, "items": items })
+}
+
+/// Documents open in the editor, by URI.
+#[derive(Default)]
+struct Server {
+    documents: HashMap<String, String>,
+}
+
+fn invalid_params() -> Error {
+    Error::new(INVALID_PARAMS, "Missing or malformed params")
+}
+
+impl Server {
+    /// The line and byte offset a `textDocument/position` request points at.
+    fn position<'a>(&'a self, params: &Value) -> Result<(usize, &'a str, usize), Error> {
+        let uri = params["textDocument"]["uri"]
+            .as_str()
+            .ok_or_else(invalid_params)?;
+        let text = self
+            .documents
+            .get(uri)
+            .ok_or_else(|| Error::new(INVALID_PARAMS, format!("{} isn't open", uri)))?;
+        let position = &params["position"];
+        let (Some(number), Some(character)) =
+            (position["line"].as_u64(), position["character"].as_u64())
+        else {
+            return Err(invalid_params());
+        };
+        let number = number as usize;
+        let line = text.lines().nth(number).unwrap_or_default();
+        Ok((number, line, byte_offset(line, character as usize)))
+    }
+
+    fn request(&self, method: &str, params: &Value) -> Result<Value, Error> {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "completionProvider": { "triggerCharacters": ["X", "{"] },
+                    "hoverProvider": true,
+                },
+                "serverInfo": { "name": "whatthecommitcli", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "shutdown" => Ok(Value::Null),
+            "textDocument/completion" => {
+                let (number, line, at) = self.position(params)?;
+                Ok(completions(number, line, at))
+            }
+            "textDocument/hover" => {
+                let (number, line, at) = self.position(params)?;
+                Ok(hover(number, line, at).unwrap_or(Value::Null))
+            }
+            other => Err(Error::new(
+                METHOD_NOT_FOUND,
+                format!("Unknown method `{}`", other),
+            )),
+        }
+    }
+
+    /// Handles a notification, returning the diagnostics to publish for the document it
+    /// changed, if any.
+    fn notify(&mut self, method: &str, params: &Value) -> Option<Value> {
+        let uri = params["textDocument"]["uri"].as_str()?.to_string();
+        let text = match method {
+            "textDocument/didOpen" => params["textDocument"]["text"].as_str()?,
+            // Changes are always whole documents, as `initialize` asks for
+            "textDocument/didChange" => {
+                params["contentChanges"].as_array()?.last()?["text"].as_str()?
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                return Some(publish(&uri, Vec::new()));
+            }
+            _ => return None,
+        };
+        let found = diagnostics(text);
+        self.documents.insert(uri.clone(), text.to_string());
+        Some(publish(&uri, found))
+    }
+}
+
+fn publish(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+/// Serves the Language Server Protocol on `input` and `output` until the client sends
+/// `exit` or the input ends.
+pub fn serve<R: BufRead, W: Write>(mut input: R, mut output: W) -> Result<()> {
+    let mut server = Server::default();
+    while let Some(message) = read_message(&mut input)? {
+        let Some(method) = message["method"].as_str() else {
+            if !message.is_object() {
+                let error = Error::new(PARSE_ERROR, "Not a JSON-RPC message");
+                write_message(&mut output, &rpc::response(Value::Null, Err(error)))?;
+            }
+            // Responses to requests the server never sends
+            continue;
+        };
+        debug!("LSP message: {}", method);
+        let params = &message["params"];
+        match message.get("id") {
+            Some(id) => {
+                let response = rpc::response(id.clone(), server.request(method, params));
+                write_message(&mut output, &response)?;
+            }
+            None if method == "exit" => return Ok(()),
+            None => {
+                if let Some(notification) = server.notify(method, params) {
+                    write_message(&mut output, &notification)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_lsp_server() {
+        let frame = |message: Value| {
+            let body = message.to_string();
+            format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+        };
+        let document = json!({ "uri": "file:///t.txt" });
+        let input = [
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+            json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+                "textDocument": { "uri": "file:///t.txt", "text": "fixed XNUM2,7X bugs\nblame XNAMX" },
+            }}),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "textDocument/hover", "params": {
+                "textDocument": document, "position": { "line": 0, "character": 8 },
+            }}),
+            json!({ "jsonrpc": "2.0", "id": 3, "method": "textDocument/completion", "params": {
+                "textDocument": document, "position": { "line": 1, "character": 9 },
+            }}),
+            json!({ "jsonrpc": "2.0", "method": "textDocument/didChange", "params": {
+                "textDocument": document, "contentChanges": [{ "text": "é XNAMEX" }],
+            }}),
+            json!({ "jsonrpc": "2.0", "id": 4, "method": "textDocument/hover", "params": {
+                "textDocument": document, "position": { "line": 0, "character": 3 },
+            }}),
+            json!({ "jsonrpc": "2.0", "method": "exit" }),
+            json!({ "jsonrpc": "2.0", "id": 5, "method": "shutdown" }),
+        ]
+        .map(frame)
+        .concat();
+        let mut output = Vec::new();
+        serve(input.as_bytes(), &mut output).unwrap();
+        let mut output = output.as_slice();
+        let mut messages = Vec::new();
+        while let Some(message) = read_message(&mut output).unwrap() {
+            messages.push(message);
+        }
+        assert_eq!(messages.len(), 6, "nothing is read after exit");
+        assert_eq!(messages[0]["result"]["capabilities"]["hoverProvider"], true);
+
+        let published = &messages[1]["params"]["diagnostics"];
+        assert_eq!(published.as_array().unwrap().len(), 1);
+        assert_eq!(published[0]["range"]["start"]["line"], 1);
+        assert_eq!(published[0]["range"]["start"]["character"], 6);
+        assert_eq!(published[0]["severity"], WARNING);
+
+        let hover = &messages[2]["result"]["contents"]["value"];
+        assert_eq!(hover, "**XNUM2,7X**\n\nA random number from 2 to 7.");
+
+        let items = messages[3]["result"]["items"].as_array().unwrap();
+        assert_eq!(items.len(), PLACEHOLDERS.len() + DIRECTIVES.len());
+        assert_eq!(items[0]["textEdit"]["range"]["start"]["character"], 6);
+
+        assert_eq!(messages[4]["params"]["diagnostics"], json!([]));
+        assert_eq!(messages[5]["result"]["range"]["start"]["character"], 2);
+        assert!(messages[5]["result"]["contents"]["value"]
+            .as_str()
+            .unwrap()
+            .starts_with("**XNAMEX**"));
+
+        let sectioned = "[today]\n# a comment\nXNAMEX fixed it";
+        assert!(diagnostics(sectioned).is_empty());
+        assert_eq!(diagnostics("# XNAMX in a commit message").len(), 1);
+        let range = lint::parse_number_range("0,100:10:percent").unwrap();
+        assert_eq!(
+            describe_range(&range),
+            "A random number from 0 to 100, in multiples of 10, as a percentage."
+        );
+    }
+
+    #[test]
+    fn t_utf16_positions() {
+        // "é" is one UTF-16 unit in two bytes; "🔥" is two units in four bytes
+        let line = "é🔥 XNAMX";
+        let at = line.find('X').unwrap();
+        assert_eq!(at, 7);
+        assert_eq!(character(line, at), 4);
+        assert_eq!(byte_offset(line, 4), at);
+        assert_eq!(byte_offset(line, 1), "é".len());
+        // Halfway through a surrogate pair, and past the end of the line
+        assert_eq!(byte_offset(line, 2), "é🔥".len());
+        assert_eq!(byte_offset(line, 99), line.len());
+        for at in line.char_indices().map(|(at, _)| at) {
+            assert_eq!(byte_offset(line, character(line, at)), at);
+        }
+
+        let found = diagnostics(line);
+        assert_eq!(found[0]["range"]["start"]["character"], 4);
+        assert_eq!(found[0]["range"]["end"]["character"], 9);
+        let hovered = hover(0, line, byte_offset(line, 5)).unwrap();
+        assert_eq!(hovered["range"]["start"]["character"], 4);
+        assert_eq!(hover(0, line, byte_offset(line, 1)), None);
+        let hovered = hover(0, "🔥 XNAMEX", byte_offset("🔥 XNAMEX", 4)).unwrap();
+        assert_eq!(hovered["range"]["start"]["character"], 3);
+    }
+
+    #[test]
+    fn t_document_changes() {
+        let mut server = Server::default();
+        let document =
+            |text: &str| json!({ "textDocument": { "uri": "file:///t.txt", "text": text } });
+        let published = server
+            .notify("textDocument/didOpen", &document("fixed it"))
+            .unwrap();
+        assert_eq!(published["params"]["diagnostics"], json!([]));
+
+        // Only the last of several changes counts, each being the whole document
+        let change = json!({
+            "textDocument": { "uri": "file:///t.txt" },
+            "contentChanges": [{ "text": "XNAMX" }, { "text": "ok\nfixed XNUM5,1X" }],
+        });
+        let published = server.notify("textDocument/didChange", &change).unwrap();
+        let found = published["params"]["diagnostics"].as_array().unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0]["range"]["start"]["line"], 1);
+        let position = json!({
+            "textDocument": { "uri": "file:///t.txt" },
+            "position": { "line": 1, "character": 7 },
+        });
+        assert_eq!(
+            server.position(&position).ok(),
+            Some((1, "fixed XNUM5,1X", 7))
+        );
+
+        // A change without its text, or to a document never opened, is ignored
+        let empty = json!({ "textDocument": { "uri": "file:///t.txt" }, "contentChanges": [] });
+        assert_eq!(server.notify("textDocument/didChange", &empty), None);
+        assert_eq!(server.notify("textDocument/didSave", &position), None);
+
+        let published = server.notify("textDocument/didClose", &position).unwrap();
+        assert_eq!(published["params"]["diagnostics"], json!([]));
+        let closed = rpc::response(json!(1), server.request("textDocument/hover", &position));
+        assert!(closed["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("isn't open"));
+        let missing = rpc::response(json!(2), server.request("textDocument/hover", &json!({})));
+        assert_eq!(missing["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn t_malformed_frames() {
+        let mut input = "Content-Length: 4\r\n\r\nnope".as_bytes();
+        assert_eq!(read_message(&mut input).unwrap(), Some(Value::Null));
+        assert!(read_message(&mut "X-Other: 1\r\n\r\n{}".as_bytes()).is_err());
+        assert!(read_message(&mut "Content-Length: ten\r\n\r\n".as_bytes()).is_err());
+        assert!(read_message(&mut "Content-Length: 10\r\n\r\n{}".as_bytes()).is_err());
+
+        let mut output = Vec::new();
+        serve("Content-Length: 2\r\n\r\n[]".as_bytes(), &mut output).unwrap();
+        let reply = read_message(&mut output.as_slice()).unwrap().unwrap();
+        assert_eq!(reply["error"]["code"], PARSE_ERROR);
+    }
+}
@@ -1,11 +1,225 @@
 use anyhow::{Context, Result};
-use clap::Parser;
-use log::{debug, info};
 use rand::prelude::IndexedRandom;
+use rand::seq::SliceRandom;
 use rand::Rng;
-use regex_lite::Regex;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::borrow::Cow;
+use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, instrument, warn};
+
+#[cfg(feature = "tui")]
+mod accessible;
+mod acrostic;
+mod animation;
+mod bake;
+mod banner;
+mod bench;
+mod blocklist;
+mod boost;
+mod branch;
+mod bundle;
+mod cache;
+mod choose;
+mod ci;
+mod cli;
+mod coauthor;
+mod codename;
+mod commitlint;
+mod company;
+mod config;
+mod conventional;
+mod corpus;
+mod coverage;
+mod cowsay;
+mod cz;
+mod daily;
+mod date;
+mod dedupe;
+mod diagnostic;
+mod disabled;
+mod easter_eggs;
+mod editor;
+mod emoji;
+mod error;
+mod escape;
+mod explain;
+mod favorites;
+mod filepath;
+mod format;
+mod formatter;
+#[cfg(feature = "git")]
+mod git;
+mod github;
+mod grammar;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod haiku;
+mod history;
+mod hook;
+mod http;
+mod i18n;
+mod imperative;
+mod init;
+#[cfg(feature = "network")]
+mod install;
+mod ip;
+#[cfg(feature = "network")]
+mod keychain;
+#[cfg(feature = "tui")]
+mod keys;
+// Only the template editor reports problems, so most of this goes unused without the TUI
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+mod lint;
+mod list;
+mod llm;
+#[cfg(feature = "network")]
+mod lockfile;
+mod logging;
+mod lsp;
+mod man;
+#[cfg(feature = "network")]
+mod minisign;
+mod mutate;
+mod name;
+mod normalize;
+#[cfg(feature = "notify")]
+mod notify;
+mod output;
+mod pack;
+mod paths;
+#[cfg(feature = "tui")]
+mod picker;
+mod preview;
+mod prompt;
+mod quiz;
+mod rating;
+mod release_notes;
+mod replace;
+mod rng;
+mod rpc;
+mod rules;
+mod sample;
+#[cfg(any(test, not(feature = "regex")))]
+mod scanner;
+#[cfg(feature = "lua")]
+mod script;
+mod search;
+mod seasonal;
+mod sections;
+#[cfg(feature = "self-update")]
+mod self_update;
+mod series;
+mod signoff;
+mod simulate;
+mod spellcheck;
+mod spice;
+mod squash;
+mod standup;
+// Corpus statistics are only shown in the TUI
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+mod stats;
+#[cfg(feature = "network")]
+mod team;
+mod template;
+#[cfg(feature = "tui")]
+mod template_editor;
+mod text;
+mod theme;
+mod tmux;
+mod transform;
+#[cfg(feature = "tui")]
+mod tui;
+mod url;
+mod usage;
+mod uuid;
+mod verify;
+mod watch;
+#[cfg(feature = "network")]
+mod webhook;
+mod weights;
+mod widget;
+mod words;
+mod wrap;
+
+use blocklist::Blocklist;
+use cli::{
+    Args, AuthAction, BundleAction, Command, FavAction, I18nAction, PackAction, WeightsAction,
+};
+use commitlint::Commitlint;
+use corpus::{Corpus, LineIndex, Mix, Mixed};
+use date::Date;
+use diagnostic::Diagnostic;
+#[cfg(not(feature = "git"))]
+use disabled::git;
+#[cfg(not(feature = "grpc"))]
+use disabled::grpc;
+#[cfg(not(feature = "network"))]
+use disabled::install;
+#[cfg(not(feature = "network"))]
+use disabled::keychain;
+#[cfg(not(feature = "notify"))]
+use disabled::notify;
+#[cfg(not(feature = "lua"))]
+use disabled::script;
+#[cfg(not(feature = "self-update"))]
+use disabled::self_update;
+#[cfg(not(feature = "network"))]
+use disabled::team;
+#[cfg(not(feature = "network"))]
+use disabled::webhook;
+use error::WtcError;
+use explain::Explainer;
+use grammar::Grammar;
+use http::NetworkPolicy;
+use imperative::Imperatives;
+#[cfg(feature = "tui")]
+use keys::Keymap;
+use normalize::Normalizer;
+use output::{ColorChoice, Output};
+use replace::Replacements;
+use rng::Generator;
+use rules::Rules;
+use sample::Reservoir;
+use script::Script;
+use seasonal::Seasonal;
+use signoff::{Signer, Signoff};
+use template::Template;
+use theme::Theme;
+use transform::Transform;
+
+/// How many messages to generate before giving up when a script keeps vetoing them.
+const MAX_ATTEMPTS: usize = 100;
+
+/// A names or templates path of `-` reads the corpus from stdin.
+const STDIN: &str = "-";
+
+/// Lines kept from a corpus streamed on stdin when generating: a uniform random sample,
+/// so that huge or endless streams never have to fit in memory.
+const STDIN_SAMPLE: usize = 1000;
+
+/// Templates drawn from a `--grammar` file for a run to pick from, so everything that works
+/// on a corpus works on a grammar too.
+const GRAMMAR_TEMPLATES: usize = 1000;
+
+/// Number of messages flashing past in the slot-machine animation.
+const REEL_LENGTH: usize = 30;
+
+/// Batches of at least this many messages are generated in parallel.
+const PARALLEL_THRESHOLD: usize = 10_000;
+
+/// Number of messages each parallel task generates. Fixed, so that a seeded batch comes out
+/// the same whatever the number of threads.
+const PARALLEL_CHUNK: usize = 1_000;
+
+/// Number of recent commits the quiz draws real subjects from.
+const QUIZ_HISTORY: usize = 500;
 
 fn default_names() -> Vec<String> {
     include_str!("names.txt")
@@ -15,6 +229,30 @@ fn default_names() -> Vec<String> {
         .collect()
 }
 
+fn default_excuses() -> Vec<String> {
+    include_str!("excuses.txt")
+        .split('\n')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_branch_names() -> Vec<String> {
+    branch::NAME_TEMPLATES
+        .split('\n')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_reviews() -> Vec<String> {
+    include_str!("reviews.txt")
+        .split('\n')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
 fn default_commit_messages() -> Vec<String> {
     include_str!("commit_messages.txt")
         .split('\n')
@@ -23,42 +261,122 @@ fn default_commit_messages() -> Vec<String> {
         .collect()
 }
 
-#[derive(Parser)]
-#[command(author, version, about, long_about = None)]
-struct Args {
-    /// Optional path to a custom names file
-    #[arg(short = 'n', long = "names", value_name = "FILE")]
-    names: Option<PathBuf>,
+/// What `--explain` needs to say where messages came from: the templates file or stream,
+/// the number of templates `loaded` from it and the seed.
+fn explainer(
+    args: &Args,
+    templates_path: &Option<PathBuf>,
+    loaded: usize,
+    seed: Option<u64>,
+) -> Explainer {
+    let (source, text) = match (&args.grammar, templates_path) {
+        (Some(grammar), _) => (format!("the templates of {:?}", grammar), None),
+        _ if args.mix.is_some() => ("the sources --mix names".to_string(), None),
+        (None, Some(path)) if path.as_path() == Path::new(STDIN) => ("stdin".to_string(), None),
+        (None, Some(path)) => {
+            let source = match (&args.commit_messages_template, &args.team_corpus_url) {
+                (None, Some(url)) => format!("the team corpus at {}", url),
+                _ => format!("{:?}", path),
+            };
+            (source, fs::read_to_string(path).ok())
+        }
+        (None, None) if args.haiku => ("the built-in haiku templates".to_string(), None),
+        (None, None) => (
+            "the built-in templates".to_string(),
+            Some(include_str!("commit_messages.txt").to_string()),
+        ),
+    };
+    Explainer::new(source, text, loaded, seed)
+}
 
-    /// Optional path to a custom commit messages template file
-    #[arg(short = 'c', long = "commit-messages-template", value_name = "FILE")]
-    commit_messages_template: Option<PathBuf>,
+/// The file given on the command line, or else the cached copy of the team's shared one
+/// at `url`. A team file that can't be had is warned about, or only logged under the
+/// `prefer` and `never` network policies, and the built-in one is used, unless `--locked`
+/// or the `required` policy asks for the shared one.
+fn team_file(given: &Option<PathBuf>, url: Option<&str>, args: &Args) -> Result<Option<PathBuf>> {
+    let url = match url {
+        Some(url) if given.is_none() => url,
+        _ => return Ok(given.clone()),
+    };
+    let fallback = "Using the built-in corpus instead of";
+    match (
+        team::corpus(url, &args.http(), args.locked),
+        args.network_policy,
+    ) {
+        (Ok(file), _) => Ok(Some(file)),
+        (Err(e), _) if args.locked => Err(e),
+        (Err(e), Some(NetworkPolicy::Required)) => Err(e),
+        (Err(e), Some(NetworkPolicy::Prefer | NetworkPolicy::Never)) => {
+            debug!("{} {}: {:#}", fallback, url, e);
+            Ok(None)
+        }
+        (Err(e), None) => {
+            warn!("{} {}: {:#}", fallback, url, e);
+            Ok(None)
+        }
+    }
+}
+
+/// The authors of the current repository's commits, weighted by how many each made with
+/// `by_commits`.
+fn git_names(by_commits: bool) -> Result<Vec<String>> {
+    let authors = git::authors()?;
+    if authors.is_empty() {
+        return Err(WtcError::EmptyPool("No commits to take author names from".to_string()).into());
+    }
+    debug!("Taking names from {} git authors", authors.len());
+    Ok(match by_commits {
+        true => name::by_commits(&authors),
+        false => authors.into_iter().map(|(name, _)| name).collect(),
+    })
 }
 
 /// Load lines from a file or return defaults
+///
+/// With no `default_fn` (the default corpus is disabled), a file must be given.
+#[instrument(level = "debug", skip_all, fields(what = file_type, path = ?file_path))]
 fn load_lines_or_default(
     file_path: &Option<PathBuf>,
-    default_fn: fn() -> Vec<String>,
-    file_type: &str,
+    default_fn: Option<fn() -> Vec<String>>,
+    file_type: &'static str,
 ) -> Result<Vec<String>> {
     match file_path {
-        None => {
-            debug!("Using default {}", file_type);
-            Ok(default_fn())
-        }
+        None => match default_fn {
+            Some(default_fn) => {
+                debug!("Using default {}", file_type);
+                Ok(default_fn())
+            }
+            None => Err(WtcError::NoCorpus { what: file_type }.into()),
+        },
         Some(path) => {
             debug!("Loading {} from: {:?}", file_type, path);
-            let content = fs::read_to_string(path)
-                .with_context(|| format!("Failed to read {} file: {:?}", file_type, path))?;
+            let content = fs::read(path).map_err(|source| WtcError::IoError {
+                context: format!("Failed to read {} file: {:?}", file_type, path),
+                source,
+            })?;
+            let content = corpus::decode(content)
+                .with_context(|| format!("Invalid {} file: {:?}", file_type, path))?;
 
-            let lines: Vec<String> = content
-                .lines()
-                .filter(|s| !s.trim().is_empty())
-                .map(|s| s.to_string())
-                .collect();
+            // Malformed placeholders only matter in templates, where they are left as written
+            let templates = file_type == "commit messages";
+            let mut lines = Vec::new();
+            for (i, line) in content.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let diagnostic =
+                    |at, problem| Box::new(Diagnostic::new(path, i + 1, line, at, problem));
+                if let Some((at, problem)) = diagnostic::check_line(line) {
+                    return Err(WtcError::Diagnostic(diagnostic(at, problem)).into());
+                }
+                if templates {
+                    corpus::check_placeholders(path, i + 1, line)?;
+                }
+                lines.push(line.to_string());
+            }
 
             if lines.is_empty() {
-                anyhow::bail!("{} file is empty or contains only empty lines", file_type);
+                return Err(WtcError::EmptyCorpus { what: file_type }.into());
             }
 
             info!("Loaded {} {} from {:?}", lines.len(), file_type, path);
@@ -67,107 +385,364 @@ fn load_lines_or_default(
     }
 }
 
-fn generate_commit_message<R>(
-    names: &[String],
-    commit_messages: &[String],
+/// Reads a names or templates corpus streamed to `reader`. With `sample`, only that many
+/// lines are kept, chosen uniformly at random in a single pass.
+fn load_stream<B, R>(
+    reader: B,
+    file_type: &'static str,
+    sample: Option<usize>,
     rng: &mut R,
-) -> Result<String>
+) -> Result<Vec<String>>
 where
+    B: io::BufRead,
     R: Rng + ?Sized,
 {
-    let name = names.choose(rng).context("Failed to select any names")?;
-    let template = commit_messages
-        .choose(rng)
-        .context("Failed to select any commit messages")?;
+    let path = Path::new("<stdin>");
+    let lines = match sample {
+        Some(capacity) => {
+            let mut reservoir = Reservoir::new(capacity);
+            corpus::stream_lines(reader, path, file_type, |line| {
+                reservoir.push(line.to_string(), rng)
+            })?;
+            debug!("Sampled {} {} from a stream", file_type, reservoir.seen());
+            reservoir.into_vec()
+        }
+        None => {
+            let mut lines = Vec::new();
+            corpus::stream_lines(reader, path, file_type, |line| lines.push(line.to_string()))?;
+            lines
+        }
+    };
+    if lines.is_empty() {
+        return Err(WtcError::EmptyCorpus { what: file_type }.into());
+    }
+    info!("Loaded {} {} from stdin", lines.len(), file_type);
+    Ok(lines)
+}
 
-    Ok(substitute_placeholders(template, name, rng))
+/// Loads the template corpus. Files of at least `corpus::STREAM_THRESHOLD` bytes are
+/// indexed instead, so that only the templates used are ever read.
+#[instrument(level = "debug", skip_all, fields(path = ?file_path))]
+fn load_templates(
+    file_path: &Option<PathBuf>,
+    default_fn: Option<fn() -> Vec<String>>,
+) -> Result<Corpus> {
+    if let Some(path) = file_path.as_deref().filter(|p| corpus::should_index(p)) {
+        debug!("Indexing commit messages from: {:?}", path);
+        return LineIndex::build(path).map(Corpus::Indexed);
+    }
+    load_lines_or_default(file_path, default_fn, "commit messages").map(Corpus::from)
 }
 
-/// Parses a number range specification from XNUM...X placeholders.
-///
-/// # Returns
-/// A tuple of (start, end) for the range.
-///
-/// # Examples
-/// - "" -> (1, 999) - default range
-/// - "10" -> (1, 10) - simple upper limit
-/// - "1,5" -> (1, 5) - explicit range
-/// - ",5" -> (1, 5) - range with default start
-/// - "5," -> (5, 999) - range with default end
-fn parse_number_range(value_str: &str) -> (u32, u32) {
-    if value_str.is_empty() {
-        // XNUMX - default range
-        return (1, 999);
-    }
-
-    if !value_str.contains(',') {
-        // XNUM10X - simple number, range from 1 to specified value
-        let end = value_str.parse::<u32>().unwrap_or(999);
-        return (1, end);
-    }
-
-    // Handle comma-separated values as ranges
-    let comma_pos = value_str.find(',').unwrap(); // Safe because we checked contains above
-
-    if comma_pos == 0 {
-        // XNUM,5X - range from 1 to specified end
-        let end_str = &value_str[1..];
-        let end = end_str.parse::<u32>().unwrap_or(999);
-        (1, end)
-    } else if comma_pos == value_str.len() - 1 {
-        // XNUM5,X - range from specified start to 999
-        let start_str = &value_str[..comma_pos];
-        let start = start_str.parse::<u32>().unwrap_or(1);
-        (start, 999)
-    } else {
-        // XNUM1,5X - treat as range (start,end)
-        let before_comma = &value_str[..comma_pos];
-        let after_comma = &value_str[comma_pos + 1..];
-        let start = before_comma.parse::<u32>().unwrap_or(1);
-        let end = after_comma.parse::<u32>().unwrap_or(999);
-        (start, end)
+/// Loads each source `mix` names into one corpus: `default` for the templates of
+/// `default_fn`, `custom` for the file at `templates_path`, and anything else for the
+/// installed pack of that name.
+fn load_mix(
+    mix: &Mix,
+    templates_path: &Option<PathBuf>,
+    default_fn: Option<fn() -> Vec<String>>,
+) -> Result<(Corpus, Mixed)> {
+    let mut sources = Vec::with_capacity(mix.shares.len());
+    for (source, share) in &mix.shares {
+        let templates = match source.as_str() {
+            "default" => load_lines_or_default(&None, default_fn, "commit messages")?,
+            "custom" => match templates_path {
+                Some(_) => load_lines_or_default(templates_path, None, "commit messages")?,
+                None => anyhow::bail!(
+                    "--mix gives `custom` a share, but no templates file or team corpus is set"
+                ),
+            },
+            pack => {
+                let path = paths::packs_dir()
+                    .map(|dir| dir.join(pack))
+                    .filter(|path| path.is_file())
+                    .with_context(|| {
+                        format!("--mix names {:?}, which is no installed pack", pack)
+                    })?;
+                load_lines_or_default(&Some(path), None, "commit messages")?
+            }
+        };
+        sources.push((source.clone(), templates, *share));
     }
+    Ok(Mixed::compose(sources))
 }
 
-/// Generates a random number within the specified range.
-///
-/// If start > end, automatically adjusts end to start * 2.
-fn generate_random_in_range<R>(start: u32, end: u32, rng: &mut R) -> u32
+/// A generated commit message along with the inputs that produced it.
+#[derive(Debug, Clone, PartialEq)]
+struct Generated {
+    message: String,
+    name: String,
+    template_index: usize,
+    /// The numbers drawn for the message, when `--explain` asked for them.
+    drawn: Vec<template::Drawn>,
+    /// The second template and the template spliced from both, when `--mutate` made the
+    /// message from two.
+    spliced: Option<(usize, String)>,
+}
+
+impl Generated {
+    /// The indices of the templates the message was made from.
+    fn templates(&self) -> Vec<usize> {
+        let mut templates = vec![self.template_index];
+        templates.extend(self.spliced.as_ref().map(|&(second, _)| second));
+        templates
+    }
+
+    /// The template the message was rendered from, spliced or out of `commit_messages`.
+    fn template<'a>(&'a self, commit_messages: &'a Corpus) -> Result<Cow<'a, str>> {
+        match &self.spliced {
+            Some((_, template)) => Ok(Cow::Borrowed(template)),
+            None => commit_messages.get(self.template_index),
+        }
+    }
+}
+
+/// Returns the indices of the templates generation may pick from: only `template_id` when
+/// given, minus any template the blocklist rejects or `spice` caps.
+#[instrument(level = "debug", skip_all, fields(templates = commit_messages.len()))]
+fn template_pool(
+    commit_messages: &Corpus,
+    template_id: Option<usize>,
+    blocklist: Option<&Blocklist>,
+    spice: rating::Cap,
+) -> Result<Vec<usize>> {
+    let pool: Vec<usize> = match template_id {
+        Some(id) if id >= commit_messages.len() => {
+            return Err(WtcError::TemplateOutOfRange {
+                id,
+                templates: commit_messages.len(),
+            }
+            .into())
+        }
+        Some(id) => vec![id],
+        None => (0..commit_messages.len()).collect(),
+    };
+    // Every level is allowed at the top, so the templates needn't be read
+    if blocklist.is_none() && spice.allows_all() {
+        return Ok(pool);
+    }
+
+    let mut allowed = Vec::new();
+    for i in pool {
+        let template = commit_messages.get(i)?;
+        if blocklist.is_none_or(|b| b.allows(&template)) && spice.allows(&template) {
+            allowed.push(i);
+        }
+    }
+    if allowed.is_empty() && !commit_messages.is_empty() {
+        return Err(WtcError::EmptyPool(
+            "Every candidate template is blocked or too spicy for --spice".to_string(),
+        )
+        .into());
+    }
+    debug!(
+        "{} templates left after the blocklist and spice level",
+        allowed.len()
+    );
+    Ok(allowed)
+}
+
+/// Generates a message from a random template out of `pool`, a list of template indices.
+fn generate_commit_message<R>(
+    names: &[String],
+    commit_messages: &Corpus,
+    pool: &[usize],
+    explain: bool,
+    rng: &mut R,
+) -> Result<Generated>
 where
     R: Rng + ?Sized,
 {
-    let final_end = if start > end { start * 2 } else { end };
+    let name = names
+        .choose(rng)
+        .ok_or_else(|| WtcError::EmptyPool("Failed to select any names".to_string()))?;
+    let template_index = *pool
+        .choose(rng)
+        .ok_or_else(|| WtcError::EmptyPool("Failed to select any commit messages".to_string()))?;
+    let template = commit_messages.get(template_index)?;
+    let (message, drawn) = render_explained(&template, name, explain, rng);
 
-    if final_end > start {
-        rng.random_range(start..=final_end)
-    } else {
-        start
-    }
+    Ok(Generated {
+        message,
+        name: name::split(name).0.to_string(),
+        template_index,
+        drawn,
+        spliced: None,
+    })
 }
 
-/// Substitutes number placeholders (XNUM...X) in a template string.
-fn substitute_number_placeholders<R>(template: &str, rng: &mut R) -> String
+/// Most pairs of templates tried for a well-formed splice before a mutant falls back on a
+/// single template.
+const MUTATION_ATTEMPTS: usize = 20;
+
+/// Generates a message from a new template spliced together from two random templates out
+/// of `pool`, or from a single one when no splice of the pairs tried is well-formed, as
+/// with a pool of one template.
+fn generate_mutant<R>(
+    names: &[String],
+    commit_messages: &Corpus,
+    pool: &[usize],
+    explain: bool,
+    rng: &mut R,
+) -> Result<Generated>
 where
     R: Rng + ?Sized,
 {
-    let num_re = Regex::new(r"XNUM([0-9,]*)X").unwrap();
-
-    num_re
-        .replace_all(template, |caps: &regex_lite::Captures| {
-            let value_str = &caps[1];
-            let (start, end) = parse_number_range(value_str);
-            let random_num = generate_random_in_range(start, end, rng);
-            random_num.to_string()
-        })
-        .into_owned()
+    for _ in 0..MUTATION_ATTEMPTS {
+        let (Some(&first), Some(&second)) = (pool.choose(rng), pool.choose(rng)) else {
+            break;
+        };
+        if first == second {
+            continue;
+        }
+        let (head, tail) = (commit_messages.get(first)?, commit_messages.get(second)?);
+        let Some(template) = mutate::splice(&head, &tail, rng) else {
+            continue;
+        };
+        debug!("Spliced templates {} and {}: {}", first, second, template);
+        let name = names
+            .choose(rng)
+            .ok_or_else(|| WtcError::EmptyPool("Failed to select any names".to_string()))?;
+        let (message, drawn) = render_explained(&template, name, explain, rng);
+        return Ok(Generated {
+            message,
+            name: name::split(name).0.to_string(),
+            template_index: first,
+            drawn,
+            spliced: Some((second, template)),
+        });
+    }
+    debug!("No well-formed splice; generating from a single template");
+    generate_commit_message(names, commit_messages, pool, explain, rng)
+}
+
+/// Everything a generated message has to get past before it is printed.
+#[derive(Default, Clone, Copy)]
+struct Constraints<'a> {
+    script: Option<&'a Script>,
+    /// Rewrites of the message after the script, so rules judge what is printed.
+    replacements: Option<&'a Replacements>,
+    transforms: &'a [Transform],
+    /// Fixes to the subject line for a style guide, after the transforms.
+    normalizers: &'a [Normalizer],
+    /// Strip emoji before the normalizers, for `--no-emoji`.
+    no_emoji: bool,
+    /// The width to lay the message out at as a subject and a wrapped body, for `--wrap`.
+    wrap: Option<usize>,
+    /// `Co-authored-by` trailers to end the message with, for `--co-author`.
+    co_authors: usize,
+    /// Who signs off the message after any co-authors, for `--signoff`.
+    signer: Option<&'a Signer>,
+    commitlint: Option<&'a Commitlint>,
+    rules: Option<&'a Rules>,
+    /// Only accept messages that can be laid out as a haiku, and lay them out so.
+    haiku: bool,
+    /// Splice the clauses of two templates into a new one, as `--mutate` does.
+    mutate: bool,
+    /// The letter or digit the printed message has to start with, for `--acrostic`.
+    initial: Option<char>,
+    /// Keep the numbers drawn for each message, for `--explain`.
+    explain: bool,
 }
 
-/// Substitutes name placeholders in a template string.
-fn substitute_name_placeholders(template: &str, name: &str) -> String {
-    template
-        .replace("XUPPERNAMEX", &name.to_ascii_uppercase())
-        .replace("XLOWERNAMEX", &name.to_ascii_lowercase())
-        .replace("XNAMEX", name)
+/// Generates messages until one gets past the script and passes all rules, giving up
+/// after `MAX_ATTEMPTS`.
+#[instrument(level = "debug", skip_all)]
+fn generate_accepted<R>(
+    names: &[String],
+    commit_messages: &Corpus,
+    pool: &[usize],
+    constraints: &Constraints,
+    rng: &mut R,
+) -> Result<Generated>
+where
+    R: Rng + ?Sized,
+{
+    let mut rejection = String::new();
+    for _ in 0..MAX_ATTEMPTS {
+        let mut generated = match constraints.mutate {
+            true => generate_mutant(names, commit_messages, pool, constraints.explain, rng)?,
+            false => {
+                generate_commit_message(names, commit_messages, pool, constraints.explain, rng)?
+            }
+        };
+        let template = generated.template(commit_messages)?.into_owned();
+        if let Some(max) = lint::tags(&template).max_len {
+            if generated.message.chars().count() > max {
+                debug!("Message is longer than {}: {}", max, generated.message);
+                rejection = format!("it was longer than its template's max-len of {}", max);
+                continue;
+            }
+        }
+        if let Some(script) = constraints.script {
+            match script.apply(&generated.message, &generated.name, &template)? {
+                Some(message) => generated.message = message,
+                None => {
+                    debug!("Script vetoed message: {}", generated.message);
+                    rejection = "the script vetoed it".to_string();
+                    continue;
+                }
+            }
+        }
+        if let Some(replacements) = constraints.replacements {
+            generated.message = replacements.apply(&generated.message).into_owned();
+        }
+        if constraints.haiku {
+            match haiku::arrange(&generated.message) {
+                Some(message) => generated.message = message,
+                None => {
+                    debug!("Message isn't a haiku: {}", generated.message);
+                    rejection = "it isn't a 5-7-5 haiku".to_string();
+                    continue;
+                }
+            }
+        }
+        generated.message = transform::apply_all(constraints.transforms, generated.message);
+        if constraints.no_emoji {
+            generated.message = emoji::strip(&generated.message);
+        }
+        generated.message = normalize::apply_all(constraints.normalizers, generated.message);
+        if let Some(width) = constraints.wrap {
+            generated.message = wrap::apply(&generated.message, width);
+        }
+        if constraints.co_authors > 0 {
+            let trailers = coauthor::trailers(names, constraints.co_authors, rng);
+            generated.message = coauthor::append(&generated.message, &trailers);
+        }
+        if let Some(trailer) = constraints.signer.and_then(|s| s.trailer(names, rng)) {
+            generated.message = coauthor::append(&generated.message, &[trailer]);
+        }
+
+        if let Some(letter) = constraints.initial {
+            if acrostic::initial(&generated.message) != Some(letter) {
+                debug!(
+                    "Message doesn't start with {:?}: {}",
+                    letter, generated.message
+                );
+                rejection = format!("it doesn't start with {:?}", letter);
+                continue;
+            }
+        }
+        let mut violations = Vec::new();
+        if let Some(commitlint) = constraints.commitlint {
+            violations.extend(commitlint.violations(&generated.message));
+        }
+        if let Some(rules) = constraints.rules {
+            violations.extend(rules.violations(&generated.message));
+        }
+        if !violations.is_empty() {
+            debug!("Message breaks rules: {}", generated.message);
+            rejection = format!("it broke these rules: {}", violations.join(", "));
+            continue;
+        }
+        return Ok(generated);
+    }
+
+    Err(WtcError::EmptyPool(format!(
+        "No acceptable message after {} attempts; the last was rejected because {}",
+        MAX_ATTEMPTS, rejection
+    ))
+    .into())
 }
 
 /// Substitutes placeholders in a template string with actual values.
@@ -182,17 +757,50 @@ fn substitute_name_placeholders(template: &str, name: &str) -> String {
 /// - `XNUM1,5X` - Random number from 1 to 5 (range syntax with comma)
 /// - `XNUM,5X` - Random number from 1 to 5 (start defaults to 1)
 /// - `XNUM5,X` - Random number from 5 to 999 (end defaults to 999)
+/// - `XNUM-50,50X` - Random number from -50 to 50
+/// - `XNUM0,100:10X` - Random multiple of 10 from 0 to 100
+/// - `XNUM1,100000:logX` - Log-uniform, so small numbers are as likely as large ones
+/// - `XNUM0,100:normalX` - A bell curve around the middle of the range
+/// - `XNUM3,20:romanX` - Random number from 3 to 20 in Roman numerals
+/// - `XNUM10:ordinalX` - Random ordinal from 1st to 10th
+/// - `XNUM300:percentX` - Random percentage from 1% to 300%
+/// - `XNUM100,100000:currencyX` - Random amount of dollars and cents, such as $1,234.56
 ///
 /// Note: Commas are always treated as range separators. `XNUM1,000X` means range 1 to 0,
 /// which gets adjusted to 1 to 2 (since start > end triggers end = start * 2).
 /// Use `XNUM1000X` for 1 to 1000.
 ///
-/// If start > end, end is automatically set to start * 2.
+/// If start > end, end is automatically set to start * 2, or for a start of zero or below
+/// the bounds are swapped.
 ///
 /// ## Name Placeholders
 /// - `XNAMEX` - Replaced with the name as-is
 /// - `XUPPERNAMEX` - Replaced with the name in UPPERCASE
 /// - `XLOWERNAMEX` - Replaced with the name in lowercase
+/// - `XTITLENAMEX` - Replaced with the name in Title Case
+/// - `XMOCKNAMEX` - Replaced with the name in alternating case, like "jOhN"
+/// - `XPRONOUNX`, `XPOSSESSIVEX` - Replaced with the name's pronouns, like "she" and "her"
+///
+/// ## Other Placeholders
+/// - `XCOMPANYX` - Replaced with a made-up company or product name
+/// - `XLANGX` - Replaced with a programming language or framework
+/// - `XVERBX`, `XNOUNX`, `XADJX` - Replaced with a verb, noun or adjective
+/// - `XIPX`, `XIP6X` - Replaced with an IPv4 or IPv6 address reserved for documentation
+/// - `XUUIDX`, `XSHORTUUIDX` - Replaced with a random UUID or its first 8 hex digits
+/// - `XPATHX` - Replaced with a made-up source path, or a real one with `--real-paths`
+/// - `XURLX` - Replaced with a believable link that doesn't resolve
+/// - `XVERSIONX` - Replaced with a version number, or the one `release-notes` is given
+/// - `XSWEARX` - Replaced with a swear word, censored at `--spice censored`
+/// - `XQUOTEX` - Replaced with a short quote, from the fortune file `--quotes` gives if any
+///
+/// ## Directives
+/// - `{{set n = num:1..10}}` - Draws a number into the variable `n`, writing nothing
+/// - `{{n}}`, `{{n*10}}`, `{{n+1}}`, `{{n-1}}` - Writes `n`, maybe worked on
+/// - `{{50%: , I think}}` - Writes the text after the colon half of the time
+/// - `{{repeat 3: XPATHX, }}` - Writes a list of three paths, separated by `, `
+///
+/// With `--consistent`, a placeholder written exactly as an earlier one in the template
+/// repeats its value.
 ///
 /// # Arguments
 /// * `template` - The template string containing placeholders
@@ -202,38 +810,1921 @@ fn substitute_placeholders<R>(template: &str, name: &str, rng: &mut R) -> String
 where
     R: Rng + ?Sized,
 {
-    // First handle number placeholders
-    let with_numbers = substitute_number_placeholders(template, rng);
+    Template::parse(template).render(name, rng)
+}
 
-    // Then apply name substitutions
-    substitute_name_placeholders(&with_numbers, name)
+/// Substitutes the placeholders of `template`, returning the numbers drawn along with the
+/// message when `explain`, and none otherwise.
+fn render_explained<R>(
+    template: &str,
+    name: &str,
+    explain: bool,
+    rng: &mut R,
+) -> (String, Vec<template::Drawn>)
+where
+    R: Rng + ?Sized,
+{
+    match explain {
+        true => Template::parse(template).render_explained(name, rng),
+        false => (substitute_placeholders(template, name, rng), Vec::new()),
+    }
+}
+
+fn main() -> ExitCode {
+    let raw_args = match cli::expand_aliases(std::env::args_os().collect()) {
+        Ok(raw_args) => raw_args,
+        Err(e) => {
+            let output = Output::new(Default::default(), false, Theme::default());
+            return error::report(&e, Default::default(), &output);
+        }
+    };
+    let mut args = Args::parse_given(raw_args);
+
+    if let Err(e) = logging::init(args.log_level(), args.log_format) {
+        let output = Output::new(Default::default(), false, Theme::default());
+        return error::report(&e, Default::default(), &output);
+    }
+
+    // `init` writes the config file, so it must not fail on a missing or broken one
+    let setup = if matches!(args.command, Some(Command::Init)) {
+        args.output()
+    } else {
+        args.apply_config().and_then(|()| args.output())
+    };
+    // Errors during setup are still reported, just without the configured theme
+    let output = match &setup {
+        Ok(output) => *output,
+        Err(_) => Output::new(args.color.unwrap_or_default(), args.plain, Theme::default()),
+    };
+
+    match setup.and_then(|output| run(&args, &output)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => error::report(&e, args.errors.unwrap_or_default(), &output),
+    }
+}
+
+/// Builds the random number generator, returning it with its seed.
+///
+/// The seed comes from `--seed` or `--daily` when given and is random otherwise, so that
+/// any run can be reproduced from its recorded seed, except that `simulate` uses a fixed
+/// one. The crypto backend can't be seeded, so it has no seed.
+fn make_rng(args: &Args) -> Result<(Generator, Option<u64>)> {
+    let backend = args.rng.unwrap_or_default();
+    if !backend.seedable() {
+        if args.seed.is_some() || args.daily.is_some() {
+            anyhow::bail!(
+                "--rng crypto can't be seeded, so it can't be combined with --seed or --daily"
+            );
+        }
+        debug!("Using the {:?} generator, unseeded", backend);
+        return Ok((Generator::new(backend, 0), None));
+    }
+    let seed = match args.daily {
+        Some(scope) => daily::seed(scope)?,
+        None => match (args.seed, &args.command) {
+            (Some(seed), _) => seed,
+            (None, Some(Command::Simulate { .. })) => simulate::DEFAULT_SEED,
+            (None, _) => rand::rng().random(),
+        },
+    };
+    debug!("Using the {:?} generator with seed {}", backend, seed);
+    Ok((Generator::new(backend, seed), Some(seed)))
+}
+
+/// Combines the picked messages into one squash-style message, keeping the first one's
+/// name and template.
+#[cfg(feature = "tui")]
+fn squash_picked(batch: &[Generated], indices: &[usize]) -> Generated {
+    let messages: Vec<&str> = indices.iter().map(|&i| batch[i].message.as_str()).collect();
+    Generated {
+        message: squash::message(&messages),
+        ..batch[indices[0]].clone()
+    }
+}
+
+/// Commits with the message, then shows a notification if asked to. A notification
+/// that can't be shown only warrants a warning.
+fn commit(args: &Args, message: &str) -> Result<()> {
+    if !args.force {
+        check_branch(&args.protected_branches)?;
+    }
+    git::commit(message)?;
+    if args.notify {
+        if let Err(e) = git::short_head().and_then(|hash| notify::committed(&hash, message)) {
+            warn!("Failed to show a notification: {:#}", e);
+        }
+    }
+    Ok(())
+}
+
+/// `message` with a `Generated-by` trailer when `--provenance` asks for one, naming the
+/// version and, when they are known, the seed and the templates of the messages in it.
+fn with_provenance(args: &Args, message: &str, seed: Option<u64>, templates: &[usize]) -> String {
+    if !args.provenance {
+        return message.to_string();
+    }
+    let mut details = Vec::new();
+    if let Some(seed) = seed {
+        details.push(format!("seed={}", seed));
+    }
+    if !templates.is_empty() {
+        let templates: Vec<String> = templates.iter().map(usize::to_string).collect();
+        details.push(format!("template={}", templates.join("+")));
+    }
+    let mut trailer = format!(
+        "Generated-by: whatthecommit-cli v{}",
+        env!("CARGO_PKG_VERSION")
+    );
+    if !details.is_empty() {
+        trailer += &format!(" ({})", details.join(", "));
+    }
+    coauthor::append(message, &[trailer])
+}
+
+/// Refuses to commit to a branch matching one of `patterns`.
+fn check_branch(patterns: &[String]) -> Result<()> {
+    let Some(branch) = git::current_branch()? else {
+        return Ok(());
+    };
+    match branch::protected(&branch, patterns) {
+        Some(pattern) => Err(WtcError::GitError {
+            message: format!(
+                "Refusing to commit to protected branch `{}` (matches `{}`); use --force to commit anyway",
+                branch, pattern
+            ),
+            source: None,
+        }
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// Prints a generated message in the requested style, escaped for `--escape`, then posts
+/// it as it is to any webhooks.
+fn emit(
+    args: &Args,
+    output: &Output,
+    commit_messages: &Corpus,
+    generated: &Generated,
+) -> Result<()> {
+    let message = match args.ci {
+        true => ci::single_line(&generated.message),
+        false => Cow::Borrowed(generated.message.as_str()),
+    };
+    let message = args.escape.unwrap_or_default().apply(&message);
+    let rendered = if args.cz {
+        // Adapters get the message as generated, and escape it themselves if they need to
+        format!("{}\n", cz::render(&generated.message))
+    } else if args.banner {
+        banner::render(&message, banner::terminal_width())?
+    } else if let Some(character) = args.cowsay {
+        cowsay::render(&message, character)
+    } else if let Some(format) = &args.format {
+        let template = generated.template(commit_messages)?;
+        let escaped = Generated {
+            message: message.into_owned(),
+            ..generated.clone()
+        };
+        format!("{}\n", format.render(&escaped, &template))
+    } else {
+        format!("{}\n", output.message(&message, &generated.name))
+    };
+    match args.typewriter.filter(|_| animate(args)) {
+        Some(ms) => animation::typewriter(&rendered, Duration::from_millis(ms), args.bell)?,
+        None => {
+            let mut stdout = io::stdout();
+            stdout.write_all(rendered.as_bytes())?;
+            stdout.flush()?;
+        }
+    }
+
+    let http = args.http();
+    for url in &args.post {
+        if http.offline {
+            warn!("Not posting to {} while offline", url);
+            continue;
+        }
+        match webhook::post(url, &generated.message, &http) {
+            Err(e) if args.network_policy == Some(NetworkPolicy::Prefer) => {
+                debug!("Not posted to {}: {:#}", url, e);
+            }
+            posted => posted?,
+        }
+    }
+    Ok(())
+}
+
+/// The corpora and settings of an `rpc` session.
+struct RpcSession<'a> {
+    names: &'a [String],
+    /// Each corpus by name, with its pool and constraints
+    corpora: Vec<(&'static str, &'a Corpus, Vec<usize>, Constraints<'a>)>,
+    transforms: Vec<Transform>,
+    unique: bool,
+    rng: &'a mut Generator,
+}
+
+impl rpc::Methods for RpcSession<'_> {
+    fn generate(&mut self, corpus: &str, count: usize) -> Result<Vec<rpc::Message>> {
+        let Some((_, templates, pool, constraints)) =
+            self.corpora.iter().find(|(name, ..)| *name == corpus)
+        else {
+            anyhow::bail!("No corpus named `{}`; see list-packs", corpus);
+        };
+        let constraints = Constraints {
+            transforms: &self.transforms,
+            ..*constraints
+        };
+        let batch = generate_batch(
+            self.names,
+            templates,
+            pool.clone(),
+            &constraints,
+            count,
+            self.unique,
+            self.rng,
+        )?;
+        batch
+            .into_iter()
+            .map(|generated| {
+                Ok(rpc::Message {
+                    template: generated.template(templates)?.into_owned(),
+                    message: generated.message,
+                    name: generated.name,
+                })
+            })
+            .collect()
+    }
+
+    fn packs(&self) -> Vec<rpc::Pack> {
+        self.corpora
+            .iter()
+            .map(|(name, _, pool, _)| rpc::Pack {
+                name,
+                templates: pool.len(),
+            })
+            .collect()
+    }
+
+    fn set_config(&mut self, settings: rpc::Settings) -> Result<()> {
+        if let Some(unique) = settings.unique {
+            self.unique = unique;
+        }
+        if let Some(transforms) = settings.transform {
+            self.transforms = transforms;
+        }
+        if let Some(seed) = settings.seed {
+            if !self.rng.backend().seedable() {
+                anyhow::bail!("The crypto generator can't be seeded");
+            }
+            *self.rng = Generator::new(self.rng.backend(), seed);
+        }
+        Ok(())
+    }
+}
+
+/// Prints a new message every `--interval` until interrupted or until whoever reads
+/// stdout goes away.
+fn stream(
+    args: &Args,
+    output: &Output,
+    names: &[String],
+    commit_messages: &Corpus,
+    pool: &[usize],
+    constraints: &Constraints,
+    rng: &mut Generator,
+) -> Result<()> {
+    let clear = args.clear && !args.accessible && io::stdout().is_terminal();
+    loop {
+        let generated = generate_accepted(names, commit_messages, pool, constraints, rng)?;
+        if clear {
+            animation::clear_screen()?;
+        }
+        match emit(args, output, commit_messages, &generated) {
+            Err(e) if reader_gone(&e) => return Ok(()),
+            result => result?,
+        }
+        remember(args, &[&generated.message], None);
+        tally(args, commit_messages, std::slice::from_ref(&generated));
+        thread::sleep(args.interval);
+    }
+}
+
+/// Whether `error` is a write to a pipe whose reader has gone, like `head` once it has
+/// read enough.
+fn reader_gone(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<io::Error>()
+        .is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe)
+}
+
+/// Whether animations are shown: only on a terminal, and never in accessible mode.
+fn animate(args: &Args) -> bool {
+    !args.accessible && animation::enabled()
+}
+
+/// Whether prompt and status line segments are colored. Their output is captured rather
+/// than written to a terminal, so only explicit opt-outs count.
+fn colored_status(args: &Args) -> bool {
+    !args.plain
+        && !args.accessible
+        && args.color != Some(ColorChoice::Never)
+        && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+}
+
+/// Records emitted messages in the history unless it is disabled. Failing to do so
+/// only warrants a warning.
+fn remember(args: &Args, messages: &[&str], seed: Option<u64>) {
+    if args.no_history {
+        return;
+    }
+    let entries: Vec<history::Entry> = messages
+        .iter()
+        .map(|message| history::Entry::now(message, seed))
+        .collect();
+    if let Err(e) =
+        history::path().and_then(|path| history::record(&path, &entries, args.retention))
+    {
+        warn!("Failed to record history: {:#}", e);
+    }
+}
+
+/// Counts a skip of each of the templates with indices `skipped`, so they come up less
+/// often, unless learning is turned off.
+fn learn_skips(args: &Args, commit_messages: &Corpus, skipped: &[usize]) {
+    if args.no_learn || skipped.is_empty() {
+        return;
+    }
+    let recorded = skipped
+        .iter()
+        .map(|&i| commit_messages.get(i))
+        .collect::<Result<Vec<_>>>()
+        .and_then(|templates| {
+            let templates: Vec<&str> = templates.iter().map(|t| t.as_ref()).collect();
+            weights::record(&weights::path()?, &templates, weights::now())
+        });
+    if let Err(e) = recorded {
+        warn!("Failed to record skipped templates: {:#}", e);
+    }
+}
+
+/// The template indices of the messages of `batch` not at the `picked` positions.
+fn passed_over(batch: &[Generated], picked: &[usize]) -> Vec<usize> {
+    (batch.iter().enumerate())
+        .filter(|(i, _)| !picked.contains(i))
+        .map(|(_, g)| g.template_index)
+        .collect()
+}
+
+/// Counts generated messages in the usage statistics, when they are turned on.
+fn tally(args: &Args, commit_messages: &Corpus, batch: &[Generated]) {
+    if !args.usage_stats {
+        return;
+    }
+    let corpus = match (&args.commit_messages_template, &args.team_corpus_url) {
+        (Some(path), _) => path.display().to_string(),
+        (None, Some(url)) => url.clone(),
+        (None, None) => "built-in".to_string(),
+    };
+    let recorded = batch
+        .iter()
+        .map(|g| commit_messages.get(g.template_index))
+        .collect::<Result<Vec<_>>>()
+        .and_then(|templates| {
+            let templates: Vec<&str> = templates.iter().map(|t| t.as_ref()).collect();
+            usage::record(&usage::path()?, &corpus, &templates)
+        });
+    if let Err(e) = recorded {
+        warn!("Failed to record usage statistics: {:#}", e);
+    }
 }
 
-fn main() -> Result<()> {
-    env_logger::init();
+/// Fails before doing anything when the command line asks for something this build was
+/// compiled without, rather than part-way through.
+fn check_features(args: &Args) -> Result<()> {
+    let interactive =
+        matches!(args.command, Some(Command::Tui | Command::Keys)) || args.pick.is_some();
+    let missing = if interactive && !cfg!(feature = "tui") {
+        Some(("the full-screen browser, `--pick` or key bindings", "tui"))
+    } else if !args.post.is_empty() && !args.offline && !cfg!(feature = "network") {
+        Some(("webhooks", "network"))
+    } else if (args.commit || args.names_from_git) && !cfg!(feature = "git") {
+        Some(("git", "git"))
+    } else if args.llm.is_some() && !cfg!(feature = "network") {
+        Some(("--llm", "network"))
+    } else {
+        None
+    };
+    match missing {
+        Some((what, feature)) => Err(WtcError::Disabled { what, feature }.into()),
+        None => Ok(()),
+    }
+}
 
-    let args = Args::parse();
+/// Fails when `--ci` is combined with a command that waits for a person or never ends.
+fn check_ci(args: &Args) -> Result<()> {
+    let interactive = match args.command {
+        Some(Command::Init) => "init",
+        Some(Command::Tui) => "tui",
+        Some(Command::Quiz { .. }) => "quiz",
+        Some(Command::Watch { .. }) => "watch",
+        _ => return Ok(()),
+    };
+    anyhow::bail!(
+        "`{}` is interactive, so it can't run with --ci",
+        interactive
+    )
+}
 
-    let names = load_lines_or_default(&args.names, default_names, "names")?;
-    let commit_messages = load_lines_or_default(
+fn run(args: &Args, output: &Output) -> Result<()> {
+    check_features(args)?;
+    if args.ci {
+        check_ci(args)?;
+        ci::set_strict();
+    }
+    match &args.command {
+        Some(Command::Init) => return init::run(args.config.as_deref()),
+        Some(Command::Paths) => return paths::print(args.config.as_deref()),
+        Some(Command::Bundle { action }) => {
+            return match action {
+                BundleAction::Export { output } => bundle::export(args.config.as_deref(), output),
+                BundleAction::Import { file, force } => bundle::import(file, *force),
+            };
+        }
+        Some(Command::Bake { output, force }) => {
+            return bake::run(args.config.as_deref(), output, *force);
+        }
+        Some(Command::Auth { action }) => {
+            return match action {
+                AuthAction::Login { name } => keychain::login(name),
+                AuthAction::Logout { name } => keychain::logout(name),
+            };
+        }
+        Some(Command::Pack { action }) => {
+            return match action {
+                PackAction::Merge {
+                    files,
+                    output,
+                    distance,
+                } => pack::merge_files(files, output, *distance),
+                PackAction::Diff { old, new, distance } => pack::diff_files(old, new, *distance),
+                PackAction::Install {
+                    source,
+                    signature,
+                    name,
+                    force,
+                } => install::run(
+                    source,
+                    signature.as_deref(),
+                    name.as_deref(),
+                    *force,
+                    &args.trusted_keys,
+                    &args.http(),
+                    args.locked,
+                ),
+            };
+        }
+        Some(Command::Fmt { files, sort, check }) => return formatter::run(files, *sort, *check),
+        Some(Command::Man { output }) => return man::run(output.as_deref()),
+        Some(Command::Widget { shell, key }) => {
+            print!("{}", widget::script(*shell, *key));
+            return Ok(());
+        }
+        Some(Command::Lsp) => return lsp::serve(io::stdin().lock(), io::stdout().lock()),
+        Some(Command::Weights { action }) => {
+            let path = weights::path()?;
+            return match action {
+                WeightsAction::Show => {
+                    println!(
+                        "{}",
+                        weights::report(&weights::load(&path)?, weights::now())
+                    );
+                    Ok(())
+                }
+                WeightsAction::Reset => weights::reset(&path),
+            };
+        }
+        Some(Command::Fav { action }) => {
+            let path = favorites::path()?;
+            return match action {
+                FavAction::Add { message } => {
+                    if !favorites::add(&path, &message.join(" "))? {
+                        eprintln!("Already a favorite");
+                    }
+                    Ok(())
+                }
+                FavAction::List => favorites::list(&path),
+                FavAction::Random => favorites::random(&path, &mut make_rng(args)?.0),
+                FavAction::Remove { index } => {
+                    println!("{}", favorites::remove(&path, *index)?);
+                    Ok(())
+                }
+            };
+        }
+        #[cfg(feature = "tui")]
+        Some(Command::Keys) => return keys::print(args.keys.as_ref()),
+        Some(Command::History { grep, limit, clear }) => {
+            let path = history::path()?;
+            return match clear {
+                true => history::clear(&path),
+                false => history::print(&path, grep.as_deref(), *limit),
+            };
+        }
+        Some(Command::Usage { top, clear }) => {
+            let path = usage::path()?;
+            if *clear {
+                return usage::clear(&path);
+            }
+            println!("{}", usage::report(&usage::load(&path)?, *top));
+            return Ok(());
+        }
+        Some(Command::SelfUpdate { check, force }) => {
+            return self_update::run(*check, *force, &args.http())
+        }
+        Some(Command::RefreshTeamCorpus { url }) => return team::refresh(url, &args.http()),
+        _ => {}
+    }
+
+    let (mut rng, seed) = make_rng(args)?;
+
+    let defaults =
+        |default_fn: fn() -> Vec<String>| (!args.no_default_corpus).then_some(default_fn);
+    let from_stdin = |path: &Option<PathBuf>| path.as_deref() == Some(Path::new(STDIN));
+    if from_stdin(&args.names) && from_stdin(&args.commit_messages_template) {
+        anyhow::bail!("Only one of the names and the commit messages can be read from stdin");
+    }
+    // Generating needs only a sample of a streamed corpus, while subcommands like `list`
+    // show all of it
+    let sample = (args.command.is_none() && args.template_id.is_none())
+        .then(|| STDIN_SAMPLE.max(args.pick.or(args.choose).unwrap_or(args.count)));
+    let loading = Instant::now();
+    let names_path = team_file(&args.names, args.team_names_url.as_deref(), args)?;
+    let templates_path = team_file(
         &args.commit_messages_template,
-        default_commit_messages,
-        "commit messages",
+        args.team_corpus_url.as_deref(),
+        args,
     )?;
+    let names = match from_stdin(&args.names) {
+        true => load_stream(io::stdin().lock(), "names", sample, &mut rng)?,
+        false if args.names_from_git => git_names(args.weight_by_commits)?,
+        false => load_lines_or_default(&names_path, defaults(default_names), "names")?,
+    };
+    let grammar = args.grammar.as_deref().map(Grammar::load).transpose()?;
+    let mut mixed = None;
+    let stdin = from_stdin(&args.commit_messages_template);
+    let mut commit_messages = match (stdin, &grammar, &args.mix) {
+        (true, _, Some(_)) => anyhow::bail!("--mix can't mix in templates read from stdin"),
+        (true, _, None) => Corpus::from(load_stream(
+            io::stdin().lock(),
+            "commit messages",
+            sample,
+            &mut rng,
+        )?),
+        (false, Some(grammar), _) => Corpus::from(grammar.templates(GRAMMAR_TEMPLATES, &mut rng)),
+        (false, None, Some(mix)) => {
+            let default_fn = match args.haiku {
+                true => haiku::templates,
+                false => default_commit_messages,
+            };
+            let (corpus, sources) = load_mix(mix, &templates_path, defaults(default_fn))?;
+            mixed = Some(sources);
+            corpus
+        }
+        (false, None, None) if args.haiku => {
+            load_templates(&templates_path, defaults(haiku::templates))?
+        }
+        (false, None, None) => load_templates(&templates_path, defaults(default_commit_messages))?,
+    };
+    let explainer = match args.explain {
+        true => Some(explainer(
+            args,
+            &templates_path,
+            commit_messages.len(),
+            seed,
+        )),
+        false => None,
+    };
+    let mut word_lists = words::Words::load(paths::words_dir().as_deref(), args.spice())?;
+    if args.real_paths {
+        match git::tracked_files() {
+            Ok(files) => word_lists.paths = files,
+            Err(e) => info!("Making up paths instead of using real ones: {:#}", e),
+        }
+    }
+    if let Some(path) = &args.quotes {
+        word_lists.quotes = words::read_quotes(path)?;
+    }
+    word_lists.consistent = args.consistent;
+    if let Some(Command::ReleaseNotes { version, .. }) = &args.command {
+        // The heading and every XVERSIONX in the notes name the same release
+        word_lists.version = Some(
+            version
+                .clone()
+                .unwrap_or_else(|| words::random_version(&mut rng)),
+        );
+    }
+    words::init(word_lists);
+    let load_time = loading.elapsed();
+
+    match &args.command {
+        Some(Command::Preview { template, count }) => {
+            let commit_messages = commit_messages.to_lines()?;
+            return preview::run(template, *count, &names, &commit_messages, &mut rng, output);
+        }
+        Some(Command::Watch { path, count }) => {
+            return watch::run(path, *count, &names, &mut rng, output, !args.accessible)
+        }
+        Some(Command::I18n { action }) => {
+            return match action {
+                I18nAction::Export { files, output } => {
+                    let loaded = templates_path
+                        .as_deref()
+                        .unwrap_or(Path::new("built-in templates"));
+                    let lines = commit_messages.to_lines()?;
+                    i18n::export_files(files, (loaded, &lines), output)
+                }
+                I18nAction::Import { po, output } => i18n::import_file(po, output),
+            };
+        }
+        Some(Command::Spellcheck { files, allowlist }) => {
+            let loaded = templates_path
+                .as_deref()
+                .unwrap_or(Path::new("built-in templates"));
+            let lines = commit_messages.to_lines()?;
+            return spellcheck::run(files, (loaded, &lines), allowlist.as_deref());
+        }
+        Some(Command::Coverage { files, format }) => {
+            let loaded = templates_path
+                .as_deref()
+                .unwrap_or(Path::new("built-in templates"));
+            let lines = commit_messages.to_lines()?;
+            let words_dir = paths::words_dir();
+            return coverage::run(files, (loaded, &lines), words_dir.as_deref(), *format);
+        }
+        Some(Command::Dedupe {
+            files,
+            fix,
+            distance,
+            ..
+        }) => {
+            let loaded = templates_path
+                .as_deref()
+                .unwrap_or(Path::new("built-in templates"));
+            let files = match files.is_empty() {
+                true => templates_path.iter().cloned().collect(),
+                false => files.clone(),
+            };
+            let lines = commit_messages.to_lines()?;
+            return dedupe::run(&files, (loaded, &lines), *distance, *fix);
+        }
+        Some(Command::Search { query }) => return search::run(query, &commit_messages.to_lines()?),
+        Some(Command::List {
+            filter,
+            offset,
+            limit,
+        }) => return list::run(&commit_messages.to_lines()?, filter, *offset, *limit),
+        Some(Command::Name { case, count }) => return name::run(&names, *case, *count, &mut rng),
+        Some(Command::Codename {
+            count,
+            starting_letter,
+            version,
+        }) => return codename::run(*count, *starting_letter, version.as_deref(), &mut rng),
+        _ => {}
+    }
+
+    let blocklist = args.blocklist.as_deref().map(Blocklist::load).transpose()?;
+    let max_spice = args.spice();
+    let spice = rating::Cap::new(max_spice, false);
+    let replacements = Replacements::new(&args.replace);
+    // Commit message rules and scripts don't apply to excuses, standups and release notes
+    let text_constraints = Constraints {
+        replacements: replacements.as_ref(),
+        transforms: &args.transform,
+        ..Default::default()
+    };
+    // Subcommands printing lines from a corpus of their own
+    let own_corpus = match &args.command {
+        Some(Command::Excuse { count, file }) => Some((
+            count,
+            file,
+            default_excuses as fn() -> Vec<String>,
+            "excuses",
+        )),
+        Some(Command::Review { count, file }) => Some((
+            count,
+            file,
+            default_reviews as fn() -> Vec<String>,
+            "review comments",
+        )),
+        _ => None,
+    };
+    if let Some((count, file, default_fn, what)) = own_corpus {
+        let templates = Corpus::from(load_lines_or_default(file, defaults(default_fn), what)?);
+        let pool = template_pool(&templates, None, blocklist.as_ref(), spice)?;
+        for _ in 0..*count {
+            let line = generate_accepted(&names, &templates, &pool, &text_constraints, &mut rng)?;
+            println!("{}", line.message);
+        }
+        return Ok(());
+    }
+    if let Some(Command::BranchName {
+        count,
+        create,
+        file,
+    }) = &args.command
+    {
+        let templates = Corpus::from(load_lines_or_default(
+            file,
+            defaults(default_branch_names),
+            "branch names",
+        )?);
+        let pool = template_pool(&templates, None, blocklist.as_ref(), spice)?;
+        for _ in 0..*count {
+            let generated =
+                generate_accepted(&names, &templates, &pool, &text_constraints, &mut rng)?;
+            let name = branch::slug(&generated.message);
+            if let Err(problem) = branch::check_name(&name) {
+                anyhow::bail!(
+                    "Branch name {:?} from template {:?} {}",
+                    name,
+                    generated.template(&templates)?,
+                    problem
+                );
+            }
+            if *create {
+                git::create_branch(&name)?;
+            } else {
+                println!("{}", name);
+            }
+        }
+        return Ok(());
+    }
+    if let Some(Command::Series { count, file }) = &args.command {
+        let mut stages = match file {
+            Some(path) => {
+                let text = fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read series file: {:?}", path))?;
+                series::parse(&text).with_context(|| format!("Invalid series file: {:?}", path))?
+            }
+            None if args.no_default_corpus => {
+                return Err(WtcError::NoCorpus { what: "series" }.into())
+            }
+            None => series::parse(series::DEFAULT)?,
+        };
+        for templates in &mut stages {
+            templates.retain(|template| {
+                blocklist.as_ref().is_none_or(|b| b.allows(template)) && spice.allows(template)
+            });
+            if templates.is_empty() {
+                return Err(WtcError::EmptyPool(
+                    "Every template of a series stage is blocked or too spicy for --spice"
+                        .to_string(),
+                )
+                .into());
+            }
+        }
+        let name = names
+            .choose(&mut rng)
+            .ok_or_else(|| WtcError::EmptyPool("Failed to select any names".to_string()))?;
+        for message in series::generate(&stages, *count, name, &mut rng) {
+            let message = match &replacements {
+                Some(replacements) => replacements.apply(&message).into_owned(),
+                None => message,
+            };
+            println!("{}", transform::apply_all(&args.transform, message));
+        }
+        return Ok(());
+    }
+    if let Some(Command::Standup {
+        items,
+        output,
+        file,
+    }) = &args.command
+    {
+        let sections = match file {
+            Some(path) => {
+                let text = fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read standup file: {:?}", path))?;
+                standup::parse(&text)
+                    .with_context(|| format!("Invalid standup file: {:?}", path))?
+            }
+            None if args.no_default_corpus => {
+                return Err(WtcError::NoCorpus { what: "standup" }.into())
+            }
+            None => standup::parse(standup::DEFAULT)?,
+        };
+        let mut parts = Vec::new();
+        for (section, templates) in standup::Section::ALL.into_iter().zip(sections) {
+            let templates = Corpus::from(templates);
+            let pool = template_pool(&templates, None, blocklist.as_ref(), spice)?;
+            let count = match section {
+                standup::Section::Blockers => 1,
+                _ => *items,
+            };
+            // Repeating an item reads worse than a short section
+            let count = count.min(pool.len());
+            let batch = generate_batch(
+                &names,
+                &templates,
+                pool,
+                &text_constraints,
+                count,
+                true,
+                &mut rng,
+            )?;
+            parts.push((section, batch.into_iter().map(|g| g.message).collect()));
+        }
+        println!("{}", standup::render(&parts, *output));
+        return Ok(());
+    }
+    if let Some(Command::ReleaseNotes { count, file, .. }) = &args.command {
+        let sections = match file {
+            Some(path) => {
+                let text = fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read release notes file: {:?}", path))?;
+                release_notes::parse(&text)
+                    .with_context(|| format!("Invalid release notes file: {:?}", path))?
+            }
+            None if args.no_default_corpus => {
+                return Err(WtcError::NoCorpus {
+                    what: "release notes",
+                }
+                .into())
+            }
+            None => release_notes::parse(release_notes::DEFAULT)?,
+        };
+        let counts = release_notes::split(*count);
+        let mut parts = Vec::new();
+        for ((section, templates), count) in release_notes::Section::ALL
+            .into_iter()
+            .zip(sections)
+            .zip(counts)
+        {
+            let templates = Corpus::from(templates);
+            let pool = template_pool(&templates, None, blocklist.as_ref(), spice)?;
+            let count = count.min(pool.len());
+            let batch = generate_batch(
+                &names,
+                &templates,
+                pool,
+                &text_constraints,
+                count,
+                true,
+                &mut rng,
+            )?;
+            parts.push((section, batch.into_iter().map(|g| g.message).collect()));
+        }
+        let version = words::get().version(&mut rng);
+        println!("{}", release_notes::render(&version, &parts));
+        return Ok(());
+    }
+    // Templates fetched from a team's URL are rated by their words unless tagged
+    let fetched = args.commit_messages_template.is_none() && args.team_corpus_url.is_some();
+    let mut pool = template_pool(
+        &commit_messages,
+        args.template_id,
+        blocklist.as_ref(),
+        rating::Cap::new(max_spice, fetched),
+    )?;
+    let imperatives = match args.imperative {
+        true => Some(Imperatives::load(paths::words_dir().as_deref())?),
+        false => None,
+    };
+    if let Some(imperatives) = &imperatives {
+        let mut kept = Vec::with_capacity(pool.len());
+        for i in pool {
+            if imperatives.allows(&commit_messages.get(i)?) {
+                kept.push(i);
+            }
+        }
+        if kept.is_empty() {
+            return Err(WtcError::EmptyPool(
+                "No candidate template starts with an imperative verb for --imperative".to_string(),
+            )
+            .into());
+        }
+        pool = kept;
+    }
+    // Messages are Conventional Commits for commitizen and commitlint
+    if args.cz || args.commitlint.is_some() {
+        let mut kept = Vec::with_capacity(pool.len());
+        for i in pool {
+            if !lint::tags(&commit_messages.get(i)?).no_conventional {
+                kept.push(i);
+            }
+        }
+        if kept.is_empty() {
+            return Err(WtcError::EmptyPool(
+                "Every candidate template is tagged {{no-conventional}}".to_string(),
+            )
+            .into());
+        }
+        pool = kept;
+    }
+    if let (Some(mixed), None) = (&mixed, args.template_id) {
+        mixed.weigh(&mut pool);
+    }
+    let eggs = match args.no_easter_eggs {
+        true => Vec::new(),
+        false => {
+            let (today, hour) = date::local(date::now());
+            easter_eggs::hatched(today, hour)
+        }
+    };
+    // The season and easter eggs only flavor the built-in corpus
+    let built_in =
+        templates_path.is_none() && grammar.is_none() && !args.haiku && args.template_id.is_none();
+    if let (Corpus::Lines(lines), true) = (&mut commit_messages, built_in) {
+        let mut extra = match args.seasonal.unwrap_or_default() {
+            Seasonal::On => seasonal::templates(Date::today()),
+            Seasonal::Off => Vec::new(),
+        };
+        extra.extend(eggs.iter().flat_map(|egg| egg.templates.iter().cloned()));
+        if let Some(blocklist) = &blocklist {
+            extra.retain(|template| blocklist.allows(template));
+        }
+        extra.retain(|template| spice.allows(template));
+        if let Some(imperatives) = &imperatives {
+            extra.retain(|template| imperatives.allows(template));
+        }
+        let weight = seasonal::weight(pool.len(), extra.len());
+        debug!(
+            "Mixing in {} seasonal and easter egg templates",
+            extra.len()
+        );
+        for template in extra {
+            pool.extend(std::iter::repeat_n(lines.len(), weight));
+            lines.push(template);
+        }
+    }
+    let pinned = boost::apply(&args.boost, &commit_messages, &mut pool)?;
+    if !args.no_learn && args.template_id.is_none() {
+        let weights = weights::path().and_then(|path| weights::load(&path))?;
+        weights.apply(&commit_messages, &mut pool, weights::now())?;
+    }
+    let script = args.script.as_deref().map(Script::load).transpose()?;
+    let commitlint = args
+        .commitlint
+        .as_deref()
+        .map(Commitlint::load)
+        .transpose()?;
+    let rules = args.rules.as_deref().map(Rules::load).transpose()?;
+    let signer = args.signoff.map(Signoff::signer).transpose()?;
+    let constraints = Constraints {
+        script: script.as_ref(),
+        replacements: replacements.as_ref(),
+        transforms: &args.transform,
+        normalizers: &args.normalize,
+        no_emoji: args.no_emoji,
+        wrap: args.wrap,
+        co_authors: args.co_author.unwrap_or_default(),
+        signer: signer.as_ref(),
+        commitlint: commitlint.as_ref(),
+        rules: rules.as_ref(),
+        haiku: args.haiku,
+        mutate: args.mutate,
+        initial: None,
+        explain: args.explain,
+    };
+    if let Some(Command::Verify { files, renders }) = &args.command {
+        let loaded = templates_path
+            .as_deref()
+            .unwrap_or(Path::new("built-in templates"));
+        let checks = verify::Checks {
+            rules: rules.as_ref(),
+            commitlint: commitlint.as_ref(),
+        };
+        let seed = args.seed.unwrap_or_default();
+        let lines = commit_messages.to_lines()?;
+        return verify::run(files, (loaded, &lines), &names, *renders, seed, checks);
+    }
+    // Prompt and status line segments show the same message until it goes stale
+    let cached_message = |name: &str, ttl, rng: &mut Generator| -> Result<(String, String)> {
+        cache::get_or_generate(&cache::path(name)?, ttl, || {
+            let generated = generate_accepted(&names, &commit_messages, &pool, &constraints, rng)?;
+            Ok((generated.message, generated.name))
+        })
+    };
+    if args.prepare_commit_msg {
+        let (path, source) = match args.hook_args.as_slice() {
+            [path, source, ..] => (path, Some(source.as_str())),
+            [path] => (path, None),
+            [] => anyhow::bail!("--prepare-commit-msg needs the commit message file"),
+        };
+        return hook::run(Path::new(path), source, || {
+            let generated =
+                generate_accepted(&names, &commit_messages, &pool, &constraints, &mut rng)?;
+            let templates = generated.templates();
+            let message = rework(args, generated.message);
+            Ok(with_provenance(args, &message, seed, &templates))
+        });
+    }
+    if let Some(Command::Prompt {
+        ttl,
+        shell,
+        max_width,
+    }) = &args.command
+    {
+        let (message, name) = cached_message("prompt", *ttl, &mut rng)?;
+        let theme = args.theme()?;
+        let theme = colored_status(args).then_some(&theme);
+        println!(
+            "{}",
+            prompt::render(&message, &name, theme, *shell, *max_width)
+        );
+        return Ok(());
+    }
+    if let Some(Command::Tmux {
+        interval,
+        max_width,
+    }) = &args.command
+    {
+        let (message, name) = cached_message("tmux", *interval, &mut rng)?;
+        let theme = args.theme()?;
+        let theme = colored_status(args).then_some(&theme);
+        println!("{}", tmux::render(&message, &name, theme, *max_width));
+        return Ok(());
+    }
+
+    if let Some(Command::Simulate { messages }) = &args.command {
+        let seed = seed.context("--rng crypto can't be seeded, so it can't simulate")?;
+        let report = simulate::run(&commit_messages, &pool, *messages, seed, &mut rng, |rng| {
+            generate_accepted(&names, &commit_messages, &pool, &constraints, rng)
+        })?;
+        println!("{}", report.render(&commit_messages)?);
+        return Ok(());
+    }
+
+    if let Some(Command::Bench { messages }) = &args.command {
+        let report = bench::measure(
+            &names,
+            &commit_messages,
+            &pool,
+            &constraints,
+            args.format.as_ref(),
+            output,
+            *messages,
+            load_time,
+            &mut rng,
+        )?;
+        println!("{}", report.render());
+        return Ok(());
+    }
+
+    if let Some(Command::Quiz { rounds }) = &args.command {
+        let real = git::log_subjects(QUIZ_HISTORY)?;
+        let generate = |rng: &mut Generator| {
+            generate_accepted(&names, &commit_messages, &pool, &constraints, rng)
+                .map(|generated| generated.message)
+        };
+        let stdin = io::stdin();
+        quiz::run(
+            real,
+            generate,
+            *rounds,
+            &mut rng,
+            stdin.lock(),
+            io::stdout(),
+        )?;
+        return Ok(());
+    }
+
+    if let Some(Command::Rpc | Command::Serve { .. }) = &args.command {
+        let own = |default_fn: fn() -> Vec<String>| -> Result<(Corpus, Vec<usize>)> {
+            let templates = Corpus::from(default_fn());
+            let pool = template_pool(&templates, None, blocklist.as_ref(), spice)?;
+            Ok((templates, pool))
+        };
+        let (excuses, reviews) = match args.no_default_corpus {
+            true => (None, None),
+            false => (Some(own(default_excuses)?), Some(own(default_reviews)?)),
+        };
+        let mut corpora = vec![(rpc::DEFAULT_CORPUS, &commit_messages, pool, constraints)];
+        for (name, own) in [("excuses", &excuses), ("reviews", &reviews)] {
+            if let Some((templates, pool)) = own {
+                corpora.push((name, templates, pool.clone(), text_constraints));
+            }
+        }
+        let mut session = RpcSession {
+            names: &names,
+            corpora,
+            transforms: args.transform.clone(),
+            unique: args.unique,
+            rng: &mut rng,
+        };
+        return match &args.command {
+            Some(Command::Serve { listen, .. }) => grpc::serve(&mut session, *listen),
+            _ => rpc::serve(&mut session, io::stdin().lock(), io::stdout().lock()),
+        };
+    }
+
+    if args.stream {
+        return stream(
+            args,
+            output,
+            &names,
+            &commit_messages,
+            &pool,
+            &constraints,
+            &mut rng,
+        );
+    }
+
+    #[cfg(feature = "tui")]
+    if let Some(Command::Tui) = &args.command {
+        let templates = commit_messages.to_lines()?;
+        // Asking for another batch passes over every template of the one before
+        let mut shown: Vec<usize> = Vec::new();
+        let regenerate = |count| {
+            learn_skips(args, &commit_messages, &shown);
+            let batch = generate_pinned(
+                &names,
+                &commit_messages,
+                pool.clone(),
+                &pinned,
+                &constraints,
+                count,
+                args.unique,
+                &mut rng,
+            )?;
+            shown = batch.iter().map(|g| g.template_index).collect();
+            Ok(batch)
+        };
+        let action = if args.accessible {
+            accessible::browse(regenerate, io::stdin().lock(), io::stderr())?
+        } else {
+            let (keys, _) = Keymap::load(args.keys.as_ref())?;
+            tui::run(&templates, &names, keys, regenerate)?
+        };
+        return match action {
+            tui::Action::Print(message) => {
+                println!("{}", message);
+                remember(args, &[&message], None);
+                Ok(())
+            }
+            tui::Action::Commit(message) => {
+                remember(args, &[&message], None);
+                commit(args, &with_provenance(args, &message, None, &[]))
+            }
+            tui::Action::Edit(message) => {
+                let message = editor::edit(&message)?;
+                println!("{}", message);
+                remember(args, &[&message], None);
+                Ok(())
+            }
+            tui::Action::Quit => Ok(()),
+        };
+    }
+
+    // Refused before anything is shown, so no message is printed for a commit that won't
+    // happen
+    if args.commit && !args.force {
+        check_branch(&args.protected_branches)?;
+    }
+
+    let slots = args.slots.filter(|_| animate(args));
+    let reel: Vec<String> = match slots {
+        Some(_) => (0..REEL_LENGTH)
+            .map(|_| generate_commit_message(&names, &commit_messages, &pool, false, &mut rng))
+            .map(|generated| generated.map(|g| g.message))
+            .collect::<Result<_>>()?,
+        None => Vec::new(),
+    };
+
+    let pool_size = pool.len();
+    let mut batch = match &args.acrostic {
+        Some(word) => generate_acrostic(
+            &names,
+            &commit_messages,
+            &pool,
+            &constraints,
+            word,
+            &mut rng,
+        )?,
+        None => generate_pinned(
+            &names,
+            &commit_messages,
+            pool,
+            &pinned,
+            &constraints,
+            args.pick.or(args.choose).unwrap_or(args.count),
+            args.unique,
+            &mut rng,
+        )?,
+    };
+
+    #[cfg(feature = "tui")]
+    let edit = if args.pick.is_some() {
+        let candidates: Vec<String> = batch.iter().map(|g| g.message.clone()).collect();
+        let pick = if args.accessible {
+            accessible::pick(&candidates, io::stdin().lock(), io::stderr())?
+        } else {
+            let (_, keys) = Keymap::load(args.keys.as_ref())?;
+            picker::run(&candidates, &keys)?
+        };
+        if let picker::Pick::Print(picked)
+        | picker::Pick::Edit(picked)
+        | picker::Pick::Commit(picked) = &pick
+        {
+            learn_skips(args, &commit_messages, &passed_over(&batch, picked));
+        }
+        match pick {
+            picker::Pick::Print(indices) => {
+                batch = vec![squash_picked(&batch, &indices)];
+                args.edit
+            }
+            picker::Pick::Edit(indices) => {
+                batch = vec![squash_picked(&batch, &indices)];
+                true
+            }
+            picker::Pick::Commit(indices) => {
+                let picked = squash_picked(&batch, &indices);
+                remember(args, &[&picked.message], seed);
+                tally(args, &commit_messages, std::slice::from_ref(&picked));
+                let message = with_provenance(args, &picked.message, seed, &picked.templates());
+                return commit(args, &message);
+            }
+            picker::Pick::Cancel => return Ok(()),
+        }
+    } else {
+        args.edit
+    };
+    #[cfg(not(feature = "tui"))]
+    let edit = args.edit;
+
+    if args.choose.is_some() {
+        let candidates: Vec<String> = batch.iter().map(|g| g.message.clone()).collect();
+        let index = choose::run(&candidates, io::stdin().lock(), io::stderr())?;
+        learn_skips(args, &commit_messages, &passed_over(&batch, &[index]));
+        batch = vec![batch.swap_remove(index)];
+    }
+
+    for generated in &mut batch {
+        generated.message = rework(args, std::mem::take(&mut generated.message));
+    }
+
+    if edit {
+        for generated in &mut batch {
+            generated.message = editor::edit(&generated.message)?;
+        }
+    }
 
-    let mut rng = rand::rng();
-    let message = generate_commit_message(&names, &commit_messages, &mut rng)?;
+    if io::stderr().is_terminal() && !args.plain {
+        for banner in eggs.iter().filter_map(|egg| egg.banner.as_deref()) {
+            eprintln!("{}", banner);
+        }
+    }
+    for generated in &batch {
+        if let Some(ms) = slots {
+            animation::slots(&reel, Duration::from_millis(ms))?;
+        }
+        emit(args, output, &commit_messages, generated)?;
+        if let Some(explainer) = &explainer {
+            let template = generated.template(&commit_messages)?;
+            eprintln!("{}", explainer.render(generated, &template));
+        }
+    }
 
-    println!("{}", message);
+    let messages: Vec<&str> = batch.iter().map(|g| g.message.as_str()).collect();
+    remember(args, &messages, seed);
+    tally(args, &commit_messages, &batch);
+    if args.github_output {
+        github::write_output(&messages)?;
+    }
+
+    if args.commit {
+        let templates: Vec<usize> = batch.iter().flat_map(Generated::templates).collect();
+        commit(
+            args,
+            &with_provenance(args, &squash::message(&messages), seed, &templates),
+        )?;
+    }
+
+    if let Some(format) = args.stats {
+        eprintln!(
+            "{}",
+            stats::render(&batch, &commit_messages, pool_size, format)?
+        );
+    }
 
     Ok(())
 }
 
+/// `message` as `--llm` reworks it, if it was given.
+fn rework(args: &Args, message: String) -> String {
+    match args.llm {
+        Some(mode) => llm::apply(mode, &message, &args.llm()),
+        None => message,
+    }
+}
+
+/// Generates `count` accepted messages.
+///
+/// With `unique`, templates are drawn from the pool without replacement and repeated
+/// messages are discarded, so the pool must hold at least `count` distinct templates.
+fn generate_batch(
+    names: &[String],
+    commit_messages: &Corpus,
+    mut pool: Vec<usize>,
+    constraints: &Constraints,
+    count: usize,
+    unique: bool,
+    rng: &mut Generator,
+) -> Result<Vec<Generated>> {
+    if unique {
+        let mut distinct = HashSet::new();
+        let mut kept = Vec::new();
+        for i in pool {
+            if distinct.insert(commit_messages.get(i)?) {
+                kept.push(i);
+            }
+        }
+        pool = kept;
+        if count > pool.len() {
+            return Err(WtcError::EmptyPool(format!(
+                "Cannot generate {} unique messages from {} distinct templates",
+                count,
+                pool.len()
+            ))
+            .into());
+        }
+    }
+
+    if !unique && count >= PARALLEL_THRESHOLD {
+        return generate_parallel(names, commit_messages, &pool, constraints, count, rng);
+    }
+
+    let mut batch: Vec<Generated> = Vec::with_capacity(count);
+    while batch.len() < count {
+        let generated = generate_accepted(names, commit_messages, &pool, constraints, rng)?;
+        if unique {
+            pool.retain(|&i| i != generated.template_index);
+            if batch.iter().any(|g| g.message == generated.message) {
+                continue;
+            }
+        }
+        batch.push(generated);
+    }
+    Ok(batch)
+}
+
+/// Generates `count` accepted messages like `generate_batch`, starting with one from each
+/// of the `pinned` templates, or as many as fit, and shuffling them in among the rest.
+#[allow(clippy::too_many_arguments)]
+fn generate_pinned(
+    names: &[String],
+    commit_messages: &Corpus,
+    mut pool: Vec<usize>,
+    pinned: &[usize],
+    constraints: &Constraints,
+    count: usize,
+    unique: bool,
+    rng: &mut Generator,
+) -> Result<Vec<Generated>> {
+    if pinned.is_empty() {
+        return generate_batch(
+            names,
+            commit_messages,
+            pool,
+            constraints,
+            count,
+            unique,
+            rng,
+        );
+    }
+    let mut batch = Vec::with_capacity(count);
+    let mut texts = HashSet::new();
+    for index in boost::for_batch(pinned, count, rng) {
+        batch.push(generate_accepted(
+            names,
+            commit_messages,
+            &[index],
+            constraints,
+            rng,
+        )?);
+        texts.insert(commit_messages.get(index)?);
+    }
+    if unique {
+        let mut kept = Vec::with_capacity(pool.len());
+        for i in pool {
+            if !texts.contains(&commit_messages.get(i)?) {
+                kept.push(i);
+            }
+        }
+        pool = kept;
+    }
+    let rest = count - batch.len();
+    if rest > 0 {
+        batch.extend(generate_batch(
+            names,
+            commit_messages,
+            pool,
+            constraints,
+            rest,
+            unique,
+            rng,
+        )?);
+    }
+    batch.shuffle(rng);
+    Ok(batch)
+}
+
+/// Generates an accepted message for each letter or digit of `word`, starting with it, by
+/// only drawing templates that could start with the letter.
+fn generate_acrostic(
+    names: &[String],
+    commit_messages: &Corpus,
+    pool: &[usize],
+    constraints: &Constraints,
+    word: &str,
+    rng: &mut Generator,
+) -> Result<Vec<Generated>> {
+    let letters = acrostic::letters(word);
+    if letters.is_empty() {
+        anyhow::bail!("--acrostic needs a word with letters or digits");
+    }
+    let mut batch = Vec::with_capacity(letters.len());
+    for letter in letters {
+        let mut candidates = Vec::new();
+        for &i in pool {
+            if acrostic::could_start_with(&commit_messages.get(i)?, letter) {
+                candidates.push(i);
+            }
+        }
+        if candidates.is_empty() {
+            return Err(WtcError::EmptyPool(format!(
+                "No template can start with {:?} for the acrostic",
+                letter
+            ))
+            .into());
+        }
+        let constraints = Constraints {
+            initial: Some(letter),
+            ..*constraints
+        };
+        batch.push(generate_accepted(
+            names,
+            commit_messages,
+            &candidates,
+            &constraints,
+            rng,
+        )?);
+    }
+    Ok(batch)
+}
+
+/// Generates `count` accepted messages across threads, in chunks with their own generators
+/// forked from `rng`, and returns them in chunk order. Without the `parallel` feature the
+/// chunks are generated one after another, giving the same messages.
+fn generate_parallel(
+    names: &[String],
+    commit_messages: &Corpus,
+    pool: &[usize],
+    constraints: &Constraints,
+    count: usize,
+    rng: &mut Generator,
+) -> Result<Vec<Generated>> {
+    let chunks: Vec<(Generator, usize)> = (0..count)
+        .step_by(PARALLEL_CHUNK)
+        .map(|start| (rng.fork(), PARALLEL_CHUNK.min(count - start)))
+        .collect();
+    debug!("Generating {} messages in {} chunks", count, chunks.len());
+    #[cfg(feature = "parallel")]
+    let chunks = chunks.into_par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let chunks = chunks.into_iter();
+    let chunks: Vec<Vec<Generated>> = chunks
+        .map(|(mut rng, size)| {
+            (0..size)
+                .map(|_| generate_accepted(names, commit_messages, pool, constraints, &mut rng))
+                .collect()
+        })
+        .collect::<Result<_>>()?;
+    Ok(chunks.into_iter().flatten().collect())
+}
+
 #[cfg(test)]
+#[allow(clippy::manual_range_contains)]
 mod test {
     use super::*;
+    use crate::lint::assert_lints_clean;
+    use clap::Parser;
+    use error::ErrorKind;
     use rand::rngs::StdRng;
     use rand::SeedableRng;
+    use rng::Backend;
+    use spice::Spice;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn t_generate_from_template_id() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let names = vec!["Ann".to_string()];
+        let templates = Corpus::from(vec!["first".to_string(), "XNAMEX's pick".to_string()]);
+        let pool = template_pool(
+            &templates,
+            Some(1),
+            None,
+            rating::Cap::new(Spice::Strong, false),
+        )
+        .unwrap();
+        let generated =
+            generate_commit_message(&names, &templates, &pool, false, &mut rng).unwrap();
+        assert_eq!(generated.message, "Ann's pick");
+        assert_eq!(generated.template_index, 1);
+        let err = template_pool(
+            &templates,
+            Some(2),
+            None,
+            rating::Cap::new(Spice::Strong, false),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<WtcError>(),
+            Some(WtcError::TemplateOutOfRange {
+                id: 2,
+                templates: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn t_rules_exhaust_attempts() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let names = vec!["Ann".to_string()];
+        let templates = Corpus::from(vec!["too long for the rules".to_string()]);
+        let rules = Rules::parse("max-length = 5").unwrap();
+        let constraints = Constraints {
+            rules: Some(&rules),
+            ..Default::default()
+        };
+        let err = generate_accepted(&names, &templates, &[0], &constraints, &mut rng).unwrap_err();
+        assert_eq!(error::kind_of(&err), ErrorKind::EmptyPool);
+        assert!(err.to_string().contains("max-length: 22 > 5"));
+    }
+
+    #[test]
+    fn t_template_max_len() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let names = vec!["Ann".to_string()];
+        let templates = Corpus::from(vec!["{{max-len: 13}}fixed XNUM1,100X bugs".to_string()]);
+        let constraints = Constraints::default();
+        for _ in 0..20 {
+            let generated =
+                generate_accepted(&names, &templates, &[0], &constraints, &mut rng).unwrap();
+            assert!(generated.message.chars().count() <= 13);
+        }
+        let templates = Corpus::from(vec!["{{max-len: 5}}much too long".to_string()]);
+        let err = generate_accepted(&names, &templates, &[0], &constraints, &mut rng).unwrap_err();
+        assert!(err.to_string().contains("max-len of 5"));
+    }
+
+    #[test]
+    fn t_unique_batch() {
+        let mut rng = Generator::new(Backend::Std, 42);
+        let names = vec!["Ann".to_string()];
+        let templates = Corpus::from(["a", "b", "a", "c"].map(String::from).to_vec());
+        let constraints = Constraints::default();
+        let batch = generate_batch(
+            &names,
+            &templates,
+            vec![0, 1, 2, 3],
+            &constraints,
+            3,
+            true,
+            &mut rng,
+        )
+        .unwrap();
+        let mut messages: Vec<&str> = batch.iter().map(|g| g.message.as_str()).collect();
+        messages.sort();
+        assert_eq!(messages, vec!["a", "b", "c"]);
+
+        let err = generate_batch(
+            &names,
+            &templates,
+            vec![0, 1, 2, 3],
+            &constraints,
+            4,
+            true,
+            &mut rng,
+        )
+        .unwrap_err();
+        assert_eq!(error::kind_of(&err), ErrorKind::EmptyPool);
+    }
+
+    #[test]
+    fn t_excuses_share_the_engine() {
+        let excuses = [default_excuses(), default_reviews()].concat();
+        assert_lints_clean("excuses", &excuses);
+        let map = BTreeMap::from([("blame".to_string(), "thank".to_string())]);
+        let replacements = Replacements::new(&map);
+        let constraints = Constraints {
+            replacements: replacements.as_ref(),
+            transforms: &[Transform::Rot13],
+            ..Default::default()
+        };
+        let excuses = Corpus::from(vec!["blame XNAMEX".to_string()]);
+        let mut rng = Generator::new(Backend::Std, 42);
+        let names = vec!["Ann".to_string()];
+        let excuse = generate_accepted(&names, &excuses, &[0], &constraints, &mut rng).unwrap();
+        assert_eq!(excuse.message, "gunax Naa");
+    }
+
+    #[test]
+    fn t_review_comments() {
+        let args = Args::try_parse_from(["wtc", "review", "-n", "3"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(Command::Review {
+                count: 3,
+                file: None
+            })
+        ));
+
+        let reviews = default_reviews();
+        let excuses = default_excuses();
+        assert!(reviews.iter().all(|review| !excuses.contains(review)));
+        let templates = Corpus::from(reviews);
+        let pool = template_pool(
+            &templates,
+            None,
+            None,
+            rating::Cap::new(Spice::Strong, false),
+        )
+        .unwrap();
+        assert_eq!(pool.len(), templates.len());
+        let names = default_names();
+        let constraints = Constraints::default();
+        let mut rng = Generator::new(Backend::Std, 42);
+        for _ in 0..200 {
+            let review =
+                generate_accepted(&names, &templates, &pool, &constraints, &mut rng).unwrap();
+            assert_eq!(lint::scan(&review.message), [], "{}", review.message);
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn t_parallel_batch_is_deterministic() {
+        let names = vec!["Ann".to_string(), "Bob".to_string()];
+        let templates = Corpus::from(vec!["XNAMEX fixed XNUMX bugs".to_string()]);
+        let constraints = Constraints::default();
+        let count = PARALLEL_THRESHOLD + PARALLEL_CHUNK / 2;
+        let batch_on = |backend, threads| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                let mut rng = Generator::new(backend, 42);
+                generate_batch(
+                    &names,
+                    &templates,
+                    vec![0],
+                    &constraints,
+                    count,
+                    false,
+                    &mut rng,
+                )
+                .unwrap()
+            })
+        };
+        for backend in [Backend::Std, Backend::Fast] {
+            let batch = batch_on(backend, 1);
+            assert_eq!(batch.len(), count);
+            assert_eq!(batch_on(backend, 4), batch);
+        }
+    }
+
+    #[test]
+    fn t_blocklist_shrinks_pool() {
+        let templates = Corpus::from(vec!["fix prod".to_string(), "fix tests".to_string()]);
+        let blocklist = Blocklist::parse("prod");
+        assert_eq!(
+            template_pool(
+                &templates,
+                None,
+                Some(&blocklist),
+                rating::Cap::new(Spice::Strong, false)
+            )
+            .unwrap(),
+            vec![1]
+        );
+        let err = template_pool(
+            &templates,
+            Some(0),
+            Some(&blocklist),
+            rating::Cap::new(Spice::Strong, false),
+        )
+        .unwrap_err();
+        assert_eq!(error::kind_of(&err), ErrorKind::EmptyPool);
+    }
+
+    #[test]
+    fn t_provenance_trailer() {
+        let args = Args::try_parse_from(["wtc"]).unwrap();
+        assert_eq!(
+            with_provenance(&args, "Fixed it", Some(7), &[3]),
+            "Fixed it"
+        );
+        let args = Args::try_parse_from(["wtc", "--provenance"]).unwrap();
+        let version = env!("CARGO_PKG_VERSION");
+        assert_eq!(
+            with_provenance(&args, "Fixed it\n", Some(7), &[3, 12]),
+            format!(
+                "Fixed it\n\nGenerated-by: whatthecommit-cli v{} (seed=7, template=3+12)",
+                version
+            )
+        );
+        assert_eq!(
+            with_provenance(&args, "Fixed it", None, &[]),
+            format!("Fixed it\n\nGenerated-by: whatthecommit-cli v{}", version)
+        );
+    }
+
+    #[test]
+    fn t_mutant_keeps_both_templates() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let names = vec!["Ann".to_string()];
+        let templates = Corpus::from(vec![
+            "Fixed the build, XNAMEX broke it again".to_string(),
+            "Reverted XNAMEX's change because it worked on my machine".to_string(),
+        ]);
+        let mutant = generate_mutant(&names, &templates, &[0, 1], false, &mut rng).unwrap();
+        let (second, spliced) = mutant.spliced.clone().unwrap();
+        assert_ne!(mutant.template_index, second);
+        assert_eq!(mutant.templates(), [mutant.template_index, second]);
+        assert_eq!(mutant.template(&templates).unwrap(), spliced);
+        assert_ne!(spliced, templates.get(mutant.template_index).unwrap());
+
+        let args = Args::try_parse_from(["wtc", "--provenance"]).unwrap();
+        let message = with_provenance(&args, &mutant.message, None, &mutant.templates());
+        assert!(message.ends_with(&format!("(template={}+{})", mutant.template_index, second)));
+    }
+
+    #[test]
+    fn t_default_spice() {
+        let templates = Corpus::from(default_commit_messages());
+        let run = |argv: &[&str]| {
+            let spice = Args::try_parse_from(argv).unwrap().spice();
+            let pool = template_pool(&templates, None, None, rating::Cap::new(spice, false));
+            (
+                pool.unwrap(),
+                words::Words::load(None, spice).unwrap().swears,
+            )
+        };
+        assert_eq!(run(&["wtc"]), run(&["wtc", "--spice", "medium"]));
+        assert_eq!(run(&["wtc"]), run(&["wtc", "--spice", "PG"]));
+        assert_ne!(run(&["wtc"]), run(&["wtc", "--spice", "mild"]));
+    }
+
+    #[test]
+    fn t_spice_caps_pool() {
+        let templates = Corpus::from(vec![
+            "Fixed it".to_string(),
+            "{{spice: medium}}Fixed the damn thing".to_string(),
+            "{{spice: savage}}Fixed the XSWEARX thing".to_string(),
+        ]);
+        let pool =
+            |spice| template_pool(&templates, None, None, rating::Cap::new(spice, false)).unwrap();
+        assert_eq!(pool(Spice::Censored), vec![0]);
+        assert_eq!(pool(Spice::Mild), vec![0]);
+        assert_eq!(pool(Spice::default()), vec![0, 1]);
+        assert_eq!(pool(Spice::Strong), vec![0, 1, 2]);
+        let err = template_pool(
+            &templates,
+            Some(2),
+            None,
+            rating::Cap::new(Spice::Mild, false),
+        )
+        .unwrap_err();
+        assert_eq!(error::kind_of(&err), ErrorKind::EmptyPool);
+    }
+
+    #[test]
+    fn t_malformed_numbers_are_left_as_written() {
+        let mut rng = StdRng::seed_from_u64(42);
+        assert_eq!(
+            substitute_placeholders("Fixed XNUM99999999999999999999X bugs", "Ann", &mut rng),
+            "Fixed XNUM99999999999999999999X bugs"
+        );
+        let result = substitute_placeholders("XNUM4000000000,1X", "Ann", &mut rng);
+        assert!(result.parse::<u64>().unwrap() >= 4_000_000_000);
+    }
+
+    #[test]
+    fn t_substitute_large_number_ranges() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let result = substitute_placeholders("XNUM1,1000000000000X", "Ann", &mut rng);
+            assert!((1..=1_000_000_000_000).contains(&result.parse::<u64>().unwrap()));
+        }
+        // Doubling the start would overflow, so the end is the largest number instead
+        let result = substitute_placeholders("XNUM10000000000000000000,1X", "Ann", &mut rng);
+        assert!(result.parse::<u64>().unwrap() >= 10_000_000_000_000_000_000);
+        assert_eq!(
+            substitute_placeholders("XNUM18446744073709551615,X", "Ann", &mut rng),
+            u64::MAX.to_string()
+        );
+    }
+
+    #[test]
+    fn t_invalid_utf8_names_the_line() {
+        let path = std::env::temp_dir().join(format!("wtc-utf8-{}.txt", std::process::id()));
+        fs::write(&path, b"fine\n\nbroken \xff\n").unwrap();
+        let err = load_lines_or_default(&Some(path.clone()), None, "names").unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            err.downcast_ref::<WtcError>(),
+            Some(WtcError::InvalidTemplate { line: 3, .. })
+        ));
+        assert_eq!(error::kind_of(&err), ErrorKind::Corpus);
+    }
+
+    #[test]
+    fn t_corpus_problems_point_at_the_column() {
+        let path = std::env::temp_dir().join(format!("wtc-diag-{}.txt", std::process::id()));
+        fs::write(&path, "fine\r\n\r\nXNAMEX rang the \x07 bell\r\n").unwrap();
+        let err = load_lines_or_default(&Some(path.clone()), None, "commit messages");
+        let indexed = LineIndex::build(&path).err().unwrap();
+        fs::remove_file(&path).unwrap();
+        for err in [err.unwrap_err(), indexed] {
+            let Some(WtcError::Diagnostic(diagnostic)) = err.downcast_ref::<WtcError>() else {
+                panic!("{:#}", err);
+            };
+            assert_eq!((diagnostic.line, diagnostic.column), (3, 17));
+            assert_eq!(error::kind_of(&err), ErrorKind::Corpus);
+        }
+    }
+
+    #[test]
+    fn t_streamed_corpus_is_sampled() {
+        let stream: String = (0..5_000).map(|i| format!("template {}\n\n", i)).collect();
+        let mut rng = StdRng::seed_from_u64(42);
+        let sampled =
+            load_stream(stream.as_bytes(), "commit messages", Some(10), &mut rng).unwrap();
+        assert_eq!(sampled.len(), 10);
+        assert!(sampled.iter().all(|t| t.starts_with("template ")));
+        // The sample reaches past the start of the stream
+        assert!(sampled
+            .iter()
+            .any(|t| t[9..].parse::<usize>().unwrap() >= 10));
+
+        let all = load_stream(stream.as_bytes(), "commit messages", None, &mut rng).unwrap();
+        assert_eq!(all.len(), 5_000);
+        let err = load_stream(&b"\n \n"[..], "names", Some(10), &mut rng).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<WtcError>(),
+            Some(WtcError::EmptyCorpus { what: "names" })
+        ));
+    }
+
+    #[test]
+    fn t_stream_ends_when_the_reader_goes() {
+        let gone = anyhow::Error::from(io::Error::from(io::ErrorKind::BrokenPipe));
+        assert!(reader_gone(&gone));
+        assert!(reader_gone(&gone.context("Failed to write")));
+        let full = anyhow::Error::from(io::Error::from(io::ErrorKind::StorageFull));
+        assert!(!reader_gone(&full));
+        assert!(!reader_gone(&anyhow::anyhow!("broken pipe")));
+    }
+
+    #[test]
+    fn t_no_default_corpus() {
+        let names = load_lines_or_default(&None, Some(default_names), "names").unwrap();
+        assert_eq!(names, default_names());
+        let err = load_lines_or_default(&None, None, "names").unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<WtcError>(),
+            Some(WtcError::NoCorpus { what: "names" })
+        ));
+        assert_eq!(error::kind_of(&err), ErrorKind::Corpus);
+
+        // A given file is still read
+        let path = std::env::temp_dir().join(format!("wtc-own-{}.txt", std::process::id()));
+        fs::write(&path, "Ann\n").unwrap();
+        let names = load_lines_or_default(&Some(path.clone()), None, "names").unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(names, ["Ann"]);
+    }
+
+    #[test]
+    fn t_non_ascii_names_from_file() {
+        let path = std::env::temp_dir().join(format!("wtc-names-{}.txt", std::process::id()));
+        fs::write(&path, "Łukasz\nÇağla\n").unwrap();
+        let names = load_lines_or_default(&Some(path.clone()), None, "names").unwrap();
+        fs::remove_file(&path).unwrap();
+        let mut rng = StdRng::seed_from_u64(42);
+        let template = "XUPPERNAMEX, XLOWERNAMEX and XTITLENAMEX";
+        assert_eq!(
+            substitute_placeholders(template, &names[0], &mut rng),
+            "ŁUKASZ, łukasz and Łukasz"
+        );
+        assert_eq!(
+            substitute_placeholders(template, &names[1], &mut rng),
+            "ÇAĞLA, çağla and Çağla"
+        );
+    }
 
     #[test]
     fn t_substitute_single_placeholder() {
@@ -0,0 +1,198 @@
+#[cfg(feature = "man")]
+use crate::cli::Args;
+#[cfg(not(feature = "man"))]
+use crate::error::WtcError;
+use crate::lint;
+#[cfg(feature = "man")]
+use anyhow::Context;
+use anyhow::Result;
+#[cfg(feature = "man")]
+use clap::CommandFactory;
+#[cfg(feature = "man")]
+use clap_mangen::Man;
+#[cfg(feature = "man")]
+use roff::{bold, roman, Roff};
+#[cfg(feature = "man")]
+use std::fs;
+#[cfg(feature = "man")]
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Adds a titled section made of paragraphs to a roff document.
+#[cfg(feature = "man")]
+fn section(roff: &mut Roff, title: &str, paragraphs: &[&str]) {
+    roff.control("SH", [title]);
+    for paragraph in paragraphs {
+        roff.control("PP", []).text([roman(*paragraph)]);
+    }
+}
+
+/// Adds a titled section made of tagged entries (term and description) to a roff document.
+#[cfg(feature = "man")]
+fn tagged_section(roff: &mut Roff, title: &str, intro: &str, entries: &[(&str, &str)]) {
+    roff.control("SH", [title]);
+    if !intro.is_empty() {
+        roff.text([roman(intro)]);
+    }
+    for (term, description) in entries {
+        roff.control("TP", [])
+            .text([bold(*term)])
+            .text([roman(*description)]);
+    }
+}
+
+/// An example of each kind of number placeholder, with what it is replaced with.
+const NUMBER_PLACEHOLDERS: [(&str, &str); 13] = [
+    ("XNUMX", "A random number from 1 to 999."),
+    ("XNUM10X", "A random number from 1 to 10."),
+    ("XNUM1,5X", "A random number from 1 to 5. Commas always separate the start and end of a range."),
+    ("XNUM,5X", "A random number from 1 to 5; the start defaults to 1."),
+    ("XNUM5,X", "A random number from 5 to 999; the end defaults to 999."),
+    ("XNUM-50,50X", "A random number from -50 to 50. Bounds may be negative."),
+    ("XNUM0,100:10X", "A random multiple of 10 from 0 to 100."),
+    ("XNUM1,100000:logX", "A log-uniform number from 1 to 100000: 1 to 9, 10 to 99 and so on are equally likely. Needs a range above zero."),
+    ("XNUM0,100:normalX", "A number from 0 to 100 on a bell curve around the middle. Steps and distributions combine, as in XNUM0,1000:10:normalX."),
+    ("XNUM3,20:romanX", "A random number from 3 to 20 written in Roman numerals, such as XIV. The range must lie within 1 to 3999."),
+    ("XNUM10:ordinalX", "A random ordinal from 1st to 10th, such as 3rd or 21st."),
+    ("XNUM300:percentX", "A random percentage from 1% to 300%."),
+    ("XNUM100,100000:currencyX", "A random amount from $100 to $100,000 with cents and thousands separators, such as $1,234.56."),
+];
+
+/// Every placeholder, from `lint::PLACEHOLDERS`, then an example of each kind of number
+/// placeholder, with what it is replaced with.
+pub const PLACEHOLDERS: [(&str, &str); lint::PLACEHOLDERS.len() + NUMBER_PLACEHOLDERS.len()] = {
+    let mut all = [("", ""); lint::PLACEHOLDERS.len() + NUMBER_PLACEHOLDERS.len()];
+    let mut i = 0;
+    while i < lint::PLACEHOLDERS.len() {
+        all[i] = (lint::PLACEHOLDERS[i].0, lint::PLACEHOLDERS[i].2);
+        i += 1;
+    }
+    while i < all.len() {
+        all[i] = NUMBER_PLACEHOLDERS[i - lint::PLACEHOLDERS.len()];
+        i += 1;
+    }
+    all
+};
+
+/// Sections documenting template syntax and file formats, which clap knows nothing about.
+#[cfg(feature = "man")]
+fn extra_sections(roff: &mut Roff) {
+    tagged_section(
+        roff,
+        "PLACEHOLDERS",
+        "Commit message templates may contain placeholders that are replaced when a message is generated.",
+        &PLACEHOLDERS,
+    );
+    roff.control("PP", []).text([roman(
+        "If the start of a range is greater than its end, the end is adjusted to twice the start, or for a start of zero or below the bounds are swapped.",
+    )]);
+    roff.control("PP", []).text([roman(
+        "Directives between double braces name numbers for reuse: {{set n = num:1..10}} draws a number and writes nothing, then {{n}} writes it and {{n*10}}, {{n+1}} or {{n-1}} work on it. Variables must be set before they are used.",
+    )]);
+    roff.control("PP", []).text([roman(
+        "{{N%: TEXT}} writes TEXT, which may hold placeholders, only N times in a hundred. The one space after the colon is left out.",
+    )]);
+    roff.control("PP", []).text([roman(
+        "{{repeat N: TEXT}} writes TEXT N times, up to 100, drawing its placeholders anew each time. Literal text after the last placeholder goes only between the items, so {{repeat 3: XPATHX, }} writes a list of three paths.",
+    )]);
+    roff.control("PP", []).text([roman(
+        "Each placeholder draws its own value. With --consistent, a placeholder written exactly like an earlier one in the same template repeats that value.",
+    )]);
+
+    section(
+        roff,
+        "FILES",
+        &[
+            "Names and commit message template files contain one entry per line. Empty lines are ignored.",
+            "The config file is a TOML file named config.toml in the config directory (see the paths subcommand). Its keys match the long command-line options, and it may define [profiles.NAME] tables selectable with --profile, an [alias] table and a [colors] table overriding theme colors.",
+            "Emitted messages are recorded in history.jsonl in the data directory, one JSON object per line, unless --no-history is given.",
+        ],
+    );
+
+    tagged_section(
+        roff,
+        "EXIT STATUS",
+        "",
+        &[
+            ("0", "Success."),
+            ("1", "Any other error."),
+            ("2", "Invalid command-line usage."),
+            ("3", "A corpus file could not be loaded."),
+            ("4", "No message could be produced from the remaining pool."),
+            ("5", "A git command failed."),
+            ("6", "A network request failed."),
+        ],
+    );
+}
+
+/// Renders the complete man page.
+#[cfg(feature = "man")]
+pub fn render(w: &mut dyn Write) -> Result<()> {
+    let man = Man::new(Args::command());
+    man.render_title(w)?;
+    man.render_name_section(w)?;
+    man.render_synopsis_section(w)?;
+    man.render_description_section(w)?;
+    man.render_options_section(w)?;
+    man.render_subcommands_section(w)?;
+
+    let mut roff = Roff::new();
+    extra_sections(&mut roff);
+    roff.to_writer(w)?;
+
+    man.render_version_section(w)?;
+    man.render_authors_section(w)?;
+    Ok(())
+}
+
+/// Prints the man page to stdout, or installs it into `dir` when given.
+#[cfg(feature = "man")]
+pub fn run(dir: Option<&Path>) -> Result<()> {
+    match dir {
+        None => render(&mut io::stdout().lock()),
+        Some(dir) => {
+            let path = dir.join(Man::new(Args::command()).get_filename());
+            let mut page = Vec::new();
+            render(&mut page)?;
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create directory: {:?}", dir))?;
+            fs::write(&path, page)
+                .with_context(|| format!("Failed to write man page: {:?}", path))?;
+            println!("{}", path.display());
+            Ok(())
+        }
+    }
+}
+
+#[cfg(not(feature = "man"))]
+pub fn run(_dir: Option<&Path>) -> Result<()> {
+    Err(WtcError::Disabled {
+        what: "the man page",
+        feature: "man",
+    }
+    .into())
+}
+
+#[cfg(all(test, feature = "man"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_man_page_has_all_sections() {
+        let mut page = Vec::new();
+        render(&mut page).unwrap();
+        let page = String::from_utf8(page).unwrap();
+        for heading in [
+            "NAME",
+            "SYNOPSIS",
+            "OPTIONS",
+            "PLACEHOLDERS",
+            "FILES",
+            "EXIT STATUS",
+        ] {
+            assert!(page.contains(heading), "missing {}", heading);
+        }
+        assert!(page.contains("XUPPERNAMEX"));
+        assert!(page.contains("\\-\\-no\\-default\\-corpus"));
+    }
+}
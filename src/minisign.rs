@@ -0,0 +1,165 @@
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use blake2::{Blake2b512, Digest};
+use ring::signature::{UnparsedPublicKey, ED25519};
+
+/// How minisign shows a key id: the little-endian number it makes, in hex.
+fn key_id(id: [u8; 8]) -> String {
+    format!("{:016X}", u64::from_le_bytes(id))
+}
+
+/// A minisign public key, as `minisign -G` writes it on the second line of `minisign.pub`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublicKey {
+    id: [u8; 8],
+    key: [u8; 32],
+}
+
+impl std::str::FromStr for PublicKey {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let bytes = (BASE64.decode(text.trim()).ok())
+            .filter(|bytes| bytes.len() == 42 && bytes.starts_with(b"Ed"))
+            .with_context(|| format!("{:?} isn't a minisign public key", text))?;
+        Ok(PublicKey {
+            id: bytes[2..10].try_into()?,
+            key: bytes[10..].try_into()?,
+        })
+    }
+}
+
+/// A parsed `.minisig` file.
+#[derive(Debug)]
+struct Signature {
+    /// Whether the file's BLAKE2b-512 hash was signed rather than the file itself.
+    prehashed: bool,
+    key_id: [u8; 8],
+    signature: [u8; 64],
+    trusted_comment: String,
+    /// The signature of `signature` followed by the trusted comment.
+    global: [u8; 64],
+}
+
+fn parse(text: &str) -> Result<Signature> {
+    let mut lines = text.lines().map(str::trim_end);
+    let invalid = || anyhow::anyhow!("The signature isn't a minisign signature");
+    lines
+        .next()
+        .filter(|l| l.starts_with("untrusted comment:"))
+        .ok_or_else(invalid)?;
+    let bytes = lines
+        .next()
+        .and_then(|l| BASE64.decode(l).ok())
+        .filter(|bytes| bytes.len() == 74)
+        .ok_or_else(invalid)?;
+    let prehashed = match &bytes[..2] {
+        b"ED" => true,
+        b"Ed" => false,
+        _ => anyhow::bail!("The signature uses an algorithm minisign doesn't have"),
+    };
+    let trusted_comment = lines
+        .next()
+        .and_then(|l| l.strip_prefix("trusted comment: "))
+        .ok_or_else(invalid)?
+        .to_string();
+    let global = lines
+        .next()
+        .and_then(|l| BASE64.decode(l).ok())
+        .filter(|bytes| bytes.len() == 64)
+        .ok_or_else(invalid)?;
+    Ok(Signature {
+        prehashed,
+        key_id: bytes[2..10].try_into()?,
+        signature: bytes[10..].try_into()?,
+        trusted_comment,
+        global: global.try_into().map_err(|_| invalid())?,
+    })
+}
+
+/// Checks that the minisign signature `signature` of `bytes` was made by one of the
+/// `trusted` keys, returning its trusted comment.
+pub fn verify(bytes: &[u8], signature: &str, trusted: &[PublicKey]) -> Result<String> {
+    let signature = parse(signature)?;
+    let key = trusted
+        .iter()
+        .find(|key| key.id == signature.key_id)
+        .with_context(|| {
+            format!(
+                "The signature was made with key {}, which isn't one of the trusted keys",
+                key_id(signature.key_id)
+            )
+        })?;
+    let key = UnparsedPublicKey::new(&ED25519, key.key);
+    let signed = if signature.prehashed {
+        key.verify(&Blake2b512::digest(bytes), &signature.signature)
+    } else {
+        key.verify(bytes, &signature.signature)
+    };
+    signed.map_err(|_| anyhow::anyhow!("The signature doesn't match the file"))?;
+    let mut global = signature.signature.to_vec();
+    global.extend_from_slice(signature.trusted_comment.as_bytes());
+    key.verify(&global, &signature.global)
+        .map_err(|_| anyhow::anyhow!("The signature's trusted comment was tampered with"))?;
+    Ok(signature.trusted_comment)
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    /// Signs `bytes` the way `minisign -S` does, with `-H` for `prehashed` and `-t` for
+    /// the trusted `comment`.
+    pub fn sign(
+        pair: &Ed25519KeyPair,
+        id: [u8; 8],
+        bytes: &[u8],
+        prehashed: bool,
+        comment: &str,
+    ) -> String {
+        let (algorithm, signed) = if prehashed {
+            (b"ED", pair.sign(&Blake2b512::digest(bytes)))
+        } else {
+            (b"Ed", pair.sign(bytes))
+        };
+        let mut global = signed.as_ref().to_vec();
+        global.extend_from_slice(comment.as_bytes());
+        let line = [algorithm.as_slice(), &id, signed.as_ref()].concat();
+        format!(
+            "untrusted comment: signature from minisign secret key\n{}\ntrusted comment: {}\n{}\n",
+            BASE64.encode(&line),
+            comment,
+            BASE64.encode(pair.sign(&global).as_ref())
+        )
+    }
+
+    #[test]
+    fn t_minisign_verify() {
+        let pair = Ed25519KeyPair::from_seed_unchecked(&[7; 32]).unwrap();
+        let id = *b"\x01\x02\x03\x04\x05\x06\x07\x08";
+        let public = BASE64.encode([b"Ed".as_slice(), &id, pair.public_key().as_ref()].concat());
+        let key: PublicKey = public.parse().unwrap();
+        let other: PublicKey = BASE64
+            .encode([b"Ed".as_slice(), &[9; 8], &[1; 32]].concat())
+            .parse()
+            .unwrap();
+        assert!("RWQ".parse::<PublicKey>().is_err());
+
+        let pack = b"XNAMEX fixed it\n";
+        for prehashed in [false, true] {
+            let comment = "timestamp:1760000000\tfile:standup.txt";
+            let signature = sign(&pair, id, pack, prehashed, comment);
+            assert_eq!(
+                verify(pack, &signature, &[other.clone(), key.clone()]).unwrap(),
+                comment
+            );
+            assert!(verify(b"XNAMEX broke it\n", &signature, std::slice::from_ref(&key)).is_err());
+            let untrusted = verify(pack, &signature, std::slice::from_ref(&other)).unwrap_err();
+            assert!(untrusted.to_string().contains("0807060504030201"));
+            let forged = signature.replace("standup", "standu");
+            assert!(verify(pack, &forged, std::slice::from_ref(&key)).is_err());
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::lint;
+use rand::seq::IndexedRandom;
+use rand::Rng;
+
+/// What clauses are joined with. A template is cut around one of them: before a
+/// conjunction, which the clause after it keeps, and after punctuation.
+const SEPARATORS: [&str; 12] = [
+    ", ",
+    "; ",
+    ": ",
+    ". ",
+    "! ",
+    "? ",
+    " - ",
+    " and ",
+    " but ",
+    " because ",
+    " so ",
+    " until ",
+];
+
+/// A place a template can be cut between two clauses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cut {
+    /// Where the clause before the cut ends.
+    head_end: usize,
+    /// Where the clause after it starts.
+    tail_start: usize,
+}
+
+impl Cut {
+    /// Whether the clauses are joined by punctuation rather than a conjunction.
+    fn punctuated(self, template: &str) -> bool {
+        !template[self.head_end..].starts_with(' ')
+    }
+}
+
+/// Every place `template` can be cut into clauses, none of them inside a placeholder or
+/// directive, and none leaving an empty clause.
+fn cuts(template: &str) -> Vec<Cut> {
+    let inside: Vec<_> = lint::scan(template)
+        .into_iter()
+        .map(|placeholder| placeholder.range)
+        .collect();
+    // Directives with a body are scanned as their opening, body and closing, so a cut in
+    // the body is caught by the braces still open before it
+    let splits = |at: usize| {
+        let before = &template[..at];
+        inside
+            .iter()
+            .any(|range| range.start < at && at < range.end)
+            || before.matches("{{").count() > before.matches("}}").count()
+    };
+    let mut cuts: Vec<Cut> = SEPARATORS
+        .iter()
+        .flat_map(|separator| {
+            template.match_indices(separator).map(|(at, _)| {
+                // A conjunction goes with the clause after it
+                let glue = separator.len() - separator.trim_start().len();
+                Cut {
+                    head_end: at,
+                    tail_start: match separator.starts_with(' ') {
+                        true => at + glue,
+                        false => at + separator.len(),
+                    },
+                }
+            })
+        })
+        .filter(|cut| cut.head_end > 0 && cut.tail_start < template.len())
+        .filter(|cut| !splits(cut.head_end) && !splits(cut.tail_start))
+        .collect();
+    cuts.sort_by_key(|cut| cut.head_end);
+    cuts
+}
+
+/// A new template made of the clauses of `first` up to a random cut, then those of
+/// `second` after one, joined by the punctuation of either cut, or else by a space before
+/// the conjunction `second` cut at; or `None` if either can't be cut, or the splice is one
+/// of them again or isn't well-formed, say because it uses a variable only `first` sets
+/// after its cut.
+pub fn splice<R>(first: &str, second: &str, rng: &mut R) -> Option<String>
+where
+    R: Rng + ?Sized,
+{
+    let head = *cuts(first).choose(rng)?;
+    let tail = *cuts(second).choose(rng)?;
+    let glue = match head.punctuated(first) {
+        true => &first[head.head_end..head.tail_start],
+        false => &second[tail.head_end..tail.tail_start],
+    };
+    let spliced = format!(
+        "{}{}{}",
+        &first[..head.head_end],
+        glue,
+        &second[tail.tail_start..]
+    );
+    let novel = spliced != first && spliced != second;
+    (novel && lint::problems(&spliced).is_empty()).then_some(spliced)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_splice_clauses() {
+        let fixed = "Fixed the build, XNAMEX broke it again";
+        assert_eq!(
+            cuts(fixed),
+            vec![Cut {
+                head_end: 15,
+                tail_start: 17
+            }]
+        );
+        let blamed = "Reverted XNAMEX's change because it worked on my machine";
+        assert_eq!(cuts(blamed)[0].tail_start, 25);
+        assert!(cuts("{{50%: one, two}} ok").is_empty());
+        assert!(cuts("No clauses here").is_empty());
+
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(
+            splice(fixed, blamed, &mut rng).as_deref(),
+            Some("Fixed the build, because it worked on my machine")
+        );
+        assert_eq!(
+            splice(blamed, fixed, &mut rng).as_deref(),
+            Some("Reverted XNAMEX's change, XNAMEX broke it again")
+        );
+        assert_eq!(splice(fixed, "No clauses here", &mut rng), None);
+        // The variable is only set in the clause left behind
+        let set = "Tried {{set n = num:1..10}}XNAMEX, failed {{n}} times";
+        assert_eq!(splice("Gave up, again", set, &mut rng), None);
+    }
+}
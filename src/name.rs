@@ -0,0 +1,215 @@
+use crate::transform;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use rand::prelude::IndexedRandom;
+use rand::Rng;
+
+/// Case transforms matching the name placeholders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum NameCase {
+    /// The name as written in the names file, like XNAMEX
+    #[default]
+    AsIs,
+    /// UPPERCASE, like XUPPERNAMEX
+    Upper,
+    /// lowercase, like XLOWERNAMEX
+    Lower,
+    /// Title Case, like XTITLENAMEX
+    Title,
+    /// aLtErNaTiNg case, like XMOCKNAMEX
+    Mock,
+}
+
+impl NameCase {
+    pub fn apply(self, name: &str) -> String {
+        match self {
+            NameCase::AsIs => name.to_string(),
+            NameCase::Upper => name.to_uppercase(),
+            NameCase::Lower => name.to_lowercase(),
+            NameCase::Title => title_case(name),
+            NameCase::Mock => transform::mock(name),
+        }
+    }
+
+    /// Appends the name in this case to `out`, without allocating for the common cases.
+    pub fn push_to(self, name: &str, out: &mut String) {
+        match self {
+            NameCase::AsIs => out.push_str(name),
+            NameCase::Upper => out.extend(name.chars().flat_map(char::to_uppercase)),
+            // Lowercasing depends on context, like a final sigma, so goes through `apply`
+            NameCase::Lower | NameCase::Title | NameCase::Mock => out.push_str(&self.apply(name)),
+        }
+    }
+}
+
+/// Capitalizes the first letter of each word and lowercases the rest, so "o'BRIEN-SMITH"
+/// becomes "O'Brien-Smith".
+fn title_case(name: &str) -> String {
+    let mut titled = String::with_capacity(name.len());
+    let mut word_start = true;
+    // Lowercasing the whole name first keeps context-sensitive mappings like a final sigma
+    for c in name.to_lowercase().chars() {
+        if word_start {
+            titled.extend(c.to_uppercase());
+        } else {
+            titled.push(c);
+        }
+        word_start = !c.is_alphabetic();
+    }
+    titled
+}
+
+/// The pronouns a name takes, for `XPRONOUNX` and `XPOSSESSIVEX`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pronouns<'a> {
+    /// Like "she" in "she says it works"
+    pub subject: &'a str,
+    /// Like "her" in "on her machine"
+    pub possessive: &'a str,
+}
+
+impl Default for Pronouns<'_> {
+    fn default() -> Self {
+        Pronouns {
+            subject: "they",
+            possessive: "their",
+        }
+    }
+}
+
+/// Possessives of the common pronouns, found by their subject form.
+const POSSESSIVES: [(&str, &str); 4] = [
+    ("he", "his"),
+    ("she", "her"),
+    ("they", "their"),
+    ("it", "its"),
+];
+
+/// Splits a names file entry into the name and its pronouns, given after the name in
+/// parentheses like "Alex (she/her)". A pronoun set in three parts, like "xe/xem/xyr",
+/// names the possessive last; otherwise the first part must be he, she, they or it.
+/// Entries without pronouns, or with ones that can't be read, take they/their.
+pub fn split(entry: &str) -> (&str, Pronouns<'_>) {
+    let parsed = entry
+        .strip_suffix(')')
+        .and_then(|rest| rest.rsplit_once(" ("))
+        .and_then(|(name, set)| {
+            let parts: Vec<&str> = set.split('/').map(str::trim).collect();
+            let subject = parts[0];
+            let possessive = match parts.len() {
+                3 => parts[2],
+                _ => {
+                    POSSESSIVES
+                        .iter()
+                        .find(|(s, _)| s.eq_ignore_ascii_case(subject))?
+                        .1
+                }
+            };
+            let valid = |p: &str| !p.is_empty() && p.chars().all(char::is_alphabetic);
+            (parts.len() <= 3 && parts.iter().all(|p| valid(p))).then_some((
+                name.trim_end(),
+                Pronouns {
+                    subject,
+                    possessive,
+                },
+            ))
+        });
+    parsed.unwrap_or((entry, Pronouns::default()))
+}
+
+/// About how many entries [`by_commits`] spreads the authors over; authors with only a few
+/// commits among many still get one.
+const WEIGHTED_ENTRIES: usize = 1000;
+
+/// The names of `authors`, each repeated in proportion to their commits, so picking one at
+/// random blames the most prolific authors most often.
+pub fn by_commits(authors: &[(String, usize)]) -> Vec<String> {
+    let total: usize = authors.iter().map(|(_, commits)| commits).sum();
+    authors
+        .iter()
+        .flat_map(|(name, commits)| {
+            let weight = (commits * WEIGHTED_ENTRIES)
+                .checked_div(total)
+                .unwrap_or(0)
+                .max(1);
+            std::iter::repeat_n(name.clone(), weight)
+        })
+        .collect()
+}
+
+/// Prints `count` random names, one per line.
+pub fn run<R>(names: &[String], case: NameCase, count: usize, rng: &mut R) -> Result<()>
+where
+    R: Rng + ?Sized,
+{
+    for _ in 0..count {
+        let name = names.choose(rng).context("Failed to select any names")?;
+        println!("{}", case.apply(split(name).0));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_names_by_commits() {
+        let authors = [
+            ("Ada".to_string(), 300),
+            ("Grace".to_string(), 100),
+            ("Linus".to_string(), 0),
+        ];
+        let names = by_commits(&authors);
+        let count = |name| names.iter().filter(|n| *n == name).count();
+        assert_eq!(
+            (count("Ada"), count("Grace"), count("Linus")),
+            (750, 250, 1)
+        );
+        assert!(by_commits(&[]).is_empty());
+    }
+
+    #[test]
+    fn t_name_case() {
+        assert_eq!(NameCase::AsIs.apply("McKenzie"), "McKenzie");
+        assert_eq!(NameCase::Upper.apply("McKenzie"), "MCKENZIE");
+        assert_eq!(NameCase::Lower.apply("McKenzie"), "mckenzie");
+        assert_eq!(NameCase::Title.apply("McKenzie"), "Mckenzie");
+        assert_eq!(NameCase::Mock.apply("McKenzie"), "mCkEnZiE");
+    }
+
+    #[test]
+    fn t_split_pronouns() {
+        let they = Pronouns::default();
+        assert_eq!(split("Ann"), ("Ann", they));
+        assert_eq!(split("Ann Lee (she/her)").0, "Ann Lee");
+        assert_eq!(split("Ann (she/her)").1.possessive, "her");
+        assert_eq!(split("Bo (He/Him)").1.possessive, "his");
+        assert_eq!(
+            split("Cy (xe/xem/xyr)").1,
+            Pronouns {
+                subject: "xe",
+                possessive: "xyr"
+            }
+        );
+        // Parentheses that aren't pronouns stay part of the name
+        assert_eq!(split("Dee (contractor)"), ("Dee (contractor)", they));
+        assert_eq!(split("Ed (he/)"), ("Ed (he/)", they));
+    }
+
+    #[test]
+    fn t_name_case_unicode() {
+        assert_eq!(NameCase::Upper.apply("Łukasz"), "ŁUKASZ");
+        assert_eq!(NameCase::Upper.apply("Çağla"), "ÇAĞLA");
+        assert_eq!(NameCase::Lower.apply("ÇAĞLA ÖZ"), "çağla öz");
+        assert_eq!(NameCase::Lower.apply("ΟΔΥΣΣΕΑΣ"), "οδυσσεας");
+        assert_eq!(NameCase::Upper.apply("Strauß"), "STRAUSS");
+        assert_eq!(NameCase::Title.apply("o'BRIEN-SMITH"), "O'Brien-Smith");
+        assert_eq!(NameCase::Title.apply("ÉLODIE łukasz"), "Élodie Łukasz");
+        for case in NameCase::value_variants() {
+            let mut out = "> ".to_string();
+            case.push_to("ΟΔΥΣΣΕΑΣ Strauß", &mut out);
+            assert_eq!(out, format!("> {}", case.apply("ΟΔΥΣΣΕΑΣ Strauß")));
+        }
+    }
+}
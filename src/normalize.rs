@@ -0,0 +1,95 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// A fix to the subject line, the first line, of a rendered message for a commit style
+/// guide. However they are given, they run in the order listed here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Normalizer {
+    /// Runs of spaces and tabs squeezed into one space, and none at either end
+    Whitespace,
+    /// A period ending the subject dropped, though not an ellipsis
+    NoPeriod,
+    /// The first letter of the subject capitalized
+    Capitalize,
+    /// All of the above
+    All,
+}
+
+impl Normalizer {
+    /// Every normalizer `All` stands for.
+    const EACH: [Normalizer; 3] = [
+        Normalizer::Whitespace,
+        Normalizer::NoPeriod,
+        Normalizer::Capitalize,
+    ];
+
+    fn apply(self, subject: &str) -> String {
+        match self {
+            Normalizer::Whitespace => subject.split_whitespace().collect::<Vec<_>>().join(" "),
+            Normalizer::NoPeriod => match subject.strip_suffix('.') {
+                Some(rest) if !rest.ends_with('.') => rest.trim_end().to_string(),
+                _ => subject.to_string(),
+            },
+            Normalizer::Capitalize => {
+                let start = subject.len() - subject.trim_start().len();
+                let mut chars = subject[start..].chars();
+                match chars.next() {
+                    Some(first) => {
+                        let capital: String = first.to_uppercase().chain(chars).collect();
+                        format!("{}{}", &subject[..start], capital)
+                    }
+                    None => subject.to_string(),
+                }
+            }
+            Normalizer::All => apply_all(&Self::EACH, subject.to_string()),
+        }
+    }
+}
+
+/// Runs the subject line of `message` through `normalizers`, in their own order, leaving
+/// any body alone.
+pub fn apply_all(normalizers: &[Normalizer], message: String) -> String {
+    if normalizers.is_empty() {
+        return message;
+    }
+    let mut normalizers = normalizers.to_vec();
+    normalizers.sort();
+    normalizers.dedup();
+    let (subject, body) = match message.split_once('\n') {
+        Some((subject, body)) => (subject, Some(body)),
+        None => (message.as_str(), None),
+    };
+    let subject = (normalizers.iter()).fold(subject.to_string(), |subject, normalizer| {
+        normalizer.apply(&subject)
+    });
+    match body {
+        Some(body) => format!("{}\n{}", subject, body),
+        None => subject,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_normalize_subject() {
+        let all = |message: &str| apply_all(&[Normalizer::All], message.to_string());
+        assert_eq!(all("  fixed   the\tbuild . "), "Fixed the build");
+        assert_eq!(
+            all("écrit le code.\n\nbody stays.  "),
+            "Écrit le code\n\nbody stays.  "
+        );
+        assert_eq!(all("wait for it..."), "Wait for it...");
+        assert_eq!(all(""), "");
+        assert_eq!(
+            apply_all(
+                &[Normalizer::Capitalize, Normalizer::NoPeriod],
+                " oops.".to_string()
+            ),
+            " Oops"
+        );
+        assert_eq!(apply_all(&[], "as is.".to_string()), "as is.");
+    }
+}
@@ -0,0 +1,39 @@
+use anyhow::Result;
+use notify_rust::Notification;
+
+/// Shows a desktop notification for a new commit, with its short hash and subject.
+pub fn committed(hash: &str, message: &str) -> Result<()> {
+    let (summary, body) = content(hash, message);
+    Notification::new()
+        .appname("whatthecommit")
+        .summary(&summary)
+        .body(body)
+        .show()?;
+    Ok(())
+}
+
+/// The summary and body of the notification for the commit `hash` with `message`.
+fn content<'a>(hash: &str, message: &'a str) -> (String, &'a str) {
+    let subject = message.lines().next().unwrap_or_default();
+    (format!("Committed {}", hash), subject)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_notification_shows_the_subject() {
+        assert_eq!(
+            content(
+                "1a2b3c4",
+                "fixed it\n\nSigned-off-by: Ann <ann@example.com>\n"
+            ),
+            ("Committed 1a2b3c4".to_string(), "fixed it")
+        );
+        assert_eq!(
+            content("1a2b3c4", ""),
+            ("Committed 1a2b3c4".to_string(), "")
+        );
+    }
+}
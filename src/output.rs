@@ -0,0 +1,115 @@
+use crate::theme::Theme;
+use anstyle::{AnsiColor, Style};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::env;
+use std::io::{self, IsTerminal};
+
+/// When to use colors and decorations in terminal output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorChoice {
+    /// Decorate only when writing to a terminal
+    #[default]
+    Auto,
+    /// Always decorate
+    Always,
+    /// Never decorate
+    Never,
+}
+
+impl ColorChoice {
+    /// Resolves the choice for a stream, honoring `NO_COLOR` and `TERM=dumb` in auto mode.
+    fn enabled(self, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                is_terminal
+                    && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+                    && env::var("TERM").map_or(true, |t| t != "dumb")
+            }
+        }
+    }
+}
+
+/// Decides how output is decorated for stdout and stderr.
+///
+/// Humans at a terminal get styled output, while pipes and redirects get the bare
+/// message so scripts never have to strip escape codes.
+#[derive(Debug, Clone, Copy)]
+pub struct Output {
+    stdout: bool,
+    stderr: bool,
+    theme: Theme,
+}
+
+impl Output {
+    /// Resolves decoration for both streams; `plain` forces undecorated output.
+    pub fn new(choice: ColorChoice, plain: bool, theme: Theme) -> Self {
+        let choice = if plain { ColorChoice::Never } else { choice };
+        Output {
+            stdout: choice.enabled(io::stdout().is_terminal()),
+            stderr: choice.enabled(io::stderr().is_terminal()),
+            theme,
+        }
+    }
+
+    /// Formats a generated message for stdout, highlighting the substituted name.
+    pub fn message(&self, message: &str, name: &str) -> String {
+        if self.stdout {
+            self.theme.highlight(message, name)
+        } else {
+            message.to_string()
+        }
+    }
+
+    /// Formats the `Error:` label for stderr.
+    pub fn error_label(&self) -> String {
+        paint(
+            self.stderr,
+            Style::new().bold().fg_color(Some(AnsiColor::Red.into())),
+            "Error:",
+        )
+    }
+}
+
+/// Wraps text in a style's escape codes when enabled.
+fn paint(enabled: bool, style: Style, text: &str) -> String {
+    if enabled {
+        format!("{style}{text}{style:#}")
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::theme::ThemeName;
+
+    #[test]
+    fn t_explicit_choices_ignore_terminal() {
+        assert!(ColorChoice::Always.enabled(false));
+        assert!(!ColorChoice::Never.enabled(true));
+        assert!(!ColorChoice::Auto.enabled(false));
+    }
+
+    #[test]
+    fn t_plain_output_is_bare() {
+        let output = Output::new(ColorChoice::Never, false, ThemeName::Default.into());
+        assert_eq!(output.message("Bob fixed it", "Bob"), "Bob fixed it");
+        assert_eq!(output.error_label(), "Error:");
+    }
+
+    #[test]
+    fn t_decorated_output_is_styled() {
+        let theme = Theme::from(ThemeName::Mono);
+        let output = Output::new(ColorChoice::Always, false, theme);
+        assert_eq!(output.message("fixed it", "Bob"), "\x1b[1mfixed it\x1b[0m");
+        assert_eq!(
+            Output::new(ColorChoice::Always, true, theme).message("fixed it", "Bob"),
+            "fixed it"
+        );
+    }
+}
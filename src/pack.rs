@@ -0,0 +1,384 @@
+use crate::corpus;
+use crate::dedupe;
+use crate::error::WtcError;
+use crate::sections;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Write to stdout instead of a file.
+const STDIO: &str = "-";
+
+/// Templates files combined into one.
+#[derive(Debug, Default, PartialEq)]
+struct Merged {
+    /// Each section, by the spelling of its header first seen, with its templates, in the
+    /// order they first appear. Templates outside any section come under `None`.
+    sections: Vec<(Option<String>, Vec<String>)>,
+    /// Templates left out because an earlier file already had them.
+    dropped: usize,
+    /// What someone should look at: templates put under different sections by different
+    /// files, and templates kept that nearly duplicate others.
+    conflicts: Vec<String>,
+}
+
+impl Merged {
+    fn templates(&self) -> usize {
+        self.sections
+            .iter()
+            .map(|(_, templates)| templates.len())
+            .sum()
+    }
+
+    /// The merged templates file.
+    fn render(&self) -> String {
+        render(&self.sections)
+    }
+}
+
+/// A templates file with `sections`, each by its header and templates, those outside any
+/// section first.
+pub fn render(sections: &[(Option<String>, Vec<String>)]) -> String {
+    let mut text = String::new();
+    let (plain, named): (Vec<_>, Vec<_>) =
+        sections.iter().partition(|(header, _)| header.is_none());
+    for (header, templates) in plain.into_iter().chain(named) {
+        if let Some(header) = header {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            let _ = writeln!(text, "[{}]", header);
+        }
+        for template in templates {
+            let _ = writeln!(text, "{}", template);
+        }
+    }
+    text
+}
+
+/// Writes a templates file to `output`, or stdout for `-`.
+pub fn write(output: &Path, text: &str) -> Result<()> {
+    if output == Path::new(STDIO) {
+        io::stdout().lock().write_all(text.as_bytes())?;
+    } else {
+        fs::write(output, text).map_err(|source| WtcError::IoError {
+            context: format!("Failed to write templates file: {:?}", output),
+            source,
+        })?;
+    }
+    Ok(())
+}
+
+/// A template of a templates file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry<'a> {
+    /// Zero-based line number.
+    pub line: usize,
+    /// The header of its section, if the file has sections and it is in one.
+    pub section: Option<String>,
+    pub template: &'a str,
+}
+
+/// The templates of `text`, with the sections they're in.
+pub fn entries(text: &str) -> Vec<Entry<'_>> {
+    let sectioned = sections::is_sectioned(text);
+    let mut section = None;
+    let mut entries = Vec::new();
+    for (line, template) in text.lines().enumerate() {
+        let template = template.trim();
+        if sectioned {
+            if let Some(header) = template.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+                section = Some(header.trim().to_string());
+                continue;
+            }
+            if template.starts_with('#') {
+                continue;
+            }
+        }
+        if !template.is_empty() {
+            entries.push(Entry {
+                line,
+                section: section.clone(),
+                template,
+            });
+        }
+    }
+    entries
+}
+
+/// Whether two sections are the same, ignoring case as headers do.
+fn same_section(a: &Option<String>, b: &Option<String>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        (a, b) => a.is_none() && b.is_none(),
+    }
+}
+
+/// Where a template came from, as `file:line [section]`.
+fn origin(path: &Path, line: usize, section: Option<&str>) -> String {
+    match section {
+        Some(section) => format!("{}:{} [{}]", path.display(), line + 1, section),
+        None => format!("{}:{}", path.display(), line + 1),
+    }
+}
+
+/// Combines `packs` in order. A template already merged, once case and whitespace are set
+/// aside, is dropped, and reported when it was under another section; one that only
+/// nearly duplicates a merged template, by `dedupe::near`, is kept and reported.
+fn merge(packs: &[(PathBuf, String)], max_distance: usize) -> Merged {
+    let mut merged = Merged::default();
+    // Every template kept, normalized, with its section index and origin
+    let mut kept: Vec<(Vec<char>, usize, String)> = Vec::new();
+    let mut exact: HashMap<Vec<char>, usize> = HashMap::new();
+    for (path, text) in packs {
+        for Entry {
+            line,
+            section,
+            template,
+        } in entries(text)
+        {
+            let here = origin(path, line, section.as_deref());
+            let index = merged
+                .sections
+                .iter()
+                .position(|(header, _)| same_section(header, &section))
+                .unwrap_or_else(|| {
+                    merged.sections.push((section.clone(), Vec::new()));
+                    merged.sections.len() - 1
+                });
+
+            let normalized = dedupe::normalize(template);
+            if let Some(&found) = exact.get(&normalized) {
+                let (_, other, ref there) = kept[found];
+                if other != index {
+                    merged.conflicts.push(format!(
+                        "{} has the template from {} under another section; kept the first",
+                        here, there
+                    ));
+                }
+                merged.dropped += 1;
+                continue;
+            }
+            if let Some((_, _, there)) = kept
+                .iter()
+                .find(|(other, _, _)| dedupe::near(&normalized, other, max_distance))
+            {
+                merged
+                    .conflicts
+                    .push(format!("{} nearly duplicates {}; kept both", here, there));
+            }
+            exact.insert(normalized.clone(), kept.len());
+            kept.push((normalized, index, here));
+            merged.sections[index].1.push(template.to_string());
+        }
+    }
+    merged
+}
+
+/// Merges the templates files at `paths` into `output`, or stdout for `-`, and reports
+/// what was dropped and every conflict on stderr.
+pub fn merge_files(paths: &[PathBuf], output: &Path, max_distance: usize) -> Result<()> {
+    let packs = corpus::packs(paths, (Path::new(STDIO), &[]))?;
+    let merged = merge(&packs, max_distance);
+    for conflict in &merged.conflicts {
+        eprintln!("conflict: {}", conflict);
+    }
+    write(output, &merged.render())?;
+    eprintln!(
+        "Merged {} templates from {} files, dropping {} duplicates, with {} conflicts",
+        merged.templates(),
+        paths.len(),
+        merged.dropped,
+        merged.conflicts.len()
+    );
+    Ok(())
+}
+
+/// How a template differs between two versions of a templates file.
+#[derive(Debug, PartialEq)]
+enum Change<'a> {
+    Added(Entry<'a>),
+    Removed(Entry<'a>),
+    /// Reworded, by at most the edits `dedupe::near` allows.
+    Changed {
+        from: Entry<'a>,
+        to: Entry<'a>,
+    },
+    /// The same template, once case and whitespace are set aside, in another section.
+    Moved {
+        from: Entry<'a>,
+        to: Entry<'a>,
+    },
+}
+
+/// The changes from the templates file `old` to `new`: exact matches first, in the same
+/// section or moved, then near-duplicates as rewordings, then what's left over as removed
+/// or added.
+fn diff<'a>(old: &'a str, new: &'a str, max_distance: usize) -> Vec<Change<'a>> {
+    let normalized = |text| -> Vec<(Entry, Vec<char>)> {
+        let entries = entries(text).into_iter();
+        entries
+            .map(|e| (e.clone(), dedupe::normalize(e.template)))
+            .collect()
+    };
+    let (old, new) = (normalized(old), normalized(new));
+    // The new entry each old one became, if any, and whether it is only a rewording
+    let mut matched: Vec<Option<(usize, bool)>> = vec![None; old.len()];
+    let mut taken = vec![false; new.len()];
+    for (i, (_, normalized)) in old.iter().enumerate() {
+        let found = (0..new.len()).find(|&j| !taken[j] && new[j].1 == *normalized);
+        if let Some(j) = found {
+            matched[i] = Some((j, false));
+            taken[j] = true;
+        }
+    }
+    for (i, (_, normalized)) in old.iter().enumerate() {
+        if matched[i].is_some() {
+            continue;
+        }
+        let found = (0..new.len())
+            .find(|&j| !taken[j] && dedupe::near(normalized, &new[j].1, max_distance));
+        if let Some(j) = found {
+            matched[i] = Some((j, true));
+            taken[j] = true;
+        }
+    }
+
+    let mut changes = Vec::new();
+    for (i, (from, _)) in old.iter().enumerate() {
+        let from = from.clone();
+        match matched[i] {
+            None => changes.push(Change::Removed(from)),
+            Some((j, true)) => changes.push(Change::Changed {
+                from,
+                to: new[j].0.clone(),
+            }),
+            Some((j, false)) if !same_section(&from.section, &new[j].0.section) => {
+                changes.push(Change::Moved {
+                    from,
+                    to: new[j].0.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    let added = new.iter().zip(&taken).filter(|(_, &taken)| !taken);
+    changes.extend(added.map(|((entry, _), _)| Change::Added(entry.clone())));
+    changes
+}
+
+/// An entry's template, after its section's header if it has one.
+fn describe(entry: &Entry) -> String {
+    match &entry.section {
+        Some(section) => format!("[{}] {}", section, entry.template),
+        None => entry.template.to_string(),
+    }
+}
+
+impl std::fmt::Display for Change<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Added(entry) => write!(f, "+ {}", describe(entry)),
+            Change::Removed(entry) => write!(f, "- {}", describe(entry)),
+            Change::Changed { from, to } => {
+                write!(f, "~ {}\n  {}", describe(from), describe(to))
+            }
+            Change::Moved { from, to } => {
+                let section = |entry: &Entry| match &entry.section {
+                    Some(section) => format!("[{}]", section),
+                    None => "no section".to_string(),
+                };
+                write!(
+                    f,
+                    "> {}: {} -> {}",
+                    from.template,
+                    section(from),
+                    section(to)
+                )
+            }
+        }
+    }
+}
+
+/// Prints how the templates file at `new` differs from the one at `old`, and a count of
+/// each kind of change.
+pub fn diff_files(old: &Path, new: &Path, max_distance: usize) -> Result<()> {
+    let packs = corpus::packs(
+        &[old.to_path_buf(), new.to_path_buf()],
+        (Path::new(STDIO), &[]),
+    )?;
+    let changes = diff(&packs[0].1, &packs[1].1, max_distance);
+    let mut counts = [0; 4];
+    for change in &changes {
+        println!("{}", change);
+        counts[match change {
+            Change::Added(_) => 0,
+            Change::Removed(_) => 1,
+            Change::Changed { .. } => 2,
+            Change::Moved { .. } => 3,
+        }] += 1;
+    }
+    eprintln!(
+        "{} added, {} removed, {} changed, {} moved",
+        counts[0], counts[1], counts[2], counts[3]
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_merge_packs() {
+        let packs = vec![
+            (
+                PathBuf::from("a.txt"),
+                "[Monday]\nFixed the build again\n# a comment\nXNAMEX broke it\n[friday]\nshipped it\n"
+                    .to_string(),
+            ),
+            (
+                PathBuf::from("b.txt"),
+                "[monday]\nfixed  the BUILD again\nnew one\n[friday]\nXNAMEX broke it\nFixed the builds again\n"
+                    .to_string(),
+            ),
+            (PathBuf::from("c.txt"), "plain\nshipped it\n".to_string()),
+        ];
+        let merged = merge(&packs, 2);
+        assert_eq!(merged.templates(), 6);
+        assert_eq!(merged.dropped, 3);
+        assert_eq!(
+            merged.conflicts,
+            [
+                "b.txt:5 [friday] has the template from a.txt:4 [Monday] under another section; kept the first",
+                "b.txt:6 [friday] nearly duplicates a.txt:2 [Monday]; kept both",
+                "c.txt:2 has the template from a.txt:6 [friday] under another section; kept the first",
+            ]
+        );
+        assert_eq!(
+            merged.render(),
+            "plain\n\n[Monday]\nFixed the build again\nXNAMEX broke it\nnew one\n\n[friday]\nshipped it\nFixed the builds again\n"
+        );
+    }
+
+    #[test]
+    fn t_diff_packs() {
+        let old = "[monday]\nFixed the build again\nXNAMEX broke it\nshipped it\n[friday]\nbeer\n";
+        let new = "[Monday]\nfixed the builds again\nshipped  IT\n[friday]\nXNAMEX broke it\nbeer\nnew one\n";
+        let changes: Vec<String> = diff(old, new, 2).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            changes,
+            [
+                "~ [monday] Fixed the build again\n  [Monday] fixed the builds again",
+                "> XNAMEX broke it: [monday] -> [friday]",
+                "+ [friday] new one",
+            ]
+        );
+        let removed = diff("gone\nkept\n", "kept\n", 2);
+        assert_eq!(removed.len(), 1);
+        assert!(matches!(&removed[0], Change::Removed(e) if e.template == "gone" && e.line == 0));
+    }
+}
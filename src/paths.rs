@@ -0,0 +1,160 @@
+use anyhow::Result;
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Name of the directory created inside each platform base directory.
+const APP_DIR: &str = "whatthecommit";
+
+/// Directory holding the user config file.
+///
+/// `$XDG_CONFIG_HOME/whatthecommit` (or `~/.config/whatthecommit`) on Linux,
+/// `~/Library/Application Support/whatthecommit` on macOS and
+/// `%APPDATA%\whatthecommit` on Windows.
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join(APP_DIR))
+}
+
+/// Directory of word lists extending the built-in ones, such as `languages.txt`: the
+/// user's own, or without one, those baked into this binary.
+pub fn words_dir() -> Option<PathBuf> {
+    let own = own_words_dir();
+    match crate::bake::dir() {
+        Some(baked) if !own.as_deref().is_some_and(Path::is_dir) => Some(baked.join("words")),
+        _ => own,
+    }
+}
+
+/// The user's own directory of word lists, in the config directory.
+pub fn own_words_dir() -> Option<PathBuf> {
+    config_dir().map(|d| d.join("words"))
+}
+
+/// Directory for persistent data such as favorites and history.
+///
+/// `$XDG_DATA_HOME/whatthecommit` (or `~/.local/share/whatthecommit`) on Linux.
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join(APP_DIR))
+}
+
+/// Directory of templates files installed with `pack install`.
+pub fn packs_dir() -> Option<PathBuf> {
+    data_dir().map(|d| d.join("packs"))
+}
+
+/// Whether `name` can name an installed pack: a plain file name, so that it can't lead
+/// outside the packs directory.
+pub fn is_pack_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\', ':'])
+}
+
+/// Directory for disposable cached data.
+///
+/// `$XDG_CACHE_HOME/whatthecommit` (or `~/.cache/whatthecommit`) on Linux.
+pub fn cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|d| d.join(APP_DIR))
+}
+
+/// Name of the per-repository config file, looked up at the repository root.
+pub const REPO_CONFIG_FILE: &str = ".whatthecommit.toml";
+
+/// Name of the lockfile recording the content hash of every remote corpus source.
+pub const LOCK_FILE: &str = "wtc.lock";
+
+/// Where the lockfile goes: the repository root, so it can be committed, or else the
+/// config directory.
+pub fn lock_file() -> Option<PathBuf> {
+    repo_root_of_cwd()
+        .or_else(config_dir)
+        .map(|dir| dir.join(LOCK_FILE))
+}
+
+/// Default location of the user config file.
+pub fn config_file() -> Option<PathBuf> {
+    config_dir().map(|d| d.join("config.toml"))
+}
+
+/// Returns the root of the repository containing `dir`: the nearest ancestor with a `.git` entry.
+fn repo_root(dir: &Path) -> Option<&Path> {
+    dir.ancestors().find(|d| d.join(".git").exists())
+}
+
+/// Root of the repository containing the current directory, if any.
+pub fn repo_root_of_cwd() -> Option<PathBuf> {
+    let cwd = env::current_dir().ok()?;
+    repo_root(&cwd).map(Path::to_path_buf)
+}
+
+/// Location of the per-repository config file, when inside a repository.
+pub fn repo_config_file() -> Option<PathBuf> {
+    repo_root_of_cwd().map(|root| root.join(REPO_CONFIG_FILE))
+}
+
+fn describe(path: Option<&Path>) -> String {
+    match path {
+        Some(path) if path.exists() => path.display().to_string(),
+        Some(path) => format!("{} (not found)", path.display()),
+        None => "(unavailable on this platform)".to_string(),
+    }
+}
+
+/// Prints the resolved locations, preferring an explicitly given config file.
+pub fn print(config: Option<&Path>) -> Result<()> {
+    let config_file = config.map(Path::to_path_buf).or_else(config_file);
+    println!("config file  {}", describe(config_file.as_deref()));
+    println!("repo config  {}", describe(repo_config_file().as_deref()));
+    println!("lockfile     {}", describe(lock_file().as_deref()));
+    println!("config dir   {}", describe(config_dir().as_deref()));
+    println!("words dir    {}", describe(words_dir().as_deref()));
+    println!("data dir     {}", describe(data_dir().as_deref()));
+    println!("packs dir    {}", describe(packs_dir().as_deref()));
+    println!("cache dir    {}", describe(cache_dir().as_deref()));
+    if let Some(baked) = crate::bake::dir() {
+        println!("baked in     {}", describe(Some(baked)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn t_repo_root_is_nearest_git_ancestor() {
+        let root = env::temp_dir().join(format!("wtc-repo-root-{}", std::process::id()));
+        let nested = root.join("src/deep");
+        fs::create_dir_all(&nested).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        assert_eq!(repo_root(&nested), Some(root.as_path()));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn t_app_dirs() {
+        for dir in [config_dir(), data_dir(), cache_dir()]
+            .into_iter()
+            .flatten()
+        {
+            assert!(dir.ends_with(APP_DIR), "{}", dir.display());
+        }
+        if let Some(file) = config_file() {
+            assert_eq!(file.parent(), config_dir().as_deref());
+            assert!(file.ends_with("whatthecommit/config.toml"));
+        }
+        if let (Some(packs), Some(data)) = (packs_dir(), data_dir()) {
+            assert_eq!(packs, data.join("packs"));
+        }
+    }
+
+    #[test]
+    fn t_describe() {
+        let dir = env::temp_dir();
+        assert_eq!(describe(Some(&dir)), dir.display().to_string());
+        let missing = dir.join(format!("wtc-missing-{}", std::process::id()));
+        assert_eq!(
+            describe(Some(&missing)),
+            format!("{} (not found)", missing.display())
+        );
+        assert_eq!(describe(None), "(unavailable on this platform)");
+    }
+}
@@ -0,0 +1,262 @@
+use crate::favorites;
+use crate::keys::{KeyAction, Keymap};
+use crate::tui;
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+/// Scores how well `query` fuzzily matches `text`, skim-style: every query character must
+/// appear in order, ignoring case. Consecutive matches and matches at word starts score
+/// higher. Returns the score and the matched character positions.
+fn fuzzy_match(query: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut positions = Vec::new();
+    let mut score = 0;
+    let mut next = 0;
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        let q = q.to_lowercase().next().unwrap_or(q);
+        let found = (next..chars.len()).find(|&i| chars[i].to_lowercase().next() == Some(q))?;
+        score += 1;
+        if positions.last() == Some(&(found.wrapping_sub(1))) {
+            score += 5;
+        }
+        if found == 0 || !chars[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (found - next) as i64 / 4;
+        positions.push(found);
+        next = found + 1;
+    }
+    Some((score, positions))
+}
+
+struct Picker<'a> {
+    candidates: &'a [String],
+    query: String,
+    state: ListState,
+    status: String,
+    keys: &'a Keymap,
+    /// Marked candidates, in the order they were marked.
+    marked: Vec<usize>,
+}
+
+impl Picker<'_> {
+    /// Indices of candidates matching the query, best match first.
+    fn matches(&self) -> Vec<(usize, Vec<usize>)> {
+        let mut scored: Vec<(i64, usize, Vec<usize>)> = self
+            .candidates
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| fuzzy_match(&self.query, c).map(|(s, p)| (s, i, p)))
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        scored.into_iter().map(|(_, i, p)| (i, p)).collect()
+    }
+
+    fn selected(&self) -> Option<usize> {
+        let index = self.state.selected()?;
+        self.matches().get(index).map(|(i, _)| *i)
+    }
+
+    /// The marked candidates, or the selected one if none are marked.
+    fn picked(&self) -> Option<Vec<usize>> {
+        if self.marked.is_empty() {
+            self.selected().map(|i| vec![i])
+        } else {
+            Some(self.marked.clone())
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Pick> {
+        if let Some(action) = self.keys.action(&key) {
+            match action {
+                KeyAction::Accept => return self.picked().map(Pick::Print),
+                KeyAction::Commit => return self.picked().map(Pick::Commit),
+                KeyAction::Edit => return self.picked().map(Pick::Edit),
+                KeyAction::Mark => {
+                    if let Some(i) = self.selected() {
+                        match self.marked.iter().position(|&m| m == i) {
+                            Some(at) => {
+                                self.marked.remove(at);
+                            }
+                            None => self.marked.push(i),
+                        }
+                        self.state.select_next();
+                    }
+                }
+                KeyAction::Quit => return Some(Pick::Cancel),
+                KeyAction::Star => {
+                    if let Some(i) = self.selected() {
+                        self.status = favorites::star(&self.candidates[i]);
+                    }
+                }
+                KeyAction::Copy => {
+                    if let Some(i) = self.selected() {
+                        self.status = match tui::copy(&self.candidates[i]) {
+                            Ok(()) => "Copied to the clipboard".to_string(),
+                            Err(e) => format!("Error: {}", e),
+                        };
+                    }
+                }
+                KeyAction::Regenerate => {}
+            }
+            return None;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Up => self.state.select_previous(),
+            KeyCode::Char('p') if ctrl => self.state.select_previous(),
+            KeyCode::Down => self.state.select_next(),
+            KeyCode::Char('n') if ctrl => self.state.select_next(),
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.state.select(Some(0));
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.state.select(Some(0));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [list_area, prompt_area] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+
+        let matches = self.matches();
+        let bold = Style::new().add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+        let items: Vec<ListItem> = matches
+            .iter()
+            .map(|(i, positions)| {
+                let marker = if self.marked.contains(i) { "* " } else { "  " };
+                let spans: Vec<Span> = std::iter::once(Span::raw(marker))
+                    .chain(self.candidates[*i].chars().enumerate().map(|(pos, c)| {
+                        let style = if positions.contains(&pos) {
+                            bold
+                        } else {
+                            Style::new()
+                        };
+                        Span::styled(c.to_string(), style)
+                    }))
+                    .collect();
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_symbol("> ")
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, list_area, &mut self.state);
+
+        let hint = if self.status.is_empty() {
+            self.keys.help()
+        } else {
+            self.status.clone()
+        };
+        let marked = match self.marked.len() {
+            0 => String::new(),
+            n => format!(", {} marked", n),
+        };
+        let prompt = format!(
+            "> {}  {}/{}{}  ({})",
+            self.query,
+            matches.len(),
+            self.candidates.len(),
+            marked,
+            hint
+        );
+        frame.render_widget(Paragraph::new(prompt), prompt_area);
+    }
+}
+
+/// What the user picked, as indices into the candidates: the marked ones in the order
+/// they were marked, or else just the selected one.
+#[derive(Debug, PartialEq)]
+pub enum Pick {
+    Print(Vec<usize>),
+    Commit(Vec<usize>),
+    Edit(Vec<usize>),
+    Cancel,
+}
+
+/// Lets the user fuzzy-find one of the candidates.
+pub fn run(candidates: &[String], keys: &Keymap) -> Result<Pick> {
+    let mut picker = Picker {
+        candidates,
+        query: String::new(),
+        state: ListState::default().with_selected(Some(0)),
+        status: String::new(),
+        keys,
+        marked: Vec::new(),
+    };
+    tui::with_terminal(|terminal| loop {
+        terminal.draw(|frame| picker.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                picker.status.clear();
+                if let Some(pick) = picker.handle_key(key) {
+                    return Ok(pick);
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_fuzzy_match() {
+        assert_eq!(fuzzy_match("fb", "Fix bug").map(|m| m.1), Some(vec![0, 4]));
+        assert!(fuzzy_match("bf", "Fix bug").is_none());
+        let (consecutive, _) = fuzzy_match("fix", "fix it").unwrap();
+        let (scattered, _) = fuzzy_match("fix", "far in the box").unwrap();
+        assert!(consecutive > scattered);
+    }
+
+    #[test]
+    fn t_picker_orders_best_first() {
+        let candidates = vec!["far in the box".to_string(), "fix it".to_string()];
+        let mut picker = Picker {
+            candidates: &candidates,
+            query: String::new(),
+            state: ListState::default().with_selected(Some(0)),
+            status: String::new(),
+            keys: &Keymap::picker_defaults(),
+            marked: Vec::new(),
+        };
+        for c in "fix".chars() {
+            picker.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        assert_eq!(picker.selected(), Some(1));
+    }
+
+    #[test]
+    fn t_marked_in_order() {
+        let candidates: Vec<String> = ["one", "two", "three"].map(String::from).to_vec();
+        let keys = Keymap::picker_defaults();
+        let mut picker = Picker {
+            candidates: &candidates,
+            query: String::new(),
+            state: ListState::default().with_selected(Some(2)),
+            status: String::new(),
+            keys: &keys,
+            marked: Vec::new(),
+        };
+        let press =
+            |picker: &mut Picker, code| picker.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+        press(&mut picker, KeyCode::Tab);
+        picker.state.select(Some(0));
+        press(&mut picker, KeyCode::Tab);
+        assert_eq!(
+            press(&mut picker, KeyCode::Enter),
+            Some(Pick::Print(vec![2, 0]))
+        );
+    }
+}
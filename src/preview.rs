@@ -0,0 +1,58 @@
+use crate::output::Output;
+use anyhow::{Context, Result};
+use rand::prelude::IndexedRandom;
+use rand::Rng;
+
+/// Resolves a template argument: a template index into the loaded corpus, or an inline template.
+fn resolve<'a>(template: &'a str, commit_messages: &'a [String]) -> Result<&'a str> {
+    match template.parse::<usize>() {
+        Ok(index) => commit_messages
+            .get(index)
+            .map(String::as_str)
+            .with_context(|| {
+                format!(
+                    "Template index {} is out of range (the corpus has {} templates)",
+                    index,
+                    commit_messages.len()
+                )
+            }),
+        Err(_) => Ok(template),
+    }
+}
+
+/// Prints `count` expansions of a single template, each with a random name.
+pub fn run<R>(
+    template: &str,
+    count: usize,
+    names: &[String],
+    commit_messages: &[String],
+    rng: &mut R,
+    output: &Output,
+) -> Result<()>
+where
+    R: Rng + ?Sized,
+{
+    let template = resolve(template, commit_messages)?;
+    for _ in 0..count {
+        let name = names.choose(rng).context("Failed to select any names")?;
+        let message = crate::substitute_placeholders(template, name, rng);
+        println!("{}", output.message(&message, crate::name::split(name).0));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_resolve_index_or_inline() {
+        let corpus = vec!["first".to_string(), "second XNAMEX".to_string()];
+        assert_eq!(resolve("1", &corpus).unwrap(), "second XNAMEX");
+        assert_eq!(resolve("blame XNAMEX", &corpus).unwrap(), "blame XNAMEX");
+        assert!(resolve("2", &corpus)
+            .unwrap_err()
+            .to_string()
+            .contains("out of range"));
+    }
+}
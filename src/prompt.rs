@@ -0,0 +1,86 @@
+use crate::animation;
+use crate::theme::Theme;
+use clap::ValueEnum;
+
+/// How escape codes are wrapped so the shell doesn't count them towards the prompt width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum PromptShell {
+    /// Wrap escape codes in the \001 and \002 bytes readline understands, for PS1
+    Bash,
+    /// Wrap escape codes in `%{` and `%}` for PROMPT
+    Zsh,
+    /// Leave escape codes as they are, as starship and most prompt tools expect
+    #[default]
+    Raw,
+}
+
+/// Wraps every escape sequence in the shell's non-printing markers.
+fn wrap_escapes(text: &str, shell: PromptShell) -> String {
+    let (open, close) = match shell {
+        // `\[` and `\]` aren't interpreted in the output of a command substitution
+        PromptShell::Bash => ("\x01", "\x02"),
+        PromptShell::Zsh => ("%{", "%}"),
+        PromptShell::Raw => return text.to_string(),
+    };
+    // Styling is only ever SGR sequences, `ESC [ params m`
+    let mut wrapped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find("\x1b[") {
+        let params = rest[at + 2..]
+            .bytes()
+            .take_while(|&b| b.is_ascii_digit() || b == b';')
+            .count();
+        let end = at + 2 + params;
+        wrapped.push_str(&rest[..at]);
+        if rest[end..].starts_with('m') {
+            wrapped.push_str(open);
+            wrapped.push_str(&rest[at..=end]);
+            wrapped.push_str(close);
+            rest = &rest[end + 1..];
+        } else {
+            wrapped.push_str(&rest[at..end]);
+            rest = &rest[end..];
+        }
+    }
+    wrapped.push_str(rest);
+    wrapped
+}
+
+/// Renders a message as a prompt segment: the first line, shortened to `max_width`
+/// characters and styled with the theme when one is given.
+pub fn render(
+    message: &str,
+    name: &str,
+    theme: Option<&Theme>,
+    shell: PromptShell,
+    max_width: usize,
+) -> String {
+    let short = animation::fit(message, max_width);
+    match theme {
+        Some(theme) => wrap_escapes(&theme.highlight(&short, name), shell),
+        None => short,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::theme::ThemeName;
+
+    #[test]
+    fn t_prompt_segment() {
+        let theme = Theme::from(ThemeName::Mono);
+        assert_eq!(
+            render("fixed it\nbody", "Bob", Some(&theme), PromptShell::Bash, 20),
+            "\x01\x1b[1m\x02fixed it\x01\x1b[0m\x02"
+        );
+        assert_eq!(
+            render("fixed it", "Bob", Some(&theme), PromptShell::Zsh, 20),
+            "%{\x1b[1m%}fixed it%{\x1b[0m%}"
+        );
+        assert_eq!(
+            render("much too long", "", None, PromptShell::Raw, 5),
+            "much…"
+        );
+    }
+}
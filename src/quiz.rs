@@ -0,0 +1,118 @@
+use anyhow::Result;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::io::{BufRead, Write};
+
+/// How a quiz went.
+#[derive(Debug, PartialEq)]
+pub struct Score {
+    pub correct: usize,
+    pub asked: usize,
+}
+
+/// Reads a guess, asking again until it is `r`, `g` or `q`. Returns `None` to stop.
+fn guess<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> Result<Option<bool>> {
+    loop {
+        write!(output, "(r)eal or (g)enerated? ")?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        match line.trim().to_lowercase().as_str() {
+            "r" | "real" => return Ok(Some(true)),
+            "g" | "generated" => return Ok(Some(false)),
+            "q" | "quit" => return Ok(None),
+            _ => writeln!(output, "Please answer r or g, or q to stop")?,
+        }
+    }
+}
+
+/// Asks the user to tell real commit subjects from generated messages for `rounds`
+/// rounds, or until they quit or the real subjects run out.
+pub fn run<G, Rn, R, W>(
+    mut real: Vec<String>,
+    mut generate: G,
+    rounds: usize,
+    rng: &mut Rn,
+    mut input: R,
+    mut output: W,
+) -> Result<Score>
+where
+    G: FnMut(&mut Rn) -> Result<String>,
+    Rn: Rng + ?Sized,
+    R: BufRead,
+    W: Write,
+{
+    if real.is_empty() {
+        anyhow::bail!("No commit subjects found to quiz with");
+    }
+    real.shuffle(rng);
+    let mut score = Score {
+        correct: 0,
+        asked: 0,
+    };
+    for round in 1..=rounds {
+        let is_real = rng.random_bool(0.5);
+        let message = if is_real {
+            match real.pop() {
+                Some(subject) => subject,
+                None => break,
+            }
+        } else {
+            generate(rng)?
+        };
+        writeln!(output, "\nRound {}/{}: {}", round, rounds, message)?;
+        let Some(answer) = guess(&mut input, &mut output)? else {
+            break;
+        };
+        score.asked += 1;
+        if answer == is_real {
+            score.correct += 1;
+            writeln!(output, "Correct!")?;
+        } else {
+            let truth = if is_real { "real" } else { "generated" };
+            writeln!(output, "Nope, that one was {}.", truth)?;
+        }
+    }
+    writeln!(output, "\nScore: {}/{}", score.correct, score.asked)?;
+    Ok(score)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_quiz_scores_answers() {
+        let real = vec!["Bump version".to_string(); 10];
+        let generate = |_: &mut StdRng| Ok("made it worse".to_string());
+        let mut output = Vec::new();
+
+        // Always answering "real" is right exactly when a real subject was shown
+        let answers = "r\n".repeat(6);
+        let mut rng = StdRng::seed_from_u64(3);
+        let score = run(real, generate, 6, &mut rng, answers.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(score.asked, 6);
+        assert_eq!(score.correct, output.matches(": Bump version").count());
+        assert!(output.ends_with(&format!("Score: {}/6\n", score.correct)));
+    }
+
+    #[test]
+    fn t_quiz_stops_on_quit() {
+        let real = vec!["Bump version".to_string()];
+        let generate = |_: &mut StdRng| Ok("made it worse".to_string());
+        let mut rng = StdRng::seed_from_u64(3);
+        let score = run(real, generate, 5, &mut rng, "x\nq\n".as_bytes(), Vec::new()).unwrap();
+        assert_eq!(
+            score,
+            Score {
+                correct: 0,
+                asked: 0
+            }
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use crate::lint;
+use crate::spice::Spice;
+
+/// Parts of words that make an untagged template savage, wherever they are in the word,
+/// as in "fuckup" or "bullshit".
+const SAVAGE_ANYWHERE: [&str; 2] = ["fuck", "shit"];
+
+/// Starts of words that make an untagged template savage.
+const SAVAGE: [&str; 6] = ["asshole", "bastard", "bitch", "cock", "cunt", "dick"];
+
+/// Starts of words that make an untagged template medium.
+const MEDIUM: [&str; 5] = ["bollock", "crap", "damn", "piss", "wtf"];
+
+/// Whole words that make an untagged template medium.
+const MEDIUM_WORDS: [&str; 5] = ["hell", "suck", "sucked", "sucks", "sucky"];
+
+/// How spicy `template` reads, going by the words in it, for templates nobody tagged.
+/// Errs on the spicy side: "Hell Divers" is medium.
+pub fn guess(template: &str) -> Spice {
+    let lower = template.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    let starts =
+        |stems: &[&str]| (words.iter()).any(|word| stems.iter().any(|stem| word.starts_with(stem)));
+    if SAVAGE_ANYWHERE.iter().any(|part| lower.contains(part)) || starts(&SAVAGE) {
+        Spice::Strong
+    } else if starts(&MEDIUM) || words.iter().any(|word| MEDIUM_WORDS.contains(word)) {
+        Spice::Medium
+    } else {
+        Spice::Censored
+    }
+}
+
+/// The spiciest templates allowed, and how the spice of templates is found.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cap {
+    pub max: Spice,
+    /// Whether templates without a `{{spice: LEVEL}}` tag are rated by `guess` rather than
+    /// taken as clean, for templates from somewhere nobody vouched for.
+    pub guess: bool,
+}
+
+impl Cap {
+    /// The cap for `--spice` at `max`. Untagged templates are guessed at when they were
+    /// fetched, and always at the censored floor, so `--clean` holds for any template.
+    pub fn new(max: Spice, fetched: bool) -> Self {
+        Cap {
+            max,
+            guess: fetched || max == Spice::Censored,
+        }
+    }
+
+    /// Whether every template is allowed, so none need be read.
+    pub fn allows_all(&self) -> bool {
+        self.max == Spice::Strong
+    }
+
+    /// How spicy `template` is: what its tags say, or what it's guessed to be.
+    pub fn rate(&self, template: &str) -> Spice {
+        match lint::spice(template) {
+            Some(spice) => spice,
+            None if self.guess => guess(template),
+            None => Spice::Censored,
+        }
+    }
+
+    pub fn allows(&self, template: &str) -> bool {
+        self.rate(template) <= self.max
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_rating_guesses() {
+        assert_eq!(guess("Fixed the build"), Spice::Censored);
+        assert_eq!(guess("Shell scripts say hello to XSWEARX"), Spice::Censored);
+        assert_eq!(guess("What the HELL happened"), Spice::Medium);
+        assert_eq!(guess("crappy fix"), Spice::Medium);
+        assert_eq!(guess("refuckulated the carbonator"), Spice::Strong);
+
+        let clean = Cap::new(Spice::Censored, false);
+        assert!(!clean.allows("damn it"));
+        assert!(clean.allows("{{spice: g}}damn it"));
+        let medium = Cap::new(Spice::Medium, false);
+        assert!(medium.allows("what the fuck"));
+        assert!(!medium.allows("{{rating: R}}what the heck"));
+        assert!(!Cap::new(Spice::Medium, true).allows("what the fuck"));
+        assert!(!Cap::new(Spice::Mild, false).allows("{{spice: medium}}damn it"));
+
+        // The tags of the built-in templates never go past what their words give away
+        for template in include_str!("commit_messages.txt").lines() {
+            let tagged = lint::spice(template).unwrap_or(Spice::Censored);
+            assert!(guess(template) >= tagged, "{}", template);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::escape::Escape;
+use crate::sections;
+use anyhow::Result;
+
+/// The built-in templates, under `[added]`, `[fixed]` and `[known issues]` headers.
+pub const DEFAULT: &str = include_str!("release_notes.txt");
+
+/// A group of changes in the notes, each with its own templates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Added,
+    Fixed,
+    KnownIssues,
+}
+
+impl Section {
+    pub const ALL: [Section; 3] = [Section::Added, Section::Fixed, Section::KnownIssues];
+
+    /// The header naming the section in a templates file.
+    fn key(self) -> &'static str {
+        match self {
+            Section::Added => "added",
+            Section::Fixed => "fixed",
+            Section::KnownIssues => "known issues",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Section::Added => "Added",
+            Section::Fixed => "Fixed",
+            Section::KnownIssues => "Known Issues",
+        }
+    }
+}
+
+/// Splits a templates file into the templates of each section, in the order of
+/// [`Section::ALL`]. Blank lines and `#` comments are skipped.
+pub fn parse(text: &str) -> Result<[Vec<String>; 3]> {
+    sections::parse(text, Section::ALL.map(Section::key), "release notes")
+}
+
+/// How many of `count` bullet points go in each section, in the order of
+/// [`Section::ALL`]: as even a split as there can be, earlier sections getting any extra.
+pub fn split(count: usize) -> [usize; 3] {
+    let len = Section::ALL.len();
+    std::array::from_fn(|i| count / len + usize::from(i < count % len))
+}
+
+/// Lays out the notes for `version` as Markdown, leaving out empty sections.
+pub fn render(version: &str, parts: &[(Section, Vec<String>)]) -> String {
+    let mut out = format!("## {}\n", Escape::Markdown.apply(version));
+    for (section, items) in parts.iter().filter(|(_, items)| !items.is_empty()) {
+        out.push_str(&format!("\n### {}\n\n", section.title()));
+        for item in items {
+            out.push_str(&format!("- {}\n", Escape::Markdown.apply(item)));
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lint::assert_lints_clean;
+
+    #[test]
+    fn t_release_notes() {
+        let sections = parse(DEFAULT).unwrap();
+        assert!(sections.iter().all(|templates| templates.len() > 5));
+        assert_lints_clean("release notes", sections.iter().flatten());
+        assert!(parse("[added]\na\n[fixed]\nb\n").is_err());
+        assert!(parse("[Added]\na\n[FIXED]\nb\n[Known Issues]\nc\n").is_ok());
+
+        assert_eq!(split(7), [3, 2, 2]);
+        assert_eq!(split(2), [1, 1, 0]);
+
+        let parts = [
+            (Section::Added, vec!["A `--fast` flag".to_string()]),
+            (Section::Fixed, Vec::new()),
+            (Section::KnownIssues, vec!["Leap years".to_string()]),
+        ];
+        assert_eq!(
+            render("1.2.0", &parts),
+            "## 1.2.0\n\n### Added\n\n- A \\`--fast\\` flag\n\n### Known Issues\n\n- Leap years"
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+
+/// Lowercases a word or phrase and separates its words with single spaces, so matches can
+/// be looked up however they were spaced.
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Word characters for the boundaries a match must sit between.
+fn is_word(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Whitespace that may separate the words of a matched phrase.
+fn is_space(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\x0b' | '\x0c' | '\r' | ' ')
+}
+
+/// Whether byte `at` of `text` lies between a word character and something else.
+fn at_boundary(text: &str, at: usize) -> bool {
+    let before = text[..at].chars().next_back().is_some_and(is_word);
+    let after = text[at..].chars().next().is_some_and(is_word);
+    before != after
+}
+
+/// A word or phrase to replace, split into its words.
+#[derive(Debug)]
+struct Phrase {
+    words: Vec<String>,
+    /// Whether the match must start, or end, at a word boundary: when the phrase starts, or
+    /// ends, with a letter, digit or underscore.
+    bounded: (bool, bool),
+}
+
+impl Phrase {
+    fn new(from: &str) -> Self {
+        let word_char = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+        Phrase {
+            words: from.split_whitespace().map(String::from).collect(),
+            bounded: (
+                word_char(from.chars().next()),
+                word_char(from.chars().last()),
+            ),
+        }
+    }
+
+    /// The end of a match starting at byte `at` of `text`, ignoring ASCII case and with
+    /// any run of whitespace between the words.
+    fn match_at(&self, text: &str, at: usize) -> Option<usize> {
+        if self.bounded.0 && !at_boundary(text, at) {
+            return None;
+        }
+        let mut end = at;
+        for (i, word) in self.words.iter().enumerate() {
+            if i > 0 {
+                let rest = &text[end..];
+                let space = rest.len() - rest.trim_start_matches(is_space).len();
+                if space == 0 {
+                    return None;
+                }
+                end += space;
+            }
+            let candidate = text.get(end..end + word.len())?;
+            if !candidate.eq_ignore_ascii_case(word) {
+                return None;
+            }
+            end += word.len();
+        }
+        (!self.bounded.1 || at_boundary(text, end)).then_some(end)
+    }
+}
+
+/// Words and phrases swapped for others in every final message, from the config file's
+/// `[replace]` table.
+#[derive(Debug)]
+pub struct Replacements {
+    /// Longest first, so that the longest phrase matching at a position wins
+    phrases: Vec<Phrase>,
+    with: HashMap<String, String>,
+}
+
+impl Replacements {
+    /// Builds the replacements from `from = to` pairs. Matching ignores case and how the
+    /// words of a phrase are spaced, and only considers whole words.
+    pub fn new(map: &BTreeMap<String, String>) -> Option<Self> {
+        let mut from: Vec<&str> = map
+            .keys()
+            .map(|k| k.trim())
+            .filter(|k| !k.is_empty())
+            .collect();
+        if from.is_empty() {
+            return None;
+        }
+        // Longer phrases first, so "legacy billing" wins over "legacy"
+        from.sort_by_key(|k| std::cmp::Reverse(k.len()));
+        let phrases = from.into_iter().map(Phrase::new).collect();
+        let with = map
+            .iter()
+            .map(|(from, to)| (normalize(from), to.clone()))
+            .collect();
+        Some(Replacements { phrases, with })
+    }
+
+    /// Replaces every match in `text`, keeping a shouted or capitalized match that way.
+    pub fn apply<'t>(&self, text: &'t str) -> Cow<'t, str> {
+        let mut replaced = String::new();
+        let (mut copied, mut at) = (0, 0);
+        while at < text.len() {
+            let end = self.phrases.iter().find_map(|p| p.match_at(text, at));
+            match end {
+                Some(end) if end > at => {
+                    let found = &text[at..end];
+                    let to = self
+                        .with
+                        .get(&normalize(found))
+                        .map_or(found, String::as_str);
+                    replaced.push_str(&text[copied..at]);
+                    replaced.push_str(&match_case(found, to));
+                    (copied, at) = (end, end);
+                }
+                _ => at += text[at..].chars().next().map_or(1, char::len_utf8),
+            }
+        }
+        if copied == 0 {
+            return Cow::Borrowed(text);
+        }
+        replaced.push_str(&text[copied..]);
+        Cow::Owned(replaced)
+    }
+}
+
+/// `to` in the case of `found`: all capitals if it was shouted, capitalized if it was.
+fn match_case(found: &str, to: &str) -> String {
+    let letters = || found.chars().filter(|c| c.is_alphabetic());
+    if letters().count() > 1 && letters().all(char::is_uppercase) {
+        return to.to_uppercase();
+    }
+    let mut chars = to.chars();
+    match (found.chars().next(), chars.next()) {
+        (Some(f), Some(t)) if f.is_uppercase() => t.to_uppercase().chain(chars).collect(),
+        _ => to.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_replacements() {
+        let map = BTreeMap::from([
+            ("damn".to_string(), "darn".to_string()),
+            ("project  hydra".to_string(), "the platform".to_string()),
+            ("hydra".to_string(), "the service".to_string()),
+            ("c++".to_string(), "rust".to_string()),
+        ]);
+        let replacements = Replacements::new(&map).unwrap();
+        assert_eq!(
+            replacements.apply("Damn, Project Hydra broke hydra. DAMN! damnit"),
+            "Darn, The platform broke the service. DARN! damnit"
+        );
+        assert_eq!(
+            replacements.apply("Ported c++ to C++"),
+            "Ported rust to Rust"
+        );
+        assert!(matches!(replacements.apply("all fine"), Cow::Borrowed(_)));
+        assert!(Replacements::new(&BTreeMap::new()).is_none());
+    }
+}
@@ -0,0 +1,290 @@
+use crate::transform::Transform;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use tracing::debug;
+
+/// Error codes from the JSON-RPC 2.0 specification.
+pub const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// A request that was understood but failed, such as a blocked corpus.
+const SERVER_ERROR: i64 = -32000;
+
+/// Corpus `generate` draws from when the request doesn't name one.
+pub const DEFAULT_CORPUS: &str = "commit-messages";
+
+/// A generated message as `generate` returns it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Message {
+    pub message: String,
+    pub name: String,
+    pub template: String,
+}
+
+/// A corpus `generate` can draw from, as `list-packs` describes it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Pack {
+    pub name: &'static str,
+    pub templates: usize,
+}
+
+/// Settings `set-config` can change for the rest of the session.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Settings {
+    pub unique: Option<bool>,
+    pub transform: Option<Vec<Transform>>,
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GenerateParams {
+    #[serde(default = "one")]
+    count: usize,
+    #[serde(default = "default_corpus")]
+    corpus: String,
+}
+
+fn one() -> usize {
+    1
+}
+
+fn default_corpus() -> String {
+    DEFAULT_CORPUS.to_string()
+}
+
+/// What the server does for each method, kept apart from the protocol.
+pub trait Methods {
+    /// Generates `count` messages from the corpus named `corpus`.
+    fn generate(&mut self, corpus: &str, count: usize) -> Result<Vec<Message>>;
+    /// The corpora `generate` can draw from.
+    fn packs(&self) -> Vec<Pack>;
+    /// Changes settings for the requests that follow.
+    fn set_config(&mut self, settings: Settings) -> Result<()>;
+}
+
+/// A JSON-RPC error object.
+pub struct Error {
+    code: i64,
+    message: String,
+}
+
+impl Error {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Error {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Calls the method a request names with its params.
+fn dispatch<M: Methods>(methods: &mut M, method: &str, params: Value) -> Result<Value, Error> {
+    let params = match params {
+        Value::Null => Value::Object(Default::default()),
+        params => params,
+    };
+    let invalid = |e: serde_json::Error| Error::new(INVALID_PARAMS, e.to_string());
+    let failed = |e: anyhow::Error| Error::new(SERVER_ERROR, format!("{:#}", e));
+    match method {
+        "generate" => {
+            let p: GenerateParams = serde_json::from_value(params).map_err(invalid)?;
+            let messages = methods.generate(&p.corpus, p.count).map_err(failed)?;
+            Ok(json!(messages))
+        }
+        "list-packs" => Ok(json!(methods.packs())),
+        "set-config" => {
+            let settings: Settings = serde_json::from_value(params).map_err(invalid)?;
+            methods.set_config(settings).map_err(failed)?;
+            Ok(json!(true))
+        }
+        other => Err(Error::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method `{}`", other),
+        )),
+    }
+}
+
+/// Handles one line of input, returning the response to write, if any. Notifications,
+/// requests without an `id`, get none.
+fn handle<M: Methods>(methods: &mut M, line: &str) -> Option<Value> {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => {
+            return Some(response(
+                Value::Null,
+                Err(Error::new(PARSE_ERROR, e.to_string())),
+            ))
+        }
+    };
+    let id = request.get("id").cloned();
+    let method = request.get("method").and_then(Value::as_str);
+    let result = match (request.get("jsonrpc").and_then(Value::as_str), method) {
+        (Some("2.0"), Some(method)) => {
+            debug!("RPC request: {}", method);
+            let params = request.get("params").cloned().unwrap_or(Value::Null);
+            dispatch(methods, method, params)
+        }
+        _ => Err(Error::new(
+            INVALID_REQUEST,
+            "Not a JSON-RPC 2.0 request with a method",
+        )),
+    };
+    id.map(|id| response(id, result))
+}
+
+/// A response to the request with `id`.
+pub fn response(id: Value, result: Result<Value, Error>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message },
+        }),
+    }
+}
+
+/// Serves requests, one JSON object per line of `input`, answering each on a line of
+/// `output`, until `input` ends.
+pub fn serve<M, R, W>(methods: &mut M, input: R, mut output: W) -> Result<()>
+where
+    M: Methods,
+    R: BufRead,
+    W: Write,
+{
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle(methods, &line) {
+            writeln!(output, "{}", response)?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Default)]
+    struct Fake {
+        settings: Vec<Settings>,
+    }
+
+    impl Methods for Fake {
+        fn generate(&mut self, corpus: &str, count: usize) -> Result<Vec<Message>> {
+            if corpus != DEFAULT_CORPUS {
+                anyhow::bail!("No corpus named {}", corpus);
+            }
+            Ok(vec![
+                Message {
+                    message: "fixed it".to_string(),
+                    name: "Ann".to_string(),
+                    template: "fixed it".to_string(),
+                };
+                count
+            ])
+        }
+
+        fn packs(&self) -> Vec<Pack> {
+            vec![Pack {
+                name: DEFAULT_CORPUS,
+                templates: 1,
+            }]
+        }
+
+        fn set_config(&mut self, settings: Settings) -> Result<()> {
+            self.settings.push(settings);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn t_rpc_server() {
+        let input = [
+            r#"{"jsonrpc":"2.0","id":1,"method":"generate","params":{"count":2}}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"generate","params":{"corpus":"nope"}}"#,
+            r#"{"jsonrpc":"2.0","id":"a","method":"list-packs"}"#,
+            r#"{"jsonrpc":"2.0","method":"set-config","params":{"unique":true}}"#,
+            r#"{"jsonrpc":"2.0","id":3,"method":"set-config","params":{"spice":"mild"}}"#,
+            r#"{"jsonrpc":"2.0","id":4,"method":"explode"}"#,
+            "not json",
+        ]
+        .join("\n");
+        let mut fake = Fake::default();
+        let mut output = Vec::new();
+        serve(&mut fake, input.as_bytes(), &mut output).unwrap();
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 6, "the notification gets no response");
+        assert_eq!(responses[0]["result"][1]["message"], "fixed it");
+        assert_eq!(responses[1]["error"]["code"], SERVER_ERROR);
+        assert_eq!(responses[2]["id"], "a");
+        assert_eq!(responses[2]["result"][0]["name"], DEFAULT_CORPUS);
+        assert_eq!(responses[3]["error"]["code"], INVALID_PARAMS);
+        assert_eq!(responses[4]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(responses[5]["error"]["code"], PARSE_ERROR);
+        assert_eq!(fake.settings[0].unique, Some(true));
+    }
+
+    #[test]
+    fn t_malformed_requests() {
+        let mut fake = Fake::default();
+        let mut code = |line: &str| handle(&mut fake, line).map(|r| r["error"]["code"].clone());
+        assert_eq!(
+            code("{\"jsonrpc\":\"2.0\",\"id\":1"),
+            Some(json!(PARSE_ERROR))
+        );
+        assert_eq!(code("[1, 2]"), None, "no id, so no response");
+        assert_eq!(
+            code(r#"{"id":1,"method":"list-packs"}"#),
+            Some(json!(INVALID_REQUEST))
+        );
+        assert_eq!(
+            code(r#"{"jsonrpc":"1.0","id":1,"method":"list-packs"}"#),
+            Some(json!(INVALID_REQUEST))
+        );
+        assert_eq!(
+            code(r#"{"jsonrpc":"2.0","id":1}"#),
+            Some(json!(INVALID_REQUEST))
+        );
+        assert_eq!(
+            code(r#"{"jsonrpc":"2.0","id":1,"method":7}"#),
+            Some(json!(INVALID_REQUEST))
+        );
+        assert_eq!(
+            code(r#"{"jsonrpc":"2.0","id":1,"method":"generate","params":"three"}"#),
+            Some(json!(INVALID_PARAMS))
+        );
+        assert_eq!(
+            code(r#"{"jsonrpc":"2.0","id":1,"method":"generate","params":{"count":"two"}}"#),
+            Some(json!(INVALID_PARAMS))
+        );
+        assert_eq!(
+            code(r#"{"jsonrpc":"2.0","id":1,"method":"set-config","params":{"seed":-1}}"#),
+            Some(json!(INVALID_PARAMS))
+        );
+        assert_eq!(code(r#"{"jsonrpc":"2.0","method":"explode"}"#), None);
+
+        let response = handle(&mut fake, r#"{"jsonrpc":"2.0","id":null,"method":"x"}"#).unwrap();
+        assert_eq!(response["id"], Value::Null);
+        assert_eq!(response["error"]["message"], "Unknown method `x`");
+        let response = handle(&mut fake, "}").unwrap();
+        assert_eq!(
+            response["id"],
+            Value::Null,
+            "a parse error has no id to echo"
+        );
+    }
+}
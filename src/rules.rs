@@ -0,0 +1,95 @@
+use crate::blocklist::Blocklist;
+#[cfg(not(feature = "regex"))]
+use crate::disabled::regex::Regex;
+use crate::text;
+use anyhow::{Context, Result};
+#[cfg(feature = "regex")]
+use regex_lite::Regex;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use tracing::info;
+
+/// The rules file as written, before the prefix is compiled.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+struct RawRules {
+    max_length: Option<usize>,
+    min_length: Option<usize>,
+    prefix: Option<String>,
+    forbidden_words: Vec<String>,
+}
+
+/// Constraints every printed message must satisfy, read from a TOML rules file.
+#[derive(Debug)]
+pub struct Rules {
+    max_length: Option<usize>,
+    min_length: Option<usize>,
+    prefix: Option<Regex>,
+    forbidden_words: Blocklist,
+}
+
+impl Rules {
+    pub fn parse(content: &str) -> Result<Self> {
+        let raw: RawRules = toml::from_str(content)?;
+        let prefix = raw
+            .prefix
+            .map(|p| Regex::new(&format!("^(?:{})", p)))
+            .transpose()
+            .context("Invalid prefix regex")?;
+        Ok(Rules {
+            max_length: raw.max_length,
+            min_length: raw.min_length,
+            prefix,
+            forbidden_words: Blocklist::parse(&raw.forbidden_words.join("\n")),
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read rules file: {:?}", path))?;
+        let rules = Self::parse(&content)
+            .with_context(|| format!("Failed to parse rules file: {:?}", path))?;
+        info!("Loaded rules from {:?}", path);
+        Ok(rules)
+    }
+
+    /// Returns a description of every rule the message breaks.
+    pub fn violations(&self, message: &str) -> Vec<String> {
+        let mut violations = Vec::new();
+        let len = text::length(message);
+        if let Some(max) = self.max_length.filter(|&max| len > max) {
+            violations.push(format!("max-length: {} > {}", len, max));
+        }
+        if let Some(min) = self.min_length.filter(|&min| len < min) {
+            violations.push(format!("min-length: {} < {}", len, min));
+        }
+        if let Some(prefix) = self.prefix.as_ref().filter(|p| !p.is_match(message)) {
+            violations.push(format!("prefix: does not start with `{}`", prefix.as_str()));
+        }
+        if !self.forbidden_words.allows(message) {
+            violations.push("forbidden-words: contains a forbidden word".to_string());
+        }
+        violations
+    }
+}
+
+#[cfg(all(test, feature = "regex"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_rules_violations() {
+        let rules = Rules::parse(
+            "max-length = 20\nmin-length = 5\nprefix = \"[A-Z]+-[0-9]+ \"\nforbidden-words = [\"yolo\"]\n",
+        )
+        .unwrap();
+        assert!(rules.violations("WTC-12 fix it").is_empty());
+        assert_eq!(
+            rules.violations("WTC-1 yolo"),
+            vec!["forbidden-words: contains a forbidden word"]
+        );
+        assert_eq!(rules.violations("oops").len(), 2);
+        assert!(Rules::parse("max-lenght = 3").is_err());
+    }
+}
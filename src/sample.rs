@@ -0,0 +1,79 @@
+use rand::Rng;
+
+/// A uniform random sample of up to `capacity` items from a stream of unknown length,
+/// drawn in one pass without keeping the rest of the stream (reservoir sampling).
+#[derive(Debug)]
+pub struct Reservoir<T> {
+    items: Vec<T>,
+    capacity: usize,
+    seen: usize,
+}
+
+impl<T> Reservoir<T> {
+    pub fn new(capacity: usize) -> Self {
+        Reservoir {
+            items: Vec::new(),
+            capacity,
+            seen: 0,
+        }
+    }
+
+    /// Offers the next item of the stream, which replaces a random kept item with
+    /// probability `capacity / seen` once the reservoir is full.
+    pub fn push<R>(&mut self, item: T, rng: &mut R)
+    where
+        R: Rng + ?Sized,
+    {
+        self.seen += 1;
+        if self.items.len() < self.capacity {
+            self.items.push(item);
+        } else {
+            let slot = rng.random_range(0..self.seen);
+            if slot < self.capacity {
+                self.items[slot] = item;
+            }
+        }
+    }
+
+    /// How many items have been offered.
+    pub fn seen(&self) -> usize {
+        self.seen
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.items
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_reservoir_is_uniform() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut counts = [0; 10];
+        for _ in 0..10_000 {
+            let mut reservoir = Reservoir::new(2);
+            for i in 0..10 {
+                reservoir.push(i, &mut rng);
+            }
+            assert_eq!(reservoir.seen(), 10);
+            for i in reservoir.into_vec() {
+                counts[i] += 1;
+            }
+        }
+        // Each item is kept 2,000 times on average
+        assert!(
+            counts.iter().all(|&c| (1_800..=2_200).contains(&c)),
+            "{:?}",
+            counts
+        );
+
+        let mut short = Reservoir::new(5);
+        short.push("only", &mut rng);
+        assert_eq!(short.into_vec(), ["only"]);
+    }
+}
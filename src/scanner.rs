@@ -0,0 +1,116 @@
+//! A hand-written scanner for the placeholder grammar, for builds without the `regex`
+//! feature. It finds exactly what the patterns in `lint` do.
+
+use crate::lint;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// The first start of a placeholder or directive at or after byte `at`, trying the
+/// starts `lint` gives in the order the alternation there does.
+pub fn next_start(text: &str, at: usize) -> Option<Range<usize>> {
+    static STARTS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    let starts = STARTS.get_or_init(lint::starts);
+    let bytes = text.as_bytes();
+    (at..text.len())
+        .filter(|&i| bytes[i] == b'X' || bytes[i] == b'{')
+        .find_map(|i| {
+            let start = starts.iter().find(|s| text[i..].starts_with(*s))?;
+            Some(i..i + start.len())
+        })
+}
+
+/// The spec of a well-formed number placeholder at the start of `rest`: what lies
+/// between `XNUM` and the closing `X`, made of digits, lowercase letters, `,`, `:` and
+/// `-`.
+pub fn number_spec(rest: &str) -> Option<&str> {
+    let after = rest.strip_prefix("XNUM")?;
+    let len = after
+        .bytes()
+        .take_while(|&b| b.is_ascii_digit() || b.is_ascii_lowercase() || b",:-".contains(&b))
+        .count();
+    after[len..].starts_with('X').then(|| &after[..len])
+}
+
+/// The length of the `X` and capital letters at the start of `rest`.
+pub fn junk_len(rest: &str) -> Option<usize> {
+    let after = rest.strip_prefix('X')?;
+    Some(1 + after.bytes().take_while(u8::is_ascii_uppercase).count())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// What each function finds in a few hand-picked templates, for both scanners.
+    const STARTS_AT: [(&str, usize, Option<Range<usize>>); 11] = [
+        ("Fixed XNUM1,5X bugs", 0, Some(6..10)),
+        ("Fixed XNUM1,5X bugs", 7, None),
+        ("XUPPERNAMEX was here", 0, Some(0..9)),
+        ("XX XNAMEX", 0, Some(3..7)),
+        ("Xylophone {{n}}", 0, Some(10..12)),
+        ("no {{ closing", 0, Some(3..5)),
+        ("no {{ closing", 4, None),
+        ("café ☕ XIP6X", 0, Some(10..13)),
+        ("日本XSHORTUUIDX", 6, Some(6..15)),
+        ("as they say, XQUOTEX", 0, Some(13..18)),
+        ("", 0, None),
+    ];
+
+    #[test]
+    fn t_next_start() {
+        for (template, at, expected) in STARTS_AT {
+            assert_eq!(
+                next_start(template, at),
+                expected,
+                "{:?} at {}",
+                template,
+                at
+            );
+        }
+        // A start may begin right at `at`, and multibyte text before it is skipped whole
+        let text = "ünïcödé XNAMEX";
+        let at = text.find('X').unwrap();
+        assert_eq!(next_start(text, at), Some(at..at + 4));
+    }
+
+    #[test]
+    fn t_number_spec() {
+        assert_eq!(number_spec("XNUMX"), Some(""));
+        assert_eq!(number_spec("XNUM-5,5:logX bugs"), Some("-5,5:log"));
+        // Malformed: no closing X, capitals or other symbols inside, or not a number at all
+        assert_eq!(number_spec("XNUM1,5"), None);
+        assert_eq!(number_spec("XNUM1,5 X"), None);
+        assert_eq!(number_spec("XNUM1.5X"), None);
+        assert_eq!(number_spec("XNUMBERX"), None);
+        assert_eq!(number_spec("XNUM5é X"), None);
+        assert_eq!(number_spec("XNAMEX"), None);
+    }
+
+    #[test]
+    fn t_junk_len() {
+        assert_eq!(junk_len("XNAMX's"), Some(5));
+        assert_eq!(junk_len("X"), Some(1));
+        assert_eq!(junk_len("XÉTÉ"), Some(1));
+        assert_eq!(junk_len("{{"), None);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn t_same_as_regex() {
+        use crate::lint::matchers;
+        for (template, at, _) in STARTS_AT {
+            assert_eq!(next_start(template, at), matchers::next_start(template, at));
+            let rest = &template[at..];
+            assert_eq!(number_spec(rest), matchers::number_spec(rest));
+            assert_eq!(junk_len(rest), matchers::junk_len(rest));
+        }
+        for (placeholder, ..) in lint::PLACEHOLDERS {
+            let template = format!("fixed {}", placeholder);
+            assert_eq!(next_start(&template, 0), matchers::next_start(&template, 0));
+        }
+        for rest in ["XNUM1,5", "XNUM1.5X", "XNUMBERX", "XNAMX's", "XÉTÉ", "{{n"] {
+            assert_eq!(number_spec(rest), matchers::number_spec(rest), "{:?}", rest);
+            assert_eq!(junk_len(rest), matchers::junk_len(rest), "{:?}", rest);
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+use mlua::{Function, Lua, LuaOptions, StdLib, Value};
+use std::fs;
+use std::path::Path;
+use tracing::debug;
+
+/// Name of the global function a post-processing script must define.
+const HOOK_FUNCTION: &str = "transform";
+
+/// Functions of the base library that read or run files, removed along with the `os`, `io`
+/// and `package` libraries, so a script can only work on the message it is given.
+const UNSAFE_GLOBALS: [&str; 2] = ["dofile", "loadfile"];
+
+/// A user-supplied Lua script that post-processes generated messages.
+///
+/// The script must define a global `transform(message, ctx)` function, where `ctx`
+/// is a table with the `name` and `template` that produced the message. Its return
+/// value decides what happens to the message:
+///
+/// - a string replaces the message
+/// - `nil` (or no return) keeps the message unchanged
+/// - `false` vetoes the message so a new one gets generated
+pub struct Script {
+    lua: Lua,
+}
+
+impl Script {
+    /// Loads and runs a script file so its `transform` function is defined.
+    pub fn load(path: &Path) -> Result<Self> {
+        debug!("Loading script from: {:?}", path);
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read script file: {:?}", path))?;
+        Self::from_source(&source, &path.display().to_string())
+    }
+
+    /// Builds a script from Lua source, using `chunk_name` in error messages.
+    pub fn from_source(source: &str, chunk_name: &str) -> Result<Self> {
+        let lua = Lua::new_with(
+            StdLib::STRING | StdLib::TABLE | StdLib::MATH | StdLib::UTF8,
+            LuaOptions::default(),
+        )?;
+        for name in UNSAFE_GLOBALS {
+            lua.globals().set(name, Value::Nil)?;
+        }
+        lua.load(source)
+            .set_name(chunk_name)
+            .exec()
+            .with_context(|| format!("Failed to run script {}", chunk_name))?;
+
+        match lua.globals().get::<Value>(HOOK_FUNCTION) {
+            Ok(Value::Function(_)) => Ok(Script { lua }),
+            _ => anyhow::bail!(
+                "Script {} does not define a global `{}` function",
+                chunk_name,
+                HOOK_FUNCTION
+            ),
+        }
+    }
+
+    /// Runs the hook on a message.
+    ///
+    /// Returns `Ok(None)` when the script vetoed the message.
+    pub fn apply(&self, message: &str, name: &str, template: &str) -> Result<Option<String>> {
+        let hook: Function = self.lua.globals().get(HOOK_FUNCTION)?;
+
+        let ctx = self.lua.create_table()?;
+        ctx.set("name", name)?;
+        ctx.set("template", template)?;
+
+        let result: Value = hook
+            .call((message, ctx))
+            .context("Script `transform` function failed")?;
+
+        match result {
+            Value::Nil => Ok(Some(message.to_string())),
+            Value::Boolean(false) => Ok(None),
+            Value::String(s) => Ok(Some(s.to_str()?.to_string())),
+            other => anyhow::bail!(
+                "Script `transform` must return a string, nil or false, got {}",
+                other.type_name()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_script_transforms_message() {
+        let script = Script::from_source(
+            "function transform(msg, ctx) return '[' .. ctx.name .. '] ' .. msg end",
+            "test",
+        )
+        .unwrap();
+        assert_eq!(
+            script.apply("fixed it", "Alice", "fixed it").unwrap(),
+            Some("[Alice] fixed it".to_string())
+        );
+    }
+
+    #[test]
+    fn t_script_nil_keeps_message() {
+        let script = Script::from_source("function transform(msg) end", "test").unwrap();
+        assert_eq!(
+            script.apply("fixed it", "Alice", "fixed it").unwrap(),
+            Some("fixed it".to_string())
+        );
+    }
+
+    #[test]
+    fn t_script_false_vetoes_message() {
+        let script = Script::from_source(
+            "function transform(msg) if msg:find('bug') then return false end end",
+            "test",
+        )
+        .unwrap();
+        assert_eq!(script.apply("fixed a bug", "Alice", "").unwrap(), None);
+        assert!(script.apply("fixed it", "Alice", "").unwrap().is_some());
+    }
+
+    #[test]
+    fn t_script_has_no_os_or_io() {
+        let script = Script::from_source(
+            "function transform(msg) return tostring(os == nil and io == nil and \
+             require == nil and dofile == nil and loadfile == nil) end",
+            "test",
+        )
+        .unwrap();
+        assert_eq!(script.apply("", "", "").unwrap(), Some("true".to_string()));
+        let escape = "function transform(msg) os.execute('touch /tmp/wtc-pwned') end";
+        let script = Script::from_source(escape, "test").unwrap();
+        assert!(script.apply("fixed it", "Alice", "").is_err());
+    }
+
+    #[test]
+    fn t_script_without_hook_is_rejected() {
+        assert!(Script::from_source("x = 1", "test").is_err());
+    }
+}
@@ -0,0 +1,53 @@
+use crate::error::{ErrorKind, WithKind};
+use anyhow::Result;
+
+/// Returns whether every query term occurs in the template, ignoring case.
+pub fn matches(template: &str, terms: &[String]) -> bool {
+    let template = template.to_lowercase();
+    terms
+        .iter()
+        .all(|term| template.contains(&term.to_lowercase()))
+}
+
+/// Returns the index and text of every template matching all query terms.
+fn search<'a>(commit_messages: &'a [String], terms: &[String]) -> Vec<(usize, &'a str)> {
+    commit_messages
+        .iter()
+        .enumerate()
+        .filter(|(_, template)| matches(template, terms))
+        .map(|(index, template)| (index, template.as_str()))
+        .collect()
+}
+
+/// Prints the templates matching every query term along with their indices.
+pub fn run(terms: &[String], commit_messages: &[String]) -> Result<()> {
+    let found = search(commit_messages, terms);
+    if found.is_empty() {
+        return Err(anyhow::anyhow!("No templates match `{}`", terms.join(" ")))
+            .kind(ErrorKind::EmptyPool);
+    }
+    let width = commit_messages.len().saturating_sub(1).to_string().len();
+    for (index, template) in found {
+        println!("{:>width$}  {}", index, template, width = width);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_search_requires_all_terms() {
+        let corpus = vec![
+            "Deployed to production on a Friday".to_string(),
+            "Friday afternoon fix".to_string(),
+            "production hotfix".to_string(),
+        ];
+        let terms = vec!["friday".to_string(), "PRODUCTION".to_string()];
+        assert_eq!(
+            search(&corpus, &terms),
+            vec![(0, "Deployed to production on a Friday")]
+        );
+    }
+}
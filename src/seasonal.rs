@@ -0,0 +1,120 @@
+use crate::date::Date;
+use clap::ValueEnum;
+use serde::Deserialize;
+use tracing::debug;
+
+/// Whether seasonal templates are mixed into the built-in corpus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Seasonal {
+    /// Mix in the templates of the season, if any
+    #[default]
+    On,
+    /// Only ever use the corpus as it is
+    Off,
+}
+
+/// A small corpus for part of the year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pack {
+    /// The last two weeks of October
+    Halloween,
+    /// The code freeze from December 15th to January 2nd
+    YearEnd,
+    /// Every Friday
+    FridayDeploy,
+}
+
+impl Pack {
+    pub const ALL: [Pack; 3] = [Pack::Halloween, Pack::YearEnd, Pack::FridayDeploy];
+
+    fn name(self) -> &'static str {
+        match self {
+            Pack::Halloween => "halloween",
+            Pack::YearEnd => "year-end",
+            Pack::FridayDeploy => "friday-deploy",
+        }
+    }
+
+    fn templates(self) -> &'static str {
+        match self {
+            Pack::Halloween => include_str!("seasonal/halloween.txt"),
+            Pack::YearEnd => include_str!("seasonal/year_end.txt"),
+            Pack::FridayDeploy => include_str!("seasonal/friday.txt"),
+        }
+    }
+
+    /// Whether the pack is in season on `date`.
+    pub fn in_season(self, date: Date) -> bool {
+        match self {
+            Pack::Halloween => date.between((10, 17), (10, 31)),
+            Pack::YearEnd => date.between((12, 15), (1, 2)),
+            Pack::FridayDeploy => date.weekday() == 4,
+        }
+    }
+}
+
+/// The templates of every pack in season on `date`.
+pub fn templates(date: Date) -> Vec<String> {
+    Pack::ALL
+        .into_iter()
+        .filter(|pack| pack.in_season(date))
+        .inspect(|pack| debug!("In season: {}", pack.name()))
+        .flat_map(|pack| pack.templates().lines())
+        .filter(|line| !line.trim().is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// How many times each of `seasonal` templates is put in a pool of `pool` others, so the
+/// season comes up in about one message in ten however large the corpus.
+pub fn weight(pool: usize, seasonal: usize) -> usize {
+    match seasonal {
+        0 => 0,
+        seasonal => (pool / 9 / seasonal).max(1),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lint::assert_lints_clean;
+
+    #[test]
+    fn t_seasonal_packs() {
+        for pack in Pack::ALL {
+            assert_lints_clean(pack.name(), pack.templates().lines());
+        }
+        // 2024-10-31 was a Thursday and 2024-11-01 a Friday
+        let halloween = Date::from_unix(1_730_332_800);
+        assert_eq!(
+            (halloween.year, halloween.month, halloween.day),
+            (2024, 10, 31)
+        );
+        assert_eq!(halloween.weekday(), 3);
+        assert_eq!(templates(halloween).len(), 10);
+        let friday = Date::from_unix(1_730_419_200);
+        assert!(Pack::FridayDeploy.in_season(friday));
+        assert!(!Pack::Halloween.in_season(friday));
+        assert!(Pack::YearEnd.in_season(Date::from_unix(1_735_776_000)));
+        assert!(Pack::YearEnd.in_season(Date::from_unix(1_734_652_800)));
+        assert!(templates(Date::from_unix(1_730_764_800)).is_empty());
+
+        assert_eq!(weight(700, 10), 7);
+        assert_eq!(weight(20, 10), 1);
+        assert_eq!(weight(700, 0), 0);
+    }
+
+    #[test]
+    fn t_season_of_the_local_day() {
+        use crate::date;
+        use jiff::tz::{self, TimeZone};
+        // 2024-11-01 02:30 UTC, a Friday, is still Thursday evening on Halloween at UTC-4
+        let secs = 1_730_428_200;
+        let (utc, _) = date::in_zone(secs, &TimeZone::UTC);
+        let (local, hour) = date::in_zone(secs, &TimeZone::fixed(tz::offset(-4)));
+        assert_eq!((local.month, local.day, hour), (10, 31, 22));
+        assert!(Pack::Halloween.in_season(local) && !Pack::FridayDeploy.in_season(local));
+        assert!(!Pack::Halloween.in_season(utc) && Pack::FridayDeploy.in_season(utc));
+    }
+}
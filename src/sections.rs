@@ -0,0 +1,53 @@
+use crate::error::WtcError;
+use anyhow::Result;
+
+/// Splits a templates file into the templates under each of the `keys` headers, such as
+/// `[today]`, in the order of `keys`. Headers ignore case. Blank lines and `#` comments are
+/// skipped. Every section needs at least one template; `what` names the file in the error.
+pub fn parse<const N: usize>(text: &str, keys: [&str; N], what: &str) -> Result<[Vec<String>; N]> {
+    let mut sections: [Vec<String>; N] = std::array::from_fn(|_| Vec::new());
+    let mut current = None;
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(key) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let section = keys.iter().position(|k| k.eq_ignore_ascii_case(key.trim()));
+            current = Some(section.ok_or_else(|| invalid(number, "is an unknown section"))?);
+            continue;
+        }
+        let section = current.ok_or_else(|| invalid(number, "comes before any section header"))?;
+        sections[section].push(line.to_string());
+    }
+    if let Some(empty) = keys.iter().zip(&sections).find(|(_, s)| s.is_empty()) {
+        anyhow::bail!("The {} templates have no [{}] section", what, empty.0);
+    }
+    Ok(sections)
+}
+
+/// Whether a file is split into `[section]`s, like the standup templates.
+pub fn is_sectioned(text: &str) -> bool {
+    text.lines().map(str::trim).any(|line| {
+        line.len() > 2 && line.starts_with('[') && line.ends_with(']') && !line.contains(' ')
+    })
+}
+
+/// The zero-based number and text of every template line of a file, which may be split
+/// into sections: lines that aren't blank, nor the headers and `#` comments of a file with
+/// sections.
+pub fn templates(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let sectioned = is_sectioned(text);
+    text.lines().enumerate().filter(move |(_, line)| {
+        let line = line.trim();
+        !line.is_empty() && !(sectioned && (line.starts_with('#') || line.starts_with('[')))
+    })
+}
+
+fn invalid(number: usize, problem: &'static str) -> anyhow::Error {
+    WtcError::InvalidTemplate {
+        line: number + 1,
+        problem,
+    }
+    .into()
+}
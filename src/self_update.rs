@@ -0,0 +1,285 @@
+use crate::http;
+use crate::lockfile;
+use crate::minisign::{self, PublicKey};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::env::{self, consts};
+use std::fs;
+use std::path::Path;
+use tracing::{debug, info};
+
+/// The release asset listing the SHA-256 of every other asset, as `sha256sum` writes it.
+const CHECKSUMS: &str = "SHA256SUMS";
+
+/// The release asset holding the minisign signature of `CHECKSUMS`.
+const SIGNATURE: &str = "SHA256SUMS.minisig";
+
+/// The minisign public key releases are signed with, pinned when the binary is built. A
+/// build without one can't update itself, since it couldn't tell a release from a forgery.
+const RELEASE_KEY: Option<&str> = option_env!("WTC_RELEASE_KEY");
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl Release {
+    fn asset(&self, name: &str) -> Result<&Asset> {
+        self.assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .with_context(|| format!("Release {} has no {} to download", self.tag_name, name))
+    }
+}
+
+/// The GitHub API address of the latest release of this repository.
+fn latest_url() -> String {
+    let repository = env!("CARGO_PKG_REPOSITORY");
+    let repository = repository.replacen("https://github.com/", "https://api.github.com/repos/", 1);
+    format!("{}/releases/latest", repository)
+}
+
+/// The name of the binary built for this platform, such as
+/// `whatthecommitcli-x86_64-linux` or `whatthecommitcli-x86_64-windows.exe`.
+fn asset_name() -> String {
+    format!(
+        "{}-{}-{}{}",
+        env!("CARGO_BIN_NAME"),
+        consts::ARCH,
+        consts::OS,
+        consts::EXE_SUFFIX
+    )
+}
+
+/// Parses a version like `1.2.3` or a tag like `v1.2.3`, ignoring any pre-release suffix.
+fn version(text: &str) -> Option<(u64, u64, u64)> {
+    let text = text.strip_prefix('v').unwrap_or(text);
+    let core = text.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse().ok());
+    let version = (parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(version)
+}
+
+/// The checksum `sums` gives for the file `name`.
+fn checksum_for<'a>(sums: &'a str, name: &str) -> Option<&'a str> {
+    sums.lines().find_map(|line| {
+        let (sum, file) = line.split_once(char::is_whitespace)?;
+        // `*` marks files sha256sum read in binary mode
+        let file = file.trim_start().trim_start_matches('*');
+        (file == name).then_some(sum)
+    })
+}
+
+/// Fails unless `sums` carry a minisign `signature` made with the release `key`, whose
+/// trusted comment names the release `tag` as `tag:TAG`. The comment is signed too, so
+/// the checksums of an older release can't be passed off as those of `tag`.
+fn verify_signature(sums: &[u8], signature: &str, key: &str, tag: &str) -> Result<()> {
+    let key: PublicKey = key
+        .parse()
+        .context("The pinned release key can't be read")?;
+    let comment = minisign::verify(sums, signature, &[key]).with_context(|| {
+        format!(
+            "{} isn't signed with the release key; not installing anything",
+            CHECKSUMS
+        )
+    })?;
+    let tagged = format!("tag:{}", tag);
+    if !comment.split_whitespace().any(|field| field == tagged) {
+        anyhow::bail!(
+            "{} is signed for another release than {} ({:?}); not installing anything",
+            CHECKSUMS,
+            tag,
+            comment
+        );
+    }
+    Ok(())
+}
+
+/// Fails unless `bytes` have the SHA-256 `expected`.
+fn verify(bytes: &[u8], expected: &str) -> Result<()> {
+    let actual = lockfile::sha256(bytes);
+    if !actual.eq_ignore_ascii_case(expected) {
+        anyhow::bail!(
+            "The download's SHA-256 is {}, but the release lists {}; not installing it",
+            actual,
+            expected
+        );
+    }
+    Ok(())
+}
+
+/// Puts `bytes` in place of the executable at `exe`. The new binary is written next to
+/// it first, so a failed write leaves the old one working, then renamed over it, which is
+/// atomic on Unix. Windows won't replace a running executable, but will rename it, so
+/// there the old one is moved aside first and removed by the next update.
+fn replace(exe: &Path, bytes: &[u8]) -> Result<()> {
+    let file_name = exe
+        .file_name()
+        .context("The executable's path has no file name")?
+        .to_string_lossy();
+    let staged = exe.with_file_name(format!(".{}.new", file_name));
+    fs::write(&staged, bytes).with_context(|| format!("Failed to write {:?}", staged))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("Failed to make {:?} executable", staged))?;
+    }
+    #[cfg(windows)]
+    {
+        let old = exe.with_file_name(format!(".{}.old", file_name));
+        let _ = fs::remove_file(&old);
+        fs::rename(exe, &old).with_context(|| format!("Failed to move {:?} aside", exe))?;
+    }
+    fs::rename(&staged, exe).with_context(|| format!("Failed to replace {:?}", exe))?;
+    Ok(())
+}
+
+/// How the release tagged `latest` compares with this version, `current`.
+fn compare(latest: &str, current: &str) -> Ordering {
+    match (version(latest), version(current)) {
+        (Some(latest), Some(current)) => latest.cmp(&current),
+        _ if latest.trim_start_matches('v') == current => Ordering::Equal,
+        _ => Ordering::Greater,
+    }
+}
+
+/// Checks GitHub for a newer release and, unless `check` only asks whether there is one,
+/// downloads the binary for this platform, verifies it against the release's checksums,
+/// signed with the pinned `RELEASE_KEY`, and replaces the running executable with it.
+/// `force` reinstalls the latest release if it is this version, but never an older one.
+pub fn run(check: bool, force: bool, settings: &http::Settings) -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    let release: Release = serde_json::from_slice(&http::get(&latest_url(), settings)?)
+        .context("GitHub's description of the latest release couldn't be read")?;
+    debug!(
+        "Latest release is {}; this is {}",
+        release.tag_name, current
+    );
+    match compare(&release.tag_name, current) {
+        Ordering::Less => {
+            println!(
+                "{} is newer than the latest release, {}; not going back to it",
+                current, release.tag_name
+            );
+            return Ok(());
+        }
+        Ordering::Equal if !force => {
+            println!("{} is up to date", current);
+            return Ok(());
+        }
+        _ => {}
+    }
+    if check {
+        println!("{} is available; this is {}", release.tag_name, current);
+        return Ok(());
+    }
+
+    let key = RELEASE_KEY.context(
+        "This build has no release key to check updates with; update it the way it was installed",
+    )?;
+    let name = asset_name();
+    let asset = release.asset(&name)?;
+    let sums = http::get(&release.asset(CHECKSUMS)?.browser_download_url, settings)?;
+    let signature = http::get(&release.asset(SIGNATURE)?.browser_download_url, settings)?;
+    verify_signature(
+        &sums,
+        &String::from_utf8_lossy(&signature),
+        key,
+        &release.tag_name,
+    )?;
+    let sums = String::from_utf8_lossy(&sums);
+    let expected = checksum_for(&sums, &name).with_context(|| {
+        format!(
+            "{} of {} has no entry for {}",
+            CHECKSUMS, release.tag_name, name
+        )
+    })?;
+    let bytes = http::get(&asset.browser_download_url, settings)?;
+    verify(&bytes, expected)?;
+
+    let exe = env::current_exe()
+        .and_then(|exe| exe.canonicalize())
+        .context("Could not find the running executable")?;
+    replace(&exe, &bytes)?;
+    info!("Replaced {:?}", exe);
+    println!("Updated from {} to {}", current, release.tag_name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_release_checks() {
+        assert!(latest_url().ends_with("/repos/keongalvin/whatthecommit-cli/releases/latest"));
+        assert!(asset_name().starts_with("whatthecommitcli-"));
+        assert!(version("v0.10.0") > version("0.9.12"));
+        assert_eq!(version("1.2.3-rc.1"), Some((1, 2, 3)));
+        assert_eq!(version("1.2"), None);
+        assert_eq!(compare("v0.10.0", "0.9.12"), Ordering::Greater);
+        assert_eq!(compare("v0.9.12", "0.9.12"), Ordering::Equal);
+        // Not even `--force` goes back to an older release
+        assert_eq!(compare("v0.9.11", "0.9.12"), Ordering::Less);
+        assert_eq!(compare("nightly", "0.9.12"), Ordering::Greater);
+
+        let sums = "aaaa  whatthecommitcli-x86_64-linux\nbbbb *whatthecommitcli-aarch64-macos\n";
+        assert_eq!(
+            checksum_for(sums, "whatthecommitcli-aarch64-macos"),
+            Some("bbbb")
+        );
+        assert_eq!(
+            checksum_for(sums, "whatthecommitcli-x86_64-windows.exe"),
+            None
+        );
+        let abc = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert!(verify(b"abc", abc).is_ok());
+        assert!(verify(b"abd", abc).is_err());
+
+        let exe = env::temp_dir().join(format!("wtc-self-update-{}", std::process::id()));
+        fs::write(&exe, "old").unwrap();
+        replace(&exe, b"new").unwrap();
+        assert_eq!(fs::read_to_string(&exe).unwrap(), "new");
+        fs::remove_file(exe).unwrap();
+    }
+
+    #[test]
+    fn t_checksums_must_be_signed() {
+        use base64::engine::general_purpose::STANDARD as BASE64;
+        use base64::Engine;
+        use ring::signature::{Ed25519KeyPair, KeyPair};
+
+        let pair = Ed25519KeyPair::from_seed_unchecked(&[3; 32]).unwrap();
+        let id = [5; 8];
+        let key = BASE64.encode([b"Ed".as_slice(), &id, pair.public_key().as_ref()].concat());
+        let sums = b"aaaa  whatthecommitcli-x86_64-linux\n";
+        let comment = "timestamp:1760000000\tfile:SHA256SUMS\ttag:v1.2.0";
+        let signature = minisign::test::sign(&pair, id, sums, true, comment);
+        assert!(verify_signature(sums, &signature, &key, "v1.2.0").is_ok());
+        let other = b"bbbb  whatthecommitcli-x86_64-linux\n";
+        let err = verify_signature(other, &signature, &key, "v1.2.0");
+        assert!(err.unwrap_err().to_string().contains("isn't signed"));
+
+        // A signature made with another key, such as the one of whoever swapped the files
+        let forger = Ed25519KeyPair::from_seed_unchecked(&[4; 32]).unwrap();
+        let forged = minisign::test::sign(&forger, id, sums, true, comment);
+        assert!(verify_signature(sums, &forged, &key, "v1.2.0").is_err());
+        assert!(verify_signature(sums, &signature, "RWQ", "v1.2.0").is_err());
+
+        // The genuine checksums of an older release, passed off as the latest
+        let err = verify_signature(sums, &signature, &key, "v1.3.0").unwrap_err();
+        assert!(err.to_string().contains("another release"));
+        assert!(verify_signature(sums, &signature, &key, "v1.2").is_err());
+        let untagged = minisign::test::sign(&pair, id, sums, true, "timestamp:1760000000");
+        assert!(verify_signature(sums, &untagged, &key, "v1.2.0").is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::sections;
+use crate::template::Template;
+use anyhow::Result;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// The built-in templates, under `[bug]`, `[panic]`, `[blame]` and `[revert]` headers.
+pub const DEFAULT: &str = include_str!("series.txt");
+
+/// A stage of the story a series tells, in the order they happen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Bug,
+    Panic,
+    Blame,
+    Revert,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 4] = [Stage::Bug, Stage::Panic, Stage::Blame, Stage::Revert];
+
+    /// The header naming the stage in a templates file.
+    fn key(self) -> &'static str {
+        match self {
+            Stage::Bug => "bug",
+            Stage::Panic => "panic",
+            Stage::Blame => "blame",
+            Stage::Revert => "revert",
+        }
+    }
+}
+
+/// Splits a templates file into the templates of each stage, in the order of
+/// [`Stage::ALL`]. Blank lines and `#` comments are skipped.
+pub fn parse(text: &str) -> Result<[Vec<String>; 4]> {
+    sections::parse(text, Stage::ALL.map(Stage::key), "series")
+}
+
+/// The stage of each of `count` messages. The story always starts with the bug and, given
+/// two messages or more, ends with the revert; longer series spend longer in each stage.
+pub fn stages(count: usize) -> Vec<Stage> {
+    let last = Stage::ALL.len() - 1;
+    (0..count)
+        .map(|i| match count {
+            1 => Stage::ALL[0],
+            _ => Stage::ALL[i * last / (count - 1)],
+        })
+        .collect()
+}
+
+/// Generates `count` messages telling one story with the templates of each stage. They
+/// share `name`, and placeholders written the same way share their values.
+pub fn generate<R>(
+    stage_templates: &[Vec<String>; 4],
+    count: usize,
+    name: &str,
+    rng: &mut R,
+) -> Vec<String>
+where
+    R: Rng + ?Sized,
+{
+    let stages = stages(count);
+    let mut picked: Vec<&str> = Vec::with_capacity(count);
+    for stage in Stage::ALL {
+        let wanted = stages.iter().filter(|s| **s == stage).count();
+        // Different templates within a stage while there are enough of them
+        let mut templates: Vec<&str> = stage_templates[stage as usize]
+            .iter()
+            .map(String::as_str)
+            .collect();
+        templates.shuffle(rng);
+        picked.extend(templates.iter().cycle().take(wanted));
+    }
+    // One template, so the consistent values carry over from one message to the next
+    let joined = picked.join("\n");
+    Template::parse(&joined)
+        .render_consistent(name, rng)
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lint::assert_lints_clean;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_series_shares_values() {
+        use Stage::*;
+        assert_eq!(stages(1), [Bug]);
+        assert_eq!(stages(2), [Bug, Revert]);
+        assert_eq!(stages(4), [Bug, Panic, Blame, Revert]);
+        assert_eq!(stages(6), [Bug, Bug, Panic, Panic, Blame, Revert]);
+
+        let templates = parse(DEFAULT).unwrap();
+        assert_lints_clean("series", templates.iter().flatten());
+
+        let templates = [
+            vec!["broke XPATHX".to_string()],
+            vec!["XNUM1,1000000X things on fire".to_string()],
+            vec!["blame XNAMEX for XPATHX".to_string()],
+            vec!["revert XPATHX, XNUM1,1000000X things fixed".to_string()],
+        ];
+        let mut rng = StdRng::seed_from_u64(9);
+        let series = generate(&templates, 4, "Ann", &mut rng);
+        assert_eq!(series.len(), 4);
+        let path = series[0].strip_prefix("broke ").unwrap();
+        let number = series[1].strip_suffix(" things on fire").unwrap();
+        assert_eq!(series[2], format!("blame Ann for {}", path));
+        assert_eq!(
+            series[3],
+            format!("revert {}, {} things fixed", path, number)
+        );
+    }
+}
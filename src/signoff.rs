@@ -0,0 +1,78 @@
+use crate::coauthor;
+use crate::git;
+use anyhow::Result;
+use clap::ValueEnum;
+use rand::seq::IndexedRandom;
+use rand::Rng;
+use serde::Deserialize;
+
+/// Whose name a `Signed-off-by` trailer gives, for `--signoff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Signoff {
+    /// The identity git commits as, like `git commit --signoff`
+    Git,
+    /// A name from the names pool, with a made-up address, different for each message
+    Random,
+}
+
+impl Signoff {
+    /// Looks up what the trailers will be signed with, asking git once for its identity.
+    pub fn signer(self) -> Result<Signer> {
+        Ok(match self {
+            Signoff::Git => Signer::Identity(git::identity()?),
+            Signoff::Random => Signer::Names,
+        })
+    }
+}
+
+/// Who signs off each message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Signer {
+    /// "Name <email>", the same for every message
+    Identity(String),
+    /// A name drawn from the names pool for each message
+    Names,
+}
+
+impl Signer {
+    /// The `Signed-off-by` trailer for a message, or `None` with no names to draw from.
+    pub fn trailer<R>(&self, names: &[String], rng: &mut R) -> Option<String>
+    where
+        R: Rng + ?Sized,
+    {
+        let identity = match self {
+            Signer::Identity(identity) => identity.clone(),
+            Signer::Names => {
+                let names: Vec<&str> = (names.iter())
+                    .map(|name| name.trim())
+                    .filter(|name| !name.is_empty())
+                    .collect();
+                let name = names.choose(rng)?;
+                format!("{} <{}>", name, coauthor::email(name, rng))
+            }
+        };
+        Some(format!("Signed-off-by: {}", identity))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_signoff_trailer() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let names = ["Ann Lee", " "].map(String::from);
+        let signer = Signer::Identity("Bob <bob@example.com>".to_string());
+        assert_eq!(
+            signer.trailer(&names, &mut rng).as_deref(),
+            Some("Signed-off-by: Bob <bob@example.com>")
+        );
+        let trailer = Signer::Names.trailer(&names, &mut rng).unwrap();
+        assert!(trailer.starts_with("Signed-off-by: Ann Lee <ann.lee@example."));
+        assert_eq!(Signer::Names.trailer(&[], &mut rng), None);
+    }
+}
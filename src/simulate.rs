@@ -0,0 +1,318 @@
+use crate::corpus::Corpus;
+use crate::lint::{Directive, Distribution, NumberRange};
+use crate::template::{self, Node, Template};
+use crate::text;
+use crate::Generated;
+use anyhow::Result;
+use rand::Rng;
+use std::fmt::Write as _;
+
+/// Seed used without `--seed`, so a simulation can be compared with the one before it.
+pub const DEFAULT_SEED: u64 = 0;
+
+/// Templates listed in the selection histogram, the most picked first.
+const TOP_TEMPLATES: usize = 20;
+
+/// Most buckets the draws of a number range are sorted into.
+const BUCKETS: usize = 10;
+
+/// Width of the bar of the fullest bucket.
+const BAR_WIDTH: usize = 30;
+
+/// Width templates are cut to in the selection histogram.
+const TEMPLATE_WIDTH: usize = 40;
+
+/// How often a template was picked, against how often its share of the pool says it
+/// should be.
+#[derive(Debug, PartialEq)]
+struct Picked {
+    index: usize,
+    count: usize,
+    expected: f64,
+}
+
+/// The draws of one number range, by bucket: its label and how many draws fell in it.
+#[derive(Debug, PartialEq)]
+struct Histogram {
+    range: NumberRange,
+    buckets: Vec<(String, usize)>,
+}
+
+/// What a simulation saw.
+#[derive(Debug)]
+pub struct Report {
+    seed: u64,
+    messages: usize,
+    templates: usize,
+    pool: usize,
+    /// Every template in the pool, the most picked first.
+    picked: Vec<Picked>,
+    /// One per distinct number range in the pool's templates.
+    numbers: Vec<Histogram>,
+}
+
+/// Generates `messages` messages with `generate`, counting the templates picked, then
+/// draws as many numbers from each number range the pool's templates use.
+pub fn run<R, F>(
+    commit_messages: &Corpus,
+    pool: &[usize],
+    messages: usize,
+    seed: u64,
+    rng: &mut R,
+    mut generate: F,
+) -> Result<Report>
+where
+    R: Rng + ?Sized,
+    F: FnMut(&mut R) -> Result<Generated>,
+{
+    let mut counts = vec![0; commit_messages.len()];
+    let mut expected = vec![0.0; commit_messages.len()];
+    for &index in pool {
+        expected[index] += 1.0 / pool.len() as f64;
+    }
+    for _ in 0..messages {
+        let generated = generate(rng)?;
+        if let Some(count) = counts.get_mut(generated.template_index) {
+            *count += 1;
+        }
+    }
+    let mut picked: Vec<Picked> = (0..commit_messages.len())
+        .filter(|&index| expected[index] > 0.0)
+        .map(|index| Picked {
+            index,
+            count: counts[index],
+            expected: expected[index],
+        })
+        .collect();
+    picked.sort_by(|a, b| b.count.cmp(&a.count).then(a.index.cmp(&b.index)));
+
+    let mut numbers = Vec::new();
+    for range in ranges(commit_messages, pool)? {
+        numbers.push(histogram(range, messages, rng));
+    }
+    Ok(Report {
+        seed,
+        messages,
+        templates: commit_messages.len(),
+        pool: pool.len(),
+        picked,
+        numbers,
+    })
+}
+
+/// Adds the number ranges of `nodes`, and of the text of their directives, to `ranges`.
+fn collect_ranges(nodes: &[Node], ranges: &mut Vec<NumberRange>) {
+    for node in nodes {
+        let range = match node {
+            Node::Number(range) => *range,
+            Node::Directive(Directive::Set { range, .. }) => *range,
+            Node::Maybe { body, .. } | Node::Repeat { body, .. } => {
+                collect_ranges(body.nodes(), ranges);
+                continue;
+            }
+            _ => continue,
+        };
+        // Only how numbers are written differs between these, not which are drawn
+        let same = |other: &NumberRange| {
+            (other.first, other.last, other.step, other.distribution)
+                == (range.first, range.last, range.step, range.distribution)
+        };
+        if !ranges.iter().any(same) {
+            ranges.push(range);
+        }
+    }
+}
+
+/// The distinct number ranges the templates of `pool` use, in the order they're found.
+fn ranges(commit_messages: &Corpus, pool: &[usize]) -> Result<Vec<NumberRange>> {
+    let mut seen = vec![false; commit_messages.len()];
+    let mut ranges = Vec::new();
+    for &index in pool {
+        if std::mem::replace(&mut seen[index], true) {
+            continue;
+        }
+        let template = commit_messages.get(index)?;
+        collect_ranges(Template::parse(&template).nodes(), &mut ranges);
+    }
+    Ok(ranges)
+}
+
+/// Where each bucket of `range` starts, the first number above the last bucket last. Log
+/// ranges get a bucket per order of magnitude, others up to `BUCKETS` even ones.
+fn bucket_starts(range: &NumberRange) -> Vec<i128> {
+    let step = i128::from(range.step);
+    let values = (range.last - range.first) / step + 1;
+    let mut starts: Vec<i128> = match range.distribution {
+        Distribution::Log => std::iter::once(range.first)
+            .chain(
+                std::iter::successors(Some(1i128), |power| power.checked_mul(10))
+                    .filter(|&power| power > range.first && power <= range.last),
+            )
+            .collect(),
+        _ => {
+            let buckets = values.min(BUCKETS as i128);
+            (0..buckets)
+                .map(|b| range.first + b * values / buckets * step)
+                .collect()
+        }
+    };
+    starts.dedup();
+    starts.push(range.last + 1);
+    starts
+}
+
+/// Draws `draws` numbers from `range` the way templates do and sorts them into buckets.
+fn histogram<R>(range: NumberRange, draws: usize, rng: &mut R) -> Histogram
+where
+    R: Rng + ?Sized,
+{
+    let starts = bucket_starts(&range);
+    let mut counts = vec![0; starts.len() - 1];
+    for _ in 0..draws {
+        let number = template::generate_random_in_range(&range, rng);
+        let bucket = starts.partition_point(|&start| start <= number) - 1;
+        counts[bucket] += 1;
+    }
+    let buckets = starts
+        .windows(2)
+        .zip(counts)
+        .map(|(bounds, count)| {
+            // The last number of the bucket that the range can draw
+            let last = bounds[1] - 1 - (bounds[1] - 1 - range.first) % i128::from(range.step);
+            let label = match bounds[0] == last {
+                true => bounds[0].to_string(),
+                false => format!("{}..{}", bounds[0], last),
+            };
+            (label, count)
+        })
+        .collect();
+    Histogram { range, buckets }
+}
+
+/// A bar `count` long out of `most`, at most `BAR_WIDTH` wide.
+fn bar(count: usize, most: usize) -> String {
+    "█".repeat((count * BAR_WIDTH).div_ceil(most.max(1)))
+}
+
+impl Report {
+    /// Renders the histograms as aligned lines for people.
+    pub fn render(&self, commit_messages: &Corpus) -> Result<String> {
+        let share = |count: usize| 100.0 * count as f64 / self.messages.max(1) as f64;
+        let mut out = format!(
+            "seed {}, {} messages from {} templates ({} in the pool)\n\n\
+             {:>6}{:>9}{:>10}\n",
+            self.seed, self.messages, self.templates, self.pool, "index", "picked", "expected"
+        );
+        let most = self.picked.first().map_or(0, |p| p.count);
+        let shown = (self.picked.iter().take(TOP_TEMPLATES))
+            .map(|picked| Ok((picked, commit_messages.get(picked.index)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let width = (shown.iter())
+            .map(|(_, template)| text::truncate(template, TEMPLATE_WIDTH).chars().count())
+            .max()
+            .unwrap_or(0);
+        for (picked, template) in shown {
+            let line = format!(
+                "{:>6}{:>8.2}%{:>9.2}%  {:<width$}  {}",
+                picked.index,
+                share(picked.count),
+                100.0 * picked.expected,
+                text::truncate(&template, TEMPLATE_WIDTH),
+                bar(picked.count, most),
+                width = width
+            );
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        let never = self.picked.iter().filter(|p| p.count == 0).count();
+        let _ = writeln!(
+            out,
+            "{} of {} templates in the pool never picked",
+            never,
+            self.picked.len()
+        );
+
+        for histogram in &self.numbers {
+            let _ = write!(out, "\n{}\n", histogram.range);
+            let most = histogram.buckets.iter().map(|(_, c)| *c).max().unwrap_or(0);
+            let width = histogram.buckets.iter().map(|(l, _)| l.len()).max();
+            for (label, count) in &histogram.buckets {
+                let _ = writeln!(
+                    out,
+                    "  {:<width$}{:>8.2}%  {}",
+                    label,
+                    share(*count),
+                    bar(*count, most),
+                    width = width.unwrap_or(0)
+                );
+            }
+        }
+        out.pop();
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lint::parse_number_range;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_simulate_histograms() {
+        let range = |spec| parse_number_range(spec).unwrap();
+        assert_eq!(bucket_starts(&range("1,3")), vec![1, 2, 3, 4]);
+        assert_eq!(
+            bucket_starts(&range("1,100")),
+            vec![1, 11, 21, 31, 41, 51, 61, 71, 81, 91, 101]
+        );
+        assert_eq!(
+            bucket_starts(&range("5,5000:log")),
+            vec![5, 10, 100, 1000, 5001]
+        );
+        assert_eq!(
+            bucket_starts(&range("0,100:25")),
+            vec![0, 25, 50, 75, 100, 101]
+        );
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let uniform = histogram(range("1,100"), 10_000, &mut rng);
+        assert_eq!(uniform.buckets[0].0, "1..10");
+        assert!(uniform.buckets.iter().all(|(_, c)| (800..1200).contains(c)));
+        let log = histogram(range("1,9999:log"), 10_000, &mut rng);
+        assert_eq!(log.buckets.len(), 4);
+        assert!(log.buckets.iter().all(|(_, c)| (2000..3000).contains(c)));
+
+        let templates = Corpus::from(vec![
+            "Fixed XNUM1,3X bugs".to_string(),
+            "{{set n = num:1..3}}{{50%: again, XNUM1,3:romanX}} {{n}}".to_string(),
+            "No numbers".to_string(),
+        ]);
+        let pool = [0, 0, 1, 2];
+        let mut picks = [2, 0, 0, 1].into_iter().cycle();
+        let report = run(&templates, &pool, 8, 7, &mut rng, |_| {
+            Ok(Generated {
+                message: String::new(),
+                name: String::new(),
+                template_index: picks.next().unwrap(),
+                drawn: Vec::new(),
+                spliced: None,
+            })
+        })
+        .unwrap();
+        assert_eq!(
+            report.picked[0],
+            Picked {
+                index: 0,
+                count: 4,
+                expected: 0.5
+            }
+        );
+        assert_eq!(report.numbers.len(), 1);
+        let rendered = report.render(&templates).unwrap();
+        assert!(rendered.starts_with("seed 7, 8 messages from 3 templates (4 in the pool)"));
+        assert!(rendered.contains("0 of 3 templates in the pool never picked"));
+        assert!(rendered.contains("\n1..3\n  1 "));
+    }
+}
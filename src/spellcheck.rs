@@ -0,0 +1,345 @@
+use crate::corpus;
+use crate::diagnostic::Diagnostic;
+use crate::error::WtcError;
+use crate::lint;
+use crate::sections;
+use anyhow::Result;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Common English words and words of the trade, in their base forms.
+const DICTIONARY: &str = include_str!("dictionary.txt");
+
+fn dictionary() -> &'static HashSet<&'static str> {
+    static WORDS: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| {
+        DICTIONARY
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .flat_map(str::split_whitespace)
+            .collect()
+    })
+}
+
+/// Endings of inflected and derived words, with what to put back to get the word they
+/// come from, tried in order. The first `INFLECTIONS` are the regular inflections.
+const SUFFIXES: [(&str, &str); 22] = [
+    ("'s", ""),
+    ("ies", "y"),
+    ("es", ""),
+    ("s", ""),
+    ("ied", "y"),
+    ("ed", ""),
+    ("ed", "e"),
+    ("ing", ""),
+    ("ing", "e"),
+    ("ily", "y"),
+    ("ly", ""),
+    ("ier", "y"),
+    ("iest", "y"),
+    ("er", ""),
+    ("er", "e"),
+    ("est", ""),
+    ("est", "e"),
+    ("able", ""),
+    ("ment", ""),
+    ("ness", ""),
+    ("less", ""),
+    ("ful", ""),
+];
+
+/// How many of `SUFFIXES` are inflections rather than derivations.
+const INFLECTIONS: usize = 9;
+
+/// Beginnings of derived words.
+const PREFIXES: [&str; 9] = [
+    "un", "re", "pre", "non", "over", "under", "sub", "auto", "multi",
+];
+
+/// The lowercase `word`, or a word it is inflected or derived from, is in the dictionary
+/// or `allowed`. Derivations stack up to `depth` deep, as in `unchanged`.
+fn known(word: &str, allowed: &HashSet<String>, depth: usize) -> bool {
+    if dictionary().contains(word) || allowed.contains(word) {
+        return true;
+    }
+    if depth == 0 {
+        return false;
+    }
+    let from_suffix = SUFFIXES.iter().any(|(suffix, back)| {
+        let Some(stem) = word.strip_suffix(suffix).filter(|s| s.len() >= 2) else {
+            return false;
+        };
+        if known(&format!("{}{}", stem, back), allowed, depth - 1) {
+            return true;
+        }
+        // A doubled final consonant, as in `stopped` or `running`
+        let bytes = stem.as_bytes();
+        back.is_empty()
+            && bytes.len() >= 3
+            && bytes[bytes.len() - 1] == bytes[bytes.len() - 2]
+            && known(&stem[..stem.len() - 1], allowed, depth - 1)
+    });
+    from_suffix
+        || PREFIXES.iter().any(|prefix| {
+            word.strip_prefix(prefix)
+                .filter(|rest| rest.len() >= 3)
+                .is_some_and(|rest| known(rest, allowed, depth - 1))
+        })
+}
+
+/// Whether `word` is in the dictionary or `allowed`, or is a regular inflection of a word
+/// that is, such as a plural or past tense. Unlike `known`, this is strict enough that a
+/// misspelling is unlikely to pass as a word, so suggestions come from it.
+fn real(word: &str, allowed: &HashSet<String>) -> bool {
+    let listed = |word: &str| dictionary().contains(word) || allowed.contains(word);
+    listed(word)
+        || SUFFIXES[..INFLECTIONS].iter().any(|(suffix, back)| {
+            word.strip_suffix(suffix)
+                .filter(|stem| stem.len() >= 2)
+                .is_some_and(|stem| listed(&format!("{}{}", stem, back)))
+        })
+}
+
+/// A real word one edit from `word`: a letter left out, added, changed, or swapped with
+/// the next. Short words differ from real ones by a letter too easily, so only swapped
+/// letters are tried on them.
+fn suggest(word: &str, allowed: &HashSet<String>) -> Option<String> {
+    let chars: Vec<char> = word.chars().collect();
+    let mut candidates = Vec::new();
+    for i in 0..chars.len().saturating_sub(1) {
+        let mut swapped = chars.clone();
+        swapped.swap(i, i + 1);
+        candidates.push(swapped);
+    }
+    if chars.len() >= 5 {
+        for i in 0..=chars.len() {
+            for c in ('a'..='z').chain(['\'']) {
+                let mut inserted = chars.clone();
+                inserted.insert(i, c);
+                candidates.push(inserted);
+                if i < chars.len() && chars[i] != c {
+                    let mut changed = chars.clone();
+                    changed[i] = c;
+                    candidates.push(changed);
+                }
+            }
+            if i < chars.len() {
+                let mut deleted = chars.clone();
+                deleted.remove(i);
+                candidates.push(deleted);
+            }
+        }
+    }
+    candidates
+        .into_iter()
+        .map(|c| c.into_iter().collect::<String>())
+        .find(|candidate| real(candidate, allowed))
+}
+
+/// Whether a word is checked at all: not an acronym, `camelCase` or a name with capitals
+/// past its first letter, and long enough to misspell.
+fn checkable(word: &str) -> bool {
+    word.chars().count() >= 3 && !word.chars().skip(1).any(char::is_uppercase)
+}
+
+/// The likely typos in `template`, by byte offset, each with the word it probably meant.
+/// Placeholders, text in backticks, anything that looks like code or a path and
+/// capitalized words within sentences, likely names, are skipped.
+fn typos(template: &str, allowed: &HashSet<String>) -> Vec<(usize, String)> {
+    let mut text = template.to_string();
+    for placeholder in lint::scan(template) {
+        let blank = " ".repeat(placeholder.range.len());
+        text.replace_range(placeholder.range, &blank);
+    }
+    let mut in_code = false;
+    let mut found = Vec::new();
+    let mut at = 0;
+    let mut sentence_start = true;
+    for chunk in text.split_inclusive(char::is_whitespace) {
+        let start = at;
+        at += chunk.len();
+        // Capitalized words past the start of a sentence are taken for names
+        let starts_sentence = sentence_start;
+        if !chunk.trim().is_empty() {
+            sentence_start = chunk.trim_end().ends_with(['.', '!', '?', ':']);
+        }
+        let ticks = chunk.matches('`').count();
+        let was_in_code = in_code;
+        in_code ^= ticks % 2 == 1;
+        if was_in_code || ticks > 0 {
+            continue;
+        }
+        let trimmed = chunk.trim_start_matches(|c: char| !c.is_alphanumeric());
+        let offset = start + chunk.len() - trimmed.len();
+        let word = trimmed.trim_end_matches(|c: char| !c.is_alphanumeric());
+        if !word
+            .chars()
+            .all(|c| c.is_alphabetic() || c == '\'' || c == '-')
+        {
+            continue;
+        }
+        let mut part_at = offset;
+        for part in word.split('-') {
+            let lower = part.to_lowercase();
+            let name = !starts_sentence && part.starts_with(char::is_uppercase);
+            if checkable(part) && !name && !known(&lower, allowed, 2) {
+                if let Some(suggestion) = suggest(&lower, allowed) {
+                    found.push((
+                        part_at,
+                        format!(
+                            "`{}` may be misspelled; did you mean `{}`?",
+                            part, suggestion
+                        ),
+                    ));
+                }
+            }
+            part_at += part.len() + 1;
+        }
+    }
+    found
+}
+
+/// Reads the allowlist at `path`: a word per line, ignoring case, with `#` comments.
+fn allowlist(path: &Path) -> Result<HashSet<String>> {
+    let content = fs::read_to_string(path).map_err(|source| WtcError::IoError {
+        context: format!("Failed to read allowlist: {:?}", path),
+        source,
+    })?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_lowercase)
+        .collect())
+}
+
+/// Spellchecks the templates files at `paths`, or the loaded templates if none are given,
+/// printing every likely typo and failing if there are any. Words in the file at
+/// `allowlist_path`, if any, are always accepted.
+pub fn run(
+    paths: &[PathBuf],
+    loaded: (&Path, &[String]),
+    allowlist_path: Option<&Path>,
+) -> Result<()> {
+    let allowed = match allowlist_path {
+        Some(path) => allowlist(path)?,
+        None => HashSet::new(),
+    };
+    let mut count = 0;
+    for (path, text) in corpus::packs(paths, loaded)? {
+        for (number, line) in sections::templates(&text) {
+            for (at, problem) in typos(line, &allowed) {
+                println!("{}", Diagnostic::new(&path, number + 1, line, at, problem));
+                count += 1;
+            }
+        }
+    }
+    if count > 0 {
+        let noun = if count == 1 {
+            "likely typo"
+        } else {
+            "likely typos"
+        };
+        anyhow::bail!("{} {}", count, noun);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_spellcheck_templates() {
+        let allowed = HashSet::from(["frobnicate".to_string()]);
+        for word in [
+            "fixed",
+            "stopped",
+            "running",
+            "unchanged",
+            "rewrites",
+            "easily",
+            "don't",
+            "frobnicated",
+        ] {
+            assert!(known(word, &allowed, 2), "{}", word);
+        }
+        let found = typos(
+            "XNAMEX fixde teh bug in `recieve` and the parser's handler",
+            &allowed,
+        );
+        let words: Vec<(usize, &str)> = found
+            .iter()
+            .map(|(at, problem)| (*at, problem.split('`').nth(3).unwrap()))
+            .collect();
+        assert_eq!(words, [(7, "fixed"), (13, "the")]);
+        assert!(typos(
+            "Refactored recieve_handler, HTTP and the kubernetes thing",
+            &allowed
+        )
+        .is_empty());
+        assert_eq!(typos("XTHINGX brokne", &allowed).len(), 1);
+        assert!(typos("Sam shipped the frobnicaet-free build", &allowed)[0]
+            .1
+            .contains("frobnicate"));
+    }
+
+    #[test]
+    fn t_suggestions() {
+        let none = HashSet::new();
+        assert_eq!(suggest("teh", &none).as_deref(), Some("the"));
+        // Short words only get their letters swapped
+        assert_eq!(suggest("thw", &none), None);
+        assert_eq!(suggest("buidl", &none).as_deref(), Some("build"));
+        assert_eq!(suggest("qzxqzxqzx", &none), None);
+        assert!(checkable("fixde") && !checkable("ok") && !checkable("parseJSON"));
+        assert!(checkable("Fixde") && !checkable("HTTP"));
+    }
+
+    #[test]
+    fn t_typo_offsets() {
+        let none = HashSet::new();
+        // Offsets are in bytes, past multibyte text and placeholders
+        let found = typos("Café déjà vu, XNAMEX fixde it", &none);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, "Café déjà vu, XNAMEX ".len());
+        // Each part of a hyphenated word is checked at its own offset
+        let found = typos("a well-tetsed fix", &none);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, "a well-".len());
+        // Words in backticks, even across spaces, and names within sentences are skipped
+        assert!(typos("Ran `git fixde teh` again", &none).is_empty());
+        assert!(typos("Blamed Fixde for it", &none).is_empty());
+        assert_eq!(typos("Done. Fixde it", &none).len(), 1);
+    }
+
+    #[test]
+    fn t_allowlist_and_run() {
+        let dir = std::env::temp_dir().join(format!("wtc-spellcheck-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let list = dir.join("allow.txt");
+        fs::write(&list, "# team words\n  Frobnicate \n\n").unwrap();
+        assert_eq!(
+            allowlist(&list).unwrap(),
+            HashSet::from(["frobnicate".to_string()])
+        );
+        assert!(allowlist(&dir.join("missing.txt")).is_err());
+
+        let templates = dir.join("templates.txt");
+        fs::write(&templates, "Fixed teh build\nFixed it\n").unwrap();
+        let err = run(std::slice::from_ref(&templates), (&templates, &[]), None).unwrap_err();
+        assert_eq!(err.to_string(), "1 likely typo");
+        // A word near nothing known is let be, unless the allowlist has one close to it
+        fs::write(&templates, "Fixed the frobnicaet\n").unwrap();
+        assert!(run(std::slice::from_ref(&templates), (&templates, &[]), None).is_ok());
+        assert!(run(
+            std::slice::from_ref(&templates),
+            (&templates, &[]),
+            Some(&list)
+        )
+        .is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,26 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// How rude messages may be, both the templates used and the words `XSWEARX` draws.
+///
+/// Templates tagged with `{{spice: LEVEL}}` are only used at that level or above; levels
+/// are ordered from the safe-for-work floor up, and go by the ratings G, PG and R too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Spice {
+    /// Censored symbols like `%$#@!` and only clean templates, safe for work
+    #[value(alias = "g")]
+    #[serde(alias = "g")]
+    Censored,
+    /// Mild words like "heck", and templates up to mild
+    Mild,
+    /// Mild words, and templates up to medium (the default)
+    #[default]
+    #[value(alias = "pg")]
+    #[serde(alias = "pg")]
+    Medium,
+    /// Strong language, mild words included, and savage templates
+    #[value(aliases = ["savage", "r"])]
+    #[serde(alias = "savage", alias = "r")]
+    Strong,
+}
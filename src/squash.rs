@@ -0,0 +1,26 @@
+/// Assembles several messages into one squash-style commit message: the first becomes
+/// the subject and the rest become bullet points in the body.
+pub fn message<S: AsRef<str>>(messages: &[S]) -> String {
+    let mut lines = messages.iter().map(|m| m.as_ref().trim());
+    let subject = lines.next().unwrap_or_default().to_string();
+    let body: Vec<String> = lines.map(|line| format!("- {}", line)).collect();
+    if body.is_empty() {
+        subject
+    } else {
+        format!("{}\n\n{}", subject, body.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_squash_message() {
+        assert_eq!(message(&["only one"]), "only one");
+        assert_eq!(
+            message(&["Fix it", "Break it again ", "Blame Ann"]),
+            "Fix it\n\n- Break it again\n- Blame Ann"
+        );
+    }
+}
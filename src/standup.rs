@@ -0,0 +1,106 @@
+use crate::escape::Escape;
+use crate::sections;
+use anyhow::Result;
+use clap::ValueEnum;
+
+/// The built-in templates, under `[yesterday]`, `[today]` and `[blockers]` headers.
+pub const DEFAULT: &str = include_str!("standup.txt");
+
+/// A part of a standup update, each with its own templates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Yesterday,
+    Today,
+    Blockers,
+}
+
+impl Section {
+    pub const ALL: [Section; 3] = [Section::Yesterday, Section::Today, Section::Blockers];
+
+    /// The header naming the section in a templates file.
+    fn key(self) -> &'static str {
+        match self {
+            Section::Yesterday => "yesterday",
+            Section::Today => "today",
+            Section::Blockers => "blockers",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Section::Yesterday => "Yesterday",
+            Section::Today => "Today",
+            Section::Blockers => "Blockers",
+        }
+    }
+}
+
+/// How the update is laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum StandupFormat {
+    /// A line per section, its items separated by semicolons
+    #[default]
+    Text,
+    /// Bold section titles over bullet lists, escaped for pasting into Slack or a PR
+    Markdown,
+}
+
+/// Splits a templates file into the templates of each section, in the order of
+/// [`Section::ALL`]. Blank lines and `#` comments are skipped.
+pub fn parse(text: &str) -> Result<[Vec<String>; 3]> {
+    sections::parse(text, Section::ALL.map(Section::key), "standup")
+}
+
+/// Lays out the generated items of each section.
+pub fn render(parts: &[(Section, Vec<String>)], format: StandupFormat) -> String {
+    let mut out = String::new();
+    for (section, items) in parts {
+        match format {
+            StandupFormat::Text => {
+                out.push_str(&format!("{}: {}\n", section.title(), items.join("; ")));
+            }
+            StandupFormat::Markdown => {
+                if !out.is_empty() {
+                    out.push('\n');
+                }
+                out.push_str(&format!("**{}**\n", section.title()));
+                for item in items {
+                    out.push_str(&format!("- {}\n", Escape::Markdown.apply(item)));
+                }
+            }
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lint::assert_lints_clean;
+
+    #[test]
+    fn t_standup_sections() {
+        let sections = parse(DEFAULT).unwrap();
+        assert!(sections.iter().all(|templates| templates.len() > 5));
+        assert_lints_clean("standup", sections.iter().flatten());
+        assert!(parse("[yesterday]\na\n[today]\nb\n").is_err());
+        assert!(parse("stray\n[yesterday]\na\n").is_err());
+
+        let parts = [
+            (
+                Section::Yesterday,
+                vec!["Fixed *it*".to_string(), "Slept".to_string()],
+            ),
+            (Section::Blockers, vec!["None".to_string()]),
+        ];
+        assert_eq!(
+            render(&parts, StandupFormat::Text),
+            "Yesterday: Fixed *it*; Slept\nBlockers: None"
+        );
+        assert_eq!(
+            render(&parts, StandupFormat::Markdown),
+            "**Yesterday**\n- Fixed \\*it\\*\n- Slept\n\n**Blockers**\n- None"
+        );
+    }
+}
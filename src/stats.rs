@@ -0,0 +1,233 @@
+use crate::corpus::Corpus;
+use crate::lint::{self, PLACEHOLDERS};
+use crate::list::placeholder_kinds;
+use crate::text;
+use crate::Generated;
+use anyhow::Result;
+use clap::ValueEnum;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// How to print batch statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum StatsFormat {
+    /// Aligned lines for people
+    #[default]
+    Text,
+    /// A single JSON object
+    Json,
+}
+
+/// Summary of a generated batch.
+#[derive(Debug, PartialEq)]
+struct Stats {
+    messages: usize,
+    min_length: usize,
+    max_length: usize,
+    mean_length: f64,
+    placeholders: BTreeMap<&'static str, usize>,
+    templates_used: usize,
+    templates_available: usize,
+}
+
+fn collect(batch: &[Generated], commit_messages: &Corpus, pool_size: usize) -> Result<Stats> {
+    let lengths: Vec<usize> = batch.iter().map(|g| text::length(&g.message)).collect();
+    let mut placeholders = BTreeMap::from([("name", 0), ("number", 0)]);
+    for generated in batch {
+        for kind in placeholder_kinds(&commit_messages.get(generated.template_index)?) {
+            *placeholders.entry(kind).or_default() += 1;
+        }
+    }
+    let templates: BTreeSet<usize> = batch.iter().map(|g| g.template_index).collect();
+    Ok(Stats {
+        messages: batch.len(),
+        min_length: lengths.iter().copied().min().unwrap_or(0),
+        max_length: lengths.iter().copied().max().unwrap_or(0),
+        mean_length: lengths.iter().sum::<usize>() as f64 / lengths.len().max(1) as f64,
+        placeholders,
+        templates_used: templates.len(),
+        templates_available: pool_size,
+    })
+}
+
+/// Renders statistics for a batch of messages generated from a pool of `pool_size` templates.
+pub fn render(
+    batch: &[Generated],
+    commit_messages: &Corpus,
+    pool_size: usize,
+    format: StatsFormat,
+) -> Result<String> {
+    let stats = collect(batch, commit_messages, pool_size)?;
+    let coverage = 100.0 * stats.templates_used as f64 / stats.templates_available.max(1) as f64;
+    Ok(match format {
+        StatsFormat::Text => {
+            let placeholders: Vec<String> = stats
+                .placeholders
+                .iter()
+                .map(|(kind, count)| format!("{} {}", kind, count))
+                .collect();
+            format!(
+                "messages      {}\nlength        min {}, max {}, mean {:.1}\nplaceholders  {}\ntemplates     {} of {} ({:.1}%)",
+                stats.messages,
+                stats.min_length,
+                stats.max_length,
+                stats.mean_length,
+                placeholders.join(", "),
+                stats.templates_used,
+                stats.templates_available,
+                coverage
+            )
+        }
+        StatsFormat::Json => json!({
+            "messages": stats.messages,
+            "length": {
+                "min": stats.min_length,
+                "max": stats.max_length,
+                "mean": stats.mean_length,
+            },
+            "placeholders": stats.placeholders,
+            "templates": {
+                "used": stats.templates_used,
+                "available": stats.templates_available,
+                "coverage": coverage,
+            },
+        })
+        .to_string(),
+    })
+}
+
+/// Placeholders counted in the corpus heatmap, those of `lint::PLACEHOLDERS` then `XNUM`,
+/// standing for every number range, and `none`.
+pub const HEATMAP_PLACEHOLDERS: [&str; PLACEHOLDERS.len() + 2] = {
+    let mut heatmap = [""; PLACEHOLDERS.len() + 2];
+    let mut i = 0;
+    while i < PLACEHOLDERS.len() {
+        heatmap[i] = PLACEHOLDERS[i].0;
+        i += 1;
+    }
+    heatmap[i] = "XNUM";
+    heatmap[i + 1] = "none";
+    heatmap
+};
+
+/// Upper bounds of the template length buckets in the heatmap; the last is open-ended.
+pub const LENGTH_BUCKETS: [usize; 4] = [20, 40, 60, 80];
+
+/// Number of templates listed as the shortest and longest.
+const EXTREMES: usize = 5;
+
+/// Summary of a whole template corpus.
+#[derive(Debug, PartialEq)]
+pub struct CorpusStats {
+    pub templates: usize,
+    pub min_length: usize,
+    pub max_length: usize,
+    pub mean_length: f64,
+    /// Indices of the shortest templates, shortest first.
+    pub shortest: Vec<usize>,
+    /// Indices of the longest templates, longest first.
+    pub longest: Vec<usize>,
+    /// For each of `HEATMAP_PLACEHOLDERS`, the number of templates using it per length bucket.
+    pub heatmap: Vec<[usize; LENGTH_BUCKETS.len() + 1]>,
+}
+
+/// Analyzes template lengths and placeholder usage across a corpus.
+pub fn corpus(commit_messages: &[String]) -> CorpusStats {
+    let lengths: Vec<usize> = commit_messages.iter().map(|t| text::length(t)).collect();
+    let mut by_length: Vec<usize> = (0..commit_messages.len()).collect();
+    by_length.sort_by_key(|&i| (lengths[i], i));
+
+    let mut heatmap = vec![[0; LENGTH_BUCKETS.len() + 1]; HEATMAP_PLACEHOLDERS.len()];
+    for (template, &length) in commit_messages.iter().zip(&lengths) {
+        let bucket = LENGTH_BUCKETS
+            .iter()
+            .position(|&bound| length < bound)
+            .unwrap_or(LENGTH_BUCKETS.len());
+        let mut used: BTreeSet<usize> = lint::scan(template)
+            .into_iter()
+            .filter(|p| p.problem.is_none())
+            .filter_map(|p| {
+                let text = &template[p.range];
+                let text = if text.starts_with("XNUM") {
+                    "XNUM"
+                } else {
+                    text
+                };
+                HEATMAP_PLACEHOLDERS.iter().position(|&h| h == text)
+            })
+            .collect();
+        if used.is_empty() {
+            used.insert(HEATMAP_PLACEHOLDERS.len() - 1);
+        }
+        for row in used {
+            heatmap[row][bucket] += 1;
+        }
+    }
+
+    CorpusStats {
+        templates: commit_messages.len(),
+        min_length: lengths.iter().copied().min().unwrap_or(0),
+        max_length: lengths.iter().copied().max().unwrap_or(0),
+        mean_length: lengths.iter().sum::<usize>() as f64 / lengths.len().max(1) as f64,
+        shortest: by_length.iter().copied().take(EXTREMES).collect(),
+        longest: by_length.iter().rev().copied().take(EXTREMES).collect(),
+        heatmap,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn generated(message: &str, template_index: usize) -> Generated {
+        Generated {
+            message: message.to_string(),
+            name: "Ann".to_string(),
+            template_index,
+            drawn: Vec::new(),
+            spliced: None,
+        }
+    }
+
+    #[test]
+    fn t_batch_stats() {
+        let templates = Corpus::from(vec!["XNAMEX did it".to_string(), "XNUMX bugs".to_string()]);
+        let batch = vec![
+            generated("Ann did it", 0),
+            generated("Bo did it", 0),
+            generated("7 bugs", 1),
+        ];
+        let stats = collect(&batch, &templates, 4).unwrap();
+        assert_eq!(stats.min_length, 6);
+        assert_eq!(stats.max_length, 10);
+        assert!((stats.mean_length - 25.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.placeholders["name"], 2);
+        assert_eq!(stats.placeholders["number"], 1);
+        assert_eq!(stats.templates_used, 2);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&batch, &templates, 4, StatsFormat::Json).unwrap())
+                .unwrap();
+        assert_eq!(json["templates"]["coverage"], 50.0);
+    }
+
+    #[test]
+    fn t_corpus_stats() {
+        let templates: Vec<String> = [
+            "XNAMEX did it",
+            "XUPPERNAMEX SHOUTED about XNUM1,5X bugs for a long while",
+            "oops",
+        ]
+        .map(String::from)
+        .to_vec();
+        let stats = corpus(&templates);
+        assert_eq!((stats.min_length, stats.max_length), (4, 56));
+        assert_eq!(stats.shortest, vec![2, 0, 1]);
+        assert_eq!(stats.longest, vec![1, 0, 2]);
+        assert_eq!(stats.heatmap[0], [1, 0, 0, 0, 0]);
+        assert_eq!(stats.heatmap[1], [0, 0, 1, 0, 0]);
+        assert_eq!(stats.heatmap[4], [0, 0, 0, 0, 0]);
+        assert_eq!(stats.heatmap[21], [0, 0, 1, 0, 0]);
+        assert_eq!(stats.heatmap[22], [1, 0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::daily;
+use crate::error::WtcError;
+use crate::http::{self, Fetched};
+use crate::lockfile;
+use crate::paths;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info, warn};
+
+/// How long a fetched corpus is used before it is checked for changes.
+const REFRESH_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// The hidden command that refreshes a corpus in a background process.
+pub const REFRESH_COMMAND: &str = "refresh-team-corpus";
+
+/// What is remembered about the last fetch of a corpus, for conditional requests.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Meta {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// Seconds since the epoch when the server last answered.
+    checked: u64,
+}
+
+/// Where the corpus from `url` and what is known about it are cached.
+fn cache_paths(url: &str) -> Result<(PathBuf, PathBuf)> {
+    let dir = paths::cache_dir()
+        .context("Could not determine the cache directory")?
+        .join("team");
+    let key = format!("{:016x}", daily::fnv1a(url.as_bytes()));
+    Ok((
+        dir.join(format!("{}.txt", key)),
+        dir.join(format!("{}.json", key)),
+    ))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn read_meta(path: &Path, url: &str) -> Meta {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str::<Meta>(&text).ok())
+        .filter(|meta| meta.url == url)
+        .unwrap_or_else(|| Meta {
+            url: url.to_string(),
+            ..Default::default()
+        })
+}
+
+/// Returns the cached copy of the shared corpus at `url`, fetching it first if there is
+/// none. A copy older than `REFRESH_AFTER` is still used, while a background process
+/// checks the server for a newer one, so no run waits on the network once it is cached.
+/// The copy is checked against the lockfile, and with `locked` refused if it doesn't
+/// match.
+pub fn corpus(url: &str, settings: &http::Settings, locked: bool) -> Result<PathBuf> {
+    let (file, meta_path) = cache_paths(url)?;
+    if file.exists() {
+        let meta = read_meta(&meta_path, url);
+        let stale = now().saturating_sub(meta.checked) >= REFRESH_AFTER.as_secs();
+        if stale && !settings.offline {
+            spawn_refresh(url);
+        }
+        debug!("Using the cached team corpus from {}", url);
+    } else {
+        refresh(url, settings)?;
+    }
+    let bytes = fs::read(&file).map_err(|source| WtcError::IoError {
+        context: format!("Failed to read the team corpus cache: {:?}", file),
+        source,
+    })?;
+    lockfile::check_source(url, &bytes, locked)?;
+    Ok(file)
+}
+
+/// Checks `url` for a newer corpus than the cached one, sending the validators of the last
+/// response so an unchanged file isn't downloaded again.
+pub fn refresh(url: &str, settings: &http::Settings) -> Result<()> {
+    let (file, meta_path) = cache_paths(url)?;
+    let mut meta = read_meta(&meta_path, url);
+    // Validators are only worth sending when there is a copy to keep
+    let (etag, last_modified) = match file.exists() {
+        true => (meta.etag.as_deref(), meta.last_modified.as_deref()),
+        false => (None, None),
+    };
+    let Fetched {
+        body,
+        etag,
+        last_modified,
+    } = http::get_if_changed(url, etag, last_modified, settings)?;
+    if let Some(body) = body {
+        write_atomically(&file, &body)?;
+        info!("Fetched the team corpus from {}", url);
+    } else {
+        debug!("The team corpus at {} hasn't changed", url);
+    }
+    meta.etag = etag.or(meta.etag);
+    meta.last_modified = last_modified.or(meta.last_modified);
+    meta.checked = now();
+    write_atomically(&meta_path, &serde_json::to_string(&meta)?)
+}
+
+/// Writes through a temporary file, so a run reading the cache at the same time never
+/// sees half a file.
+fn write_atomically(path: &Path, contents: &str) -> Result<()> {
+    let io_error = |source| WtcError::IoError {
+        context: format!("Failed to write the team corpus cache: {:?}", path),
+        source,
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(io_error)?;
+    }
+    let temp = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&temp, contents).map_err(io_error)?;
+    fs::rename(&temp, path).map_err(io_error)?;
+    Ok(())
+}
+
+/// Starts this program again in the background to refresh the corpus, without waiting
+/// for it. If that fails, the cached copy is used a while longer.
+fn spawn_refresh(url: &str) {
+    let spawned = env::current_exe().and_then(|exe| {
+        Command::new(exe)
+            .args([REFRESH_COMMAND, url])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+    });
+    match spawned {
+        Ok(_) => debug!("Refreshing the team corpus from {} in the background", url),
+        Err(e) => warn!("Failed to start refreshing the team corpus: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_cache_paths_per_url() {
+        let (file, meta) = cache_paths("https://example.com/templates.txt").unwrap();
+        assert_eq!(file.extension().unwrap(), "txt");
+        assert_eq!(file.with_extension("json"), meta);
+        assert_ne!(
+            cache_paths("https://example.com/names.txt").unwrap().0,
+            file
+        );
+    }
+
+    #[test]
+    fn t_offline_without_cache_fails() {
+        let settings = http::Settings {
+            timeout: Duration::from_secs(1),
+            retries: 0,
+            backoff: Duration::ZERO,
+            offline: true,
+            proxy: None,
+            auth: Vec::new(),
+        };
+        let url = format!(
+            "https://example.com/never-cached-{}.txt",
+            std::process::id()
+        );
+        let err = corpus(&url, &settings, false).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<WtcError>(),
+            Some(WtcError::Offline { .. })
+        ));
+    }
+}
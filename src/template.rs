@@ -0,0 +1,915 @@
+use crate::company;
+use crate::ip;
+use crate::lint::{self, Directive, Distribution, Kind, NumberFormat, NumberRange};
+use crate::name::{self, NameCase, Pronouns};
+use crate::url;
+use crate::uuid;
+use crate::words::{self, List};
+use rand::seq::IndexedRandom;
+use rand::Rng;
+use std::fmt::Write;
+use std::ops::Range;
+
+/// A piece of a parsed template.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node<'a> {
+    /// Text copied to the message as it is.
+    Literal(&'a str),
+    /// A name placeholder, such as `XUPPERNAMEX`.
+    Name(NameCase),
+    /// `XPRONOUNX`, the name's subject pronoun, like "she".
+    Pronoun,
+    /// `XPOSSESSIVEX`, the name's possessive pronoun, like "her".
+    Possessive,
+    /// `XCOMPANYX`, a made-up company or product name.
+    Company,
+    /// A placeholder replaced with a word from a list, such as `XLANGX` or `XVERBX`.
+    Word(List),
+    /// `XIPX`, an IPv4 address from a documentation block.
+    Ipv4,
+    /// `XIP6X`, an IPv6 address from the documentation block.
+    Ipv6,
+    /// `XUUIDX`, a random UUID.
+    Uuid,
+    /// `XSHORTUUIDX`, the start of a random UUID.
+    ShortUuid,
+    /// `XPATHX`, a source file path.
+    Path,
+    /// `XURLX`, a link that doesn't resolve.
+    Url,
+    /// `XVERSIONX`, a version number such as `2.13.4`.
+    Version,
+    /// `XSWEARX`, a swear word, censored at `--spice censored`.
+    Swear,
+    /// `XQUOTEX`, a short quote from a fortune file.
+    Quote,
+    /// A number placeholder, such as `XNUM1,5X`, with its range.
+    Number(NumberRange),
+    /// A directive, such as `{{set n = num:1..10}}` or `{{n*10}}`.
+    Directive(Directive<'a>),
+    /// `{{N%: TEXT}}`, text written only `chance` times in a hundred.
+    Maybe { chance: u8, body: Template<'a> },
+    /// `{{repeat N: TEXT}}`, text written `count` times, with any literal text that ends it
+    /// only between the items.
+    Repeat { count: u8, body: Template<'a> },
+    /// A number placeholder whose range can't be read, such as `XNUM1,2,3X`, or a
+    /// directive that can't be used. It is kept as written.
+    Malformed { text: &'a str, problem: String },
+}
+
+/// A template parsed into literal text and placeholders, in the order they appear.
+///
+/// Templates are scanned once from left to right, so values substituted into a message
+/// are never scanned again for placeholders.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template<'a> {
+    nodes: Vec<Node<'a>>,
+}
+
+/// The node for a placeholder of `lint::PLACEHOLDERS`.
+fn node(kind: Kind) -> Node<'static> {
+    match kind {
+        Kind::Name => Node::Name(NameCase::AsIs),
+        Kind::UpperName => Node::Name(NameCase::Upper),
+        Kind::LowerName => Node::Name(NameCase::Lower),
+        Kind::TitleName => Node::Name(NameCase::Title),
+        Kind::MockName => Node::Name(NameCase::Mock),
+        Kind::Pronoun => Node::Pronoun,
+        Kind::Possessive => Node::Possessive,
+        Kind::Company => Node::Company,
+        Kind::Language => Node::Word(List::Language),
+        Kind::Verb => Node::Word(List::Verb),
+        Kind::Noun => Node::Word(List::Noun),
+        Kind::Adjective => Node::Word(List::Adjective),
+        Kind::Ipv4 => Node::Ipv4,
+        Kind::Ipv6 => Node::Ipv6,
+        Kind::Uuid => Node::Uuid,
+        Kind::ShortUuid => Node::ShortUuid,
+        Kind::Path => Node::Path,
+        Kind::Url => Node::Url,
+        Kind::Version => Node::Version,
+        Kind::Swear => Node::Swear,
+        Kind::Quote => Node::Quote,
+    }
+}
+
+/// Reads a number placeholder at the start of `rest`, returning its length and node.
+fn number(rest: &str) -> Option<(usize, Node<'_>)> {
+    let spec = rest.strip_prefix("XNUM")?;
+    let len =
+        spec.find(|c: char| !c.is_ascii_digit() && !c.is_ascii_lowercase() && !",:-".contains(c))?;
+    if !spec[len..].starts_with('X') {
+        return None;
+    }
+    let text = &rest[.."XNUM".len() + len + 1];
+    let node = match lint::parse_number_range(&spec[..len]) {
+        Ok(range) => Node::Number(range),
+        Err(problem) => Node::Malformed { text, problem },
+    };
+    Some((text.len(), node))
+}
+
+impl<'a> Template<'a> {
+    /// Parses a template in a single pass. Anything that isn't a placeholder is literal text.
+    pub fn parse(text: &'a str) -> Self {
+        let mut nodes = Vec::new();
+        let mut defined = Vec::new();
+        let mut literal = 0;
+        let mut at = 0;
+        while let Some(offset) = text[at..].find(['X', '{']) {
+            let start = at + offset;
+            let rest = &text[start..];
+            let found = if rest.starts_with("{{") {
+                let (len, directive) = lint::directive_at(rest, &mut defined);
+                let node = match directive {
+                    Ok(Directive::Maybe { chance, body }) => Node::Maybe {
+                        chance,
+                        body: Template::parse(body),
+                    },
+                    Ok(Directive::Repeat { count, body }) => Node::Repeat {
+                        count,
+                        body: Template::parse(body),
+                    },
+                    Ok(directive) => Node::Directive(directive),
+                    Err(problem) => Node::Malformed {
+                        text: &rest[..len],
+                        problem,
+                    },
+                };
+                Some((len, node))
+            } else {
+                match lint::known(rest) {
+                    Some((placeholder, kind)) => Some((placeholder.len(), node(kind))),
+                    None => number(rest),
+                }
+            };
+            let Some((len, node)) = found else {
+                at = start + 1;
+                continue;
+            };
+            if literal < start {
+                nodes.push(Node::Literal(&text[literal..start]));
+            }
+            nodes.push(node);
+            at = start + len;
+            literal = at;
+        }
+        if literal < text.len() {
+            nodes.push(Node::Literal(&text[literal..]));
+        }
+        let mut template = Template { nodes };
+        template.require();
+        template
+    }
+
+    /// Makes optional text holding a placeholder that a `{{requires: PLACEHOLDER}}`
+    /// directive names always written.
+    fn require(&mut self) {
+        let required: Vec<Node> = (self.nodes.iter())
+            .filter_map(|node| match node {
+                Node::Directive(Directive::Requires(placeholder)) => {
+                    Template::parse(placeholder).nodes.into_iter().next()
+                }
+                _ => None,
+            })
+            .collect();
+        for node in &mut self.nodes {
+            if let Node::Maybe { chance, body } = node {
+                if body.nodes.iter().any(|n| required.contains(n)) {
+                    *chance = 100;
+                }
+            }
+        }
+    }
+
+    pub fn nodes(&self) -> &[Node<'a>] {
+        &self.nodes
+    }
+
+    /// Renders a message, drawing numbers and words from `rng` from left to right. `name`
+    /// is an entry of the names file, which may give pronouns after the name.
+    pub fn render<R>(&self, name: &str, rng: &mut R) -> String
+    where
+        R: Rng + ?Sized,
+    {
+        let mut message = String::with_capacity(self.size_hint(name));
+        self.render_into(name, rng, &mut message);
+        message
+    }
+
+    /// Renders a message onto the end of `out`, so callers generating many messages can
+    /// reuse one buffer instead of allocating for each.
+    pub fn render_into<R>(&self, name: &str, rng: &mut R, out: &mut String)
+    where
+        R: Rng + ?Sized,
+    {
+        self.render_nodes(name, rng, out, words::get().consistent, None);
+    }
+
+    /// Renders a message as [`render`](Self::render) does, along with every number drawn
+    /// for it, with its range, in the order they were drawn, as `--explain` shows them.
+    pub fn render_explained<R>(&self, name: &str, rng: &mut R) -> (String, Vec<Drawn>)
+    where
+        R: Rng + ?Sized,
+    {
+        let mut message = String::with_capacity(self.size_hint(name));
+        let mut drawn = Vec::new();
+        let consistent = words::get().consistent;
+        self.render_nodes(name, rng, &mut message, consistent, Some(&mut drawn));
+        (message, drawn)
+    }
+
+    /// Renders a message as [`render`](Self::render) does with `--consistent`, whatever
+    /// the setting, so a placeholder written exactly as an earlier one repeats its value.
+    pub fn render_consistent<R>(&self, name: &str, rng: &mut R) -> String
+    where
+        R: Rng + ?Sized,
+    {
+        let mut message = String::with_capacity(self.size_hint(name));
+        self.render_nodes(name, rng, &mut message, true, None);
+        message
+    }
+
+    /// Renders every node onto `out`, adding the numbers drawn to `drawn` if given. When
+    /// `consistent`, a placeholder written exactly as an earlier one repeats that one's
+    /// value instead of drawing a new one.
+    fn render_nodes<R>(
+        &self,
+        entry: &str,
+        rng: &mut R,
+        out: &mut String,
+        consistent: bool,
+        drawn: Option<&mut Vec<Drawn>>,
+    ) where
+        R: Rng + ?Sized,
+    {
+        let (name, pronouns) = name::split(entry);
+        // Where in `out` each placeholder's value was written, for repeating it
+        let mut values: Vec<(&Node, Range<usize>)> = Vec::new();
+        let mut scope = Scope {
+            variables: Vec::new(),
+            drawn,
+        };
+        for node in &self.nodes {
+            let drawn = !matches!(
+                node,
+                Node::Literal(_)
+                    | Node::Name(_)
+                    | Node::Pronoun
+                    | Node::Possessive
+                    | Node::Directive(_)
+                    | Node::Malformed { .. }
+            );
+            let repeats = consistent && drawn;
+            if let Some((_, value)) = values.iter().find(|(n, _)| repeats && *n == node) {
+                out.extend_from_within(value.clone());
+                continue;
+            }
+            let start = out.len();
+            render_node(node, name, pronouns, &mut scope, rng, out);
+            if repeats {
+                values.push((node, start..out.len()));
+            }
+        }
+    }
+
+    /// A guess at the length of a rendered message, close enough that rendering rarely
+    /// has to grow its buffer.
+    fn size_hint(&self, name: &str) -> usize {
+        self.nodes
+            .iter()
+            .map(|node| match node {
+                Node::Literal(text) | Node::Malformed { text, .. } => text.len(),
+                Node::Name(_) => name.len(),
+                Node::Number(_) | Node::Directive(_) | Node::Pronoun | Node::Possessive => 4,
+                Node::Maybe { body, .. } => body.size_hint(name),
+                Node::Repeat { count, body } => usize::from(*count) * body.size_hint(name),
+                Node::Company | Node::Word(_) | Node::Swear => 12,
+                Node::Path => 24,
+                Node::Url | Node::Quote => 60,
+                Node::Ipv4 | Node::Ipv6 => 15,
+                Node::Version => 6,
+                Node::Uuid => 36,
+                Node::ShortUuid => 8,
+            })
+            .sum()
+    }
+}
+
+/// A number drawn while rendering, with the range it was drawn from.
+pub type Drawn = (NumberRange, i128);
+
+/// What rendering a message keeps track of besides the message.
+struct Scope<'a, 'd> {
+    /// The numbers of `{{set}}` directives, by variable.
+    variables: Vec<(&'a str, i128)>,
+    /// Every number drawn, when explaining the message.
+    drawn: Option<&'d mut Vec<Drawn>>,
+}
+
+impl Scope<'_, '_> {
+    fn draw<R>(&mut self, range: &NumberRange, rng: &mut R) -> i128
+    where
+        R: Rng + ?Sized,
+    {
+        let number = generate_random_in_range(range, rng);
+        if let Some(drawn) = &mut self.drawn {
+            drawn.push((*range, number));
+        }
+        number
+    }
+}
+
+/// Renders one node onto `out`, keeping the numbers of `{{set}}` directives in `scope`.
+fn render_node<'a, R>(
+    node: &Node<'a>,
+    name: &str,
+    pronouns: Pronouns,
+    scope: &mut Scope<'a, '_>,
+    rng: &mut R,
+    out: &mut String,
+) where
+    R: Rng + ?Sized,
+{
+    match node {
+        Node::Literal(text) => out.push_str(text),
+        Node::Name(case) => case.push_to(name, out),
+        Node::Pronoun => out.push_str(pronouns.subject),
+        Node::Possessive => out.push_str(pronouns.possessive),
+        Node::Company => out.push_str(&company::generate(rng)),
+        Node::Word(list) => {
+            if let Some(word) = words::get().list(*list).choose(rng) {
+                out.push_str(word);
+            }
+        }
+        Node::Ipv4 => {
+            let _ = write!(out, "{}", ip::v4(rng));
+        }
+        Node::Ipv6 => {
+            let _ = write!(out, "{}", ip::v6(rng));
+        }
+        Node::Uuid => out.push_str(&uuid::v4(rng)),
+        Node::ShortUuid => out.push_str(&uuid::short(rng)),
+        Node::Path => out.push_str(&words::get().path(rng)),
+        Node::Url => out.push_str(&url::generate(rng)),
+        Node::Version => out.push_str(&words::get().version(rng)),
+        Node::Swear => out.push_str(&words::get().swear(rng)),
+        Node::Quote => out.push_str(words::get().quote(rng)),
+        Node::Number(range) => {
+            let number = scope.draw(range, rng);
+            match range.format {
+                // Writing to a `String` can't fail
+                NumberFormat::Plain => {
+                    let _ = write!(out, "{}", number);
+                }
+                NumberFormat::Roman => push_roman(number, out),
+                NumberFormat::Ordinal => {
+                    let _ = write!(out, "{}{}", number, ordinal_suffix(number));
+                }
+                NumberFormat::Percent => {
+                    let _ = write!(out, "{}%", number);
+                }
+                NumberFormat::Currency => push_currency(number, rng.random_range(0..100), out),
+            }
+        }
+        Node::Directive(Directive::Set {
+            name: variable,
+            range,
+        }) => {
+            let number = scope.draw(range, rng);
+            scope.variables.push((variable, number));
+        }
+        Node::Directive(Directive::Get { name: variable, op }) => {
+            // Parsing checked that every variable is set before it is used
+            let value = (scope.variables.iter())
+                .rev()
+                .find(|(n, _)| n == variable)
+                .map_or(0, |&(_, value)| value);
+            let value = match op {
+                None => value,
+                Some(('*', n)) => value.saturating_mul(*n),
+                Some(('-', n)) => value.saturating_sub(*n),
+                Some((_, n)) => value.saturating_add(*n),
+            };
+            let _ = write!(out, "{}", value);
+        }
+        Node::Maybe { chance, body } => {
+            if rng.random_range(0..100) < *chance {
+                for node in &body.nodes {
+                    render_node(node, name, pronouns, scope, rng, out);
+                }
+            }
+        }
+        Node::Repeat { count, body } => {
+            // Literal text after the last placeholder separates the items, unless that
+            // is all there is
+            let (items, separator) = match body.nodes.split_last() {
+                Some((Node::Literal(separator), items)) if !items.is_empty() => (items, *separator),
+                _ => (body.nodes.as_slice(), ""),
+            };
+            for i in 0..*count {
+                if i > 0 {
+                    out.push_str(separator);
+                }
+                for node in items {
+                    render_node(node, name, pronouns, scope, rng, out);
+                }
+            }
+        }
+        // Parsing turns these into `Node::Maybe` and `Node::Repeat`
+        Node::Directive(Directive::Maybe { .. } | Directive::Repeat { .. }) => {}
+        // Only chooses which templates are used, and how
+        Node::Directive(
+            Directive::Spice(_)
+            | Directive::MaxLen(_)
+            | Directive::Requires(_)
+            | Directive::NoConventional,
+        ) => {}
+        // Reported as the template was loaded
+        Node::Malformed { text, .. } => out.push_str(text),
+    }
+}
+
+/// Generates a random number within the specified range.
+pub fn generate_random_in_range<R>(range: &NumberRange, rng: &mut R) -> i128
+where
+    R: Rng + ?Sized,
+{
+    let step = i128::from(range.step);
+    let steps = (range.last - range.first) / step;
+    if steps == 0 {
+        return range.first;
+    }
+    let offset = match range.distribution {
+        Distribution::Uniform => None,
+        // Uniform in the logarithm of the number, so each order of magnitude is as likely
+        Distribution::Log => {
+            let (low, high) = ((range.first as f64).ln(), ((range.last + 1) as f64).ln());
+            let number = rng.random_range(low..high).exp().floor() as i128;
+            Some((number - range.first) as f64 / step as f64)
+        }
+        // Most numbers within a sixth of the range of the middle
+        Distribution::Normal => {
+            Some(steps as f64 / 2.0 + standard_normal(rng) * steps as f64 / 6.0)
+        }
+    };
+    if let Some(offset) = offset {
+        let offset = (offset.round() as i128).clamp(0, steps);
+        return range.first + offset * step;
+    }
+    // Ranges of 32-bit numbers are sampled as before, so seeded output doesn't change
+    if let (1, Ok(first), Ok(last)) = (
+        range.step,
+        u32::try_from(range.first),
+        u32::try_from(range.last),
+    ) {
+        return rng.random_range(first..=last).into();
+    }
+    range.first + rng.random_range(0..=steps) * step
+}
+
+/// Roman numeral symbols with their values, largest first, including the subtractive pairs.
+const ROMAN_NUMERALS: [(i128, &str); 13] = [
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+];
+
+/// Writes a number from 1 to `lint::ROMAN_LIMIT` as Roman numerals.
+fn push_roman(mut number: i128, out: &mut String) {
+    for (value, symbol) in ROMAN_NUMERALS {
+        while number >= value {
+            out.push_str(symbol);
+            number -= value;
+        }
+    }
+}
+
+/// The English suffix making `number` an ordinal: 1st, 2nd, 3rd, 4th, 11th, 21st.
+fn ordinal_suffix(number: i128) -> &'static str {
+    let number = number.unsigned_abs();
+    match (number % 10, number % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+/// Writes dollars and `cents` with commas between thousands, such as `-$1,234.56`.
+fn push_currency(dollars: i128, cents: u8, out: &mut String) {
+    if dollars < 0 {
+        out.push('-');
+    }
+    out.push('$');
+    let digits = dollars.unsigned_abs().to_string();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    let _ = write!(out, ".{:02}", cents);
+}
+
+/// A draw from the standard normal distribution, by the Box-Muller transform.
+fn standard_normal<R>(rng: &mut R) -> f64
+where
+    R: Rng + ?Sized,
+{
+    let (u, v): (f64, f64) = (1.0 - rng.random::<f64>(), rng.random());
+    (-2.0 * u.ln()).sqrt() * (std::f64::consts::TAU * v).cos()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lint::PLACEHOLDERS;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_every_placeholder_is_parsed() {
+        for (placeholder, kind, _) in PLACEHOLDERS {
+            assert_eq!(Template::parse(placeholder).nodes, [node(kind)]);
+            if let Node::Word(list) = node(kind) {
+                assert_eq!(list.placeholder(), placeholder);
+            }
+        }
+    }
+
+    #[test]
+    fn t_parse_nodes() {
+        let template = Template::parse("XNAMEX fixed XNUM1,5X bugs, XNUM1,2,3X and XNUM5 left");
+        assert_eq!(
+            template.nodes(),
+            [
+                Node::Name(NameCase::AsIs),
+                Node::Literal(" fixed "),
+                Node::Number(lint::parse_number_range("1,5").unwrap()),
+                Node::Literal(" bugs, "),
+                Node::Malformed {
+                    text: "XNUM1,2,3X",
+                    problem: "a range has a single comma between its bounds".to_string()
+                },
+                Node::Literal(" and XNUM5 left"),
+            ]
+        );
+        assert!(Template::parse("").nodes().is_empty());
+    }
+
+    #[test]
+    fn t_render_scans_once() {
+        let mut rng = StdRng::seed_from_u64(42);
+        // Substituted names are not placeholders, whatever they look like
+        assert_eq!(
+            Template::parse("XUPPERNAMEX and XNAMEX").render("xnamex", &mut rng),
+            "XNAMEX and xnamex"
+        );
+        assert_eq!(
+            Template::parse("XNAMEX").render("XNUM5X", &mut rng),
+            "XNUM5X"
+        );
+        // Placeholders are read left to right, so a name ending in X doesn't start a number
+        assert_eq!(
+            Template::parse("XNAMEXNUM5X").render("Ann", &mut rng),
+            "AnnNUM5X"
+        );
+    }
+
+    #[test]
+    fn t_render_into_reuses_buffer() {
+        let template = Template::parse("XTITLENAMEX fixed XNUM1,5X bugs");
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut buffer = "> ".to_string();
+        template.render_into("ann", &mut rng, &mut buffer);
+        assert_eq!(
+            buffer,
+            format!(
+                "> {}",
+                template.render("ann", &mut StdRng::seed_from_u64(42))
+            )
+        );
+        buffer.clear();
+        let capacity = buffer.capacity();
+        template.render_into("ann", &mut rng, &mut buffer);
+        assert!(buffer.starts_with("Ann fixed "));
+        assert_eq!(buffer.capacity(), capacity);
+    }
+
+    #[test]
+    fn t_render_company() {
+        let template = Template::parse("ported everything to XCOMPANYX's new framework");
+        assert_eq!(template.nodes()[1], Node::Company);
+        let mut rng = StdRng::seed_from_u64(42);
+        let message = template.render("Ann", &mut rng);
+        let company = message
+            .strip_prefix("ported everything to ")
+            .and_then(|m| m.strip_suffix("'s new framework"))
+            .unwrap();
+        assert_eq!(company, company::generate(&mut StdRng::seed_from_u64(42)));
+    }
+
+    #[test]
+    fn t_render_language() {
+        let template = Template::parse("rewrote it in XLANGX, again");
+        assert_eq!(template.nodes()[1], Node::Word(List::Language));
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..20 {
+            let message = template.render("Ann", &mut rng);
+            let language = message
+                .strip_prefix("rewrote it in ")
+                .and_then(|m| m.strip_suffix(", again"))
+                .unwrap();
+            assert!(words::get()
+                .list(List::Language)
+                .iter()
+                .any(|l| l == language));
+        }
+    }
+
+    #[test]
+    fn t_render_ip_addresses() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let message =
+            Template::parse("hardcoded XIPX and XIP6X, will fix later").render("Ann", &mut rng);
+        let words: Vec<&str> = message.split([' ', ',']).collect();
+        assert!(words[1]
+            .parse::<std::net::Ipv4Addr>()
+            .unwrap()
+            .is_documentation());
+        assert!(words[3].parse::<std::net::Ipv6Addr>().is_ok());
+        assert!(message.ends_with(", will fix later"));
+    }
+
+    #[test]
+    fn t_render_uuids() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let message = Template::parse("flag XUUIDX, request XSHORTUUIDX").render("Ann", &mut rng);
+        let mut expected = StdRng::seed_from_u64(42);
+        assert_eq!(
+            message,
+            format!(
+                "flag {}, request {}",
+                uuid::v4(&mut expected),
+                uuid::short(&mut expected)
+            )
+        );
+    }
+
+    #[test]
+    fn t_render_path() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let message = Template::parse("moved XPATHX again").render("Ann", &mut rng);
+        let path = message
+            .strip_prefix("moved ")
+            .and_then(|m| m.strip_suffix(" again"))
+            .unwrap();
+        assert!(path.contains('/') && path.contains('.'));
+    }
+
+    #[test]
+    fn t_render_url() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let message =
+            Template::parse("copied from XURLX without reading it").render("Ann", &mut rng);
+        assert!(message.starts_with("copied from https://"));
+        assert!(message.ends_with(" without reading it"));
+    }
+
+    #[test]
+    fn t_render_parts_of_speech() {
+        let template = Template::parse("XVERBX the XADJX XNOUNX");
+        assert_eq!(
+            template.nodes(),
+            [
+                Node::Word(List::Verb),
+                Node::Literal(" the "),
+                Node::Word(List::Adjective),
+                Node::Literal(" "),
+                Node::Word(List::Noun),
+            ]
+        );
+        let mut rng = StdRng::seed_from_u64(42);
+        let messages: Vec<String> = (0..20).map(|_| template.render("Ann", &mut rng)).collect();
+        assert!(messages
+            .iter()
+            .all(|m| !m.contains('X') && m.contains(" the ")));
+        assert!(messages.iter().any(|m| m != &messages[0]));
+    }
+
+    #[test]
+    fn t_roman_numerals() {
+        let roman = |number| {
+            let mut out = String::new();
+            push_roman(number, &mut out);
+            out
+        };
+        assert_eq!(roman(3), "III");
+        assert_eq!(roman(14), "XIV");
+        assert_eq!(roman(1994), "MCMXCIV");
+        assert_eq!(roman(lint::ROMAN_LIMIT), "MMMCMXCIX");
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let template = Template::parse("attempt XNUM3,20:romanX");
+        for _ in 0..50 {
+            let message = template.render("Ann", &mut rng);
+            let numeral = message.strip_prefix("attempt ").unwrap();
+            assert!((3..=20).any(|n| roman(n) == numeral), "{}", numeral);
+        }
+    }
+
+    #[test]
+    fn t_ordinals() {
+        let ordinal = |n: i128| format!("{}{}", n, ordinal_suffix(n));
+        let ordinals: Vec<String> = [0, 1, 2, 3, 4, 11, 12, 13, 21, 22, 101, 111, 112, -3]
+            .into_iter()
+            .map(ordinal)
+            .collect();
+        assert_eq!(
+            ordinals,
+            [
+                "0th", "1st", "2nd", "3rd", "4th", "11th", "12th", "13th", "21st", "22nd", "101st",
+                "111th", "112th", "-3rd"
+            ]
+        );
+        let mut rng = StdRng::seed_from_u64(42);
+        let message = Template::parse("the XNUM3,3:ordinalX time").render("Ann", &mut rng);
+        assert_eq!(message, "the 3rd time");
+    }
+
+    #[test]
+    fn t_percent_and_currency() {
+        let currency = |dollars, cents| {
+            let mut out = String::new();
+            push_currency(dollars, cents, &mut out);
+            out
+        };
+        assert_eq!(currency(1234, 56), "$1,234.56");
+        assert_eq!(currency(0, 5), "$0.05");
+        assert_eq!(currency(999, 0), "$999.00");
+        assert_eq!(currency(1_000_000, 99), "$1,000,000.99");
+        assert_eq!(currency(-12345, 10), "-$12,345.10");
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let message = Template::parse("XNUM40,40:percentX faster, XNUM5000,5000:currencyX a month")
+            .render("Ann", &mut rng);
+        let bill = message.strip_prefix("40% faster, $5,000.").unwrap();
+        assert_eq!(bill.len(), " a month".len() + 2);
+    }
+
+    #[test]
+    fn t_template_variables() {
+        let template =
+            Template::parse("{{set n = num:1..10}}estimated {{n}} hours, took {{ n*10 }}");
+        assert!(matches!(
+            template.nodes()[0],
+            Node::Directive(Directive::Set { name: "n", .. })
+        ));
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..20 {
+            let message = template.render("Ann", &mut rng);
+            let numbers: Vec<i128> = message
+                .split(|c: char| !c.is_ascii_digit())
+                .filter_map(|n| n.parse().ok())
+                .collect();
+            assert!((1..=10).contains(&numbers[0]));
+            assert_eq!(numbers[1], numbers[0] * 10);
+        }
+        // Variables can't be used before they are set, and unknown directives are kept
+        let message = Template::parse("{{n}} then {{set n = num:5..5}}{{n-1}}, {{oops!}}")
+            .render("Ann", &mut rng);
+        assert_eq!(message, "{{n}} then 4, {{oops!}}");
+    }
+
+    #[test]
+    fn t_pronouns_follow_name() {
+        let template = Template::parse("XNAMEX says XPRONOUNX tested it on XPOSSESSIVEX laptop");
+        let mut rng = StdRng::seed_from_u64(42);
+        assert_eq!(
+            template.render("Ann (she/her)", &mut rng),
+            "Ann says she tested it on her laptop"
+        );
+        assert_eq!(
+            template.render("Bo", &mut rng),
+            "Bo says they tested it on their laptop"
+        );
+    }
+
+    #[test]
+    fn t_optional_text() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let always = Template::parse("{{100%: XUPPERNAMEX: }}done{{0%: , never}}");
+        assert_eq!(always.render("Ann", &mut rng), "ANN: done");
+        let half = Template::parse("fixed it{{50%: , I think}}");
+        let hedged = (0..1000)
+            .filter(|_| half.render("Ann", &mut rng) == "fixed it, I think")
+            .count();
+        assert!((400..600).contains(&hedged));
+        // Nested directives aren't allowed, so the whole directive is kept as written
+        let message = Template::parse("x{{50%: {{n}}}}").render("Ann", &mut rng);
+        assert!(message.starts_with("x{{50%: {{n}}"));
+
+        // Text holding a required placeholder is always written
+        let required = Template::parse("fixed it {{1%: for XNAMEX}}{{requires: XNAMEX}}{{0%: !}}");
+        assert!((0..100).all(|_| required.render("Ann", &mut rng) == "fixed it for Ann"));
+    }
+
+    #[test]
+    fn t_repeat_lists() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let template = Template::parse("touched {{repeat 3: XPATHX, }} again");
+        let message = template.render("Ann", &mut rng);
+        let paths = message
+            .strip_prefix("touched ")
+            .and_then(|m| m.strip_suffix(" again"))
+            .unwrap();
+        assert_eq!(paths.split(", ").count(), 3);
+        assert!(!paths.ends_with(", "));
+        // Text with no placeholders is repeated whole
+        let message =
+            Template::parse("{{repeat 3: ha}}{{repeat 0: XNAMEX}}").render("Ann", &mut rng);
+        assert_eq!(message, "hahaha");
+    }
+
+    #[test]
+    fn t_consistent_repeats() {
+        let template =
+            Template::parse("XNUM1000000X, XNUM1000000X and XNUM999999X at XUUIDX, XUUIDX");
+        let render = |consistent| {
+            let mut out = String::new();
+            let mut drawn = Vec::new();
+            let mut rng = StdRng::seed_from_u64(42);
+            template.render_nodes("Ann", &mut rng, &mut out, consistent, Some(&mut drawn));
+            (out, drawn.len())
+        };
+        let (message, drawn) = render(true);
+        // Only numbers drawn anew are explained, not those repeated
+        assert_eq!(drawn, 2);
+        let (numbers, uuids) = message.split_once(" at ").unwrap();
+        let numbers: Vec<&str> = numbers
+            .split([',', ' '])
+            .filter(|s| !s.is_empty())
+            .collect();
+        assert_eq!(numbers[0], numbers[1]);
+        assert_ne!(numbers[0], numbers[3]);
+        let (first, second) = uuids.split_once(", ").unwrap();
+        assert_eq!(first, second);
+
+        let (message, drawn) = render(false);
+        assert_eq!(drawn, 3);
+        let (_, uuids) = message.split_once(" at ").unwrap();
+        let (first, second) = uuids.split_once(", ").unwrap();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn t_number_distributions() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let draw = |spec: &str, rng: &mut StdRng| -> Vec<i128> {
+            let range = lint::parse_number_range(spec).unwrap();
+            (0..2_000)
+                .map(|_| generate_random_in_range(&range, rng))
+                .collect()
+        };
+        // Log-uniform gives small numbers about as often as huge ones
+        let log = draw("1,100000:log", &mut rng);
+        assert!(log.iter().all(|n| (1..=100_000).contains(n)));
+        let small = log.iter().filter(|&&n| n < 100).count();
+        assert!((600..=1_000).contains(&small), "{}", small);
+
+        // A bell curve keeps most numbers near the middle
+        let normal = draw("0,100:normal", &mut rng);
+        assert!(normal.iter().all(|n| (0..=100).contains(n)));
+        let middle = normal.iter().filter(|&&n| (17..=83).contains(&n)).count();
+        assert!(middle > 1_800, "{}", middle);
+
+        let stepped = draw("0,1000:10:normal", &mut rng);
+        assert!(stepped.iter().all(|n| n % 10 == 0));
+    }
+
+    #[test]
+    fn t_render_signed_and_stepped_numbers() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let template = Template::parse("XNUM-50,50X XNUM0,100:10X XNUM-30,-10:10X");
+        for _ in 0..200 {
+            let message = template.render("Ann", &mut rng);
+            let numbers: Vec<i128> = message.split(' ').map(|n| n.parse().unwrap()).collect();
+            assert!((-50..=50).contains(&numbers[0]));
+            assert!((0..=100).contains(&numbers[1]) && numbers[1] % 10 == 0);
+            assert!([-30, -20, -10].contains(&numbers[2]));
+        }
+        assert_eq!(Template::parse("XNUM7,9:8X").render("Ann", &mut rng), "8");
+    }
+}
@@ -0,0 +1,194 @@
+use crate::lint;
+use rand::prelude::IndexedRandom;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui::Frame;
+
+/// Number of expansions shown under the template.
+const PREVIEW_COUNT: usize = 3;
+
+/// A single-line template editor that highlights placeholders, flags malformed ones and
+/// previews expansions as the template is typed.
+pub struct TemplateEditor {
+    text: String,
+    /// Cursor position in characters.
+    cursor: usize,
+    /// Seed of the preview, kept fixed while typing so that the expansions don't flicker.
+    seed: u64,
+}
+
+impl TemplateEditor {
+    pub fn new(seed: u64) -> Self {
+        TemplateEditor {
+            text: String::new(),
+            cursor: 0,
+            seed,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the text, leaving the cursor at its end.
+    pub fn load(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = text.chars().count();
+    }
+
+    pub fn reroll(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    fn byte_offset(&self, cursor: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+
+    /// Applies an editing key: typing, deleting and moving the cursor.
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        let len = self.text.chars().count();
+        match key.code {
+            KeyCode::Char(c) => {
+                let at = self.byte_offset(self.cursor);
+                self.text.insert(at, c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                let at = self.byte_offset(self.cursor);
+                self.text.remove(at);
+            }
+            KeyCode::Delete if self.cursor < len => {
+                let at = self.byte_offset(self.cursor);
+                self.text.remove(at);
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(len),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = len,
+            _ => {}
+        }
+    }
+
+    /// The template with valid placeholders highlighted and malformed ones in red.
+    fn highlighted(&self) -> Line<'_> {
+        let valid = Style::new().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+        let invalid = Style::new()
+            .fg(Color::Red)
+            .add_modifier(Modifier::UNDERLINED);
+        let mut spans = Vec::new();
+        let mut at = 0;
+        for placeholder in lint::scan(&self.text) {
+            spans.push(Span::raw(&self.text[at..placeholder.range.start]));
+            let style = match placeholder.problem {
+                Some(_) => invalid,
+                None => valid,
+            };
+            spans.push(Span::styled(&self.text[placeholder.range.clone()], style));
+            at = placeholder.range.end;
+        }
+        spans.push(Span::raw(&self.text[at..]));
+        Line::from(spans)
+    }
+
+    /// Expands the template a few times with the preview seed.
+    fn expansions(&self, names: &[String]) -> Vec<String> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        (0..PREVIEW_COUNT)
+            .filter_map(|_| {
+                let name = names.choose(&mut rng)?;
+                Some(crate::substitute_placeholders(&self.text, name, &mut rng))
+            })
+            .collect()
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect, names: &[String]) {
+        let [edit_area, problems_area, preview_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(5),
+            Constraint::Min(3),
+        ])
+        .areas(area);
+
+        frame.render_widget(
+            Paragraph::new(self.highlighted()).block(Block::bordered().title(" template ")),
+            edit_area,
+        );
+        let before_cursor = Line::from(&self.text[..self.byte_offset(self.cursor)]).width();
+        frame.set_cursor_position(Position::new(
+            edit_area.x + 1 + before_cursor as u16,
+            edit_area.y + 1,
+        ));
+
+        let problems = lint::problems(&self.text);
+        let problems: Vec<Line> = if problems.is_empty() {
+            vec![Line::from("No problems")]
+        } else {
+            problems
+                .into_iter()
+                .map(|p| Line::styled(p, Style::new().fg(Color::Red)))
+                .collect()
+        };
+        frame.render_widget(
+            Paragraph::new(problems)
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(" problems ")),
+            problems_area,
+        );
+
+        let preview: Vec<Line> = self.expansions(names).into_iter().map(Line::from).collect();
+        frame.render_widget(
+            Paragraph::new(preview)
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(" preview ")),
+            preview_area,
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratatui::crossterm::event::KeyModifiers;
+
+    fn press(editor: &mut TemplateEditor, code: KeyCode) {
+        editor.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn t_editing_moves_the_cursor() {
+        let mut editor = TemplateEditor::new(0);
+        editor.load("fixé XNAMEX");
+        press(&mut editor, KeyCode::Home);
+        press(&mut editor, KeyCode::Delete);
+        press(&mut editor, KeyCode::Char('F'));
+        press(&mut editor, KeyCode::End);
+        press(&mut editor, KeyCode::Backspace);
+        assert_eq!(editor.text(), "Fixé XNAME");
+        for _ in 0..6 {
+            press(&mut editor, KeyCode::Left);
+        }
+        press(&mut editor, KeyCode::Backspace);
+        assert_eq!(editor.text(), "Fix XNAME");
+    }
+
+    #[test]
+    fn t_preview_is_stable_until_rerolled() {
+        let names: Vec<String> = ["Ann", "Bob", "Cy"].map(String::from).to_vec();
+        let mut editor = TemplateEditor::new(1);
+        editor.load("XNAMEX rolled XNUMX");
+        let first = editor.expansions(&names);
+        assert_eq!(first.len(), PREVIEW_COUNT);
+        assert_eq!(editor.expansions(&names), first);
+        editor.reroll(2);
+        assert_ne!(editor.expansions(&names), first);
+    }
+}
@@ -0,0 +1,89 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Number of grapheme clusters in `text`, which is what people count as characters: an
+/// accented letter or a flag emoji counts once, however many code points it is made of.
+pub fn length(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+/// Number of terminal columns `text` takes up. CJK characters and most emoji take two.
+pub fn width(text: &str) -> usize {
+    text.width()
+}
+
+/// The longest start of `text` that is at most `width` columns wide, never splitting a
+/// grapheme cluster.
+pub fn truncate(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (i, grapheme) in text.grapheme_indices(true) {
+        used += grapheme.width();
+        if used > width {
+            return &text[..i];
+        }
+    }
+    text
+}
+
+/// Splits `text` into pieces at most `width` columns wide, for words too long for a line.
+/// A grapheme cluster wider than `width` gets a piece of its own.
+pub fn chunks(mut text: &str, width: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    while !text.is_empty() {
+        let mut piece = truncate(text, width);
+        if piece.is_empty() {
+            piece = text.graphemes(true).next().unwrap_or(text);
+        }
+        pieces.push(piece);
+        text = &text[piece.len()..];
+    }
+    pieces
+}
+
+/// Greedily wraps text into lines of at most `width` columns, breaking
+/// words that are wider than a whole line.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        // Words too wide for a line are broken up, keeping only the last piece for the
+        // current line
+        let mut pieces = chunks(word, width);
+        let word = pieces.pop().unwrap_or_default();
+        if !pieces.is_empty() && !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+        }
+        lines.extend(pieces.into_iter().map(String::from));
+        let len = current.width();
+        if len > 0 && len + 1 + word.width() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_graphemes_and_widths() {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        assert_eq!(length(&format!("fix {}", family)), 5);
+        assert_eq!(length("cafe\u{301}"), 4);
+        assert_eq!(width("修复错误"), 8);
+
+        assert_eq!(truncate("修复错误", 5), "修复");
+        assert_eq!(truncate(&format!("a{}b", family), 2), "a");
+        assert_eq!(truncate("cafe\u{301}!", 4), "cafe\u{301}");
+        assert_eq!(chunks("修复错误了", 4), vec!["修复", "错误", "了"]);
+        assert_eq!(chunks("修复", 1), vec!["修", "复"]);
+    }
+}
@@ -0,0 +1,244 @@
+use crate::emoji::is_emoji;
+use crate::name::NameCase;
+use anstyle::{AnsiColor, Style};
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// Built-in color themes for highlighting parts of a generated message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    /// Bold message with colored names, numbers and emoji
+    #[default]
+    Default,
+    /// Cool blues and greens
+    Ocean,
+    /// No colors, only bold and underline
+    Mono,
+}
+
+/// Styles applied to the different kinds of spans in a message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub text: Style,
+    pub name: Style,
+    pub number: Style,
+    pub emoji: Style,
+}
+
+impl From<ThemeName> for Theme {
+    fn from(name: ThemeName) -> Self {
+        let bold = Style::new().bold();
+        let color = |c: AnsiColor| bold.fg_color(Some(c.into()));
+        match name {
+            ThemeName::Default => Theme {
+                text: bold,
+                name: color(AnsiColor::Cyan),
+                number: color(AnsiColor::Yellow),
+                emoji: color(AnsiColor::Magenta),
+            },
+            ThemeName::Ocean => Theme {
+                text: Style::new().fg_color(Some(AnsiColor::BrightWhite.into())),
+                name: color(AnsiColor::BrightBlue),
+                number: color(AnsiColor::BrightGreen),
+                emoji: color(AnsiColor::BrightCyan),
+            },
+            ThemeName::Mono => Theme {
+                text: bold,
+                name: bold.underline(),
+                number: bold.underline(),
+                emoji: bold,
+            },
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        ThemeName::default().into()
+    }
+}
+
+/// Per-span style overrides from the config file's `[colors]` table.
+///
+/// Each value is a space-separated list of effects (`bold`, `dim`, `italic`,
+/// `underline`) and at most one color name such as `cyan` or `bright-blue`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorOverrides {
+    pub text: Option<String>,
+    pub name: Option<String>,
+    pub number: Option<String>,
+    pub emoji: Option<String>,
+}
+
+/// Parses a style specification like `"bold bright-blue"`.
+fn parse_style(spec: &str) -> Result<Style, String> {
+    let mut style = Style::new();
+    for word in spec.split_whitespace() {
+        style = match word {
+            "bold" => style.bold(),
+            "dim" => style.dimmed(),
+            "italic" => style.italic(),
+            "underline" => style.underline(),
+            color => {
+                let (bright, base) = match color.strip_prefix("bright-") {
+                    Some(base) => (true, base),
+                    None => (false, color),
+                };
+                let color = match base {
+                    "black" => AnsiColor::Black,
+                    "red" => AnsiColor::Red,
+                    "green" => AnsiColor::Green,
+                    "yellow" => AnsiColor::Yellow,
+                    "blue" => AnsiColor::Blue,
+                    "magenta" => AnsiColor::Magenta,
+                    "cyan" => AnsiColor::Cyan,
+                    "white" => AnsiColor::White,
+                    _ => return Err(format!("unknown color or effect `{}`", word)),
+                };
+                style.fg_color(Some(color.bright(bright).into()))
+            }
+        };
+    }
+    Ok(style)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Span {
+    Text,
+    Name,
+    Number,
+    Emoji,
+}
+
+impl Theme {
+    /// Applies style overrides on top of this theme.
+    pub fn with_overrides(mut self, overrides: &ColorOverrides) -> Result<Self, String> {
+        for (style, spec) in [
+            (&mut self.text, &overrides.text),
+            (&mut self.name, &overrides.name),
+            (&mut self.number, &overrides.number),
+            (&mut self.emoji, &overrides.emoji),
+        ] {
+            if let Some(spec) = spec {
+                *style = parse_style(spec)?;
+            }
+        }
+        Ok(self)
+    }
+
+    fn style(&self, span: Span) -> Style {
+        match span {
+            Span::Text => self.text,
+            Span::Name => self.name,
+            Span::Number => self.number,
+            Span::Emoji => self.emoji,
+        }
+    }
+
+    /// Styles a message, highlighting occurrences of `name` (in any of the
+    /// substituted casings), numbers and emoji.
+    pub fn highlight(&self, message: &str, name: &str) -> String {
+        self.spans(message, name)
+            .into_iter()
+            .map(|(style, text)| format!("{style}{}{style:#}", text))
+            .collect()
+    }
+
+    /// Splits a message into styled spans: occurrences of `name`, numbers, emoji and
+    /// the text in between.
+    pub fn spans<'m>(&self, message: &'m str, name: &str) -> Vec<(Style, &'m str)> {
+        let mut variants: Vec<String> = NameCase::value_variants()
+            .iter()
+            .map(|case| case.apply(name))
+            .collect();
+        variants.retain(|v| !v.is_empty());
+        variants.sort_by_key(|v| std::cmp::Reverse(v.len()));
+
+        // Adjacent characters of the same kind are merged into one span
+        let mut spans: Vec<(Span, usize, usize)> = Vec::new();
+        let mut pos = 0;
+        while pos < message.len() {
+            let rest = &message[pos..];
+            let c = rest.chars().next().unwrap_or_default();
+            let (span, len) =
+                if let Some(v) = variants.iter().find(|v| rest.starts_with(v.as_str())) {
+                    (Span::Name, v.len())
+                } else if c.is_ascii_digit() {
+                    let len = rest
+                        .find(|c: char| !c.is_ascii_digit())
+                        .unwrap_or(rest.len());
+                    (Span::Number, len)
+                } else if is_emoji(c) {
+                    (Span::Emoji, c.len_utf8())
+                } else {
+                    (Span::Text, c.len_utf8())
+                };
+            match spans.last_mut() {
+                Some((last, _, end)) if *last == span => *end += len,
+                _ => spans.push((span, pos, pos + len)),
+            }
+            pos += len;
+        }
+
+        spans
+            .into_iter()
+            .map(|(span, start, end)| (self.style(span), &message[start..end]))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_highlight_spans() {
+        let theme = Theme {
+            text: Style::new(),
+            name: Style::new().bold(),
+            number: Style::new().italic(),
+            emoji: Style::new().underline(),
+        };
+        assert_eq!(
+            theme.highlight("Bob fixed 42 bugs 🎉", "Bob"),
+            "\x1b[1mBob\x1b[0m fixed \x1b[3m42\x1b[0m bugs \x1b[4m🎉\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn t_overrides_replace_styles() {
+        let overrides = ColorOverrides {
+            name: Some("bold bright-blue".to_string()),
+            ..Default::default()
+        };
+        let theme = Theme::from(ThemeName::Default)
+            .with_overrides(&overrides)
+            .unwrap();
+        assert_eq!(
+            theme.name,
+            Style::new()
+                .bold()
+                .fg_color(Some(AnsiColor::BrightBlue.into()))
+        );
+        assert_eq!(theme.number, Theme::from(ThemeName::Default).number);
+
+        let invalid = ColorOverrides {
+            text: Some("sparkly".to_string()),
+            ..Default::default()
+        };
+        assert!(Theme::from(ThemeName::Default)
+            .with_overrides(&invalid)
+            .is_err());
+    }
+
+    #[test]
+    fn t_highlight_name_casings() {
+        let theme = Theme::from(ThemeName::Mono);
+        let highlighted = theme.highlight("BOB and bob", "Bob");
+        let name = theme.name;
+        assert!(highlighted.starts_with(&format!("{name}BOB{name:#}")));
+        assert!(highlighted.ends_with(&format!("{name}bob{name:#}")));
+    }
+}
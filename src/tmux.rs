@@ -0,0 +1,85 @@
+use crate::animation;
+use crate::theme::Theme;
+use anstyle::{AnsiColor, Color, Effects, Style};
+
+/// Escapes `#` so that tmux doesn't read the message as a format.
+fn escape(text: &str) -> String {
+    text.replace('#', "##")
+}
+
+fn color_name(color: AnsiColor) -> &'static str {
+    match color {
+        AnsiColor::Black => "black",
+        AnsiColor::Red => "red",
+        AnsiColor::Green => "green",
+        AnsiColor::Yellow => "yellow",
+        AnsiColor::Blue => "blue",
+        AnsiColor::Magenta => "magenta",
+        AnsiColor::Cyan => "cyan",
+        AnsiColor::White => "white",
+        AnsiColor::BrightBlack => "brightblack",
+        AnsiColor::BrightRed => "brightred",
+        AnsiColor::BrightGreen => "brightgreen",
+        AnsiColor::BrightYellow => "brightyellow",
+        AnsiColor::BrightBlue => "brightblue",
+        AnsiColor::BrightMagenta => "brightmagenta",
+        AnsiColor::BrightCyan => "brightcyan",
+        AnsiColor::BrightWhite => "brightwhite",
+    }
+}
+
+/// Translates a terminal style into a tmux style directive such as `#[default,fg=cyan,bold]`.
+fn directive(style: Style) -> String {
+    let mut parts = vec!["default".to_string()];
+    match style.get_fg_color() {
+        Some(Color::Ansi(color)) => parts.push(format!("fg={}", color_name(color))),
+        Some(Color::Ansi256(color)) => parts.push(format!("fg=colour{}", color.0)),
+        Some(Color::Rgb(rgb)) => parts.push(format!("fg=#{:02x}{:02x}{:02x}", rgb.0, rgb.1, rgb.2)),
+        None => {}
+    }
+    let effects = style.get_effects();
+    for (effect, name) in [
+        (Effects::BOLD, "bold"),
+        (Effects::DIMMED, "dim"),
+        (Effects::ITALIC, "italics"),
+        (Effects::UNDERLINE, "underscore"),
+    ] {
+        if effects.contains(effect) {
+            parts.push(name.to_string());
+        }
+    }
+    format!("#[{}]", parts.join(","))
+}
+
+/// Renders a message for a tmux status line: the first line, shortened to `max_width`
+/// characters, escaped and styled with tmux directives when a theme is given.
+pub fn render(message: &str, name: &str, theme: Option<&Theme>, max_width: usize) -> String {
+    let short = animation::fit(message, max_width);
+    match theme {
+        Some(theme) => {
+            let styled: String = theme
+                .spans(&short, name)
+                .into_iter()
+                .map(|(style, text)| format!("{}{}", directive(style), escape(text)))
+                .collect();
+            format!("{}#[default]", styled)
+        }
+        None => escape(&short),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::theme::ThemeName;
+
+    #[test]
+    fn t_tmux_status() {
+        let theme = Theme::from(ThemeName::Default);
+        assert_eq!(
+            render("Bob fixed #12", "Bob", Some(&theme), 40),
+            "#[default,fg=cyan,bold]Bob#[default,bold] fixed ###[default,fg=yellow,bold]12#[default]"
+        );
+        assert_eq!(render("fixed #12\nbody", "", None, 40), "fixed ##12");
+    }
+}
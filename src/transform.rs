@@ -0,0 +1,154 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// A joke rewrite of a whole rendered message. Several can be given, and each works on
+/// what the one before it wrote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Transform {
+    /// aLtErNaTiNg CaSe, like the mocking SpongeBob meme
+    Mock,
+    /// Some letters swapped for look-alike digits, like l33t 5p34k
+    Leet,
+    /// Every letter moved 13 places along the alphabet, so doing it twice undoes it
+    Rot13,
+    /// Each word's first consonants moved to its end, followed by "ay"
+    Piglatin,
+}
+
+impl Transform {
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            Transform::Mock => mock(text),
+            Transform::Leet => leet(text),
+            Transform::Rot13 => rot13(text),
+            Transform::Piglatin => pig_latin(text),
+        }
+    }
+}
+
+/// Runs `message` through every transform in turn.
+pub fn apply_all(transforms: &[Transform], message: String) -> String {
+    transforms
+        .iter()
+        .fold(message, |message, transform| transform.apply(&message))
+}
+
+/// Alternates lowercase and uppercase letters, starting lowercase, so "John" becomes
+/// "jOhN". Anything that isn't a letter is kept and doesn't count.
+pub fn mock(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut upper = false;
+    for c in text.chars() {
+        if !c.is_alphabetic() {
+            out.push(c);
+            continue;
+        }
+        if upper {
+            out.extend(c.to_uppercase());
+        } else {
+            out.extend(c.to_lowercase());
+        }
+        upper = !upper;
+    }
+    out
+}
+
+/// Swaps the letters with a digit that looks like them, in either case.
+fn leet(text: &str) -> String {
+    text.chars()
+        .map(|c| match c.to_ascii_lowercase() {
+            'a' => '4',
+            'e' => '3',
+            'i' => '1',
+            'o' => '0',
+            's' => '5',
+            't' => '7',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Rotates the ASCII letters 13 places, leaving everything else alone.
+fn rot13(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'a'..='z' => (b'a' + (c as u8 - b'a' + 13) % 26) as char,
+            'A'..='Z' => (b'A' + (c as u8 - b'A' + 13) % 26) as char,
+            _ => c,
+        })
+        .collect()
+}
+
+/// Rewrites each run of ASCII letters in Pig Latin, keeping everything between them.
+fn pig_latin(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + text.len() / 2);
+    let mut rest = text;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_alphabetic()) {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        push_pig_latin(&rest[..end], &mut out);
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Writes one word in Pig Latin: "string" becomes "ingstray", "apple" "appleway" and
+/// "quit" "itquay". A capitalized word stays capitalized, and a shouted one shouted.
+fn push_pig_latin(word: &str, out: &mut String) {
+    let lower = word.to_ascii_lowercase();
+    let is_vowel = |(i, c): (usize, char)| "aeiou".contains(c) || (i > 0 && c == 'y');
+    let mut split = lower
+        .char_indices()
+        .position(is_vowel)
+        .unwrap_or(lower.len());
+    // The "u" of "qu" sounds like a consonant, so it moves with the "q"
+    if split > 0 && lower[split - 1..].starts_with("qu") {
+        split += 1;
+    }
+    let suffix = if split == 0 { "way" } else { "ay" };
+    let pig = format!("{}{}{}", &lower[split..], &lower[..split], suffix);
+    let shouted = word.len() > 1 && !word.contains(|c: char| c.is_ascii_lowercase());
+    if shouted {
+        out.push_str(&pig.to_ascii_uppercase());
+    } else if word.starts_with(|c: char| c.is_ascii_uppercase()) {
+        out.push(pig.as_bytes()[0].to_ascii_uppercase() as char);
+        out.push_str(&pig[1..]);
+    } else {
+        out.push_str(&pig);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_transforms_compose() {
+        assert_eq!(mock("John Smith's fix"), "jOhN sMiTh'S fIx");
+        assert_eq!(Transform::Leet.apply("Elite hackers"), "3l173 h4ck3r5");
+        assert_eq!(
+            apply_all(&[Transform::Mock, Transform::Leet], "just testing".into()),
+            "jU57 73571Ng"
+        );
+        assert_eq!(apply_all(&[], "as is".into()), "as is");
+    }
+
+    #[test]
+    fn t_rot13_and_pig_latin() {
+        assert_eq!(rot13("Fixed it, Zed!"), "Svkrq vg, Mrq!");
+        assert_eq!(rot13(&rot13("Any message 42")), "Any message 42");
+        assert_eq!(
+            pig_latin("Quit string apple, rhythm. NO-OP!"),
+            "Itquay ingstray appleway, ythmrhay. ONAY-OPWAY!"
+        );
+        assert_eq!(
+            apply_all(&[Transform::Piglatin, Transform::Rot13], "hi".into()),
+            "vunl"
+        );
+    }
+}
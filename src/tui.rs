@@ -0,0 +1,572 @@
+use crate::favorites;
+use crate::keys::{KeyAction, Keymap};
+use crate::search;
+use crate::squash;
+use crate::stats::{self, CorpusStats, HEATMAP_PLACEHOLDERS, LENGTH_BUCKETS};
+use crate::template_editor::TemplateEditor;
+use crate::Generated;
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::prelude::CrosstermBackend;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Cell, List, ListItem, ListState, Paragraph, Row, Table, Tabs};
+use ratatui::{Frame, Terminal};
+use std::io::{self, Stderr, Write};
+
+/// Number of messages generated at a time.
+const BATCH_SIZE: usize = 20;
+
+const HELP: &str = "↑/↓ move  ←/→ earlier/later batch  tab switch view  / filter";
+
+const EDITOR_HELP: &str = "tab switch view  ctrl-r new preview  enter print template  esc back";
+
+/// What to do once the browser is closed.
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    Print(String),
+    Commit(String),
+    Edit(String),
+    Quit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum View {
+    Generated,
+    Templates,
+    Editor,
+    Dashboard,
+}
+
+struct App<'a> {
+    view: View,
+    /// Every batch generated this session, so earlier ones can be revisited.
+    batches: Vec<Vec<Generated>>,
+    /// Index of the batch being shown.
+    batch: usize,
+    templates: &'a [String],
+    names: &'a [String],
+    editor: TemplateEditor,
+    corpus_stats: CorpusStats,
+    filter: String,
+    editing_filter: bool,
+    state: ListState,
+    status: String,
+    keys: Keymap,
+    /// Marked entries from any view or batch, in the order they were marked.
+    marked: Vec<String>,
+}
+
+impl<'a> App<'a> {
+    fn new(
+        generated: Vec<Generated>,
+        templates: &'a [String],
+        names: &'a [String],
+        keys: Keymap,
+    ) -> Self {
+        App {
+            view: View::Generated,
+            batches: vec![generated],
+            batch: 0,
+            templates,
+            names,
+            editor: TemplateEditor::new(rand::random()),
+            corpus_stats: stats::corpus(templates),
+            filter: String::new(),
+            editing_filter: false,
+            state: ListState::default().with_selected(Some(0)),
+            status: String::new(),
+            keys,
+            marked: Vec::new(),
+        }
+    }
+
+    /// The entries of the current view matching the filter, as (template index, text) pairs.
+    fn entries(&self) -> Vec<(usize, &str)> {
+        let terms: Vec<String> = self.filter.split_whitespace().map(String::from).collect();
+        let all: Vec<(usize, &str)> = match self.view {
+            View::Generated => self.batches[self.batch]
+                .iter()
+                .map(|g| (g.template_index, g.message.as_str()))
+                .collect(),
+            View::Templates => self
+                .templates
+                .iter()
+                .enumerate()
+                .map(|(i, t)| (i, t.as_str()))
+                .collect(),
+            View::Editor | View::Dashboard => Vec::new(),
+        };
+        all.into_iter()
+            .filter(|(_, text)| search::matches(text, &terms))
+            .collect()
+    }
+
+    fn selected(&self) -> Option<String> {
+        let index = self.state.selected()?;
+        self.entries().get(index).map(|(_, text)| text.to_string())
+    }
+
+    /// The marked entries squashed into one message, or the selected entry if none are
+    /// marked.
+    fn picked(&self) -> Option<String> {
+        if self.marked.is_empty() {
+            self.selected()
+        } else {
+            Some(squash::message(&self.marked))
+        }
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        let len = self.entries().len();
+        if len == 0 {
+            self.state.select(None);
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0) as isize;
+        let next = (current + delta).clamp(0, len as isize - 1);
+        self.state.select(Some(next as usize));
+    }
+
+    fn switch_view(&mut self, view: View) {
+        self.view = view;
+        self.state.select(Some(0));
+        self.move_by(0);
+    }
+
+    /// Shows the batch `delta` steps earlier or later in the session, if there is one.
+    fn step_batch(&mut self, delta: isize) {
+        let Some(batch) = self
+            .batch
+            .checked_add_signed(delta)
+            .filter(|&b| b < self.batches.len())
+        else {
+            self.status = "No more batches".to_string();
+            return;
+        };
+        self.batch = batch;
+        self.view = View::Generated;
+        self.state.select(Some(0));
+        self.move_by(0);
+        self.status = format!("Batch {}/{}", batch + 1, self.batches.len());
+    }
+
+    /// Applies a key press, returning the action that ends the session, if any.
+    fn handle_key<F>(&mut self, key: KeyEvent, regenerate: &mut F) -> Option<Action>
+    where
+        F: FnMut(usize) -> Result<Vec<Generated>>,
+    {
+        if self.editing_filter {
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc => self.editing_filter = false,
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Char(c) => self.filter.push(c),
+                _ => {}
+            }
+            self.state.select(Some(0));
+            self.move_by(0);
+            return None;
+        }
+
+        if self.view == View::Editor {
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::Char('c') if ctrl => return Some(Action::Quit),
+                KeyCode::Char('r') if ctrl => self.editor.reroll(rand::random()),
+                KeyCode::Enter if !self.editor.text().is_empty() => {
+                    return Some(Action::Print(self.editor.text().to_string()))
+                }
+                KeyCode::Esc => self.switch_view(View::Templates),
+                KeyCode::Tab => self.switch_view(View::Dashboard),
+                _ => self.editor.handle_key(key),
+            }
+            return None;
+        }
+
+        if let Some(action) = self.keys.action(&key) {
+            match action {
+                KeyAction::Accept => return self.picked().map(Action::Print),
+                KeyAction::Commit => return self.picked().map(Action::Commit),
+                KeyAction::Edit if self.view == View::Templates => {
+                    if let Some(template) = self.selected() {
+                        self.editor.load(&template);
+                        self.switch_view(View::Editor);
+                    }
+                }
+                KeyAction::Edit => return self.picked().map(Action::Edit),
+                KeyAction::Mark => {
+                    if let Some(text) = self.selected() {
+                        match self.marked.iter().position(|m| *m == text) {
+                            Some(at) => {
+                                self.marked.remove(at);
+                            }
+                            None => self.marked.push(text),
+                        }
+                        self.move_by(1);
+                    }
+                }
+                KeyAction::Quit => return Some(Action::Quit),
+                KeyAction::Regenerate => match regenerate(BATCH_SIZE) {
+                    Ok(generated) => {
+                        self.batches.push(generated);
+                        self.batch = self.batches.len() - 1;
+                        self.view = View::Generated;
+                        self.state.select(Some(0));
+                        self.move_by(0);
+                        self.status = format!("Regenerated, batch {}", self.batches.len());
+                    }
+                    Err(e) => self.status = format!("Error: {:#}", e),
+                },
+                KeyAction::Star => {
+                    if let Some(text) = self.selected() {
+                        self.status = favorites::star(&text);
+                    }
+                }
+                KeyAction::Copy => {
+                    if let Some(text) = self.selected() {
+                        self.status = match copy(&text) {
+                            Ok(()) => "Copied to the clipboard".to_string(),
+                            Err(e) => format!("Error: {}", e),
+                        };
+                    }
+                }
+            }
+            return None;
+        }
+
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::PageUp => self.move_by(-10),
+            KeyCode::PageDown => self.move_by(10),
+            KeyCode::Left | KeyCode::Char('h') => self.step_batch(-1),
+            KeyCode::Right | KeyCode::Char('l') => self.step_batch(1),
+            KeyCode::Tab => self.switch_view(match self.view {
+                View::Generated => View::Templates,
+                View::Templates => View::Editor,
+                _ => View::Generated,
+            }),
+            KeyCode::Char('/') => self.editing_filter = true,
+            _ => {}
+        }
+        None
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [tabs_area, list_area, footer_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let generated_tab = match self.batches.len() {
+            1 => "Generated".to_string(),
+            n => format!("Generated {}/{}", self.batch + 1, n),
+        };
+        let tabs = Tabs::new([
+            generated_tab,
+            "Templates".to_string(),
+            "Editor".to_string(),
+            "Dashboard".to_string(),
+        ])
+        .select(match self.view {
+            View::Generated => 0,
+            View::Templates => 1,
+            View::Editor => 2,
+            View::Dashboard => 3,
+        })
+        .highlight_style(Style::new().add_modifier(Modifier::BOLD | Modifier::REVERSED));
+        frame.render_widget(tabs, tabs_area);
+
+        if self.view == View::Editor {
+            self.editor.draw(frame, list_area, self.names);
+            frame.render_widget(Paragraph::new(EDITOR_HELP), footer_area);
+            return;
+        }
+        if self.view == View::Dashboard {
+            self.draw_dashboard(frame, list_area);
+            frame.render_widget(Paragraph::new("tab switch view  q quit"), footer_area);
+            return;
+        }
+
+        let items: Vec<ListItem> = self
+            .entries()
+            .into_iter()
+            .map(|(index, text)| {
+                let marker = if self.marked.iter().any(|m| m == text) {
+                    '*'
+                } else {
+                    ' '
+                };
+                ListItem::new(format!("{} {:>4}  {}", marker, index, text))
+            })
+            .collect();
+        let mut title = String::new();
+        if !self.filter.is_empty() {
+            title += &format!(" filter: {} ", self.filter);
+        }
+        if !self.marked.is_empty() {
+            title += &format!(" {} marked ", self.marked.len());
+        }
+        let list = List::new(items)
+            .block(Block::bordered().title(title))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, list_area, &mut self.state);
+
+        let footer = if self.editing_filter {
+            format!("/{}", self.filter)
+        } else if !self.status.is_empty() {
+            self.status.clone()
+        } else {
+            format!("{}  {}", HELP, self.keys.help())
+        };
+        frame.render_widget(Paragraph::new(Line::from(footer)), footer_area);
+    }
+}
+
+impl App<'_> {
+    /// Draws the corpus summary: lengths, the shortest and longest templates, and a heatmap
+    /// of placeholder use by template length.
+    fn draw_dashboard(&self, frame: &mut Frame, area: Rect) {
+        let stats = &self.corpus_stats;
+        let [summary_area, extremes_area, heatmap_area] = Layout::vertical([
+            Constraint::Length(4),
+            Constraint::Length(2 * stats.shortest.len() as u16 + 5),
+            Constraint::Min(HEATMAP_PLACEHOLDERS.len() as u16 + 3),
+        ])
+        .areas(area);
+
+        let summary = vec![
+            Line::from(format!("templates  {}", stats.templates)),
+            Line::from(format!(
+                "length     min {}, max {}, mean {:.1}",
+                stats.min_length, stats.max_length, stats.mean_length
+            )),
+        ];
+        frame.render_widget(
+            Paragraph::new(summary).block(Block::bordered().title(" corpus ")),
+            summary_area,
+        );
+
+        let listed = |title: &'static str, indices: &[usize]| {
+            let lines: Vec<Line> = std::iter::once(Line::styled(
+                title,
+                Style::new().add_modifier(Modifier::BOLD),
+            ))
+            .chain(
+                indices
+                    .iter()
+                    .map(|&i| Line::from(format!("{:>4}  {}", i, self.templates[i]))),
+            )
+            .collect();
+            lines
+        };
+        let mut extremes = listed("shortest", &stats.shortest);
+        extremes.push(Line::default());
+        extremes.extend(listed("longest", &stats.longest));
+        frame.render_widget(
+            Paragraph::new(extremes).block(Block::bordered().title(" templates ")),
+            extremes_area,
+        );
+
+        let max = stats
+            .heatmap
+            .iter()
+            .flatten()
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let mut header = vec![Cell::from("")];
+        let mut lower = 0;
+        for bound in LENGTH_BUCKETS {
+            header.push(Cell::from(format!("{}-{}", lower, bound - 1)));
+            lower = bound;
+        }
+        header.push(Cell::from(format!("{}+", lower)));
+        let rows = HEATMAP_PLACEHOLDERS
+            .iter()
+            .zip(&stats.heatmap)
+            .map(|(placeholder, counts)| {
+                let cells = counts.iter().map(|&count| {
+                    let shade = [" ", "░", "▒", "▓", "█"][(count * 4).div_ceil(max)];
+                    Cell::from(format!("{} {}", shade.repeat(2), count))
+                });
+                Row::new(std::iter::once(Cell::from(*placeholder)).chain(cells))
+            });
+        let widths = std::iter::once(Constraint::Length(12))
+            .chain([Constraint::Length(9); LENGTH_BUCKETS.len() + 1]);
+        let table = Table::new(rows, widths)
+            .header(Row::new(header).style(Style::new().add_modifier(Modifier::BOLD)))
+            .block(Block::bordered().title(" placeholders by template length "));
+        frame.render_widget(table, heatmap_area);
+    }
+}
+
+/// Copies text to the system clipboard through the terminal's OSC 52 support, which takes
+/// it in base64.
+pub fn copy(text: &str) -> io::Result<()> {
+    let mut stderr = io::stderr();
+    write!(stderr, "\x1b]52;c;{}\x07", BASE64.encode(text))?;
+    stderr.flush()
+}
+
+fn event_loop<F>(
+    terminal: &mut Terminal<CrosstermBackend<Stderr>>,
+    app: &mut App,
+    regenerate: &mut F,
+) -> Result<Action>
+where
+    F: FnMut(usize) -> Result<Vec<Generated>>,
+{
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                app.status.clear();
+                if let Some(action) = app.handle_key(key, regenerate) {
+                    return Ok(action);
+                }
+            }
+        }
+    }
+}
+
+/// Runs `f` with a full-screen terminal drawn on stderr, restoring the terminal afterwards.
+///
+/// Drawing on stderr keeps stdout free, so a chosen message can be captured from it.
+pub fn with_terminal<T, F>(f: F) -> Result<T>
+where
+    F: FnOnce(&mut Terminal<CrosstermBackend<Stderr>>) -> Result<T>,
+{
+    enable_raw_mode()?;
+    execute!(io::stderr(), EnterAlternateScreen)?;
+    let result = Terminal::new(CrosstermBackend::new(io::stderr()))
+        .map_err(anyhow::Error::from)
+        .and_then(|mut terminal| f(&mut terminal));
+    disable_raw_mode()?;
+    execute!(io::stderr(), LeaveAlternateScreen)?;
+    result
+}
+
+/// Runs the full-screen browser over generated messages and the template corpus.
+pub fn run<F>(
+    templates: &[String],
+    names: &[String],
+    keys: Keymap,
+    mut regenerate: F,
+) -> Result<Action>
+where
+    F: FnMut(usize) -> Result<Vec<Generated>>,
+{
+    let mut app = App::new(regenerate(BATCH_SIZE)?, templates, names, keys);
+    with_terminal(|terminal| event_loop(terminal, &mut app, &mut regenerate))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn t_filter_and_pick() {
+        let templates = vec!["Fix the build".to_string(), "Break the build".to_string()];
+        let generated = vec![Generated {
+            message: "Ann broke it".to_string(),
+            name: "Ann".to_string(),
+            template_index: 0,
+            drawn: Vec::new(),
+            spliced: None,
+        }];
+        let mut app = App::new(generated, &templates, &[], Keymap::tui_defaults());
+        let mut regenerate = |_| -> Result<Vec<Generated>> { Ok(Vec::new()) };
+
+        app.handle_key(key(KeyCode::Tab), &mut regenerate);
+        for code in [KeyCode::Char('/'), KeyCode::Char('b'), KeyCode::Char('r')] {
+            app.handle_key(key(code), &mut regenerate);
+        }
+        app.handle_key(key(KeyCode::Enter), &mut regenerate);
+        assert_eq!(app.entries(), vec![(1, "Break the build")]);
+        assert_eq!(
+            app.handle_key(key(KeyCode::Char('c')), &mut regenerate),
+            Some(Action::Commit("Break the build".to_string()))
+        );
+    }
+
+    #[test]
+    fn t_edit_template_in_editor() {
+        let templates = vec!["Fix XNAMEX".to_string()];
+        let mut app = App::new(Vec::new(), &templates, &[], Keymap::tui_defaults());
+        let mut regenerate = |_| -> Result<Vec<Generated>> { Ok(Vec::new()) };
+
+        app.handle_key(key(KeyCode::Tab), &mut regenerate);
+        app.handle_key(key(KeyCode::Char('e')), &mut regenerate);
+        assert!(app.view == View::Editor);
+        // Keys bound to actions are typed into the template instead
+        for c in " quickly".chars() {
+            app.handle_key(key(KeyCode::Char(c)), &mut regenerate);
+        }
+        assert_eq!(
+            app.handle_key(key(KeyCode::Enter), &mut regenerate),
+            Some(Action::Print("Fix XNAMEX quickly".to_string()))
+        );
+    }
+
+    #[test]
+    fn t_back_to_earlier_batch() {
+        let generated = |message: &str| Generated {
+            message: message.to_string(),
+            name: "Ann".to_string(),
+            template_index: 0,
+            drawn: Vec::new(),
+            spliced: None,
+        };
+        let mut app = App::new(vec![generated("first")], &[], &[], Keymap::tui_defaults());
+        let mut regenerate = |_| -> Result<Vec<Generated>> { Ok(vec![generated("second")]) };
+
+        app.handle_key(key(KeyCode::Char('r')), &mut regenerate);
+        assert_eq!(app.selected().as_deref(), Some("second"));
+        app.handle_key(key(KeyCode::Left), &mut regenerate);
+        assert_eq!(app.selected().as_deref(), Some("first"));
+        app.handle_key(key(KeyCode::Left), &mut regenerate);
+        assert_eq!(app.status, "No more batches");
+        app.handle_key(key(KeyCode::Right), &mut regenerate);
+        assert_eq!(app.selected().as_deref(), Some("second"));
+    }
+
+    #[test]
+    fn t_marked_messages_are_squashed() {
+        let generated: Vec<Generated> = ["first", "second", "third"]
+            .map(|message| Generated {
+                message: message.to_string(),
+                name: "Ann".to_string(),
+                template_index: 0,
+                drawn: Vec::new(),
+                spliced: None,
+            })
+            .to_vec();
+        let mut app = App::new(generated, &[], &[], Keymap::tui_defaults());
+        let mut regenerate = |_| -> Result<Vec<Generated>> { Ok(Vec::new()) };
+
+        app.handle_key(key(KeyCode::Down), &mut regenerate);
+        app.handle_key(key(KeyCode::Char(' ')), &mut regenerate);
+        app.handle_key(key(KeyCode::Char(' ')), &mut regenerate);
+        assert_eq!(
+            app.handle_key(key(KeyCode::Enter), &mut regenerate),
+            Some(Action::Print("second\n\n- third".to_string()))
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use rand::seq::IndexedRandom;
+use rand::Rng;
+
+/// Question titles for Q&A-site links, as URL slugs.
+const QUESTIONS: [&str; 10] = [
+    "how-to-exit-vim",
+    "why-does-this-work",
+    "undefined-is-not-a-function",
+    "how-to-undo-the-most-recent-local-commits",
+    "regex-to-parse-html",
+    "why-is-my-code-slow",
+    "what-does-this-error-mean",
+    "how-to-center-a-div",
+    "is-it-safe-to-disable-ssl-verification",
+    "how-do-i-remove-a-file-from-git",
+];
+
+/// Page titles for internal wiki links.
+const WIKI_PAGES: [&str; 8] = [
+    "Deploy+Process",
+    "Onboarding+(OUTDATED)",
+    "Architecture+Decisions",
+    "Oncall+Runbook",
+    "Who+Owns+This",
+    "Coding+Standards+v3",
+    "Incident+Postmortems",
+    "Do+Not+Edit",
+];
+
+/// Wiki spaces a page can live in.
+const WIKI_SPACES: [&str; 4] = ["ENG", "OPS", "PLATFORM", "LEGACY"];
+
+/// A believable link that doesn't resolve: the hosts are under `.example` or `.invalid`,
+/// top-level domains reserved so they never exist (RFC 2606).
+pub fn generate<R>(rng: &mut R) -> String
+where
+    R: Rng + ?Sized,
+{
+    match rng.random_range(0..3) {
+        0 => format!(
+            "https://stackoverflow.example/questions/{}/{}",
+            rng.random_range(1_000_000..80_000_000),
+            QUESTIONS.choose(rng).unwrap()
+        ),
+        1 => format!(
+            "https://wiki.corp.invalid/display/{}/{}",
+            WIKI_SPACES.choose(rng).unwrap(),
+            WIKI_PAGES.choose(rng).unwrap()
+        ),
+        _ => format!(
+            "https://gist.example/{:x}",
+            rng.random::<u64>() & 0xffff_ffff_ffff
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_urls_never_resolve() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let urls: Vec<String> = (0..100).map(|_| generate(&mut rng)).collect();
+        for url in &urls {
+            let host = url
+                .strip_prefix("https://")
+                .unwrap()
+                .split('/')
+                .next()
+                .unwrap();
+            assert!(
+                host.ends_with(".example") || host.ends_with(".invalid"),
+                "{}",
+                url
+            );
+            assert!(!url.contains(char::is_whitespace));
+        }
+        assert!(urls.iter().any(|u| u.contains("/questions/")));
+        assert!(urls.iter().any(|u| u.contains("/display/")));
+    }
+}
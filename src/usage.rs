@@ -0,0 +1,143 @@
+use crate::history;
+use crate::list;
+use crate::paths;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Counts of what has been generated since the statistics were turned on or cleared. They
+/// never leave the machine.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Usage {
+    /// Seconds since the Unix epoch when counting started
+    pub since: u64,
+    pub messages: u64,
+    /// Messages per template file or URL, or `built-in`
+    pub corpora: BTreeMap<String, u64>,
+    /// Messages using each kind of placeholder, as `list` names them
+    pub placeholders: BTreeMap<String, u64>,
+    /// Messages per template
+    pub templates: BTreeMap<String, u64>,
+}
+
+impl Usage {
+    /// Counts one message generated from `template` in `corpus`.
+    pub fn add(&mut self, corpus: &str, template: &str) {
+        self.messages += 1;
+        *self.corpora.entry(corpus.to_string()).or_default() += 1;
+        for kind in list::placeholder_kinds(template) {
+            *self.placeholders.entry(kind.to_string()).or_default() += 1;
+        }
+        *self.templates.entry(template.to_string()).or_default() += 1;
+    }
+}
+
+/// Location of the usage statistics file.
+pub fn path() -> Result<PathBuf> {
+    paths::data_dir()
+        .map(|d| d.join("usage.json"))
+        .context("Could not determine the data directory")
+}
+
+/// Reads the statistics, starting afresh if there are none yet or they can't be parsed.
+pub fn load(path: &Path) -> Result<Usage> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(serde_json::from_str(&content).unwrap_or_default()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Usage::default()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read usage file: {:?}", path)),
+    }
+}
+
+/// Adds the messages generated from `templates` in `corpus` to the statistics at `path`.
+pub fn record(path: &Path, corpus: &str, templates: &[&str]) -> Result<()> {
+    let mut usage = load(path)?;
+    if usage.since == 0 {
+        usage.since = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+    }
+    for template in templates {
+        usage.add(corpus, template);
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory: {:?}", dir))?;
+    }
+    fs::write(path, serde_json::to_string(&usage)?)
+        .with_context(|| format!("Failed to write usage file: {:?}", path))
+}
+
+/// The `top` largest counts, largest first, ties in name order.
+fn ranked(counts: &BTreeMap<String, u64>, top: usize) -> Vec<(&str, u64)> {
+    let mut ranked: Vec<(&str, u64)> = counts.iter().map(|(k, &v)| (k.as_str(), v)).collect();
+    ranked.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    ranked.truncate(top);
+    ranked
+}
+
+/// Formats the report, showing the `top` most used corpora, placeholders and templates.
+pub fn report(usage: &Usage, top: usize) -> String {
+    if usage.messages == 0 {
+        return "No usage recorded yet; set `usage-stats = true` in the config file to start"
+            .to_string();
+    }
+    let mut out = format!(
+        "{} messages since {}\n",
+        usage.messages,
+        history::format_time(usage.since)
+    );
+    for (title, counts) in [
+        ("Corpora", &usage.corpora),
+        ("Placeholders", &usage.placeholders),
+        ("Templates", &usage.templates),
+    ] {
+        out.push_str(&format!("\n{}\n", title));
+        if counts.is_empty() {
+            out.push_str("     none\n");
+        }
+        for (name, count) in ranked(counts, top) {
+            out.push_str(&format!("{:>8}  {}\n", count, name));
+        }
+    }
+    out
+}
+
+/// Deletes the statistics file.
+pub fn clear(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(e).with_context(|| format!("Failed to remove usage file: {:?}", path))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_usage_counts() {
+        let path = std::env::temp_dir().join(format!("wtc-usage-{}.json", std::process::id()));
+        record(&path, "built-in", &["XNAMEX did it", "fixed XNUMX bugs"]).unwrap();
+        record(&path, "team.txt", &["XNAMEX did it"]).unwrap();
+        let usage = load(&path).unwrap();
+        assert_eq!(usage.messages, 3);
+        assert_eq!(usage.corpora["built-in"], 2);
+        assert_eq!(usage.placeholders["name"], 2);
+        assert_eq!(usage.placeholders["number"], 1);
+        assert_eq!(
+            ranked(&usage.templates, 1),
+            [("XNAMEX did it", 2)],
+            "the most used template comes first"
+        );
+        assert!(report(&usage, 5).starts_with("3 messages since "));
+        clear(&path).unwrap();
+        assert_eq!(load(&path).unwrap(), Usage::default());
+    }
+}
@@ -0,0 +1,62 @@
+use rand::Rng;
+use std::fmt::Write;
+
+/// Hex digits shown by `XSHORTUUIDX`, like an abbreviated commit hash.
+const SHORT_LENGTH: usize = 8;
+
+/// A random version 4 UUID in its usual hyphenated form, such as
+/// `3f2b8c1e-9d4a-4e7b-a1c2-5d6e7f809a1b`.
+pub fn v4<R>(rng: &mut R) -> String
+where
+    R: Rng + ?Sized,
+{
+    let mut bytes: [u8; 16] = rng.random();
+    // Version 4 and the RFC 4122 variant
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let mut uuid = String::with_capacity(36);
+    for (i, byte) in bytes.iter().enumerate() {
+        if matches!(i, 4 | 6 | 8 | 10) {
+            uuid.push('-');
+        }
+        // Writing to a `String` can't fail
+        let _ = write!(uuid, "{:02x}", byte);
+    }
+    uuid
+}
+
+/// The first hex digits of a random version 4 UUID.
+pub fn short<R>(rng: &mut R) -> String
+where
+    R: Rng + ?Sized,
+{
+    let mut uuid = v4(rng);
+    uuid.truncate(SHORT_LENGTH);
+    uuid
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_uuid_v4_format() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..50 {
+            let uuid = v4(&mut rng);
+            let groups: Vec<&str> = uuid.split('-').collect();
+            assert_eq!(
+                groups.iter().map(|g| g.len()).collect::<Vec<_>>(),
+                [8, 4, 4, 4, 12]
+            );
+            assert!(uuid.chars().all(|c| c == '-' || c.is_ascii_hexdigit()));
+            assert!(groups[2].starts_with('4'));
+            assert!("89ab".contains(&groups[3][..1]));
+        }
+        let short = short(&mut rng);
+        assert_eq!(short.len(), SHORT_LENGTH);
+        assert!(short.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+}
@@ -0,0 +1,176 @@
+use crate::commitlint::Commitlint;
+use crate::corpus;
+use crate::daily;
+use crate::diagnostic::{self, Diagnostic};
+use crate::lint;
+use crate::rules::Rules;
+use crate::sections;
+use anyhow::Result;
+use rand::prelude::IndexedRandom;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::BTreeSet;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+/// What rendered messages are checked against, besides rendering at all.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Checks<'a> {
+    pub rules: Option<&'a Rules>,
+    pub commitlint: Option<&'a Commitlint>,
+}
+
+/// The text of a panic, when it was given one.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("no message")
+}
+
+/// Why a template fails, if it does: a problem the linter finds, or else a render out of
+/// `renders` that panics, comes out empty or breaks a check. The renders use names from
+/// `names` and a generator seeded with `seed` and the template, so they are the same
+/// every run, however the pack changes around the template.
+fn check_template(
+    template: &str,
+    names: &[String],
+    renders: usize,
+    seed: u64,
+    checks: Checks,
+) -> Vec<(usize, String)> {
+    if let Some(problem) = diagnostic::check_line(template) {
+        return vec![problem];
+    }
+    let problems: Vec<(usize, String)> = lint::scan(template)
+        .into_iter()
+        .filter_map(|p| {
+            let problem = format!("`{}`: {}", &template[p.range.clone()], p.problem?);
+            Some((p.range.start, problem))
+        })
+        .collect();
+    if !problems.is_empty() {
+        return problems;
+    }
+    let mut rng = StdRng::seed_from_u64(seed ^ daily::fnv1a(template.as_bytes()));
+    for _ in 0..renders {
+        let name = names.choose(&mut rng).map_or("", String::as_str);
+        let rendered = panic::catch_unwind(AssertUnwindSafe(|| {
+            crate::substitute_placeholders(template, name, &mut rng)
+        }));
+        let message = match rendered {
+            Ok(message) => message,
+            Err(payload) => {
+                return vec![(0, format!("panicked: {}", panic_message(&*payload)))];
+            }
+        };
+        if message.trim().is_empty() {
+            return vec![(0, "renders an empty message".to_string())];
+        }
+        let mut violations = Vec::new();
+        if let Some(rules) = checks.rules {
+            violations.extend(rules.violations(&message));
+        }
+        if let Some(commitlint) = checks.commitlint {
+            violations.extend(commitlint.violations(&message));
+        }
+        if !violations.is_empty() {
+            let problem = format!(
+                "renders {:?}, which breaks {}",
+                message,
+                violations.join(", ")
+            );
+            return vec![(0, problem)];
+        }
+    }
+    Vec::new()
+}
+
+/// Verifies every template of a pack, the templates file `text` read from `path`.
+///
+/// # Returns
+/// How many templates it has, and a diagnostic for every problem found.
+pub fn verify(
+    path: &Path,
+    text: &str,
+    names: &[String],
+    renders: usize,
+    seed: u64,
+    checks: Checks,
+) -> (usize, Vec<Diagnostic>) {
+    let mut templates = 0;
+    let mut found = Vec::new();
+    for (number, line) in sections::templates(text) {
+        templates += 1;
+        for (at, problem) in check_template(line, names, renders, seed, checks) {
+            found.push(Diagnostic::new(path, number + 1, line, at, problem));
+        }
+    }
+    (templates, found)
+}
+
+/// Verifies the templates files at `paths`, or the loaded templates, `loaded`, if none are
+/// given, printing every problem and failing if there are any.
+pub fn run(
+    paths: &[PathBuf],
+    loaded: (&Path, &[String]),
+    names: &[String],
+    renders: usize,
+    seed: u64,
+    checks: Checks,
+) -> Result<()> {
+    let packs = corpus::packs(paths, loaded)?;
+    // Panics are reported as problems with their templates, not printed as they happen
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let mut failed = 0;
+    for (path, text) in &packs {
+        let (templates, found) = verify(path, text, names, renders, seed, checks);
+        for diagnostic in &found {
+            eprintln!("{}", diagnostic);
+        }
+        let lines: BTreeSet<usize> = found.iter().map(|d| d.line).collect();
+        println!(
+            "{}: {} templates, {} failed",
+            path.display(),
+            templates,
+            lines.len()
+        );
+        failed += lines.len();
+    }
+    panic::set_hook(hook);
+    if failed > 0 {
+        let noun = if failed == 1 { "template" } else { "templates" };
+        anyhow::bail!("{} {} failed verification", failed, noun);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_verify_pack() {
+        let rules = Rules::parse("max-length = 30").unwrap();
+        let checks = Checks {
+            rules: Some(&rules),
+            commitlint: None,
+        };
+        let names = vec!["Sam".to_string()];
+        let text = "[today]\n# fine\nXNAMEX fixed it\nbroke XNAMX\nXNUM1,5X tests\n\nXNAMEX rewrote XNUM1000000000X modules today";
+        let path = Path::new("pack.txt");
+        let (templates, found) = verify(path, text, &names, 10, 7, checks);
+        assert_eq!(templates, 4);
+        let lines: Vec<usize> = found.iter().map(|d| d.line).collect();
+        assert_eq!(lines, [4, 7]);
+        assert_eq!(found[0].column, 7);
+        assert!(found[1].problem.contains("max-length"), "{}", found[1]);
+
+        // The same seed renders the same messages
+        let again = verify(path, text, &names, 10, 7, checks).1;
+        assert_eq!(again, found);
+        assert_eq!(panic_message(&"boom"), "boom");
+    }
+}
@@ -0,0 +1,126 @@
+use crate::animation;
+use crate::output::Output;
+use anyhow::{Context, Result};
+use rand::prelude::IndexedRandom;
+use rand::Rng;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// How often the template file is checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Expands `count` randomly chosen templates, each with a random name, prefixed with
+/// the template's zero-based index.
+fn samples<R>(
+    templates: &[String],
+    count: usize,
+    names: &[String],
+    rng: &mut R,
+    output: &Output,
+) -> Result<Vec<String>>
+where
+    R: Rng + ?Sized,
+{
+    let indices = rand::seq::index::sample(rng, templates.len(), count.min(templates.len()));
+    let mut indices = indices.into_vec();
+    indices.sort_unstable();
+    indices
+        .into_iter()
+        .map(|i| {
+            let name = names.choose(rng).context("Failed to select any names")?;
+            let message = crate::substitute_placeholders(&templates[i], name, rng);
+            Ok(format!(
+                "{:>4}  {}",
+                i,
+                output.message(&message, crate::name::split(name).0)
+            ))
+        })
+        .collect()
+}
+
+fn render<R>(path: &Path, count: usize, names: &[String], rng: &mut R, output: &Output, clear: bool)
+where
+    R: Rng + ?Sized,
+{
+    if clear && io::stdout().is_terminal() {
+        let _ = animation::clear_screen();
+    }
+    let result = crate::load_lines_or_default(&Some(path.to_path_buf()), None, "commit messages")
+        .and_then(|templates| {
+            let lines = samples(&templates, count, names, rng, output)?;
+            Ok((templates.len(), lines))
+        });
+    match result {
+        Ok((total, lines)) => {
+            println!("{} ({} templates)\n", path.display(), total);
+            for line in lines {
+                println!("{}", line);
+            }
+        }
+        Err(e) => println!("{}\n\nError: {:#}", path.display(), e),
+    }
+    println!("\nWatching for changes, press ctrl-c to stop");
+}
+
+/// Re-renders sample expansions of a template file every time it changes, clearing the
+/// screen first when `clear` is set.
+pub fn run<R>(
+    path: &Path,
+    count: usize,
+    names: &[String],
+    rng: &mut R,
+    output: &Output,
+    clear: bool,
+) -> Result<()>
+where
+    R: Rng + ?Sized,
+{
+    let modified = |path: &Path| -> Option<SystemTime> { fs::metadata(path).ok()?.modified().ok() };
+    let mut last = None;
+    let mut first = true;
+    loop {
+        let current = modified(path);
+        if first || current != last {
+            first = false;
+            last = current;
+            render(path, count, names, rng, output, clear);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::output::ColorChoice;
+    use crate::theme::Theme;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_samples_are_sorted_and_capped() {
+        let templates: Vec<String> = (0..3).map(|i| format!("template {} XNAMEX", i)).collect();
+        let names = vec!["Ann".to_string()];
+        let output = Output::new(ColorChoice::Never, true, Theme::default());
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let lines = samples(&templates, 10, &names, &mut rng, &output).unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                "   0  template 0 Ann",
+                "   1  template 1 Ann",
+                "   2  template 2 Ann"
+            ]
+        );
+        assert_eq!(
+            samples(&templates, 2, &names, &mut rng, &output)
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+}
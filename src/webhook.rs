@@ -0,0 +1,96 @@
+use crate::http;
+use crate::keychain;
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use tracing::{debug, info};
+
+/// Chat services whose incoming webhooks expect different payloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Service {
+    Slack,
+    Discord,
+}
+
+impl Service {
+    /// Guesses the service from the webhook URL, defaulting to Slack's format
+    /// which most other chat tools also accept.
+    fn detect(url: &str) -> Self {
+        let host = url
+            .split("://")
+            .nth(1)
+            .unwrap_or(url)
+            .split(['/', '?', '#'])
+            .next()
+            .unwrap_or_default();
+        if host.ends_with("discord.com") || host.ends_with("discordapp.com") {
+            Service::Discord
+        } else {
+            Service::Slack
+        }
+    }
+
+    fn payload(self, message: &str) -> Value {
+        match self {
+            Service::Slack => json!({
+                "text": message,
+                "blocks": [{
+                    "type": "section",
+                    "text": { "type": "mrkdwn", "text": format!("> {}", message) },
+                }],
+            }),
+            Service::Discord => json!({
+                "username": "whatthecommit",
+                "content": format!("> {}", message),
+            }),
+        }
+    }
+}
+
+/// Posts a message to a Slack or Discord incoming webhook. Environment variables and
+/// keychain tokens written as `${...}` in `url` are expanded for the request only: logs and
+/// errors show `url` as written.
+pub fn post(url: &str, message: &str, settings: &http::Settings) -> Result<()> {
+    let expanded = keychain::expand(url, &keychain::resolve)?;
+    let service = Service::detect(&expanded);
+    debug!("Posting message to {:?} webhook: {}", service, url);
+    http::post_json(
+        &expanded,
+        url,
+        &service.payload(message).to_string(),
+        settings,
+    )
+    .context("Failed to post message to webhook")?;
+    info!("Posted message to webhook: {}", url);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_detect_service() {
+        assert_eq!(
+            Service::detect("https://discord.com/api/webhooks/1/abc"),
+            Service::Discord
+        );
+        assert_eq!(
+            Service::detect("https://hooks.slack.com/services/T/B/X"),
+            Service::Slack
+        );
+        assert_eq!(
+            Service::detect("https://example.com/discord.com"),
+            Service::Slack
+        );
+    }
+
+    #[test]
+    fn t_payload_shapes() {
+        let slack = Service::Slack.payload("oops");
+        assert_eq!(slack["text"], "oops");
+        assert_eq!(slack["blocks"][0]["text"]["text"], "> oops");
+
+        let discord = Service::Discord.payload("oops");
+        assert_eq!(discord["content"], "> oops");
+    }
+}
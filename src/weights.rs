@@ -0,0 +1,205 @@
+use crate::corpus::Corpus;
+use crate::paths;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::debug;
+
+/// Seconds for the skips of a template to count half as much: 30 days.
+const HALF_LIFE: f64 = 30.0 * 24.0 * 60.0 * 60.0;
+
+/// Copies of each template in a pool thinned out by skips, so the most skipped template
+/// keeps one in `SCALE` of its chances.
+const SCALE: usize = 4;
+
+/// Skips a template is forgotten below.
+const FORGOTTEN: f64 = 0.05;
+
+/// How often a template was skipped, fading as time goes on.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Skips {
+    count: f64,
+    /// Seconds since the Unix epoch when `count` was last updated
+    at: u64,
+}
+
+impl Skips {
+    /// The skips as they count at `now`.
+    fn at(&self, now: u64) -> f64 {
+        let elapsed = now.saturating_sub(self.at) as f64;
+        self.count * 0.5f64.powf(elapsed / HALF_LIFE)
+    }
+}
+
+/// Templates skipped in the interactive modes, by their text, so a template passed over
+/// in batch after batch comes up less often. They never leave the machine.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Weights {
+    skipped: BTreeMap<String, Skips>,
+}
+
+impl Weights {
+    /// Counts a skip of `template` at `now`.
+    fn skip(&mut self, template: &str, now: u64) {
+        let skips = self.skipped.entry(template.to_string()).or_insert(Skips {
+            count: 0.0,
+            at: now,
+        });
+        *skips = Skips {
+            count: skips.at(now) + 1.0,
+            at: now,
+        };
+    }
+
+    /// The share of its chances `template` keeps at `now`: a half after two skips and a
+    /// third after four, never less than one in `SCALE`.
+    pub fn keep(&self, template: &str, now: u64) -> f64 {
+        let skips = self.skipped.get(template).map_or(0.0, |s| s.at(now));
+        (1.0 / (1.0 + skips / 2.0)).max(1.0 / SCALE as f64)
+    }
+
+    /// Thins out `pool` by the skips of its templates at `now`, giving each template
+    /// `SCALE` copies of each of its entries, fewer the more it was skipped.
+    pub fn apply(&self, commit_messages: &Corpus, pool: &mut Vec<usize>, now: u64) -> Result<()> {
+        if self.skipped.is_empty() {
+            return Ok(());
+        }
+        let mut copies: HashMap<usize, usize> = HashMap::new();
+        for &index in pool.iter() {
+            if let Entry::Vacant(entry) = copies.entry(index) {
+                let keep = self.keep(&commit_messages.get(index)?, now);
+                entry.insert((keep * SCALE as f64).round().max(1.0) as usize);
+            }
+        }
+        if copies.values().all(|&n| n == SCALE) {
+            return Ok(());
+        }
+        let thinned: Vec<usize> = (pool.iter())
+            .flat_map(|&index| std::iter::repeat_n(index, copies[&index]))
+            .collect();
+        debug!(
+            "Skips thinned out {} of {} templates in the pool",
+            copies.values().filter(|&&n| n < SCALE).count(),
+            copies.len()
+        );
+        *pool = thinned;
+        Ok(())
+    }
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Location of the weights file.
+pub fn path() -> Result<PathBuf> {
+    paths::data_dir()
+        .map(|d| d.join("weights.json"))
+        .context("Could not determine the data directory")
+}
+
+/// Reads the weights, starting afresh if there are none yet or they can't be parsed.
+pub fn load(path: &Path) -> Result<Weights> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(serde_json::from_str(&content).unwrap_or_default()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Weights::default()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read weights file: {:?}", path)),
+    }
+}
+
+/// Counts a skip of each of `templates` at `now` in the weights at `path`, forgetting
+/// templates whose skips have faded away.
+pub fn record(path: &Path, templates: &[&str], now: u64) -> Result<()> {
+    let mut weights = load(path)?;
+    for template in templates {
+        weights.skip(template, now);
+    }
+    weights
+        .skipped
+        .retain(|_, skips| skips.at(now) >= FORGOTTEN);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory: {:?}", dir))?;
+    }
+    fs::write(path, serde_json::to_string(&weights)?)
+        .with_context(|| format!("Failed to write weights file: {:?}", path))
+}
+
+/// Formats the skipped templates with the share of their chances they keep, the least
+/// first.
+pub fn report(weights: &Weights, now: u64) -> String {
+    if weights.skipped.is_empty() {
+        return "No templates skipped yet".to_string();
+    }
+    let mut skipped: Vec<(&str, f64)> = (weights.skipped.iter())
+        .map(|(template, skips)| (template.as_str(), skips.at(now)))
+        .collect();
+    skipped.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut out = format!("{:>6}{:>7}\n", "skips", "keeps");
+    for (template, skips) in skipped {
+        out.push_str(&format!(
+            "{:>6.1}{:>6.0}%  {}\n",
+            skips,
+            100.0 * weights.keep(template, now),
+            template
+        ));
+    }
+    out.pop();
+    out
+}
+
+/// Deletes the weights file, so every template has its full chances again.
+pub fn reset(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(e).with_context(|| format!("Failed to remove weights file: {:?}", path))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_skips_fade() {
+        let path = std::env::temp_dir()
+            .join(format!("wtc-weights-{}", std::process::id()))
+            .join("weights.json");
+        let day = 24 * 60 * 60;
+        record(&path, &["Fixed it", "Broke it"], 0).unwrap();
+        record(&path, &["Fixed it"], 0).unwrap();
+        let weights = load(&path).unwrap();
+        assert_eq!(weights.keep("Fixed it", 0), 0.5);
+        assert_eq!(weights.keep("Fixed it", 30 * day), 1.0 / 1.5);
+        assert_eq!(weights.keep("Shipped it", 0), 1.0);
+
+        let templates = Corpus::from(vec![
+            "Fixed it".to_string(),
+            "Broke it".to_string(),
+            "Shipped it".to_string(),
+        ]);
+        let mut pool = vec![0, 1, 2, 2];
+        weights.apply(&templates, &mut pool, 0).unwrap();
+        assert_eq!(pool.iter().filter(|&&i| i == 0).count(), 2);
+        assert_eq!(pool.iter().filter(|&&i| i == 1).count(), 3);
+        assert_eq!(pool.iter().filter(|&&i| i == 2).count(), 8);
+        assert!(report(&weights, 0).contains("   2.0    50%  Fixed it"));
+
+        // Skips long faded are forgotten
+        record(&path, &[], 365 * day).unwrap();
+        assert_eq!(load(&path).unwrap(), Weights::default());
+        reset(&path).unwrap();
+        reset(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
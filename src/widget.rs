@@ -0,0 +1,107 @@
+use clap::ValueEnum;
+
+/// Shells `widget` can write a key binding for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WidgetShell {
+    /// A zle widget, bound with bindkey
+    Zsh,
+    /// A function bound with bind, in the default and vi insert modes
+    Fish,
+}
+
+/// A letter pressed with Ctrl or Alt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    alt: bool,
+    letter: char,
+}
+
+/// Parses a key like `ctrl-g` or `alt-m`.
+pub fn parse_key(value: &str) -> Result<Key, String> {
+    let invalid = || format!("`{}` is not a key like ctrl-g or alt-m", value);
+    let (modifier, letter) = value.split_once('-').ok_or_else(invalid)?;
+    let alt = match modifier.to_ascii_lowercase().as_str() {
+        "ctrl" => false,
+        "alt" => true,
+        _ => return Err(invalid()),
+    };
+    let mut letters = letter.chars();
+    match (letters.next(), letters.next()) {
+        (Some(letter), None) if letter.is_ascii_alphabetic() => Ok(Key {
+            alt,
+            letter: letter.to_ascii_lowercase(),
+        }),
+        _ => Err(invalid()),
+    }
+}
+
+impl Key {
+    /// The key as bindkey writes it, such as `^G` or `^[m`.
+    fn zsh(self) -> String {
+        match self.alt {
+            true => format!("^[{}", self.letter),
+            false => format!("^{}", self.letter.to_ascii_uppercase()),
+        }
+    }
+
+    /// The key as fish's bind writes it, such as `\cg` or `\em`.
+    fn fish(self) -> String {
+        match self.alt {
+            true => format!("\\e{}", self.letter),
+            false => format!("\\c{}", self.letter),
+        }
+    }
+}
+
+/// A script to source in `shell` that binds `key` to insert the first line of a generated
+/// message, quoted, at the cursor.
+pub fn script(shell: WidgetShell, key: Key) -> String {
+    let program = env!("CARGO_BIN_NAME");
+    match shell {
+        WidgetShell::Zsh => format!(
+            r#"# Add to ~/.zshrc: eval "$({program} widget zsh)"
+_whatthecommit_insert() {{
+  local message
+  message="$({program} --plain 2>/dev/null)" || {{ zle beep; return 1; }}
+  LBUFFER+="${{(qq)${{message%%$'\n'*}}}}"
+}}
+zle -N _whatthecommit_insert
+bindkey '{key}' _whatthecommit_insert
+"#,
+            key = key.zsh(),
+        ),
+        WidgetShell::Fish => format!(
+            r#"# Add to ~/.config/fish/config.fish: {program} widget fish | source
+function __whatthecommit_insert
+    set -l message ({program} --plain 2>/dev/null)
+    or return 1
+    commandline --insert -- (string escape -- $message[1])
+    commandline --function repaint
+end
+bind {key} __whatthecommit_insert
+bind -M insert {key} __whatthecommit_insert
+"#,
+            key = key.fish(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_widget_scripts() {
+        let ctrl_g = parse_key("ctrl-g").unwrap();
+        assert_eq!(parse_key("Alt-M").unwrap().zsh(), "^[m");
+        assert!(parse_key("ctrl-gg").is_err());
+        assert!(parse_key("shift-g").is_err());
+        assert!(parse_key("ctrl-1").is_err());
+
+        let zsh = script(WidgetShell::Zsh, ctrl_g);
+        assert!(zsh.contains("bindkey '^G' _whatthecommit_insert"));
+        assert!(zsh.contains(r#"LBUFFER+="${(qq)${message%%$'\n'*}}""#));
+        let fish = script(WidgetShell::Fish, ctrl_g);
+        assert!(fish.contains(r"bind -M insert \cg __whatthecommit_insert"));
+    }
+}
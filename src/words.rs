@@ -0,0 +1,461 @@
+use crate::corpus;
+use crate::error::WtcError;
+use crate::filepath;
+use crate::spice::Spice;
+use anyhow::{Context, Result};
+use rand::seq::IndexedRandom;
+use rand::Rng;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Programming languages and frameworks for `XLANGX`.
+const LANGUAGES: [&str; 24] = [
+    "Rust",
+    "Go",
+    "Haskell",
+    "Elixir",
+    "Zig",
+    "TypeScript",
+    "JavaScript",
+    "Python",
+    "Ruby",
+    "Java",
+    "Kotlin",
+    "Scala",
+    "Clojure",
+    "OCaml",
+    "C++",
+    "COBOL",
+    "Fortran",
+    "Perl",
+    "PHP",
+    "Bash",
+    "React",
+    "Svelte",
+    "Django",
+    "Rails",
+];
+
+/// Verbs for `XVERBX`, in the imperative like a commit subject.
+const VERBS: [&str; 20] = [
+    "refactor",
+    "rewrite",
+    "nuke",
+    "yeet",
+    "untangle",
+    "patch",
+    "revert",
+    "optimize",
+    "deprecate",
+    "resurrect",
+    "monkey-patch",
+    "hardcode",
+    "inline",
+    "rename",
+    "delete",
+    "duct-tape",
+    "bikeshed",
+    "fix",
+    "break",
+    "appease",
+];
+
+/// Nouns for `XNOUNX`.
+const NOUNS: [&str; 20] = [
+    "parser",
+    "cache",
+    "build",
+    "linter",
+    "config",
+    "test suite",
+    "migration",
+    "race condition",
+    "null check",
+    "regex",
+    "dependency",
+    "login page",
+    "CI pipeline",
+    "feature flag",
+    "hotfix",
+    "memory leak",
+    "timeout",
+    "spaghetti",
+    "TODO",
+    "semicolon",
+];
+
+/// Adjectives for `XADJX`.
+const ADJECTIVES: [&str; 20] = [
+    "flaky",
+    "cursed",
+    "legacy",
+    "temporary",
+    "mysterious",
+    "haunted",
+    "load-bearing",
+    "undocumented",
+    "shiny",
+    "deprecated",
+    "recursive",
+    "async",
+    "fragile",
+    "questionable",
+    "mostly working",
+    "enterprise-grade",
+    "artisanal",
+    "glorious",
+    "broken",
+    "sneaky",
+];
+
+/// The word lists placeholders can draw from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum List {
+    Language,
+    Verb,
+    Noun,
+    Adjective,
+}
+
+impl List {
+    pub const ALL: [List; 4] = [List::Language, List::Verb, List::Noun, List::Adjective];
+
+    /// The placeholder replaced with a word from this list.
+    pub fn placeholder(self) -> &'static str {
+        match self {
+            List::Language => "XLANGX",
+            List::Verb => "XVERBX",
+            List::Noun => "XNOUNX",
+            List::Adjective => "XADJX",
+        }
+    }
+
+    /// What `list` calls templates using this list.
+    pub fn kind(self) -> &'static str {
+        match self {
+            List::Language => "language",
+            List::Verb => "verb",
+            List::Noun => "noun",
+            List::Adjective => "adjective",
+        }
+    }
+
+    /// The file in the words directory with the user's own words.
+    pub fn file(self) -> &'static str {
+        match self {
+            List::Language => "languages.txt",
+            List::Verb => "verbs.txt",
+            List::Noun => "nouns.txt",
+            List::Adjective => "adjectives.txt",
+        }
+    }
+
+    fn built_in(self) -> &'static [&'static str] {
+        match self {
+            List::Language => &LANGUAGES,
+            List::Verb => &VERBS,
+            List::Noun => &NOUNS,
+            List::Adjective => &ADJECTIVES,
+        }
+    }
+
+    /// Whether the user's file adds to the built-in words rather than replacing them.
+    /// Languages are a catalogue that only grows, while the parts of speech set the tone
+    /// of the generated messages, so a file of them takes over.
+    fn extends(self) -> bool {
+        self == List::Language
+    }
+}
+
+/// Mild swear words for `XSWEARX` at `--spice mild` and up.
+const MILD_SWEARS: [&str; 8] = [
+    "darn", "heck", "dang", "crap", "frick", "blimey", "shoot", "bloody",
+];
+
+/// Strong swear words for `XSWEARX` at `--spice strong`.
+const STRONG_SWEARS: [&str; 6] = ["shit", "fuck", "damn", "bullshit", "goddammit", "fml"];
+
+/// Longest quote `XQUOTEX` is replaced with; longer records of a fortune file are
+/// skipped.
+const MAX_QUOTE: usize = 100;
+
+/// Symbols a censored swear word is made of.
+const GRAWLIX: [char; 6] = ['%', '$', '#', '@', '&', '*'];
+
+/// Word lists and settings for the placeholders that draw from them, set up once per run.
+#[derive(Debug)]
+pub struct Words {
+    /// The words of each of `List::ALL`, in order.
+    lists: Vec<Vec<String>>,
+    /// Real swear words; censored symbols are used when empty.
+    pub swears: Vec<String>,
+    /// Real paths for `XPATHX`; made-up ones are used when empty.
+    pub paths: Vec<String>,
+    /// Quotes for `XQUOTEX`, the built-in ones unless `--quotes` gives a fortune file.
+    pub quotes: Vec<String>,
+    /// The version for `XVERSIONX`; a random one is used for each when unset.
+    pub version: Option<String>,
+    /// Whether a placeholder repeated in a template repeats its value too.
+    pub consistent: bool,
+}
+
+static WORDS: OnceLock<Words> = OnceLock::new();
+
+impl Words {
+    fn built_in() -> Self {
+        Words {
+            lists: List::ALL
+                .iter()
+                .map(|list| list.built_in().iter().map(|w| w.to_string()).collect())
+                .collect(),
+            swears: Vec::new(),
+            paths: Vec::new(),
+            quotes: parse_quotes(include_str!("quotes.txt")),
+            version: None,
+            consistent: false,
+        }
+    }
+
+    /// The built-in lists with the non-blank lines of any list files in `dir` added to
+    /// or replacing them, and swear words as strong as `spice` allows. Missing or empty
+    /// files are skipped.
+    pub fn load(dir: Option<&Path>, spice: Spice) -> Result<Self> {
+        let mut words = Words::built_in();
+        if let Some(dir) = dir {
+            for (list, words) in List::ALL.iter().zip(&mut words.lists) {
+                let Some(own) = read(&dir.join(list.file()))? else {
+                    continue;
+                };
+                if !list.extends() {
+                    words.clear();
+                }
+                for word in own {
+                    if !words.contains(&word) {
+                        words.push(word);
+                    }
+                }
+            }
+        }
+        let swears: &[&str] = match spice {
+            Spice::Censored => &[],
+            Spice::Mild | Spice::Medium => &MILD_SWEARS,
+            Spice::Strong => &[MILD_SWEARS.as_slice(), &STRONG_SWEARS].concat(),
+        };
+        words.swears = swears.iter().map(|s| s.to_string()).collect();
+        Ok(words)
+    }
+
+    /// The words of `list`.
+    pub fn list(&self, list: List) -> &[String] {
+        &self.lists[list as usize]
+    }
+
+    /// A path for `XPATHX`: a real one if there are any, or else a made-up one.
+    pub fn path<R>(&self, rng: &mut R) -> String
+    where
+        R: Rng + ?Sized,
+    {
+        match self.paths.choose(rng) {
+            Some(path) => path.clone(),
+            None => filepath::generate(rng),
+        }
+    }
+
+    /// A version for `XVERSIONX`: the one given, or else a random one.
+    pub fn version<R>(&self, rng: &mut R) -> String
+    where
+        R: Rng + ?Sized,
+    {
+        match &self.version {
+            Some(version) => version.clone(),
+            None => random_version(rng),
+        }
+    }
+
+    /// A quote for `XQUOTEX`.
+    pub fn quote<R>(&self, rng: &mut R) -> &str
+    where
+        R: Rng + ?Sized,
+    {
+        self.quotes.choose(rng).map_or("", String::as_str)
+    }
+
+    /// A swear word for `XSWEARX`, or a censored one like `%$#@!`.
+    pub fn swear<R>(&self, rng: &mut R) -> String
+    where
+        R: Rng + ?Sized,
+    {
+        if let Some(swear) = self.swears.choose(rng) {
+            return swear.clone();
+        }
+        let len = rng.random_range(3..=5);
+        let mut grawlix: String = (0..len).map(|_| *GRAWLIX.choose(rng).unwrap()).collect();
+        grawlix.push('!');
+        grawlix
+    }
+}
+
+/// Reads the non-blank lines of the word list at `path`, or `None` if there are none
+/// or there is no such file.
+pub fn read(path: &Path) -> Result<Option<Vec<String>>> {
+    let context = || format!("Failed to read word list: {:?}", path);
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(source) => {
+            return Err(WtcError::IoError {
+                context: context(),
+                source,
+            }
+            .into())
+        }
+    };
+    let text = corpus::decode(bytes).with_context(context)?;
+    let words: Vec<String> = text
+        .lines()
+        .map(str::trim)
+        .filter(|word| !word.is_empty())
+        .map(String::from)
+        .collect();
+    Ok((!words.is_empty()).then_some(words))
+}
+
+/// The short quotes of a fortune file: records separated by lines holding only `%`, each
+/// folded onto one line. Blank records and ones longer than `MAX_QUOTE` are skipped.
+fn parse_quotes(text: &str) -> Vec<String> {
+    let mut quotes = Vec::new();
+    let mut record: Vec<&str> = Vec::new();
+    for line in text.lines().chain(["%"]) {
+        if line.trim() != "%" {
+            record.extend(line.split_whitespace());
+            continue;
+        }
+        let quote = record.join(" ");
+        record.clear();
+        if !quote.is_empty() && quote.chars().count() <= MAX_QUOTE {
+            quotes.push(quote);
+        }
+    }
+    quotes
+}
+
+/// Reads the quotes of the fortune file at `path` for `XQUOTEX`.
+pub fn read_quotes(path: &Path) -> Result<Vec<String>> {
+    let context = || format!("Failed to read quotes file: {:?}", path);
+    let bytes = fs::read(path).map_err(|source| WtcError::IoError {
+        context: context(),
+        source,
+    })?;
+    let quotes = parse_quotes(&corpus::decode(bytes).with_context(context)?);
+    if quotes.is_empty() {
+        anyhow::bail!(
+            "{:?} has no quotes of up to {} characters; separate them with lines of `%`",
+            path,
+            MAX_QUOTE
+        );
+    }
+    Ok(quotes)
+}
+
+/// A random semantic version such as `2.13.4`.
+pub fn random_version<R>(rng: &mut R) -> String
+where
+    R: Rng + ?Sized,
+{
+    format!(
+        "{}.{}.{}",
+        rng.random_range(0..5),
+        rng.random_range(0..20),
+        rng.random_range(0..10)
+    )
+}
+
+/// Sets the word lists for the rest of the run. Only the first call has any effect.
+pub fn init(words: Words) {
+    let _ = WORDS.set(words);
+}
+
+/// The word lists set with [`init`], or the built-in ones.
+pub fn get() -> &'static Words {
+    WORDS.get_or_init(Words::built_in)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn t_load_user_lists() {
+        let dir = std::env::temp_dir().join(format!("wtc-words-{}", std::process::id()));
+        let words = Words::load(Some(&dir), Spice::Censored).unwrap();
+        assert_eq!(words.list(List::Language).len(), LANGUAGES.len());
+        assert_eq!(words.list(List::Adjective)[0], ADJECTIVES[0]);
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("languages.txt"),
+            "\u{feff}Brainfuck\n\n  Rust  \nVisual Basic\r\n",
+        )
+        .unwrap();
+        fs::write(dir.join("verbs.txt"), "grep\nsed\n").unwrap();
+        fs::write(dir.join("nouns.txt"), "\n  \n").unwrap();
+        let words = Words::load(Some(&dir), Spice::Censored).unwrap();
+        // Languages are added to, other lists replaced, and empty files ignored
+        assert_eq!(
+            &words.list(List::Language)[LANGUAGES.len()..],
+            ["Brainfuck", "Visual Basic"]
+        );
+        assert_eq!(words.list(List::Verb), ["grep", "sed"]);
+        assert_eq!(words.list(List::Noun).len(), NOUNS.len());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn t_fortune_quotes() {
+        let long = "word ".repeat(MAX_QUOTE);
+        let text = format!(
+            "Ship it.\n%\n%\nMake it work,\n  make it right.\n%\n{}\n",
+            long
+        );
+        assert_eq!(
+            parse_quotes(&text),
+            ["Ship it.", "Make it work, make it right."]
+        );
+        assert!(parse_quotes(include_str!("quotes.txt")).len() > 20);
+        let mut rng = StdRng::seed_from_u64(1);
+        assert!(!Words::built_in().quote(&mut rng).is_empty());
+    }
+
+    #[test]
+    fn t_real_paths_replace_made_up_ones() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut words = Words::load(None, Spice::Censored).unwrap();
+        assert!(words.path(&mut rng).contains('/'));
+        words.paths = vec!["Cargo.toml".to_string()];
+        assert_eq!(words.path(&mut rng), "Cargo.toml");
+    }
+
+    #[test]
+    fn t_swear_follows_spice() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let words = |spice| Words::load(None, spice).unwrap();
+        let censored = words(Spice::Censored);
+        for _ in 0..50 {
+            let swear = censored.swear(&mut rng);
+            assert!(swear.ends_with('!') && (4..=6).contains(&swear.len()));
+            assert!(swear[..swear.len() - 1]
+                .chars()
+                .all(|c| GRAWLIX.contains(&c)));
+        }
+        let mild = words(Spice::Mild);
+        assert!((0..50).all(|_| MILD_SWEARS.contains(&mild.swear(&mut rng).as_str())));
+        let strong = words(Spice::Strong);
+        let swears: Vec<String> = (0..200).map(|_| strong.swear(&mut rng)).collect();
+        assert!(swears.iter().any(|s| STRONG_SWEARS.contains(&s.as_str())));
+        assert!(swears.iter().any(|s| MILD_SWEARS.contains(&s.as_str())));
+    }
+}
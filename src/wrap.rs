@@ -0,0 +1,124 @@
+use crate::text;
+
+/// Most columns a subject line takes up, as the git documentation recommends.
+pub const SUBJECT_WIDTH: usize = 50;
+
+/// Fewest columns a subject cut at the end of a clause keeps, so a comma early on doesn't
+/// leave a subject of two words.
+const MIN_CLAUSE: usize = 20;
+
+/// Splits a subject line too long for `SUBJECT_WIDTH` at the last clause that fits, or
+/// else the last word, returning the subject and what is left over for the body.
+fn split_subject(line: &str) -> (&str, Option<&str>) {
+    if text::width(line) <= SUBJECT_WIDTH {
+        return (line, None);
+    }
+    let mut word = None;
+    let mut clause = None;
+    for (i, _) in line.match_indices(' ') {
+        let head = line[..i].trim_end();
+        let used = text::width(head);
+        if used > SUBJECT_WIDTH {
+            break;
+        }
+        if head.is_empty() {
+            continue;
+        }
+        word = Some(i);
+        if used >= MIN_CLAUSE && head.ends_with(['.', ',', ';', ':', '-']) {
+            clause = Some(i);
+        }
+    }
+    match clause.or(word) {
+        Some(i) => {
+            let subject = line[..i].trim_end();
+            // A clause ends the subject without its comma, but an ellipsis stays
+            let subject = match subject.ends_with("...") {
+                true => subject,
+                false => subject.trim_end_matches([',', ';', ':', '-', '.', ' ']),
+            };
+            (subject, Some(line[i..].trim_start()))
+        }
+        None => {
+            let subject = text::truncate(line, SUBJECT_WIDTH);
+            (subject, Some(&line[subject.len()..]))
+        }
+    }
+}
+
+/// Lays `message` out as a textbook commit message: a subject of at most `SUBJECT_WIDTH`
+/// columns, a blank line, and a body wrapped at `width` columns. Whatever doesn't fit on
+/// the subject line starts the body; lines of the body are wrapped one by one, keeping
+/// their indentation, so lists and haikus keep their shape.
+pub fn apply(message: &str, width: usize) -> String {
+    let (first, rest) = message.split_once('\n').unwrap_or((message, ""));
+    let (subject, overflow) = split_subject(first.trim());
+    let mut body = match overflow {
+        Some(overflow) => text::wrap(overflow, width),
+        None => Vec::new(),
+    };
+    let rest = rest.trim_matches('\n').trim_end();
+    if !rest.is_empty() && !body.is_empty() {
+        body.push(String::new());
+    }
+    for line in rest.lines() {
+        let line = line.trim_end();
+        if text::width(line) <= width {
+            body.push(line.to_string());
+            continue;
+        }
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let lines = text::wrap(line, width.saturating_sub(text::width(indent)));
+        body.extend(lines.into_iter().map(|l| format!("{}{}", indent, l)));
+    }
+    match body.is_empty() {
+        true => subject.to_string(),
+        false => format!("{}\n\n{}", subject, body.join("\n")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t_wrap_textbook() {
+        assert_eq!(apply("  Fixed the build ", 72), "Fixed the build");
+        assert_eq!(
+            apply(
+                "Refactored the payment module, because the old one was haunted",
+                20
+            ),
+            "Refactored the payment module\n\nbecause the old one\nwas haunted"
+        );
+        assert_eq!(
+            apply(
+                "I have no idea what this does but it compiles now so ship it",
+                72
+            ),
+            "I have no idea what this does but it compiles now\n\nso ship it"
+        );
+        assert_eq!(
+            apply(&"x".repeat(55), 72),
+            format!("{}\n\n{}", "x".repeat(50), "x".repeat(5))
+        );
+        assert_eq!(
+            apply("Wait for it... this one takes a while to explain", 72),
+            "Wait for it... this one takes a while to explain"
+        );
+
+        // The body keeps its lines, wrapping only those too wide
+        let message = "Fix it\n\n\n  - one two three four\nshort\n";
+        assert_eq!(
+            apply(message, 12),
+            "Fix it\n\n  - one two\n  three four\nshort"
+        );
+        assert_eq!(
+            apply(
+                "Fix it, then break it all again, and fix it once more\nfor real",
+                72
+            ),
+            "Fix it, then break it all again\n\nand fix it once more\n\nfor real"
+        );
+    }
+}